@@ -0,0 +1,459 @@
+//! Aggregate (net direction) copying
+//!
+//! Receivers with `aggregate_mode` don't copy the master's individual
+//! trades. Instead the queue worker periodically nets the master's open
+//! positions per symbol (buys minus sells), scales the net with the
+//! receiver's risk mode and sends the sync commands that make the receiver
+//! hold exactly that, so a master scalping in and out only moves the
+//! receiver when its net exposure changes. A receiver is left alone while
+//! its EA still has sync commands to pick up, so the next plan is made from
+//! the positions those commands produced.
+//!
+//! The receiver holds one net position per symbol, opened under a synthetic
+//! master position id (see [`aggregate_position_id`]); those are never
+//! matched against real master positions by reconciliation.
+
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::event_processor::{self, get_cached_account_info, map_symbol};
+use super::position_sync::{self, MasterPosition, ReceiverPosition, SyncCommand};
+use super::symbol_catalog::{self, SymbolCatalog, SymbolSpec};
+use super::{
+    command_backlog, kill_switch, lot_calculator, safety, CopierConfig, CopierState, CopyMode, Execution,
+    ReceiverConfig,
+};
+
+/// Lot differences below this are noise
+const LOT_EPSILON: f64 = 1e-6;
+
+/// Synthetic master position id of the receiver's net position in
+/// `master_symbol`. Always negative, so it never collides with an MT5 ticket;
+/// FNV-1a keeps it stable across restarts.
+pub fn aggregate_position_id(master_symbol: &str) -> i64 {
+    let hash = master_symbol
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+    -((hash >> 1) as i64) - 1
+}
+
+/// Whether a receiver position is an aggregate net position
+pub fn is_aggregate_position(master_position_id: i64) -> bool {
+    master_position_id < 0
+}
+
+fn signed_volume(direction: &str, volume: f64) -> f64 {
+    if direction == "sell" {
+        -volume
+    } else {
+        volume
+    }
+}
+
+/// Master net exposure in one symbol
+struct NetExposure {
+    symbol: String,
+    /// Positive = long
+    lots: f64,
+    /// Volume-weighted open price, for risk modes that size on price
+    price: f64,
+}
+
+/// Net master lots per symbol, minus symbols whose mapping is disabled
+fn master_net(master_positions: &[MasterPosition], receiver: &ReceiverConfig) -> BTreeMap<String, NetExposure> {
+    let mut net: BTreeMap<String, NetExposure> = BTreeMap::new();
+    let mut weighted: HashMap<String, (f64, f64)> = HashMap::new();
+    for pos in master_positions {
+        if receiver.symbol_mappings.iter().any(|m| m.master_symbol == pos.symbol && !m.is_enabled) {
+            continue;
+        }
+        let entry = net.entry(pos.symbol.clone()).or_insert_with(|| NetExposure {
+            symbol: pos.symbol.clone(),
+            lots: 0.0,
+            price: 0.0,
+        });
+        entry.lots += signed_volume(&pos.direction, pos.volume);
+        let (volume, notional) = weighted.entry(pos.symbol.clone()).or_default();
+        *volume += pos.volume;
+        *notional += pos.volume * pos.open_price;
+    }
+    for (symbol, exposure) in net.iter_mut() {
+        let (volume, notional) = weighted[symbol];
+        exposure.price = if volume > 0.0 { notional / volume } else { 0.0 };
+    }
+    net
+}
+
+/// Receiver lots (signed) for a master net, scaled with its risk mode and
+/// clamped to the receiver symbol's min, max and lot step when its spec is
+/// known
+fn target_lots(
+    net: &NetExposure,
+    receiver: &ReceiverConfig,
+    account: Option<&lot_calculator::AccountInfo>,
+    spec: Option<&SymbolSpec>,
+) -> f64 {
+    if net.lots.abs() < LOT_EPSILON {
+        return 0.0;
+    }
+    let lots = lot_calculator::calculate_lots(
+        &receiver.risk_mode,
+        receiver.risk_value,
+        net.lots.abs(),
+        net.price,
+        None,
+        None,
+        account,
+        None,
+    );
+    let lots = match spec {
+        Some(spec) => symbol_catalog::clamp_lots_with(lots, spec, receiver.lot_rounding).lots,
+        None => lots,
+    };
+    lots.copysign(net.lots)
+}
+
+/// Remove float drift at the precision of the symbol's lot step
+fn round_lots(lots: f64, spec: Option<&SymbolSpec>) -> f64 {
+    let step = spec.map_or(lot_calculator::DEFAULT_LOT_STEP, |s| s.lot_step);
+    lot_calculator::round_to_step_precision(lots, step)
+}
+
+/// Commands that move the receiver's net positions to the master's scaled
+/// net: opens to add, closes and partial closes (largest position first) to
+/// reduce, a close then an open to flip. Opens go out on the receiver's
+/// mapped symbol and respect `enabled` and `copy_mode` exits-only;
+/// reductions respect entries-only. `catalog` (the receiver's) supplies the
+/// lot step, min and max.
+pub fn plan_aggregate(
+    master_positions: &[MasterPosition],
+    receiver_positions: &[ReceiverPosition],
+    receiver: &ReceiverConfig,
+    receiver_account: Option<&lot_calculator::AccountInfo>,
+    catalog: Option<&SymbolCatalog>,
+) -> Vec<SyncCommand> {
+    let opens_allowed = receiver.enabled && receiver.copy_mode != CopyMode::ExitsOnly;
+    let reductions_allowed = receiver.copy_mode != CopyMode::EntriesOnly;
+
+    let net = master_net(master_positions, receiver);
+    let spec_of = |receiver_symbol: &str| catalog.and_then(|c| c.symbols.iter().find(|s| s.name == receiver_symbol));
+    let targets: BTreeMap<i64, (&NetExposure, String, f64)> = net
+        .values()
+        .map(|n| {
+            let mapped = map_symbol(receiver, &n.symbol);
+            let lots = target_lots(n, receiver, receiver_account, spec_of(&mapped));
+            (aggregate_position_id(&n.symbol), (n, mapped, lots))
+        })
+        .collect();
+
+    let mut ids: Vec<i64> = targets.keys().copied().collect();
+    for pos in receiver_positions.iter().filter(|p| is_aggregate_position(p.master_position_id)) {
+        if !ids.contains(&pos.master_position_id) {
+            ids.push(pos.master_position_id);
+        }
+    }
+
+    let mut commands = Vec::new();
+    for id in ids {
+        let mut held: Vec<&ReceiverPosition> = receiver_positions.iter().filter(|p| p.master_position_id == id).collect();
+        held.sort_by(|a, b| b.volume.total_cmp(&a.volume));
+        let current: f64 = held.iter().map(|p| signed_volume(&p.direction, p.volume)).sum();
+        let target = targets.get(&id).map(|(_, _, lots)| *lots).unwrap_or(0.0);
+        let spec = targets
+            .get(&id)
+            .map(|(_, mapped, _)| mapped.as_str())
+            .or_else(|| held.first().map(|p| p.symbol.as_str()))
+            .and_then(spec_of);
+        if (current - target).abs() < LOT_EPSILON {
+            continue;
+        }
+
+        let flips = current.abs() > LOT_EPSILON && (target.abs() < LOT_EPSILON || current.signum() != target.signum());
+        let (reduce, add) = if flips {
+            (current.abs(), target.abs())
+        } else if target.abs() < current.abs() {
+            (current.abs() - target.abs(), 0.0)
+        } else {
+            (0.0, target.abs() - current.abs())
+        };
+
+        if reduce > LOT_EPSILON {
+            if !reductions_allowed {
+                continue;
+            }
+            let mut remaining = round_lots(reduce, spec);
+            for pos in &held {
+                if remaining < LOT_EPSILON {
+                    break;
+                }
+                if remaining >= pos.volume - LOT_EPSILON {
+                    commands.push(SyncCommand::close_position(pos.position_id));
+                    remaining = round_lots(remaining - pos.volume, spec);
+                } else {
+                    commands.push(SyncCommand::partial_close(pos.position_id, id, remaining));
+                    remaining = 0.0;
+                }
+            }
+        }
+
+        if add > LOT_EPSILON && opens_allowed {
+            let Some((exposure, mapped, _)) = targets.get(&id) else {
+                continue;
+            };
+            let net_position = MasterPosition {
+                position_id: id,
+                symbol: mapped.clone(),
+                direction: if target > 0.0 { "buy" } else { "sell" }.to_string(),
+                volume: round_lots(add, spec),
+                open_price: exposure.price,
+                sl: 0.0,
+                tp: 0.0,
+                sl_distance_points: None,
+                tp_distance_points: None,
+            };
+            commands.push(SyncCommand {
+                sl: None,
+                tp: None,
+                ..SyncCommand::open_position(&net_position)
+            });
+        }
+    }
+    commands
+}
+
+/// Bring every aggregate-mode receiver to its master's net. Receivers whose
+/// EA hasn't picked up the previous commands yet are skipped. Called from
+/// the queue worker while running.
+pub fn run_pending(config: &CopierConfig, state: &Arc<Mutex<CopierState>>) {
+    if kill_switch::is_engaged() {
+        return;
+    }
+
+    let groups = config
+        .all_masters()
+        .into_iter()
+        .filter_map(|master| config.for_master(&master.account_id));
+    for group in groups {
+        for receiver in group.receivers.iter().filter(|r| r.aggregate_mode) {
+            if command_backlog::has_pending_commands(&receiver.terminal_id) {
+                continue;
+            }
+            aggregate_receiver(&group, receiver, state);
+        }
+    }
+}
+
+fn aggregate_receiver(config: &CopierConfig, receiver: &ReceiverConfig, state: &Arc<Mutex<CopierState>>) {
+    let positions = position_sync::read_master_positions(&config.master.terminal_id).and_then(|master| {
+        position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number).map(|recv| (master, recv))
+    });
+    let (master_positions, receiver_positions) = match positions {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("Aggregate sync skipped for {}: {}", receiver.account_number, e);
+            return;
+        }
+    };
+
+    let account = get_cached_account_info(&receiver.terminal_id);
+    let catalog = symbol_catalog::fetch_symbol_catalog(&receiver.terminal_id).ok();
+    let commands = plan_aggregate(&master_positions, &receiver_positions, receiver, account.as_ref(), catalog.as_ref());
+    if commands.is_empty() {
+        return;
+    }
+    info!("Aggregate sync of {}: {} command(s)", receiver.account_number, commands.len());
+
+    let safety_config = event_processor::receiver_safety_config(receiver);
+
+    for command in commands {
+        let mut execution = aggregate_execution(&command, receiver);
+
+        if command.command_type == "open" {
+            let balance = account.as_ref().map(|a| a.balance).unwrap_or_default();
+            if let safety::SafetyCheckResult::Blocked(reason) =
+                safety::check_trade_safety(&receiver.account_number, &safety_config, balance)
+            {
+                warn!("Aggregate open blocked for {}: {}", receiver.account_number, reason);
+                execution.status = "blocked".to_string();
+                execution.error_message = Some(reason);
+                event_processor::store_execution(execution, state);
+                continue;
+            }
+        }
+
+        match position_sync::write_sync_command(&receiver.terminal_id, &command) {
+            Ok(()) => execution.status = "success".to_string(),
+            Err(e) => {
+                warn!("Aggregate {} failed for {}: {}", command.command_type, receiver.account_number, e);
+                execution.status = "error".to_string();
+                execution.error_message = Some(e.to_string());
+            }
+        }
+        event_processor::store_execution(execution, state);
+    }
+}
+
+/// Audit record for an aggregate command
+fn aggregate_execution(command: &SyncCommand, receiver: &ReceiverConfig) -> Execution {
+    let event_type = match command.command_type.as_str() {
+        "open" => "entry",
+        "close" => "exit",
+        other => other,
+    };
+    Execution {
+        id: Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_type: event_type.to_string(),
+        symbol: command.symbol.clone().unwrap_or_default(),
+        direction: command.direction.clone().unwrap_or_default(),
+        master_lots: 0.0,
+        receiver_lots: command.volume.unwrap_or(0.0),
+        master_price: 0.0,
+        executed_price: None,
+        slippage_pips: None,
+        status: "pending".to_string(),
+        error_message: None,
+        receiver_account: receiver.account_number.clone(),
+        master_position_id: command.master_position_id,
+        receiver_position_id: command.position_id,
+        idempotency_key: None,
+        master_account_number: None,
+        intended_lots: None,
+        requested_lots: None,
+        lot_adjustment: None,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+
+    fn master_position(position_id: i64, direction: &str, volume: f64) -> MasterPosition {
+        MasterPosition {
+            position_id,
+            symbol: "EURUSD".to_string(),
+            direction: direction.to_string(),
+            volume,
+            open_price: 1.1,
+            sl: 1.09,
+            tp: 0.0,
+            sl_distance_points: None,
+            tp_distance_points: None,
+        }
+    }
+
+    fn net_position(position_id: i64, direction: &str, volume: f64) -> ReceiverPosition {
+        ReceiverPosition {
+            position_id,
+            master_position_id: aggregate_position_id("EURUSD"),
+            symbol: "EURUSD".to_string(),
+            direction: direction.to_string(),
+            volume,
+            sl: None,
+            tp: None,
+            magic: None,
+        }
+    }
+
+    fn aggregate_receiver() -> ReceiverConfig {
+        ReceiverConfig {
+            risk_mode: "lot_multiplier".to_string(),
+            risk_value: 0.5,
+            aggregate_mode: true,
+            ..test_receiver()
+        }
+    }
+
+    #[test]
+    fn test_three_master_trades_net_to_one_receiver_position() {
+        // Three rapid trades on the master: long 1.0, long 0.5, short 0.3
+        let master = vec![
+            master_position(100, "buy", 1.0),
+            master_position(101, "buy", 0.5),
+            master_position(102, "sell", 0.3),
+        ];
+        let receiver = aggregate_receiver();
+
+        let commands = plan_aggregate(&master, &[], &receiver, None, None);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command_type, "open");
+        assert_eq!(commands[0].direction.as_deref(), Some("buy"));
+        assert_eq!(commands[0].volume, Some(0.6));
+        assert_eq!(commands[0].master_position_id, Some(aggregate_position_id("EURUSD")));
+        assert_eq!(commands[0].sl, None);
+
+        // Once the receiver holds the net there's nothing to do
+        let held = vec![net_position(900, "buy", 0.6)];
+        assert!(plan_aggregate(&master, &held, &receiver, None, None).is_empty());
+    }
+
+    #[test]
+    fn test_net_changes_reduce_flip_and_close() {
+        let receiver = aggregate_receiver();
+        let held = vec![net_position(900, "buy", 0.6)];
+
+        // Master trims to net long 0.4: partial close of 0.4 lots
+        let master = vec![master_position(100, "buy", 0.4)];
+        let commands = plan_aggregate(&master, &held, &receiver, None, None);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command_type, "partial_close");
+        assert_eq!(commands[0].volume, Some(0.4));
+
+        // Master flips to net short 0.2: close, then open short 0.1
+        let master = vec![master_position(101, "sell", 0.2)];
+        let commands = plan_aggregate(&master, &held, &receiver, None, None);
+        let kinds: Vec<_> = commands.iter().map(|c| (c.command_type.as_str(), c.direction.as_deref(), c.volume)).collect();
+        assert_eq!(kinds, vec![("close", None, None), ("open", Some("sell"), Some(0.1))]);
+
+        // Master flat (a scalp opened and closed): the net position is closed
+        let commands = plan_aggregate(&[], &held, &receiver, None, None);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command_type, "close");
+        assert_eq!(commands[0].position_id, Some(900));
+    }
+
+    #[test]
+    fn test_net_open_uses_mapped_symbol_and_its_lot_step() {
+        let receiver = ReceiverConfig {
+            symbol_mappings: vec![crate::copier::SymbolMapping {
+                master_symbol: "EURUSD".to_string(),
+                receiver_symbol: "EURUSD.pro".to_string(),
+                is_enabled: true,
+            }],
+            ..aggregate_receiver()
+        };
+        let catalog = SymbolCatalog {
+            terminal_id: "R1".to_string(),
+            symbols: vec![SymbolSpec {
+                name: "EURUSD.pro".to_string(),
+                normalized_key: "EURUSD".to_string(),
+                tick_value: 1.0,
+                tick_size: 0.00001,
+                contract_size: 100_000.0,
+                digits: 5,
+                min_lot: 0.1,
+                lot_step: 0.1,
+                max_lot: 50.0,
+                description: None,
+                trade_mode: Some("full".to_string()),
+                profit_currency: None,
+            }],
+            fetched_at: String::new(),
+            broker_suffix: Some(".pro".to_string()),
+        };
+
+        // Net long 1.25 at 0.5x is 0.625 lots: 0.6 on a 0.1 lot step
+        let master = vec![master_position(100, "buy", 1.25)];
+        let commands = plan_aggregate(&master, &[], &receiver, None, Some(&catalog));
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].symbol.as_deref(), Some("EURUSD.pro"));
+        assert_eq!(commands[0].volume, Some(0.6));
+    }
+}
@@ -0,0 +1,233 @@
+//! Copier alerts
+//!
+//! Structured, user-facing alerts (safety pauses, failed executions, slippage
+//! rejections, masters going offline) kept in a capped in-memory buffer,
+//! newest first. Unacknowledged alerts are persisted so they survive a
+//! restart; acknowledged ones are dropped from disk on the next save.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use crate::data_dir::app_data_dir;
+use super::{live_balance, CopierConfig};
+
+const ALERTS_FILE: &str = "alerts.json";
+
+/// Maximum alerts kept; the oldest are dropped first
+pub const MAX_ALERTS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub timestamp: String,
+    pub acknowledged: bool,
+}
+
+/// Capped alert buffer, newest first
+pub struct AlertLog {
+    alerts: VecDeque<Alert>,
+    capacity: usize,
+    persist_path: Option<PathBuf>,
+}
+
+impl AlertLog {
+    pub fn new(capacity: usize, persist_path: Option<PathBuf>) -> Self {
+        Self {
+            alerts: VecDeque::new(),
+            capacity,
+            persist_path,
+        }
+    }
+
+    /// Load the unacknowledged alerts saved by a previous run
+    pub fn load_from_disk(capacity: usize, path: PathBuf) -> Self {
+        let mut log = Self::new(capacity, Some(path.clone()));
+        match fs::read_to_string(&path).map(|c| serde_json::from_str::<Vec<Alert>>(&c)) {
+            Ok(Ok(alerts)) => log.alerts = alerts.into_iter().take(capacity).collect(),
+            Ok(Err(e)) => warn!("Ignoring unreadable alerts file: {}", e),
+            Err(_) => {}
+        }
+        log
+    }
+
+    pub fn push(&mut self, severity: AlertSeverity, message: String) -> Alert {
+        let alert = Alert {
+            id: uuid::Uuid::new_v4().to_string(),
+            severity,
+            message,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            acknowledged: false,
+        };
+        self.alerts.push_front(alert.clone());
+        self.alerts.truncate(self.capacity);
+        self.save();
+        alert
+    }
+
+    /// Mark an alert acknowledged. Returns false if there is no such alert.
+    pub fn acknowledge(&mut self, id: &str) -> bool {
+        let Some(alert) = self.alerts.iter_mut().find(|a| a.id == id) else {
+            return false;
+        };
+        alert.acknowledged = true;
+        self.save();
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.alerts.clear();
+        self.save();
+    }
+
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.alerts.iter().cloned().collect()
+    }
+
+    /// Write the unacknowledged alerts (atomic write)
+    fn save(&self) {
+        let Some(path) = &self.persist_path else {
+            return;
+        };
+        let pending: Vec<&Alert> = self.alerts.iter().filter(|a| !a.acknowledged).collect();
+        let result = serde_json::to_string_pretty(&pending)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let temp_path = path.with_extension("tmp");
+                fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+                fs::rename(&temp_path, path).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("Failed to persist alerts: {}", e);
+        }
+    }
+}
+
+fn get_alerts_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(ALERTS_FILE))
+}
+
+static ALERTS: LazyLock<Mutex<AlertLog>> = LazyLock::new(|| {
+    Mutex::new(match get_alerts_path() {
+        Some(path) => AlertLog::load_from_disk(MAX_ALERTS, path),
+        None => AlertLog::new(MAX_ALERTS, None),
+    })
+});
+
+/// Masters (terminal ids) last seen online, for offline transitions
+static MASTER_ONLINE: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Record an alert
+pub fn push_alert(severity: AlertSeverity, message: impl Into<String>) {
+    ALERTS.lock().push(severity, message.into());
+}
+
+pub fn get_alerts() -> Vec<Alert> {
+    ALERTS.lock().alerts()
+}
+
+pub fn acknowledge_alert(id: &str) -> bool {
+    ALERTS.lock().acknowledge(id)
+}
+
+pub fn clear_alerts() {
+    ALERTS.lock().clear();
+}
+
+/// Error text that means the receiver refused the fill over price movement
+pub fn is_slippage_rejection(error: &str) -> bool {
+    let error = error.to_lowercase();
+    ["slippage", "requote", "price changed", "10004", "10020"]
+        .iter()
+        .any(|p| error.contains(p))
+}
+
+/// Alert once when a master stops heartbeating (and again only after it
+/// came back). Called from the queue worker while copying.
+pub fn watch_masters(config: &CopierConfig) {
+    let mut last_seen = MASTER_ONLINE.lock();
+    for master in config.all_masters() {
+        let online = live_balance::live_heartbeat(&master.terminal_id).is_some();
+        let was_online = last_seen.insert(master.terminal_id.clone(), online);
+        if !online && was_online != Some(false) {
+            push_alert(
+                AlertSeverity::Critical,
+                format!("Master {} is offline (no recent heartbeat)", master.account_number),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_acknowledge() {
+        let mut log = AlertLog::new(10, None);
+        let first = log.push(AlertSeverity::Warning, "first".to_string());
+        log.push(AlertSeverity::Critical, "second".to_string());
+
+        let alerts = log.alerts();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "second");
+        assert!(alerts.iter().all(|a| !a.acknowledged));
+
+        assert!(log.acknowledge(&first.id));
+        assert!(!log.acknowledge("missing"));
+        assert!(log.alerts()[1].acknowledged);
+
+        log.clear();
+        assert!(log.alerts().is_empty());
+    }
+
+    #[test]
+    fn test_buffer_is_capped() {
+        let mut log = AlertLog::new(3, None);
+        for i in 0..5 {
+            log.push(AlertSeverity::Info, format!("alert {}", i));
+        }
+        let messages: Vec<String> = log.alerts().into_iter().map(|a| a.message).collect();
+        assert_eq!(messages, vec!["alert 4", "alert 3", "alert 2"]);
+    }
+
+    #[test]
+    fn test_unacknowledged_alerts_survive_restart() {
+        let path = std::env::temp_dir().join(format!("saturn_alerts_test_{}.json", uuid::Uuid::new_v4()));
+        let mut log = AlertLog::new(10, Some(path.clone()));
+        let seen = log.push(AlertSeverity::Warning, "seen".to_string());
+        log.push(AlertSeverity::Critical, "unseen".to_string());
+        log.acknowledge(&seen.id);
+
+        let restored = AlertLog::load_from_disk(10, path.clone());
+        let alerts = restored.alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].message, "unseen");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_slippage_rejection_detection() {
+        assert!(is_slippage_rejection("Error 10004: Requote"));
+        assert!(is_slippage_rejection("Slippage 4.2 pips exceeds max 3"));
+        assert!(!is_slippage_rejection("Not enough money"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Manual trade approval
+//!
+//! Receivers with `manual_confirm_mode` don't execute new opens straight
+//! away: the event processor parks them here and raises an alert, and the
+//! user approves or rejects each one. Approved trades continue down the
+//! normal execution path; rejected and expired ones are recorded with status
+//! `rejected_manual`, as are approvals whose master position closed while
+//! they waited. Closes and modifies never wait for approval.
+//!
+//! Pending approvals are kept in memory only. After a restart the master's
+//! price has moved on, so an old approval shouldn't be executable anyway.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, LazyLock};
+use tracing::info;
+
+use super::alerts::{self, AlertSeverity};
+use super::event_processor;
+use super::position_sync::{self, MasterPosition};
+use super::{CopierState, ReceiverConfig, TradeEvent};
+
+/// Seconds a trade waits for approval when the receiver doesn't set
+/// `manual_confirm_timeout_secs`
+pub const DEFAULT_APPROVAL_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub id: String,
+    /// Receiver `account_id`
+    pub receiver_id: String,
+    pub receiver_account: String,
+    pub event: TradeEvent,
+    pub created_at: DateTime<Utc>,
+    /// Auto-rejected after this
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Trades waiting for the user, oldest first
+#[derive(Debug, Default)]
+pub struct ApprovalQueue {
+    pending: Vec<PendingApproval>,
+}
+
+impl ApprovalQueue {
+    pub fn submit(&mut self, event: &TradeEvent, receiver: &ReceiverConfig, now: DateTime<Utc>) -> PendingApproval {
+        let timeout = receiver.manual_confirm_timeout_secs.unwrap_or(DEFAULT_APPROVAL_TIMEOUT_SECS);
+        let approval = PendingApproval {
+            id: uuid::Uuid::new_v4().to_string(),
+            receiver_id: receiver.account_id.clone(),
+            receiver_account: receiver.account_number.clone(),
+            event: event.clone(),
+            created_at: now,
+            expires_at: now + Duration::seconds(timeout as i64),
+        };
+        self.pending.push(approval.clone());
+        approval
+    }
+
+    /// Remove and return a pending approval
+    pub fn take(&mut self, id: &str) -> Option<PendingApproval> {
+        let index = self.pending.iter().position(|a| a.id == id)?;
+        Some(self.pending.remove(index))
+    }
+
+    /// Remove and return every approval past its deadline
+    pub fn take_expired(&mut self, now: DateTime<Utc>) -> Vec<PendingApproval> {
+        let (expired, waiting) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|a| a.expires_at <= now);
+        self.pending = waiting;
+        expired
+    }
+
+    pub fn pending(&self) -> Vec<PendingApproval> {
+        self.pending.clone()
+    }
+}
+
+static APPROVALS: LazyLock<Mutex<ApprovalQueue>> = LazyLock::new(|| Mutex::new(ApprovalQueue::default()));
+
+/// Whether `event` has to wait for the user on `receiver`
+pub fn needs_approval(event: &TradeEvent, receiver: &ReceiverConfig) -> bool {
+    receiver.manual_confirm_mode && event_processor::is_opening_event(event)
+}
+
+/// Park an event for approval and alert the user
+pub fn request_approval(event: &TradeEvent, receiver: &ReceiverConfig) -> PendingApproval {
+    let approval = APPROVALS.lock().submit(event, receiver, Utc::now());
+    info!("Trade {} on {} awaiting manual approval", approval.id, receiver.account_number);
+    alerts::push_alert(
+        AlertSeverity::Warning,
+        format!(
+            "Approval needed on {}: {} {} {} lots (expires {})",
+            receiver.account_number,
+            event.direction,
+            event.symbol,
+            event.lots,
+            approval.expires_at.format("%H:%M:%S UTC")
+        ),
+    );
+    approval
+}
+
+pub fn list_pending() -> Vec<PendingApproval> {
+    APPROVALS.lock().pending()
+}
+
+/// Receiver an approval was made for, from the current config
+fn receiver_for(approval: &PendingApproval, state: &Arc<Mutex<CopierState>>) -> Result<ReceiverConfig, String> {
+    state
+        .lock()
+        .config
+        .as_ref()
+        .and_then(|c| c.receivers.iter().find(|r| r.account_id == approval.receiver_id).cloned())
+        .ok_or_else(|| format!("Receiver {} is no longer configured", approval.receiver_id))
+}
+
+/// Why an approved entry must not run: the master closed its position
+/// while it waited. None when the position is still open or the master's
+/// positions couldn't be read.
+fn closed_while_waiting(event: &TradeEvent, master_positions: Option<&[MasterPosition]>) -> Option<String> {
+    if event.event_type != "entry" {
+        return None;
+    }
+    let still_open = master_positions?.iter().any(|p| p.position_id == event.ticket);
+    (!still_open).then(|| format!("Master position {} closed before the trade was approved", event.ticket))
+}
+
+/// Approve a pending trade and send it down the normal execution path,
+/// unless its master position has closed in the meantime
+pub fn approve(id: &str, state: &Arc<Mutex<CopierState>>) -> Result<(), String> {
+    let approval = APPROVALS.lock().take(id).ok_or_else(|| format!("No pending approval {}", id))?;
+    let receiver = receiver_for(&approval, state)?;
+    let master_positions = approval
+        .event
+        .terminal_id
+        .as_deref()
+        .and_then(|terminal_id| position_sync::read_master_positions(terminal_id).ok());
+    if let Some(reason) = closed_while_waiting(&approval.event, master_positions.as_deref()) {
+        event_processor::record_manual_rejection(&approval.event, &receiver, &reason, state.clone());
+        return Err(reason);
+    }
+    info!("Trade {} approved for {}", id, receiver.account_number);
+    event_processor::execute_approved(&approval.event, &receiver, state.clone());
+    Ok(())
+}
+
+/// Reject a pending trade
+pub fn reject(id: &str, state: &Arc<Mutex<CopierState>>) -> Result<(), String> {
+    let approval = APPROVALS.lock().take(id).ok_or_else(|| format!("No pending approval {}", id))?;
+    let receiver = receiver_for(&approval, state)?;
+    event_processor::record_manual_rejection(&approval.event, &receiver, "Rejected by user", state.clone());
+    Ok(())
+}
+
+/// Auto-reject approvals past their deadline in `queue`
+fn expire_from(queue: &Mutex<ApprovalQueue>, state: &Arc<Mutex<CopierState>>, now: DateTime<Utc>) {
+    let expired = queue.lock().take_expired(now);
+    for approval in expired {
+        let reason = "Approval timed out";
+        match receiver_for(&approval, state) {
+            Ok(receiver) => event_processor::record_manual_rejection(&approval.event, &receiver, reason, state.clone()),
+            Err(e) => info!("Dropping expired approval {}: {}", approval.id, e),
+        }
+    }
+}
+
+/// Auto-reject overdue approvals. Called periodically by the queue worker.
+pub fn expire_overdue(state: &Arc<Mutex<CopierState>>) {
+    expire_from(&APPROVALS, state, Utc::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::{CopierConfig, MasterConfig};
+    use crate::copier::test_support::{test_event, test_receiver};
+
+    fn receiver() -> ReceiverConfig {
+        ReceiverConfig {
+            account_id: "acc-approve".to_string(),
+            account_number: "approve-test-3003".to_string(),
+            manual_confirm_mode: true,
+            manual_confirm_timeout_secs: Some(30),
+            ..test_receiver()
+        }
+    }
+
+    fn state_with(receiver: ReceiverConfig) -> Arc<Mutex<CopierState>> {
+        Arc::new(Mutex::new(CopierState {
+            config: Some(CopierConfig {
+                version: 1,
+                config_hash: String::new(),
+                master: MasterConfig {
+                    account_id: "m".to_string(),
+                    account_number: "1001".to_string(),
+                    broker: "B".to_string(),
+                    terminal_id: "MASTER".to_string(),
+                },
+                receivers: vec![receiver],
+                masters: vec![],
+                execution_strategy: Default::default(),
+            }),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_approve_takes_the_trade_once() {
+        let receiver = receiver();
+        assert!(needs_approval(&test_event("entry", 42), &receiver));
+        let exit = TradeEvent {
+            event_type: "exit".to_string(),
+            ..test_event("entry", 42)
+        };
+        assert!(!needs_approval(&exit, &receiver));
+
+        let mut queue = ApprovalQueue::default();
+        let approval = queue.submit(&test_event("entry", 42), &receiver, Utc::now());
+        assert_eq!(queue.pending().len(), 1);
+
+        let approved = queue.take(&approval.id).unwrap();
+        assert_eq!(approved.event.ticket, 42);
+        assert_eq!(receiver_for(&approved, &state_with(receiver)).unwrap().account_id, "acc-approve");
+        assert!(queue.take(&approval.id).is_none());
+        assert!(queue.pending().is_empty());
+    }
+
+    #[test]
+    fn test_approval_rejected_once_master_position_closed() {
+        let position = |position_id: i64| -> MasterPosition {
+            serde_json::from_value(serde_json::json!({
+                "position_id": position_id,
+                "symbol": "EURUSD",
+                "direction": "buy",
+                "volume": 0.1,
+                "open_price": 1.1,
+                "sl": 0.0,
+                "tp": 0.0
+            }))
+            .unwrap()
+        };
+        let entry = test_event("entry", 42);
+
+        assert!(closed_while_waiting(&entry, Some(&[position(42)])).is_none());
+        assert!(closed_while_waiting(&entry, Some(&[position(7)])).unwrap().contains("42 closed"));
+        // Unreadable master positions don't block the approval
+        assert!(closed_while_waiting(&entry, None).is_none());
+    }
+
+    #[test]
+    fn test_expired_approval_is_rejected() {
+        let receiver = receiver();
+        let state = state_with(receiver.clone());
+        let now = Utc::now();
+        let queue = Mutex::new(ApprovalQueue::default());
+        let approval = queue.lock().submit(&test_event("entry", 42), &receiver, now);
+        assert_eq!(approval.expires_at, now + Duration::seconds(30));
+
+        // Still within the timeout
+        expire_from(&queue, &state, now + Duration::seconds(29));
+        assert_eq!(queue.lock().pending().len(), 1);
+        assert!(state.lock().recent_executions.is_empty());
+
+        expire_from(&queue, &state, now + Duration::seconds(30));
+        assert!(queue.lock().pending().is_empty());
+        let executions = state.lock().recent_executions.clone();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].status, "rejected_manual");
+        assert_eq!(executions[0].error_message.as_deref(), Some("Approval timed out"));
+    }
+}
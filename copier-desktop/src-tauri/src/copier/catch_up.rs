@@ -0,0 +1,297 @@
+//! Catch-up on attach
+//!
+//! A receiver that comes online after the master already has open positions
+//! never sees the `entry` events for them. For receivers with
+//! `copy_existing_on_start`, the queue worker opens those positions once per
+//! session via `open` sync commands, using the same MissingOnReceiver
+//! detection as position reconciliation. A receiver only counts as caught up
+//! once its commands were written; if the position files can't be read it
+//! is tried again on the next pass.
+
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::event_processor::{self, get_cached_account_info};
+use super::position_sync::{self, DiscrepancyType, MasterPosition, ReceiverPosition, SyncCommand};
+use super::{kill_switch, lot_calculator, safety, CopierConfig, CopierState, CopyMode, Execution, ReceiverConfig};
+
+/// Receivers (terminal ids) already caught up this session
+static CAUGHT_UP: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Mark a receiver as caught up. Returns false if it already was, so the
+/// catch-up never runs twice for the same receiver.
+fn mark_caught_up(caught_up: &mut HashSet<String>, terminal_id: &str) -> bool {
+    caught_up.insert(terminal_id.to_string())
+}
+
+/// Master positions the receiver doesn't hold yet, minus symbols whose
+/// mapping is disabled for this receiver
+fn positions_to_copy(
+    master_positions: &[MasterPosition],
+    receiver_positions: &[ReceiverPosition],
+    receiver: &ReceiverConfig,
+) -> Vec<MasterPosition> {
+    // Only missing positions are used, so SL/TP tolerances don't matter here
+    position_sync::find_discrepancies(
+        master_positions,
+        receiver_positions,
+        &position_sync::DiscrepancyCheck::new(&receiver.terminal_id, receiver.sltp_policy),
+    )
+    .into_iter()
+    .filter(|d| d.discrepancy_type == DiscrepancyType::MissingOnReceiver)
+    .filter_map(|d| d.master_position)
+    .filter(|p| {
+        !receiver
+            .symbol_mappings
+            .iter()
+            .any(|m| m.master_symbol == p.symbol && !m.is_enabled)
+    })
+    .collect()
+}
+
+/// Build `open` commands for the master positions a late receiver is
+/// missing, on the receiver's mapped symbols and sized with its own risk
+/// mode.
+pub fn build_catch_up_commands(
+    master_positions: &[MasterPosition],
+    receiver_positions: &[ReceiverPosition],
+    receiver: &ReceiverConfig,
+    receiver_account: Option<&lot_calculator::AccountInfo>,
+) -> Vec<SyncCommand> {
+    positions_to_copy(master_positions, receiver_positions, receiver)
+        .iter()
+        .map(|pos| {
+            let sl = (pos.sl > 0.0).then_some(pos.sl);
+            let lots = lot_calculator::calculate_lots(
+                &receiver.risk_mode,
+                receiver.risk_value,
+                pos.volume,
+                pos.open_price,
+                sl,
+                None,
+                receiver_account,
+                None,
+            );
+            SyncCommand {
+                symbol: Some(event_processor::map_symbol(receiver, &pos.symbol)),
+                volume: Some(lots),
+                sl: receiver.sltp_policy.apply(Some(pos.sl)),
+                tp: receiver.sltp_policy.apply(Some(pos.tp)),
+                ..SyncCommand::open_position(pos)
+            }
+        })
+        .collect()
+}
+
+/// Run the one-time catch-up for every opted-in receiver that is online and
+/// hasn't been caught up yet. Called from the queue worker while running.
+pub fn run_pending(config: &CopierConfig, state: &Arc<Mutex<CopierState>>) {
+    if kill_switch::is_engaged() {
+        return;
+    }
+
+    // Each receiver catches up on the positions of the master it follows
+    let groups = config
+        .all_masters()
+        .into_iter()
+        .filter_map(|master| config.for_master(&master.account_id));
+
+    for group in groups {
+        // Aggregate-mode receivers take the master's net from `aggregate`
+        let opted_in = |r: &&ReceiverConfig| {
+            r.copy_existing_on_start && r.enabled && r.copy_mode != CopyMode::ExitsOnly && !r.aggregate_mode
+        };
+        for receiver in group.receivers.iter().filter(opted_in) {
+            if CAUGHT_UP.lock().contains(&receiver.terminal_id) {
+                continue;
+            }
+
+            // Not detected yet - try again once its EA has written account info
+            let Some(account) = get_cached_account_info(&receiver.terminal_id) else {
+                continue;
+            };
+
+            match catch_up_receiver(&group, receiver, &account, state) {
+                Ok(()) => {
+                    mark_caught_up(&mut CAUGHT_UP.lock(), &receiver.terminal_id);
+                }
+                Err(e) => warn!("Catch-up for {} will be retried: {}", receiver.account_number, e),
+            }
+        }
+    }
+}
+
+/// Open the receiver's missing master positions. Errs, writing nothing, if
+/// either side's positions can't be read.
+fn catch_up_receiver(
+    config: &CopierConfig,
+    receiver: &ReceiverConfig,
+    account: &lot_calculator::AccountInfo,
+    state: &Arc<Mutex<CopierState>>,
+) -> Result<(), String> {
+    let master_positions = position_sync::read_master_positions(&config.master.terminal_id).map_err(|e| e.to_string())?;
+    let receiver_positions = position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number)
+        .map_err(|e| e.to_string())?;
+
+    let commands = build_catch_up_commands(&master_positions, &receiver_positions, receiver, Some(account));
+    if commands.is_empty() {
+        return Ok(());
+    }
+    info!(
+        "Catching up {} existing master position(s) on {}",
+        commands.len(),
+        receiver.account_number
+    );
+
+    let safety_config = event_processor::receiver_safety_config(receiver);
+
+    for command in commands {
+        let Some(master_position) = master_positions.iter().find(|p| Some(p.position_id) == command.master_position_id)
+        else {
+            continue;
+        };
+        let mut execution = catch_up_execution(&command, master_position, receiver);
+        execution.master_account_number = Some(config.master.account_number.clone());
+
+        if let safety::SafetyCheckResult::Blocked(reason) =
+            safety::check_trade_safety(&receiver.account_number, &safety_config, account.balance)
+        {
+            warn!("Catch-up blocked for {}: {}", receiver.account_number, reason);
+            execution.status = "blocked".to_string();
+            execution.error_message = Some(reason);
+            event_processor::store_execution(execution, state);
+            continue;
+        }
+
+        match position_sync::write_sync_command(&receiver.terminal_id, &command) {
+            Ok(()) => execution.status = "success".to_string(),
+            Err(e) => {
+                warn!("Catch-up open failed for {}: {}", receiver.account_number, e);
+                execution.status = "error".to_string();
+                execution.error_message = Some(e.to_string());
+            }
+        }
+        event_processor::store_execution(execution, state);
+    }
+    Ok(())
+}
+
+/// Audit record for a catch-up open of `master_position`
+fn catch_up_execution(command: &SyncCommand, master_position: &MasterPosition, receiver: &ReceiverConfig) -> Execution {
+    Execution {
+        id: Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_type: "entry".to_string(),
+        symbol: command.symbol.clone().unwrap_or_default(),
+        direction: master_position.direction.clone(),
+        master_lots: master_position.volume,
+        receiver_lots: command.volume.unwrap_or(0.0),
+        master_price: master_position.open_price,
+        executed_price: None,
+        slippage_pips: None,
+        status: "pending".to_string(),
+        error_message: None,
+        receiver_account: receiver.account_number.clone(),
+        master_position_id: command.master_position_id,
+        receiver_position_id: None,
+        idempotency_key: command
+            .master_position_id
+            .map(|id| format!("{}:{}:catch_up", receiver.terminal_id, id)),
+        master_account_number: None,
+        intended_lots: None,
+        requested_lots: None,
+        lot_adjustment: None,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+    use crate::copier::SymbolMapping;
+
+    fn master_position(position_id: i64, symbol: &str) -> MasterPosition {
+        MasterPosition {
+            position_id,
+            symbol: symbol.to_string(),
+            direction: "buy".to_string(),
+            volume: 1.0,
+            open_price: 1.1,
+            sl: 1.09,
+            tp: 0.0,
+            sl_distance_points: None,
+            tp_distance_points: None,
+        }
+    }
+
+    fn late_receiver() -> ReceiverConfig {
+        ReceiverConfig { risk_value: 0.2, copy_existing_on_start: true, ..test_receiver() }
+    }
+
+    #[test]
+    fn test_late_receiver_opens_existing_master_positions() {
+        let master = vec![master_position(100, "EURUSD"), master_position(101, "GBPUSD")];
+        let mut receiver = late_receiver();
+        receiver.symbol_mappings.push(SymbolMapping {
+            master_symbol: "EURUSD".to_string(),
+            receiver_symbol: "EURUSD.r".to_string(),
+            is_enabled: true,
+        });
+
+        let commands = build_catch_up_commands(&master, &[], &receiver, None);
+        assert_eq!(commands.len(), 2);
+        assert!(commands.iter().all(|c| c.command_type == "open"));
+        assert_eq!(commands[0].master_position_id, Some(100));
+        assert_eq!(commands[1].master_position_id, Some(101));
+        assert!((commands[0].volume.unwrap() - 0.2).abs() < 1e-9);
+
+        // Opened on the receiver's symbol names
+        assert_eq!(commands[0].symbol.as_deref(), Some("EURUSD.r"));
+        assert_eq!(commands[1].symbol.as_deref(), Some("GBPUSD"));
+
+        // The audit row carries the master's side of the trade
+        let execution = catch_up_execution(&commands[0], &master[0], &receiver);
+        assert_eq!((execution.master_lots, execution.master_price), (1.0, 1.1));
+        assert_eq!(execution.symbol, "EURUSD.r");
+
+        // One-time: the second pass for the same receiver is refused
+        let mut caught_up = HashSet::new();
+        assert!(mark_caught_up(&mut caught_up, "R1"));
+        assert!(!mark_caught_up(&mut caught_up, "R1"));
+    }
+
+    #[test]
+    fn test_catch_up_skips_held_and_filtered_positions() {
+        let master = vec![
+            master_position(100, "EURUSD"),
+            master_position(101, "GBPUSD"),
+            master_position(102, "XAUUSD"),
+        ];
+        let held = vec![ReceiverPosition {
+            position_id: 555,
+            master_position_id: 100,
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            volume: 0.2,
+            sl: None,
+            tp: None,
+            magic: None,
+        }];
+        let mut receiver = late_receiver();
+        receiver.symbol_mappings.push(SymbolMapping {
+            master_symbol: "XAUUSD".to_string(),
+            receiver_symbol: "GOLD".to_string(),
+            is_enabled: false,
+        });
+
+        let commands = build_catch_up_commands(&master, &held, &receiver, None);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].master_position_id, Some(101));
+    }
+}
@@ -0,0 +1,75 @@
+//! Terminal clock skew
+//!
+//! Event and heartbeat timestamps come from the terminal's clock. If that
+//! clock (or the EA's idea of UTC) is off, age filters and staleness checks
+//! quietly misjudge every event. The skew is estimated by comparing the
+//! heartbeat's `timestamp_utc` with the heartbeat file's modification time,
+//! which this machine's clock stamps as the EA writes it, so the estimate
+//! doesn't depend on how often heartbeats are written. Receivers write no
+//! heartbeat, so their account info's `updated_at` is used the same way.
+
+use serde::{Deserialize, Serialize};
+
+use super::commands::{read_heartbeat_written_at, read_receiver_heartbeat_written_at};
+
+/// Skew beyond this many seconds is reported as a warning
+pub const SKEW_WARNING_SECS: i64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSkew {
+    pub terminal_id: String,
+    /// Terminal clock minus local clock; positive = terminal runs ahead
+    pub estimated_skew_secs: i64,
+    pub warning: Option<String>,
+}
+
+/// Skew of a heartbeat stamped `timestamp_utc` by the terminal and observed
+/// being written at `written_at` by the local clock
+pub fn estimate_skew(
+    terminal_id: &str,
+    timestamp_utc: &str,
+    written_at: chrono::DateTime<chrono::Utc>,
+) -> Option<ClockSkew> {
+    let stamped = chrono::DateTime::parse_from_rfc3339(timestamp_utc).ok()?;
+    let skew = stamped.signed_duration_since(written_at).num_seconds();
+    let warning = (skew.abs() > SKEW_WARNING_SECS).then(|| {
+        format!(
+            "Terminal {} clock is {}s {} this computer - check its time and timezone",
+            terminal_id,
+            skew.abs(),
+            if skew > 0 { "ahead of" } else { "behind" }
+        )
+    });
+    Some(ClockSkew {
+        terminal_id: terminal_id.to_string(),
+        estimated_skew_secs: skew,
+        warning,
+    })
+}
+
+/// Current skew estimate for a terminal, from its latest heartbeat or,
+/// for a receiver, its account info
+pub fn measure(terminal_id: &str) -> Option<ClockSkew> {
+    let (heartbeat, written_at) = read_heartbeat_written_at(terminal_id)
+        .or_else(|_| read_receiver_heartbeat_written_at(terminal_id))
+        .ok()?;
+    estimate_skew(terminal_id, &heartbeat.timestamp_utc, written_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_from_the_future_warns() {
+        let written_at = chrono::Utc::now();
+        let stamped = (written_at + chrono::Duration::seconds(30)).to_rfc3339();
+
+        let skew = estimate_skew("T1", &stamped, written_at).unwrap();
+        assert_eq!(skew.estimated_skew_secs, 30);
+        assert!(skew.warning.unwrap().contains("30s ahead"));
+
+        let in_sync = estimate_skew("T1", &(written_at + chrono::Duration::seconds(1)).to_rfc3339(), written_at).unwrap();
+        assert!(in_sync.warning.is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Unconsumed receiver commands
+//!
+//! The receiver EA deletes each command file in `CopierCommands` once it has
+//! handled it, within a second or two. Command files older than
+//! [`UNCONSUMED_AFTER`] mean the EA is detached, stuck or disabled, and
+//! every copy sent to that receiver is silently going nowhere. The queue
+//! worker checks each receiver's folder periodically and alerts once per
+//! backlog; the alert re-arms when the folder drains.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::alerts::{self, AlertSeverity};
+use super::{config_generator, CopierConfig};
+
+/// Command files still waiting after this long count as unconsumed
+pub const UNCONSUMED_AFTER: Duration = Duration::from_secs(30);
+
+/// How often the queue worker checks the receivers' folders
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Files the EA consumes: trade commands, sync commands, emergency commands
+const COMMAND_PREFIXES: &[&str] = &["cmd_", "sync_", "emergency_"];
+
+/// Last check, and whether each receiver (by terminal id) was last seen
+/// with a backlog
+static LAST_CHECK: LazyLock<Mutex<Option<Instant>>> = LazyLock::new(|| Mutex::new(None));
+static BACKLOGGED: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A receiver's command files the EA hasn't picked up
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnconsumedCommands {
+    pub receiver_id: String,
+    pub terminal_id: String,
+    /// Files older than `UNCONSUMED_AFTER`
+    pub count: usize,
+    pub oldest_age_secs: Option<u64>,
+    /// File names, oldest first
+    pub files: Vec<String>,
+}
+
+/// Command files in `commands_folder` at least `threshold` old, oldest first.
+/// A missing folder has none.
+fn unconsumed_in(commands_folder: &Path, threshold: Duration, now: SystemTime) -> Vec<(String, Duration)> {
+    let Ok(entries) = fs::read_dir(commands_folder) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(String, Duration)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_command = name.ends_with(".json") && COMMAND_PREFIXES.iter().any(|p| name.starts_with(p));
+            if !is_command {
+                return None;
+            }
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            let age = now.duration_since(modified).unwrap_or_default();
+            (age >= threshold).then_some((name, age))
+        })
+        .collect();
+    files.sort_by_key(|(_, age)| std::cmp::Reverse(*age));
+    files
+}
+
+fn report(receiver_id: &str, terminal_id: &str, files: Vec<(String, Duration)>) -> UnconsumedCommands {
+    UnconsumedCommands {
+        receiver_id: receiver_id.to_string(),
+        terminal_id: terminal_id.to_string(),
+        count: files.len(),
+        oldest_age_secs: files.first().map(|(_, age)| age.as_secs()),
+        files: files.into_iter().map(|(name, _)| name).collect(),
+    }
+}
+
+/// Unconsumed command files of one receiver (by `account_id`)
+pub fn get_unconsumed_commands(config: &CopierConfig, receiver_id: &str) -> Result<UnconsumedCommands, String> {
+    let receiver = config
+        .receivers
+        .iter()
+        .find(|r| r.account_id == receiver_id)
+        .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+    let files_path = config_generator::get_terminal_files_path(&receiver.terminal_id)
+        .ok_or_else(|| format!("Could not find MQL5/Files for terminal {}", receiver.terminal_id))?;
+    let files = unconsumed_in(&files_path.join("CopierCommands"), UNCONSUMED_AFTER, SystemTime::now());
+    Ok(report(receiver_id, &receiver.terminal_id, files))
+}
+
+/// Whether a receiver's EA has command files (of any age) it hasn't picked
+/// up yet
+pub fn has_pending_commands(terminal_id: &str) -> bool {
+    config_generator::get_terminal_files_path(terminal_id)
+        .is_some_and(|path| !unconsumed_in(&path.join("CopierCommands"), Duration::ZERO, SystemTime::now()).is_empty())
+}
+
+/// Alert when `backlog` is new for this receiver; clear the flag once it's
+/// empty again
+fn flag_backlog(backlogged: &mut HashMap<String, bool>, account_number: &str, backlog: &UnconsumedCommands) {
+    let has_backlog = backlog.count > 0;
+    let had_backlog = backlogged.insert(backlog.terminal_id.clone(), has_backlog);
+    if has_backlog && had_backlog != Some(true) {
+        alerts::push_alert(
+            AlertSeverity::Critical,
+            format!(
+                "Receiver {} not consuming commands — EA may be detached ({} command file(s) waiting, oldest {}s)",
+                account_number,
+                backlog.count,
+                backlog.oldest_age_secs.unwrap_or_default()
+            ),
+        );
+    }
+}
+
+/// Check every receiver's folder for unconsumed commands, at most every
+/// `CHECK_INTERVAL`. Called from the queue worker while copying.
+pub fn watch_receivers(config: &CopierConfig) {
+    {
+        let mut last = LAST_CHECK.lock();
+        if last.is_some_and(|t| t.elapsed() < CHECK_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+
+    let mut backlogged = BACKLOGGED.lock();
+    for receiver in &config.receivers {
+        let Some(files_path) = config_generator::get_terminal_files_path(&receiver.terminal_id) else {
+            continue;
+        };
+        let files = unconsumed_in(&files_path.join("CopierCommands"), UNCONSUMED_AFTER, SystemTime::now());
+        let backlog = report(&receiver.account_id, &receiver.terminal_id, files);
+        flag_backlog(&mut backlogged, &receiver.account_number, &backlog);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_aged(folder: &Path, name: &str, age: Duration) {
+        let path = folder.join(name);
+        fs::write(&path, "{}").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn test_aged_command_files_raise_alert() {
+        let folder = std::env::temp_dir().join(format!("saturn_backlog_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&folder).unwrap();
+        write_aged(&folder, "cmd_1.json", Duration::from_secs(300));
+        write_aged(&folder, "sync_2_0.json", Duration::from_secs(120));
+        // Fresh, in flight, or not a command the EA consumes
+        write_aged(&folder, "cmd_3.json", Duration::from_secs(1));
+        write_aged(&folder, "sync_4_0.json.tmp", Duration::from_secs(300));
+        write_aged(&folder, "resp_1.json", Duration::from_secs(300));
+
+        let files = unconsumed_in(&folder, UNCONSUMED_AFTER, SystemTime::now());
+        let backlog = report("r1", "T-BACKLOG", files);
+        assert_eq!(backlog.count, 2);
+        assert_eq!(backlog.files, vec!["cmd_1.json".to_string(), "sync_2_0.json".to_string()]);
+        assert!(backlog.oldest_age_secs.is_some_and(|age| age >= 300));
+
+        let mut backlogged = HashMap::new();
+        flag_backlog(&mut backlogged, "7007", &backlog);
+        let alerted = |n: usize| {
+            alerts::get_alerts()
+                .iter()
+                .filter(|a| a.message.starts_with("Receiver 7007 not consuming commands"))
+                .count()
+                == n
+        };
+        assert!(alerted(1));
+
+        // Still backlogged: no repeat. Drained, then backlogged again: re-alerts.
+        flag_backlog(&mut backlogged, "7007", &backlog);
+        assert!(alerted(1));
+        flag_backlog(&mut backlogged, "7007", &report("r1", "T-BACKLOG", Vec::new()));
+        flag_backlog(&mut backlogged, "7007", &backlog);
+        assert!(alerted(2));
+
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
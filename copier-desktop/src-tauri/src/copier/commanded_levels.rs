@@ -0,0 +1,130 @@
+//! SL/TP levels the copier last set on receiver positions
+//!
+//! Every successful entry or modify records the absolute SL/TP it sent,
+//! keyed by receiver terminal and master position id. Reconciliation
+//! compares the live receiver levels against these rather than only against
+//! the master, so a level the user moved by hand on the receiver is
+//! recognised as such (see `position_sync::find_discrepancies`). Levels the
+//! EA computes itself (relative SL/TP) aren't known here and are not
+//! recorded. Persisted so a restart doesn't forget them.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use super::persistence;
+use super::position_sync::SyncCommand;
+use crate::data_dir::app_data_dir;
+
+const LEVELS_FILE: &str = "commanded_levels.json";
+
+/// Last SL/TP sent for one receiver position. `None` = not known;
+/// `Some(0.0)` = the copier cleared the level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandedLevels {
+    pub sl: Option<f64>,
+    pub tp: Option<f64>,
+}
+
+impl CommandedLevels {
+    /// Lay newly sent levels over these. A level that wasn't sent keeps its
+    /// previous value.
+    fn update(&mut self, sl: Option<f64>, tp: Option<f64>) {
+        self.sl = sl.or(self.sl);
+        self.tp = tp.or(self.tp);
+    }
+}
+
+/// Whether a live receiver level differs from what the copier last set by
+/// more than `tolerance`. Unknown commanded levels never count as moved.
+pub fn moved_by_hand(commanded: Option<f64>, live: Option<f64>, tolerance: f64) -> bool {
+    commanded.is_some_and(|commanded| (commanded.max(0.0) - live.unwrap_or(0.0).max(0.0)).abs() > tolerance)
+}
+
+/// Receiver terminal id -> master position id -> levels
+type LevelsBook = HashMap<String, HashMap<i64, CommandedLevels>>;
+
+fn get_levels_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(LEVELS_FILE))
+}
+
+fn load() -> LevelsBook {
+    get_levels_path()
+        .and_then(|path| {
+            persistence::load_state_file(&path, "Commanded SL/TP levels", |content| {
+                serde_json::from_str::<LevelsBook>(content).map_err(|e| e.to_string())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Write the levels (atomic write)
+fn save(book: &LevelsBook) -> Result<(), String> {
+    let Some(path) = get_levels_path() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string(book).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write commanded levels: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save commanded levels: {}", e))
+}
+
+static LEVELS: LazyLock<Mutex<LevelsBook>> = LazyLock::new(|| Mutex::new(load()));
+
+fn persist(book: &LevelsBook) {
+    if let Err(e) = save(book) {
+        warn!("Failed to persist commanded SL/TP levels: {}", e);
+    }
+}
+
+/// Record the SL/TP just sent for a receiver position. Nothing is recorded
+/// when neither level was sent.
+pub fn record(terminal_id: &str, master_position_id: i64, sl: Option<f64>, tp: Option<f64>) {
+    if sl.is_none() && tp.is_none() {
+        return;
+    }
+    let mut book = LEVELS.lock();
+    book.entry(terminal_id.to_string())
+        .or_default()
+        .entry(master_position_id)
+        .or_default()
+        .update(sl, tp);
+    persist(&book);
+}
+
+/// Drop a closed position's levels
+pub fn forget(terminal_id: &str, master_position_id: i64) {
+    let mut book = LEVELS.lock();
+    let removed = book
+        .get_mut(terminal_id)
+        .and_then(|positions| positions.remove(&master_position_id))
+        .is_some();
+    if removed {
+        persist(&book);
+    }
+}
+
+/// Keep the book in step with a sync command just written to a receiver:
+/// opens and SL/TP modifies record their levels, closes drop them
+pub fn remember_command(terminal_id: &str, command: &SyncCommand) {
+    let Some(master_position_id) = command.master_position_id else {
+        return;
+    };
+    match command.command_type.as_str() {
+        "open" | "modify_sl_tp" => record(terminal_id, master_position_id, command.sl, command.tp),
+        "close" => forget(terminal_id, master_position_id),
+        _ => {}
+    }
+}
+
+/// Levels recorded for a receiver's positions, by master position id
+pub fn for_receiver(terminal_id: &str) -> HashMap<i64, CommandedLevels> {
+    LEVELS.lock().get(terminal_id).cloned().unwrap_or_default()
+}
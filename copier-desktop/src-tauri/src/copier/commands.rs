@@ -3,9 +3,17 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use super::alerts::{self, AlertSeverity};
 use super::event_processor::get_cached_terminals;
+use super::file_lock;
+use super::position_sync::{self, ReceiverPosition, SyncCommand};
+use super::CopierState;
 
 /// Emergency command types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +62,21 @@ impl EmergencyCommand {
             reason: None,
         }
     }
+
+    /// Panic sequence: close everything, then pause so nothing reopens
+    pub fn flatten_and_pause(reason: Option<String>) -> [Self; 2] {
+        let mut pause = Self::pause();
+        pause.reason = reason.clone();
+        [Self::close_all(reason), pause]
+    }
+}
+
+/// Per-receiver outcome of a multi-receiver emergency action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiverCommandResult {
+    pub terminal_id: String,
+    pub success: bool,
+    pub error: Option<String>,
 }
 
 /// Get the commands folder path for a terminal
@@ -86,6 +109,12 @@ fn get_commands_folder(terminal_id: &str) -> Option<PathBuf> {
         .join("CopierCommands"))
 }
 
+/// Check the MQL5/Files folder holding `commands_folder` can be written
+fn ensure_writable(commands_folder: &Path) -> Result<(), String> {
+    let files_path = commands_folder.parent().unwrap_or(commands_folder);
+    Ok(crate::mt5::discovery::ensure_files_writable(files_path)?)
+}
+
 /// Write an emergency command to a receiver terminal (atomic write)
 pub fn send_emergency_command(
     terminal_id: &str,
@@ -93,6 +122,7 @@ pub fn send_emergency_command(
 ) -> Result<(), String> {
     let commands_folder = get_commands_folder(terminal_id)
         .ok_or_else(|| "Could not determine commands folder path".to_string())?;
+    ensure_writable(&commands_folder)?;
     
     fs::create_dir_all(&commands_folder)
         .map_err(|e| format!("Failed to create commands folder: {}", e))?;
@@ -107,17 +137,101 @@ pub fn send_emergency_command(
     let json = serde_json::to_string_pretty(command)
         .map_err(|e| format!("Failed to serialize command: {}", e))?;
     
-    // Write to temp file first
-    fs::write(&temp_file, json)
-        .map_err(|e| format!("Failed to write command: {}", e))?;
-    
-    // Atomic rename
-    fs::rename(&temp_file, &command_file)
+    file_lock::with_terminal_lock(terminal_id, || {
+        // Write to temp file first
+        fs::write(&temp_file, json)
+            .map_err(|e| format!("Failed to write command: {}", e))?;
+
+        // Atomic rename
+        fs::rename(&temp_file, &command_file)
+            .map_err(|e| format!("Failed to finalize command: {}", e))
+    })
+}
+
+/// Write close-all + pause to one receiver as an ordered pair.
+///
+/// File names sort close-all first, so an EA pass that sees both applies them
+/// in that order. Both are staged as `.tmp` and the pause is published first:
+/// if the EA polls between the two renames it pauses (blocking new entries)
+/// rather than flattening and then copying a late entry before the pause lands.
+/// Emergency commands are checked ahead of the pause gate, so the close-all
+/// still runs on the next pass.
+fn send_flatten_and_pause(commands_folder: &Path, reason: Option<String>) -> Result<(), String> {
+    ensure_writable(commands_folder)?;
+    fs::create_dir_all(commands_folder)
+        .map_err(|e| format!("Failed to create commands folder: {}", e))?;
+
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let [close_all, pause] = EmergencyCommand::flatten_and_pause(reason);
+
+    let close_file = commands_folder.join(format!("emergency_{}_0.json", timestamp));
+    let pause_file = commands_folder.join(format!("emergency_{}_1.json", timestamp));
+    let close_tmp = stage_command(&close_file, &close_all)?;
+    let pause_tmp = stage_command(&pause_file, &pause)?;
+
+    fs::rename(&pause_tmp, &pause_file)
         .map_err(|e| format!("Failed to finalize command: {}", e))?;
-    
+    fs::rename(&close_tmp, &close_file)
+        .map_err(|e| format!("Failed to finalize command: {}", e))?;
+
     Ok(())
 }
 
+/// Write `command` next to `final_path` as `.tmp`, returning the temp path
+fn stage_command(final_path: &Path, command: &EmergencyCommand) -> Result<PathBuf, String> {
+    let json = serde_json::to_string_pretty(command)
+        .map_err(|e| format!("Failed to serialize command: {}", e))?;
+    let temp_path = final_path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write command: {}", e))?;
+    Ok(temp_path)
+}
+
+/// Panic button: stop the copier, then flatten and pause every configured receiver.
+///
+/// `is_running` is cleared before any file is written so the desktop side
+/// stops dispatching new entries immediately. Returns one result per receiver.
+pub fn panic_stop(state: &Arc<Mutex<CopierState>>, reason: &str) -> Vec<ReceiverCommandResult> {
+    panic_stop_with(state, reason, get_commands_folder)
+}
+
+fn panic_stop_with(
+    state: &Arc<Mutex<CopierState>>,
+    reason: &str,
+    commands_folder_for: impl Fn(&str) -> Option<PathBuf>,
+) -> Vec<ReceiverCommandResult> {
+    let receiver_ids: Vec<String> = {
+        let mut copier = state.lock();
+        copier.is_running = false;
+        copier.panic_reason = Some(reason.to_string());
+        copier
+            .config
+            .as_ref()
+            .map(|c| c.receivers.iter().map(|r| r.terminal_id.clone()).collect())
+            .unwrap_or_default()
+    };
+
+    tracing::warn!("PANIC: flattening and pausing {} receivers ({})", receiver_ids.len(), reason);
+
+    receiver_ids
+        .into_iter()
+        .map(|terminal_id| {
+            let result = commands_folder_for(&terminal_id)
+                .ok_or_else(|| "Could not determine commands folder path".to_string())
+                .and_then(|folder| {
+                    file_lock::with_terminal_lock(&terminal_id, || {
+                        send_flatten_and_pause(&folder, Some(reason.to_string()))
+                    })
+                });
+            ReceiverCommandResult {
+                success: result.is_ok(),
+                error: result.err(),
+                terminal_id,
+            }
+        })
+        .collect()
+}
+
 /// Send close all command to all receivers
 pub fn close_all_positions(receiver_terminal_ids: &[String], reason: Option<String>) -> Result<(), String> {
     let command = EmergencyCommand::close_all(reason);
@@ -151,6 +265,49 @@ pub fn resume_all_receivers(receiver_terminal_ids: &[String]) -> Result<(), Stri
     Ok(())
 }
 
+/// `close_position` command for the receiver position copied from
+/// `master_position_id`
+pub fn build_close_command(positions: &[ReceiverPosition], master_position_id: i64) -> Result<SyncCommand, String> {
+    positions
+        .iter()
+        .find(|p| p.master_position_id == master_position_id)
+        .map(|p| SyncCommand::close_position(p.position_id))
+        .ok_or_else(|| format!("No receiver position mapped to master position {}", master_position_id))
+}
+
+/// Close one copied position on one receiver (e.g. to cut a loser) without
+/// touching the rest. Returns the receiver position id that was closed.
+pub fn close_receiver_position(
+    state: &Arc<Mutex<CopierState>>,
+    receiver_id: &str,
+    master_position_id: i64,
+) -> Result<i64, String> {
+    let receiver = state
+        .lock()
+        .config
+        .as_ref()
+        .and_then(|c| c.receivers.iter().find(|r| r.account_id == receiver_id).cloned())
+        .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+
+    let positions = position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number)?;
+    let command = build_close_command(&positions, master_position_id)?;
+    position_sync::write_sync_command(&receiver.terminal_id, &command)?;
+
+    let position_id = command.position_id.unwrap_or_default();
+    tracing::info!(
+        "Manual close of position {} (master {}) sent to {}",
+        position_id, master_position_id, receiver.account_number
+    );
+    alerts::push_alert(
+        AlertSeverity::Info,
+        format!(
+            "Manually closed position {} (master {}) on {}",
+            position_id, master_position_id, receiver.account_number
+        ),
+    );
+    Ok(position_id)
+}
+
 /// Read heartbeat from master terminal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Heartbeat {
@@ -162,7 +319,8 @@ pub struct Heartbeat {
     pub open_positions: i32,
 }
 
-pub fn read_master_heartbeat(terminal_id: &str) -> Result<Heartbeat, String> {
+/// Path of a terminal's heartbeat file (current or legacy location)
+fn heartbeat_file(terminal_id: &str) -> Result<PathBuf, String> {
     // Use the cached terminal list for portable support
     let terminals = get_cached_terminals();
     
@@ -192,26 +350,84 @@ pub fn read_master_heartbeat(terminal_id: &str) -> Result<Heartbeat, String> {
         .join("heartbeat.json");
     
     if heartbeat_file.exists() {
-        let content = fs::read_to_string(&heartbeat_file)
-            .map_err(|e| format!("Failed to read heartbeat: {}", e))?;
-        
-        return serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse heartbeat: {}", e));
+        return Ok(heartbeat_file);
     }
     
     // Fallback: legacy path
     let legacy_file = terminal_path.join("MQL5").join("Files").join("CopierHeartbeat.json");
     if legacy_file.exists() {
-        let content = fs::read_to_string(&legacy_file)
-            .map_err(|e| format!("Failed to read heartbeat: {}", e))?;
-        
-        return serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse heartbeat: {}", e));
+        return Ok(legacy_file);
     }
     
     Err("Heartbeat file not found".to_string())
 }
 
+pub fn read_master_heartbeat(terminal_id: &str) -> Result<Heartbeat, String> {
+    let content = fs::read_to_string(heartbeat_file(terminal_id)?)
+        .map_err(|e| format!("Failed to read heartbeat: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse heartbeat: {}", e))
+}
+
+/// Live values from a receiver's `CopierAccountInfo.json`
+#[derive(Deserialize)]
+struct ReceiverAccountFile {
+    #[serde(default)]
+    account_number: String,
+    balance: f64,
+    equity: f64,
+    /// UTC, as the receiver EA's clock sees it
+    updated_at: String,
+}
+
+/// A receiver's balance and equity as a `Heartbeat`. Receiver EAs don't
+/// write `heartbeat.json`; the account info file they refresh every 10s
+/// carries the same values.
+pub fn read_receiver_heartbeat(terminal_id: &str) -> Result<Heartbeat, String> {
+    let content = super::file_lock::read_contended(&receiver_account_file(terminal_id)?)
+        .map_err(|e| format!("Failed to read account info: {}", e))?;
+    parse_receiver_heartbeat(terminal_id, &content)
+}
+
+/// `read_receiver_heartbeat` plus when the file was last written, by this
+/// machine's clock
+pub fn read_receiver_heartbeat_written_at(terminal_id: &str) -> Result<(Heartbeat, chrono::DateTime<chrono::Utc>), String> {
+    let path = receiver_account_file(terminal_id)?;
+    let modified = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read account info mtime: {}", e))?;
+    let content =
+        super::file_lock::read_contended(&path).map_err(|e| format!("Failed to read account info: {}", e))?;
+    Ok((parse_receiver_heartbeat(terminal_id, &content)?, modified.into()))
+}
+
+fn receiver_account_file(terminal_id: &str) -> Result<PathBuf, String> {
+    Ok(crate::mt5::bridge::resolve_files_path(terminal_id, false)?.join("CopierAccountInfo.json"))
+}
+
+fn parse_receiver_heartbeat(terminal_id: &str, content: &str) -> Result<Heartbeat, String> {
+    let file: ReceiverAccountFile =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse account info: {}", e))?;
+    Ok(Heartbeat {
+        timestamp_utc: file.updated_at,
+        terminal_id: terminal_id.to_string(),
+        account: file.account_number.parse().unwrap_or_default(),
+        balance: file.balance,
+        equity: file.equity,
+        open_positions: 0,
+    })
+}
+
+/// The heartbeat plus when the file was last written, by this machine's clock
+pub fn read_heartbeat_written_at(terminal_id: &str) -> Result<(Heartbeat, chrono::DateTime<chrono::Utc>), String> {
+    let path = heartbeat_file(terminal_id)?;
+    let modified = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read heartbeat mtime: {}", e))?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read heartbeat: {}", e))?;
+    let heartbeat = serde_json::from_str(&content).map_err(|e| format!("Failed to parse heartbeat: {}", e))?;
+    Ok((heartbeat, modified.into()))
+}
+
 /// Check if master is online (heartbeat within last 30 seconds)
 pub fn is_master_online(terminal_id: &str) -> bool {
     match read_master_heartbeat(terminal_id) {
@@ -227,3 +443,275 @@ pub fn is_master_online(terminal_id: &str) -> bool {
         Err(_) => false,
     }
 }
+
+/// Result of an end-to-end connectivity ping to a terminal's EA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub success: bool,
+    /// Round-trip time from writing CopierPing.json to reading CopierPong.json
+    pub latency_ms: Option<u64>,
+    pub ea_version: Option<String>,
+    /// Whether the ping file could be written to CopierCommands
+    pub folder_writable: bool,
+    /// Whether a recent heartbeat/status file suggests an EA is attached
+    pub ea_attached: bool,
+    pub message: String,
+}
+
+/// Pong written by the EA in response to CopierPing.json
+#[derive(Debug, Clone, Deserialize)]
+struct PongFile {
+    #[serde(default)]
+    ping_id: Option<String>,
+    #[serde(default)]
+    ea_version: Option<String>,
+}
+
+const PING_FILE: &str = "CopierPing.json";
+const PONG_FILE: &str = "CopierPong.json";
+
+/// Ping a terminal's EA via the CopierCommands folder and measure round-trip latency
+pub fn ping_terminal(terminal_id: &str, timeout: Duration) -> Result<PingResult, String> {
+    let files_path = super::config_generator::get_terminal_files_path(terminal_id)
+        .ok_or_else(|| format!("Could not find MQL5/Files for terminal {}", terminal_id))?;
+    Ok(ping_files_folder(terminal_id, &files_path, timeout))
+}
+
+/// Ping through `<files>/CopierCommands`; split out so tests can use a temp folder
+fn ping_files_folder(terminal_id: &str, files_path: &Path, timeout: Duration) -> PingResult {
+    let commands_folder = files_path.join("CopierCommands");
+    let ping_path = commands_folder.join(PING_FILE);
+    let pong_path = commands_folder.join(PONG_FILE);
+    let ping_id = uuid::Uuid::new_v4().to_string();
+
+    let _ = fs::remove_file(&pong_path);
+
+    let started = Instant::now();
+    let written = file_lock::with_terminal_lock(terminal_id, || write_ping_file(&commands_folder, &ping_path, &ping_id));
+    if let Err(e) = written {
+        return PingResult {
+            success: false,
+            latency_ms: None,
+            ea_version: None,
+            folder_writable: false,
+            ea_attached: ea_appears_attached(files_path),
+            message: format!("Cannot write to {}: {}", commands_folder.display(), e),
+        };
+    }
+
+    while started.elapsed() < timeout {
+        if let Some(pong) = read_pong(&pong_path) {
+            // Ignore pongs left over from an earlier ping
+            if pong.ping_id.as_deref().is_none_or(|id| id == ping_id) {
+                let _ = fs::remove_file(&pong_path);
+                return PingResult {
+                    success: true,
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    ea_version: pong.ea_version,
+                    folder_writable: true,
+                    ea_attached: true,
+                    message: "EA responded".to_string(),
+                };
+            }
+        }
+        std::thread::sleep(Duration::from_millis(25));
+    }
+
+    // Don't leave the ping behind for a late EA to answer
+    let _ = fs::remove_file(&ping_path);
+    let ea_attached = ea_appears_attached(files_path);
+    let message = if ea_attached {
+        "Folder is writable and an EA heartbeat is recent, but no pong was received (EA may be outdated)"
+    } else {
+        "Folder is writable but no EA appears to be attached to this terminal"
+    };
+
+    PingResult {
+        success: false,
+        latency_ms: None,
+        ea_version: None,
+        folder_writable: true,
+        ea_attached,
+        message: message.to_string(),
+    }
+}
+
+fn write_ping_file(commands_folder: &Path, ping_path: &Path, ping_id: &str) -> std::io::Result<()> {
+    fs::create_dir_all(commands_folder)?;
+    let json = serde_json::json!({
+        "ping_id": ping_id,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    let temp_path = ping_path.with_extension("json.tmp");
+    fs::write(&temp_path, json.to_string())?;
+    fs::rename(&temp_path, ping_path)
+}
+
+fn read_pong(pong_path: &Path) -> Option<PongFile> {
+    let content = fs::read_to_string(pong_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Heuristic: an EA is attached if a heartbeat or status file was touched in the last 30s
+fn ea_appears_attached(files_path: &Path) -> bool {
+    [
+        files_path.join("CopierQueue").join("heartbeat.json"),
+        files_path.join("CopierHeartbeat.json"),
+        files_path.join("copier-status.json"),
+    ]
+    .iter()
+    .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+    .filter_map(|t| t.elapsed().ok())
+    .any(|age| age < Duration::from_secs(30))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+    use crate::copier::{CopierConfig, MasterConfig, ReceiverConfig};
+
+    fn position(position_id: i64, master_position_id: i64) -> ReceiverPosition {
+        ReceiverPosition {
+            position_id,
+            master_position_id,
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            volume: 0.1,
+            sl: None,
+            tp: None,
+            magic: None,
+        }
+    }
+
+    #[test]
+    fn test_close_targets_the_mapped_receiver_position() {
+        let positions = vec![position(501, 101), position(502, 102), position(503, 103)];
+
+        let command = build_close_command(&positions, 102).unwrap();
+        assert_eq!(command.command_type, "close");
+        assert_eq!(command.position_id, Some(502));
+
+        let error = build_close_command(&positions, 999).unwrap_err();
+        assert!(error.contains("999"));
+    }
+
+    fn temp_files_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("saturn_ping_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_receiver_account_info_reads_as_heartbeat() {
+        let content = r#"{
+  "account_number": "2002",
+  "broker": "B",
+  "balance": 5100.50,
+  "equity": 5050.00,
+  "leverage": 100,
+  "currency": "USD",
+  "ea_version": "3.1.0",
+  "updated_at": "2024-01-01T12:00:00Z"
+}"#;
+        let heartbeat = parse_receiver_heartbeat("R1", content).unwrap();
+        assert_eq!((heartbeat.account, heartbeat.balance, heartbeat.equity), (2002, 5100.5, 5050.0));
+        assert_eq!(heartbeat.timestamp_utc, "2024-01-01T12:00:00Z");
+        assert_eq!(heartbeat.terminal_id, "R1");
+    }
+
+    #[test]
+    fn test_ping_reads_pong() {
+        let dir = temp_files_dir();
+        let commands = dir.join("CopierCommands");
+
+        // Simulated EA: answer the ping once it appears
+        let ea_commands = commands.clone();
+        let ea = std::thread::spawn(move || {
+            let ping = ea_commands.join(PING_FILE);
+            for _ in 0..200 {
+                if let Ok(content) = fs::read_to_string(&ping) {
+                    let v: serde_json::Value = serde_json::from_str(&content).unwrap();
+                    let pong = serde_json::json!({ "ping_id": v["ping_id"], "ea_version": "2.00" });
+                    fs::remove_file(&ping).unwrap();
+                    fs::write(ea_commands.join(PONG_FILE), pong.to_string()).unwrap();
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        let result = ping_files_folder("T-PING", &dir, Duration::from_secs(3));
+        ea.join().unwrap();
+
+        assert!(result.success, "{}", result.message);
+        assert_eq!(result.ea_version.as_deref(), Some("2.00"));
+        assert!(result.latency_ms.is_some());
+        assert!(!commands.join(PONG_FILE).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ping_timeout_reports_diagnostics() {
+        let dir = temp_files_dir();
+
+        let result = ping_files_folder("T-PING", &dir, Duration::from_millis(100));
+        assert!(!result.success);
+        assert!(result.folder_writable);
+        assert!(!result.ea_attached);
+        assert!(!dir.join("CopierCommands").join(PING_FILE).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn receiver(terminal_id: &str) -> ReceiverConfig {
+        ReceiverConfig { terminal_id: terminal_id.to_string(), ..test_receiver() }
+    }
+
+    #[test]
+    fn test_panic_writes_close_then_pause_and_stops() {
+        let dir = std::env::temp_dir().join(format!("saturn_panic_test_{}", uuid::Uuid::new_v4()));
+        let state = Arc::new(Mutex::new(CopierState {
+            is_running: true,
+            config: Some(CopierConfig {
+                version: 1,
+                config_hash: String::new(),
+                master: MasterConfig {
+                    account_id: "m".to_string(),
+                    account_number: "1001".to_string(),
+                    broker: "B".to_string(),
+                    terminal_id: "MASTER".to_string(),
+                },
+                receivers: vec![receiver("R1")],
+                masters: vec![],
+                execution_strategy: Default::default(),
+            }),
+            ..Default::default()
+        }));
+
+        let folder = dir.join("CopierCommands");
+        let results = panic_stop_with(&state, "test", |_| Some(folder.clone()));
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success, "{:?}", results[0].error);
+        assert!(!state.lock().is_running);
+        assert_eq!(state.lock().panic_reason.as_deref(), Some("test"));
+
+        let mut files: Vec<String> = fs::read_dir(&folder)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| f.ends_with(".json")));
+
+        let first = fs::read_to_string(folder.join(&files[0])).unwrap();
+        let second = fs::read_to_string(folder.join(&files[1])).unwrap();
+        assert!(first.contains("close_all"));
+        assert!(second.contains("pause_copying"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
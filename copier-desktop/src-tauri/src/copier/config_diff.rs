@@ -0,0 +1,231 @@
+//! What a config sync changed
+//!
+//! `sync_config` returns a [`ConfigDiff`] between the previously active
+//! config and the new one so the UI can show what the cloud changed:
+//! receivers added or removed, risk changes, symbol mapping changes and
+//! safety setting changes. Receivers are matched by `account_id`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::{CopierConfig, ReceiverConfig, SymbolMapping};
+
+/// Receiver fields reported under `risk_changes`
+const RISK_FIELDS: &[&str] = &["risk_mode", "risk_value"];
+
+/// Receiver fields reported under `safety_changes`
+const SAFETY_FIELDS: &[&str] = &[
+    "max_slippage_pips",
+    "slippage_unit",
+    "max_daily_loss_r",
+    "prop_firm_safe_mode",
+    "max_total_lots",
+    "max_open_positions",
+    "profit_target_amount",
+    "profit_target_percent",
+    "block_closes_when_paused",
+    "auto_resume_after_secs",
+    "auto_resume_min_equity",
+];
+
+/// One changed receiver setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Receiver `account_id`
+    pub receiver_id: String,
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// One master symbol whose mapping was added, removed or changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingChange {
+    /// Receiver `account_id`
+    pub receiver_id: String,
+    pub master_symbol: String,
+    /// None = the mapping was added
+    pub old: Option<SymbolMapping>,
+    /// None = the mapping was removed
+    pub new: Option<SymbolMapping>,
+}
+
+/// Differences between two configs. Empty when nothing the UI shows changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    /// `account_id`s of receivers only in the new config
+    pub added_receivers: Vec<String>,
+    /// `account_id`s of receivers only in the old config
+    pub removed_receivers: Vec<String>,
+    pub risk_changes: Vec<FieldChange>,
+    pub symbol_mapping_changes: Vec<MappingChange>,
+    pub safety_changes: Vec<FieldChange>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_receivers.is_empty()
+            && self.removed_receivers.is_empty()
+            && self.risk_changes.is_empty()
+            && self.symbol_mapping_changes.is_empty()
+            && self.safety_changes.is_empty()
+    }
+}
+
+fn field_changes(receiver_id: &str, old: &Value, new: &Value, fields: &[&str]) -> Vec<FieldChange> {
+    fields
+        .iter()
+        .filter(|field| old.get(**field) != new.get(**field))
+        .map(|field| FieldChange {
+            receiver_id: receiver_id.to_string(),
+            field: field.to_string(),
+            old: old.get(*field).cloned().unwrap_or(Value::Null),
+            new: new.get(*field).cloned().unwrap_or(Value::Null),
+        })
+        .collect()
+}
+
+fn mapping_changes(old: &ReceiverConfig, new: &ReceiverConfig) -> Vec<MappingChange> {
+    let by_symbol = |receiver: &ReceiverConfig| -> BTreeMap<String, SymbolMapping> {
+        receiver
+            .symbol_mappings
+            .iter()
+            .map(|m| (m.master_symbol.clone(), m.clone()))
+            .collect()
+    };
+    let (old_mappings, new_mappings) = (by_symbol(old), by_symbol(new));
+    let mut symbols: Vec<&String> = old_mappings.keys().chain(new_mappings.keys()).collect();
+    symbols.sort();
+    symbols.dedup();
+    symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let (before, after) = (old_mappings.get(symbol), new_mappings.get(symbol));
+            let same = match (before, after) {
+                (Some(a), Some(b)) => a.receiver_symbol == b.receiver_symbol && a.is_enabled == b.is_enabled,
+                _ => false,
+            };
+            (!same).then(|| MappingChange {
+                receiver_id: new.account_id.clone(),
+                master_symbol: symbol.clone(),
+                old: before.cloned(),
+                new: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// What changed from `old` to `new`. Configs with the same `config_hash`
+/// have the same content and are reported unchanged without comparing.
+pub fn diff_config(old: &CopierConfig, new: &CopierConfig) -> ConfigDiff {
+    let mut diff = ConfigDiff::default();
+    if old.config_hash == new.config_hash {
+        return diff;
+    }
+
+    for receiver in &old.receivers {
+        if !new.receivers.iter().any(|r| r.account_id == receiver.account_id) {
+            diff.removed_receivers.push(receiver.account_id.clone());
+        }
+    }
+    for receiver in &new.receivers {
+        let Some(before) = old.receivers.iter().find(|r| r.account_id == receiver.account_id) else {
+            diff.added_receivers.push(receiver.account_id.clone());
+            continue;
+        };
+        let old_value = serde_json::to_value(before).unwrap_or_default();
+        let new_value = serde_json::to_value(receiver).unwrap_or_default();
+        diff.risk_changes
+            .extend(field_changes(&receiver.account_id, &old_value, &new_value, RISK_FIELDS));
+        diff.safety_changes
+            .extend(field_changes(&receiver.account_id, &old_value, &new_value, SAFETY_FIELDS));
+        diff.symbol_mapping_changes.extend(mapping_changes(before, receiver));
+    }
+    diff
+}
+
+/// `diff_config` against the config that was active before a sync; with
+/// none, every receiver counts as added
+pub fn diff_from(old: Option<&CopierConfig>, new: &CopierConfig) -> ConfigDiff {
+    match old {
+        Some(old) => diff_config(old, new),
+        None => ConfigDiff {
+            added_receivers: new.receivers.iter().map(|r| r.account_id.clone()).collect(),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(config_hash: &str, receivers: serde_json::Value) -> CopierConfig {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": config_hash,
+            "master": {
+                "account_id": "m1",
+                "account_number": "1001",
+                "broker": "A",
+                "terminal_id": "M1"
+            },
+            "receivers": receivers
+        }))
+        .unwrap()
+    }
+
+    fn receiver(account_id: &str, risk_value: f64) -> serde_json::Value {
+        serde_json::json!({
+            "account_id": account_id,
+            "account_number": "2001",
+            "broker": "B",
+            "terminal_id": format!("T-{}", account_id),
+            "risk_mode": "fixed_lot",
+            "risk_value": risk_value,
+            "max_slippage_pips": 3.0,
+            "max_daily_loss_r": null,
+            "prop_firm_safe_mode": false,
+            "symbol_mappings": [
+                { "master_symbol": "XAUUSD", "receiver_symbol": "GOLD", "is_enabled": true }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_diff_reports_added_receiver_and_risk_change() {
+        let old = config("aaa", serde_json::json!([receiver("r1", 0.1)]));
+        let new = config("bbb", serde_json::json!([receiver("r1", 0.2), receiver("r2", 0.1)]));
+
+        let diff = diff_config(&old, &new);
+        assert_eq!(diff.added_receivers, vec!["r2".to_string()]);
+        assert!(diff.removed_receivers.is_empty());
+        assert_eq!(
+            diff.risk_changes,
+            vec![FieldChange {
+                receiver_id: "r1".to_string(),
+                field: "risk_value".to_string(),
+                old: serde_json::json!(0.1),
+                new: serde_json::json!(0.2),
+            }]
+        );
+        assert!(diff.safety_changes.is_empty());
+        assert!(diff.symbol_mapping_changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_same_hash_is_empty() {
+        let old = config("aaa", serde_json::json!([receiver("r1", 0.1)]));
+        let mut new = old.clone();
+        new.receivers[0].symbol_mappings[0].receiver_symbol = "XAUUSD.m".to_string();
+        assert!(diff_config(&old, &new).is_empty());
+
+        new.config_hash = "bbb".to_string();
+        new.receivers[0].max_slippage_pips = 5.0;
+        let diff = diff_config(&old, &new);
+        assert_eq!(diff.symbol_mapping_changes.len(), 1);
+        assert_eq!(diff.symbol_mapping_changes[0].master_symbol, "XAUUSD");
+        assert_eq!(diff.safety_changes[0].field, "max_slippage_pips");
+    }
+}
@@ -4,7 +4,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use super::file_lock;
 
 /// Risk configuration for a receiver
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,10 +94,20 @@ impl Default for RiskConfig {
 }
 
 /// Generate a stable config hash using CRC32 (consistent across Rust versions)
+///
+/// The hash covers everything except `config_hash` itself, so a config can
+/// be re-hashed after a round trip through the UI.
 pub fn generate_config_hash(config: &CopierConfigFile) -> String {
-    // Create a reproducible hash by serializing to sorted JSON
+    // Create a reproducible hash by serializing to sorted JSON: going through
+    // `Value` orders object keys, so HashMap iteration order doesn't matter.
     // We use a simple FNV-1a hash which is stable across versions
-    let json = serde_json::to_string(config).unwrap_or_default();
+    let unhashed = CopierConfigFile {
+        config_hash: String::new(),
+        ..config.clone()
+    };
+    let json = serde_json::to_value(&unhashed)
+        .map(|v| v.to_string())
+        .unwrap_or_default();
     
     // FNV-1a 64-bit hash (stable, deterministic)
     let mut hash: u64 = 0xcbf29ce484222325;
@@ -114,14 +126,21 @@ pub fn get_terminal_files_path(terminal_id: &str) -> Option<PathBuf> {
     crate::mt5::bridge::resolve_files_path(terminal_id, true).ok()
 }
 
-/// Save config file to a receiver terminal (atomic write)
-pub fn save_config_to_terminal(
-    terminal_id: &str,
-    config: &CopierConfigFile,
-) -> Result<PathBuf, String> {
-    let files_path = get_terminal_files_path(terminal_id)
-        .ok_or_else(|| format!("Could not find MQL5/Files for terminal {}", terminal_id))?;
-    
+/// Check that `config_hash` matches the config's contents. Catches configs
+/// edited after they were built/previewed.
+pub fn verify_config_hash(config: &CopierConfigFile) -> Result<(), String> {
+    let expected = generate_config_hash(config);
+    if config.config_hash != expected {
+        return Err(format!(
+            "Config hash mismatch (got {}, expected {}) - the config changed after it was generated; preview it again",
+            config.config_hash, expected
+        ));
+    }
+    Ok(())
+}
+
+/// Write `copier-config.json` into an MQL5/Files folder (atomic write)
+fn write_config_file(files_path: &Path, config: &CopierConfigFile) -> Result<PathBuf, String> {
     let config_path = files_path.join("copier-config.json");
     let temp_path = files_path.join("copier-config.json.tmp");
     
@@ -139,6 +158,13 @@ pub fn save_config_to_terminal(
     Ok(config_path)
 }
 
+/// The `copier-config.json` currently provisioned on a terminal, if any
+pub fn read_provisioned_config(terminal_id: &str) -> Option<CopierConfigFile> {
+    let files_path = crate::mt5::bridge::resolve_files_path(terminal_id, false).ok()?;
+    let content = fs::read_to_string(files_path.join("copier-config.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 /// Build a complete config file from wizard data
 pub fn build_config_file(
     master_terminal_id: &str,
@@ -165,21 +191,92 @@ pub fn build_config_file(
     config
 }
 
+/// Copier folders under MQL5/Files, parents before children
+const COPIER_FOLDERS: [&str; 4] = [
+    "CopierQueue",
+    "CopierQueue/pending",
+    "CopierQueue/executed",
+    "CopierCommands",
+];
+
+/// What `provision_terminal` did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionSummary {
+    pub terminal_id: String,
+    /// Folders that didn't exist before (already present ones are omitted)
+    pub created_folders: Vec<PathBuf>,
+    pub config_path: PathBuf,
+}
+
 /// Create copier folders in a terminal's MQL5/Files directory
 pub fn ensure_copier_folders(terminal_id: &str) -> Result<(), String> {
     let files_path = get_terminal_files_path(terminal_id)
         .ok_or_else(|| format!("Could not find MQL5/Files for terminal {}", terminal_id))?;
     
-    let copier_queue = files_path.join("CopierQueue");
-    let pending = copier_queue.join("pending");
-    let executed = copier_queue.join("executed");
-    
-    fs::create_dir_all(&pending)
-        .map_err(|e| format!("Failed to create pending folder: {}", e))?;
-    fs::create_dir_all(&executed)
-        .map_err(|e| format!("Failed to create executed folder: {}", e))?;
-    
-    Ok(())
+    create_copier_folders(&files_path).map(|_| ())
+}
+
+/// Create any missing copier folders, returning the ones created. On
+/// failure the folders created by this call are removed again.
+fn create_copier_folders(files_path: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut created = Vec::new();
+    for folder in COPIER_FOLDERS {
+        let path = files_path.join(folder);
+        if path.is_dir() {
+            continue;
+        }
+        if let Err(e) = fs::create_dir(&path) {
+            remove_folders(&created);
+            return Err(format!("Failed to create {} folder: {}", folder, e));
+        }
+        created.push(path);
+    }
+    Ok(created)
+}
+
+/// Best-effort removal of folders created during a failed provision
+fn remove_folders(created: &[PathBuf]) {
+    for path in created.iter().rev() {
+        let _ = fs::remove_dir_all(path);
+    }
+}
+
+/// Prepare a receiver terminal in one step: copier folders first, then the
+/// config written atomically last, so the config appearing means the
+/// terminal is ready. If the config can't be written the folders created
+/// here are rolled back.
+pub fn provision_terminal(terminal_id: &str, config: &CopierConfigFile) -> Result<ProvisionSummary, String> {
+    let files_path = get_terminal_files_path(terminal_id)
+        .ok_or_else(|| format!("Could not find MQL5/Files for terminal {}", terminal_id))?;
+
+    provision_files_path(terminal_id, &files_path, config)
+}
+
+fn provision_files_path(
+    terminal_id: &str,
+    files_path: &Path,
+    config: &CopierConfigFile,
+) -> Result<ProvisionSummary, String> {
+    verify_config_hash(config)?;
+    crate::mt5::discovery::ensure_files_writable(files_path)?;
+
+    // Another provision of the same terminal would share the config's temp file
+    file_lock::with_terminal_lock(terminal_id, || {
+        let created_folders = create_copier_folders(files_path)?;
+
+        match write_config_file(files_path, config) {
+            Ok(config_path) => Ok(ProvisionSummary {
+                terminal_id: terminal_id.to_string(),
+                created_folders,
+                config_path,
+            }),
+            Err(e) => {
+                let _ = fs::remove_file(files_path.join("copier-config.json.tmp"));
+                remove_folders(&created_folders);
+                Err(e)
+            }
+        }
+    })
 }
 
 #[cfg(test)]
@@ -218,4 +315,78 @@ mod tests {
         let hash2 = generate_config_hash(&config);
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn test_preview_hash_matches_saved_file() {
+        let receiver = ReceiverConfigFile {
+            receiver_id: "receiver_0".to_string(),
+            account_name: "B - 2002".to_string(),
+            account_number: "2002".to_string(),
+            broker: "B".to_string(),
+            terminal_id: "R1".to_string(),
+            risk: RiskConfig::default(),
+            safety: SafetyConfig::default(),
+            symbol_mappings: (0..20).map(|i| (format!("SYM{}", i), format!("SYM{}.r", i))).collect(),
+            symbol_overrides: None,
+        };
+        let preview = build_config_file("M1", "1001", "A", vec![receiver]);
+
+        // The UI sends the preview back as JSON; map order may differ
+        let returned: CopierConfigFile =
+            serde_json::from_str(&serde_json::to_string(&preview).unwrap()).unwrap();
+        verify_config_hash(&returned).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("saturn_config_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = write_config_file(&dir, &returned).unwrap();
+        let saved: CopierConfigFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.config_hash, preview.config_hash);
+        assert_eq!(generate_config_hash(&saved), preview.config_hash);
+
+        // Edited after preview: refused
+        let mut tampered = returned;
+        tampered.receivers[0].risk.value = 10.0;
+        assert!(verify_config_hash(&tampered).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn temp_files_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("saturn_provision_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_provision_creates_folders_before_config() {
+        let config = build_config_file("M1", "1001", "A", vec![]);
+
+        // Folder creation fails part-way: no config, no leftover folders
+        let dir = temp_files_dir();
+        fs::write(dir.join("CopierCommands"), "not a folder").unwrap();
+        assert!(provision_files_path("R1", &dir, &config).is_err());
+        assert!(!dir.join("copier-config.json").exists());
+        assert!(!dir.join("CopierQueue").exists());
+        let _ = fs::remove_dir_all(&dir);
+
+        // Config write fails: folders created for it are rolled back
+        let dir = temp_files_dir();
+        fs::create_dir(dir.join("copier-config.json")).unwrap();
+        fs::write(dir.join("copier-config.json").join("blocker"), "").unwrap();
+        assert!(provision_files_path("R1", &dir, &config).is_err());
+        assert!(!dir.join("CopierQueue").exists());
+        assert!(!dir.join("CopierCommands").exists());
+        let _ = fs::remove_dir_all(&dir);
+
+        // Success: every folder exists alongside the config
+        let dir = temp_files_dir();
+        fs::create_dir(dir.join("CopierCommands")).unwrap();
+        let summary = provision_files_path("R1", &dir, &config).unwrap();
+        assert_eq!(summary.created_folders.len(), 3);
+        for folder in COPIER_FOLDERS {
+            assert!(dir.join(folder).is_dir());
+        }
+        assert!(summary.config_path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
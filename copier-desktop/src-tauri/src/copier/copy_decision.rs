@@ -0,0 +1,285 @@
+//! Copy decision explanations
+//!
+//! Answers "why didn't this copy?" for one receiver and master symbol by
+//! walking the checks `event_processor` applies to an entry, in the same
+//! order, and reporting each one instead of stopping at the first refusal.
+//! There is no per-symbol allow/blocklist; a symbol is effectively blocked
+//! when it resolves to a name the receiver's broker doesn't offer.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::event_processor::{get_cached_account_info, map_symbol, outdated_ea_reason, receiver_safety_config};
+use super::safety::{self, SafetyCheckResult};
+use super::symbol_catalog::{self, SymbolCatalog};
+use super::{kill_switch, CopierState, ReceiverConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckOutcome {
+    Pass,
+    /// Copies, but worth knowing about
+    Warn,
+    /// Stops the copy
+    Fail,
+    /// Couldn't be evaluated (e.g. catalog not fetched yet)
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionCheck {
+    pub check: String,
+    pub outcome: CheckOutcome,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyDecision {
+    pub receiver_id: String,
+    pub master_symbol: String,
+    /// Symbol an entry would be sent as
+    pub receiver_symbol: String,
+    /// False if any check fails
+    pub would_copy: bool,
+    pub checks: Vec<DecisionCheck>,
+}
+
+/// Live state the checks read, gathered up front so `explain` stays pure
+pub struct DecisionInputs<'a> {
+    pub copier_running: bool,
+    pub kill_switch_engaged: bool,
+    pub outdated_ea: Option<String>,
+    pub safety: SafetyCheckResult,
+    pub catalog: Option<&'a SymbolCatalog>,
+}
+
+fn check(name: &str, outcome: CheckOutcome, detail: impl Into<String>) -> DecisionCheck {
+    DecisionCheck {
+        check: name.to_string(),
+        outcome,
+        detail: detail.into(),
+    }
+}
+
+/// Evaluate every copy check for `master_symbol` on `receiver`
+pub fn explain(receiver: &ReceiverConfig, master_symbol: &str, inputs: &DecisionInputs) -> CopyDecision {
+    use CheckOutcome::*;
+    let mut checks = Vec::new();
+
+    checks.push(if inputs.copier_running {
+        check("copier_running", Pass, "Copier is running")
+    } else {
+        check("copier_running", Fail, "Copier is stopped - events are not processed")
+    });
+
+    checks.push(if inputs.kill_switch_engaged {
+        check("kill_switch", Fail, "Kill switch is engaged")
+    } else {
+        check("kill_switch", Pass, "Kill switch is off")
+    });
+
+    checks.push(if receiver.enabled {
+        check("receiver_enabled", Pass, "Receiver is enabled")
+    } else {
+        check("receiver_enabled", Fail, "Receiver is disabled - only closes are copied")
+    });
+
+    if receiver.manual_confirm_mode {
+        checks.push(check("manual_confirm", Warn, "Entries wait for manual approval"));
+    }
+
+    if let Some(fraction) = receiver.copy_fraction.filter(|f| *f < 1.0) {
+        checks.push(check(
+            "copy_fraction",
+            Warn,
+            format!("Only {:.0}% of master positions are copied", fraction.clamp(0.0, 1.0) * 100.0),
+        ));
+    }
+
+    checks.push(match &inputs.outdated_ea {
+        Some(reason) => check("ea_version", Fail, reason.clone()),
+        None => check("ea_version", Pass, "Receiver EA version is supported"),
+    });
+
+    checks.push(match &inputs.safety {
+        SafetyCheckResult::Allowed => check("safety", Pass, "Within safety limits"),
+        SafetyCheckResult::Warning(warning) => check("safety", Warn, warning.clone()),
+        SafetyCheckResult::Blocked(reason) => check("safety", Fail, reason.clone()),
+    });
+
+    // Mirrors `map_symbol`: only an enabled mapping renames the symbol
+    let receiver_symbol = map_symbol(receiver, master_symbol);
+    let disabled_mapping = receiver
+        .symbol_mappings
+        .iter()
+        .find(|m| m.master_symbol == master_symbol && !m.is_enabled);
+    checks.push(if receiver_symbol != master_symbol {
+        check("symbol_mapping", Pass, format!("Mapped {} -> {}", master_symbol, receiver_symbol))
+    } else if let Some(mapping) = disabled_mapping {
+        check(
+            "symbol_mapping",
+            Warn,
+            format!(
+                "Mapping {} -> {} is disabled; sent as {}",
+                master_symbol, mapping.receiver_symbol, master_symbol
+            ),
+        )
+    } else {
+        check("symbol_mapping", Pass, format!("No mapping; sent as {}", master_symbol))
+    });
+
+    checks.push(match inputs.catalog {
+        None => check("receiver_catalog", Unknown, "Receiver symbol catalog not available yet"),
+        Some(catalog) if catalog.symbols.iter().any(|s| s.name == receiver_symbol) => {
+            check("receiver_catalog", Pass, format!("{} is offered by the receiver's broker", receiver_symbol))
+        }
+        Some(_) => check(
+            "receiver_catalog",
+            Fail,
+            format!("{} is not in the receiver's symbol catalog", receiver_symbol),
+        ),
+    });
+
+    CopyDecision {
+        receiver_id: receiver.account_id.clone(),
+        master_symbol: master_symbol.to_string(),
+        receiver_symbol,
+        would_copy: checks.iter().all(|c| c.outcome != Fail),
+        checks,
+    }
+}
+
+/// Explain the copy decision for a receiver (by account id) in the current
+/// config. The safety check is a preview: it reports a limit breach the
+/// next event would hit without pausing the receiver.
+pub fn explain_for(
+    state: &Arc<Mutex<CopierState>>,
+    receiver_id: &str,
+    master_symbol: &str,
+) -> Result<CopyDecision, String> {
+    let (copier_running, receiver) = {
+        let copier = state.lock();
+        let config = copier.config.as_ref().ok_or("No copier config loaded")?;
+        let receiver = config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .cloned()
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+        (copier.is_running, receiver)
+    };
+
+    let starting_balance = get_cached_account_info(&receiver.terminal_id)
+        .map(|a| a.balance)
+        .unwrap_or(10000.0);
+    let catalog = symbol_catalog::fetch_symbol_catalog(&receiver.terminal_id).ok();
+    let inputs = DecisionInputs {
+        copier_running,
+        kill_switch_engaged: kill_switch::is_engaged(),
+        outdated_ea: outdated_ea_reason(&receiver),
+        safety: safety::preview_trade_safety(&receiver.account_number, &receiver_safety_config(&receiver), starting_balance),
+        catalog: catalog.as_ref(),
+    };
+    Ok(explain(&receiver, master_symbol, &inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+    use crate::copier::symbol_catalog::SymbolSpec;
+    use crate::copier::SymbolMapping;
+
+    fn receiver(mappings: Vec<SymbolMapping>) -> ReceiverConfig {
+        ReceiverConfig {
+            account_id: "acc-1".to_string(),
+            account_number: "explain-test-2002".to_string(),
+            symbol_mappings: mappings,
+            ..test_receiver()
+        }
+    }
+
+    fn catalog(names: &[&str]) -> SymbolCatalog {
+        SymbolCatalog {
+            terminal_id: "R1".to_string(),
+            symbols: names
+                .iter()
+                .map(|name| SymbolSpec {
+                    name: name.to_string(),
+                    normalized_key: name.to_string(),
+                    tick_value: 1.0,
+                    tick_size: 0.01,
+                    contract_size: 100.0,
+                    digits: 2,
+                    min_lot: 0.01,
+                    lot_step: 0.01,
+                    max_lot: 100.0,
+                    description: None,
+                    trade_mode: None,
+                    profit_currency: None,
+                })
+                .collect(),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            broker_suffix: None,
+        }
+    }
+
+    fn inputs(safety: SafetyCheckResult, catalog: Option<&SymbolCatalog>) -> DecisionInputs<'_> {
+        DecisionInputs {
+            copier_running: true,
+            kill_switch_engaged: false,
+            outdated_ea: None,
+            safety,
+            catalog,
+        }
+    }
+
+    fn outcome(decision: &CopyDecision, name: &str) -> CheckOutcome {
+        decision.checks.iter().find(|c| c.check == name).unwrap().outcome
+    }
+
+    #[test]
+    fn test_disabled_mapping_blocks_copy() {
+        let gold = SymbolMapping {
+            master_symbol: "XAUUSD".to_string(),
+            receiver_symbol: "GOLD".to_string(),
+            is_enabled: false,
+        };
+        let catalog = catalog(&["GOLD", "EURUSD"]);
+
+        let decision = explain(&receiver(vec![gold]), "XAUUSD", &inputs(SafetyCheckResult::Allowed, Some(&catalog)));
+        assert!(!decision.would_copy);
+        assert_eq!(decision.receiver_symbol, "XAUUSD");
+        assert_eq!(outcome(&decision, "symbol_mapping"), CheckOutcome::Warn);
+        assert_eq!(outcome(&decision, "receiver_catalog"), CheckOutcome::Fail);
+        assert_eq!(outcome(&decision, "safety"), CheckOutcome::Pass);
+
+        let unmapped = explain(&receiver(vec![]), "EURUSD", &inputs(SafetyCheckResult::Allowed, Some(&catalog)));
+        assert!(unmapped.would_copy);
+    }
+
+    #[test]
+    fn test_safety_pause_blocks_copy() {
+        let receiver = receiver(vec![]);
+        safety::update_receiver_state(
+            &receiver.account_number,
+            safety::ReceiverSafetyState {
+                is_safety_paused: true,
+                pause_reason: Some("Daily loss limit reached: $350.00".to_string()),
+                last_reset_date: Some(chrono::Utc::now().format("%Y-%m-%d").to_string()),
+                ..Default::default()
+            },
+        );
+        let safety = safety::check_trade_safety(&receiver.account_number, &receiver_safety_config(&receiver), 10000.0);
+        safety::clear_receiver_state(&receiver.account_number);
+
+        let catalog = catalog(&["EURUSD"]);
+        let decision = explain(&receiver, "EURUSD", &inputs(safety, Some(&catalog)));
+        assert!(!decision.would_copy);
+        let failed: Vec<&DecisionCheck> = decision.checks.iter().filter(|c| c.outcome == CheckOutcome::Fail).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].check, "safety");
+        assert!(failed[0].detail.contains("Daily loss limit"));
+    }
+}
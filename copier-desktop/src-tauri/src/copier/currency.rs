@@ -0,0 +1,130 @@
+//! Currency conversion for risk-based sizing
+//!
+//! `risk_percent`, `risk_dollar` and `intent` size a trade so the SL loses a
+//! target amount in the receiver's account currency. MT5 reports tick value
+//! in the terminal's deposit currency, so when sizing falls back to the
+//! master's tick value and the two accounts' currencies differ, it is
+//! converted at the receiver's own quotes (its `CopierTicks.json` snapshot);
+//! without a usable quote sizing proceeds unconverted.
+
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::symbol_catalog::normalize_symbol;
+use super::ticks;
+
+/// Mid prices by normalized symbol name (e.g. "EURUSD" -> 1.0850)
+pub type Quotes = HashMap<String, f64>;
+
+/// Receiver's current mid prices, keyed by normalized symbol name so broker
+/// suffixes don't matter. Empty if its tick file is missing or stale.
+pub fn receiver_quotes(terminal_id: &str) -> Quotes {
+    ticks::latest_snapshot(terminal_id)
+        .map(|snapshot| {
+            snapshot
+                .ticks
+                .iter()
+                .filter(|(_, tick)| tick.bid > 0.0 && tick.ask > 0.0)
+                .map(|(symbol, tick)| (normalize_symbol(symbol), (tick.bid + tick.ask) / 2.0))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Rate turning an amount in `from` into `to`, from a direct (`FROMTO`) or
+/// inverse (`TOFROM`) pair. None if neither is quoted.
+pub fn conversion_rate(from: &str, to: &str, quotes: &Quotes) -> Option<f64> {
+    let from = from.to_uppercase();
+    let to = to.to_uppercase();
+    if from == to {
+        return Some(1.0);
+    }
+    if let Some(&price) = quotes.get(&format!("{}{}", from, to)).filter(|p| **p > 0.0) {
+        return Some(price);
+    }
+    quotes
+        .get(&format!("{}{}", to, from))
+        .filter(|p| **p > 0.0)
+        .map(|price| 1.0 / price)
+}
+
+/// `tick_value` (in `from_currency`) expressed in `account_currency`.
+/// Unknown currencies or a missing quote leave it unconverted, with a
+/// warning for the latter.
+pub fn tick_value_in_account_currency(
+    tick_value: f64,
+    from_currency: Option<&str>,
+    account_currency: &str,
+    quotes: &Quotes,
+) -> f64 {
+    let Some(from_currency) = from_currency.filter(|c| !c.is_empty()) else {
+        return tick_value;
+    };
+    if account_currency.is_empty() {
+        return tick_value;
+    }
+    match conversion_rate(from_currency, account_currency, quotes) {
+        Some(rate) => tick_value * rate,
+        None => {
+            warn!(
+                "No {}/{} quote to convert tick value; sizing in {} unconverted",
+                from_currency, account_currency, from_currency
+            );
+            tick_value
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::lot_calculator::{calculate_lots, AccountInfo, SymbolInfo};
+
+    fn quotes(pairs: &[(&str, f64)]) -> Quotes {
+        pairs.iter().map(|(s, p)| (s.to_string(), *p)).collect()
+    }
+
+    #[test]
+    fn test_conversion_rate_direct_and_inverse() {
+        let quotes = quotes(&[("EURUSD", 1.25), ("USDJPY", 150.0)]);
+        assert_eq!(conversion_rate("EUR", "USD", &quotes), Some(1.25));
+        assert_eq!(conversion_rate("USD", "EUR", &quotes), Some(0.8));
+        assert_eq!(conversion_rate("usd", "USD", &quotes), Some(1.0));
+        assert_eq!(conversion_rate("GBP", "EUR", &quotes), None);
+    }
+
+    #[test]
+    fn test_eur_account_sizing_from_usd_master() {
+        // EUR account risking 120 EUR on a 20 pip EURUSD stop; the USD master's
+        // tick value is 1 USD
+        let account = AccountInfo {
+            balance: 10000.0,
+            equity: 10000.0,
+            currency: "EUR".to_string(),
+            leverage: 100,
+        };
+        let quotes = quotes(&[("EURUSD", 1.25)]);
+        let tick_value = tick_value_in_account_currency(1.0, Some("USD"), &account.currency, &quotes);
+        assert!((tick_value - 0.8).abs() < 1e-9);
+
+        let info = SymbolInfo {
+            tick_value,
+            tick_size: 0.00001,
+            ..SymbolInfo::default()
+        };
+        // 200 ticks * 0.8 EUR = 160 EUR per lot -> 120 / 160
+        let lots = calculate_lots("risk_dollar", 120.0, 1.0, 1.1, Some(1.098), None, Some(&account), Some(&info));
+        assert_eq!(lots, 0.75);
+
+        // No quote: unconverted, 120 / 200 USD per lot
+        let unconverted = tick_value_in_account_currency(1.0, Some("USD"), &account.currency, &Quotes::new());
+        assert_eq!(unconverted, 1.0);
+        let info = SymbolInfo {
+            tick_value: unconverted,
+            tick_size: 0.00001,
+            ..SymbolInfo::default()
+        };
+        let lots = calculate_lots("risk_dollar", 120.0, 1.0, 1.1, Some(1.098), None, Some(&account), Some(&info));
+        assert_eq!(lots, 0.6);
+    }
+}
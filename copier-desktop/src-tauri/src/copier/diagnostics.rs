@@ -0,0 +1,194 @@
+//! Support diagnostics bundle
+//!
+//! `collect_diagnostics` packs what support needs to look into a bug report
+//! into one zip for the user to attach: the log files, the loaded config,
+//! safety state, the execution queue and a health snapshot. It's only ever
+//! written on request.
+//!
+//! Nothing secret leaves the machine: the API key file is never read into
+//! the archive, config account numbers are masked, and every entry (logs
+//! included) is scrubbed of the API key and the configured account numbers.
+
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tracing::{info, warn};
+use zip::write::FileOptions;
+
+use super::execution_queue::EXECUTION_QUEUE;
+use super::{health, safety, CopierConfig, CopierState};
+use crate::redact::{config_accounts, mask_account, Redactor};
+
+/// What the archive holds, written as `manifest.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsManifest {
+    pub created_at: String,
+    pub app_version: String,
+    pub entries: Vec<String>,
+}
+
+/// The config with every account number masked
+fn redacted_config(config: &CopierConfig) -> CopierConfig {
+    let mut config = config.clone();
+    config.master.account_number = mask_account(&config.master.account_number);
+    for master in &mut config.masters {
+        master.account_number = mask_account(&master.account_number);
+    }
+    for receiver in &mut config.receivers {
+        receiver.account_number = mask_account(&receiver.account_number);
+    }
+    config
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+}
+
+/// Archive entries (name, unscrubbed text) for the current state
+fn gather_entries(state: &CopierState, log_dir: &Path) -> Vec<(String, String)> {
+    let config = state
+        .config
+        .as_ref()
+        .map(|c| to_json(&redacted_config(c)))
+        .unwrap_or_else(|| "null".to_string());
+    let queue = EXECUTION_QUEUE.update(|queue| queue.to_json()).unwrap_or_else(|e| to_json(&e));
+    let mut entries = vec![
+        ("config.json".to_string(), config),
+        ("safety_state.json".to_string(), to_json(&safety::get_all_receiver_states())),
+        ("queue.json".to_string(), queue),
+        ("health.json".to_string(), to_json(&health::health_snapshot(state))),
+    ];
+
+    let mut log_files: Vec<_> = fs::read_dir(log_dir)
+        .map(|dir| dir.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+        .unwrap_or_default();
+    log_files.sort();
+    for path in log_files {
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        match fs::read(&path) {
+            Ok(bytes) => entries.push((format!("logs/{}", name), String::from_utf8_lossy(&bytes).into_owned())),
+            Err(e) => warn!("Leaving log file {:?} out of diagnostics: {}", path, e),
+        }
+    }
+    entries
+}
+
+/// Scrub every entry and write them, plus a manifest, as a zip at `path`
+/// (atomic write)
+fn write_archive(path: &Path, entries: Vec<(String, String)>, redactor: &Redactor) -> Result<DiagnosticsManifest, String> {
+    let manifest = DiagnosticsManifest {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        entries: entries.iter().map(|(name, _)| name.clone()).collect(),
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder for diagnostics: {}", e))?;
+    }
+    let temp_path = path.with_extension("tmp");
+    let file = fs::File::create(&temp_path).map_err(|e| format!("Failed to create diagnostics archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = FileOptions::default();
+    let write_err = |e: &dyn std::fmt::Display| format!("Failed to write diagnostics archive: {}", e);
+
+    zip.start_file("manifest.json", options).map_err(|e| write_err(&e))?;
+    zip.write_all(to_json(&manifest).as_bytes()).map_err(|e| write_err(&e))?;
+    for (name, text) in entries {
+        zip.start_file(name, options).map_err(|e| write_err(&e))?;
+        zip.write_all(redactor.scrub(&text).as_bytes()).map_err(|e| write_err(&e))?;
+    }
+    zip.finish().map_err(|e| write_err(&e))?;
+
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to save diagnostics archive: {}", e))?;
+    Ok(manifest)
+}
+
+fn collect_from(path: &Path, state: &CopierState, log_dir: &Path, api_keys: &[&str]) -> Result<DiagnosticsManifest, String> {
+    let accounts = state.config.as_ref().map(config_accounts).unwrap_or_default();
+    let redactor = Redactor::new(api_keys, &accounts);
+    write_archive(path, gather_entries(state, log_dir), &redactor)
+}
+
+/// Write the diagnostics archive for the current state to `path`
+pub fn collect_diagnostics(path: &Path, state: &CopierState) -> Result<DiagnosticsManifest, String> {
+    // The saved key is scrubbed as well as the live one, in case they differ
+    let saved_key = crate::sync::config::load_api_key().ok();
+    let api_keys: Vec<&str> = state.api_key.iter().chain(saved_key.iter()).map(String::as_str).collect();
+    let manifest = collect_from(path, state, &crate::logging::get_log_dir(), &api_keys)?;
+    info!("Wrote diagnostics archive to {:?} ({} entries)", path, manifest.entries.len());
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redact::REDACTED;
+    use std::io::Read;
+
+    const API_KEY: &str = "sk_live_abcdef123456";
+
+    fn state() -> CopierState {
+        let config: CopierConfig = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": "h",
+            "master": {"account_id": "A", "account_number": "51234567", "broker": "B", "terminal_id": "T-A"},
+            "receivers": []
+        }))
+        .unwrap();
+        CopierState {
+            api_key: Some(API_KEY.to_string()),
+            config: Some(config),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_archive_has_expected_entries_and_no_secrets() {
+        let dir = std::env::temp_dir().join(format!("saturn_diag_test_{}", uuid::Uuid::new_v4()));
+        let log_dir = dir.join("logs");
+        fs::create_dir_all(&log_dir).unwrap();
+        fs::write(
+            log_dir.join("saturn-copier.log.2024-03-10"),
+            format!("INFO validating key {} for master 51234567\n", API_KEY),
+        )
+        .unwrap();
+        let path = dir.join("diagnostics.zip");
+
+        let manifest = collect_from(&path, &state(), &log_dir, &[API_KEY]).unwrap();
+        assert!(manifest.entries.contains(&"logs/saturn-copier.log.2024-03-10".to_string()));
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "config.json",
+                "health.json",
+                "logs/saturn-copier.log.2024-03-10",
+                "manifest.json",
+                "queue.json",
+                "safety_state.json",
+            ]
+        );
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            assert!(!text.contains(API_KEY), "{} leaks the API key", entry.name());
+            assert!(!text.contains("51234567"), "{} leaks an account number", entry.name());
+        }
+
+        let mut log = String::new();
+        archive.by_name("logs/saturn-copier.log.2024-03-10").unwrap().read_to_string(&mut log).unwrap();
+        assert!(log.contains(REDACTED) && log.contains("*****567"));
+        let mut config = String::new();
+        archive.by_name("config.json").unwrap().read_to_string(&mut config).unwrap();
+        assert!(config.contains("*****567"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,274 @@
+//! Per-receiver equity curve
+//!
+//! The background loop samples every receiver's live equity (from its
+//! heartbeat) with its balance and the day's P&L, so dashboards and
+//! prop-firm reports can plot a time series rather than just the current
+//! value. One sample is taken per `SAMPLE_INTERVAL`, and samples older than
+//! `RETENTION` are dropped. Each receiver's curve is a JSON-lines file under
+//! `equity_curves/`: new samples are appended, and the file is compacted
+//! when it is loaded.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::warn;
+
+use super::{live_balance, safety, CopierConfig};
+use crate::data_dir::app_data_dir;
+
+const CURVES_FOLDER: &str = "equity_curves";
+
+/// Samples are downsampled to one per this interval
+const SAMPLE_INTERVAL: Duration = Duration::minutes(5);
+
+/// How far back samples are kept
+const RETENTION: Duration = Duration::days(90);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EquitySample {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+    pub balance: f64,
+    pub daily_pnl: f64,
+}
+
+/// Index of the `SAMPLE_INTERVAL` a time falls in
+fn interval_of(timestamp: DateTime<Utc>) -> i64 {
+    timestamp.timestamp().div_euclid(SAMPLE_INTERVAL.num_seconds())
+}
+
+/// One receiver's samples, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EquityCurve {
+    samples: Vec<EquitySample>,
+}
+
+impl EquityCurve {
+    /// Whether a sample taken at `now` would start a new `SAMPLE_INTERVAL`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.samples.last().is_none_or(|last| interval_of(now) > interval_of(last.timestamp))
+    }
+
+    /// Add a sample if it starts a new `SAMPLE_INTERVAL`, dropping samples
+    /// past `RETENTION`. Returns whether it was added.
+    pub fn record(&mut self, sample: EquitySample) -> bool {
+        if !self.is_due(sample.timestamp) {
+            return false;
+        }
+        self.samples.push(sample);
+        let oldest_kept = sample.timestamp - RETENTION;
+        self.samples.retain(|s| s.timestamp >= oldest_kept);
+        true
+    }
+
+    /// Samples from `from` to `to`, inclusive. Open ends are unbounded.
+    pub fn range(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Vec<EquitySample> {
+        self.samples
+            .iter()
+            .filter(|s| from.is_none_or(|from| s.timestamp >= from) && to.is_none_or(|to| s.timestamp <= to))
+            .copied()
+            .collect()
+    }
+}
+
+fn get_curve_path(receiver_id: &str) -> Option<PathBuf> {
+    let file_name: String = receiver_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    Some(app_data_dir()?.join(CURVES_FOLDER).join(format!("{}.jsonl", file_name)))
+}
+
+/// Read a curve file, one sample per line. Lines that don't parse (a
+/// final line cut off mid-append) are skipped. The file is rewritten when
+/// samples were skipped or aged out.
+fn load_from(path: &Path) -> EquityCurve {
+    let Ok(content) = fs::read_to_string(path) else {
+        return EquityCurve::default();
+    };
+    let mut curve = EquityCurve::default();
+    let mut lines = 0;
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        lines += 1;
+        match serde_json::from_str::<EquitySample>(line) {
+            Ok(sample) => {
+                curve.record(sample);
+            }
+            Err(e) => warn!("Skipping bad equity curve line in {:?}: {}", path, e),
+        }
+    }
+    if curve.samples.len() != lines {
+        if let Err(e) = rewrite(path, &curve) {
+            warn!("Failed to compact equity curve {:?}: {}", path, e);
+        }
+    }
+    curve
+}
+
+fn to_lines(samples: &[EquitySample]) -> Result<String, String> {
+    samples
+        .iter()
+        .map(|sample| serde_json::to_string(sample).map(|line| line + "\n").map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Replace a curve file with `curve`'s samples (atomic write)
+fn rewrite(path: &Path, curve: &EquityCurve) -> Result<(), String> {
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, to_lines(&curve.samples)?).map_err(|e| format!("Failed to write equity curve: {}", e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to save equity curve: {}", e))
+}
+
+/// Append one sample to a curve file
+fn append_to(path: &Path, sample: &EquitySample) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open equity curve: {}", e))?;
+    file.write_all(to_lines(std::slice::from_ref(sample))?.as_bytes())
+        .map_err(|e| format!("Failed to append to equity curve: {}", e))
+}
+
+fn load(receiver_id: &str) -> EquityCurve {
+    get_curve_path(receiver_id).map(|path| load_from(&path)).unwrap_or_default()
+}
+
+/// Curves loaded so far, by receiver id
+static CURVES: LazyLock<Mutex<HashMap<String, EquityCurve>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Add a sample to a receiver's curve and append it to its file, unless
+/// one was already taken in this `SAMPLE_INTERVAL`
+pub fn record(receiver_id: &str, sample: EquitySample) {
+    let mut curves = CURVES.lock();
+    let curve = curves.entry(receiver_id.to_string()).or_insert_with(|| load(receiver_id));
+    if !curve.record(sample) {
+        return;
+    }
+    if let Some(path) = get_curve_path(receiver_id) {
+        if let Err(e) = append_to(&path, &sample) {
+            warn!("Failed to persist equity curve for {}: {}", receiver_id, e);
+        }
+    }
+}
+
+/// Whether a receiver's curve takes a sample at `now`
+fn sample_due(receiver_id: &str, now: DateTime<Utc>) -> bool {
+    CURVES
+        .lock()
+        .entry(receiver_id.to_string())
+        .or_insert_with(|| load(receiver_id))
+        .is_due(now)
+}
+
+/// Sample each configured receiver's live equity once per
+/// `SAMPLE_INTERVAL`. Receivers without a fresh heartbeat are skipped.
+pub fn sample_receivers(config: &CopierConfig) {
+    let now = Utc::now();
+    for receiver in &config.receivers {
+        if !sample_due(&receiver.account_number, now) {
+            continue;
+        }
+        let Some(heartbeat) = live_balance::live_receiver_heartbeat(&receiver.terminal_id) else {
+            continue;
+        };
+        record(
+            &receiver.account_number,
+            EquitySample {
+                timestamp: now,
+                equity: heartbeat.equity,
+                balance: heartbeat.balance,
+                daily_pnl: safety::get_receiver_state(&receiver.account_number).daily_pnl,
+            },
+        );
+    }
+}
+
+/// A receiver's samples from `from` to `to`
+pub fn equity_curve(receiver_id: &str, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Vec<EquitySample> {
+    let mut curves = CURVES.lock();
+    curves
+        .entry(receiver_id.to_string())
+        .or_insert_with(|| load(receiver_id))
+        .range(from, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: DateTime<Utc>, equity: f64) -> EquitySample {
+        EquitySample {
+            timestamp,
+            equity,
+            balance: 10_000.0,
+            daily_pnl: equity - 10_000.0,
+        }
+    }
+
+    #[test]
+    fn test_samples_are_recorded_and_queryable_by_range() {
+        let start = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let mut curve = EquityCurve::default();
+        for (minutes, equity) in [(0, 10_000.0), (10, 10_050.0), (20, 10_120.0), (30, 9_980.0), (40, 10_010.0)] {
+            curve.record(sample(at(minutes), equity));
+        }
+
+        assert_eq!(curve.range(None, None).len(), 5);
+        let middle = curve.range(Some(at(10)), Some(at(30)));
+        assert_eq!(middle.iter().map(|s| s.equity).collect::<Vec<_>>(), vec![10_050.0, 10_120.0, 9_980.0]);
+        assert_eq!(middle[2].daily_pnl, -20.0);
+        assert_eq!(curve.range(Some(at(35)), None).len(), 1);
+        assert!(curve.range(Some(at(50)), None).is_empty());
+    }
+
+    #[test]
+    fn test_close_samples_are_downsampled_and_old_ones_dropped() {
+        let start = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        let mut curve = EquityCurve::default();
+        for (minutes, equity) in [(0, 10_000.0), (1, 10_010.0), (4, 10_020.0), (5, 10_030.0), (7, 10_040.0)] {
+            curve.record(sample(start + Duration::minutes(minutes), equity));
+        }
+
+        // One sample per 5 minutes, the first of each
+        let samples = curve.range(None, None);
+        assert_eq!(samples.iter().map(|s| s.equity).collect::<Vec<_>>(), vec![10_000.0, 10_030.0]);
+        assert!(!curve.is_due(start + Duration::minutes(9)));
+        assert!(curve.is_due(start + Duration::minutes(10)));
+
+        curve.record(sample(start + RETENTION + Duration::minutes(4), 10_500.0));
+        let samples = curve.range(None, None);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].equity, 10_030.0);
+    }
+
+    #[test]
+    fn test_curve_file_is_appended_and_compacted_on_load() {
+        let dir = std::env::temp_dir().join(format!("saturn_equity_curve_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("acc.jsonl");
+        let start = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        for minutes in [0, 5, 10] {
+            append_to(&path, &sample(start + Duration::minutes(minutes), 10_000.0 + minutes as f64)).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+        assert_eq!(load_from(&path).range(None, None).len(), 3);
+
+        // A line cut off mid-append is dropped from the file on load
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"timestamp":"2024-03-10T12:15"#).unwrap();
+        drop(file);
+        assert_eq!(load_from(&path).range(None, None).len(), 3);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
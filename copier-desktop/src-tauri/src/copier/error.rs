@@ -0,0 +1,58 @@
+//! Structured errors for discovery, symbol catalog and position sync.
+//!
+//! Each variant carries the same human-readable message these modules used to
+//! return as a bare `String`, so `Display` output is unchanged. The frontend
+//! can branch on `kind` via the serialized form: `{"kind": "NotFound", "message": "..."}`.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", content = "message")]
+pub enum CopierError {
+    /// File, folder or terminal doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    /// File exists but its contents couldn't be parsed
+    #[error("{0}")]
+    ParseError(String),
+    /// Any other I/O failure
+    #[error("{0}")]
+    Io(String),
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// The EA hasn't produced a file it's expected to write
+    #[error("{0}")]
+    EaNotAttached(String),
+}
+
+impl CopierError {
+    /// Classify an I/O error, prefixing it with `context` like the old
+    /// `format!("{context}: {e}")` messages.
+    pub fn io(context: &str, err: std::io::Error) -> Self {
+        let message = format!("{}: {}", context, err);
+        match err.kind() {
+            std::io::ErrorKind::NotFound => CopierError::NotFound(message),
+            std::io::ErrorKind::PermissionDenied => CopierError::PermissionDenied(message),
+            _ => CopierError::Io(message),
+        }
+    }
+
+    pub fn parse(context: &str, err: impl std::fmt::Display) -> Self {
+        CopierError::ParseError(format!("{}: {}", context, err))
+    }
+
+    /// A terminal folder the app can't create files in
+    pub fn not_writable(path: &std::path::Path) -> Self {
+        CopierError::PermissionDenied(format!(
+            "Folder not writable: {} - try running MT5/the app as administrator, or use a portable data dir",
+            path.display()
+        ))
+    }
+}
+
+/// Lets `?` keep working in callers that still return `Result<_, String>`
+impl From<CopierError> for String {
+    fn from(err: CopierError) -> Self {
+        err.to_string()
+    }
+}
@@ -2,58 +2,485 @@
 //! 
 //! Processes trade events from the Master EA and executes them on receivers
 
+use chrono::Utc;
 use parking_lot::Mutex;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
-use super::{lot_calculator, safety, symbol_catalog, trade_executor, CopierConfig, CopierState, Execution, TradeEvent};
+use super::execution_queue::{ExecutionQueue, QueueStatus, QueuedExecution, EXECUTION_QUEUE};
+use super::position_sync::{self, ReceiverPosition, SyncCommand};
+use super::{alerts, approvals, clock_skew, commanded_levels, currency, file_lock, file_watcher, global_cap, idempotency, journal, kill_switch, latency, live_balance, lot_calculator, market_hours, receiver_stats, recovery, safety, symbol_catalog, ticks, trade_executor, CopierConfig, CopierState, CopyMode, Execution, ExecutionStrategy, ReceiverConfig, SignalDebounce, SltpPolicy, TradeEvent};
 use crate::sync::executions as exec_sync;
 
 /// R9: Clamp raw computed lots to the receiver broker's real specs from the
 /// symbol catalog (min_lot, max_lot, lot_step). Returns the input unchanged
 /// when the catalog or symbol is not yet available — the receiver EA still
 /// performs a final safety clamp using live `SymbolInfoDouble` values.
-fn clamp_to_broker_specs(terminal_id: &str, symbol: &str, raw_lots: f64) -> f64 {
+fn clamp_to_broker_specs(receiver: &ReceiverConfig, symbol: &str, raw_lots: f64) -> symbol_catalog::LotCalcResult {
+    let terminal_id = receiver.terminal_id.as_str();
     match symbol_catalog::fetch_symbol_catalog(terminal_id) {
         Ok(catalog) => {
             if let Some(spec) = catalog.symbols.iter().find(|s| s.name == symbol) {
-                let clamped = symbol_catalog::clamp_lots(raw_lots, spec);
-                if (clamped - raw_lots).abs() > f64::EPSILON {
+                let clamped = symbol_catalog::clamp_lots_with(raw_lots, spec, receiver.lot_rounding);
+                if (clamped.lots - raw_lots).abs() > f64::EPSILON {
                     debug!(
                         "Clamped lots for {} on {}: {} -> {} (min={}, max={}, step={})",
-                        symbol, terminal_id, raw_lots, clamped,
+                        symbol, terminal_id, raw_lots, clamped.lots,
                         spec.min_lot, spec.max_lot, spec.lot_step
                     );
                 }
                 clamped
             } else {
                 debug!("No catalog entry for {} on {}, EA will clamp", symbol, terminal_id);
-                raw_lots
+                symbol_catalog::LotCalcResult::unchanged(raw_lots)
             }
         }
         Err(e) => {
             debug!("Symbol catalog unavailable for {}: {} — EA will clamp", terminal_id, e);
-            raw_lots
+            symbol_catalog::LotCalcResult::unchanged(raw_lots)
         }
     }
 }
 
 
-/// Single discovery cache (10s TTL in `mt5::discovery`) — this used to wrap
-/// another 30s cache layer which could double-stale entries.
+/// Single discovery cache in `mt5::discovery`, at the background max age —
+/// this used to wrap another 30s cache layer which could double-stale entries.
 pub fn get_cached_terminals() -> Vec<crate::mt5::bridge::Mt5Terminal> {
-    crate::mt5::discovery::discover_all_terminals_cached(false)
+    crate::mt5::discovery::discover_all_terminals_cached(crate::mt5::discovery::BACKGROUND_CACHE_MAX_AGE)
         .into_iter()
         .filter_map(crate::mt5::bridge::Mt5Terminal::from_terminal_info)
         .collect()
 }
 
-/// Process a trade event from the master EA
+/// Desktop-side safety limits for a receiver.
+///
+/// `config_generator::SafetyConfig` (the EA wire format) carries
+/// several fields that have NO counterpart on the runtime
+/// `ReceiverConfig` that we receive here: `max_daily_loss_r`,
+/// `poll_interval_ms`, `max_drawdown_percent`,
+/// `trailing_drawdown_enabled`, `min_equity`. Those are consumed
+/// exclusively by the receiver EA, which enforces them itself.
+/// (`manual_confirm_mode` is on `ReceiverConfig` and handled by
+/// `approvals`, not here.)
+///
+/// The desktop-side guard intentionally uses only what flows through
+/// `ReceiverConfig` plus `SafetyConfig::default()` (3% daily loss
+/// fallback). Threading the remaining fields through requires a
+/// coordinated change to the JSON config schema and the copier-config
+/// edge function — tracked separately.
+pub(crate) fn receiver_safety_config(receiver: &ReceiverConfig) -> safety::SafetyConfig {
+    safety::SafetyConfig {
+        max_slippage_pips: receiver.max_slippage_pips,
+        prop_firm_safe_mode: receiver.prop_firm_safe_mode,
+        profit_target_amount: receiver.profit_target_amount,
+        profit_target_percent: receiver.profit_target_percent,
+        block_closes_when_paused: receiver.block_closes_when_paused,
+        auto_resume_after_secs: receiver.auto_resume_after_secs,
+        auto_resume_min_equity: receiver.auto_resume_min_equity,
+        ..Default::default()
+    }
+}
+
+/// Safety gate for one event. Opening events face every limit; closes and
+/// SL/TP changes only reduce or adjust exposure, so a paused receiver still
+/// takes them unless it's set to block them or the change `loosens_stops`.
+fn event_safety(
+    event: &TradeEvent,
+    account_number: &str,
+    safety_config: &safety::SafetyConfig,
+    starting_balance: f64,
+    loosens_stops: bool,
+) -> safety::SafetyCheckResult {
+    if is_opening_event_type(&event.event_type) {
+        safety::check_trade_safety(account_number, safety_config, starting_balance)
+    } else {
+        safety::check_exit_safety(account_number, safety_config, loosens_stops)
+    }
+}
+
+/// Whether a modify would loosen the SL/TP of any receiver position copied
+/// from its master position. Unreadable positions count as loosening, so a
+/// paused receiver fails closed.
+fn modify_loosens_stops(event: &TradeEvent, receiver: &ReceiverConfig) -> bool {
+    let position_id = position_id_of(event);
+    match position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number) {
+        Ok(positions) => positions
+            .iter()
+            .filter(|p| p.master_position_id == position_id)
+            .any(|p| safety::loosens_stops(&p.direction, (p.sl, p.tp), (event.sl, event.tp))),
+        Err(e) => {
+            warn!("Couldn't read positions on {} to check an SL/TP change: {}", receiver.account_number, e);
+            true
+        }
+    }
+}
+
+/// Flatten a receiver that just reached its profit target. The safety
+/// lock already blocks new trades, so a failed command is only alerted.
+fn close_all_for_profit_target(receiver: &ReceiverConfig, reason: String) {
+    info!("Closing all positions on {}: {}", receiver.account_number, reason);
+    if let Err(e) = super::commands::close_all_positions(std::slice::from_ref(&receiver.terminal_id), Some(reason)) {
+        error!("Failed to close positions on {} after profit target: {}", receiver.account_number, e);
+        alerts::push_alert(
+            alerts::AlertSeverity::Critical,
+            format!("Profit target reached on {} but close all failed: {}", receiver.account_number, e),
+        );
+    }
+}
+
+/// Why copying to this receiver is refused because its EA is too old, if it
+/// is (see `discovery::MIN_SUPPORTED_RECEIVER_EA_VERSION`)
+pub(crate) fn outdated_ea_reason(receiver: &ReceiverConfig) -> Option<String> {
+    if receiver.allow_outdated_ea {
+        return None;
+    }
+    let terminal = crate::mt5::discovery::discover_all_terminals_cached(crate::mt5::discovery::BACKGROUND_CACHE_MAX_AGE)
+        .into_iter()
+        .find(|t| t.terminal_id == receiver.terminal_id && t.ea_outdated)?;
+    Some(format!(
+        "Receiver EA {} is older than the minimum supported {} - reinstall the EA",
+        terminal.ea_version.as_deref().unwrap_or("(unknown version)"),
+        crate::mt5::discovery::MIN_SUPPORTED_RECEIVER_EA_VERSION
+    ))
+}
+
+/// Parse an event timestamp: RFC 3339, or the same without an offset
+/// (taken as UTC), or MQL's `yyyy.mm.dd hh:mm:ss`
+pub(crate) fn parse_event_timestamp(timestamp: &str) -> Option<chrono::DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y.%m.%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(timestamp, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Whether an event opens new exposure: a market entry or a pending order
+pub(crate) fn is_opening_event(event: &TradeEvent) -> bool {
+    is_opening_event_type(&event.event_type)
+}
+
+fn is_opening_event_type(event_type: &str) -> bool {
+    matches!(event_type, "entry" | "pending_order")
+}
+
+/// Why an entry is too old to copy for this receiver, if it is. Only
+/// opening events are filtered; an unparseable timestamp is let through.
+fn stale_entry_reason(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: chrono::DateTime<Utc>,
+    master_skew_secs: Option<i64>,
+) -> Option<String> {
+    if !is_opening_event(event) {
+        return None;
+    }
+    let max_age = receiver.max_event_age_secs?;
+    let Some(opened) = parse_event_timestamp(&event.timestamp) else {
+        debug!("Unparseable event timestamp {:?}, not age-filtering", event.timestamp);
+        return None;
+    };
+    // A master clock running ahead makes events look younger than they are
+    let age = now.signed_duration_since(opened).num_seconds() + master_skew_secs.unwrap_or(0);
+    (age > max_age as i64).then(|| format!("Entry is {}s old (limit {}s)", age, max_age))
+}
+
+/// Why an entry is refused because the receiver's price already moved more
+/// than `limit_pips` from the master's entry. No fresh tick means no check.
+fn entry_deviation_reason(event: &TradeEvent, limit_pips: f64, tick: Option<&ticks::SymbolTick>) -> Option<String> {
+    let deviation = ticks::entry_deviation_pips(&event.direction, event.price, tick?);
+    (deviation > limit_pips).then(|| {
+        format!(
+            "Receiver price is {:.1} pips from master entry {} (limit {} pips)",
+            deviation, event.price, limit_pips
+        )
+    })
+}
+
+/// Why an entry costs too much right now: the receiver's spread plus its
+/// expected slippage is over `limit_pips`. No fresh tick means no check.
+fn entry_cost_reason(limit_pips: f64, tick: Option<&ticks::SymbolTick>, expected_slippage: Option<f64>) -> Option<String> {
+    let spread = ticks::spread_pips(tick?);
+    let slippage = expected_slippage.unwrap_or(0.0).max(0.0);
+    let cost = spread + slippage;
+    (cost > limit_pips).then(|| {
+        format!(
+            "Entry cost {:.1} pips (spread {:.1} + expected slippage {:.1}) is over the {} pip limit",
+            cost, spread, slippage, limit_pips
+        )
+    })
+}
+
+/// `entry_cost_reason` for an entry on `receiver` from its live tick and
+/// fill stats (None for other events or without `max_entry_cost_pips`)
+fn live_entry_cost_reason(event: &TradeEvent, receiver: &ReceiverConfig) -> Option<String> {
+    let limit = receiver.max_entry_cost_pips.filter(|_| event.event_type == "entry")?;
+    let tick = ticks::latest_tick(&receiver.terminal_id, &map_symbol(receiver, &event.symbol));
+    entry_cost_reason(limit, tick.as_ref(), receiver_stats::expected_slippage_pips(&receiver.account_number))
+}
+
+/// Token bucket for one receiver's entry rate limit.
+///
+/// Holds up to `capacity` tokens and refills continuously at
+/// `capacity` per minute, so a burst up to the limit goes through at once
+/// and further entries are spaced out evenly.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn per_minute(max_per_minute: u32, now: Instant) -> Self {
+        let capacity = max_per_minute.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: now,
+        }
+    }
+
+    /// Switch to a new limit, keeping the tokens left (up to the new capacity)
+    pub fn set_limit(&mut self, max_per_minute: u32) {
+        self.capacity = max_per_minute.max(1) as f64;
+        self.tokens = self.tokens.min(self.capacity);
+        self.refill_per_sec = self.capacity / 60.0;
+    }
+
+    fn limit(&self) -> u32 {
+        self.capacity as u32
+    }
+
+    /// Take one token, or return how long until one is available
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec))
+        }
+    }
+}
+
+/// Per-receiver entry throttles, keyed by receiver terminal id
+static ENTRY_THROTTLES: LazyLock<Mutex<HashMap<String, TokenBucket>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether the entry throttle let an event through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Admitted,
+    Deferred,
+}
+
+/// Outcome of processing one event for one receiver
+#[derive(Debug, Clone, PartialEq)]
+enum ReceiverOutcome {
+    Executed,
+    Blocked(String),
+    Failed(String),
+}
+
+/// What `process_event` did with an event for one receiver
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiverResult {
+    pub receiver_account: String,
+    /// "executed", "blocked", "failed", "halted" (kill switch), "disabled",
+    /// "aggregate", "sampled_out", "awaiting_approval", "deferred" or
+    /// "open_not_completed"
+    pub outcome: String,
+    /// Why it wasn't executed; the approval id for "awaiting_approval"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ReceiverResult {
+    fn new(receiver: &ReceiverConfig, outcome: &str, reason: Option<String>) -> Self {
+        Self {
+            receiver_account: receiver.account_number.clone(),
+            outcome: outcome.to_string(),
+            reason,
+        }
+    }
+
+    fn from_outcome(receiver: &ReceiverConfig, outcome: ReceiverOutcome) -> Self {
+        match outcome {
+            ReceiverOutcome::Executed => Self::new(receiver, "executed", None),
+            ReceiverOutcome::Blocked(reason) => Self::new(receiver, "blocked", Some(reason)),
+            ReceiverOutcome::Failed(e) => Self::new(receiver, "failed", Some(e)),
+        }
+    }
+}
+
+/// Reason recorded on executions deferred by the entry throttle
+const THROTTLE_REASON: &str = "rate limited";
+
+/// Take a token from the receiver's entry bucket. Only opening events are
+/// throttled — closes, partial closes, modifies and pending order cancels
+/// always go through so risk can be reduced regardless of the limit. A
+/// changed `max_entries_per_minute` applies to the existing bucket.
+fn check_entry_throttle(
+    throttles: &mut HashMap<String, TokenBucket>,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: Instant,
+) -> Result<(), Duration> {
+    if !is_opening_event(event) {
+        return Ok(());
+    }
+    let Some(limit) = receiver.max_entries_per_minute else {
+        throttles.remove(&receiver.terminal_id);
+        return Ok(());
+    };
+
+    let bucket = throttles
+        .entry(receiver.terminal_id.clone())
+        .or_insert_with(|| TokenBucket::per_minute(limit, now));
+    if bucket.limit() != limit.max(1) {
+        bucket.set_limit(limit);
+    }
+    bucket.try_take(now)
+}
+
+/// Run the entry throttle and, when the bucket is empty, defer the entry
+/// into `queue` for the worker to retry once a token is available.
+pub fn throttle_entry(
+    throttles: &mut HashMap<String, TokenBucket>,
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: Instant,
+) -> Admission {
+    match check_entry_throttle(throttles, event, receiver, now) {
+        Ok(()) => Admission::Admitted,
+        Err(wait) => {
+            let until = Utc::now() + wait;
+            warn!(
+                "Entry {} {} for {} rate limited ({}/min), deferred until {}",
+                event.direction,
+                event.symbol,
+                receiver.account_number,
+                receiver.max_entries_per_minute.unwrap_or(0),
+                until.to_rfc3339()
+            );
+            let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+            queue.defer(exec, until, THROTTLE_REASON);
+            Admission::Deferred
+        }
+    }
+}
+
+/// Reason recorded on entries held back by `min_signal_interval_secs`
+const DEBOUNCE_REASON: &str = "debounced";
+
+/// When the last entry went through, keyed by (receiver terminal id, master
+/// symbol)
+static LAST_ENTRIES: LazyLock<Mutex<HashMap<(String, String), Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Check an entry against the receiver's `min_signal_interval_secs` for its
+/// symbol, or return how long until the interval has passed. An entry that
+/// passes becomes the symbol's last entry. Closes, partial closes, modifies
+/// and cancels always pass.
+fn check_signal_interval(
+    last_entries: &mut HashMap<(String, String), Instant>,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: Instant,
+) -> Result<(), Duration> {
+    if !is_opening_event(event) {
+        return Ok(());
+    }
+    let Some(interval) = receiver.min_signal_interval_secs.map(Duration::from_secs) else {
+        return Ok(());
+    };
+
+    let key = (receiver.terminal_id.clone(), event.symbol.clone());
+    if let Some(last) = last_entries.get(&key) {
+        let since_last = now.saturating_duration_since(*last);
+        if since_last < interval {
+            return Err(interval - since_last);
+        }
+    }
+    last_entries.insert(key, now);
+    Ok(())
+}
+
+/// An entry `debounce_entry` held back
+#[derive(Debug, Clone, PartialEq)]
+enum Debounced {
+    Dropped(String),
+    Deferred,
+}
+
+/// Run the signal interval check and, for an entry too soon after the last
+/// one on its symbol, drop it or defer it into `queue` per the receiver's
+/// `signal_debounce`
+fn debounce_entry(
+    last_entries: &mut HashMap<(String, String), Instant>,
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: Instant,
+) -> Option<Debounced> {
+    let wait = check_signal_interval(last_entries, event, receiver, now).err()?;
+    let reason = format!(
+        "Entry on {} within {}s of the previous one",
+        event.symbol,
+        receiver.min_signal_interval_secs.unwrap_or(0)
+    );
+    match receiver.signal_debounce {
+        SignalDebounce::Drop => Some(Debounced::Dropped(reason)),
+        SignalDebounce::Defer => {
+            let until = Utc::now() + wait;
+            info!("{} for {}, deferred until {}", reason, receiver.account_number, until.to_rfc3339());
+            let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+            queue.defer(exec, until, DEBOUNCE_REASON);
+            Some(Debounced::Deferred)
+        }
+    }
+}
+
+/// Canonical idempotency key — prefer EA-supplied, else build it.
+fn idempotency_key(event: &TradeEvent) -> String {
+    event.idempotency_key.clone().unwrap_or_else(|| {
+        let term = event.terminal_id.clone().unwrap_or_else(|| "unknown".into());
+        crate::copier::idempotency::build_canonical_key(&term, event.idempotency_id(), &event.event_type)
+    })
+}
+
+/// Process a trade event from the master EA. Returns what happened on each
+/// configured receiver, in config order; the executions are also recorded
+/// in `CopierState` as before.
 /// 
-/// NOTE (m1): Config is passed by reference and is only loaded at startup or on explicit reload.
-/// Config changes require EA restart, so there's no race condition risk during event processing.
-pub fn process_event(event: &TradeEvent, config: &CopierConfig, state: Arc<Mutex<CopierState>>) {
+/// NOTE (m1): `config` is the snapshot taken for this event; a config saved
+/// meanwhile applies from the next event. Per-receiver state kept between
+/// events (entry throttles) picks up changed limits when next used.
+pub fn process_event(event: &TradeEvent, config: &CopierConfig, state: Arc<Mutex<CopierState>>) -> Vec<ReceiverResult> {
+    process_event_journaled(event, config, state, &journal::JOURNAL)
+}
+
+fn process_event_journaled(
+    event: &TradeEvent,
+    config: &CopierConfig,
+    state: Arc<Mutex<CopierState>>,
+    journal: &journal::Journal,
+) -> Vec<ReceiverResult> {
     info!(
         "Processing {} event for {} {} @ {} (ticket: {})",
         event.event_type,
@@ -63,181 +490,1476 @@ pub fn process_event(event: &TradeEvent, config: &CopierConfig, state: Arc<Mutex
         event.ticket
     );
 
-    // Build symbol info from event if available, detecting symbol type
-    let symbol_info = if event.tick_value.is_some() {
-        let symbol_type = lot_calculator::SymbolInfo::detect_symbol_type(&event.symbol);
-        Some(lot_calculator::SymbolInfo {
-            tick_value: event.tick_value.unwrap_or(10.0),
-            tick_size: event.point.unwrap_or(0.00001),
-            contract_size: event.contract_size.unwrap_or(100000.0),
-            digits: event.digits.unwrap_or(5),
-            point: event.point.unwrap_or(0.00001),
-            symbol_type,
-        })
-    } else {
-        None
-    };
+    if kill_switch::blocks(&state) {
+        journal.record(event, &config.master.account_id, Vec::new());
+        return config
+            .receivers
+            .iter()
+            .map(|r| ReceiverResult::new(r, "halted", Some("Kill switch engaged".to_string())))
+            .collect();
+    }
 
+    // Throttling is decided up front, in config order; only the admitted
+    // receivers are fanned out. Their slots in `results` are filled in by
+    // the dispatch.
+    let mut admitted = Vec::new();
+    let mut results = Vec::with_capacity(config.receivers.len());
     for receiver in &config.receivers {
-        // Check safety limits before processing.
-        //
-        // `config_generator::SafetyConfig` (the EA wire format) carries
-        // several fields that have NO counterpart on the runtime
-        // `ReceiverConfig` that we receive here: `max_daily_loss_r`,
-        // `manual_confirm_mode`, `poll_interval_ms`, `max_drawdown_percent`,
-        // `trailing_drawdown_enabled`, `min_equity`. Those are consumed
-        // exclusively by the receiver EA, which enforces them itself.
-        //
-        // The desktop-side guard intentionally uses only what flows through
-        // `ReceiverConfig` plus `SafetyConfig::default()` (3% daily loss
-        // fallback). Threading the remaining fields through requires a
-        // coordinated change to the JSON config schema and the copier-config
-        // edge function — tracked separately.
-        let safety_config = safety::SafetyConfig {
-            max_slippage_pips: receiver.max_slippage_pips,
-            prop_firm_safe_mode: receiver.prop_firm_safe_mode,
-            ..Default::default()
-        };
+        if let Some(reason) = disabled_reason(event, receiver) {
+            info!("Skipping entry for {}: {}", receiver.account_number, reason);
+            record_skipped_execution(event, receiver, "disabled", reason, state.clone());
+            results.push(Some(ReceiverResult::new(receiver, "disabled", Some(reason.to_string()))));
+            continue;
+        }
 
-        
-        // Get receiver account info from cached state (would be updated from heartbeat)
-        let receiver_account = get_cached_account_info(&receiver.terminal_id);
-        let starting_balance = receiver_account.as_ref().map(|a| a.balance).unwrap_or(10000.0);
-        
-        match safety::check_trade_safety(&receiver.account_number, &safety_config, starting_balance) {
-            safety::SafetyCheckResult::Blocked(reason) => {
-                warn!("Trade blocked for {}: {}", receiver.account_number, reason);
-                record_blocked_execution(event, receiver, &reason, state.clone());
+        // Net positions are synced on a timer, not per trade
+        if receiver.aggregate_mode {
+            debug!("Skipping {} for {}: {}", event.event_type, receiver.account_number, AGGREGATE_REASON);
+            results.push(Some(ReceiverResult::new(receiver, AGGREGATE_STATUS, Some(AGGREGATE_REASON.to_string()))));
+            continue;
+        }
+
+        if let Some(reason) = copy_mode_reason(event, receiver) {
+            info!("Skipping {} for {}: {}", event.event_type, receiver.account_number, reason);
+            record_skipped_execution(event, receiver, COPY_MODE_STATUS, reason, state.clone());
+            results.push(Some(ReceiverResult::new(receiver, COPY_MODE_STATUS, Some(reason.to_string()))));
+            continue;
+        }
+
+        if is_sampled_out(event, receiver) {
+            if is_opening_event(event) {
+                info!("Skipping entry for {}: {}", receiver.account_number, SAMPLED_OUT_REASON);
+                record_skipped_execution(event, receiver, "sampled_out", SAMPLED_OUT_REASON, state.clone());
+            } else {
+                // The open was never copied, so there is nothing to close or modify
+                debug!(
+                    "Ignoring {} for sampled-out position {} on {}",
+                    event.event_type, event.ticket, receiver.account_number
+                );
+            }
+            results.push(Some(ReceiverResult::new(receiver, "sampled_out", Some(SAMPLED_OUT_REASON.to_string()))));
+            continue;
+        }
+
+        if let Some(reason) = EXECUTION_QUEUE.update(|queue| open_failed_reason(queue, event, &receiver.terminal_id)) {
+            info!("Skipping {} for {}: {}", event.event_type, receiver.account_number, reason);
+            record_skipped_execution(event, receiver, OPEN_NOT_COMPLETED, &reason, state.clone());
+            results.push(Some(ReceiverResult::new(receiver, OPEN_NOT_COMPLETED, Some(reason))));
+            continue;
+        }
+
+        let debounced = EXECUTION_QUEUE
+            .update(|queue| debounce_entry(&mut LAST_ENTRIES.lock(), queue, event, receiver, Instant::now()));
+        match debounced {
+            Some(Debounced::Dropped(reason)) => {
+                info!("Skipping entry for {}: {}", receiver.account_number, reason);
+                record_skipped_execution(event, receiver, DEBOUNCE_REASON, &reason, state.clone());
+                results.push(Some(ReceiverResult::new(receiver, DEBOUNCE_REASON, Some(reason))));
+                continue;
+            }
+            Some(Debounced::Deferred) => {
+                persist_queue();
+                results.push(Some(ReceiverResult::new(receiver, "deferred", Some(DEBOUNCE_REASON.to_string()))));
                 continue;
             }
-            safety::SafetyCheckResult::Warning(warning) => {
-                warn!("Safety warning for {}: {}", receiver.account_number, warning);
-                // Continue with trade but log warning
+            None => {}
+        }
+
+        if approvals::needs_approval(event, receiver) {
+            let approval = approvals::request_approval(event, receiver);
+            results.push(Some(ReceiverResult::new(receiver, "awaiting_approval", Some(approval.id))));
+            continue;
+        }
+
+        match admit(event, receiver) {
+            Admission::Admitted => {
+                admitted.push(receiver);
+                results.push(None);
             }
-            safety::SafetyCheckResult::Allowed => {
-                // Continue normally
+            Admission::Deferred => {
+                persist_queue();
+                results.push(Some(ReceiverResult::new(receiver, "deferred", None)));
             }
         }
-        
-        // Find symbol mapping
-        let mapped_symbol = receiver
-            .symbol_mappings
-            .iter()
-            .find(|m| m.master_symbol == event.symbol && m.is_enabled)
-            .map(|m| m.receiver_symbol.clone())
-            .unwrap_or_else(|| event.symbol.clone());
-
-        // Calculate lot size using the improved calculator
-        let raw_lots = lot_calculator::calculate_lots(
-            &receiver.risk_mode,
-            receiver.risk_value,
-            event.lots,
-            event.price,
-            event.sl,
-            event.master_balance,
-            receiver_account.as_ref(),
-            symbol_info.as_ref(),
-        );
+    }
 
-        // R9: clamp to the receiver broker's real min/max/step from the
-        // symbol catalog when available. Falls through to the raw value if
-        // the catalog hasn't been fetched yet — the receiver EA will then
-        // perform a second clamp using live `SymbolInfoDouble` values.
-        let receiver_lots = clamp_to_broker_specs(&receiver.terminal_id, &mapped_symbol, raw_lots);
+    let routed_to = admitted.iter().map(|r| r.account_number.clone()).collect();
+    journal.record(event, &config.master.account_id, routed_to);
 
+    let results = Mutex::new(results);
+    dispatch_receivers(&admitted, &config.execution_strategy, |receiver| {
+        let outcome = process_for_receiver(event, receiver, None, state.clone());
+        if let Some(index) = config.receivers.iter().position(|r| std::ptr::eq(r, receiver)) {
+            results.lock()[index] = Some(ReceiverResult::from_outcome(receiver, outcome));
+        }
+    });
+    results.into_inner().into_iter().flatten().collect()
+}
 
-        // Canonical idempotency key — prefer EA-supplied, else build it.
-        let deal = event.deal_id.unwrap_or(event.ticket);
-        let idem = event.idempotency_key.clone().unwrap_or_else(|| {
-            let term = event.terminal_id.clone().unwrap_or_else(|| "unknown".into());
-            crate::copier::idempotency::build_canonical_key(&term, deal, &event.event_type)
-        });
+/// What `process_event` would do with an event for one receiver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunRoute {
+    pub receiver_account: String,
+    /// "copy", "disabled", "aggregate", "copy_mode", "sampled_out", "awaiting_approval",
+    /// "market_closed", "stale", "blocked" or "symbol_disabled"
+    pub outcome: String,
+    /// Lots an opening event would be sent with, clamped to broker specs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lots: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
 
-        // Create execution record
-        let execution = Execution {
-            id: Uuid::new_v4().to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            event_type: event.event_type.clone(),
-            symbol: mapped_symbol.clone(),
-            direction: event.direction.clone(),
-            master_lots: event.lots,
-            receiver_lots,
-            master_price: event.price,
-            executed_price: None,
-            slippage_pips: None,
-            status: "pending".to_string(),
-            error_message: None,
-            receiver_account: receiver.account_number.clone(),
-            master_position_id: Some(deal),
-            receiver_position_id: None,
-            idempotency_key: Some(idem.clone()),
-            master_account_number: event.master_account_number.clone(),
-        };
+/// Route an event through `process_event`'s checks without executing,
+/// queueing or recording anything. Time-based checks use the time the event
+/// was picked up, so a replayed journal gets the answers it got live.
+/// Checks that depend on live state (throttle, signal interval, safety
+/// limits, price deviation, open exposure) are not evaluated.
+pub fn process_event_dry_run(event: &TradeEvent, config: &CopierConfig) -> Vec<DryRunRoute> {
+    let now = event
+        .detected_at
+        .as_deref()
+        .and_then(parse_event_timestamp)
+        .or_else(|| parse_event_timestamp(&event.timestamp))
+        .unwrap_or_else(Utc::now);
+    let calendar = market_hours::current();
+
+    config
+        .receivers
+        .iter()
+        .map(|receiver| {
+            let route = |outcome: &str, reason: Option<String>| DryRunRoute {
+                receiver_account: receiver.account_number.clone(),
+                outcome: outcome.to_string(),
+                lots: None,
+                reason,
+            };
+            if let Some(reason) = disabled_reason(event, receiver) {
+                return route("disabled", Some(reason.to_string()));
+            }
+            if receiver.aggregate_mode {
+                return route(AGGREGATE_STATUS, Some(AGGREGATE_REASON.to_string()));
+            }
+            if let Some(reason) = copy_mode_reason(event, receiver) {
+                return route(COPY_MODE_STATUS, Some(reason.to_string()));
+            }
+            if is_sampled_out(event, receiver) {
+                return route("sampled_out", Some(SAMPLED_OUT_REASON.to_string()));
+            }
+            if approvals::needs_approval(event, receiver) {
+                return route("awaiting_approval", None);
+            }
+            if let Some(opens_at) = calendar.next_open_for(&map_symbol(receiver, &event.symbol), now) {
+                return route("market_closed", Some(format!("Deferred until {}", opens_at.to_rfc3339())));
+            }
+            if let Some(reason) = stale_entry_reason(event, receiver, now, None) {
+                return route("stale", Some(reason));
+            }
+            if let Some(reason) = outdated_ea_reason(receiver) {
+                return route("blocked", Some(reason));
+            }
+            if is_opening_event(event) {
+                let spec = receiver_symbol_spec(&receiver.terminal_id, &map_symbol(receiver, &event.symbol));
+                if let Some(reason) = symbol_disabled_reason(event, spec.as_ref()) {
+                    return route(SYMBOL_DISABLED_STATUS, Some(reason));
+                }
+            }
+            DryRunRoute {
+                lots: is_opening_event(event).then(|| dry_run_lots(event, receiver)),
+                ..route("copy", None)
+            }
+        })
+        .collect()
+}
+
+/// Lot sizing as `process_for_receiver` does it, from the event's own
+/// balances and contract fields
+fn dry_run_lots(event: &TradeEvent, receiver: &ReceiverConfig) -> f64 {
+    let mapped_symbol = map_symbol(receiver, &event.symbol);
+    let raw_lots = lot_calculator::calculate_lots(
+        &receiver.risk_mode,
+        receiver.risk_value,
+        event.lots,
+        event.price,
+        event.sl,
+        event.master_balance,
+        get_cached_account_info(&receiver.terminal_id).as_ref(),
+        with_broker_lot_step(event_symbol_info(event), receiver, &mapped_symbol).as_ref(),
+    );
+    clamp_to_broker_specs(receiver, &mapped_symbol, raw_lots).lots
+}
+
+/// Market-hours check, then waiting for the position's open, then the
+/// receiver's copy delay, then the entry throttle. Deferred executions go
+/// into the execution queue.
+fn admit(event: &TradeEvent, receiver: &ReceiverConfig) -> Admission {
+    let calendar = market_hours::current();
+    let entry_cost = live_entry_cost_reason(event, receiver);
+    EXECUTION_QUEUE.update(|queue| {
+        if let Some(admission) = defer_if_market_closed(queue, event, receiver, &calendar, Utc::now()) {
+            return admission;
+        }
+        if let Some(admission) = defer_until_open_completes(queue, event, receiver, Utc::now()) {
+            return admission;
+        }
+        let jitter_roll = Uuid::new_v4().as_u128() as u64;
+        if let Some(admission) = defer_for_copy_delay(queue, event, receiver, jitter_roll, Utc::now()) {
+            return admission;
+        }
+        if let Some(admission) = defer_for_entry_cost(queue, event, receiver, entry_cost.as_deref(), Utc::now()) {
+            return admission;
+        }
+        throttle_entry(&mut ENTRY_THROTTLES.lock(), queue, event, receiver, Instant::now())
+    })
+}
+
+/// Reason recorded on executions parked until the session opens
+const MARKET_CLOSED_REASON: &str = "market closed";
+
+/// Park `event` in `queue` until the next session open if the market is
+/// closed at `now`. Closes are parked too; they'd fail the same way and go
+/// out first thing at the open.
+fn defer_if_market_closed(
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    calendar: &market_hours::MarketCalendar,
+    now: chrono::DateTime<Utc>,
+) -> Option<Admission> {
+    let opens_at = calendar.next_open_for(&map_symbol(receiver, &event.symbol), now)?;
+    info!(
+        "Market closed: {} {} {} for {} deferred until {}",
+        event.event_type,
+        event.direction,
+        event.symbol,
+        receiver.account_number,
+        opens_at.to_rfc3339()
+    );
+    let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+    queue.defer(exec, opens_at, MARKET_CLOSED_REASON);
+    Some(Admission::Deferred)
+}
+
+/// Reason recorded on entries held back by `max_entry_cost_pips`
+const ENTRY_COST_REASON: &str = "entry cost";
+
+/// How often a held-back entry rechecks the spread
+const ENTRY_COST_RETRY: Duration = Duration::from_secs(2);
+
+/// Longest an entry waits for the cost to come down before it's refused
+const ENTRY_COST_WAIT: Duration = Duration::from_secs(30);
+
+/// Park an entry whose cost is over the limit (`cost_reason`, from
+/// `entry_cost_reason`) to be rechecked shortly
+fn defer_for_entry_cost(
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    cost_reason: Option<&str>,
+    now: chrono::DateTime<Utc>,
+) -> Option<Admission> {
+    let reason = cost_reason?;
+    info!("Entry {} for {} deferred: {}", event.symbol, receiver.account_number, reason);
+    let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+    queue.defer(exec, now + ENTRY_COST_RETRY, ENTRY_COST_REASON);
+    Some(Admission::Deferred)
+}
+
+/// Reason recorded on executions held back by the receiver's copy delay
+const COPY_DELAY_REASON: &str = "copy delay";
+
+/// The receiver's copy delay: `copy_delay_ms` plus `jitter_roll` brought
+/// into 0..=`copy_delay_jitter_ms`
+fn copy_delay(receiver: &ReceiverConfig, jitter_roll: u64) -> Duration {
+    let jitter = match receiver.copy_delay_jitter_ms {
+        0 => 0,
+        max => jitter_roll % (max + 1),
+    };
+    Duration::from_millis(receiver.copy_delay_ms.saturating_add(jitter))
+}
+
+/// Schedule `event` for `receiver` after its copy delay instead of running
+/// it now, so receivers with their own jitter fill at staggered times. The
+/// queue worker picks it up once due; nothing sleeps.
+fn defer_for_copy_delay(
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    jitter_roll: u64,
+    now: chrono::DateTime<Utc>,
+) -> Option<Admission> {
+    let delay = copy_delay(receiver, jitter_roll);
+    if delay.is_zero() {
+        return None;
+    }
+    debug!(
+        "Delaying {} {} for {} by {}ms",
+        event.event_type,
+        event.symbol,
+        receiver.account_number,
+        delay.as_millis()
+    );
+    let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+    let until = now + chrono::Duration::from_std(delay).unwrap_or_default();
+    queue.defer(exec, until, COPY_DELAY_REASON);
+    Some(Admission::Deferred)
+}
+
+/// Reason recorded on the queued unfilled remainder of a partial fill
+const PARTIAL_FILL_REASON: &str = "partial fill remainder";
+
+/// Record the volume the broker actually filled. On a partial fill
+/// `receiver_lots` becomes the fill, `requested_lots` keeps what was sent,
+/// and the unfilled lots are returned.
+fn record_fill(execution: &mut Execution, filled: f64) -> Option<f64> {
+    let requested = execution.receiver_lots;
+    let remainder = requested - filled;
+    if filled <= 0.0 || remainder < 1e-9 {
+        return None;
+    }
+    execution.requested_lots = Some(requested);
+    execution.receiver_lots = filled;
+    Some((remainder * 1e8).round() / 1e8)
+}
 
+/// Queue the unfilled `remainder` of a partially filled entry to be sent
+/// again as-is. Remainders under the broker's minimum lot are dropped
+/// rather than rounded up. Returns whether one was queued.
+fn defer_partial_fill_remainder(
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    remainder: f64,
+    min_lot: Option<f64>,
+    now: chrono::DateTime<Utc>,
+) -> bool {
+    if min_lot.is_some_and(|min| remainder + 1e-9 < min) {
         info!(
-            "Executing {} {} {} -> {} lots on {}",
-            event.direction, mapped_symbol, event.lots, receiver_lots, receiver.account_number
+            "Partial fill remainder of {} lots on {} is under the broker minimum; not retried",
+            remainder, receiver.account_number
         );
+        return false;
+    }
+    let key = format!("{}:remainder", idempotency_key(event));
+    let remainder_event = TradeEvent {
+        idempotency_key: Some(key.clone()),
+        ..event.clone()
+    };
+    info!("Queueing partial fill remainder of {} lots for {}", remainder, receiver.account_number);
+    let mut exec = QueuedExecution::new(remainder_event, &receiver.terminal_id, &key);
+    exec.receiver_lots = Some(remainder);
+    queue.defer(exec, now, PARTIAL_FILL_REASON);
+    true
+}
 
-        // Execute the trade
-        let result = trade_executor::execute_trade(
-            &event.event_type,
-            &mapped_symbol,
-            &event.direction,
-            receiver_lots,
-            event.sl,
-            event.tp,
-            receiver,
-        );
+/// Reason recorded on follow-up events parked behind their position's open
+const OPEN_WAIT_REASON: &str = "waiting for open";
 
-        // Update execution with result
-        let mut final_execution = execution;
-        match result {
-            Ok((price, slippage)) => {
-                final_execution.status = "success".to_string();
-                final_execution.executed_price = Some(price);
-                final_execution.slippage_pips = Some(slippage);
-
-                // Update stats
-                let mut copier = state.lock();
-                copier.trades_today += 1;
-
-                info!(
-                    "Trade executed: {} @ {} (slippage: {} pips)",
-                    mapped_symbol, price, slippage
-                );
-            }
-            Err(e) => {
-                final_execution.status = "error".to_string();
-                final_execution.error_message = Some(e.to_string());
+/// How often a parked follow-up event checks on its open
+const OPEN_WAIT_POLL: Duration = Duration::from_millis(500);
 
-                let mut copier = state.lock();
-                copier.last_error = Some(e.to_string());
+/// Longest a follow-up event waits for its open before it's dropped
+const OPEN_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
 
-                error!("Trade execution failed: {}", e);
-            }
-        }
+/// Status recorded for a follow-up event whose open was never copied
+const OPEN_NOT_COMPLETED: &str = "open_not_completed";
+
+/// Whether `exec` opens, on `receiver_id`, the master position `event`
+/// follows up on
+fn is_open_of(exec: &QueuedExecution, receiver_id: &str, event: &TradeEvent) -> bool {
+    exec.receiver_id == receiver_id && is_opening_event(&exec.event) && position_id_of(&exec.event) == position_id_of(event)
+}
+
+/// Whether a close, partial close, modify or cancel may run yet, given the
+/// queued open of its position
+#[derive(Debug, Clone, PartialEq)]
+enum OpenGate {
+    /// No open is queued, or it completed
+    Ready,
+    /// The open is still pending or in progress
+    Wait,
+    /// Waited `OPEN_WAIT_TIMEOUT` and the open is still pending; an open
+    /// already in flight is waited out so it can't be orphaned
+    TimedOut,
+    /// The open failed for good
+    OpenFailed(String),
+}
+
+fn open_gate(
+    queue: &ExecutionQueue,
+    event: &TradeEvent,
+    receiver_id: &str,
+    waiting_since: chrono::DateTime<Utc>,
+    now: chrono::DateTime<Utc>,
+) -> OpenGate {
+    if is_opening_event(event) {
+        return OpenGate::Ready;
+    }
+    let Some(open) = queue.find(|e| is_open_of(e, receiver_id, event)) else {
+        return OpenGate::Ready;
+    };
+    match open.status {
+        QueueStatus::Completed => OpenGate::Ready,
+        QueueStatus::Failed => OpenGate::OpenFailed(open.last_error.clone().unwrap_or_else(|| "failed".to_string())),
+        QueueStatus::Pending if waiting_since + OPEN_WAIT_TIMEOUT <= now => OpenGate::TimedOut,
+        QueueStatus::Pending | QueueStatus::InProgress => OpenGate::Wait,
+    }
+}
+
+/// Why a follow-up event is a no-op because its position's open failed, if
+/// it is
+fn open_failed_reason(queue: &ExecutionQueue, event: &TradeEvent, receiver_id: &str) -> Option<String> {
+    match open_gate(queue, event, receiver_id, Utc::now(), Utc::now()) {
+        OpenGate::OpenFailed(error) => Some(format!("Open of position {} never completed: {}", position_id_of(event), error)),
+        _ => None,
+    }
+}
 
-        // Queue execution for cloud upload (best-effort)
-        if let Err(e) = exec_sync::queue_for_upload(&final_execution) {
-            warn!("Failed to queue execution for cloud upload: {}", e);
+/// Error recorded on a queued open dropped because its master position closed
+const MASTER_CLOSED_BEFORE_OPEN: &str = "Master position closed before the open was copied";
+
+/// Park a follow-up event in `queue` while its position's open is still
+/// queued, so a receiver never closes a position it hasn't opened yet. The
+/// queue worker runs it once the open completes. A full close cancels an
+/// open still held back by debounce, so it isn't replayed on a closed
+/// master position; the parked close is then skipped.
+fn defer_until_open_completes(
+    queue: &mut ExecutionQueue,
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    now: chrono::DateTime<Utc>,
+) -> Option<Admission> {
+    let cancelled = if event.event_type == "exit" {
+        queue.cancel_pending(
+            |e| is_open_of(e, &receiver.terminal_id, event) && e.defer_reason.as_deref() == Some(DEBOUNCE_REASON),
+            MASTER_CLOSED_BEFORE_OPEN,
+        )
+    } else {
+        0
+    };
+    if cancelled == 0 && open_gate(queue, event, &receiver.terminal_id, now, now) != OpenGate::Wait {
+        return None;
+    }
+    info!(
+        "{} for position {} on {} waits for its open to complete",
+        event.event_type,
+        position_id_of(event),
+        receiver.account_number
+    );
+    let exec = QueuedExecution::new(event.clone(), &receiver.terminal_id, &idempotency_key(event));
+    queue.defer(exec, now + OPEN_WAIT_POLL, OPEN_WAIT_REASON);
+    Some(Admission::Deferred)
+}
+
+/// Execute an entry the user approved: the same throttle, checks and
+/// execution as a live event, minus the approval gate
+pub(crate) fn execute_approved(event: &TradeEvent, receiver: &ReceiverConfig, state: Arc<Mutex<CopierState>>) {
+    if kill_switch::blocks(&state) {
+        return;
+    }
+    if let Some(reason) = disabled_reason(event, receiver) {
+        record_skipped_execution(event, receiver, "disabled", reason, state);
+        return;
+    }
+
+    match admit(event, receiver) {
+        Admission::Admitted => {
+            process_for_receiver(event, receiver, None, state);
         }
+        Admission::Deferred => persist_queue(),
+    }
+}
 
-        // Store execution in recent list
-        {
-            let mut copier = state.lock();
-            copier.recent_executions.insert(0, final_execution);
-            if copier.recent_executions.len() > 100 {
-                copier.recent_executions.pop();
-            }
+/// Record a trade the user rejected, or whose approval timed out
+pub(crate) fn record_manual_rejection(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    reason: &str,
+    state: Arc<Mutex<CopierState>>,
+) {
+    info!("Trade rejected for {}: {}", receiver.account_number, reason);
+    record_skipped_execution(event, receiver, "rejected_manual", reason, state);
+}
+
+/// Why a disabled receiver skips this event. Only new opens (entries and
+/// pending orders) are skipped: closes, partial closes, modifies and pending
+/// order cancels keep its existing exposure in line with the master.
+fn disabled_reason(event: &TradeEvent, receiver: &ReceiverConfig) -> Option<&'static str> {
+    (!receiver.enabled && is_opening_event(event)).then_some("Receiver is disabled")
+}
+
+/// Outcome for events an `aggregate_mode` receiver doesn't copy one by one
+const AGGREGATE_STATUS: &str = "aggregate";
+const AGGREGATE_REASON: &str = "Receiver holds the master's net position";
+
+/// Status recorded on events a receiver's `copy_mode` leaves out
+const COPY_MODE_STATUS: &str = "copy_mode";
+
+/// Why a receiver's `copy_mode` leaves this event out, if it does
+fn copy_mode_reason(event: &TradeEvent, receiver: &ReceiverConfig) -> Option<&'static str> {
+    match receiver.copy_mode {
+        CopyMode::Full => None,
+        CopyMode::EntriesOnly => {
+            matches!(event.event_type.as_str(), "exit" | "partial_close").then_some("Receiver copies entries only")
         }
+        CopyMode::ExitsOnly => is_opening_event(event).then_some("Receiver copies exits only"),
     }
 }
 
-/// Record a blocked execution for audit trail
-fn record_blocked_execution(
+const SAMPLED_OUT_REASON: &str = "Not in this receiver's copy sample";
+
+/// Status recorded on entries into a symbol the receiver's broker doesn't
+/// allow opening
+const SYMBOL_DISABLED_STATUS: &str = "symbol_disabled";
+
+/// Why the receiver's broker won't take this entry, going by the catalog
+/// spec of the mapped symbol. Closes, modifies and cancels always pass, as
+/// does a symbol missing from the catalog (the EA has the final say).
+fn symbol_disabled_reason(event: &TradeEvent, spec: Option<&symbol_catalog::SymbolSpec>) -> Option<String> {
+    let spec = spec.filter(|_| is_opening_event(event))?;
+    (!spec.allows_opening(&event.direction)).then(|| {
+        format!(
+            "{} can't be opened on the receiver (trade mode {})",
+            spec.name,
+            spec.trade_mode.as_deref().unwrap_or_default()
+        )
+    })
+}
+
+/// Catalog spec of `symbol` on a receiver terminal, if the catalog is
+/// available and lists it
+fn receiver_symbol_spec(terminal_id: &str, symbol: &str) -> Option<symbol_catalog::SymbolSpec> {
+    symbol_catalog::fetch_symbol_catalog(terminal_id)
+        .ok()
+        .and_then(|c| c.symbols.into_iter().find(|s| s.name == symbol))
+}
+
+/// Master position id an event belongs to. A pending order's ticket becomes
+/// the id of the position it opens when it fills.
+pub(crate) fn position_id_of(event: &TradeEvent) -> i64 {
+    if event.event_type.starts_with("pending_") {
+        event.order_ticket.unwrap_or(event.ticket)
+    } else {
+        event.ticket
+    }
+}
+
+/// Whether a master position falls outside a `copy_fraction` sample. Pure
+/// in the position id, so an open and all its later closes and modifies
+/// get the same answer, across restarts too.
+pub(crate) fn position_sampled_out(position_id: i64, copy_fraction: f64) -> bool {
+    // splitmix64 finalizer: stable across builds, unlike std's hasher
+    let mut x = position_id as u64;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^= x >> 31;
+    let bucket = (x >> 11) as f64 / (1u64 << 53) as f64;
+    bucket >= copy_fraction.clamp(0.0, 1.0)
+}
+
+/// Whether `receiver` copies a sample of master positions that excludes
+/// this event's
+fn is_sampled_out(event: &TradeEvent, receiver: &ReceiverConfig) -> bool {
+    receiver
+        .copy_fraction
+        .is_some_and(|fraction| position_sampled_out(position_id_of(event), fraction))
+}
+
+/// Most receiver executions in flight at once in parallel modes
+pub const MAX_PARALLEL_EXECUTIONS: usize = 8;
+
+/// Run `execute` for each receiver in the order/concurrency `strategy` asks
+/// for. Returns once every receiver has been handled. Shared state touched
+/// by `execute` (`CopierState`, the execution queue) is mutex-guarded.
+fn dispatch_receivers<'a>(
+    receivers: &[&'a ReceiverConfig],
+    strategy: &ExecutionStrategy,
+    execute: impl Fn(&'a ReceiverConfig) + Sync,
+) {
+    match strategy {
+        ExecutionStrategy::Sequential => receivers.iter().for_each(|r| execute(r)),
+        ExecutionStrategy::Parallel => run_bounded(receivers, MAX_PARALLEL_EXECUTIONS, &execute),
+        ExecutionStrategy::PriorityOrdered { primary_terminal_id } => {
+            let (primary, rest): (Vec<&ReceiverConfig>, Vec<&ReceiverConfig>) = receivers
+                .iter()
+                .partition(|r| &r.terminal_id == primary_terminal_id);
+            primary.iter().for_each(|r| execute(r));
+            run_bounded(&rest, MAX_PARALLEL_EXECUTIONS, &execute);
+        }
+    }
+}
+
+/// Run `execute` over `receivers` on up to `max_concurrency` threads
+fn run_bounded<'a>(
+    receivers: &[&'a ReceiverConfig],
+    max_concurrency: usize,
+    execute: &(impl Fn(&'a ReceiverConfig) + Sync),
+) {
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for _ in 0..max_concurrency.min(receivers.len()) {
+            scope.spawn(|| {
+                while let Some(receiver) = receivers.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    execute(receiver);
+                }
+            });
+        }
+    });
+}
+
+/// Retry deferred executions whose `next_retry_at` has passed.
+///
+/// Called periodically by the queue worker while the copier is running.
+/// Entries that are still over their receiver's limit go back to the queue.
+pub fn process_deferred(config: &CopierConfig, state: Arc<Mutex<CopierState>>) {
+    if kill_switch::is_engaged() {
+        return;
+    }
+
+    loop {
+        // Stop picking up new work once shutdown starts
+        if file_watcher::is_shutdown_requested() {
+            break;
+        }
+        let Some(exec) = EXECUTION_QUEUE.update(|queue| queue.dequeue_ready(Utc::now())) else {
+            break;
+        };
+        let Some(receiver) = config.receivers.iter().find(|r| r.terminal_id == exec.receiver_id) else {
+            let error = format!("Receiver {} is no longer configured", exec.receiver_id);
+            EXECUTION_QUEUE.update(|queue| queue.mark_failed(&exec.id, &error));
+            persist_queue();
+            continue;
+        };
+
+        // Was in flight when the app stopped: don't send it twice
+        if recovery::complete_if_already_executed(&exec, receiver) {
+            persist_queue();
+            continue;
+        }
+
+        // Disabled while it waited in the queue
+        if let Some(reason) = disabled_reason(&exec.event, receiver) {
+            record_skipped_execution(&exec.event, receiver, "disabled", reason, state.clone());
+            EXECUTION_QUEUE.update(|queue| queue.mark_completed(&exec.id));
+            persist_queue();
+            continue;
+        }
+
+        // Follow-ups wait for their position's open; if it never runs there's nothing to do
+        let gate = EXECUTION_QUEUE.update(|queue| {
+            let gate = open_gate(queue, &exec.event, &exec.receiver_id, exec.detected_at, Utc::now());
+            match &gate {
+                OpenGate::Ready => {}
+                OpenGate::Wait => queue.requeue(&exec.id, Utc::now() + OPEN_WAIT_POLL, OPEN_WAIT_REASON),
+                OpenGate::TimedOut | OpenGate::OpenFailed(_) => {
+                    queue.cancel_pending(|e| is_open_of(e, &exec.receiver_id, &exec.event), MASTER_CLOSED_BEFORE_OPEN);
+                    queue.mark_completed(&exec.id);
+                }
+            }
+            gate
+        });
+        match gate {
+            OpenGate::Ready => {}
+            OpenGate::Wait => {
+                persist_queue();
+                continue;
+            }
+            OpenGate::TimedOut | OpenGate::OpenFailed(_) => {
+                let reason = match gate {
+                    OpenGate::OpenFailed(error) => {
+                        format!("Open of position {} never completed: {}", position_id_of(&exec.event), error)
+                    }
+                    _ => format!(
+                        "Open of position {} did not complete within {}s",
+                        position_id_of(&exec.event),
+                        OPEN_WAIT_TIMEOUT.as_secs()
+                    ),
+                };
+                warn!("Skipping {} for {}: {}", exec.event.event_type, receiver.account_number, reason);
+                record_skipped_execution(&exec.event, receiver, OPEN_NOT_COMPLETED, &reason, state.clone());
+                persist_queue();
+                continue;
+            }
+        }
+
+        // Debounced entries wait out the interval, then take the live path's
+        // approval gate they were held in front of
+        if exec.defer_reason.as_deref() == Some(DEBOUNCE_REASON) {
+            if let Err(wait) = check_signal_interval(&mut LAST_ENTRIES.lock(), &exec.event, receiver, Instant::now()) {
+                EXECUTION_QUEUE.update(|queue| queue.requeue(&exec.id, Utc::now() + wait, DEBOUNCE_REASON));
+                persist_queue();
+                continue;
+            }
+            if approvals::needs_approval(&exec.event, receiver) {
+                approvals::request_approval(&exec.event, receiver);
+                EXECUTION_QUEUE.update(|queue| queue.mark_completed(&exec.id));
+                persist_queue();
+                continue;
+            }
+        }
+
+        // Market closed since it was queued: wait for the open without using up an attempt
+        if let Some(opens_at) = market_hours::current().next_open_for(&map_symbol(receiver, &exec.event.symbol), Utc::now()) {
+            EXECUTION_QUEUE.update(|queue| queue.requeue(&exec.id, opens_at, MARKET_CLOSED_REASON));
+            persist_queue();
+            continue;
+        }
+
+        // Entry still too expensive: recheck shortly, or refuse once it has waited long enough
+        if let Some(reason) = live_entry_cost_reason(&exec.event, receiver) {
+            let waited = (Utc::now() - exec.detected_at).to_std().unwrap_or_default();
+            if waited < ENTRY_COST_WAIT {
+                EXECUTION_QUEUE.update(|queue| queue.requeue(&exec.id, Utc::now() + ENTRY_COST_RETRY, ENTRY_COST_REASON));
+            } else {
+                warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+                record_blocked_execution(&exec.event, receiver, &reason, state.clone());
+                EXECUTION_QUEUE.update(|queue| queue.mark_completed(&exec.id));
+            }
+            persist_queue();
+            continue;
+        }
+
+        // Still over the limit: put it back without using up an attempt
+        if let Err(wait) = check_entry_throttle(&mut ENTRY_THROTTLES.lock(), &exec.event, receiver, Instant::now()) {
+            EXECUTION_QUEUE.update(|queue| queue.requeue(&exec.id, Utc::now() + wait, THROTTLE_REASON));
+            persist_queue();
+            continue;
+        }
+
+        let outcome = process_for_receiver(&exec.event, receiver, exec.receiver_lots, state.clone());
+
+        EXECUTION_QUEUE.update(|queue| match outcome {
+            ReceiverOutcome::Executed | ReceiverOutcome::Blocked(_) => queue.mark_completed(&exec.id),
+            ReceiverOutcome::Failed(e) => {
+                queue.mark_failed(&exec.id, &e);
+            }
+        });
+        persist_queue();
+    }
+}
+
+fn persist_queue() {
+    if let Err(e) = EXECUTION_QUEUE.persist() {
+        warn!("Failed to persist execution queue: {}", e);
+    }
+}
+
+/// Symbol info from the event's contract fields, if the master sent them
+fn event_symbol_info(event: &TradeEvent) -> Option<lot_calculator::SymbolInfo> {
+    let tick_value = event.tick_value?;
+    Some(lot_calculator::SymbolInfo {
+        tick_value,
+        tick_size: event.point.unwrap_or(0.00001),
+        contract_size: event.contract_size.unwrap_or(100000.0),
+        digits: event.digits.unwrap_or(5),
+        point: event.point.unwrap_or(0.00001),
+        symbol_type: lot_calculator::SymbolInfo::detect_symbol_type(&event.symbol),
+        min_lot: None,
+        lot_step: None,
+        lot_rounding: None,
+    })
+}
+
+/// `info` with the receiver broker's minimum lot and lot step for `symbol`,
+/// so sizing keeps the finer steps crypto trades in, and the receiver's
+/// `lot_rounding`. Steps are unchanged when the catalog doesn't list the
+/// symbol.
+fn with_broker_lot_step(
+    info: Option<lot_calculator::SymbolInfo>,
+    receiver: &ReceiverConfig,
+    symbol: &str,
+) -> Option<lot_calculator::SymbolInfo> {
+    let Some(spec) = receiver_symbol_spec(&receiver.terminal_id, symbol) else {
+        return info.map(|info| lot_calculator::SymbolInfo {
+            lot_rounding: Some(receiver.lot_rounding),
+            ..info
+        });
+    };
+    Some(lot_calculator::SymbolInfo {
+        min_lot: Some(spec.min_lot),
+        lot_step: Some(spec.lot_step),
+        lot_rounding: Some(receiver.lot_rounding),
+        ..info.unwrap_or_default()
+    })
+}
+
+/// Process one event for one receiver: safety check, lot sizing and execution. Shared by the live path and the queue worker.
+fn process_for_receiver(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    lots_override: Option<f64>,
+    state: Arc<Mutex<CopierState>>,
+) -> ReceiverOutcome {
+    // Cancels only remove a mirrored order, so they skip the entry checks
+    if event.event_type == "pending_cancelled" {
+        return process_pending_cancel(event, receiver, state);
+    }
+
+    let mut symbol_info = event_symbol_info(event);
+
+    let master_skew_secs = if receiver.correct_clock_skew && receiver.max_event_age_secs.is_some() {
+        event
+            .terminal_id
+            .as_deref()
+            .and_then(clock_skew::measure)
+            .map(|skew| skew.estimated_skew_secs)
+    } else {
+        None
+    };
+    if let Some(reason) = stale_entry_reason(event, receiver, Utc::now(), master_skew_secs) {
+        warn!("Skipping entry for {}: {}", receiver.account_number, reason);
+        record_skipped_execution(event, receiver, "stale", &reason, state.clone());
+        return ReceiverOutcome::Blocked(reason);
+    }
+
+    if let Some(reason) = outdated_ea_reason(receiver) {
+        warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+        record_blocked_execution(event, receiver, &reason, state.clone());
+        return ReceiverOutcome::Blocked(reason);
+    }
+
+    // Check safety limits before processing.
+    let safety_config = receiver_safety_config(receiver);
+
+    // Get receiver account info from cached state (would be updated from heartbeat)
+    let receiver_account = get_cached_account_info(&receiver.terminal_id);
+    let starting_balance = receiver_account.as_ref().map(|a| a.balance).unwrap_or(10000.0);
+    if let Some(account) = &receiver_account {
+        if let Some(reason) = safety::update_equity(&receiver.account_number, account.equity, account.balance, &safety_config) {
+            close_all_for_profit_target(receiver, reason);
+        }
+    }
+    
+    // Only a paused receiver cares whether a modify loosens its stops
+    let loosens_stops = event.event_type == "modify"
+        && safety::is_receiver_paused(&receiver.account_number)
+        && modify_loosens_stops(event, receiver);
+    match event_safety(event, &receiver.account_number, &safety_config, starting_balance, loosens_stops) {
+        safety::SafetyCheckResult::Blocked(reason) => {
+            warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+            record_blocked_execution(event, receiver, &reason, state.clone());
+            return ReceiverOutcome::Blocked(reason);
+        }
+        safety::SafetyCheckResult::Warning(warning) => {
+            warn!("Safety warning for {}: {}", receiver.account_number, warning);
+            // Continue with trade but log warning
+        }
+        safety::SafetyCheckResult::Allowed => {
+            // Continue normally
+        }
+    }
+    
+    let mapped_symbol = map_symbol(receiver, &event.symbol);
+
+    if is_opening_event(event) {
+        let spec = receiver_symbol_spec(&receiver.terminal_id, &mapped_symbol);
+        if let Some(reason) = symbol_disabled_reason(event, spec.as_ref()) {
+            warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+            record_skipped_execution(event, receiver, SYMBOL_DISABLED_STATUS, &reason, state.clone());
+            return ReceiverOutcome::Blocked(reason);
+        }
+    }
+
+    if let Some(limit) = receiver.max_entry_deviation_pips.filter(|_| event.event_type == "entry") {
+        let tick = ticks::latest_tick(&receiver.terminal_id, &mapped_symbol);
+        if let Some(reason) = entry_deviation_reason(event, limit, tick.as_ref()) {
+            warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+            record_blocked_execution(event, receiver, &reason, state.clone());
+            return ReceiverOutcome::Blocked(reason);
+        }
+    }
+
+    // Partial closes are sized off the receiver's live position rather than
+    // the risk mode, and go out as a targeted SyncCommand.
+    if event.event_type == "partial_close" {
+        return process_partial_close(event, receiver, &mapped_symbol, state.clone());
+    }
+
+    // balance_multiplier: recompute the ratio from the latest heartbeats
+    // rather than the balances captured when the event/account info was written
+    let (master_balance, sizing_account) = if receiver.risk_mode == "balance_multiplier" {
+        let master_heartbeat = event.terminal_id.as_deref().and_then(live_balance::live_heartbeat);
+        let receiver_heartbeat = live_balance::live_receiver_heartbeat(&receiver.terminal_id);
+        live_balance::resolve_balances(
+            master_heartbeat.as_ref(),
+            receiver_heartbeat.as_ref(),
+            event.master_balance,
+            receiver_account,
+        )
+    } else {
+        (event.master_balance, receiver_account)
+    };
+
+    if let (Some(info), Some(account)) = (symbol_info.as_mut(), sizing_account.as_ref()) {
+        if RISK_BASED_MODES.contains(&receiver.risk_mode.as_str()) {
+            let spec = receiver_symbol_spec(&receiver.terminal_id, &mapped_symbol);
+            let master_currency = event
+                .terminal_id
+                .as_deref()
+                .and_then(get_cached_account_info)
+                .map(|master| master.currency);
+            let (tick_value, tick_size) = tick_value_for_account(
+                info,
+                spec.as_ref(),
+                master_currency.as_deref(),
+                account,
+                || currency::receiver_quotes(&receiver.terminal_id),
+            );
+            info.tick_value = tick_value;
+            info.tick_size = tick_size;
+            info.point = tick_size;
+        }
+    }
+
+    let symbol_info = with_broker_lot_step(symbol_info, receiver, &mapped_symbol);
+
+    // Calculate lot size using the improved calculator, unless the lots
+    // were fixed when queued (a partial fill's remainder)
+    let raw_lots = lots_override.unwrap_or_else(|| lot_calculator::calculate_lots(
+        &receiver.risk_mode,
+        receiver.risk_value,
+        event.lots,
+        event.price,
+        event.sl,
+        master_balance,
+        sizing_account.as_ref(),
+        symbol_info.as_ref(),
+    ));
+
+    // R9: clamp to the receiver broker's real min/max/step from the
+    // symbol catalog when available. Falls through to the raw value if
+    // the catalog hasn't been fetched yet — the receiver EA will then
+    // perform a second clamp using live `SymbolInfoDouble` values.
+    let lot_calc = clamp_to_broker_specs(receiver, &mapped_symbol, raw_lots);
+    let receiver_lots = lot_calc.lots;
+
+    // Surface sizes the broker specs changed a lot - usually a multiplier
+    // too small for the broker's minimum lot
+    let lot_adjustment = lot_calc.is_material().then(|| {
+        let note = lot_calc.describe();
+        warn!("Lot size for {} on {}: {}", mapped_symbol, receiver.account_number, note);
+        note
+    });
+
+
+    if is_opening_event(event) && (receiver.max_total_lots.is_some() || receiver.max_open_positions.is_some()) {
+        let reason = match position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number) {
+            Ok(open) => exposure_limit_reason(receiver, &open, receiver_lots),
+            Err(e) => Some(format!("Exposure limit: couldn't read open positions ({})", e)),
+        };
+        if let Some(reason) = reason {
+            warn!("Trade blocked for {}: {}", receiver.account_number, reason);
+            record_skipped_execution(event, receiver, "exposure_limit", &reason, state.clone());
+            return ReceiverOutcome::Blocked(reason);
+        }
+    }
+
+    let idem = idempotency_key(event);
+
+    // Create execution record
+    let execution = Execution {
+        id: Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_type: event.event_type.clone(),
+        symbol: mapped_symbol.clone(),
+        direction: event.direction.clone(),
+        master_lots: event.lots,
+        receiver_lots,
+        master_price: event.price,
+        executed_price: None,
+        slippage_pips: None,
+        status: "pending".to_string(),
+        error_message: None,
+        receiver_account: receiver.account_number.clone(),
+        master_position_id: Some(position_id_of(event)),
+        receiver_position_id: None,
+        idempotency_key: Some(idem.clone()),
+        master_account_number: event.master_account_number.clone(),
+        intended_lots: lot_adjustment.is_some().then(|| lot_calc.intended_lots()),
+        requested_lots: None,
+        lot_adjustment,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
+    };
+
+    if event.event_type == "pending_order" {
+        return process_pending_order(event, receiver, execution, state);
+    }
+
+    info!(
+        "Executing {} {} {} -> {} lots on {}",
+        event.direction, mapped_symbol, event.lots, receiver_lots, receiver.account_number
+    );
+
+    // Absolute SL/TP: from the master's distances when it sent them, else its
+    // levels if they're on the right side of the entry
+    let sltp_policy = receiver.sltp_policy;
+    let (sl, tp) = if event.event_type == "entry" {
+        let receiver_point = trade_executor::receiver_price_scale(&receiver.terminal_id, &mapped_symbol).map(|s| s.point);
+        let levels = trade_executor::entry_stops(event, &event.direction, receiver_point);
+        (levels.sl, levels.tp)
+    } else {
+        (event.sl, event.tp)
+    };
+
+    // Relative SL/TP: send the master's distances scaled to the receiver's
+    // digits so a broker price offset doesn't shift the stops
+    let stops = if receiver.use_relative_sltp && event.event_type == "entry" && sltp_policy != SltpPolicy::Ignore {
+        let receiver_digits = symbol_catalog::fetch_symbol_catalog(&receiver.terminal_id)
+            .ok()
+            .and_then(|c| c.symbols.iter().find(|s| s.name == mapped_symbol).map(|s| s.digits));
+        trade_executor::relative_stops(event, receiver_digits)
+    } else {
+        trade_executor::RelativeStops::default()
+    };
+
+    // Execute the trade
+    let (sent_sl, sent_tp) = (sltp_policy.apply(sl), sltp_policy.apply(tp));
+    let sent_at = Instant::now();
+    let order = trade_executor::TradeOrder {
+        event_type: event.event_type.clone(),
+        symbol: mapped_symbol.clone(),
+        direction: event.direction.clone(),
+        lots: receiver_lots,
+        sl: sent_sl,
+        tp: sent_tp,
+        stops,
+        master_price: Some(event.price),
+        // The EA maps the receiver position under it and finds every copy of
+        // the master position by it on exits and modifies
+        master_position_id: Some(position_id_of(event)),
+        idempotency_key: Some(idem.clone()),
+    };
+    let result = trade_executor::execute_trade(&order, receiver);
+
+    // Update execution with result
+    let mut final_execution = execution;
+    latency::stamp(&mut final_execution, event, Some(sent_at.elapsed()), Utc::now());
+    let outcome = match result {
+        Ok(executed) => {
+            let (price, slippage) = (executed.executed_price, executed.slippage_pips);
+            final_execution.status = "success".to_string();
+            final_execution.executed_price = Some(price);
+            final_execution.slippage_pips = Some(slippage);
+            final_execution.receiver_position_id = executed.receiver_position_id;
+
+            if let Some(remainder) = executed.filled_lots.and_then(|filled| record_fill(&mut final_execution, filled)) {
+                warn!(
+                    "Partial fill on {} {}: {} of {} lots",
+                    receiver.account_number, mapped_symbol, final_execution.receiver_lots, receiver_lots
+                );
+                if receiver.retry_partial_fill_remainder && event.event_type == "entry" {
+                    let min_lot = receiver_symbol_spec(&receiver.terminal_id, &mapped_symbol).map(|s| s.min_lot);
+                    let queued = EXECUTION_QUEUE.update(|queue| {
+                        defer_partial_fill_remainder(queue, event, receiver, remainder, min_lot, Utc::now())
+                    });
+                    if queued {
+                        persist_queue();
+                    }
+                }
+            }
+
+            // Remember the levels set so reconciliation can tell a hand-moved
+            // one; relative ones are placed by the EA and aren't known here
+            match event.event_type.as_str() {
+                "entry" | "modify" => commanded_levels::record(
+                    &receiver.terminal_id,
+                    position_id_of(event),
+                    sent_sl.filter(|_| stops.sl_points.is_none()),
+                    sent_tp.filter(|_| stops.tp_points.is_none()),
+                ),
+                "exit" => commanded_levels::forget(&receiver.terminal_id, position_id_of(event)),
+                _ => {}
+            }
+
+            // Update stats
+            let mut copier = state.lock();
+            copier.trades_today += 1;
+
+            info!(
+                "Trade executed: {} @ {} (slippage: {} pips)",
+                mapped_symbol, price, slippage
+            );
+            ReceiverOutcome::Executed
+        }
+        Err(e) => {
+            final_execution.status = "error".to_string();
+            final_execution.error_message = Some(e.to_string());
+
+            let mut copier = state.lock();
+            copier.last_error = Some(e.to_string());
+
+            error!("Trade execution failed: {}", e);
+            let error = e.to_string();
+            if alerts::is_slippage_rejection(&error) {
+                alerts::push_alert(
+                    alerts::AlertSeverity::Warning,
+                    format!("Slippage rejection on {} {}: {}", receiver.account_number, mapped_symbol, error),
+                );
+            } else {
+                alerts::push_alert(
+                    alerts::AlertSeverity::Critical,
+                    format!("Execution failed on {} {}: {}", receiver.account_number, mapped_symbol, error),
+                );
+            }
+            ReceiverOutcome::Failed(e.to_string())
+        }
+    };
+
+    store_execution(final_execution, &state);
+    outcome
+}
+
+/// Queue an execution for cloud upload (best-effort) and push it onto the
+/// recent executions list shown in the UI.
+pub(crate) fn store_execution(execution: Execution, state: &Arc<Mutex<CopierState>>) {
+    latency::record(&execution);
+    receiver_stats::record(&execution);
+    if execution.status == "success" && is_opening_event_type(&execution.event_type) {
+        global_cap::record_entry(state);
+    }
+    if let Err(e) = exec_sync::queue_for_upload(&execution) {
+        warn!("Failed to queue execution for cloud upload: {}", e);
+    }
+
+    let mut copier = state.lock();
+    copier.recent_executions.insert(0, execution);
+    if copier.recent_executions.len() > 100 {
+        copier.recent_executions.pop();
+    }
+}
+
+/// Receiver symbol for a master symbol, via the receiver's enabled mappings
+pub(crate) fn map_symbol(receiver: &ReceiverConfig, master_symbol: &str) -> String {
+    receiver
+        .symbol_mappings
+        .iter()
+        .find(|m| m.master_symbol == master_symbol && m.is_enabled)
+        .map(|m| m.receiver_symbol.clone())
+        .unwrap_or_else(|| master_symbol.to_string())
+}
+
+/// Direction a pending order type opens, if it's one we copy
+fn pending_direction(order_type: &str) -> Option<&'static str> {
+    match order_type {
+        "buy_limit" | "buy_stop" => Some("buy"),
+        "sell_limit" | "sell_stop" => Some("sell"),
+        _ => None,
+    }
+}
+
+/// Build the `place_pending` SyncCommand mirroring a master pending order.
+/// The event's `price` is the trigger price.
+pub fn build_pending_order_command(
+    event: &TradeEvent,
+    mapped_symbol: &str,
+    lots: f64,
+    sltp_policy: SltpPolicy,
+) -> Result<SyncCommand, String> {
+    let order_type = event
+        .order_type
+        .as_deref()
+        .ok_or_else(|| "pending_order event is missing order_type".to_string())?;
+    let direction =
+        pending_direction(order_type).ok_or_else(|| format!("Unsupported pending order type '{}'", order_type))?;
+    if event.price <= 0.0 {
+        return Err(format!("Pending {} order has no trigger price", order_type));
+    }
+    let stops = trade_executor::entry_stops(event, direction, None);
+
+    Ok(SyncCommand::place_pending(
+        event.order_ticket.unwrap_or(event.ticket),
+        mapped_symbol,
+        order_type,
+        direction,
+        lots,
+        event.price,
+        sltp_policy.apply(stops.sl),
+        sltp_policy.apply(stops.tp),
+    ))
+}
+
+/// Build the `cancel_pending` SyncCommand for a cancelled master order
+pub fn build_pending_cancel_command(event: &TradeEvent) -> SyncCommand {
+    SyncCommand::cancel_pending(event.order_ticket.unwrap_or(event.ticket))
+}
+
+/// Write a pending order SyncCommand for one receiver and record the outcome
+fn send_pending_command(
+    command: Result<SyncCommand, String>,
+    receiver: &ReceiverConfig,
+    mut execution: Execution,
+    event: &TradeEvent,
+    state: Arc<Mutex<CopierState>>,
+) -> ReceiverOutcome {
+    let sent_at = Instant::now();
+    let result = command.and_then(|command| {
+        position_sync::write_sync_command(&receiver.terminal_id, &command)?;
+        Ok(command)
+    });
+
+    let outcome = match result {
+        Ok(command) => {
+            info!(
+                "{} sent to {}: master order {:?}",
+                command.command_type, receiver.account_number, command.master_position_id
+            );
+            execution.status = "success".to_string();
+            ReceiverOutcome::Executed
+        }
+        Err(e) => {
+            error!("{} failed for {}: {}", event.event_type, receiver.account_number, e);
+            execution.status = "error".to_string();
+            execution.error_message = Some(e.clone());
+            state.lock().last_error = Some(e.clone());
+            ReceiverOutcome::Failed(e)
+        }
+    };
+
+    latency::stamp(&mut execution, event, Some(sent_at.elapsed()), Utc::now());
+    store_execution(execution, &state);
+    outcome
+}
+
+/// Mirror a master pending order on one receiver. Sized like an entry; the
+/// order goes out as a SyncCommand since there is no fill to wait for.
+fn process_pending_order(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    execution: Execution,
+    state: Arc<Mutex<CopierState>>,
+) -> ReceiverOutcome {
+    let command = build_pending_order_command(event, &execution.symbol, execution.receiver_lots, receiver.sltp_policy);
+    send_pending_command(command, receiver, execution, event, state)
+}
+
+/// Remove a receiver's mirror of a cancelled master pending order
+fn process_pending_cancel(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    state: Arc<Mutex<CopierState>>,
+) -> ReceiverOutcome {
+    let execution = Execution {
+        id: Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_type: event.event_type.clone(),
+        symbol: map_symbol(receiver, &event.symbol),
+        direction: event.direction.clone(),
+        master_lots: event.lots,
+        receiver_lots: 0.0,
+        master_price: event.price,
+        executed_price: None,
+        slippage_pips: None,
+        status: "pending".to_string(),
+        error_message: None,
+        receiver_account: receiver.account_number.clone(),
+        master_position_id: Some(position_id_of(event)),
+        receiver_position_id: None,
+        idempotency_key: Some(idempotency_key(event)),
+        master_account_number: event.master_account_number.clone(),
+        intended_lots: None,
+        requested_lots: None,
+        lot_adjustment: None,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
+    };
+    send_pending_command(Ok(build_pending_cancel_command(event)), receiver, execution, event, state)
+}
+
+/// Build the `partial_close` SyncCommands for one receiver.
+///
+/// The receiver positions are looked up by master position id
+/// (`event.ticket`) and the master's closed fraction is applied to each
+/// one's live volume, so scaled receivers reduce proportionally instead of
+/// by the master's raw lots. A partially filled entry and its remainder are
+/// two positions under the same master position, so both are reduced.
+pub fn build_partial_close_commands(
+    event: &TradeEvent,
+    receiver_positions: &[ReceiverPosition],
+) -> Result<Vec<SyncCommand>, String> {
+    let data = event
+        .partial_close_data
+        .as_ref()
+        .ok_or_else(|| "partial_close event is missing partial_close_data".to_string())?;
+
+    let mapped: Vec<&ReceiverPosition> =
+        receiver_positions.iter().filter(|p| p.master_position_id == event.ticket).collect();
+    if mapped.is_empty() {
+        return Err(format!("No receiver position mapped to master position {}", event.ticket));
+    }
+
+    let commands: Vec<SyncCommand> = mapped
+        .into_iter()
+        .filter_map(|receiver_pos| {
+            let volume = lot_calculator::calculate_partial_close_lots(
+                data.closed_volume,
+                data.remaining_volume,
+                receiver_pos.volume,
+            );
+            (volume > 0.0).then(|| SyncCommand::partial_close(receiver_pos.position_id, event.ticket, volume))
+        })
+        .collect();
+    if commands.is_empty() {
+        return Err(format!(
+            "Partial close of {} lots on master position {} rounds to zero on receiver",
+            data.closed_volume, event.ticket
+        ));
+    }
+
+    Ok(commands)
+}
+
+/// Propagate a master partial close to one receiver and record the outcome
+fn process_partial_close(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    mapped_symbol: &str,
+    state: Arc<Mutex<CopierState>>,
+) -> ReceiverOutcome {
+    // The deal id makes each partial close of the same position a distinct
+    // idempotency key, so repeated deliveries of one deal are not re-applied.
+    let idem = idempotency_key(event);
+    // Volume is taken off the live position, so a replay (queue retry,
+    // recovery) would close more: each receiver applies a deal once
+    let applied_key = format!("{}:{}", idem, receiver.terminal_id);
+    if idempotency::is_event_processed(&applied_key) {
+        info!("Partial close {} already applied on {}", idem, receiver.account_number);
+        return ReceiverOutcome::Executed;
+    }
+
+    let mut execution = Execution {
+        id: Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_type: event.event_type.clone(),
+        symbol: mapped_symbol.to_string(),
+        direction: event.direction.clone(),
+        master_lots: event.partial_close_data.as_ref().map(|d| d.closed_volume).unwrap_or(event.lots),
+        receiver_lots: 0.0,
+        master_price: event.price,
+        executed_price: None,
+        slippage_pips: None,
+        status: "pending".to_string(),
+        error_message: None,
+        receiver_account: receiver.account_number.clone(),
+        master_position_id: Some(position_id_of(event)),
+        receiver_position_id: None,
+        idempotency_key: Some(idem),
+        master_account_number: event.master_account_number.clone(),
+        intended_lots: None,
+        requested_lots: None,
+        lot_adjustment: None,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
+    };
+
+    let sent_at = Instant::now();
+    let result = position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number)
+        .map_err(String::from)
+        .and_then(|positions| build_partial_close_commands(event, &positions))
+        .and_then(|commands| {
+            for command in &commands {
+                position_sync::write_sync_command(&receiver.terminal_id, command)?;
+            }
+            Ok(commands)
+        });
+
+    let outcome = match result {
+        Ok(commands) => {
+            let lots: f64 = commands.iter().filter_map(|c| c.volume).sum();
+            let positions: Vec<i64> = commands.iter().filter_map(|c| c.position_id).collect();
+            info!(
+                "Partial close sent to {}: {} lots of position(s) {:?}",
+                receiver.account_number, lots, positions
+            );
+            execution.status = "success".to_string();
+            execution.receiver_lots = lot_calculator::round_to_step_precision(lots, lot_calculator::DEFAULT_LOT_STEP);
+            execution.receiver_position_id = positions.first().copied();
+            idempotency::mark_event_processed(&applied_key);
+            ReceiverOutcome::Executed
+        }
+        Err(e) => {
+            error!("Partial close failed for {}: {}", receiver.account_number, e);
+            execution.status = "error".to_string();
+            execution.error_message = Some(e.clone());
+            state.lock().last_error = Some(e.clone());
+            ReceiverOutcome::Failed(e)
+        }
+    };
+
+    latency::stamp(&mut execution, event, Some(sent_at.elapsed()), Utc::now());
+    store_execution(execution, &state);
+    outcome
+}
+
+/// Risk modes that size off a loss amount in account currency
+const RISK_BASED_MODES: [&str; 3] = ["risk_percent", "risk_dollar", "intent"];
+
+/// Tick value and tick size for risk sizing, with the tick value in the
+/// receiver's account currency. MT5 reports tick value in the deposit
+/// currency, so the receiver's own catalog spec is used as is; without one,
+/// the master's is converted from the master account's currency.
+fn tick_value_for_account(
+    info: &lot_calculator::SymbolInfo,
+    receiver_spec: Option<&symbol_catalog::SymbolSpec>,
+    master_currency: Option<&str>,
+    account: &lot_calculator::AccountInfo,
+    quotes: impl FnOnce() -> currency::Quotes,
+) -> (f64, f64) {
+    if let Some(spec) = receiver_spec.filter(|s| s.tick_value > 0.0 && s.tick_size > 0.0) {
+        return (spec.tick_value, spec.tick_size);
+    }
+    let Some(master_currency) = master_currency.filter(|c| !c.eq_ignore_ascii_case(&account.currency)) else {
+        return (info.tick_value, info.tick_size);
+    };
+    let tick_value =
+        currency::tick_value_in_account_currency(info.tick_value, Some(master_currency), &account.currency, &quotes());
+    (tick_value, info.tick_size)
+}
+
+/// Why opening `lots` more would breach the receiver's exposure ceilings,
+/// given the copied positions it already holds. Exposure comes from the
+/// EA's position file, so opens dispatched before it catches up aren't
+/// counted yet.
+fn exposure_limit_reason(receiver: &ReceiverConfig, open: &[ReceiverPosition], lots: f64) -> Option<String> {
+    if let Some(max) = receiver.max_open_positions {
+        if open.len() >= max as usize {
+            return Some(format!(
+                "Exposure limit: {} copied positions open (max {})",
+                open.len(), max
+            ));
+        }
+    }
+    if let Some(max) = receiver.max_total_lots {
+        let total: f64 = open.iter().map(|p| p.volume).sum();
+        if total + lots > max + 1e-9 {
+            return Some(format!(
+                "Exposure limit: {:.2} open + {:.2} lots would exceed {:.2} lots",
+                total, lots, max
+            ));
+        }
+    }
+    None
+}
+
+/// Record a blocked execution for audit trail
+fn record_blocked_execution(
+    event: &TradeEvent,
+    receiver: &ReceiverConfig,
+    reason: &str,
+    state: Arc<Mutex<CopierState>>,
+) {
+    record_skipped_execution(event, receiver, "blocked", reason, state);
+}
+
+/// Record an execution that was not attempted, with `status` ("blocked",
+/// "stale", "exposure_limit", "rejected_manual", "sampled_out") and the reason
+fn record_skipped_execution(
     event: &TradeEvent,
-    receiver: &super::ReceiverConfig,
+    receiver: &ReceiverConfig,
+    status: &str,
     reason: &str,
     state: Arc<Mutex<CopierState>>,
 ) {
@@ -254,23 +1976,23 @@ fn record_blocked_execution(
         master_price: event.price,
         executed_price: None,
         slippage_pips: None,
-        status: "blocked".to_string(),
+        status: status.to_string(),
         error_message: Some(reason.to_string()),
         receiver_account: receiver.account_number.clone(),
-        master_position_id: Some(deal),
+        master_position_id: Some(position_id_of(event)),
         receiver_position_id: None,
         idempotency_key: Some(format!("{}:{}:{}", term, deal, event.event_type)),
         master_account_number: event.master_account_number.clone(),
+        intended_lots: None,
+        requested_lots: None,
+        lot_adjustment: None,
+        latency_ms: None,
+        detection_ms: None,
+        ea_roundtrip_ms: None,
     };
 
-    // Best-effort: blocked executions also flow to cloud (status will normalize to "skipped")
-    let _ = exec_sync::queue_for_upload(&execution);
-    
-    let mut copier = state.lock();
-    copier.recent_executions.insert(0, execution);
-    if copier.recent_executions.len() > 100 {
-        copier.recent_executions.pop();
-    }
+    // Skipped executions also flow to cloud (status will normalize to "skipped")
+    store_execution(execution, &state);
 }
 
 /// Get cached account info for a terminal
@@ -284,7 +2006,7 @@ pub fn get_cached_account_info(terminal_id: &str) -> Option<lot_calculator::Acco
         if terminal.terminal_id == terminal_id {
             let info_file = format!("{}\\MQL5\\Files\\CopierAccountInfo.json", terminal.path);
             
-            if let Ok(content) = std::fs::read_to_string(&info_file) {
+            if let Ok(content) = file_lock::read_contended(Path::new(&info_file)) {
                 if let Ok(info) = serde_json::from_str::<lot_calculator::AccountInfo>(&content) {
                     return Some(info);
                 }
@@ -300,7 +2022,7 @@ pub fn get_cached_account_info(terminal_id: &str) -> Option<lot_calculator::Acco
             appdata, terminal_id
         );
         
-        if let Ok(content) = std::fs::read_to_string(&info_file) {
+        if let Ok(content) = file_lock::read_contended(Path::new(&info_file)) {
             if let Ok(info) = serde_json::from_str::<lot_calculator::AccountInfo>(&content) {
                 return Some(info);
             }
@@ -309,3 +2031,1103 @@ pub fn get_cached_account_info(terminal_id: &str) -> Option<lot_calculator::Acco
     
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::{test_event, test_receiver};
+    use crate::copier::{MasterConfig, PartialCloseData};
+
+    fn partial_close_event(ticket: i64, closed: f64, remaining: f64) -> TradeEvent {
+        TradeEvent {
+            event_type: "partial_close".to_string(),
+            ticket,
+            deal_id: Some(9001),
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            lots: closed,
+            price: 1.1,
+            sl: None,
+            tp: None,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            sl_distance_points: None,
+            tp_distance_points: None,
+            master_balance: None,
+            master_equity: None,
+            tick_value: None,
+            contract_size: None,
+            digits: None,
+            point: None,
+            terminal_id: None,
+            master_account_number: None,
+            idempotency_key: None,
+            partial_close_data: Some(PartialCloseData {
+                closed_volume: closed,
+                remaining_volume: remaining,
+            }),
+            order_type: None,
+            order_ticket: None,
+            detected_at: None,
+        }
+    }
+
+    fn receiver_position(position_id: i64, master_position_id: i64, volume: f64) -> ReceiverPosition {
+        ReceiverPosition {
+            position_id,
+            master_position_id,
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            volume,
+            sl: None,
+            tp: None,
+            magic: None,
+        }
+    }
+
+    #[test]
+    fn test_partial_close_command_scales_receiver_volume() {
+        // Master closes half of 1.0 lots; receiver holds 2.0 lots
+        let event = partial_close_event(100, 0.5, 0.5);
+        let positions = vec![receiver_position(555, 99, 0.3), receiver_position(777, 100, 2.0)];
+
+        let commands = build_partial_close_commands(&event, &positions).unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command_type, "partial_close");
+        assert_eq!(commands[0].position_id, Some(777));
+        assert!((commands[0].volume.unwrap() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_partial_close_reduces_partial_fill_remainder_too() {
+        // The entry filled 0.6 and its queued remainder 0.4: both halve
+        let event = partial_close_event(100, 0.5, 0.5);
+        let positions = vec![receiver_position(555, 100, 0.6), receiver_position(556, 100, 0.4)];
+
+        let commands = build_partial_close_commands(&event, &positions).unwrap();
+        let closes: Vec<_> = commands.iter().map(|c| (c.position_id, c.volume)).collect();
+        assert_eq!(closes, vec![(Some(555), Some(0.3)), (Some(556), Some(0.2))]);
+    }
+
+    #[test]
+    fn test_partial_close_command_requires_mapped_position() {
+        let event = partial_close_event(100, 0.5, 0.5);
+        let positions = vec![receiver_position(555, 99, 1.0)];
+
+        assert!(build_partial_close_commands(&event, &positions).is_err());
+    }
+
+    fn trade_event(event_type: &str, ticket: i64) -> TradeEvent {
+        TradeEvent {
+            event_type: event_type.to_string(),
+            deal_id: Some(ticket),
+            partial_close_data: None,
+            ..partial_close_event(ticket, 0.1, 0.0)
+        }
+    }
+
+    fn pending_event(event_type: &str, order_type: Option<&str>, order_ticket: i64) -> TradeEvent {
+        TradeEvent {
+            price: 1.095,
+            sl: Some(1.09),
+            tp: Some(1.11),
+            deal_id: None,
+            terminal_id: Some("M1".to_string()),
+            order_type: order_type.map(String::from),
+            order_ticket: Some(order_ticket),
+            ..trade_event(event_type, 4242)
+        }
+    }
+
+    #[test]
+    fn test_buy_limit_produces_pending_order_command() {
+        let event = pending_event("pending_order", Some("buy_limit"), 31337);
+
+        let cmd = build_pending_order_command(&event, "EURUSD.r", 0.3, SltpPolicy::Copy).unwrap();
+        assert_eq!(cmd.command_type, "place_pending");
+        assert_eq!(cmd.order_type.as_deref(), Some("buy_limit"));
+        assert_eq!(cmd.direction.as_deref(), Some("buy"));
+        assert_eq!(cmd.symbol.as_deref(), Some("EURUSD.r"));
+        assert_eq!(cmd.price, Some(1.095));
+        assert_eq!(cmd.volume, Some(0.3));
+        assert_eq!((cmd.sl, cmd.tp), (Some(1.09), Some(1.11)));
+        assert_eq!(cmd.master_position_id, Some(31337));
+
+        // Keyed by order ticket; the cancel of the same order gets its own key
+        assert_eq!(idempotency_key(&event), "M1:31337:pending_order");
+        let cancel = pending_event("pending_cancelled", None, 31337);
+        assert_eq!(idempotency_key(&cancel), "M1:31337:pending_cancelled");
+
+        let bad = pending_event("pending_order", Some("buy_stop_limit"), 31337);
+        assert!(build_pending_order_command(&bad, "EURUSD.r", 0.3, SltpPolicy::Copy).is_err());
+    }
+
+    #[test]
+    fn test_pending_cancel_removes_mirrored_order() {
+        let placed = build_pending_order_command(
+            &pending_event("pending_order", Some("sell_stop"), 777),
+            "EURUSD",
+            0.1,
+            SltpPolicy::Copy,
+        )
+        .unwrap();
+        let cancel = build_pending_cancel_command(&pending_event("pending_cancelled", None, 777));
+        assert_eq!(cancel.command_type, "cancel_pending");
+        assert_eq!(cancel.master_position_id, placed.master_position_id);
+
+        // Cancels still reach a disabled receiver; new pending orders don't
+        let mut receiver = throttled_receiver(1);
+        receiver.enabled = false;
+        assert!(disabled_reason(&pending_event("pending_order", Some("sell_stop"), 777), &receiver).is_some());
+        assert!(disabled_reason(&pending_event("pending_cancelled", None, 777), &receiver).is_none());
+    }
+
+    fn throttled_receiver(max_entries_per_minute: u32) -> ReceiverConfig {
+        ReceiverConfig {
+            max_entries_per_minute: Some(max_entries_per_minute),
+            ..test_receiver()
+        }
+    }
+
+    #[test]
+    fn test_entries_over_limit_are_deferred_not_executed() {
+        let receiver = throttled_receiver(3);
+        let mut throttles = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+
+        let admissions: Vec<Admission> = (1..=5)
+            .map(|ticket| throttle_entry(&mut throttles, &mut queue, &trade_event("entry", ticket), &receiver, now))
+            .collect();
+
+        let admitted = admissions.iter().filter(|a| **a == Admission::Admitted).count();
+        assert_eq!(admitted, 3);
+        assert_eq!(&admissions[3..], &[Admission::Deferred, Admission::Deferred]);
+
+        // The excess sits in the queue waiting for a token, not executed
+        assert_eq!(queue.pending_count(), 2);
+        assert!(queue.dequeue_ready(Utc::now()).is_none());
+        let later = Utc::now() + chrono::Duration::minutes(2);
+        let first = queue.dequeue_ready(later).unwrap();
+        let second = queue.dequeue_ready(later).unwrap();
+        assert_eq!((first.event.ticket, second.event.ticket), (4, 5));
+        assert_eq!(first.receiver_id, "R1");
+        assert_eq!(first.defer_reason.as_deref(), Some(THROTTLE_REASON));
+    }
+
+    #[test]
+    fn test_changed_limit_applies_to_existing_bucket() {
+        let mut throttles = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+        let admit = |throttles: &mut HashMap<String, TokenBucket>, queue: &mut ExecutionQueue, receiver, ticket| {
+            throttle_entry(throttles, queue, &trade_event("entry", ticket), receiver, now)
+        };
+
+        let strict = throttled_receiver(1);
+        assert_eq!(admit(&mut throttles, &mut queue, &strict, 1), Admission::Admitted);
+        assert_eq!(admit(&mut throttles, &mut queue, &strict, 2), Admission::Deferred);
+
+        // Raising the limit adds capacity without refilling what was used
+        let relaxed = throttled_receiver(3);
+        assert_eq!(admit(&mut throttles, &mut queue, &relaxed, 3), Admission::Deferred);
+        assert_eq!(throttles["R1"].limit(), 3);
+
+        // Lowering it caps the tokens left
+        let later = now + Duration::from_secs(60);
+        assert!(check_entry_throttle(&mut throttles, &trade_event("entry", 4), &strict, later).is_ok());
+        assert!(check_entry_throttle(&mut throttles, &trade_event("entry", 5), &strict, later).is_err());
+    }
+
+    #[test]
+    fn test_closes_are_never_throttled() {
+        let receiver = throttled_receiver(1);
+        let mut throttles = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+
+        assert_eq!(throttle_entry(&mut throttles, &mut queue, &trade_event("entry", 1), &receiver, now), Admission::Admitted);
+        assert_eq!(throttle_entry(&mut throttles, &mut queue, &trade_event("entry", 2), &receiver, now), Admission::Deferred);
+        for (ticket, event_type) in [(1, "exit"), (3, "partial_close"), (4, "modify")] {
+            let admission = throttle_entry(&mut throttles, &mut queue, &trade_event(event_type, ticket), &receiver, now);
+            assert_eq!(admission, Admission::Admitted);
+        }
+        assert_eq!(queue.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_paused_receiver_still_executes_closes() {
+        let account = "paused-close-test-2002";
+        let mut paused = safety::get_receiver_state(account);
+        paused.is_safety_paused = true;
+        paused.pause_reason = Some("Daily loss limit reached".to_string());
+        paused.last_reset_date =
+            Some(safety::get_trading_day(Utc::now(), safety::get_daily_reset_hour()).format("%Y-%m-%d").to_string());
+        safety::update_receiver_state(account, paused);
+        let receiver = ReceiverConfig {
+            account_number: account.to_string(),
+            ..throttled_receiver(10)
+        };
+        let allowed = |event_type: &str, receiver: &ReceiverConfig| {
+            let result = event_safety(&trade_event(event_type, 1), account, &receiver_safety_config(receiver), 10000.0, false);
+            !matches!(result, safety::SafetyCheckResult::Blocked(_))
+        };
+
+        assert!(!allowed("entry", &receiver));
+        assert!(!allowed("pending_order", &receiver));
+        for event_type in ["exit", "partial_close", "modify"] {
+            assert!(allowed(event_type, &receiver), "{} was blocked", event_type);
+        }
+        let loosening = event_safety(&trade_event("modify", 1), account, &receiver_safety_config(&receiver), 10000.0, true);
+        assert!(matches!(loosening, safety::SafetyCheckResult::Blocked(_)));
+
+        let full_stop = ReceiverConfig {
+            block_closes_when_paused: true,
+            ..receiver
+        };
+        assert!(!allowed("exit", &full_stop));
+        safety::clear_receiver_state(account);
+    }
+
+    fn debounced_receiver(signal_debounce: SignalDebounce) -> ReceiverConfig {
+        ReceiverConfig {
+            max_entries_per_minute: None,
+            min_signal_interval_secs: Some(10),
+            signal_debounce,
+            ..throttled_receiver(1)
+        }
+    }
+
+    #[test]
+    fn test_rapid_second_entry_is_dropped() {
+        let receiver = debounced_receiver(SignalDebounce::Drop);
+        let mut last_entries = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+
+        assert_eq!(debounce_entry(&mut last_entries, &mut queue, &trade_event("entry", 1), &receiver, now), None);
+        // The close of the first position and a flip back in 3s later
+        assert_eq!(debounce_entry(&mut last_entries, &mut queue, &trade_event("exit", 1), &receiver, now), None);
+        let second = debounce_entry(
+            &mut last_entries,
+            &mut queue,
+            &trade_event("entry", 2),
+            &receiver,
+            now + Duration::from_secs(3),
+        );
+        assert!(matches!(second, Some(Debounced::Dropped(reason)) if reason.contains("within 10s")));
+        assert_eq!(queue.pending_count(), 0);
+
+        // Other symbols and later entries are unaffected; the dropped entry
+        // didn't restart the interval
+        let other_symbol = TradeEvent {
+            symbol: "GBPUSD".to_string(),
+            ..trade_event("entry", 3)
+        };
+        let later = now + Duration::from_secs(11);
+        assert_eq!(debounce_entry(&mut last_entries, &mut queue, &other_symbol, &receiver, now), None);
+        assert_eq!(debounce_entry(&mut last_entries, &mut queue, &trade_event("entry", 4), &receiver, later), None);
+    }
+
+    #[test]
+    fn test_rapid_second_entry_is_deferred() {
+        let receiver = debounced_receiver(SignalDebounce::Defer);
+        let mut last_entries = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+
+        assert_eq!(debounce_entry(&mut last_entries, &mut queue, &trade_event("entry", 1), &receiver, now), None);
+        let second = debounce_entry(
+            &mut last_entries,
+            &mut queue,
+            &trade_event("entry", 2),
+            &receiver,
+            now + Duration::from_secs(4),
+        );
+        assert_eq!(second, Some(Debounced::Deferred));
+
+        // Parked for the rest of the interval
+        assert!(queue.dequeue_ready(Utc::now()).is_none());
+        let deferred = queue.dequeue_ready(Utc::now() + chrono::Duration::seconds(7)).unwrap();
+        assert_eq!(deferred.event.ticket, 2);
+        assert_eq!(deferred.defer_reason.as_deref(), Some(DEBOUNCE_REASON));
+        assert!(check_signal_interval(&mut last_entries, &deferred.event, &receiver, now + Duration::from_secs(10)).is_ok());
+    }
+
+    #[test]
+    fn test_close_cancels_debounced_open() {
+        let receiver = debounced_receiver(SignalDebounce::Defer);
+        let mut last_entries = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Instant::now();
+        debounce_entry(&mut last_entries, &mut queue, &trade_event("entry", 1), &receiver, now);
+        let deferred = debounce_entry(&mut last_entries, &mut queue, &trade_event("entry", 2), &receiver, now);
+        assert_eq!(deferred, Some(Debounced::Deferred));
+
+        // A partial close still waits for the open
+        let at = Utc::now();
+        let partial = trade_event("partial_close", 2);
+        assert_eq!(defer_until_open_completes(&mut queue, &partial, &receiver, at), Some(Admission::Deferred));
+        assert_eq!(queue.pending_count(), 2);
+
+        // The master closes before the interval passes: the open is dropped
+        // and the parked close becomes a no-op rather than following a replay
+        let close = trade_event("exit", 2);
+        assert_eq!(defer_until_open_completes(&mut queue, &close, &receiver, at), Some(Admission::Deferred));
+        assert!(queue.find(|e| is_opening_event(&e.event) && e.status == QueueStatus::Pending).is_none());
+        assert_eq!(
+            open_gate(&queue, &close, &receiver.terminal_id, at, at),
+            OpenGate::OpenFailed(MASTER_CLOSED_BEFORE_OPEN.to_string())
+        );
+    }
+
+    #[test]
+    fn test_close_waits_for_queued_open() {
+        let receiver = throttled_receiver(1);
+        let mut throttles = HashMap::new();
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+
+        // Position 1 opens live; position 5's open is throttled into the queue
+        throttle_entry(&mut throttles, &mut queue, &trade_event("entry", 1), &receiver, Instant::now());
+        let admission = throttle_entry(&mut throttles, &mut queue, &trade_event("entry", 5), &receiver, Instant::now());
+        assert_eq!(admission, Admission::Deferred);
+
+        // Its close arrives first and is parked behind it; position 1's goes straight through
+        let close = trade_event("exit", 5);
+        assert_eq!(defer_until_open_completes(&mut queue, &close, &receiver, now), Some(Admission::Deferred));
+        assert!(defer_until_open_completes(&mut queue, &trade_event("exit", 1), &receiver, now).is_none());
+
+        // The close comes up first but has to wait for the open
+        let parked = queue.dequeue_ready(now + chrono::Duration::seconds(1)).unwrap();
+        assert_eq!(parked.event.event_type, "exit");
+        assert_eq!(parked.defer_reason.as_deref(), Some(OPEN_WAIT_REASON));
+        assert_eq!(open_gate(&queue, &parked.event, &parked.receiver_id, parked.detected_at, now), OpenGate::Wait);
+        queue.requeue(&parked.id, now + chrono::Duration::seconds(1), OPEN_WAIT_REASON);
+
+        // Once both are ready the open runs first, then the close
+        let later = now + chrono::Duration::seconds(61);
+        let open = queue.dequeue_ready(later).unwrap();
+        assert_eq!(open.event.event_type, "entry");
+        assert_eq!(open_gate(&queue, &close, &receiver.terminal_id, later, later), OpenGate::Wait);
+        queue.mark_completed(&open.id);
+        let close_exec = queue.dequeue_ready(later).unwrap();
+        assert_eq!(close_exec.event.event_type, "exit");
+        assert_eq!(open_gate(&queue, &close_exec.event, &close_exec.receiver_id, later, later), OpenGate::Ready);
+    }
+
+    #[test]
+    fn test_close_is_noop_when_open_never_completes() {
+        let receiver = throttled_receiver(10);
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+        let close = trade_event("exit", 9);
+
+        // Open still queued after the timeout
+        queue.defer(
+            QueuedExecution::new(trade_event("entry", 9), &receiver.terminal_id, "k9"),
+            now,
+            THROTTLE_REASON,
+        );
+        let timeout = chrono::Duration::from_std(OPEN_WAIT_TIMEOUT).unwrap();
+        assert_eq!(open_gate(&queue, &close, &receiver.terminal_id, now - timeout, now), OpenGate::TimedOut);
+        assert_eq!(queue.cancel_pending(|e| is_open_of(e, &receiver.terminal_id, &close), "closed"), 1);
+        assert_eq!(queue.pending_count(), 0);
+
+        // A cancelled (or failed) open makes the close a no-op
+        assert!(matches!(open_gate(&queue, &close, &receiver.terminal_id, now, now), OpenGate::OpenFailed(_)));
+        assert!(open_failed_reason(&queue, &close, &receiver.terminal_id).is_some());
+        // Other receivers and positions are unaffected
+        assert_eq!(open_gate(&queue, &close, "R2", now, now), OpenGate::Ready);
+        assert_eq!(open_gate(&queue, &trade_event("exit", 10), &receiver.terminal_id, now, now), OpenGate::Ready);
+    }
+
+    #[test]
+    fn test_close_keeps_waiting_on_in_flight_open() {
+        let receiver = throttled_receiver(10);
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+        let close = trade_event("exit", 9);
+        queue.defer(
+            QueuedExecution::new(trade_event("entry", 9), &receiver.terminal_id, "k9"),
+            now,
+            THROTTLE_REASON,
+        );
+
+        // The open was picked up for execution, so the close can't time out and cancel it
+        let open = queue.dequeue_ready(now).unwrap();
+        let timeout = chrono::Duration::from_std(OPEN_WAIT_TIMEOUT).unwrap();
+        assert_eq!(open_gate(&queue, &close, &receiver.terminal_id, now - timeout, now), OpenGate::Wait);
+
+        // Once it lands the close goes through
+        queue.mark_completed(&open.id);
+        assert_eq!(open_gate(&queue, &close, &receiver.terminal_id, now - timeout, now), OpenGate::Ready);
+    }
+
+    #[test]
+    fn test_saturday_entry_deferred_to_sunday_open() {
+        let receiver = throttled_receiver(10);
+        let calendar = market_hours::MarketCalendar::default();
+        let mut queue = ExecutionQueue::new(None);
+        let saturday = chrono::DateTime::parse_from_rfc3339("2024-01-06T12:00:00Z").unwrap().with_timezone(&Utc);
+        let sunday_open = chrono::DateTime::parse_from_rfc3339("2024-01-07T22:00:00Z").unwrap().with_timezone(&Utc);
+
+        let admission = defer_if_market_closed(&mut queue, &trade_event("entry", 1), &receiver, &calendar, saturday);
+        assert_eq!(admission, Some(Admission::Deferred));
+        // Closes queue for the open as well
+        let admission = defer_if_market_closed(&mut queue, &trade_event("exit", 2), &receiver, &calendar, saturday);
+        assert_eq!(admission, Some(Admission::Deferred));
+
+        assert!(queue.dequeue_ready(sunday_open - chrono::Duration::seconds(1)).is_none());
+        let entry = queue.dequeue_ready(sunday_open).unwrap();
+        assert_eq!(entry.event.event_type, "entry");
+        assert_eq!(entry.defer_reason.as_deref(), Some(MARKET_CLOSED_REASON));
+        assert_eq!(entry.attempts, 1);
+
+        // Open market: nothing deferred
+        assert!(defer_if_market_closed(&mut queue, &trade_event("entry", 3), &receiver, &calendar, sunday_open).is_none());
+    }
+
+    #[test]
+    fn test_entry_over_cost_limit_is_deferred() {
+        let tick = ticks::SymbolTick {
+            bid: 1.10000,
+            ask: 1.10020,
+            point: 0.00001,
+            digits: 5,
+        };
+        // 2.0 pips spread + 1.5 expected slippage against a 3 pip cap
+        let reason = entry_cost_reason(3.0, Some(&tick), Some(1.5)).unwrap();
+        assert!(reason.contains("Entry cost 3.5 pips"), "{}", reason);
+        // Within the cap, negative slippage doesn't offset the spread, no tick means no check
+        assert!(entry_cost_reason(4.0, Some(&tick), Some(1.5)).is_none());
+        assert!(entry_cost_reason(3.0, Some(&tick), Some(-5.0)).is_none());
+        assert!(entry_cost_reason(1.0, None, Some(1.5)).is_none());
+
+        let receiver = ReceiverConfig {
+            max_entry_cost_pips: Some(3.0),
+            ..throttled_receiver(10)
+        };
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+        let admission = defer_for_entry_cost(&mut queue, &trade_event("entry", 1), &receiver, Some(&reason), now);
+        assert_eq!(admission, Some(Admission::Deferred));
+        assert!(defer_for_entry_cost(&mut queue, &trade_event("entry", 2), &receiver, None, now).is_none());
+
+        assert!(queue.dequeue_ready(now).is_none());
+        let exec = queue.dequeue_ready(now + chrono::Duration::from_std(ENTRY_COST_RETRY).unwrap()).unwrap();
+        assert_eq!(exec.defer_reason.as_deref(), Some(ENTRY_COST_REASON));
+        assert_eq!(exec.event.ticket, 1);
+    }
+
+    #[test]
+    fn test_copy_delay_schedules_with_bounded_jitter() {
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+
+        // No delay configured: runs now
+        let receiver = throttled_receiver(10);
+        assert!(defer_for_copy_delay(&mut queue, &trade_event("entry", 1), &receiver, 7, now).is_none());
+
+        let receiver = ReceiverConfig {
+            copy_delay_ms: 500,
+            copy_delay_jitter_ms: 1000,
+            ..throttled_receiver(10)
+        };
+        for (ticket, roll) in [(2, 0), (3, 250), (4, 1000), (5, 1001), (6, u64::MAX)] {
+            let admission = defer_for_copy_delay(&mut queue, &trade_event("entry", ticket), &receiver, roll, now);
+            assert_eq!(admission, Some(Admission::Deferred));
+        }
+        let scheduled: Vec<i64> = std::iter::from_fn(|| queue.dequeue_ready(now + chrono::Duration::seconds(2)))
+            .map(|exec| {
+                assert_eq!(exec.defer_reason.as_deref(), Some(COPY_DELAY_REASON));
+                (exec.next_retry_at.unwrap() - now).num_milliseconds()
+            })
+            .collect();
+        assert_eq!(scheduled.len(), 5);
+        assert_eq!(&scheduled[..4], &[500, 750, 1500, 500]);
+        assert!((500..=1500).contains(&scheduled[4]));
+
+        // Not due before the fixed delay
+        let mut queue = ExecutionQueue::new(None);
+        defer_for_copy_delay(&mut queue, &trade_event("entry", 7), &receiver, 0, now);
+        assert!(queue.dequeue_ready(now + chrono::Duration::milliseconds(499)).is_none());
+        assert!(queue.dequeue_ready(now + chrono::Duration::milliseconds(500)).is_some());
+    }
+
+    #[test]
+    fn test_partial_fill_recorded_and_remainder_queued() {
+        let mut execution: Execution = serde_json::from_value(serde_json::json!({
+            "id": "e1",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "event_type": "entry",
+            "symbol": "EURUSD",
+            "direction": "buy",
+            "master_lots": 1.0,
+            "receiver_lots": 0.5,
+            "master_price": 1.1,
+            "executed_price": 1.1,
+            "slippage_pips": 0.0,
+            "status": "success",
+            "error_message": null,
+            "receiver_account": "2001"
+        }))
+        .unwrap();
+
+        // Full fill leaves the execution alone
+        assert_eq!(record_fill(&mut execution.clone(), 0.5), None);
+
+        let remainder = record_fill(&mut execution, 0.3).unwrap();
+        assert!((remainder - 0.2).abs() < 1e-9);
+        assert_eq!(execution.receiver_lots, 0.3);
+        assert_eq!(execution.requested_lots, Some(0.5));
+
+        let receiver = throttled_receiver(10);
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+        assert!(defer_partial_fill_remainder(&mut queue, &trade_event("entry", 1), &receiver, remainder, Some(0.01), now));
+        // Under the broker minimum: not retried
+        assert!(!defer_partial_fill_remainder(&mut queue, &trade_event("entry", 2), &receiver, 0.005, Some(0.01), now));
+
+        let queued = queue.dequeue_ready(now).unwrap();
+        assert_eq!(queued.receiver_lots, Some(remainder));
+        assert_eq!(queued.defer_reason.as_deref(), Some(PARTIAL_FILL_REASON));
+        assert!(queued.idempotency_key.ends_with(":remainder"));
+        assert_eq!(queued.event.idempotency_key.as_deref(), Some(queued.idempotency_key.as_str()));
+        assert!(queue.dequeue_ready(now).is_none());
+    }
+
+    #[test]
+    fn test_crypto_entry_not_deferred_on_weekend() {
+        let receiver = throttled_receiver(10);
+        let calendar = market_hours::MarketCalendar::default();
+        let mut queue = ExecutionQueue::new(None);
+        let saturday = chrono::DateTime::parse_from_rfc3339("2024-01-06T12:00:00Z").unwrap().with_timezone(&Utc);
+
+        let entry = TradeEvent {
+            symbol: "BTCUSD".to_string(),
+            ..trade_event("entry", 1)
+        };
+        assert!(defer_if_market_closed(&mut queue, &entry, &receiver, &calendar, saturday).is_none());
+        assert_eq!(queue.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_token_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_minute(2, start);
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+
+        let wait = bucket.try_take(start).unwrap_err();
+        assert!((wait.as_secs_f64() - 30.0).abs() < 0.01);
+        assert!(bucket.try_take(start + Duration::from_secs(30)).is_ok());
+    }
+
+    fn receivers(ids: &[&str]) -> Vec<ReceiverConfig> {
+        ids.iter()
+            .map(|id| ReceiverConfig {
+                terminal_id: id.to_string(),
+                ..throttled_receiver(10)
+            })
+            .collect()
+    }
+
+    /// Fan out to `receivers`, each "execution" writing its command file and
+    /// then waiting (up to `wait`) for every receiver's file to appear before
+    /// "responding". Returns how many command files each receiver saw.
+    fn command_files_seen(receivers: &[ReceiverConfig], strategy: &ExecutionStrategy, wait: Duration) -> HashMap<String, usize> {
+        let dir = std::env::temp_dir().join(format!("saturn_dispatch_test_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let seen = Mutex::new(HashMap::new());
+        let count = || std::fs::read_dir(&dir).unwrap().count();
+
+        let refs: Vec<&ReceiverConfig> = receivers.iter().collect();
+        dispatch_receivers(&refs, strategy, |receiver| {
+            std::fs::write(dir.join(format!("{}.json", receiver.terminal_id)), "{}").unwrap();
+            let deadline = Instant::now() + wait;
+            while count() < receivers.len() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            seen.lock().insert(receiver.terminal_id.clone(), count());
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+        seen.into_inner()
+    }
+
+    #[test]
+    fn test_parallel_dispatch_writes_command_files_concurrently() {
+        let receivers = receivers(&["R1", "R2", "R3"]);
+
+        // Every receiver's command went out before any of them "responded"
+        let parallel = command_files_seen(&receivers, &ExecutionStrategy::Parallel, Duration::from_secs(5));
+        assert_eq!(parallel.len(), 3);
+        assert!(parallel.values().all(|&n| n == 3));
+
+        // Sequential waits for each response before the next command
+        let sequential = command_files_seen(&receivers, &ExecutionStrategy::Sequential, Duration::from_millis(50));
+        assert_eq!(sequential["R1"], 1);
+        assert_eq!(sequential["R2"], 2);
+
+        // The primary goes out alone, the rest together after it
+        let strategy = ExecutionStrategy::PriorityOrdered {
+            primary_terminal_id: "R2".to_string(),
+        };
+        let ordered = command_files_seen(&receivers, &strategy, Duration::from_millis(50));
+        assert_eq!(ordered["R2"], 1);
+        assert_eq!(ordered["R1"], 3);
+        assert_eq!(ordered["R3"], 3);
+    }
+
+    #[test]
+    fn test_disabled_receiver_gets_no_open_commands() {
+        let mut all = receivers(&["R1", "R2"]);
+        all[1].enabled = false;
+        let entry = trade_event("entry", 1);
+
+        let admitted: Vec<ReceiverConfig> = all
+            .iter()
+            .filter(|r| disabled_reason(&entry, r).is_none())
+            .cloned()
+            .collect();
+        let seen = command_files_seen(&admitted, &ExecutionStrategy::Sequential, Duration::from_millis(10));
+        assert!(seen.contains_key("R1"));
+        assert!(!seen.contains_key("R2"));
+
+        // Existing positions are still closed / modified / partially closed
+        for event_type in ["exit", "partial_close", "modify"] {
+            assert!(disabled_reason(&trade_event(event_type, 1), &all[1]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_close_only_symbol_rejects_entries_but_not_closes() {
+        let spec = symbol_catalog::SymbolSpec {
+            name: "EURUSD".to_string(),
+            normalized_key: "EURUSD".to_string(),
+            tick_value: 1.0,
+            tick_size: 0.00001,
+            contract_size: 100000.0,
+            digits: 5,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 10.0,
+            description: None,
+            trade_mode: Some("close_only".to_string()),
+            profit_currency: None,
+        };
+
+        let reason = symbol_disabled_reason(&trade_event("entry", 1), Some(&spec)).unwrap();
+        assert!(reason.contains("close_only"));
+        assert!(symbol_disabled_reason(&trade_event("pending_order", 1), Some(&spec)).is_some());
+        for event_type in ["exit", "partial_close", "modify"] {
+            assert!(symbol_disabled_reason(&trade_event(event_type, 1), Some(&spec)).is_none());
+        }
+
+        // Unknown symbol or full trading: the entry goes ahead
+        assert!(symbol_disabled_reason(&trade_event("entry", 1), None).is_none());
+        let full = symbol_catalog::SymbolSpec {
+            trade_mode: Some("full".to_string()),
+            ..spec
+        };
+        assert!(symbol_disabled_reason(&trade_event("entry", 1), Some(&full)).is_none());
+    }
+
+    /// Event types of a mixed stream that a receiver in `copy_mode` copies
+    fn copied_in_mode(copy_mode: CopyMode) -> Vec<&'static str> {
+        let receiver = ReceiverConfig {
+            copy_mode,
+            ..receivers(&["R1"]).remove(0)
+        };
+        ["entry", "modify", "partial_close", "pending_order", "exit"]
+            .into_iter()
+            .filter(|event_type| copy_mode_reason(&trade_event(event_type, 1), &receiver).is_none())
+            .collect()
+    }
+
+    #[test]
+    fn test_full_copy_mode_copies_every_event() {
+        assert_eq!(copied_in_mode(CopyMode::Full), vec!["entry", "modify", "partial_close", "pending_order", "exit"]);
+    }
+
+    #[test]
+    fn test_entries_only_mode_skips_closes() {
+        assert_eq!(copied_in_mode(CopyMode::EntriesOnly), vec!["entry", "modify", "pending_order"]);
+    }
+
+    #[test]
+    fn test_exits_only_mode_skips_opens() {
+        assert_eq!(copied_in_mode(CopyMode::ExitsOnly), vec!["modify", "partial_close", "exit"]);
+
+        let receiver = ReceiverConfig {
+            copy_mode: CopyMode::ExitsOnly,
+            ..receivers(&["R1"]).remove(0)
+        };
+        let config = CopierConfig {
+            version: 1,
+            config_hash: "h".to_string(),
+            master: MasterConfig {
+                account_id: "m".to_string(),
+                account_number: "1".to_string(),
+                broker: "B".to_string(),
+                terminal_id: "M1".to_string(),
+            },
+            receivers: vec![receiver],
+            masters: vec![],
+            execution_strategy: ExecutionStrategy::Sequential,
+        };
+        let route = &process_event_dry_run(&trade_event("entry", 1), &config)[0];
+        assert_eq!(route.outcome, COPY_MODE_STATUS);
+    }
+
+    #[test]
+    fn test_receiver_enabled_by_default() {
+        let receiver: ReceiverConfig = serde_json::from_value(serde_json::json!({
+            "account_id": "a",
+            "account_number": "1",
+            "broker": "B",
+            "terminal_id": "T",
+            "risk_mode": "fixed_lot",
+            "risk_value": 0.1,
+            "max_slippage_pips": 3.0,
+            "max_daily_loss_r": null,
+            "prop_firm_safe_mode": false,
+            "symbol_mappings": []
+        }))
+        .unwrap();
+        assert!(receiver.enabled);
+    }
+
+    fn event_at(event_type: &str, timestamp: &str) -> TradeEvent {
+        TradeEvent {
+            timestamp: timestamp.to_string(),
+            ..test_event(event_type, 1)
+        }
+    }
+
+    #[test]
+    fn test_stale_entries_are_skipped() {
+        let now = Utc::now();
+        let receiver = ReceiverConfig {
+            max_event_age_secs: Some(300),
+            ..throttled_receiver(10)
+        };
+
+        let fresh = event_at("entry", &(now - chrono::Duration::seconds(5)).to_rfc3339());
+        assert!(stale_entry_reason(&fresh, &receiver, now, None).is_none());
+
+        // Master EA format (second precision, Z suffix)
+        let old = now - chrono::Duration::hours(2);
+        let stale = event_at("entry", &old.format("%Y-%m-%dT%H:%M:%SZ").to_string());
+        let reason = stale_entry_reason(&stale, &receiver, now, None).unwrap();
+        assert!(reason.contains("limit 300s"));
+
+        // Closes always go through, however old
+        let old_exit = event_at("exit", &old.to_rfc3339());
+        assert!(stale_entry_reason(&old_exit, &receiver, now, None).is_none());
+
+        // Master clock 10 minutes ahead: the "5s old" entry is really stale
+        let reason = stale_entry_reason(&fresh, &receiver, now, Some(600)).unwrap();
+        assert!(reason.contains("605s old"));
+
+        // No limit configured
+        assert!(stale_entry_reason(&stale, &throttled_receiver(10), now, None).is_none());
+    }
+
+    #[test]
+    fn test_event_timestamp_formats() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(3, 4, 5)
+            .unwrap()
+            .and_utc();
+        for timestamp in [
+            "2024-01-02T03:04:05Z",
+            "2024-01-02T05:04:05+02:00",
+            "2024-01-02T03:04:05",
+            " 2024-01-02 03:04:05.000 ",
+            "2024.01.02 03:04:05",
+        ] {
+            assert_eq!(parse_event_timestamp(timestamp), Some(expected), "{}", timestamp);
+        }
+        assert!(parse_event_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_jpy_quoted_tick_value_is_not_converted_twice() {
+        // USD master and USD receiver on USDJPY: both tick values are already USD
+        let master_info = lot_calculator::SymbolInfo {
+            tick_value: 0.6667,
+            tick_size: 0.001,
+            point: 0.001,
+            digits: 3,
+            ..Default::default()
+        };
+        let spec = symbol_catalog::SymbolSpec {
+            name: "USDJPY".to_string(),
+            normalized_key: "USDJPY".to_string(),
+            tick_value: 0.6667,
+            tick_size: 0.001,
+            contract_size: 100000.0,
+            digits: 3,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 100.0,
+            description: None,
+            trade_mode: None,
+            profit_currency: Some("JPY".to_string()),
+        };
+        let account = lot_calculator::AccountInfo {
+            balance: 10000.0,
+            equity: 10000.0,
+            currency: "USD".to_string(),
+            leverage: 100,
+        };
+        let quotes = || currency::Quotes::from([("USDJPY".to_string(), 150.0)]);
+
+        let from_spec = tick_value_for_account(&master_info, Some(&spec), Some("USD"), &account, quotes);
+        assert_eq!(from_spec, (0.6667, 0.001));
+        let from_master = tick_value_for_account(&master_info, None, Some("USD"), &account, quotes);
+        assert_eq!(from_master, (0.6667, 0.001));
+
+        // $100 on a 20 pip (0.200) stop: 200 ticks * $0.6667 per lot
+        let info = lot_calculator::SymbolInfo {
+            tick_value: from_spec.0,
+            ..master_info
+        };
+        let lots = lot_calculator::calculate_lots("risk_dollar", 100.0, 1.0, 150.0, Some(149.8), None, Some(&account), Some(&info));
+        assert_eq!(lots, 0.75);
+
+        // A EUR master's tick value is converted into the receiver's USD
+        let quotes = || currency::Quotes::from([("EURUSD".to_string(), 1.25)]);
+        let (tick_value, _) = tick_value_for_account(&master_info, None, Some("EUR"), &account, quotes);
+        assert!((tick_value - 0.8334).abs() < 1e-4);
+    }
+
+    fn eurusd_tick(bid: f64, ask: f64) -> ticks::SymbolTick {
+        ticks::SymbolTick {
+            bid,
+            ask,
+            point: 0.00001,
+            digits: 5,
+        }
+    }
+
+    #[test]
+    fn test_entry_within_deviation_goes_through() {
+        let buy = event_at("entry", "2024-01-01T00:00:00Z");
+        // Ask 1.5 pips above the master's 1.1 entry, limit 2 pips
+        let tick = eurusd_tick(1.10005, 1.10015);
+        assert!(entry_deviation_reason(&buy, 2.0, Some(&tick)).is_none());
+
+        // No tick file yet: leave it to the EA's slippage check
+        assert!(entry_deviation_reason(&buy, 2.0, None).is_none());
+    }
+
+    #[test]
+    fn test_entry_beyond_deviation_is_refused() {
+        let buy = event_at("entry", "2024-01-01T00:00:00Z");
+        let tick = eurusd_tick(1.1003, 1.1004);
+        let reason = entry_deviation_reason(&buy, 2.0, Some(&tick)).unwrap();
+        assert!(reason.contains("4.0 pips"));
+
+        // Sells fill at the bid
+        let sell = TradeEvent {
+            direction: "sell".to_string(),
+            ..buy
+        };
+        assert!(entry_deviation_reason(&sell, 2.0, Some(&eurusd_tick(1.0997, 1.0998))).is_some());
+        assert!(entry_deviation_reason(&sell, 2.0, Some(&eurusd_tick(1.1, 1.1001))).is_none());
+    }
+
+    #[test]
+    fn test_open_beyond_position_count_limit_is_blocked() {
+        let receiver = ReceiverConfig {
+            max_open_positions: Some(3),
+            ..throttled_receiver(10)
+        };
+
+        // Opens 1-3 fit; the 4th would breach the limit
+        let mut open = Vec::new();
+        for n in 1..=3 {
+            assert!(exposure_limit_reason(&receiver, &open, 0.1).is_none(), "open {} blocked", n);
+            open.push(receiver_position(n, 100 + n, 0.1));
+        }
+        let reason = exposure_limit_reason(&receiver, &open, 0.1).unwrap();
+        assert!(reason.contains("3 copied positions open (max 3)"));
+
+        // A close frees a slot
+        open.pop();
+        assert!(exposure_limit_reason(&receiver, &open, 0.1).is_none());
+    }
+
+    #[test]
+    fn test_open_beyond_total_lots_limit_is_blocked() {
+        let receiver = ReceiverConfig {
+            max_total_lots: Some(1.0),
+            ..throttled_receiver(10)
+        };
+        let open = vec![receiver_position(1, 101, 0.5), receiver_position(2, 102, 0.3)];
+
+        assert!(exposure_limit_reason(&receiver, &open, 0.2).is_none());
+        assert!(exposure_limit_reason(&receiver, &open, 0.3).is_some());
+    }
+
+    #[test]
+    fn test_sampling_decision_is_consistent_per_position() {
+        let receiver = ReceiverConfig {
+            copy_fraction: Some(0.5),
+            ..throttled_receiver(10)
+        };
+
+        let mut copied = 0;
+        for ticket in 1..=1000 {
+            let open = is_sampled_out(&trade_event("entry", ticket), &receiver);
+            for event_type in ["exit", "partial_close", "modify"] {
+                let later = trade_event(event_type, ticket);
+                assert_eq!(is_sampled_out(&later, &receiver), open, "{} {}", event_type, ticket);
+            }
+            // A pending order samples the same as the position it fills into
+            assert_eq!(is_sampled_out(&pending_event("pending_order", Some("buy_limit"), ticket), &receiver), open);
+            if !open {
+                copied += 1;
+            }
+        }
+        assert!((400..=600).contains(&copied), "copied {} of 1000", copied);
+
+        assert!(!position_sampled_out(42, 1.0));
+        assert!(position_sampled_out(42, 0.0));
+        assert!(!is_sampled_out(&trade_event("entry", 42), &throttled_receiver(10)));
+    }
+
+    fn config_with(receivers: Vec<ReceiverConfig>) -> CopierConfig {
+        CopierConfig {
+            version: 1,
+            config_hash: String::new(),
+            master: MasterConfig {
+                account_id: "m".to_string(),
+                account_number: "1001".to_string(),
+                broker: "B".to_string(),
+                terminal_id: "MASTER".to_string(),
+            },
+            receivers,
+            masters: vec![],
+            execution_strategy: ExecutionStrategy::Sequential,
+        }
+    }
+
+    #[test]
+    fn test_sampled_out_entry_is_recorded_and_its_close_ignored() {
+        let receiver = ReceiverConfig {
+            copy_fraction: Some(0.0),
+            ..throttled_receiver(10)
+        };
+        let config = config_with(vec![receiver]);
+        let state = Arc::new(Mutex::new(CopierState::default()));
+
+        process_event(&trade_event("entry", 7), &config, state.clone());
+        process_event(&trade_event("exit", 7), &config, state.clone());
+
+        let executions = state.lock().recent_executions.clone();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].status, "sampled_out");
+        assert_eq!(executions[0].event_type, "entry");
+    }
+
+    #[test]
+    fn test_process_event_reports_each_receiver() {
+        let disabled = ReceiverConfig {
+            account_number: "results-disabled".to_string(),
+            enabled: false,
+            ..throttled_receiver(10)
+        };
+        let sampled_out = ReceiverConfig {
+            account_number: "results-sampled".to_string(),
+            copy_fraction: Some(0.0),
+            ..throttled_receiver(10)
+        };
+        let needs_approval = ReceiverConfig {
+            account_number: "results-approval".to_string(),
+            manual_confirm_mode: true,
+            ..throttled_receiver(10)
+        };
+        let config = config_with(vec![disabled, sampled_out, needs_approval]);
+        let state = Arc::new(Mutex::new(CopierState::default()));
+
+        let results = process_event(&trade_event("entry", 8), &config, state.clone());
+
+        let accounts: Vec<_> = results.iter().map(|r| r.receiver_account.as_str()).collect();
+        let configured: Vec<_> = config.receivers.iter().map(|r| r.account_number.as_str()).collect();
+        assert_eq!(accounts, configured);
+        let outcomes: Vec<_> = results.iter().map(|r| r.outcome.as_str()).collect();
+        assert_eq!(outcomes, ["disabled", "sampled_out", "awaiting_approval"]);
+        let approval_id = results[2].reason.clone().unwrap();
+        assert!(approvals::list_pending().iter().any(|a| a.id == approval_id));
+
+        // Still recorded in the shared state
+        let statuses: Vec<_> = state.lock().recent_executions.iter().map(|e| e.status.clone()).collect();
+        assert_eq!(statuses, ["sampled_out", "disabled"]);
+    }
+
+    #[test]
+    fn test_execution_carries_master_position_id() {
+        let sampled_out = ReceiverConfig {
+            account_number: "position-ids".to_string(),
+            copy_fraction: Some(0.0),
+            ..throttled_receiver(10)
+        };
+        let config = config_with(vec![sampled_out]);
+        let state = Arc::new(Mutex::new(CopierState::default()));
+
+        // The entry deal's id differs from the position it opens
+        let entry = TradeEvent { deal_id: Some(9001), ..trade_event("entry", 55) };
+        process_event(&entry, &config, state.clone());
+        // A pending order's position is its order ticket
+        process_event(&pending_event("pending_order", Some("buy_limit"), 31337), &config, state.clone());
+
+        let ids: Vec<_> = state.lock().recent_executions.iter().map(|e| e.master_position_id).collect();
+        assert_eq!(ids, [Some(31337), Some(55)]);
+    }
+
+    #[test]
+    fn test_processed_event_is_journaled_and_replays() {
+        let dir = std::env::temp_dir().join(format!("saturn_journal_test_{}", uuid::Uuid::new_v4()));
+        let journal = journal::Journal::new(Some(dir.clone()));
+        let sampled_out = ReceiverConfig {
+            copy_fraction: Some(0.0),
+            ..throttled_receiver(10)
+        };
+        let config = config_with(vec![sampled_out]);
+        let state = Arc::new(Mutex::new(CopierState::default()));
+        let entry = TradeEvent {
+            detected_at: Some("2024-01-01T00:00:01Z".to_string()),
+            ..trade_event("entry", 7)
+        };
+
+        process_event_journaled(&entry, &config, state, &journal);
+
+        let path = journal.path_for(Utc::now().date_naive()).unwrap();
+        let lines = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(lines.lines().count(), 1);
+
+        let replayed = journal::replay(&path, &config).unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].entry.event.ticket, 7);
+        assert_eq!(replayed[0].entry.event.detected_at.as_deref(), Some("2024-01-01T00:00:01Z"));
+        assert!(replayed[0].entry.routed_to.is_empty());
+        assert_eq!(replayed[0].routes.len(), 1);
+        assert_eq!(replayed[0].routes[0].outcome, "sampled_out");
+
+        // With sampling off the same event would be copied, sized as fixed lots
+        let copying = config_with(vec![ReceiverConfig {
+            risk_mode: "fixed_lot".to_string(),
+            risk_value: 0.3,
+            ..throttled_receiver(10)
+        }]);
+        let routes = process_event_dry_run(&replayed[0].entry.event, &copying);
+        assert_eq!(routes[0].outcome, "copy");
+        assert_eq!(routes[0].lots, Some(0.3));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,532 @@
+//! Execution queue for deferred and retryable receiver executions
+//!
+//! Entries that can't run right now (rate limited, transient failure) are
+//! parked here with a `next_retry_at` and re-driven by the queue worker in
+//! `event_processor::process_deferred`. The queue is persisted to
+//! `execution_queue.json` so deferred entries survive a restart.
+//!
+//! The queue is shared between the worker and the UI through
+//! [`SharedExecutionQueue`]; reads return snapshots and disk writes happen
+//! outside the queue lock.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tracing::info;
+
+use super::persistence;
+use super::trade_executor::{calculate_backoff_delay, RetryConfig};
+use super::TradeEvent;
+
+/// File to persist the queue
+const QUEUE_FILE: &str = "execution_queue.json";
+
+/// Number of completed executions kept for the UI
+const MAX_RECENT_COMPLETED: usize = 50;
+
+/// Default attempts before a queued execution is marked failed
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// One receiver's execution of a master event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedExecution {
+    pub id: String,
+    pub idempotency_key: String,
+    /// Receiver terminal id
+    pub receiver_id: String,
+    pub event: TradeEvent,
+    pub status: QueueStatus,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// When the master event was first seen
+    pub detected_at: DateTime<Utc>,
+    /// Earliest time the worker may pick this up again
+    pub next_retry_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Why the execution is waiting (e.g. "rate limited")
+    #[serde(default)]
+    pub defer_reason: Option<String>,
+    /// When the current (or last) attempt was picked up by the worker
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Was in progress when the app stopped; see `recovery`
+    #[serde(default)]
+    pub recovered: bool,
+    /// Receiver lots to send as-is instead of sizing the event (the unfilled
+    /// remainder of a partial fill)
+    #[serde(default)]
+    pub receiver_lots: Option<f64>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedExecution {
+    pub fn new(event: TradeEvent, receiver_id: &str, idempotency_key: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            idempotency_key: idempotency_key.to_string(),
+            receiver_id: receiver_id.to_string(),
+            event,
+            status: QueueStatus::Pending,
+            attempts: 0,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            detected_at: Utc::now(),
+            next_retry_at: None,
+            last_error: None,
+            defer_reason: None,
+            started_at: None,
+            recovered: false,
+            receiver_lots: None,
+            completed_at: None,
+        }
+    }
+
+    /// Whether the worker may run this now
+    pub fn is_ready(&self, now: DateTime<Utc>) -> bool {
+        self.status == QueueStatus::Pending && self.next_retry_at.is_none_or(|t| t <= now)
+    }
+}
+
+/// Completed/failed counters for the current UTC day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub date: Option<NaiveDate>,
+    pub completed_today: usize,
+    pub failed_today: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedQueue {
+    version: u32,
+    pending: Vec<QueuedExecution>,
+    in_progress: Vec<QueuedExecution>,
+    #[serde(default)]
+    stats: QueueStats,
+}
+
+/// Pending / in-progress executions plus today's stats
+#[derive(Debug, Default)]
+pub struct ExecutionQueue {
+    pending: VecDeque<QueuedExecution>,
+    in_progress: HashMap<String, QueuedExecution>,
+    recent_completed: VecDeque<QueuedExecution>,
+    stats: QueueStats,
+    persist_path: Option<PathBuf>,
+}
+
+impl ExecutionQueue {
+    /// Empty queue; `persist_path = None` keeps it in memory only
+    pub fn new(persist_path: Option<PathBuf>) -> Self {
+        Self {
+            persist_path,
+            ..Default::default()
+        }
+    }
+
+    /// Load the queue from disk, recovering a corrupt file if possible.
+    /// Executions that were in progress when the app stopped are moved back
+    /// to pending so the worker picks them up, flagged `recovered` so it
+    /// checks the receiver before sending them again.
+    pub fn load_from_disk(path: PathBuf) -> Self {
+        let mut queue = Self::new(Some(path.clone()));
+
+        let Some(persisted) = persistence::load_state_file(&path, "Execution queue", |content| {
+            serde_json::from_str::<PersistedQueue>(content).map_err(|e| e.to_string())
+        }) else {
+            return queue;
+        };
+
+        queue.pending = persisted.pending.into();
+        for mut exec in persisted.in_progress {
+            exec.status = QueueStatus::Pending;
+            exec.recovered = true;
+            queue.pending.push_back(exec);
+        }
+        queue.stats = persisted.stats;
+
+        if !queue.pending.is_empty() {
+            info!("Loaded {} pending executions from disk", queue.pending.len());
+        }
+        queue
+    }
+
+    /// Pending + in-progress executions and stats, as written to disk
+    fn persisted(&self) -> PersistedQueue {
+        PersistedQueue {
+            version: 1,
+            pending: self.pending.iter().cloned().collect(),
+            in_progress: self.in_progress.values().cloned().collect(),
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Park an execution until `until`. Deferral doesn't consume an attempt.
+    pub fn defer(&mut self, mut exec: QueuedExecution, until: DateTime<Utc>, reason: &str) {
+        exec.status = QueueStatus::Pending;
+        exec.next_retry_at = Some(until);
+        exec.defer_reason = Some(reason.to_string());
+        self.pending.push_back(exec);
+    }
+
+    /// Move an in-progress execution back to pending until `until`
+    /// (e.g. it was deferred again when the worker retried it). The attempt
+    /// counted by `dequeue_ready` is given back.
+    pub fn requeue(&mut self, id: &str, until: DateTime<Utc>, reason: &str) {
+        if let Some(mut exec) = self.in_progress.remove(id) {
+            exec.attempts = exec.attempts.saturating_sub(1);
+            self.defer(exec, until, reason);
+        }
+    }
+
+    /// Take the oldest ready execution and mark it in progress
+    pub fn dequeue_ready(&mut self, now: DateTime<Utc>) -> Option<QueuedExecution> {
+        let idx = self.pending.iter().position(|e| e.is_ready(now))?;
+        let mut exec = self.pending.remove(idx)?;
+        exec.status = QueueStatus::InProgress;
+        exec.attempts += 1;
+        exec.started_at = Some(now);
+        self.in_progress.insert(exec.id.clone(), exec.clone());
+        Some(exec)
+    }
+
+    pub fn mark_completed(&mut self, id: &str) {
+        let Some(mut exec) = self.in_progress.remove(id) else {
+            return;
+        };
+        exec.status = QueueStatus::Completed;
+        exec.completed_at = Some(Utc::now());
+
+        self.roll_stats_day();
+        self.stats.completed_today += 1;
+
+        self.recent_completed.push_front(exec);
+        self.recent_completed.truncate(MAX_RECENT_COMPLETED);
+    }
+
+    /// Record a failed attempt. Retries with exponential backoff until
+    /// `max_attempts`; returns true if the execution will be retried.
+    pub fn mark_failed(&mut self, id: &str, error: &str) -> bool {
+        let Some(mut exec) = self.in_progress.remove(id) else {
+            return false;
+        };
+        exec.last_error = Some(error.to_string());
+
+        if exec.attempts < exec.max_attempts {
+            let delay_ms = calculate_backoff_delay(exec.attempts.saturating_sub(1), &RetryConfig::default());
+            let until = Utc::now() + Duration::from_millis(delay_ms);
+            exec.status = QueueStatus::Pending;
+            exec.next_retry_at = Some(until);
+            self.pending.push_back(exec);
+            return true;
+        }
+
+        exec.status = QueueStatus::Failed;
+        exec.completed_at = Some(Utc::now());
+        self.roll_stats_day();
+        self.stats.failed_today += 1;
+        self.recent_completed.push_front(exec);
+        self.recent_completed.truncate(MAX_RECENT_COMPLETED);
+        false
+    }
+
+    /// The execution matching `matches` that's still queued (in progress or
+    /// pending), else the newest recently finished one
+    pub fn find(&self, matches: impl Fn(&QueuedExecution) -> bool) -> Option<&QueuedExecution> {
+        self.in_progress
+            .values()
+            .find(|e| matches(e))
+            .or_else(|| self.pending.iter().find(|e| matches(e)))
+            .or_else(|| self.recent_completed.iter().find(|e| matches(e)))
+    }
+
+    /// Drop pending executions matching `matches` without running them,
+    /// recording them as failed with `reason`. Returns how many were dropped.
+    pub fn cancel_pending(&mut self, matches: impl Fn(&QueuedExecution) -> bool, reason: &str) -> usize {
+        let (cancelled, kept): (VecDeque<_>, VecDeque<_>) = self.pending.drain(..).partition(|e| matches(e));
+        self.pending = kept;
+        let count = cancelled.len();
+        for mut exec in cancelled {
+            exec.status = QueueStatus::Failed;
+            exec.last_error = Some(reason.to_string());
+            exec.completed_at = Some(Utc::now());
+            self.roll_stats_day();
+            self.stats.failed_today += 1;
+            self.recent_completed.push_front(exec);
+        }
+        self.recent_completed.truncate(MAX_RECENT_COMPLETED);
+        count
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Pending and in-progress executions plus stats as JSON, in the
+    /// on-disk format
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.persisted()).map_err(|e| format!("Failed to serialize execution queue: {}", e))
+    }
+
+    pub fn in_progress_count(&self) -> usize {
+        self.in_progress.len()
+    }
+
+    pub fn today_stats(&self) -> QueueStats {
+        let today = Utc::now().date_naive();
+        if self.stats.date == Some(today) {
+            self.stats.clone()
+        } else {
+            QueueStats {
+                date: Some(today),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn roll_stats_day(&mut self) {
+        let today = Utc::now().date_naive();
+        if self.stats.date != Some(today) {
+            self.stats = QueueStats {
+                date: Some(today),
+                ..Default::default()
+            };
+        }
+    }
+}
+
+/// Write a queue snapshot atomically via a temp file
+fn write_queue_file(path: &Path, persisted: &PersistedQueue) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create queue directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(persisted)
+        .map_err(|e| format!("Failed to serialize execution queue: {}", e))?;
+
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, &json)
+        .map_err(|e| format!("Failed to write execution queue: {}", e))?;
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to finalize execution queue: {}", e))?;
+
+    Ok(())
+}
+
+/// Execution queue shared between the queue worker, the live event path
+/// and the UI.
+///
+/// The queue lock is only held for in-memory work: readers get cloned
+/// snapshots, and [`persist`](Self::persist) serializes a copy under the
+/// lock but writes it to disk after releasing it.
+#[derive(Clone, Default)]
+pub struct SharedExecutionQueue {
+    queue: Arc<Mutex<ExecutionQueue>>,
+    /// Orders concurrent saves so an older snapshot never overwrites a newer one
+    save_lock: Arc<Mutex<()>>,
+}
+
+impl SharedExecutionQueue {
+    pub fn new(queue: ExecutionQueue) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(queue)),
+            save_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Run `f` with the queue locked. Keep `f` free of IO; call
+    /// [`persist`](Self::persist) afterwards to save.
+    pub fn update<R>(&self, f: impl FnOnce(&mut ExecutionQueue) -> R) -> R {
+        f(&mut self.queue.lock())
+    }
+
+    /// Save the queue to disk without holding the queue lock during the write
+    pub fn persist(&self) -> Result<(), String> {
+        let _save = self.save_lock.lock();
+        let (path, persisted) = {
+            let queue = self.queue.lock();
+            match &queue.persist_path {
+                Some(path) => (path.clone(), queue.persisted()),
+                None => return Ok(()),
+            }
+        };
+        write_queue_file(&path, &persisted)
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.queue.lock().pending_count()
+    }
+
+    pub fn in_progress_count(&self) -> usize {
+        self.queue.lock().in_progress_count()
+    }
+
+    pub fn today_stats(&self) -> QueueStats {
+        self.queue.lock().today_stats()
+    }
+
+    /// Most recently completed/failed executions, newest first
+    pub fn recent_completed(&self) -> Vec<QueuedExecution> {
+        self.queue.lock().recent_completed.iter().cloned().collect()
+    }
+}
+
+/// Get the path to the queue file
+fn get_queue_file_path() -> Option<PathBuf> {
+    use crate::data_dir::app_data_dir;
+    Some(app_data_dir()?.join(QUEUE_FILE))
+}
+
+/// Global execution queue, loaded from disk on first use
+pub static EXECUTION_QUEUE: LazyLock<SharedExecutionQueue> = LazyLock::new(|| {
+    let queue = match get_queue_file_path() {
+        Some(path) => ExecutionQueue::load_from_disk(path),
+        None => ExecutionQueue::new(None),
+    };
+    SharedExecutionQueue::new(queue)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_event;
+
+    #[test]
+    fn test_deferred_entry_waits_until_retry_time() {
+        let mut queue = ExecutionQueue::new(None);
+        let now = Utc::now();
+        queue.defer(QueuedExecution::new(test_event("entry", 1), "R1", "k1"), now + chrono::Duration::seconds(30), "rate limited");
+
+        assert!(queue.dequeue_ready(now).is_none());
+        let exec = queue.dequeue_ready(now + chrono::Duration::seconds(31)).unwrap();
+        assert_eq!(exec.attempts, 1);
+
+        // Deferred again: doesn't use up an attempt
+        queue.requeue(&exec.id, now + chrono::Duration::seconds(60), "rate limited");
+        let exec = queue.dequeue_ready(now + chrono::Duration::seconds(61)).unwrap();
+        assert_eq!(exec.attempts, 1);
+        assert_eq!(queue.in_progress_count(), 1);
+
+        queue.mark_completed(&exec.id);
+        assert_eq!(queue.in_progress_count(), 0);
+        assert_eq!(queue.today_stats().completed_today, 1);
+    }
+
+    #[test]
+    fn test_failed_execution_retries_then_fails() {
+        let mut queue = ExecutionQueue::new(None);
+        queue.defer(QueuedExecution::new(test_event("entry", 1), "R1", "k1"), Utc::now(), "test");
+        let far_future = Utc::now() + chrono::Duration::hours(1);
+
+        for _ in 0..DEFAULT_MAX_ATTEMPTS - 1 {
+            let exec = queue.dequeue_ready(far_future).unwrap();
+            assert!(queue.mark_failed(&exec.id, "timeout"));
+        }
+        let exec = queue.dequeue_ready(far_future).unwrap();
+        assert!(!queue.mark_failed(&exec.id, "timeout"));
+        assert_eq!(queue.pending_count(), 0);
+        assert_eq!(queue.today_stats().failed_today, 1);
+    }
+
+    #[test]
+    fn test_in_progress_recovered_as_pending_on_load() {
+        let path = std::env::temp_dir().join(format!("saturn_queue_test_{}.json", uuid::Uuid::new_v4()));
+        let mut queue = ExecutionQueue::new(Some(path.clone()));
+        queue.defer(QueuedExecution::new(test_event("entry", 1), "R1", "k1"), Utc::now(), "test");
+        queue.dequeue_ready(Utc::now()).unwrap();
+        SharedExecutionQueue::new(queue).persist().unwrap();
+
+        let loaded = ExecutionQueue::load_from_disk(path.clone());
+        assert_eq!(loaded.pending_count(), 1);
+        assert_eq!(loaded.in_progress_count(), 0);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_shared_queue_concurrent_defer_and_snapshots() {
+        let path = std::env::temp_dir().join(format!("saturn_queue_test_{}.json", uuid::Uuid::new_v4()));
+        let shared = SharedExecutionQueue::new(ExecutionQueue::new(Some(path.clone())));
+        let later = Utc::now() + chrono::Duration::hours(1);
+
+        let writers: Vec<_> = (0..4)
+            .map(|w| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let key = format!("k{}_{}", w, i);
+                        shared.update(|q| q.defer(QueuedExecution::new(test_event("entry", 1), "R1", &key), later, "test"));
+                        shared.persist().unwrap();
+                    }
+                })
+            })
+            .collect();
+        let reader = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                let mut last = 0;
+                for _ in 0..100 {
+                    let count = shared.pending_count();
+                    assert!(count >= last && count <= 100);
+                    last = count;
+                    assert!(shared.recent_completed().is_empty());
+                    assert_eq!(shared.today_stats().completed_today, 0);
+                }
+            })
+        };
+        for handle in writers {
+            handle.join().unwrap();
+        }
+        reader.join().unwrap();
+
+        assert_eq!(shared.pending_count(), 100);
+        let exec = shared.update(|q| q.dequeue_ready(later)).unwrap();
+        shared.update(|q| q.mark_completed(&exec.id));
+        assert_eq!(shared.recent_completed()[0].id, exec.id);
+
+        // Last persisted snapshot reflects every deferral
+        assert_eq!(ExecutionQueue::load_from_disk(path.clone()).pending_count(), 100);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_truncated_queue_file_recovers_from_temp() {
+        let dir = std::env::temp_dir().join(format!("saturn_queue_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join(QUEUE_FILE);
+        let mut queue = ExecutionQueue::new(Some(path.clone()));
+        queue.defer(QueuedExecution::new(test_event("entry", 1), "R1", "k1"), Utc::now(), "test");
+        SharedExecutionQueue::new(queue).persist().unwrap();
+
+        // Power loss: the rename landed but the file was cut short, while an
+        // intact copy survives as the temp file
+        let json = fs::read_to_string(&path).unwrap();
+        fs::write(path.with_extension("tmp"), &json).unwrap();
+        fs::write(&path, &json[..json.len() / 2]).unwrap();
+
+        assert_eq!(ExecutionQueue::load_from_disk(path.clone()).pending_count(), 1);
+        let backed_up = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .any(|e| e.file_name().to_string_lossy().starts_with("execution_queue.json.corrupt-"));
+        assert!(backed_up);
+
+        // Without a temp file the queue starts empty, but the corrupt file is kept
+        fs::write(&path, &json[..json.len() / 2]).unwrap();
+        assert_eq!(ExecutionQueue::load_from_disk(path.clone()).pending_count(), 0);
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
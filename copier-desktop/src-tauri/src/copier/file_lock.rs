@@ -0,0 +1,112 @@
+//! Per-terminal file access
+//!
+//! The config writer, command writers and position readers all touch the
+//! same terminal's MQL5/Files folder, alongside the EA. Writes the app makes
+//! to one terminal are serialized by an in-process lock keyed by terminal
+//! id (advisory: the EA doesn't see it), so two writers never race on the
+//! same `.tmp` file.
+//!
+//! The EA can't take part in that lock, so reads it may be contending with
+//! are retried on Windows sharing violations, with the backoff configured
+//! for queue file reads (`watch_settings` `read_attempts` / `read_retry_ms`).
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+
+use super::watch_settings::{self, WatchSettings};
+
+/// Windows ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+const SHARING_VIOLATION_CODES: [i32; 2] = [32, 33];
+
+static TERMINAL_LOCKS: LazyLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn terminal_lock(terminal_id: &str) -> Arc<Mutex<()>> {
+    TERMINAL_LOCKS.lock().entry(terminal_id.to_string()).or_default().clone()
+}
+
+/// Run `f` holding the terminal's write lock. Keep `f` to the file writes;
+/// don't wait on the EA inside it.
+pub fn with_terminal_lock<R>(terminal_id: &str, f: impl FnOnce() -> R) -> R {
+    let lock = terminal_lock(terminal_id);
+    let _guard = lock.lock();
+    f()
+}
+
+/// Whether `e` is another process (the EA) holding the file open
+fn is_sharing_violation(e: &io::Error) -> bool {
+    cfg!(windows) && e.raw_os_error().is_some_and(|code| SHARING_VIOLATION_CODES.contains(&code))
+}
+
+fn read_with_retry(path: &Path, settings: &WatchSettings) -> io::Result<String> {
+    let attempts = settings.read_attempts.clamp(1, watch_settings::MAX_READ_ATTEMPTS);
+    let mut attempt = 0;
+    loop {
+        match fs::read_to_string(path) {
+            Err(e) if is_sharing_violation(&e) && attempt + 1 < attempts => {
+                std::thread::sleep(settings.read_retry_delay(attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Read a file the EA may have open, retrying sharing violations
+pub fn read_contended(path: &Path) -> io::Result<String> {
+    read_with_retry(path, &watch_settings::current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_concurrent_writes_to_one_terminal_serialize() {
+        let dir = std::env::temp_dir().join(format!("saturn_file_lock_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+
+        // Every writer uses the same temp file, as the config writer does
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let (dir, in_flight, overlapped) = (dir.clone(), in_flight.clone(), overlapped.clone());
+                std::thread::spawn(move || {
+                    for round in 0..10 {
+                        with_terminal_lock("T-LOCK", || -> io::Result<()> {
+                            if in_flight.fetch_add(1, Ordering::SeqCst) > 0 {
+                                overlapped.fetch_add(1, Ordering::SeqCst);
+                            }
+                            let temp = dir.join("copier-config.json.tmp");
+                            fs::write(&temp, format!("writer {} round {}", i, round))?;
+                            fs::rename(&temp, dir.join("copier-config.json"))?;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            Ok(())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+        let content = read_contended(&dir.join("copier-config.json")).unwrap();
+        assert!(content.starts_with("writer "));
+        assert!(!dir.join("copier-config.json.tmp").exists());
+
+        // Missing files fail straight away rather than being retried
+        let missing = read_with_retry(&dir.join("missing.json"), &WatchSettings::default());
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,27 +1,44 @@
 //! File watcher for monitoring trade event files from Master EA
 //! 
 //! This module watches the CopierQueue/pending folder for JSON event files.
+//! Each configured master's queue gets its own watcher thread, and events are
+//! routed only to the receivers following that master.
 //! Includes safety measures like file stability checks, idempotency, and graceful shutdown.
+//!
+//! Watchers re-arm themselves when their folder is deleted or `notify`
+//! reports an error, and a watchdog in `start_watching` replaces any watcher
+//! that stops ticking while its folder exists. Depending on
+//! `watch_settings`, new files are detected by notifications, timed folder
+//! scans, or both.
 
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::Mutex;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{info, warn, error, debug};
 
-use super::{event_processor, idempotency, CopierState, TradeEvent};
+use super::watch_settings::{self, WatchSettings};
+use super::{event_processor, idempotency, kill_switch, parse_errors, CopierConfig, CopierState, TradeEvent};
 use crate::mt5::bridge;
 
 /// Delay before reading a newly created file to ensure it's fully written
 const FILE_STABILITY_DELAY_MS: u64 = 150;
 
-/// Maximum retries for reading a file
-const MAX_READ_RETRIES: u32 = 3;
+/// Folder, next to `pending`, that unparseable event files are moved to
+const QUARANTINE_FOLDER: &str = "quarantine";
 
-/// Delay between read retries
-const RETRY_DELAY_MS: u64 = 100;
+/// How often a watcher's event loop ticks when no events arrive
+const POLL_TICK: Duration = Duration::from_millis(500);
+
+/// Pause before re-arming a watcher whose folder vanished or errored
+const REARM_DELAY: Duration = Duration::from_secs(1);
+
+/// A watcher silent this long while its folder exists is replaced. Long
+/// enough for an event whose executions retry on every receiver.
+const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(300);
 
 /// Global shutdown flag for graceful termination
 static SHUTDOWN_FLAG: AtomicBool = AtomicBool::new(false);
@@ -42,76 +59,266 @@ pub fn reset_shutdown() {
     SHUTDOWN_FLAG.store(false, Ordering::SeqCst);
 }
 
+/// A master queue folder to watch
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchTarget {
+    /// Master whose receivers get this folder's events. `None` when the
+    /// folder was auto-detected without a config (primary master's group).
+    master_account_id: Option<String>,
+    queue_path: String,
+}
+
+/// Stop flag and liveness stamp shared between a watcher thread and the
+/// watchdog
+#[derive(Debug)]
+struct WatchControl {
+    stop: AtomicBool,
+    last_alive: Mutex<Instant>,
+}
+
+impl WatchControl {
+    fn new() -> Self {
+        Self {
+            stop: AtomicBool::new(false),
+            last_alive: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Record a filesystem event or poll tick
+    fn touch(&self) {
+        *self.last_alive.lock() = Instant::now();
+    }
+
+    fn is_stalled(&self, now: Instant, timeout: Duration) -> bool {
+        now.saturating_duration_since(*self.last_alive.lock()) > timeout
+    }
+
+    fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::SeqCst) || is_shutdown_requested()
+    }
+}
+
+/// What became of an event file handed to `on_file`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileOutcome {
+    /// Processed, skipped or quarantined: not offered again this session
+    Done,
+    /// Still locked by its writer: offered again by the next scan
+    Retry,
+}
+
+/// Event files handed to `on_file` this session, so a file seen by both a
+/// notification and a scan is processed once
+#[derive(Debug, Default)]
+struct SeenFiles {
+    paths: HashSet<PathBuf>,
+    /// A file was forgotten to be retried, so the folder needs a rescan
+    /// even when the watch doesn't poll
+    retry_pending: bool,
+}
+
+impl SeenFiles {
+    /// True the first time a path is offered
+    fn first_sight(&mut self, path: &Path) -> bool {
+        self.paths.insert(path.to_path_buf())
+    }
+
+    /// Offer `path` again on the next scan
+    fn forget(&mut self, path: &Path) {
+        self.paths.remove(path);
+        self.retry_pending = true;
+    }
+
+    /// Drop files that are gone (processed files are deleted)
+    fn prune(&mut self) {
+        self.paths.retain(|path| path.exists());
+    }
+}
+
+/// Why a watch ended without an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchExit {
+    Stopped,
+    SettingsChanged,
+}
+
+/// A running watcher thread
+struct WatcherHandle {
+    thread: std::thread::JoinHandle<()>,
+    control: Arc<WatchControl>,
+}
+
 pub fn start_watching(state: Arc<Mutex<CopierState>>) {
     info!("Starting file watcher...");
 
+    // One watcher thread per queue folder
+    let mut watchers: HashMap<String, WatcherHandle> = HashMap::new();
+
     while !is_shutdown_requested() {
-        // Try to find master terminal path from config or auto-detect
-        let queue_path = find_master_queue_path(&state);
-        
-        if let Some(path) = queue_path {
-            // Watch the 'pending' subfolder where Master EA writes events
-            let pending_path = format!("{}\\pending", path);
-            
-            // Ensure the pending folder exists
-            if !Path::new(&pending_path).exists() {
-                let _ = std::fs::create_dir_all(&pending_path);
+        // Finished watchers are restarted on this pass
+        watchers.retain(|_, watcher| !watcher.thread.is_finished());
+
+        // Find master queue paths from config or auto-detect
+        let targets = find_watch_targets(&state);
+        if targets.is_empty() && watchers.is_empty() {
+            debug!("No master terminal found, waiting...");
+        }
+
+        // Re-arm on a new folder when the target set changes (MT5 path set,
+        // config changed). Dropped watchers exit at their next tick.
+        let wanted: HashSet<&str> = targets.iter().map(|t| t.queue_path.as_str()).collect();
+        watchers.retain(|queue_path, watcher| {
+            let keep = wanted.contains(queue_path.as_str());
+            if !keep {
+                info!("No longer watching {}", queue_path);
+                watcher.control.stop();
             }
-            
-            if Path::new(&pending_path).exists() {
-                info!("Watching queue folder: {}", pending_path);
-                
-                // Update state with the MT5 path for other modules
-                {
-                    let mut copier = state.lock();
-                    // Extract parent path from queue_path
-                    if let Some(parent) = Path::new(&path).parent().and_then(|p| p.parent()).and_then(|p| p.parent()) {
-                        copier.mt5_data_path = Some(parent.to_string_lossy().to_string());
-                    }
-                }
-                
-                if let Err(e) = watch_folder(&pending_path, state.clone()) {
-                    if is_shutdown_requested() {
-                        info!("File watcher shutting down gracefully");
-                        break;
-                    }
-                    error!("File watcher error: {}", e);
-                    let mut copier = state.lock();
-                    copier.last_error = Some(format!("Watcher error: {}", e));
-                }
-            } else {
-                warn!("Pending folder does not exist: {}", pending_path);
+            keep
+        });
+
+        // Watchdog: replace watchers that stopped ticking
+        let now = Instant::now();
+        watchers.retain(|queue_path, watcher| {
+            let stalled = watcher.control.is_stalled(now, WATCHDOG_TIMEOUT) && Path::new(queue_path).exists();
+            if stalled {
+                warn!("Watcher for {} stopped responding, restarting it", queue_path);
+                watcher.control.stop();
             }
-        } else {
-            debug!("No master terminal found, waiting...");
+            !stalled
+        });
+
+        for target in targets {
+            if watchers.contains_key(&target.queue_path) {
+                continue;
+            }
+            let queue_path = target.queue_path.clone();
+            let control = Arc::new(WatchControl::new());
+            let thread_control = control.clone();
+            let state = state.clone();
+            let thread = std::thread::spawn(move || watch_target(&target, &thread_control, state));
+            watchers.insert(queue_path, WatcherHandle { thread, control });
         }
 
         // Check shutdown flag during wait
         for _ in 0..50 {
             if is_shutdown_requested() {
                 info!("File watcher received shutdown signal");
-                return;
+                break;
             }
             std::thread::sleep(Duration::from_millis(100));
         }
     }
-    
+
+    for (_, watcher) in watchers {
+        let _ = watcher.thread.join();
+    }
     info!("File watcher stopped");
 }
 
-/// Find the queue path from the master terminal
-fn find_master_queue_path(state: &Arc<Mutex<CopierState>>) -> Option<String> {
-    // First check if we have a config with master terminal
+/// Watch one master's queue folder until shutdown or the watchdog stops it
+fn watch_target(target: &WatchTarget, control: &WatchControl, state: Arc<Mutex<CopierState>>) {
+    // Watch the 'pending' subfolder where Master EA writes events
+    let pending_path = format!("{}\\pending", target.queue_path);
+
+    info!(
+        "Watching queue folder: {} (master {})",
+        pending_path,
+        target.master_account_id.as_deref().unwrap_or("auto-detected")
+    );
+
+    // Update state with the MT5 path for other modules
     {
-        let copier = state.lock();
-        if let Some(ref config) = copier.config {
-            let terminal_id = &config.master.terminal_id;
-            if let Some(path) = get_terminal_queue_path(terminal_id) {
-                return Some(path);
+        let mut copier = state.lock();
+        // Extract parent path from queue_path
+        if let Some(parent) = Path::new(&target.queue_path).parent().and_then(|p| p.parent()).and_then(|p| p.parent()) {
+            copier.mt5_data_path = Some(parent.to_string_lossy().to_string());
+        }
+    }
+
+    let master_account_id = target.master_account_id.as_deref();
+    watch_pending_folder(
+        &pending_path,
+        control,
+        watch_settings::current,
+        |path| process_event_file(path, master_account_id, state.clone()),
+        |e| state.lock().last_error = Some(format!("Watcher error: {}", e)),
+    );
+}
+
+/// Watch a pending folder, re-arming the watcher whenever it fails (folder
+/// deleted, `notify` error) or `settings` change, until `control` says stop
+fn watch_pending_folder(
+    pending_path: &str,
+    control: &WatchControl,
+    settings: impl Fn() -> WatchSettings,
+    on_file: impl Fn(&Path) -> FileOutcome,
+    on_error: impl Fn(&str),
+) {
+    let mut seen = SeenFiles::default();
+    while !control.should_stop() {
+        // Ensure the pending folder exists
+        if !Path::new(pending_path).exists() {
+            let _ = std::fs::create_dir_all(pending_path);
+        }
+
+        if Path::new(pending_path).exists() {
+            match watch_folder(pending_path, control, &settings, &mut seen, &on_file) {
+                Ok(WatchExit::Stopped) => break,
+                Ok(WatchExit::SettingsChanged) => continue,
+                Err(e) if !control.should_stop() => {
+                    error!("File watcher error: {}", e);
+                    on_error(&e.to_string());
+                }
+                Err(_) => break,
             }
+        } else {
+            warn!("Pending folder does not exist: {}", pending_path);
         }
-        
-        // Check if mt5_data_path is already set
+
+        std::thread::sleep(REARM_DELAY);
+        control.touch();
+    }
+    info!("File watcher for {} shutting down gracefully", pending_path);
+}
+
+/// Queue folders to watch: one per configured master, else an
+/// auto-detected master
+fn find_watch_targets(state: &Arc<Mutex<CopierState>>) -> Vec<WatchTarget> {
+    let config = state.lock().config.clone();
+    if let Some(config) = config {
+        let targets: Vec<WatchTarget> = config
+            .all_masters()
+            .into_iter()
+            .filter_map(|master| {
+                get_terminal_queue_path(&master.terminal_id).map(|queue_path| WatchTarget {
+                    master_account_id: Some(master.account_id.clone()),
+                    queue_path,
+                })
+            })
+            .collect();
+        if !targets.is_empty() {
+            return targets;
+        }
+    }
+
+    find_master_queue_path(state)
+        .map(|queue_path| WatchTarget {
+            master_account_id: None,
+            queue_path,
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Find a master queue path when no configured master's folder exists
+fn find_master_queue_path(state: &Arc<Mutex<CopierState>>) -> Option<String> {
+    // Check if mt5_data_path is already set
+    {
+        let copier = state.lock();
         if let Some(ref path) = copier.mt5_data_path {
             let queue_path = format!("{}\\MQL5\\Files\\CopierQueue", path);
             if Path::new(&queue_path).exists() {
@@ -191,47 +398,74 @@ fn get_terminal_queue_path(terminal_id: &str) -> Option<String> {
     None
 }
 
-fn watch_folder(path: &str, state: Arc<Mutex<CopierState>>) -> Result<(), Box<dyn std::error::Error>> {
+/// Watch `path` until `control` says stop or the settings change (`Ok`),
+/// or the watch breaks (`Err`): the folder disappeared or `notify` reported
+/// an error
+fn watch_folder(
+    path: &str,
+    control: &WatchControl,
+    settings: &impl Fn() -> WatchSettings,
+    seen: &mut SeenFiles,
+    on_file: &impl Fn(&Path) -> FileOutcome,
+) -> Result<WatchExit, Box<dyn std::error::Error>> {
+    let armed = settings();
+    // `tx` stays alive here so a poll-only watch doesn't see a disconnect
     let (tx, rx) = std::sync::mpsc::channel();
 
-    let mut watcher = RecommendedWatcher::new(
-        move |res| {
-            if let Ok(event) = res {
-                let _ = tx.send(event);
-            }
-        },
-        Config::default().with_poll_interval(Duration::from_millis(100)),
-    )?;
-
-    watcher.watch(Path::new(path), RecursiveMode::NonRecursive)?;
+    let _watcher = if armed.mode.uses_notify() {
+        let tx = tx.clone();
+        let mut watcher = RecommendedWatcher::new(
+            move |res| {
+                let _ = tx.send(res);
+            },
+            Config::default().with_poll_interval(Duration::from_millis(100)),
+        )?;
+        watcher.watch(Path::new(path), RecursiveMode::NonRecursive)?;
+        Some(watcher)
+    } else {
+        None
+    };
 
     // Also process any existing files
-    process_existing_files(path, state.clone())?;
+    scan_folder(path, seen, on_file)?;
+    let mut last_scan = Instant::now();
+    let tick = if armed.mode.uses_poll() {
+        armed.poll_interval().min(POLL_TICK)
+    } else {
+        POLL_TICK
+    };
 
     // Process new files as they arrive with shutdown check
     loop {
+        control.touch();
+
         // Check for shutdown
-        if is_shutdown_requested() {
+        if control.should_stop() {
             info!("File watcher received shutdown signal during event loop");
-            return Ok(());
+            return Ok(WatchExit::Stopped);
+        }
+
+        if settings() != armed {
+            info!("Watch settings changed, re-arming watcher for {}", path);
+            return Ok(WatchExit::SettingsChanged);
+        }
+
+        if !Path::new(path).exists() {
+            return Err(format!("Watched folder was removed: {}", path).into());
+        }
+
+        if (armed.mode.uses_poll() || seen.retry_pending) && last_scan.elapsed() >= armed.poll_interval() {
+            scan_folder(path, seen, on_file)?;
+            last_scan = Instant::now();
         }
         
         // Use recv_timeout to allow periodic shutdown checks
-        match rx.recv_timeout(Duration::from_millis(500)) {
-            Ok(event) => {
+        match rx.recv_timeout(tick) {
+            Ok(Err(e)) => return Err(e.into()),
+            Ok(Ok(event)) => {
                 if let notify::EventKind::Create(_) = event.kind {
                     for file_path in event.paths {
-                        if file_path.extension().map(|e| e == "json").unwrap_or(false) {
-                            // Wait for file to be fully written before processing
-                            std::thread::sleep(Duration::from_millis(FILE_STABILITY_DELAY_MS));
-                            
-                            // Verify file stability (size not changing)
-                            if is_file_stable(&file_path) {
-                                process_event_file(&file_path, state.clone());
-                            } else {
-                                warn!("File not stable, skipping: {:?}", file_path);
-                            }
-                        }
+                        handle_file(&file_path, seen, on_file);
                     }
                 }
             }
@@ -262,77 +496,157 @@ fn is_file_stable(path: &Path) -> bool {
     }
 }
 
-fn process_existing_files(
+/// Hand a new event file to `on_file` once it's fully written, unless it was
+/// already handled this session. A file `on_file` couldn't read yet is
+/// forgotten so a later scan offers it again.
+fn handle_file(path: &Path, seen: &mut SeenFiles, on_file: &impl Fn(&Path) -> FileOutcome) {
+    if !path.extension().map(|e| e == "json").unwrap_or(false) || !path.exists() || !seen.first_sight(path) {
+        return;
+    }
+
+    // Wait for file to be fully written before processing
+    std::thread::sleep(Duration::from_millis(FILE_STABILITY_DELAY_MS));
+
+    // Verify file stability (size not changing)
+    if is_file_stable(path) {
+        if on_file(path) == FileOutcome::Retry {
+            seen.forget(path);
+        }
+    } else {
+        warn!("File not stable, skipping: {:?}", path);
+        // Let the next scan or notification retry it
+        seen.forget(path);
+    }
+}
+
+/// Handle every event file currently in `folder`
+fn scan_folder(
     folder: &str,
-    state: Arc<Mutex<CopierState>>,
+    seen: &mut SeenFiles,
+    on_file: &impl Fn(&Path) -> FileOutcome,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    seen.prune();
+    seen.retry_pending = false;
     let entries = std::fs::read_dir(folder)?;
 
     for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().map(|e| e == "json").unwrap_or(false) {
-            process_event_file(&path, state.clone());
-        }
+        handle_file(&entry.path(), seen, on_file);
     }
 
     Ok(())
 }
 
-/// Read file with retries for robustness
-fn read_file_with_retry(path: &Path) -> Result<String, String> {
-    let mut last_error = String::new();
-    
-    for attempt in 0..MAX_READ_RETRIES {
-        match std::fs::read_to_string(path) {
-            Ok(content) => return Ok(content),
-            Err(e) => {
-                last_error = e.to_string();
-                warn!(
-                    "Failed to read file {:?} (attempt {}/{}): {}",
-                    path, attempt + 1, MAX_READ_RETRIES, e
-                );
-                
-                if attempt < MAX_READ_RETRIES - 1 {
-                    std::thread::sleep(Duration::from_millis(RETRY_DELAY_MS));
-                }
-            }
+/// Why an event file couldn't be read as an event
+#[derive(Debug)]
+enum EventFileError {
+    /// Still locked by its writer after every attempt
+    Locked(String),
+    /// Deleted before it could be read (another watcher took it)
+    Gone,
+    /// Unreadable or not a valid event after every attempt
+    Invalid(String),
+}
+
+/// Whether a read failed because another process holds the file. Only
+/// Windows sharing and lock violations count; other permission errors
+/// won't clear by waiting.
+fn is_lock_error(e: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(e.raw_os_error(), Some(32 | 33))
+}
+
+/// Read and parse an event file, retrying with backoff while it may still
+/// be being written: a locked file or one that doesn't parse yet (the EA
+/// hasn't finished) is read again, up to `settings.read_attempts` times.
+fn read_event_with_retry<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    settings: &WatchSettings,
+) -> Result<T, EventFileError> {
+    let attempts = settings.read_attempts.clamp(1, watch_settings::MAX_READ_ATTEMPTS);
+    let mut last_error = EventFileError::Gone;
+
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            std::thread::sleep(settings.read_retry_delay(attempt - 1));
         }
+        last_error = match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(event) => return Ok(event),
+                Err(e) => EventFileError::Invalid(e.to_string()),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(EventFileError::Gone),
+            Err(e) if is_lock_error(&e) => EventFileError::Locked(e.to_string()),
+            Err(e) => EventFileError::Invalid(e.to_string()),
+        };
+        debug!("Event file {:?} not readable yet (attempt {}/{}): {:?}", path, attempt + 1, attempts, last_error);
     }
-    
+
     Err(last_error)
 }
 
-fn process_event_file(path: &Path, state: Arc<Mutex<CopierState>>) {
-    info!("Processing event file: {:?}", path);
+/// Move a bad event file out of the queue into the `quarantine` folder next
+/// to it, so it can be inspected instead of being lost
+fn quarantine_file(path: &Path) -> Result<PathBuf, String> {
+    let queue = path
+        .parent()
+        .and_then(Path::parent)
+        .ok_or_else(|| format!("No queue folder above {:?}", path))?;
+    let folder = queue.join(QUARANTINE_FOLDER);
+    std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create quarantine folder: {}", e))?;
+    let file_name = path.file_name().ok_or_else(|| format!("No file name in {:?}", path))?;
+    let target = folder.join(file_name);
+    std::fs::rename(path, &target).map_err(|e| format!("Failed to quarantine {:?}: {}", path, e))?;
+    Ok(target)
+}
 
-    // Read the file with retry logic
-    let content = match read_file_with_retry(path) {
-        Ok(c) => c,
-        Err(e) => {
-            error!("Failed to read event file after retries: {}", e);
-            return;
-        }
-    };
+/// The config to process an event from `master_account_id`'s queue with:
+/// only that master's receivers. Auto-detected folders (`None`) feed the
+/// primary master's group.
+fn route_config(config: &CopierConfig, master_account_id: Option<&str>) -> Option<CopierConfig> {
+    config.for_master(master_account_id.unwrap_or(&config.master.account_id))
+}
 
-    // Parse the trade event
-    let event: TradeEvent = match serde_json::from_str(&content) {
+fn process_event_file(path: &Path, master_account_id: Option<&str>, state: Arc<Mutex<CopierState>>) -> FileOutcome {
+    info!("Processing event file: {:?}", path);
+    let detected_at = chrono::Utc::now().to_rfc3339();
+
+    // The EA may still be writing the file: retry before giving up on it
+    let mut event: TradeEvent = match read_event_with_retry(path, &watch_settings::current()) {
         Ok(e) => e,
-        Err(e) => {
-            error!("Failed to parse event file: {}", e);
-            // Still delete malformed files to prevent infinite loops
-            if let Err(del_err) = std::fs::remove_file(path) {
-                error!("Failed to delete malformed file: {}", del_err);
-            }
-            return;
+        Err(EventFileError::Gone) => {
+            debug!("Event file {:?} was removed before it could be read", path);
+            return FileOutcome::Done;
+        }
+        Err(EventFileError::Locked(e)) => {
+            // Left in place: the writer still holds it, it isn't bad
+            warn!("Event file {:?} is still locked, retrying on the next scan: {}", path, e);
+            return FileOutcome::Retry;
+        }
+        Err(EventFileError::Invalid(e)) => {
+            error!("Failed to parse event file {:?}: {}", path, e);
+            // Move malformed files out of the queue to prevent infinite loops
+            let quarantined = match quarantine_file(path) {
+                Ok(target) => {
+                    warn!("Moved malformed event file to {:?}", target);
+                    Some(target)
+                }
+                Err(q_err) => {
+                    error!("{}", q_err);
+                    None
+                }
+            };
+            parse_errors::record(path, &e, quarantined.as_deref());
+            return FileOutcome::Done;
         }
     };
 
+    event.detected_at = Some(detected_at);
+
     // Prefer the EA-supplied idempotency key (canonical format). Fall back to
     // reconstructing the same shape for legacy EA versions that don't include it.
     let idempotency_key = event.idempotency_key.clone().unwrap_or_else(|| {
         let term = event.terminal_id.clone().unwrap_or_else(|| "unknown".into());
-        let deal = event.deal_id.unwrap_or(event.ticket);
-        idempotency::build_canonical_key(&term, deal, &event.event_type)
+        idempotency::build_canonical_key(&term, event.idempotency_id(), &event.event_type)
     });
     
     // U-9: Atomic claim closes the TOCTOU race where two watcher threads
@@ -342,7 +656,7 @@ fn process_event_file(path: &Path, state: Arc<Mutex<CopierState>>) {
         if let Err(e) = std::fs::remove_file(path) {
             error!("Failed to delete duplicate file: {}", e);
         }
-        return;
+        return FileOutcome::Done;
     }
 
     // Check if copier is running
@@ -353,17 +667,29 @@ fn process_event_file(path: &Path, state: Arc<Mutex<CopierState>>) {
 
     if !is_running {
         info!("Copier is not running, skipping event");
-        return;
+        return FileOutcome::Done;
+    }
+
+    if kill_switch::blocks(&state) {
+        return FileOutcome::Done;
     }
 
     let config = match config {
         Some(c) => c,
         None => {
             warn!("No configuration loaded, skipping event");
-            return;
+            return FileOutcome::Done;
         }
     };
 
+    let Some(config) = route_config(&config, master_account_id) else {
+        warn!(
+            "Master {} is no longer configured, skipping event",
+            master_account_id.unwrap_or_default()
+        );
+        return FileOutcome::Done;
+    };
+
     // Process the event for each receiver
     event_processor::process_event(&event, &config, state.clone());
 
@@ -371,4 +697,299 @@ fn process_event_file(path: &Path, state: Arc<Mutex<CopierState>>) {
     if let Err(e) = std::fs::remove_file(path) {
         error!("Failed to delete processed file: {}", e);
     }
+    FileOutcome::Done
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+    use crate::copier::{MasterConfig, ReceiverConfig};
+
+    fn master(account_id: &str) -> MasterConfig {
+        MasterConfig {
+            account_id: account_id.to_string(),
+            account_number: format!("{}-num", account_id),
+            broker: "B".to_string(),
+            terminal_id: format!("T-{}", account_id),
+        }
+    }
+
+    fn receiver(terminal_id: &str, master_account_id: Option<&str>) -> ReceiverConfig {
+        ReceiverConfig {
+            account_id: terminal_id.to_string(),
+            account_number: terminal_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+            master_account_id: master_account_id.map(String::from),
+            ..test_receiver()
+        }
+    }
+
+    /// Wait up to 10s for `cond`
+    fn wait_for(cond: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if cond() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[test]
+    fn test_deleted_and_recreated_folder_resumes_processing() {
+        let dir = std::env::temp_dir().join(format!("saturn_watch_test_{}", uuid::Uuid::new_v4()));
+        let pending = dir.join("pending");
+        std::fs::create_dir_all(&pending).unwrap();
+        let pending_path = pending.to_string_lossy().to_string();
+
+        let seen = Arc::new(Mutex::new(Vec::<String>::new()));
+        let control = Arc::new(WatchControl::new());
+        let watcher = {
+            let (seen, control, pending_path) = (seen.clone(), control.clone(), pending_path.clone());
+            std::thread::spawn(move || {
+                watch_pending_folder(
+                    &pending_path,
+                    &control,
+                    WatchSettings::default,
+                    |path| {
+                        seen.lock().push(path.file_name().unwrap().to_string_lossy().to_string());
+                        let _ = std::fs::remove_file(path);
+                        FileOutcome::Done
+                    },
+                    |_| {},
+                )
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(300));
+        std::fs::write(pending.join("first.json"), "{}").unwrap();
+        assert!(wait_for(|| seen.lock().contains(&"first.json".to_string())));
+
+        std::fs::remove_dir_all(&pending).unwrap();
+        std::thread::sleep(Duration::from_millis(800));
+        std::fs::create_dir_all(&pending).unwrap();
+        std::fs::write(pending.join("second.json"), "{}").unwrap();
+        assert!(wait_for(|| seen.lock().contains(&"second.json".to_string())));
+
+        control.stop();
+        watcher.join().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_poll_only_mode_picks_up_dropped_file_once() {
+        let dir = std::env::temp_dir().join(format!("saturn_poll_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let folder = dir.to_string_lossy().to_string();
+
+        // Files are left in place, so every scan sees them again
+        let handled = Arc::new(Mutex::new(Vec::<String>::new()));
+        let control = Arc::new(WatchControl::new());
+        let watcher = {
+            let (handled, control, folder) = (handled.clone(), control.clone(), folder.clone());
+            std::thread::spawn(move || {
+                watch_pending_folder(
+                    &folder,
+                    &control,
+                    || WatchSettings {
+                        mode: watch_settings::WatchMode::Poll,
+                        poll_interval_ms: 100,
+                        ..WatchSettings::default()
+                    },
+                    |path| {
+                        handled.lock().push(path.file_name().unwrap().to_string_lossy().to_string());
+                        FileOutcome::Done
+                    },
+                    |_| {},
+                )
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(200));
+        std::fs::write(dir.join("dropped.json"), "{}").unwrap();
+        std::fs::write(dir.join("dropped.json.tmp"), "{}").unwrap();
+        assert!(wait_for(|| !handled.lock().is_empty()));
+        std::thread::sleep(Duration::from_millis(600));
+
+        control.stop();
+        watcher.join().unwrap();
+        assert_eq!(*handled.lock(), vec!["dropped.json".to_string()]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_locked_file_is_offered_again() {
+        let dir = std::env::temp_dir().join(format!("saturn_locked_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("locked.json");
+        std::fs::write(&path, "{}").unwrap();
+
+        let mut seen = SeenFiles::default();
+        let offered = Mutex::new(0);
+        let locked_once = |_: &Path| {
+            *offered.lock() += 1;
+            if *offered.lock() == 1 {
+                FileOutcome::Retry
+            } else {
+                FileOutcome::Done
+            }
+        };
+
+        handle_file(&path, &mut seen, &locked_once);
+        assert!(seen.retry_pending);
+        scan_folder(&dir.to_string_lossy(), &mut seen, &locked_once).unwrap();
+        assert!(!seen.retry_pending);
+        // Handled now: later scans leave it alone
+        scan_folder(&dir.to_string_lossy(), &mut seen, &locked_once).unwrap();
+        assert_eq!(*offered.lock(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_permission_denied_is_not_a_lock() {
+        assert!(!is_lock_error(&std::io::Error::from(std::io::ErrorKind::PermissionDenied)));
+    }
+
+    #[test]
+    fn test_watch_control_stalls_without_ticks() {
+        let control = WatchControl::new();
+        let later = Instant::now() + Duration::from_secs(10);
+        assert!(control.is_stalled(later, Duration::from_secs(5)));
+        assert!(!control.is_stalled(later, Duration::from_secs(60)));
+    }
+
+    fn receiver_ids(config: &CopierConfig) -> Vec<&str> {
+        config.receivers.iter().map(|r| r.terminal_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_events_route_only_to_their_masters_receivers() {
+        let config = CopierConfig {
+            version: 1,
+            config_hash: String::new(),
+            master: master("A"),
+            receivers: vec![receiver("R1", None), receiver("R2", Some("A")), receiver("R3", Some("B"))],
+            masters: vec![master("A"), master("B")],
+            execution_strategy: Default::default(),
+        };
+        assert_eq!(config.all_masters().len(), 2);
+
+        let from_b = route_config(&config, Some("B")).unwrap();
+        assert_eq!(from_b.master.terminal_id, "T-B");
+        assert_eq!(receiver_ids(&from_b), vec!["R3"]);
+
+        let from_a = route_config(&config, Some("A")).unwrap();
+        assert_eq!(receiver_ids(&from_a), vec!["R1", "R2"]);
+
+        // Removed master: nothing to route to
+        assert!(route_config(&config, Some("C")).is_none());
+    }
+
+    #[test]
+    fn test_single_master_config_routes_to_all_receivers() {
+        let legacy: CopierConfig = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": "h",
+            "master": {"account_id": "A", "account_number": "1", "broker": "B", "terminal_id": "T-A"},
+            "receivers": []
+        }))
+        .unwrap();
+        let config = CopierConfig {
+            receivers: vec![receiver("R1", None), receiver("R2", None)],
+            ..legacy
+        };
+
+        assert_eq!(receiver_ids(&route_config(&config, None).unwrap()), vec!["R1", "R2"]);
+        assert_eq!(receiver_ids(&route_config(&config, Some("A")).unwrap()), vec!["R1", "R2"]);
+    }
+
+    #[test]
+    fn test_growing_file_parses_once_complete() {
+        let dir = std::env::temp_dir().join(format!("saturn_read_test_{}", uuid::Uuid::new_v4()));
+        let pending = dir.join("pending");
+        std::fs::create_dir_all(&pending).unwrap();
+        let path = pending.join("event.json");
+        std::fs::write(&path, r#"{"event_type": "entry", "tick"#).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(60));
+                let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+                std::io::Write::write_all(&mut file, br#"et": 42}"#).unwrap();
+            })
+        };
+
+        let settings = WatchSettings::default();
+        let event: serde_json::Value = read_event_with_retry(&path, &settings).unwrap();
+        writer.join().unwrap();
+        assert_eq!(event["ticket"], 42);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unparseable_file_is_quarantined_after_retries() {
+        let dir = std::env::temp_dir().join(format!("saturn_read_test_{}", uuid::Uuid::new_v4()));
+        let pending = dir.join("pending");
+        std::fs::create_dir_all(&pending).unwrap();
+        let path = pending.join("bad.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let settings = WatchSettings {
+            read_attempts: 3,
+            read_retry_ms: 30,
+            ..WatchSettings::default()
+        };
+        let result = read_event_with_retry::<serde_json::Value>(&path, &settings);
+        assert!(matches!(result, Err(EventFileError::Invalid(_))));
+
+        let target = quarantine_file(&path).unwrap();
+        assert_eq!(target, dir.join(QUARANTINE_FOLDER).join("bad.json"));
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "not json");
+
+        assert!(matches!(
+            read_event_with_retry::<serde_json::Value>(&path, &settings),
+            Err(EventFileError::Gone)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_malformed_event_file_raises_parse_error_alert() {
+        let dir = std::env::temp_dir().join(format!("saturn_read_test_{}", uuid::Uuid::new_v4()));
+        let pending = dir.join("pending");
+        std::fs::create_dir_all(&pending).unwrap();
+        let file_name = format!("{}.json", uuid::Uuid::new_v4());
+        let path = pending.join(&file_name);
+        std::fs::write(&path, r#"{"event_type": "entry", "ticket": }"#).unwrap();
+
+        let before = parse_errors::get_parse_errors().total;
+        process_event_file(&path, None, Arc::new(Mutex::new(CopierState::default())));
+
+        let report = parse_errors::get_parse_errors();
+        assert!(report.total > before);
+        let recorded = report.recent.iter().find(|e| e.file_name == file_name).unwrap();
+        assert!(recorded.error.contains("expected value"));
+        assert!(recorded.quarantined_to.is_some());
+        assert!(crate::copier::alerts::get_alerts()
+            .iter()
+            .any(|a| a.severity == crate::copier::alerts::AlertSeverity::Warning && a.message.contains(&file_name)));
+        assert!(dir.join(QUARANTINE_FOLDER).join(&file_name).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_retry_delays_double_within_window() {
+        let settings = WatchSettings::default();
+        let delays: Vec<u64> = (0..4).map(|a| settings.read_retry_delay(a).as_millis() as u64).collect();
+        assert_eq!(delays, vec![17, 33, 67, 133]);
+        let single = WatchSettings {
+            read_attempts: 1,
+            ..WatchSettings::default()
+        };
+        assert_eq!(single.read_retry_delay(0), Duration::ZERO);
+    }
 }
@@ -0,0 +1,215 @@
+//! Global daily entry cap
+//!
+//! `max_trades_per_day` limits each receiver on its own; this is the circuit
+//! breaker across all of them. Every copied entry (on any receiver) counts
+//! towards one daily total, and once it reaches the configured cap the
+//! copier is stopped: that many entries in a day points at a malfunctioning
+//! master rather than normal trading. The count resets with the safety
+//! trading day and is persisted so a restart doesn't clear it.
+
+use chrono::{NaiveDate, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use tracing::{error, warn};
+
+use super::safety;
+use crate::data_dir::app_data_dir;
+use super::{alerts, persistence, CopierState};
+
+const CAP_FILE: &str = "global_entry_cap.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalEntryCap {
+    /// Entries allowed across all receivers per trading day (None = no cap)
+    #[serde(default)]
+    pub max_entries_per_day: Option<u32>,
+    /// Trading day `entries_today` counts
+    #[serde(default)]
+    pub trading_day: Option<NaiveDate>,
+    #[serde(default)]
+    pub entries_today: u32,
+}
+
+impl GlobalEntryCap {
+    /// Count one copied entry on `day`. Returns why copying must stop when
+    /// the count has reached the cap.
+    pub fn count_entry(&mut self, day: NaiveDate) -> Option<String> {
+        if self.trading_day != Some(day) {
+            self.trading_day = Some(day);
+            self.entries_today = 0;
+        }
+        self.entries_today = self.entries_today.saturating_add(1);
+        let cap = self.max_entries_per_day?;
+        (self.entries_today >= cap).then(|| {
+            format!(
+                "Global daily entry cap reached: {} entries copied today (cap {})",
+                self.entries_today, cap
+            )
+        })
+    }
+
+    /// Entries counted on `day`
+    pub fn entries_on(&self, day: NaiveDate) -> u32 {
+        if self.trading_day == Some(day) {
+            self.entries_today
+        } else {
+            0
+        }
+    }
+}
+
+fn get_cap_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(CAP_FILE))
+}
+
+fn load() -> GlobalEntryCap {
+    get_cap_path()
+        .and_then(|path| {
+            persistence::load_state_file(&path, "Global entry cap", |content| {
+                serde_json::from_str::<GlobalEntryCap>(content).map_err(|e| e.to_string())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Write the cap and today's count (atomic write)
+fn save(cap: &GlobalEntryCap) -> Result<(), String> {
+    let Some(path) = get_cap_path() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(cap).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write global entry cap: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save global entry cap: {}", e))
+}
+
+static CAP: LazyLock<Mutex<GlobalEntryCap>> = LazyLock::new(|| Mutex::new(load()));
+
+fn current_trading_day() -> NaiveDate {
+    safety::get_trading_day(Utc::now(), safety::get_daily_reset_hour())
+}
+
+/// Count an entry against `cap`, stopping the copier when the cap is reached
+fn record_entry_in(cap: &mut GlobalEntryCap, state: &Arc<Mutex<CopierState>>, day: NaiveDate) {
+    let Some(reason) = cap.count_entry(day) else {
+        return;
+    };
+
+    let was_running = {
+        let mut copier = state.lock();
+        let was_running = copier.is_running;
+        copier.is_running = false;
+        copier.last_error = Some(reason.clone());
+        was_running
+    };
+    if was_running {
+        error!("{} - copier stopped", reason);
+        alerts::push_alert(alerts::AlertSeverity::Critical, format!("{} - copier stopped", reason));
+    }
+}
+
+/// Count a copied entry towards today's global total
+pub fn record_entry(state: &Arc<Mutex<CopierState>>) {
+    let mut cap = CAP.lock();
+    record_entry_in(&mut cap, state, current_trading_day());
+    if let Err(e) = save(&cap) {
+        warn!("Failed to persist global entry count: {}", e);
+    }
+}
+
+/// The configured cap and today's count
+pub fn current() -> GlobalEntryCap {
+    let cap = CAP.lock();
+    let today = current_trading_day();
+    GlobalEntryCap {
+        max_entries_per_day: cap.max_entries_per_day,
+        trading_day: Some(today),
+        entries_today: cap.entries_on(today),
+    }
+}
+
+/// Set (or clear, with None) the global daily entry cap
+pub fn set_max_entries_per_day(max_entries_per_day: Option<u32>) -> Result<(), String> {
+    if max_entries_per_day == Some(0) {
+        return Err("Global entry cap must be at least 1".to_string());
+    }
+    let mut cap = CAP.lock();
+    let updated = GlobalEntryCap {
+        max_entries_per_day,
+        ..cap.clone()
+    };
+    save(&updated)?;
+    *cap = updated;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    fn running_state() -> Arc<Mutex<CopierState>> {
+        let state = Arc::new(Mutex::new(CopierState::default()));
+        state.lock().is_running = true;
+        state
+    }
+
+    #[test]
+    fn test_count_resets_on_new_trading_day() {
+        let mut cap = GlobalEntryCap {
+            max_entries_per_day: Some(3),
+            ..Default::default()
+        };
+        assert!(cap.count_entry(day(10)).is_none());
+        assert!(cap.count_entry(day(10)).is_none());
+        assert!(cap.count_entry(day(11)).is_none());
+        assert_eq!(cap.entries_on(day(11)), 1);
+        assert_eq!(cap.entries_on(day(10)), 0);
+
+        // Without a cap entries are only counted
+        let mut uncapped = GlobalEntryCap::default();
+        for _ in 0..100 {
+            assert!(uncapped.count_entry(day(10)).is_none());
+        }
+        assert_eq!(uncapped.entries_on(day(10)), 100);
+    }
+
+    #[test]
+    fn test_cap_halts_copying() {
+        let mut cap = GlobalEntryCap {
+            max_entries_per_day: Some(3),
+            ..Default::default()
+        };
+        let state = running_state();
+
+        record_entry_in(&mut cap, &state, day(10));
+        record_entry_in(&mut cap, &state, day(10));
+        assert!(state.lock().is_running);
+
+        record_entry_in(&mut cap, &state, day(10));
+        let copier = state.lock();
+        assert!(!copier.is_running);
+        assert!(copier.last_error.as_deref().unwrap().contains("Global daily entry cap"));
+        drop(copier);
+
+        // Restarting the same day halts again on the next entry
+        state.lock().is_running = true;
+        record_entry_in(&mut cap, &state, day(10));
+        assert!(!state.lock().is_running);
+
+        // A new trading day starts from zero
+        state.lock().is_running = true;
+        record_entry_in(&mut cap, &state, day(11));
+        assert!(state.lock().is_running);
+        assert_eq!(cap.entries_on(day(11)), 1);
+    }
+}
@@ -0,0 +1,280 @@
+//! Health snapshot
+//!
+//! One cheap call summarising copier health for support and the status UI:
+//! queue counts, today's execution stats, per-receiver safety pauses, master
+//! heartbeats, terminal clock skew, discovery cache age and config age. Everything is read from in-memory
+//! state or heartbeat files; discovery is never refreshed from here.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::clock_skew::{self, ClockSkew};
+use super::execution_queue::{SharedExecutionQueue, EXECUTION_QUEUE};
+use super::safety::{self, ReceiverSafetyState};
+use super::stale_config;
+use super::{commands, CopierState};
+
+/// Discovery cache older than this means nothing is polling terminals
+const STALE_DISCOVERY_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSnapshot {
+    pub timestamp: String,
+    pub is_running: bool,
+    pub queue_pending: usize,
+    pub queue_in_progress: usize,
+    pub completed_today: usize,
+    pub failed_today: usize,
+    pub masters: Vec<MasterHealth>,
+    pub receivers: Vec<ReceiverHealth>,
+    /// Seconds since terminals were last discovered (None if never)
+    pub discovery_cache_age_secs: Option<u64>,
+    pub config_from_cache: bool,
+    pub config_cache_age_secs: Option<u64>,
+    /// Seconds since the config was last synced from the cloud (None if never)
+    pub config_age_secs: Option<u64>,
+    /// Human-readable summary of anything abnormal
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasterHealth {
+    pub account_number: String,
+    pub terminal_id: String,
+    /// Heartbeat seen within the last 30 seconds
+    pub online: bool,
+    /// Terminal clock minus local clock (None without a heartbeat)
+    pub estimated_skew_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiverHealth {
+    pub account_number: String,
+    pub terminal_id: String,
+    pub is_safety_paused: bool,
+    pub pause_reason: Option<String>,
+    /// Terminal clock minus local clock (None without account info)
+    pub estimated_skew_secs: Option<i64>,
+}
+
+/// Build the snapshot for the current app state
+pub fn health_snapshot(state: &CopierState) -> HealthSnapshot {
+    build_snapshot(
+        state,
+        &EXECUTION_QUEUE,
+        &safety::get_all_receiver_states(),
+        commands::is_master_online,
+        clock_skew::measure,
+        crate::mt5::discovery::discovery_cache_age().map(|age| age.as_secs()),
+        stale_config::current().stale_after_secs,
+    )
+}
+
+fn build_snapshot(
+    state: &CopierState,
+    queue: &SharedExecutionQueue,
+    safety_states: &HashMap<String, ReceiverSafetyState>,
+    master_online: impl Fn(&str) -> bool,
+    skew: impl Fn(&str) -> Option<ClockSkew>,
+    discovery_cache_age_secs: Option<u64>,
+    stale_after_secs: u64,
+) -> HealthSnapshot {
+    let stats = queue.today_stats();
+    let mut warnings = Vec::new();
+    let mut skew_warnings = Vec::new();
+    let mut skew_of = |terminal_id: &str| {
+        let measured = skew(terminal_id)?;
+        skew_warnings.extend(measured.warning);
+        Some(measured.estimated_skew_secs)
+    };
+
+    let masters: Vec<MasterHealth> = state
+        .config
+        .iter()
+        .flat_map(|c| c.all_masters())
+        .map(|m| MasterHealth {
+            account_number: m.account_number.clone(),
+            terminal_id: m.terminal_id.clone(),
+            online: master_online(&m.terminal_id),
+            estimated_skew_secs: skew_of(&m.terminal_id),
+        })
+        .collect();
+
+    // Safety state is keyed by receiver account number
+    let receivers: Vec<ReceiverHealth> = state
+        .config
+        .iter()
+        .flat_map(|c| c.receivers.iter())
+        .map(|r| {
+            let safety = safety_states.get(&r.account_number);
+            ReceiverHealth {
+                account_number: r.account_number.clone(),
+                terminal_id: r.terminal_id.clone(),
+                is_safety_paused: safety.is_some_and(|s| s.is_safety_paused),
+                pause_reason: safety.and_then(|s| s.pause_reason.clone()),
+                estimated_skew_secs: skew_of(&r.terminal_id),
+            }
+        })
+        .collect();
+
+    if state.config.is_none() {
+        warnings.push("No copier config loaded".to_string());
+    }
+    for master in masters.iter().filter(|m| !m.online) {
+        warnings.push(format!("Master {} is offline (no recent heartbeat)", master.account_number));
+    }
+    for receiver in receivers.iter().filter(|r| r.is_safety_paused) {
+        warnings.push(format!(
+            "Receiver {} is safety paused: {}",
+            receiver.account_number,
+            receiver.pause_reason.as_deref().unwrap_or("no reason recorded")
+        ));
+    }
+    warnings.extend(skew_warnings);
+    if state.config_from_cache {
+        warnings.push(format!(
+            "Config loaded from local cache ({}s old) - cloud unreachable",
+            state.config_cache_age_secs.unwrap_or(0)
+        ));
+    }
+    if discovery_cache_age_secs.is_some_and(|age| age > STALE_DISCOVERY_SECS) {
+        warnings.push("Terminal discovery hasn't run for over 5 minutes".to_string());
+    }
+    let config_age_secs = stale_config::config_age_secs(state.last_sync.as_deref(), chrono::Utc::now());
+    warnings.extend(stale_config::stale_config_warning(config_age_secs, stale_after_secs));
+
+    HealthSnapshot {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        is_running: state.is_running,
+        queue_pending: queue.pending_count(),
+        queue_in_progress: queue.in_progress_count(),
+        completed_today: stats.completed_today,
+        failed_today: stats.failed_today,
+        masters,
+        receivers,
+        discovery_cache_age_secs,
+        config_from_cache: state.config_from_cache,
+        config_cache_age_secs: state.config_cache_age_secs,
+        config_age_secs,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::execution_queue::{ExecutionQueue, QueuedExecution};
+    use crate::copier::{CopierConfig, MasterConfig, ReceiverConfig};
+    use crate::copier::test_support::{test_event, test_receiver};
+    use chrono::Utc;
+
+    const DAY: u64 = stale_config::DEFAULT_STALE_AFTER_SECS;
+
+    fn receiver(account_number: &str) -> ReceiverConfig {
+        ReceiverConfig {
+            account_id: account_number.to_string(),
+            account_number: account_number.to_string(),
+            terminal_id: format!("T{}", account_number),
+            ..test_receiver()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_from_seeded_state() {
+        let queue = SharedExecutionQueue::new(ExecutionQueue::new(None));
+        queue.update(|q| {
+            for key in ["k1", "k2", "k3"] {
+                q.defer(QueuedExecution::new(test_event("entry", 1), "R1", key), Utc::now(), "rate limited");
+            }
+            let done = q.dequeue_ready(Utc::now()).unwrap();
+            q.mark_completed(&done.id);
+            // Left in flight
+            q.dequeue_ready(Utc::now()).unwrap();
+        });
+
+        let state = CopierState {
+            config: Some(CopierConfig {
+                version: 1,
+                config_hash: "h".to_string(),
+                master: MasterConfig {
+                    account_id: "m".to_string(),
+                    account_number: "1001".to_string(),
+                    broker: "B".to_string(),
+                    terminal_id: "M1".to_string(),
+                },
+                receivers: vec![receiver("2001"), receiver("2002")],
+                masters: vec![],
+                execution_strategy: Default::default(),
+            }),
+            is_running: true,
+            config_from_cache: true,
+            config_cache_age_secs: Some(600),
+            ..Default::default()
+        };
+
+        let mut safety_states = HashMap::new();
+        safety_states.insert(
+            "2002".to_string(),
+            ReceiverSafetyState {
+                is_safety_paused: true,
+                pause_reason: Some("Daily loss limit".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let snapshot = build_snapshot(&state, &queue, &safety_states, |_| false, |_| None, Some(5), DAY);
+
+        assert_eq!(snapshot.queue_pending, 1);
+        assert_eq!(snapshot.queue_in_progress, 1);
+        assert_eq!(snapshot.completed_today, 1);
+        assert_eq!(snapshot.failed_today, 0);
+        assert!(!snapshot.masters[0].online);
+        assert!(!snapshot.receivers[0].is_safety_paused);
+        assert!(snapshot.receivers[1].is_safety_paused);
+        assert_eq!(snapshot.discovery_cache_age_secs, Some(5));
+
+        assert_eq!(snapshot.warnings.len(), 3);
+        assert!(snapshot.warnings[0].contains("Master 1001 is offline"));
+        assert!(snapshot.warnings[1].contains("2002 is safety paused: Daily loss limit"));
+        assert!(snapshot.warnings[2].contains("local cache"));
+
+        // Healthy state: no warnings
+        let healthy = CopierState {
+            config_from_cache: false,
+            config_cache_age_secs: None,
+            ..state
+        };
+        let snapshot = build_snapshot(&healthy, &queue, &HashMap::new(), |_| true, |_| None, Some(5), DAY);
+        assert!(snapshot.warnings.is_empty());
+
+        // A receiver terminal running 30s ahead
+        let now = Utc::now();
+        let ahead = |terminal_id: &str| {
+            let stamped = (now + chrono::Duration::seconds(30)).to_rfc3339();
+            (terminal_id == "T2001").then(|| clock_skew::estimate_skew(terminal_id, &stamped, now)).flatten()
+        };
+        let snapshot = build_snapshot(&healthy, &queue, &HashMap::new(), |_| true, ahead, Some(5), DAY);
+        assert_eq!(snapshot.receivers[0].estimated_skew_secs, Some(30));
+        assert_eq!(snapshot.warnings.len(), 1);
+        assert!(snapshot.warnings[0].contains("T2001 clock is 30s ahead"));
+    }
+
+    #[test]
+    fn test_stale_config_warning() {
+        let queue = SharedExecutionQueue::new(ExecutionQueue::new(None));
+        let state = CopierState {
+            last_sync: Some((Utc::now() - chrono::Duration::days(2)).to_rfc3339()),
+            ..Default::default()
+        };
+        let snapshot = build_snapshot(&state, &queue, &HashMap::new(), |_| true, |_| None, Some(5), DAY);
+        assert!(snapshot.config_age_secs.is_some_and(|age| age >= 2 * DAY));
+        assert!(snapshot.warnings.iter().any(|w| w.starts_with("Config last synced 48h ago")));
+
+        let fresh = CopierState {
+            last_sync: Some(Utc::now().to_rfc3339()),
+            ..state
+        };
+        let snapshot = build_snapshot(&fresh, &queue, &HashMap::new(), |_| true, |_| None, Some(5), DAY);
+        assert!(!snapshot.warnings.iter().any(|w| w.starts_with("Config last synced")));
+    }
+}
@@ -0,0 +1,302 @@
+//! Config hot-reload
+//!
+//! When a cloud sync brings a config whose content differs from the active
+//! one, every receiver terminal is re-provisioned with the new EA config so
+//! the change takes effect without a restart. Content is compared by
+//! `config_hash`, so a re-sync that only bumps timestamps is a no-op. The
+//! execution queue is left alone: in-flight executions finish against the
+//! receivers they were queued for. Watch targets and catch-up read the
+//! config from `CopierState` on every pass and pick the change up on their
+//! own.
+//!
+//! Re-provisioning runs on one worker thread; configs that arrive while it
+//! is busy replace each other, so only the latest is written next. Safety
+//! settings the cloud config doesn't carry (drawdown, minimum equity, poll
+//! interval...) keep the values the setup wizard provisioned.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use super::config_generator::{
+    self, CopierConfigFile, MasterConfigFile, ProvisionSummary, ReceiverConfigFile, RiskConfig, SafetyConfig,
+};
+use super::{manual_mappings, receiver_toggles, CopierConfig, CopierState, ReceiverConfig};
+
+/// Make `config` the active config. Returns true if its content changed
+/// (or there was none before), i.e. receivers need re-provisioning.
+pub fn install_config(state: &mut CopierState, mut config: CopierConfig) -> bool {
+    receiver_toggles::apply_all(&mut config);
+    manual_mappings::apply_all(&mut config);
+    crate::redact::register_accounts(&crate::redact::config_accounts(&config));
+    let changed = state
+        .config
+        .as_ref()
+        .is_none_or(|active| active.config_hash != config.config_hash);
+    state.config_version = config.version;
+    state.config = Some(config);
+    changed
+}
+
+/// Config waiting for the re-provisioning worker, and whether it runs
+#[derive(Default)]
+struct ReprovisionQueue {
+    pending: Option<CopierConfig>,
+    running: bool,
+}
+
+impl ReprovisionQueue {
+    /// Queue `config` in place of any config still waiting. True if the
+    /// caller must start the worker.
+    fn offer(&mut self, config: CopierConfig) -> bool {
+        self.pending = Some(config);
+        !std::mem::replace(&mut self.running, true)
+    }
+
+    /// The worker's next config; None stops it
+    fn next(&mut self) -> Option<CopierConfig> {
+        let next = self.pending.take();
+        self.running = next.is_some();
+        next
+    }
+}
+
+static REPROVISION: LazyLock<Mutex<ReprovisionQueue>> = LazyLock::new(Default::default);
+
+/// Re-provision receiver terminals for `config` off the caller's thread
+/// (terminal folders can be slow to reach). Serialized with earlier calls:
+/// the latest config wins.
+pub fn queue_reprovision(config: CopierConfig) {
+    if !REPROVISION.lock().offer(config) {
+        return;
+    }
+    std::thread::spawn(|| {
+        while let Some(config) = REPROVISION.lock().next() {
+            reprovision_receivers(&config);
+        }
+    });
+}
+
+/// Safety settings provisioned on a receiver terminal, from the setup
+/// wizard or an earlier re-provision
+fn provisioned_safety(terminal_id: &str) -> Option<SafetyConfig> {
+    config_generator::read_provisioned_config(terminal_id)?
+        .receivers
+        .into_iter()
+        .find(|r| r.terminal_id == terminal_id)
+        .map(|r| r.safety)
+}
+
+/// EA config file for one receiver entry of a cloud config. Safety fields
+/// the cloud config lacks come from `provisioned`, else the defaults.
+fn receiver_config_file(receiver: &ReceiverConfig, provisioned: Option<SafetyConfig>) -> ReceiverConfigFile {
+    let defaults = provisioned.unwrap_or_default();
+    ReceiverConfigFile {
+        receiver_id: receiver.account_id.clone(),
+        account_name: format!("{} - {}", receiver.broker, receiver.account_number),
+        account_number: receiver.account_number.clone(),
+        broker: receiver.broker.clone(),
+        terminal_id: receiver.terminal_id.clone(),
+        risk: RiskConfig {
+            mode: receiver.risk_mode.clone(),
+            value: receiver.risk_value,
+        },
+        safety: SafetyConfig {
+            max_slippage_pips: receiver.max_slippage_pips,
+            max_daily_loss_r: receiver.max_daily_loss_r.unwrap_or(defaults.max_daily_loss_r),
+            prop_firm_safe_mode: receiver.prop_firm_safe_mode,
+            ..defaults
+        },
+        symbol_mappings: receiver
+            .symbol_mappings
+            .iter()
+            .filter(|m| m.is_enabled)
+            .map(|m| (m.master_symbol.clone(), m.receiver_symbol.clone()))
+            .collect::<HashMap<_, _>>(),
+        symbol_overrides: None,
+    }
+}
+
+/// EA config files for a cloud config, one per master group (a receiver's
+/// file names the master it follows)
+pub fn config_files(config: &CopierConfig) -> Vec<CopierConfigFile> {
+    config_files_with(config, provisioned_safety)
+}
+
+/// `config_files` with the provisioned safety settings looked up by
+/// `provisioned` (by receiver terminal id)
+fn config_files_with(config: &CopierConfig, provisioned: impl Fn(&str) -> Option<SafetyConfig>) -> Vec<CopierConfigFile> {
+    config
+        .all_masters()
+        .into_iter()
+        .filter_map(|master| config.for_master(&master.account_id))
+        .filter(|group| !group.receivers.is_empty())
+        .map(|group| {
+            let mut file = config_generator::build_config_file(
+                &group.master.terminal_id,
+                &group.master.account_number,
+                &group.master.broker,
+                group
+                    .receivers
+                    .iter()
+                    .map(|receiver| receiver_config_file(receiver, provisioned(&receiver.terminal_id)))
+                    .collect(),
+            );
+            file.version = group.version;
+            file.master = MasterConfigFile {
+                account_id: group.master.account_id.clone(),
+                ..file.master
+            };
+            file.config_hash = config_generator::generate_config_hash(&file);
+            file
+        })
+        .collect()
+}
+
+/// Re-provision every receiver terminal with `config`. Failures are logged
+/// and skipped so one offline terminal doesn't block the rest.
+pub fn reprovision_receivers(config: &CopierConfig) -> Vec<ProvisionSummary> {
+    let mut summaries = Vec::new();
+    for file in config_files(config) {
+        for receiver in &file.receivers {
+            match config_generator::provision_terminal(&receiver.terminal_id, &file) {
+                Ok(summary) => summaries.push(summary),
+                Err(e) => warn!("Re-provisioning {} failed: {}", receiver.terminal_id, e),
+            }
+        }
+    }
+    info!(
+        "Config v{} applied to {} receiver terminal(s)",
+        config.version,
+        summaries.len()
+    );
+    summaries
+}
+
+/// Re-provision one receiver terminal with its entry of `config`
+pub fn reprovision_receiver(config: &CopierConfig, terminal_id: &str) -> Result<ProvisionSummary, String> {
+    let file = config_files(config)
+        .into_iter()
+        .find(|file| file.receivers.iter().any(|r| r.terminal_id == terminal_id))
+        .ok_or_else(|| format!("Receiver terminal {} is not in the current config", terminal_id))?;
+    config_generator::provision_terminal(terminal_id, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::MasterConfig;
+
+    fn cloud_config(version: i32, config_hash: &str) -> CopierConfig {
+        serde_json::from_value(serde_json::json!({
+            "version": version,
+            "config_hash": config_hash,
+            "master": {
+                "account_id": "m1",
+                "account_number": "1001",
+                "broker": "A",
+                "terminal_id": "M1"
+            },
+            "receivers": [{
+                "account_id": "r1",
+                "account_number": "2001",
+                "broker": "B",
+                "terminal_id": "R1",
+                "risk_mode": "fixed_lot",
+                "risk_value": 0.1,
+                "max_slippage_pips": 3.0,
+                "max_daily_loss_r": null,
+                "prop_firm_safe_mode": false,
+                "symbol_mappings": [
+                    { "master_symbol": "XAUUSD", "receiver_symbol": "GOLD", "is_enabled": true },
+                    { "master_symbol": "US30", "receiver_symbol": "DJ30", "is_enabled": false }
+                ]
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_config_version_triggers_reprovision() {
+        let mut state = CopierState::default();
+        assert!(install_config(&mut state, cloud_config(1, "aaa")));
+        assert_eq!(state.config_version, 1);
+
+        // Same content re-synced (only timestamps differ upstream)
+        assert!(!install_config(&mut state, cloud_config(1, "aaa")));
+
+        // New version with new content
+        assert!(install_config(&mut state, cloud_config(2, "bbb")));
+        assert_eq!(state.config_version, 2);
+    }
+
+    #[test]
+    fn test_config_files_group_receivers_by_master() {
+        let mut config = cloud_config(3, "ccc");
+        config.masters.push(MasterConfig {
+            account_id: "m2".to_string(),
+            account_number: "1002".to_string(),
+            broker: "A".to_string(),
+            terminal_id: "M2".to_string(),
+        });
+        let mut second = config.receivers[0].clone();
+        second.terminal_id = "R2".to_string();
+        second.master_account_id = Some("m2".to_string());
+        config.receivers.push(second);
+
+        let files = config_files_with(&config, |_| None);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].master.terminal_id, "M1");
+        assert_eq!(files[0].receivers[0].terminal_id, "R1");
+        assert_eq!(files[1].master.account_id, "m2");
+        assert_eq!(files[1].receivers[0].terminal_id, "R2");
+
+        let file = &files[0];
+        assert_eq!(file.version, 3);
+        assert!(config_generator::verify_config_hash(file).is_ok());
+        let mappings = &file.receivers[0].symbol_mappings;
+        assert_eq!(mappings.get("XAUUSD").map(String::as_str), Some("GOLD"));
+        assert!(!mappings.contains_key("US30"));
+    }
+
+    #[test]
+    fn test_reprovision_keeps_wizard_safety_settings() {
+        let wizard = SafetyConfig {
+            max_slippage_pips: 9.0,
+            max_daily_loss_r: 2.0,
+            max_drawdown_percent: Some(8.0),
+            trailing_drawdown_enabled: true,
+            min_equity: Some(9000.0),
+            poll_interval_ms: 250,
+            ..Default::default()
+        };
+        let files = config_files_with(&cloud_config(1, "aaa"), |terminal_id| {
+            (terminal_id == "R1").then(|| wizard.clone())
+        });
+        let safety = &files[0].receivers[0].safety;
+
+        assert_eq!(safety.max_drawdown_percent, Some(8.0));
+        assert!(safety.trailing_drawdown_enabled);
+        assert_eq!(safety.min_equity, Some(9000.0));
+        assert_eq!(safety.poll_interval_ms, 250);
+        // The cloud config's own settings still win; an unset daily loss
+        // limit keeps the provisioned one
+        assert_eq!(safety.max_slippage_pips, 3.0);
+        assert_eq!(safety.max_daily_loss_r, 2.0);
+    }
+
+    #[test]
+    fn test_reprovision_queue_runs_latest_config_once() {
+        let mut queue = ReprovisionQueue::default();
+        assert!(queue.offer(cloud_config(1, "aaa")));
+        // Arrivals while the worker is busy replace each other
+        assert!(!queue.offer(cloud_config(2, "bbb")));
+        assert!(!queue.offer(cloud_config(3, "ccc")));
+
+        assert_eq!(queue.next().map(|c| c.version), Some(3));
+        assert!(queue.next().is_none());
+        // The worker stopped, so the next config starts a new one
+        assert!(queue.offer(cloud_config(4, "ddd")));
+    }
+}
@@ -5,9 +5,11 @@
 use parking_lot::Mutex;
 use std::collections::{HashSet, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
+use super::persistence;
+
 /// Maximum number of keys to keep in memory
 const MAX_KEYS_IN_MEMORY: usize = 10_000;
 
@@ -78,11 +80,8 @@ static PROCESSED_KEYS: LazyLock<Mutex<IdempotencyCache>> = LazyLock::new(|| {
 
 /// Get the path to the idempotency file
 fn get_idempotency_file_path() -> Option<PathBuf> {
-    use super::safety::APP_DATA_FOLDER;
-    let appdata = std::env::var("APPDATA").ok()?;
-    Some(PathBuf::from(appdata)
-        .join(APP_DATA_FOLDER)
-        .join(IDEMPOTENCY_FILE))
+    use crate::data_dir::app_data_dir;
+    Some(app_data_dir()?.join(IDEMPOTENCY_FILE))
 }
 
 /// Load previously processed keys from disk (maintains file order = insertion order)
@@ -90,31 +89,47 @@ fn load_processed_keys() -> Result<Vec<String>, String> {
     let path = get_idempotency_file_path()
         .ok_or_else(|| "Failed to get idempotency file path".to_string())?;
     
-    if !path.exists() {
-        return Ok(Vec::new());
-    }
-    
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read idempotency file: {}", e))?;
-    
-    let keys: Vec<String> = content
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|s| s.to_string())
-        .collect();
+    Ok(load_processed_keys_from(&path))
+}
+
+/// Load processed keys from `path`, recovering a corrupt file if possible
+/// (see `persistence::load_state_file`)
+fn load_processed_keys_from(path: &Path) -> Vec<String> {
+    let Some(mut keys) = persistence::load_state_file(path, "Processed events log", parse_processed_keys) else {
+        return Vec::new();
+    };
     
     // Only keep the most recent keys to prevent unbounded growth
     if keys.len() > MAX_KEYS_IN_MEMORY {
         // Take the last MAX_KEYS_IN_MEMORY keys (most recent)
         let skip_count = keys.len().saturating_sub(MAX_KEYS_IN_MEMORY);
-        let recent_keys: Vec<String> = keys
-            .into_iter()
-            .skip(skip_count)
-            .collect();
-        return Ok(recent_keys);
+        keys.drain(..skip_count);
     }
     
-    Ok(keys)
+    keys
+}
+
+/// Keys are written one per line, each newline-terminated. Control
+/// characters (e.g. the zero fill a power loss leaves) mean the file is
+/// corrupt; a final line without its newline was cut off mid-append and is
+/// dropped.
+fn parse_processed_keys(content: &str) -> Result<Vec<String>, String> {
+    if content.chars().any(|c| c.is_control() && c != '\n' && c != '\r') {
+        return Err("contains control characters".to_string());
+    }
+    let complete = match content.rfind('\n') {
+        Some(end) => &content[..end],
+        None => "",
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        tracing::warn!("Dropping truncated last line of processed events log");
+    }
+    Ok(complete
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .map(|s| s.to_string())
+        .collect())
 }
 
 /// Save processed keys to disk (maintains FIFO order)
@@ -128,8 +143,8 @@ fn save_processed_keys(cache: &IdempotencyCache) -> Result<(), String> {
             .map_err(|e| format!("Failed to create idempotency directory: {}", e))?;
     }
     
-    // Join keys in order (oldest first, newest last)
-    let content = cache.to_vec().join("\n");
+    // Join keys in order (oldest first, newest last), newline-terminated
+    let content = snapshot_content(cache);
     
     // Write atomically via temp file
     let temp_path = path.with_extension("tmp");
@@ -142,6 +157,12 @@ fn save_processed_keys(cache: &IdempotencyCache) -> Result<(), String> {
     Ok(())
 }
 
+/// Full file contents for `cache`; every key ends with a newline so a
+/// truncated final line is detectable
+fn snapshot_content(cache: &IdempotencyCache) -> String {
+    cache.to_vec().iter().map(|key| format!("{}\n", key)).collect()
+}
+
 /// Check if an event has already been processed
 pub fn is_event_processed(idempotency_key: &str) -> bool {
     let cache = PROCESSED_KEYS.lock();
@@ -182,7 +203,7 @@ fn persist_append(new_key: &str, cache: &IdempotencyCache) {
     // the file to stay in sync. Otherwise append a single line.
     let cache_len = cache.len();
     if cache_len >= MAX_KEYS_IN_MEMORY {
-        let content = cache.to_vec().join("\n");
+        let content = snapshot_content(cache);
         let temp_path = path.with_extension("tmp");
         if let Err(e) = fs::write(&temp_path, &content) {
             tracing::warn!("Failed to write idempotency snapshot: {}", e);
@@ -207,7 +228,9 @@ fn persist_append(new_key: &str, cache: &IdempotencyCache) {
 
 /// Build the canonical idempotency key used end-to-end.
 ///
-/// Format: `{terminal_id}:{deal_id_or_position_id}:{event_type}`.
+/// Format: `{terminal_id}:{deal_id_or_position_id}:{event_type}`. Pending
+/// order events (`pending_order`, `pending_cancelled`) carry the order ticket
+/// in the middle slot instead, see `TradeEvent::idempotency_id`.
 /// This matches what the Master EA writes into each event JSON, what the
 /// Receiver execution log stores, and what the cloud's `events.idempotency_key`
 /// column expects.
@@ -282,4 +305,20 @@ mod tests {
         assert!(cache.contains("key3"));
         assert!(!cache.contains("key4"));
     }
+
+    #[test]
+    fn test_truncated_processed_events_file() {
+        // A cut-off append loses only the partial last key
+        assert_eq!(parse_processed_keys("T:1:entry\nT:2:entry\nT:3:en").unwrap(), vec!["T:1:entry", "T:2:entry"]);
+        assert!(parse_processed_keys("T:1:entry\n\0\0\0\0").is_err());
+
+        let dir = std::env::temp_dir().join(format!("saturn_idempotency_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(IDEMPOTENCY_FILE);
+        fs::write(&path, "T:1:entry\n\0\0\0\0").unwrap();
+        fs::write(path.with_extension("tmp"), "T:1:entry\nT:2:entry\n").unwrap();
+
+        assert_eq!(load_processed_keys_from(&path), vec!["T:1:entry", "T:2:entry"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
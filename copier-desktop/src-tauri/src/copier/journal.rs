@@ -0,0 +1,227 @@
+//! Event journal
+//!
+//! Append-only JSONL record of every `TradeEvent` the event processor saw:
+//! when it was picked up, which master group it was routed through and the
+//! receivers it was dispatched to. Support uses it to answer "why did (or
+//! didn't) this copy happen"; `replay` feeds a journal file back through
+//! the event processor's dry run.
+//!
+//! One file per UTC day, like the logs. Starting a new day's file deletes
+//! journals older than `JOURNAL_RETENTION_DAYS`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use super::event_processor::{self, DryRunRoute};
+use crate::data_dir::app_data_dir;
+use super::{CopierConfig, TradeEvent};
+
+/// Days of journal files kept, today included
+pub const JOURNAL_RETENTION_DAYS: i64 = 14;
+
+const JOURNAL_FOLDER: &str = "journal";
+
+/// File name prefix; the day is appended as `YYYY-MM-DD.jsonl`
+const JOURNAL_FILE_PREFIX: &str = "events-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub recorded_at: DateTime<Utc>,
+    /// Master group the event was processed for
+    pub master_account_id: String,
+    /// Carries `detected_at` from the file watcher
+    pub event: TradeEvent,
+    /// Receiver account numbers the event was dispatched to straight away
+    /// (not skipped, deferred or held for approval)
+    pub routed_to: Vec<String>,
+}
+
+/// Journal files in one folder. None = journaling off (no app data dir).
+pub struct Journal {
+    dir: Option<PathBuf>,
+    write_lock: Mutex<()>,
+}
+
+pub static JOURNAL: LazyLock<Journal> = LazyLock::new(|| Journal::new(default_dir()));
+
+fn default_dir() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(JOURNAL_FOLDER))
+}
+
+fn file_name(day: NaiveDate) -> String {
+    format!("{}{}.jsonl", JOURNAL_FILE_PREFIX, day.format("%Y-%m-%d"))
+}
+
+impl Journal {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Journal file for a UTC day
+    pub fn path_for(&self, day: NaiveDate) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(file_name(day)))
+    }
+
+    /// Append one line to today's file
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), String> {
+        let Some(path) = self.path_for(entry.recorded_at.date_naive()) else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock();
+        if !path.exists() {
+            if let Some(dir) = &self.dir {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create journal folder: {}", e))?;
+                prune(dir, entry.recorded_at.date_naive());
+            }
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to append to journal {:?}: {}", path, e))
+    }
+
+    /// Journal an event; failures are logged, never fatal to processing
+    pub fn record(&self, event: &TradeEvent, master_account_id: &str, routed_to: Vec<String>) {
+        let entry = JournalEntry {
+            recorded_at: Utc::now(),
+            master_account_id: master_account_id.to_string(),
+            event: event.clone(),
+            routed_to,
+        };
+        if let Err(e) = self.append(&entry) {
+            warn!("{}", e);
+        }
+    }
+}
+
+/// Delete journal files from before the retention window
+fn prune(dir: &Path, today: NaiveDate) {
+    let cutoff = today - chrono::Duration::days(JOURNAL_RETENTION_DAYS - 1);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let day = name
+            .strip_prefix(JOURNAL_FILE_PREFIX)
+            .and_then(|rest| rest.strip_suffix(".jsonl"))
+            .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok());
+        if day.is_some_and(|day| day < cutoff) {
+            match fs::remove_file(entry.path()) {
+                Ok(()) => info!("Deleted old event journal {}", name),
+                Err(e) => warn!("Failed to delete old event journal {}: {}", name, e),
+            }
+        }
+    }
+}
+
+/// Read a journal file. Unreadable lines (e.g. a torn final write) are skipped.
+pub fn read_entries(path: &Path) -> Result<Vec<JournalEntry>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read journal {:?}: {}", path, e))?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(n, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Skipping unreadable journal line {}: {}", n + 1, e);
+                None
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayedEvent {
+    pub entry: JournalEntry,
+    /// What the current config would do with the event, per receiver of its
+    /// master group (empty if that master is no longer configured)
+    pub routes: Vec<DryRunRoute>,
+}
+
+/// Re-feed a journal through the event processor in dry-run mode
+pub fn replay(path: &Path, config: &CopierConfig) -> Result<Vec<ReplayedEvent>, String> {
+    Ok(read_entries(path)?
+        .into_iter()
+        .map(|entry| {
+            let routes = config
+                .for_master(&entry.master_account_id)
+                .map(|routed| event_processor::process_event_dry_run(&entry.event, &routed))
+                .unwrap_or_default();
+            ReplayedEvent { entry, routes }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_journal() -> (Journal, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("saturn_journal_test_{}", uuid::Uuid::new_v4()));
+        (Journal::new(Some(dir.clone())), dir)
+    }
+
+    #[test]
+    fn test_new_day_prunes_files_past_retention() {
+        let (journal, dir) = temp_journal();
+        fs::create_dir_all(&dir).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        let oldest_kept = today - chrono::Duration::days(JOURNAL_RETENTION_DAYS - 1);
+        let expired = oldest_kept - chrono::Duration::days(1);
+        for day in [expired, oldest_kept] {
+            fs::write(journal.path_for(day).unwrap(), "").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        prune(&dir, today);
+        assert!(!journal.path_for(expired).unwrap().exists());
+        assert!(journal.path_for(oldest_kept).unwrap().exists());
+        assert!(dir.join("notes.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_torn_line_is_skipped() {
+        let (journal, dir) = temp_journal();
+        let event: TradeEvent = serde_json::from_value(serde_json::json!({
+            "event_type": "exit",
+            "ticket": 5,
+            "symbol": "EURUSD",
+            "direction": "buy",
+            "lots": 0.1,
+            "price": 1.1,
+            "timestamp": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        journal.record(&event, "m", vec!["2002".to_string()]);
+        let path = journal.path_for(Utc::now().date_naive()).unwrap();
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"recorded_at\": \"2024")
+            .unwrap();
+
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event.ticket, 5);
+        assert_eq!(entries[0].routed_to, vec!["2002"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,145 @@
+//! Global kill switch
+//!
+//! While a `KILL_SWITCH` file exists in the app data folder no trade is
+//! processed. Operators on headless/VPS installs can create it by hand (or
+//! via `engage_kill_switch`) to stop all copying without the UI.
+
+use parking_lot::Mutex;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::CopierState;
+
+/// Sentinel file name in the app data folder
+const KILL_SWITCH_FILE: &str = "KILL_SWITCH";
+
+/// How long a stat result is reused; keeps the per-event check cheap
+const CACHE_TTL: Duration = Duration::from_millis(500);
+
+/// Kill switch backed by a sentinel file, with a short-lived stat cache
+pub struct KillSwitch {
+    path: Option<PathBuf>,
+    /// (checked_at, engaged)
+    cache: Mutex<Option<(Instant, bool)>>,
+}
+
+impl KillSwitch {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Whether the sentinel file exists (cached for `CACHE_TTL`)
+    pub fn is_engaged(&self) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+
+        let mut cache = self.cache.lock();
+        if let Some((checked_at, engaged)) = *cache {
+            if checked_at.elapsed() < CACHE_TTL {
+                return engaged;
+            }
+        }
+
+        let engaged = path.exists();
+        *cache = Some((Instant::now(), engaged));
+        engaged
+    }
+
+    /// Create the sentinel file
+    pub fn engage(&self, reason: &str) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or("Kill switch path unavailable")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create kill switch directory: {}", e))?;
+        }
+        let content = format!("{}\n{}\n", chrono::Utc::now().to_rfc3339(), reason);
+        fs::write(path, content).map_err(|e| format!("Failed to create kill switch: {}", e))?;
+
+        *self.cache.lock() = None;
+        warn!("Kill switch engaged: {}", reason);
+        Ok(())
+    }
+
+    /// Remove the sentinel file (no-op if it doesn't exist)
+    pub fn release(&self) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or("Kill switch path unavailable")?;
+        match fs::remove_file(path) {
+            Ok(()) => info!("Kill switch released"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to remove kill switch: {}", e)),
+        }
+
+        *self.cache.lock() = None;
+        Ok(())
+    }
+
+    /// If engaged, log it and record it as the copier's last error.
+    /// Returns true when processing must stop.
+    pub fn blocks(&self, state: &Arc<Mutex<CopierState>>) -> bool {
+        if !self.is_engaged() {
+            return false;
+        }
+        let message = format!(
+            "Kill switch engaged - refusing to process trades (remove {:?} to resume)",
+            self.path.as_deref().unwrap_or(std::path::Path::new(KILL_SWITCH_FILE))
+        );
+        warn!("{}", message);
+        state.lock().last_error = Some(message);
+        true
+    }
+}
+
+/// Get the path to the sentinel file
+fn get_kill_switch_path() -> Option<PathBuf> {
+    use crate::data_dir::app_data_dir;
+    Some(app_data_dir()?.join(KILL_SWITCH_FILE))
+}
+
+/// Global kill switch in the app data folder
+pub static KILL_SWITCH: LazyLock<KillSwitch> = LazyLock::new(|| KillSwitch::new(get_kill_switch_path()));
+
+/// Whether the global kill switch is engaged (no logging)
+pub fn is_engaged() -> bool {
+    KILL_SWITCH.is_engaged()
+}
+
+/// Check the global kill switch; see [`KillSwitch::blocks`]
+pub fn blocks(state: &Arc<Mutex<CopierState>>) -> bool {
+    KILL_SWITCH.blocks(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_switch_file_blocks_processing() {
+        let path = std::env::temp_dir().join(format!("saturn_kill_switch_{}", uuid::Uuid::new_v4()));
+        let switch = KillSwitch::new(Some(path.clone()));
+        let state = Arc::new(Mutex::new(CopierState::default()));
+
+        assert!(!switch.blocks(&state));
+        assert!(state.lock().last_error.is_none());
+
+        // Created out-of-band, as an operator would on a VPS
+        fs::write(&path, "manual").unwrap();
+        std::thread::sleep(CACHE_TTL);
+        assert!(switch.blocks(&state));
+        assert!(state.lock().last_error.as_deref().unwrap().contains("Kill switch"));
+
+        switch.release().unwrap();
+        assert!(!path.exists());
+        assert!(!switch.blocks(&state));
+
+        switch.engage("test").unwrap();
+        assert!(switch.is_engaged());
+        switch.release().unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+//! Execution latency
+//!
+//! Splits copy latency into where the time went, per execution:
+//! - detection: master event timestamp -> file watcher picked the event up
+//! - total: picked up -> receiver result recorded (queueing + EA round-trip)
+//! - EA round-trip: command written -> result read back
+//!
+//! Successful executions are sampled into an in-memory buffer so diagnostics
+//! can show p50/p95/max for today.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::{Execution, TradeEvent};
+
+/// Samples kept; a busy day beyond this only loses the oldest samples
+const MAX_SAMPLES: usize = 5000;
+
+#[derive(Debug, Clone, Copy)]
+struct LatencySample {
+    recorded_at: DateTime<Utc>,
+    total_ms: Option<u64>,
+    detection_ms: Option<u64>,
+    ea_roundtrip_ms: Option<u64>,
+}
+
+static SAMPLES: LazyLock<Mutex<VecDeque<LatencySample>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// UTC day the summary covers
+    pub date: String,
+    pub total: LatencyStats,
+    pub detection: LatencyStats,
+    pub ea_roundtrip: LatencyStats,
+}
+
+fn millis_between(from: DateTime<Utc>, to: DateTime<Utc>) -> Option<u64> {
+    u64::try_from(to.signed_duration_since(from).num_milliseconds()).ok()
+}
+
+/// Fill in an execution's latency fields from its event
+pub fn stamp(execution: &mut Execution, event: &TradeEvent, ea_roundtrip: Option<Duration>, now: DateTime<Utc>) {
+    let detected_at = event
+        .detected_at
+        .as_deref()
+        .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| d.with_timezone(&Utc));
+    let Some(detected_at) = detected_at else {
+        return;
+    };
+    execution.latency_ms = millis_between(detected_at, now);
+    execution.detection_ms = super::event_processor::parse_event_timestamp(&event.timestamp)
+        .and_then(|opened| millis_between(opened, detected_at));
+    execution.ea_roundtrip_ms = ea_roundtrip.map(|d| d.as_millis() as u64);
+}
+
+/// Sample a successful execution's latencies
+pub fn record(execution: &Execution) {
+    if execution.status != "success" || execution.latency_ms.is_none() {
+        return;
+    }
+    let mut samples = SAMPLES.lock();
+    samples.push_back(LatencySample {
+        recorded_at: Utc::now(),
+        total_ms: execution.latency_ms,
+        detection_ms: execution.detection_ms,
+        ea_roundtrip_ms: execution.ea_roundtrip_ms,
+    });
+    while samples.len() > MAX_SAMPLES {
+        samples.pop_front();
+    }
+}
+
+/// Nearest-rank percentiles and max of `values`
+fn stats(mut values: Vec<u64>) -> LatencyStats {
+    if values.is_empty() {
+        return LatencyStats::default();
+    }
+    values.sort_unstable();
+    let rank = |p: f64| values[((p * values.len() as f64).ceil() as usize).clamp(1, values.len()) - 1];
+    LatencyStats {
+        count: values.len(),
+        p50_ms: Some(rank(0.50)),
+        p95_ms: Some(rank(0.95)),
+        max_ms: values.last().copied(),
+    }
+}
+
+fn summarize(samples: &VecDeque<LatencySample>, now: DateTime<Utc>) -> LatencySummary {
+    let today: Vec<&LatencySample> = samples
+        .iter()
+        .filter(|s| s.recorded_at.date_naive() == now.date_naive())
+        .collect();
+    LatencySummary {
+        date: now.date_naive().to_string(),
+        total: stats(today.iter().filter_map(|s| s.total_ms).collect()),
+        detection: stats(today.iter().filter_map(|s| s.detection_ms).collect()),
+        ea_roundtrip: stats(today.iter().filter_map(|s| s.ea_roundtrip_ms).collect()),
+    }
+}
+
+/// Latency percentiles for today's successful executions
+pub fn summary_today() -> LatencySummary {
+    summarize(&SAMPLES.lock(), Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(recorded_at: DateTime<Utc>, total_ms: u64) -> LatencySample {
+        LatencySample {
+            recorded_at,
+            total_ms: Some(total_ms),
+            detection_ms: Some(total_ms / 10),
+            ea_roundtrip_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_percentiles_from_seeded_results() {
+        let now = Utc::now();
+        let mut samples: VecDeque<LatencySample> = (1..=100).map(|ms| sample(now, ms * 10)).collect();
+        // Yesterday's outlier doesn't count
+        samples.push_back(sample(now - chrono::Duration::days(1), 60_000));
+
+        let summary = summarize(&samples, now);
+        assert_eq!(summary.total.count, 100);
+        assert_eq!(summary.total.p50_ms, Some(500));
+        assert_eq!(summary.total.p95_ms, Some(950));
+        assert_eq!(summary.total.max_ms, Some(1000));
+        assert_eq!(summary.detection.p95_ms, Some(95));
+        assert_eq!(summary.ea_roundtrip, LatencyStats::default());
+    }
+
+    #[test]
+    fn test_single_sample_stats() {
+        let single = stats(vec![42]);
+        assert_eq!((single.p50_ms, single.p95_ms, single.max_ms), (Some(42), Some(42), Some(42)));
+    }
+}
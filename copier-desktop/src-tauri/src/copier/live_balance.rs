@@ -0,0 +1,170 @@
+//! Live account balances for `balance_multiplier` sizing
+//!
+//! The balances carried on the master event and in `CopierAccountInfo.json`
+//! go stale as both accounts trade, so the master/receiver ratio is
+//! recomputed for each trade from the latest EA heartbeats: the master's
+//! `heartbeat.json` and the receiver's refreshed account info. Reads are
+//! cached for `HEARTBEAT_TTL` to keep bursts of entries off the disk;
+//! missing or stale heartbeats fall back to the static balances.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use super::commands::{read_master_heartbeat, read_receiver_heartbeat, Heartbeat};
+use super::lot_calculator::AccountInfo;
+
+/// How long a heartbeat read is reused
+const HEARTBEAT_TTL: Duration = Duration::from_secs(2);
+
+/// Heartbeats older than this no longer describe the account
+const MAX_HEARTBEAT_AGE_SECS: i64 = 30;
+
+/// Per-terminal heartbeat cache with a short TTL
+pub struct HeartbeatCache {
+    ttl: Duration,
+    /// terminal_id -> (read_at, heartbeat)
+    entries: Mutex<HashMap<String, (Instant, Option<Heartbeat>)>>,
+}
+
+impl HeartbeatCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached heartbeat for `terminal_id`, calling `read` when the entry is
+    /// missing or older than the TTL. Failed reads are cached too.
+    pub fn get(&self, terminal_id: &str, read: impl FnOnce(&str) -> Option<Heartbeat>) -> Option<Heartbeat> {
+        let mut entries = self.entries.lock();
+        if let Some((read_at, heartbeat)) = entries.get(terminal_id) {
+            if read_at.elapsed() < self.ttl {
+                return heartbeat.clone();
+            }
+        }
+
+        let heartbeat = read(terminal_id);
+        entries.insert(terminal_id.to_string(), (Instant::now(), heartbeat.clone()));
+        heartbeat
+    }
+}
+
+static HEARTBEATS: LazyLock<HeartbeatCache> = LazyLock::new(|| HeartbeatCache::new(HEARTBEAT_TTL));
+static RECEIVER_HEARTBEATS: LazyLock<HeartbeatCache> = LazyLock::new(|| HeartbeatCache::new(HEARTBEAT_TTL));
+
+/// The heartbeat's balance, if the heartbeat is recent and plausible
+fn fresh_balance(heartbeat: &Heartbeat, now: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&heartbeat.timestamp_utc).ok()?;
+    let age = now.signed_duration_since(timestamp).num_seconds();
+    (age <= MAX_HEARTBEAT_AGE_SECS && heartbeat.balance > 0.0).then_some(heartbeat.balance)
+}
+
+/// Latest heartbeat for a master terminal if it is fresh
+pub fn live_heartbeat(terminal_id: &str) -> Option<Heartbeat> {
+    let heartbeat = HEARTBEATS.get(terminal_id, |id| read_master_heartbeat(id).ok())?;
+    fresh_balance(&heartbeat, chrono::Utc::now()).map(|_| heartbeat)
+}
+
+/// Latest balance and equity for a receiver terminal if they are fresh
+pub fn live_receiver_heartbeat(terminal_id: &str) -> Option<Heartbeat> {
+    let heartbeat = RECEIVER_HEARTBEATS.get(terminal_id, |id| read_receiver_heartbeat(id).ok())?;
+    fresh_balance(&heartbeat, chrono::Utc::now()).map(|_| heartbeat)
+}
+
+/// Balances to size a trade with: live heartbeat values where available,
+/// otherwise the static master balance / receiver account info.
+pub fn resolve_balances(
+    master_heartbeat: Option<&Heartbeat>,
+    receiver_heartbeat: Option<&Heartbeat>,
+    master_fallback: Option<f64>,
+    receiver_fallback: Option<AccountInfo>,
+) -> (Option<f64>, Option<AccountInfo>) {
+    let master_balance = master_heartbeat.map(|hb| hb.balance).or(master_fallback);
+
+    let receiver_account = match (receiver_heartbeat, receiver_fallback) {
+        (Some(hb), Some(account)) => Some(AccountInfo {
+            balance: hb.balance,
+            equity: hb.equity,
+            ..account
+        }),
+        (Some(hb), None) => Some(AccountInfo {
+            balance: hb.balance,
+            equity: hb.equity,
+            currency: String::new(),
+            leverage: 0,
+        }),
+        (None, account) => account,
+    };
+
+    (master_balance, receiver_account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::lot_calculator::calculate_lots;
+
+    fn heartbeat(terminal_id: &str, balance: f64) -> Heartbeat {
+        Heartbeat {
+            timestamp_utc: chrono::Utc::now().to_rfc3339(),
+            terminal_id: terminal_id.to_string(),
+            account: 1,
+            balance,
+            equity: balance,
+            open_positions: 0,
+        }
+    }
+
+    fn account(balance: f64) -> AccountInfo {
+        AccountInfo {
+            balance,
+            equity: balance,
+            currency: "USD".to_string(),
+            leverage: 100,
+        }
+    }
+
+    fn balance_multiplier_lots(master_balance: Option<f64>, receiver: Option<AccountInfo>) -> f64 {
+        calculate_lots("balance_multiplier", 1.0, 1.0, 1.1, None, master_balance, receiver.as_ref(), None)
+    }
+
+    #[test]
+    fn test_live_heartbeat_balances_override_config() {
+        // Config says receiver is half the master
+        let config_lots = balance_multiplier_lots(Some(10000.0), Some(account(5000.0)));
+        assert_eq!(config_lots, 0.5);
+
+        // Master has since lost money and the receiver grown: ratio is now 1.0
+        let master_hb = heartbeat("M1", 8000.0);
+        let receiver_hb = heartbeat("R1", 8000.0);
+        let (master, receiver) = resolve_balances(Some(&master_hb), Some(&receiver_hb), Some(10000.0), Some(account(5000.0)));
+        assert_eq!(receiver.as_ref().unwrap().currency, "USD");
+        assert_eq!(balance_multiplier_lots(master, receiver), 1.0);
+
+        // Missing heartbeats fall back to config balances
+        let (master, receiver) = resolve_balances(None, None, Some(10000.0), Some(account(5000.0)));
+        assert_eq!(balance_multiplier_lots(master, receiver), config_lots);
+    }
+
+    #[test]
+    fn test_heartbeat_cache_reuses_reads_within_ttl() {
+        let cache = HeartbeatCache::new(Duration::from_secs(60));
+        let mut reads = 0;
+        for _ in 0..3 {
+            cache.get("M1", |id| {
+                reads += 1;
+                Some(heartbeat(id, 1000.0))
+            });
+        }
+        assert_eq!(reads, 1);
+
+        let stale = Heartbeat {
+            timestamp_utc: (chrono::Utc::now() - chrono::Duration::minutes(5)).to_rfc3339(),
+            ..heartbeat("M1", 1000.0)
+        };
+        assert!(fresh_balance(&stale, chrono::Utc::now()).is_none());
+    }
+}
@@ -36,6 +36,53 @@ pub struct SymbolInfo {
     /// Symbol type for special handling
     #[serde(default)]
     pub symbol_type: SymbolType,
+    /// Broker minimum lot (None = `DEFAULT_LOT_STEP`)
+    #[serde(default)]
+    pub min_lot: Option<f64>,
+    /// Broker lot step; crypto often trades in 0.001 or finer
+    /// (None = `DEFAULT_LOT_STEP`)
+    #[serde(default)]
+    pub lot_step: Option<f64>,
+    /// How sizes are brought onto the lot step (None = round to nearest)
+    #[serde(default)]
+    pub lot_rounding: Option<LotRounding>,
+}
+
+/// Lot step and minimum assumed when the broker's aren't known
+pub const DEFAULT_LOT_STEP: f64 = 0.01;
+
+/// How a lot size is brought onto the broker's lot step
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LotRounding {
+    /// Down, never risking more than intended
+    #[default]
+    Floor,
+    /// To the nearest step
+    Round,
+    /// Up (still capped at the broker maximum by the clamp)
+    Ceil,
+}
+
+impl LotRounding {
+    /// `lots` on a multiple of `lot_step`. Sizes already on a step (up to
+    /// float drift) stay put, so 0.29 doesn't floor to 0.28.
+    pub fn to_step(self, lots: f64, lot_step: f64) -> f64 {
+        if lot_step <= 0.0 {
+            return lots;
+        }
+        let steps = lots / lot_step;
+        let steps = if (steps - steps.round()).abs() < 1e-9 {
+            steps.round()
+        } else {
+            match self {
+                LotRounding::Floor => steps.floor(),
+                LotRounding::Round => steps.round(),
+                LotRounding::Ceil => steps.ceil(),
+            }
+        };
+        round_to_step_precision(steps * lot_step, lot_step)
+    }
 }
 
 /// Symbol type for lot calculation adjustments
@@ -59,6 +106,9 @@ impl Default for SymbolInfo {
             digits: 5,
             point: 0.00001,
             symbol_type: SymbolType::Forex,
+            min_lot: None,
+            lot_step: None,
+            lot_rounding: None,
         }
     }
 }
@@ -73,6 +123,9 @@ impl SymbolInfo {
             digits,
             point: if digits == 1 { 0.1 } else { 1.0 },
             symbol_type: SymbolType::Index,
+            min_lot: None,
+            lot_step: None,
+            lot_rounding: None,
         }
     }
     
@@ -85,6 +138,9 @@ impl SymbolInfo {
             digits,
             point: f64::powi(10.0, -digits),
             symbol_type: SymbolType::Cfd,
+            min_lot: None,
+            lot_step: None,
+            lot_rounding: None,
         }
     }
     
@@ -101,10 +157,7 @@ impl SymbolInfo {
             return SymbolType::Index;
         }
         
-        // Crypto patterns
-        if upper.contains("BTC") || upper.contains("ETH") || upper.contains("XRP") 
-           || upper.contains("LTC") || upper.contains("CRYPTO")
-        {
+        if super::symbol_catalog::is_crypto_symbol(symbol) {
             return SymbolType::Crypto;
         }
         
@@ -118,6 +171,16 @@ impl SymbolInfo {
         
         SymbolType::Forex
     }
+
+    /// Round lots to this symbol's lot step and minimum
+    fn round_lots(&self, lots: f64) -> f64 {
+        round_lots_with_min(
+            lots,
+            self.min_lot.unwrap_or(DEFAULT_LOT_STEP),
+            self.lot_step.unwrap_or(DEFAULT_LOT_STEP),
+            self.lot_rounding.unwrap_or(LotRounding::Round),
+        )
+    }
 }
 
 /// Calculate the lot size for a receiver based on the configured risk mode
@@ -136,13 +199,13 @@ pub fn calculate_lots(
     match risk_mode {
         "fixed_lot" => {
             // Use fixed lot size directly
-            round_lots(risk_value)
+            info.round_lots(risk_value)
         }
         
         "lot_multiplier" => {
             // Multiply master lots by factor
             let result = master_lots * risk_value;
-            round_lots(result)
+            info.round_lots(result)
         }
         
         "balance_multiplier" => {
@@ -151,14 +214,14 @@ pub fn calculate_lots(
                 if m_balance > 0.0 {
                     let ratio = r_account.balance / m_balance;
                     let scaled_lots = master_lots * ratio * risk_value;
-                    round_lots(scaled_lots.max(0.01))
+                    info.round_lots(scaled_lots)
                 } else {
-                    round_lots(master_lots)
+                    info.round_lots(master_lots)
                 }
             } else {
                 // Fallback to master lots if account info not available
                 tracing::warn!("balance_multiplier mode: missing account info, using master lots");
-                round_lots(master_lots)
+                info.round_lots(master_lots)
             }
         }
         
@@ -203,16 +266,36 @@ pub fn calculate_lots(
         
         "mirror" => {
             // Exact copy of master lots
-            round_lots(master_lots)
+            info.round_lots(master_lots)
         }
         
         _ => {
             tracing::warn!("Unknown risk mode: {}, using master lots", risk_mode);
-            round_lots(master_lots)
+            info.round_lots(master_lots)
         }
     }
 }
 
+/// Calculate the volume a receiver should close when the master partially closes.
+///
+/// The master's closed fraction (`closed / (closed + remaining)`) is applied to
+/// the receiver's live position volume, so a receiver sized at 2x the master
+/// closes 2x the master's lots. Never returns more than `receiver_volume`, and
+/// returns 0.0 when the fraction can't be derived.
+pub fn calculate_partial_close_lots(
+    master_closed: f64,
+    master_remaining: f64,
+    receiver_volume: f64,
+) -> f64 {
+    let master_original = master_closed + master_remaining;
+    if master_closed <= 0.0 || master_original <= 0.0 || receiver_volume <= 0.0 {
+        return 0.0;
+    }
+
+    let fraction = (master_closed / master_original).min(1.0);
+    round_lots(receiver_volume * fraction).min(receiver_volume)
+}
+
 /// Calculate lot size from a risk amount in account currency
 /// Handles different symbol types (forex, indices, CFDs) correctly
 fn calculate_lots_from_risk(
@@ -225,7 +308,7 @@ fn calculate_lots_from_risk(
     
     if sl_distance <= 0.0 {
         tracing::warn!("Invalid SL distance (0), returning minimum lot");
-        return 0.01;
+        return symbol_info.min_lot.unwrap_or(DEFAULT_LOT_STEP);
     }
     
     // Calculate value per lot based on SL distance
@@ -257,7 +340,7 @@ fn calculate_lots_from_risk(
             // where sl_ticks = sl_distance / tick_size.
             if symbol_info.tick_size <= 0.0 {
                 tracing::warn!("Invalid tick_size ({}), returning minimum lot", symbol_info.tick_size);
-                return 0.01;
+                return symbol_info.min_lot.unwrap_or(DEFAULT_LOT_STEP);
             }
             let sl_ticks = sl_distance / symbol_info.tick_size;
             sl_ticks * symbol_info.tick_value
@@ -266,7 +349,7 @@ fn calculate_lots_from_risk(
     
     if value_per_lot <= 0.0 {
         tracing::warn!("Invalid value per lot calculation ({}), returning minimum lot", value_per_lot);
-        return 0.01;
+        return symbol_info.min_lot.unwrap_or(DEFAULT_LOT_STEP);
     }
     
     let calculated_lots = risk_amount / value_per_lot;
@@ -280,26 +363,36 @@ fn calculate_lots_from_risk(
         "Lot calculation"
     );
     
-    round_lots_with_min(calculated_lots, 0.01, 0.01)
+    symbol_info.round_lots(calculated_lots)
 }
 
 /// Round lot size to valid MT5 increment with configurable min/step
 /// M5 fix: Uses symbol-specific min_lot and lot_step when available
-fn round_lots_with_min(lots: f64, min_lot: f64, lot_step: f64) -> f64 {
-    let rounded = (lots / lot_step).round() * lot_step;
-    // Ensure precision to avoid floating point issues
-    let rounded = (rounded * 100.0).round() / 100.0;
-    rounded.max(min_lot)
+fn round_lots_with_min(lots: f64, min_lot: f64, lot_step: f64, rounding: LotRounding) -> f64 {
+    rounding.to_step(lots, lot_step).max(min_lot)
 }
 
 /// Round lot size to valid MT5 increment (0.01 default)
 fn round_lots(lots: f64) -> f64 {
-    round_lots_with_min(lots, 0.01, 0.01)
+    round_lots_with_min(lots, DEFAULT_LOT_STEP, DEFAULT_LOT_STEP, LotRounding::Round)
+}
+
+/// Strip float drift from a lot size: round to the decimals of `lot_step`,
+/// and to at least 2
+pub fn round_to_step_precision(lots: f64, lot_step: f64) -> f64 {
+    let decimals = if lot_step > 0.0 {
+        (-lot_step.log10() - 1e-9).ceil().clamp(2.0, 8.0) as i32
+    } else {
+        2
+    };
+    let factor = 10f64.powi(decimals);
+    (lots * factor).round() / factor
 }
 
 // NOTE: Per-symbol min/max/step clamping lives in
-// `crate::copier::symbol_catalog::clamp_lots`, which uses the receiver's
-// broker spec (SymbolSpec) loaded from `CopierSymbolCatalog.json`. The old
+// `crate::copier::symbol_catalog::clamp_lots_with` (and `clamp_lots_detailed`),
+// which use the receiver's broker spec (SymbolSpec) loaded from
+// `CopierSymbolCatalog.json`. The old
 // `apply_max_lot_limit` / `apply_min_lot_limit` helpers were removed in R9 —
 // they hardcoded 0.01 defaults and were never called.
 
@@ -386,6 +479,7 @@ mod tests {
         let info = SymbolInfo {
             tick_value: 1.0, tick_size: 0.00001, contract_size: 100_000.0,
             digits: 5, point: 0.00001, symbol_type: SymbolType::Forex,
+            min_lot: None, lot_step: None, lot_rounding: None,
         };
         let lots = calculate_lots(
             "risk_dollar", 100.0, 0.5, 1.10000, Some(1.09000),
@@ -393,4 +487,62 @@ mod tests {
         );
         assert!((lots - 0.10).abs() < 0.005, "expected ~0.10, got {}", lots);
     }
+
+    #[test]
+    fn test_receiver_lot_rounding_in_calculator() {
+        // $137 over a 100 pip stop at $1/point/lot = 0.137 lots
+        let receiver = make_account(10000.0);
+        let sized = |lot_rounding| {
+            let info = SymbolInfo {
+                tick_value: 1.0,
+                tick_size: 0.00001,
+                lot_rounding,
+                ..SymbolInfo::default()
+            };
+            calculate_lots("risk_dollar", 137.0, 0.5, 1.10000, Some(1.09000), None, Some(&receiver), Some(&info))
+        };
+        assert_eq!(sized(Some(LotRounding::Floor)), 0.13);
+        assert_eq!(sized(Some(LotRounding::Round)), 0.14);
+        assert_eq!(sized(Some(LotRounding::Ceil)), 0.14);
+        assert_eq!(sized(None), 0.14);
+    }
+
+    #[test]
+    fn test_btcusd_sized_in_fractional_lots() {
+        // BTCUSD: 2 digits, contract of 1 BTC, $0.01 per tick per lot,
+        // broker lot step 0.001
+        let info = SymbolInfo {
+            tick_value: 0.01, tick_size: 0.01, contract_size: 1.0,
+            digits: 2, point: 0.01, symbol_type: SymbolInfo::detect_symbol_type("BTCUSD"),
+            min_lot: Some(0.001), lot_step: Some(0.001), lot_rounding: None,
+        };
+        assert_eq!(info.symbol_type, SymbolType::Crypto);
+
+        // $25 over a $1000 stop = 0.025 BTC, not rounded to 0.03
+        let receiver = make_account(10000.0);
+        let lots = calculate_lots(
+            "risk_dollar", 25.0, 0.1, 60_000.0, Some(59_000.0),
+            None, Some(&receiver), Some(&info),
+        );
+        assert_eq!(lots, 0.025);
+
+        // Mirroring 0.005 BTC stays 0.005 instead of becoming the 0.01 FX minimum
+        assert_eq!(calculate_lots("mirror", 0.0, 0.005, 60_000.0, None, None, None, Some(&info)), 0.005);
+        assert_eq!(calculate_lots("mirror", 0.0, 0.005, 60_000.0, None, None, None, None), 0.01);
+    }
+
+    #[test]
+    fn test_partial_close_scales_with_receiver_size() {
+        // Master holds 1.0 lot and closes half; receiver copied at 2x (2.0 lots)
+        // so it should close ~1.0 lot.
+        let lots = calculate_partial_close_lots(0.5, 0.5, 2.0);
+        assert!((lots - 1.0).abs() < 0.005, "expected ~1.0, got {}", lots);
+    }
+
+    #[test]
+    fn test_partial_close_never_exceeds_receiver_volume() {
+        assert_eq!(calculate_partial_close_lots(1.0, 0.0, 0.3), 0.3);
+        assert_eq!(calculate_partial_close_lots(0.0, 1.0, 2.0), 0.0);
+        assert_eq!(calculate_partial_close_lots(0.5, 0.5, 0.0), 0.0);
+    }
 }
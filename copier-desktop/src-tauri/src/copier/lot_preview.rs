@@ -0,0 +1,153 @@
+//! Lot sizing preview
+//!
+//! "What-if" table for the risk settings panel: the lots every risk mode
+//! would send for one sample trade, sized by `lot_calculator` and clamped to
+//! the receiver symbol's broker specs exactly as the live event path does.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::lot_calculator::{self, AccountInfo, SymbolInfo};
+use super::symbol_catalog::{clamp_lots_detailed, SymbolSpec};
+use super::ticks::pip_size;
+
+/// Risk modes shown in the preview, in display order
+pub const PREVIEW_RISK_MODES: [&str; 6] = [
+    "fixed_lot",
+    "lot_multiplier",
+    "balance_multiplier",
+    "risk_percent",
+    "risk_dollar",
+    "mirror",
+];
+
+/// Risk value used for a mode the caller didn't supply one for
+pub fn default_risk_value(risk_mode: &str) -> f64 {
+    match risk_mode {
+        "fixed_lot" => 0.1,
+        "risk_percent" => 1.0,
+        "risk_dollar" => 100.0,
+        // Multipliers; ignored by mirror
+        _ => 1.0,
+    }
+}
+
+/// Calculator inputs for a symbol from its catalog spec
+fn symbol_info(spec: &SymbolSpec) -> SymbolInfo {
+    SymbolInfo {
+        tick_value: spec.tick_value,
+        tick_size: spec.tick_size,
+        contract_size: spec.contract_size,
+        digits: spec.digits,
+        point: spec.tick_size,
+        symbol_type: SymbolInfo::detect_symbol_type(&spec.name),
+        min_lot: Some(spec.min_lot),
+        lot_step: Some(spec.lot_step),
+        lot_rounding: None,
+    }
+}
+
+/// Clamped lots for one risk mode on the sample trade
+pub fn preview_mode(
+    risk_mode: &str,
+    risk_value: f64,
+    master_lots: f64,
+    master_balance: f64,
+    receiver_balance: f64,
+    sl_distance_pips: f64,
+    spec: &SymbolSpec,
+) -> f64 {
+    let info = symbol_info(spec);
+    let account = AccountInfo {
+        balance: receiver_balance,
+        equity: receiver_balance,
+        currency: String::new(),
+        leverage: 0,
+    };
+    // Only the entry-to-SL distance matters to the calculator
+    let sl_distance = sl_distance_pips * pip_size(info.point, info.digits);
+    let raw = lot_calculator::calculate_lots(
+        risk_mode,
+        risk_value,
+        master_lots,
+        sl_distance,
+        Some(0.0),
+        Some(master_balance),
+        Some(&account),
+        Some(&info),
+    );
+    clamp_lots_detailed(raw, spec).lots
+}
+
+/// Clamped lots for every preview risk mode. `risk_values` supplies the
+/// per-mode setting (lots, multiplier, percent or dollars); missing modes
+/// use `default_risk_value`.
+pub fn preview_lot_sizing(
+    master_lots: f64,
+    master_balance: f64,
+    receiver_balance: f64,
+    sl_distance_pips: f64,
+    spec: &SymbolSpec,
+    risk_values: &HashMap<String, f64>,
+) -> BTreeMap<String, f64> {
+    PREVIEW_RISK_MODES
+        .iter()
+        .map(|&mode| {
+            let risk_value = risk_values.get(mode).copied().unwrap_or_else(|| default_risk_value(mode));
+            let lots = preview_mode(mode, risk_value, master_lots, master_balance, receiver_balance, sl_distance_pips, spec);
+            (mode.to_string(), lots)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eurusd() -> SymbolSpec {
+        SymbolSpec {
+            name: "EURUSD".to_string(),
+            normalized_key: "EURUSD".to_string(),
+            tick_value: 1.0,
+            tick_size: 0.00001,
+            contract_size: 100000.0,
+            digits: 5,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 5.0,
+            description: None,
+            trade_mode: None,
+            profit_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_table_matches_individual_modes() {
+        let spec = eurusd();
+        let risk_values = HashMap::from([("fixed_lot".to_string(), 0.25), ("lot_multiplier".to_string(), 3.0)]);
+        let table = preview_lot_sizing(0.5, 10000.0, 20000.0, 20.0, &spec, &risk_values);
+
+        assert_eq!(table.len(), PREVIEW_RISK_MODES.len());
+        for mode in PREVIEW_RISK_MODES {
+            let risk_value = risk_values.get(mode).copied().unwrap_or_else(|| default_risk_value(mode));
+            assert_eq!(table[mode], preview_mode(mode, risk_value, 0.5, 10000.0, 20000.0, 20.0, &spec), "{}", mode);
+        }
+
+        assert_eq!(table["fixed_lot"], 0.25);
+        assert_eq!(table["lot_multiplier"], 1.5);
+        assert_eq!(table["balance_multiplier"], 1.0);
+        // 1% of 20k = $200 over 20 pips at $10/pip/lot
+        assert_eq!(table["risk_percent"], 1.0);
+        assert_eq!(table["risk_dollar"], 0.5);
+        assert_eq!(table["mirror"], 0.5);
+    }
+
+    #[test]
+    fn test_preview_clamps_to_broker_specs() {
+        let spec = eurusd();
+        let risk_values = HashMap::from([("lot_multiplier".to_string(), 20.0), ("fixed_lot".to_string(), 0.001)]);
+        let table = preview_lot_sizing(0.5, 10000.0, 10000.0, 20.0, &spec, &risk_values);
+
+        assert_eq!(table["lot_multiplier"], spec.max_lot);
+        assert_eq!(table["fixed_lot"], spec.min_lot);
+    }
+}
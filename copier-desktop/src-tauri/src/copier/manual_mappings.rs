@@ -0,0 +1,271 @@
+//! Manual symbol mappings
+//!
+//! Lets the user fix one bad auto-mapping on a receiver without re-running
+//! the mapper. The receiver symbol is checked against the receiver's
+//! catalog first. Like the receiver toggles, manual mappings are persisted
+//! locally and re-applied every time a config is installed, so a cloud
+//! re-sync doesn't put the old mapping back. A master symbol can also be
+//! remembered as disabled on a receiver (a confirmed stale mapping).
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use super::persistence;
+use crate::data_dir::app_data_dir;
+use super::symbol_catalog::SymbolCatalog;
+use super::{CopierConfig, ReceiverConfig, SymbolMapping};
+
+const MAPPINGS_FILE: &str = "manual_mappings.json";
+
+/// Receiver account id -> master symbol -> receiver symbol (None = the
+/// receiver's mapping for the master symbol is disabled)
+type ManualMappings = BTreeMap<String, BTreeMap<String, Option<String>>>;
+
+static MANUAL: LazyLock<Mutex<ManualMappings>> = LazyLock::new(|| Mutex::new(load()));
+
+fn get_mappings_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(MAPPINGS_FILE))
+}
+
+fn load() -> ManualMappings {
+    get_mappings_path()
+        .and_then(|path| {
+            persistence::load_state_file(&path, "Manual symbol mappings", |content| {
+                serde_json::from_str::<ManualMappings>(content).map_err(|e| e.to_string())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Write the manual mappings (atomic write)
+fn save(mappings: &ManualMappings) -> Result<(), String> {
+    let Some(path) = get_mappings_path() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(mappings).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write manual mappings: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save manual mappings: {}", e))
+}
+
+/// Reject a receiver symbol that isn't in the catalog or can't be traded
+pub fn validate_receiver_symbol(catalog: &SymbolCatalog, receiver_symbol: &str) -> Result<(), String> {
+    let spec = catalog
+        .symbols
+        .iter()
+        .find(|s| s.name == receiver_symbol)
+        .ok_or_else(|| format!("{} is not in the symbol catalog of {}", receiver_symbol, catalog.terminal_id))?;
+    if spec.allows_opening("buy") || spec.allows_opening("sell") {
+        return Ok(());
+    }
+    Err(format!(
+        "{} can't be traded on {} (trade mode {})",
+        receiver_symbol,
+        catalog.terminal_id,
+        spec.trade_mode.as_deref().unwrap_or_default()
+    ))
+}
+
+/// Point the receiver's mapping for `master_symbol` at `receiver_symbol`
+/// (enabled), or add it. With `None` the existing mapping is disabled
+/// instead. Duplicate mappings for the master symbol are dropped.
+fn upsert(receiver: &mut ReceiverConfig, master_symbol: &str, receiver_symbol: Option<&str>) {
+    let mut seen = false;
+    receiver.symbol_mappings.retain(|m| m.master_symbol != master_symbol || !std::mem::replace(&mut seen, true));
+    let existing = receiver.symbol_mappings.iter_mut().find(|m| m.master_symbol == master_symbol);
+    match (existing, receiver_symbol) {
+        (Some(existing), Some(receiver_symbol)) => {
+            existing.receiver_symbol = receiver_symbol.to_string();
+            existing.is_enabled = true;
+        }
+        (Some(existing), None) => existing.is_enabled = false,
+        (None, Some(receiver_symbol)) => receiver.symbol_mappings.push(SymbolMapping {
+            master_symbol: master_symbol.to_string(),
+            receiver_symbol: receiver_symbol.to_string(),
+            is_enabled: true,
+        }),
+        (None, None) => {}
+    }
+}
+
+fn receiver_mut<'a>(config: &'a mut CopierConfig, receiver_id: &str) -> Result<&'a mut ReceiverConfig, String> {
+    config
+        .receivers
+        .iter_mut()
+        .find(|r| r.account_id == receiver_id)
+        .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))
+}
+
+/// Validate and apply a manual mapping to a receiver in `config`, returning
+/// the receiver's mapping as it now stands. Nothing is persisted.
+pub fn apply_to(
+    config: &mut CopierConfig,
+    receiver_id: &str,
+    master_symbol: &str,
+    receiver_symbol: &str,
+    catalog: &SymbolCatalog,
+) -> Result<SymbolMapping, String> {
+    let (master_symbol, receiver_symbol) = (master_symbol.trim(), receiver_symbol.trim());
+    if master_symbol.is_empty() {
+        return Err("Master symbol is required".to_string());
+    }
+    validate_receiver_symbol(catalog, receiver_symbol)?;
+    let receiver = receiver_mut(config, receiver_id)?;
+    upsert(receiver, master_symbol, Some(receiver_symbol));
+    receiver
+        .symbol_mappings
+        .iter()
+        .find(|m| m.master_symbol == master_symbol)
+        .cloned()
+        .ok_or_else(|| format!("Mapping for {} was not applied", master_symbol))
+}
+
+/// Disable a receiver's mapping for `master_symbol` in `config`. Nothing
+/// is persisted.
+pub fn disable_in(config: &mut CopierConfig, receiver_id: &str, master_symbol: &str) -> Result<(), String> {
+    upsert(receiver_mut(config, receiver_id)?, master_symbol, None);
+    Ok(())
+}
+
+/// Record a receiver's manual mapping and persist it
+pub fn remember(receiver_id: &str, mapping: &SymbolMapping) -> Result<(), String> {
+    remember_all(receiver_id, [(mapping.master_symbol.clone(), Some(mapping.receiver_symbol.clone()))])
+}
+
+/// Record several of a receiver's mappings (master symbol, receiver symbol
+/// or None for disabled) and persist them in one write
+pub fn remember_all(
+    receiver_id: &str,
+    entries: impl IntoIterator<Item = (String, Option<String>)>,
+) -> Result<(), String> {
+    let mut manual = MANUAL.lock();
+    manual.entry(receiver_id.to_string()).or_default().extend(entries);
+    save(&manual)
+}
+
+/// Apply every remembered manual mapping to a config
+pub fn apply_all(config: &mut CopierConfig) {
+    let manual = MANUAL.lock();
+    for receiver in config.receivers.iter_mut() {
+        let Some(mappings) = manual.get(&receiver.account_id) else {
+            continue;
+        };
+        for (master_symbol, receiver_symbol) in mappings {
+            upsert(receiver, master_symbol, receiver_symbol.as_deref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::symbol_catalog::SymbolSpec;
+
+    fn config() -> CopierConfig {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": "h",
+            "master": {"account_id": "m1", "account_number": "1001", "broker": "A", "terminal_id": "M1"},
+            "receivers": [{
+                "account_id": "r1",
+                "account_number": "2001",
+                "broker": "B",
+                "terminal_id": "R1",
+                "risk_mode": "fixed_lot",
+                "risk_value": 0.1,
+                "max_slippage_pips": 3.0,
+                "max_daily_loss_r": null,
+                "prop_firm_safe_mode": false,
+                "symbol_mappings": [
+                    { "master_symbol": "XAUUSD", "receiver_symbol": "XAUEUR", "is_enabled": false }
+                ]
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn spec(name: &str, trade_mode: &str) -> SymbolSpec {
+        SymbolSpec {
+            name: name.to_string(),
+            normalized_key: name.to_string(),
+            tick_value: 1.0,
+            tick_size: 0.01,
+            contract_size: 100.0,
+            digits: 2,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 100.0,
+            description: None,
+            trade_mode: Some(trade_mode.to_string()),
+            profit_currency: None,
+        }
+    }
+
+    fn catalog() -> SymbolCatalog {
+        SymbolCatalog {
+            terminal_id: "R1".to_string(),
+            symbols: vec![spec("GOLD", "full"), spec("XAUEUR", "full"), spec("US30.old", "close_only")],
+            fetched_at: "2024-03-10T00:00:00Z".to_string(),
+            broker_suffix: None,
+        }
+    }
+
+    #[test]
+    fn test_manual_mapping_replaces_auto_mapping() {
+        let mut config = config();
+        let mapping = apply_to(&mut config, "r1", "XAUUSD", "GOLD", &catalog()).unwrap();
+        assert_eq!((mapping.master_symbol.as_str(), mapping.receiver_symbol.as_str()), ("XAUUSD", "GOLD"));
+        assert!(mapping.is_enabled);
+
+        let mappings = &config.receivers[0].symbol_mappings;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].receiver_symbol, "GOLD");
+        assert!(mappings[0].is_enabled);
+
+        apply_to(&mut config, "r1", "EURUSD", "XAUEUR", &catalog()).unwrap();
+        assert_eq!(config.receivers[0].symbol_mappings.len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_or_untradable_symbols_are_rejected() {
+        let mut config = config();
+        let err = apply_to(&mut config, "r1", "XAUUSD", "GOLD.pro", &catalog()).unwrap_err();
+        assert!(err.contains("not in the symbol catalog"));
+        let err = apply_to(&mut config, "r1", "US30", "US30.old", &catalog()).unwrap_err();
+        assert!(err.contains("close_only"));
+        assert!(apply_to(&mut config, "r9", "XAUUSD", "GOLD", &catalog()).is_err());
+
+        // The existing mapping is untouched
+        assert_eq!(config.receivers[0].symbol_mappings[0].receiver_symbol, "XAUEUR");
+    }
+
+    #[test]
+    fn test_disabling_drops_duplicates() {
+        let mut config = config();
+        config.receivers[0].symbol_mappings.push(crate::copier::SymbolMapping {
+            master_symbol: "XAUUSD".to_string(),
+            receiver_symbol: "GOLD".to_string(),
+            is_enabled: true,
+        });
+
+        disable_in(&mut config, "r1", "XAUUSD").unwrap();
+        let mappings = &config.receivers[0].symbol_mappings;
+        assert_eq!(mappings.len(), 1);
+        assert!(!mappings[0].is_enabled);
+
+        // Nothing to disable is not an error
+        disable_in(&mut config, "r1", "US30").unwrap();
+        assert_eq!(config.receivers[0].symbol_mappings.len(), 1);
+
+        // Earlier files hold plain receiver symbols
+        let stored: ManualMappings = serde_json::from_str(r#"{"r1": {"XAUUSD": "GOLD", "US30": null}}"#).unwrap();
+        assert_eq!(stored["r1"]["XAUUSD"].as_deref(), Some("GOLD"));
+        assert_eq!(stored["r1"]["US30"], None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Broker symbol mapping hints
+//!
+//! Some broker-to-broker symbol differences are the same for every user
+//! ("SPX500" on one broker is "US500.cash" on another). The app ships a
+//! versioned table of them in `resources/mapping_hints.json`: for each
+//! broker, the symbol it uses for a common instrument name. Two brokers'
+//! entries for the same instrument give a master -> receiver hint that
+//! `auto_map_symbols_by_specs` tries before matching by specs or name. A
+//! hint only names the symbol: if the two contracts differ in size or value
+//! per point, the mapping is created disabled (`spec_mismatch`) for review.
+//!
+//! Brokers are keyed by their normalized name (`normalize_broker_name`), so
+//! "ICM" and "IC Markets" share an entry. Users can add or replace entries
+//! in a local `mapping_hints.json`; an empty symbol there removes a bundled
+//! entry.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use super::persistence;
+use crate::data_dir::app_data_dir;
+use crate::mt5::broker_names::normalize_broker_name;
+
+const OVERRIDES_FILE: &str = "mapping_hints.json";
+
+/// Broker -> instrument -> that broker's symbol for it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HintsTable {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub brokers: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl HintsTable {
+    /// The same table with every broker key normalized (entries of brokers
+    /// that normalize alike are merged)
+    fn normalized(self) -> Self {
+        let mut brokers: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for (broker, symbols) in self.brokers {
+            brokers.entry(normalize_broker_name(&broker)).or_default().extend(symbols);
+        }
+        Self {
+            version: self.version,
+            brokers,
+        }
+    }
+
+    /// `overrides` laid over this table. An empty symbol removes the entry.
+    pub fn merged(&self, overrides: &HintsTable) -> Self {
+        let mut merged = self.clone().normalized();
+        for (broker, symbols) in &overrides.clone().normalized().brokers {
+            let entries = merged.brokers.entry(broker.clone()).or_default();
+            for (instrument, symbol) in symbols {
+                if symbol.trim().is_empty() {
+                    entries.remove(instrument);
+                } else {
+                    entries.insert(instrument.clone(), symbol.clone());
+                }
+            }
+        }
+        merged.brokers.retain(|_, symbols| !symbols.is_empty());
+        merged
+    }
+
+    /// Master symbol -> receiver symbol hints for a broker pair. A broker
+    /// missing from the table is taken to use the plain instrument names.
+    pub fn pair_hints(&self, master_broker: Option<&str>, receiver_broker: Option<&str>) -> HashMap<String, String> {
+        let lookup = |broker: Option<&str>| broker.and_then(|b| self.brokers.get(&normalize_broker_name(b)));
+        let (master, receiver) = (lookup(master_broker), lookup(receiver_broker));
+        if master.is_none() && receiver.is_none() {
+            return HashMap::new();
+        }
+
+        let instruments: Vec<&String> = master.into_iter().chain(receiver).flat_map(|s| s.keys()).collect();
+        instruments
+            .into_iter()
+            .map(|instrument| {
+                let symbol_on = |table: Option<&BTreeMap<String, String>>| {
+                    table.map_or(instrument.clone(), |t| t.get(instrument).cloned().unwrap_or_else(|| instrument.clone()))
+                };
+                (symbol_on(master), symbol_on(receiver))
+            })
+            .filter(|(master_symbol, receiver_symbol)| master_symbol != receiver_symbol)
+            .collect()
+    }
+}
+
+/// The table shipped with the app
+pub fn bundled() -> &'static HintsTable {
+    static BUNDLED: LazyLock<HintsTable> = LazyLock::new(|| {
+        serde_json::from_str::<HintsTable>(include_str!("../../resources/mapping_hints.json"))
+            .map(HintsTable::normalized)
+            .unwrap_or_else(|e| {
+                warn!("Bundled mapping hints are invalid: {}", e);
+                HintsTable::default()
+            })
+    });
+    &BUNDLED
+}
+
+fn get_overrides_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(OVERRIDES_FILE))
+}
+
+/// The user's local entries
+pub fn overrides() -> HintsTable {
+    get_overrides_path()
+        .and_then(|path| {
+            persistence::load_state_file(&path, "Mapping hint overrides", |content| {
+                serde_json::from_str::<HintsTable>(content).map_err(|e| e.to_string())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Bundled hints with the local overrides applied
+pub fn current() -> HintsTable {
+    bundled().merged(&overrides())
+}
+
+/// Hints `auto_map_symbols_by_specs` uses for a broker pair
+pub fn hints_for(master_broker: Option<&str>, receiver_broker: Option<&str>) -> HashMap<String, String> {
+    current().pair_hints(master_broker, receiver_broker)
+}
+
+/// Set a local entry: `broker`'s symbol for `instrument`. `None` hides the
+/// bundled entry.
+pub fn set_override(broker: &str, instrument: &str, symbol: Option<&str>) -> Result<(), String> {
+    let broker = normalize_broker_name(broker);
+    if broker.is_empty() || instrument.trim().is_empty() {
+        return Err("Broker and instrument are required".to_string());
+    }
+    let path = get_overrides_path().ok_or("Could not determine app data folder")?;
+
+    let mut table = overrides();
+    table.version = bundled().version;
+    table
+        .brokers
+        .entry(broker)
+        .or_default()
+        .insert(instrument.trim().to_uppercase(), symbol.unwrap_or_default().trim().to_string());
+
+    let json = serde_json::to_string_pretty(&table).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write mapping hints: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save mapping hints: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_table_pairs_known_brokers() {
+        let bundled = bundled();
+        assert!(bundled.version >= 1);
+
+        let hints = bundled.pair_hints(Some("FXCM"), Some("FTMO Global Markets Ltd"));
+        assert_eq!(hints.get("SPX500").map(String::as_str), Some("US500.cash"));
+        assert_eq!(hints.get("GER30").map(String::as_str), Some("GER40.cash"));
+        // Same name on both sides: no hint needed
+        let hints = bundled.pair_hints(Some("IC Markets"), Some("Pepperstone"));
+        assert!(!hints.contains_key("US30"));
+        assert_eq!(hints.get("USTEC").map(String::as_str), Some("NAS100"));
+
+        // An unlisted master broker is taken to use the plain names
+        let hints = bundled.pair_hints(Some("Acme FX"), Some("ICM"));
+        assert_eq!(hints.get("NAS100").map(String::as_str), Some("USTEC"));
+        assert!(bundled.pair_hints(Some("Acme FX"), None).is_empty());
+    }
+
+    #[test]
+    fn test_overrides_replace_and_remove_entries() {
+        let overrides: HintsTable = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "brokers": {
+                "ICM": {"US500": "US500.raw", "NAS100": ""},
+                "Acme FX": {"US500": "SP500m"}
+            }
+        }))
+        .unwrap();
+        let merged = bundled().merged(&overrides);
+
+        let ic = &merged.brokers["IC Markets"];
+        assert_eq!(ic["US500"], "US500.raw");
+        assert!(!ic.contains_key("NAS100"));
+        assert_eq!(ic["US30"], "US30");
+
+        let hints = merged.pair_hints(Some("Acme FX"), Some("IC Markets"));
+        assert_eq!(hints.get("SP500m").map(String::as_str), Some("US500.raw"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Saved symbol mapping profiles
+//!
+//! A finished set of `SymbolMapping`s (typically reviewed output of
+//! `auto_map_symbols_by_specs`) can be saved under a name and re-applied to
+//! other receivers on the same broker. Applying checks every receiver symbol
+//! against the target terminal's catalog; mappings whose symbol isn't there
+//! are dropped and reported instead of being written blind.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::data_dir::app_data_dir;
+use super::symbol_catalog::{SymbolCatalog, SymbolMapping};
+use super::CopierError;
+
+const PROFILES_FOLDER: &str = "mapping_profiles";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingProfile {
+    pub name: String,
+    pub created_at: String,
+    pub mappings: Vec<SymbolMapping>,
+}
+
+/// A profile applied to one terminal's catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedProfile {
+    /// Mappings whose receiver symbol exists on the target terminal
+    pub mappings: Vec<SymbolMapping>,
+    /// Receiver symbols from the profile the target terminal doesn't have
+    pub missing_symbols: Vec<String>,
+}
+
+fn get_profiles_dir() -> Result<PathBuf, CopierError> {
+    let dir = app_data_dir().ok_or_else(|| CopierError::NotFound("App data folder not found".to_string()))?;
+    Ok(dir.join(PROFILES_FOLDER))
+}
+
+/// File name for a profile: anything but letters, digits, '-' and '_' becomes '_'
+fn profile_file(dir: &Path, name: &str) -> PathBuf {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.json", stem))
+}
+
+pub fn save_profile(name: &str, mappings: Vec<SymbolMapping>) -> Result<MappingProfile, CopierError> {
+    save_profile_in(&get_profiles_dir()?, name, mappings)
+}
+
+fn save_profile_in(dir: &Path, name: &str, mappings: Vec<SymbolMapping>) -> Result<MappingProfile, CopierError> {
+    if name.trim().is_empty() {
+        return Err(CopierError::ParseError("Profile name is empty".to_string()));
+    }
+    let profile = MappingProfile {
+        name: name.trim().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        mappings,
+    };
+    write_profile(dir, &profile)?;
+    Ok(profile)
+}
+
+/// Save a complete profile as-is, replacing any with the same name
+/// (settings import)
+pub fn import_profile(profile: &MappingProfile) -> Result<(), CopierError> {
+    if profile.name.trim().is_empty() {
+        return Err(CopierError::ParseError("Profile name is empty".to_string()));
+    }
+    write_profile(&get_profiles_dir()?, profile)
+}
+
+fn write_profile(dir: &Path, profile: &MappingProfile) -> Result<(), CopierError> {
+    fs::create_dir_all(dir).map_err(|e| CopierError::io("Failed to create profiles folder", e))?;
+    let path = profile_file(dir, &profile.name);
+    let json = serde_json::to_string_pretty(profile).map_err(|e| CopierError::parse("Failed to serialize profile", e))?;
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| CopierError::io("Failed to write profile", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| CopierError::io("Failed to save profile", e))?;
+    Ok(())
+}
+
+pub fn load_profile(name: &str) -> Result<MappingProfile, CopierError> {
+    load_profile_in(&get_profiles_dir()?, name)
+}
+
+fn load_profile_in(dir: &Path, name: &str) -> Result<MappingProfile, CopierError> {
+    let content = fs::read_to_string(profile_file(dir, name))
+        .map_err(|e| CopierError::io(&format!("Failed to read mapping profile '{}'", name), e))?;
+    serde_json::from_str(&content).map_err(|e| CopierError::parse("Failed to parse mapping profile", e))
+}
+
+/// Names of every saved profile
+pub fn list_profiles() -> Result<Vec<String>, CopierError> {
+    let dir = get_profiles_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .map_err(|e| CopierError::io("Failed to read profiles folder", e))?
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| load_profile_in(&dir, &e.path().file_stem()?.to_string_lossy()).ok())
+        .map(|p| p.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Keep the profile's mappings whose receiver symbol is in `catalog`
+pub fn apply_profile(profile: &MappingProfile, catalog: &SymbolCatalog) -> AppliedProfile {
+    let available: HashSet<&str> = catalog.symbols.iter().map(|s| s.name.as_str()).collect();
+    let (mappings, missing): (Vec<SymbolMapping>, Vec<SymbolMapping>) = profile
+        .mappings
+        .iter()
+        .cloned()
+        .partition(|m| available.contains(m.receiver_symbol.as_str()));
+
+    AppliedProfile {
+        mappings,
+        missing_symbols: missing.into_iter().map(|m| m.receiver_symbol).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::symbol_catalog::SymbolSpec;
+
+    fn mapping(master: &str, receiver: &str) -> SymbolMapping {
+        SymbolMapping {
+            master_symbol: master.to_string(),
+            receiver_symbol: receiver.to_string(),
+            is_enabled: true,
+            auto_mapped: true,
+            match_method: "specs".to_string(),
+            confidence: 95,
+        }
+    }
+
+    fn spec(name: &str) -> SymbolSpec {
+        SymbolSpec {
+            name: name.to_string(),
+            normalized_key: name.to_string(),
+            tick_value: 1.0,
+            tick_size: 0.00001,
+            contract_size: 100000.0,
+            digits: 5,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 100.0,
+            description: None,
+            trade_mode: None,
+            profit_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_save_profile_and_apply_to_catalog_missing_a_symbol() {
+        let dir = std::env::temp_dir().join(format!("saturn_profiles_test_{}", uuid::Uuid::new_v4()));
+        let mappings = vec![mapping("EURUSD", "EURUSD.r"), mapping("US100", "NAS100.r"), mapping("XAUUSD", "GOLD.r")];
+
+        save_profile_in(&dir, "ICM raw / FTMO", mappings).unwrap();
+        let profile = load_profile_in(&dir, "ICM raw / FTMO").unwrap();
+        assert_eq!(profile.name, "ICM raw / FTMO");
+        assert_eq!(profile.mappings.len(), 3);
+
+        let catalog = SymbolCatalog {
+            terminal_id: "R2".to_string(),
+            symbols: vec![spec("EURUSD.r"), spec("GOLD.r")],
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            broker_suffix: Some(".r".to_string()),
+        };
+        let applied = apply_profile(&profile, &catalog);
+        let kept: Vec<&str> = applied.mappings.iter().map(|m| m.master_symbol.as_str()).collect();
+        assert_eq!(kept, vec!["EURUSD", "XAUUSD"]);
+        assert_eq!(applied.missing_symbols, vec!["NAS100.r".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,368 @@
+//! Symbol mapping reconciliation after a catalog change
+//!
+//! Brokers rename and re-list symbols, so a receiver's saved mappings can
+//! end up pointing at symbols its catalog no longer has, or name the same
+//! master symbol twice. `reconcile` checks saved mappings against a freshly
+//! fetched catalog: duplicates are merged, mappings whose receiver symbol
+//! vanished are marked `stale` and disabled, the orphaned master symbols
+//! are auto-mapped again, and new collisions are resolved as in
+//! `auto_map_symbols_by_specs`. Nothing is applied; the result is a proposal
+//! plus the list of changes for the user to confirm. `apply_changes` applies
+//! the confirmed ones and they are remembered as manual mappings, so a cloud
+//! re-sync doesn't bring back stale or duplicate mappings.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::manual_mappings;
+use super::symbol_catalog::{self, MappingConflict, SymbolCatalog, SymbolMapping};
+use super::CopierConfig;
+
+/// `match_method` of a saved mapping carried over unchanged
+const SAVED_METHOD: &str = "saved";
+
+/// `match_method` of a mapping whose receiver symbol left the catalog
+pub const STALE_METHOD: &str = "stale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingChangeKind {
+    /// A second mapping for the same master symbol, dropped
+    Merged,
+    /// Receiver symbol is gone and no replacement was found; disabled
+    Stale,
+    /// Receiver symbol is gone; auto-mapping found a replacement
+    Remapped,
+    /// Now collides with another mapping on the same receiver symbol; disabled
+    Conflict,
+}
+
+/// One proposed change to a receiver's mappings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingChange {
+    pub master_symbol: String,
+    pub kind: MappingChangeKind,
+    pub before: SymbolMapping,
+    /// For `Merged`, the mapping kept for the master symbol
+    pub after: SymbolMapping,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingReconciliation {
+    pub receiver_id: String,
+    /// The receiver's mappings with every change applied
+    pub mappings: Vec<SymbolMapping>,
+    pub changes: Vec<MappingChange>,
+    pub conflicts: Vec<MappingConflict>,
+}
+
+fn saved_mapping(mapping: &super::SymbolMapping) -> SymbolMapping {
+    SymbolMapping {
+        master_symbol: mapping.master_symbol.clone(),
+        receiver_symbol: mapping.receiver_symbol.clone(),
+        is_enabled: mapping.is_enabled,
+        auto_mapped: false,
+        match_method: SAVED_METHOD.to_string(),
+        // Saved choices win collisions against re-mapped symbols
+        confidence: 100,
+    }
+}
+
+/// One mapping per master symbol: the first enabled one, else the first
+fn merge_duplicates(saved: &[super::SymbolMapping], changes: &mut Vec<MappingChange>) -> Vec<SymbolMapping> {
+    let mut kept: Vec<SymbolMapping> = Vec::new();
+    let mut dropped = Vec::new();
+    for mapping in saved.iter().map(saved_mapping) {
+        match kept.iter_mut().find(|k| k.master_symbol == mapping.master_symbol) {
+            Some(existing) if !existing.is_enabled && mapping.is_enabled => {
+                dropped.push(std::mem::replace(existing, mapping));
+            }
+            Some(_) => dropped.push(mapping),
+            None => kept.push(mapping),
+        }
+    }
+    for before in dropped {
+        let Some(after) = kept.iter().find(|k| k.master_symbol == before.master_symbol).cloned() else {
+            continue;
+        };
+        changes.push(MappingChange {
+            master_symbol: before.master_symbol.clone(),
+            kind: MappingChangeKind::Merged,
+            before,
+            after,
+        });
+    }
+    kept
+}
+
+/// Auto-mapped replacements for `orphans`, by master symbol. Matched by
+/// specs when the master catalog is available, else by name.
+fn remap_orphans(
+    orphans: &HashSet<String>,
+    master_catalog: Option<&SymbolCatalog>,
+    receiver_catalog: &SymbolCatalog,
+    hints: &HashMap<String, String>,
+) -> HashMap<String, SymbolMapping> {
+    let remapped = match master_catalog {
+        Some(master) => {
+            let master = SymbolCatalog {
+                symbols: master.symbols.iter().filter(|s| orphans.contains(&s.name)).cloned().collect(),
+                ..master.clone()
+            };
+            symbol_catalog::auto_map_symbols_by_specs(&master, receiver_catalog, hints).mappings
+        }
+        None => {
+            let mut symbols: Vec<String> = orphans.iter().cloned().collect();
+            symbols.sort();
+            symbol_catalog::auto_map_symbols(&symbols, receiver_catalog)
+        }
+    };
+    remapped.into_iter().map(|m| (m.master_symbol.clone(), m)).collect()
+}
+
+/// Check a receiver's saved mappings against its current catalog
+pub fn reconcile(
+    receiver_id: &str,
+    saved: &[super::SymbolMapping],
+    master_catalog: Option<&SymbolCatalog>,
+    receiver_catalog: &SymbolCatalog,
+    hints: &HashMap<String, String>,
+) -> MappingReconciliation {
+    let mut changes = Vec::new();
+    let mut mappings = merge_duplicates(saved, &mut changes);
+
+    let available: HashSet<&str> = receiver_catalog.symbols.iter().map(|s| s.name.as_str()).collect();
+    let orphans: HashSet<String> = mappings
+        .iter()
+        .filter(|m| !available.contains(m.receiver_symbol.as_str()))
+        .map(|m| m.master_symbol.clone())
+        .collect();
+    let mut replacements = remap_orphans(&orphans, master_catalog, receiver_catalog, hints);
+
+    for mapping in mappings.iter_mut().filter(|m| orphans.contains(&m.master_symbol)) {
+        let before = mapping.clone();
+        let kind = match replacements.remove(&mapping.master_symbol) {
+            Some(replacement) => {
+                *mapping = replacement;
+                MappingChangeKind::Remapped
+            }
+            None => {
+                mapping.is_enabled = false;
+                mapping.match_method = STALE_METHOD.to_string();
+                mapping.confidence = 0;
+                MappingChangeKind::Stale
+            }
+        };
+        changes.push(MappingChange {
+            master_symbol: mapping.master_symbol.clone(),
+            kind,
+            before,
+            after: mapping.clone(),
+        });
+    }
+
+    let before_conflicts: HashMap<String, SymbolMapping> =
+        mappings.iter().map(|m| (m.master_symbol.clone(), m.clone())).collect();
+    let conflicts = symbol_catalog::resolve_mapping_conflicts(&mut mappings);
+    for conflict in &conflicts {
+        for master_symbol in &conflict.disabled_master_symbols {
+            let Some(after) = mappings.iter().find(|m| &m.master_symbol == master_symbol).cloned() else {
+                continue;
+            };
+            // A re-mapped symbol that lost its collision is still one change
+            // from its saved mapping
+            let remapped = changes
+                .iter()
+                .position(|c| c.master_symbol == *master_symbol && c.kind == MappingChangeKind::Remapped)
+                .map(|i| changes.remove(i).before);
+            changes.push(MappingChange {
+                master_symbol: master_symbol.clone(),
+                kind: MappingChangeKind::Conflict,
+                before: remapped.unwrap_or_else(|| before_conflicts[master_symbol].clone()),
+                after,
+            });
+        }
+    }
+
+    // A merged symbol's kept mapping may have gone stale or collided since
+    for change in changes.iter_mut().filter(|c| c.kind == MappingChangeKind::Merged) {
+        if let Some(kept) = mappings.iter().find(|m| m.master_symbol == change.master_symbol) {
+            change.after = kept.clone();
+        }
+    }
+
+    changes.sort_by(|a, b| a.master_symbol.cmp(&b.master_symbol));
+    MappingReconciliation {
+        receiver_id: receiver_id.to_string(),
+        mappings,
+        changes,
+        conflicts,
+    }
+}
+
+/// Apply confirmed changes to a receiver in `config`: each master symbol
+/// ends up with the one mapping in the change's `after`, disabled ones
+/// included. Enabled targets must be tradable in `catalog`; nothing is
+/// applied if one isn't. Returns the entries to remember as manual mappings
+/// (receiver symbol, or None when disabled).
+pub fn apply_changes(
+    config: &mut CopierConfig,
+    receiver_id: &str,
+    changes: &[MappingChange],
+    catalog: &SymbolCatalog,
+) -> Result<Vec<(String, Option<String>)>, String> {
+    for change in changes.iter().filter(|c| c.after.is_enabled) {
+        manual_mappings::validate_receiver_symbol(catalog, &change.after.receiver_symbol)?;
+    }
+    let mut entries = Vec::new();
+    for change in changes {
+        let receiver_symbol = if change.after.is_enabled {
+            manual_mappings::apply_to(config, receiver_id, &change.master_symbol, &change.after.receiver_symbol, catalog)?;
+            Some(change.after.receiver_symbol.clone())
+        } else {
+            manual_mappings::disable_in(config, receiver_id, &change.master_symbol)?;
+            None
+        };
+        entries.push((change.master_symbol.clone(), receiver_symbol));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::symbol_catalog::SymbolSpec;
+
+    fn spec(name: &str, contract_size: f64) -> SymbolSpec {
+        SymbolSpec {
+            name: name.to_string(),
+            normalized_key: symbol_catalog::normalize_symbol(name),
+            tick_value: 1.0,
+            tick_size: 0.01,
+            contract_size,
+            digits: 2,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 100.0,
+            description: None,
+            trade_mode: Some("full".to_string()),
+            profit_currency: None,
+        }
+    }
+
+    fn catalog(terminal_id: &str, symbols: Vec<SymbolSpec>) -> SymbolCatalog {
+        SymbolCatalog {
+            terminal_id: terminal_id.to_string(),
+            symbols,
+            fetched_at: "2024-03-10T00:00:00Z".to_string(),
+            broker_suffix: None,
+        }
+    }
+
+    fn saved(master_symbol: &str, receiver_symbol: &str) -> super::super::SymbolMapping {
+        super::super::SymbolMapping {
+            master_symbol: master_symbol.to_string(),
+            receiver_symbol: receiver_symbol.to_string(),
+            is_enabled: true,
+        }
+    }
+
+    fn change<'a>(result: &'a MappingReconciliation, master_symbol: &str) -> &'a MappingChange {
+        result.changes.iter().find(|c| c.master_symbol == master_symbol).unwrap()
+    }
+
+    #[test]
+    fn test_vanished_receiver_symbol_is_remapped_or_marked_stale() {
+        // The broker re-listed XAUUSD.pro as GOLD and delisted US30.pro
+        let master = catalog("M1", vec![spec("XAUUSD", 100.0), spec("US30", 7.0), spec("EURUSD", 100_000.0)]);
+        let receiver = catalog("R1", vec![spec("GOLD", 100.0), spec("EURUSD.pro", 100_000.0)]);
+        let mappings = vec![saved("XAUUSD", "XAUUSD.pro"), saved("US30", "US30.pro"), saved("EURUSD", "EURUSD.pro")];
+
+        let result = reconcile("r1", &mappings, Some(&master), &receiver, &HashMap::new());
+        assert_eq!(result.changes.len(), 2);
+
+        let gold = change(&result, "XAUUSD");
+        assert_eq!(gold.kind, MappingChangeKind::Remapped);
+        assert_eq!(gold.before.receiver_symbol, "XAUUSD.pro");
+        assert_eq!(gold.after.receiver_symbol, "GOLD");
+
+        let us30 = change(&result, "US30");
+        assert_eq!(us30.kind, MappingChangeKind::Stale);
+        let stale = &us30.after;
+        assert!(!stale.is_enabled);
+        assert_eq!(stale.match_method, STALE_METHOD);
+
+        // Untouched mappings carry over
+        let eurusd = result.mappings.iter().find(|m| m.master_symbol == "EURUSD").unwrap();
+        assert!(eurusd.is_enabled && eurusd.match_method == SAVED_METHOD);
+        assert_eq!(result.mappings.len(), 3);
+    }
+
+    #[test]
+    fn test_duplicates_are_merged_and_collisions_disabled() {
+        let receiver = catalog("R1", vec![spec("GOLD", 100.0), spec("XAUUSD", 100.0)]);
+        let mut disabled = saved("XAUUSD", "XAUUSD");
+        disabled.is_enabled = false;
+        // XAUUSD twice; GOLDm vanished and its name-based replacement
+        // collides with the saved XAUUSD -> GOLD
+        let mappings = vec![disabled, saved("XAUUSD", "GOLD"), saved("GOLD", "GOLDm")];
+
+        let result = reconcile("r1", &mappings, None, &receiver, &HashMap::new());
+        let merged = change(&result, "XAUUSD");
+        assert_eq!(merged.kind, MappingChangeKind::Merged);
+        assert!(!merged.before.is_enabled);
+        assert_eq!(merged.after.receiver_symbol, "GOLD");
+
+        let collided = change(&result, "GOLD");
+        assert_eq!(collided.kind, MappingChangeKind::Conflict);
+        assert_eq!(collided.before.receiver_symbol, "GOLDm");
+        assert!(!collided.after.is_enabled);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].kept_master_symbol, "XAUUSD");
+        assert_eq!(result.mappings.len(), 2);
+    }
+
+    #[test]
+    fn test_confirmed_changes_are_applied_to_the_config() {
+        let mut config: CopierConfig = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": "h",
+            "master": {"account_id": "m1", "account_number": "1001", "broker": "A", "terminal_id": "M1"},
+            "receivers": [{
+                "account_id": "r1",
+                "account_number": "2001",
+                "broker": "B",
+                "terminal_id": "R1",
+                "risk_mode": "fixed_lot",
+                "risk_value": 0.1,
+                "max_slippage_pips": 3.0,
+                "max_daily_loss_r": null,
+                "prop_firm_safe_mode": false,
+                "symbol_mappings": []
+            }]
+        }))
+        .unwrap();
+        let master = catalog("M1", vec![spec("XAUUSD", 100.0), spec("US30", 7.0)]);
+        let receiver = catalog("R1", vec![spec("GOLD", 100.0)]);
+        let saved_mappings = vec![saved("XAUUSD", "XAUUSD.pro"), saved("US30", "US30.pro"), saved("US30", "DJ30")];
+        config.receivers[0].symbol_mappings = saved_mappings.clone();
+
+        let result = reconcile("r1", &saved_mappings, Some(&master), &receiver, &HashMap::new());
+        let entries = apply_changes(&mut config, "r1", &result.changes, &receiver).unwrap();
+
+        let mappings = &config.receivers[0].symbol_mappings;
+        assert_eq!(mappings.len(), 2);
+        let gold = mappings.iter().find(|m| m.master_symbol == "XAUUSD").unwrap();
+        assert!(gold.is_enabled && gold.receiver_symbol == "GOLD");
+        let us30 = mappings.iter().find(|m| m.master_symbol == "US30").unwrap();
+        assert!(!us30.is_enabled);
+        assert!(entries.contains(&("XAUUSD".to_string(), Some("GOLD".to_string()))));
+        assert!(entries.contains(&("US30".to_string(), None)));
+
+        // A change naming a symbol the receiver can't trade applies nothing
+        let mut bad = result.changes[0].clone();
+        bad.after.receiver_symbol = "SILVER".to_string();
+        bad.after.is_enabled = true;
+        assert!(apply_changes(&mut config, "r1", &[bad], &receiver).is_err());
+    }
+}
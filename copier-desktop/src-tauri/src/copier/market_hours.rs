@@ -0,0 +1,216 @@
+//! Market session calendar
+//!
+//! Events that arrive while the market is closed (weekend rollover
+//! artifacts, the Sunday gap) would only fail against the broker until the
+//! session opens, burning retry attempts. The event processor consults this
+//! calendar first and parks such executions in the execution queue until
+//! the next open. The calendar is a per-machine setting, persisted locally
+//! like `watch_settings`. Crypto symbols trade around the clock and are
+//! never considered closed.
+
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc, Weekday};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use super::symbol_catalog;
+use crate::data_dir::app_data_dir;
+
+const CALENDAR_FILE: &str = "market_hours.json";
+
+const MINUTES_PER_WEEK: u32 = 7 * 24 * 60;
+
+/// A time within the (UTC) week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyTime {
+    pub weekday: Weekday,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl WeeklyTime {
+    fn minute_of_week(&self) -> u32 {
+        (self.weekday.num_days_from_monday() * 24 * 60 + self.hour * 60 + self.minute) % MINUTES_PER_WEEK
+    }
+}
+
+/// A recurring weekly closure, `from` inclusive to `to` exclusive. May wrap
+/// around the end of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClosedWindow {
+    pub from: WeeklyTime,
+    pub to: WeeklyTime,
+}
+
+impl ClosedWindow {
+    fn contains(&self, minute_of_week: u32) -> bool {
+        let (from, to) = (self.from.minute_of_week(), self.to.minute_of_week());
+        if from <= to {
+            (from..to).contains(&minute_of_week)
+        } else {
+            minute_of_week >= from || minute_of_week < to
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketCalendar {
+    /// Off = treat the market as always open
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_closed")]
+    pub closed: Vec<ClosedWindow>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// FX weekend: Friday 22:00 to Sunday 22:00 UTC
+fn default_closed() -> Vec<ClosedWindow> {
+    vec![ClosedWindow {
+        from: WeeklyTime {
+            weekday: Weekday::Fri,
+            hour: 22,
+            minute: 0,
+        },
+        to: WeeklyTime {
+            weekday: Weekday::Sun,
+            hour: 22,
+            minute: 0,
+        },
+    }]
+}
+
+impl Default for MarketCalendar {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            closed: default_closed(),
+        }
+    }
+}
+
+fn minute_of_week(at: DateTime<Utc>) -> u32 {
+    at.weekday().num_days_from_monday() * 24 * 60 + at.hour() * 60 + at.minute()
+}
+
+impl MarketCalendar {
+    /// When the session next opens, or None if it is open at `at`.
+    /// Back-to-back windows are followed through to the real open.
+    pub fn next_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        let mut open_at = at;
+        // Each hop lands on the end of a window, so there can be at most one per window
+        for _ in 0..=self.closed.len() {
+            let now = minute_of_week(open_at);
+            let Some(window) = self.closed.iter().find(|w| w.contains(now)) else {
+                return (open_at != at).then_some(open_at);
+            };
+            let minutes = (window.to.minute_of_week() + MINUTES_PER_WEEK - now) % MINUTES_PER_WEEK;
+            let start_of_minute = open_at.duration_trunc(Duration::minutes(1)).unwrap_or(open_at);
+            open_at = start_of_minute + Duration::minutes(minutes as i64);
+        }
+        // Windows cover the whole week; nothing to wait for
+        None
+    }
+
+    /// `next_open` for `symbol`: always None for crypto
+    pub fn next_open_for(&self, symbol: &str, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if symbol_catalog::is_crypto_symbol(symbol) {
+            return None;
+        }
+        self.next_open(at)
+    }
+}
+
+static CALENDAR: LazyLock<Mutex<MarketCalendar>> = LazyLock::new(|| Mutex::new(load()));
+
+fn get_calendar_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(CALENDAR_FILE))
+}
+
+fn load() -> MarketCalendar {
+    let Some(path) = get_calendar_path() else {
+        return MarketCalendar::default();
+    };
+    match fs::read_to_string(&path).map(|c| serde_json::from_str(&c)) {
+        Ok(Ok(calendar)) => calendar,
+        Ok(Err(e)) => {
+            warn!("Ignoring unreadable market hours file: {}", e);
+            MarketCalendar::default()
+        }
+        Err(_) => MarketCalendar::default(),
+    }
+}
+
+/// Current calendar
+pub fn current() -> MarketCalendar {
+    CALENDAR.lock().clone()
+}
+
+/// Replace the calendar and persist it (atomic write)
+pub fn set(calendar: MarketCalendar) -> Result<(), String> {
+    if let Some(path) = get_calendar_path() {
+        let json = serde_json::to_string_pretty(&calendar).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write market hours: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save market hours: {}", e))?;
+    }
+    *CALENDAR.lock() = calendar;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_fx_weekend_closure() {
+        let calendar = MarketCalendar::default();
+        // 2024-01-05 is a Friday
+        assert!(calendar.next_open(at("2024-01-05T21:59:00Z")).is_none());
+        assert!(calendar.next_open(at("2024-01-05T22:00:00Z")).is_some());
+        assert!(calendar.next_open(at("2024-01-07T21:59:59Z")).is_some());
+        assert!(calendar.next_open(at("2024-01-07T22:00:00Z")).is_none());
+
+        assert_eq!(calendar.next_open(at("2024-01-06T10:30:45Z")), Some(at("2024-01-07T22:00:00Z")));
+
+        let disabled = MarketCalendar {
+            enabled: false,
+            ..MarketCalendar::default()
+        };
+        assert!(disabled.next_open(at("2024-01-06T10:30:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_crypto_is_open_on_the_weekend() {
+        let calendar = MarketCalendar::default();
+        let saturday = at("2024-01-06T10:30:00Z");
+        assert!(calendar.next_open_for("BTCUSD", saturday).is_none());
+        assert!(calendar.next_open_for("ETHUSD.m", saturday).is_none());
+        assert!(calendar.next_open_for("EURUSD", saturday).is_some());
+        // Gold is a metal, not crypto
+        assert!(calendar.next_open_for("XAUUSD", saturday).is_some());
+    }
+
+    #[test]
+    fn test_calendar_round_trips_through_json() {
+        let json = serde_json::to_string(&MarketCalendar::default()).unwrap();
+        let parsed: MarketCalendar = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, MarketCalendar::default());
+        assert_eq!(serde_json::from_str::<MarketCalendar>("{}").unwrap(), MarketCalendar::default());
+    }
+}
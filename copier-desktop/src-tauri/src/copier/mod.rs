@@ -1,23 +1,134 @@
+pub mod aggregate;
+pub mod alerts;
+pub mod approvals;
+pub mod catch_up;
+pub mod clock_skew;
+pub mod command_backlog;
+pub mod commanded_levels;
 pub mod commands;
+pub mod config_diff;
 pub mod config_generator;
+pub mod copy_decision;
+pub mod currency;
+pub mod diagnostics;
+pub mod equity_curve;
+pub mod error;
 pub mod event_processor;
-
+pub mod execution_queue;
+pub mod file_lock;
 pub mod file_watcher;
+pub mod global_cap;
+pub mod health;
+pub mod hot_reload;
 pub mod idempotency;
+pub mod journal;
+pub mod kill_switch;
+pub mod latency;
+pub mod live_balance;
 pub mod lot_calculator;
+pub mod lot_preview;
+pub mod manual_mappings;
+pub mod mapping_hints;
+pub mod mapping_profiles;
+pub mod mapping_reconcile;
+pub mod market_hours;
+pub mod parse_errors;
+pub mod persistence;
 pub mod position_sync;
+pub mod receiver_stats;
+pub mod receiver_toggles;
+pub mod recovery;
+pub mod resync;
+pub mod risk_suggestion;
 pub mod safety;
+pub mod selftest;
+pub mod settings_bundle;
+pub mod shutdown;
+pub mod slippage;
+pub mod stale_config;
 pub mod symbol_catalog;
+pub mod symbol_rules;
+pub mod terminal_diagnostics;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod ticks;
 pub mod trade_executor;
+pub mod watch_settings;
 
 use serde::{Deserialize, Serialize};
 
+pub use error::CopierError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CopierConfig {
     pub version: i32,
     pub config_hash: String,
+    /// Primary master; receivers that don't name a master follow it
     pub master: MasterConfig,
     pub receivers: Vec<ReceiverConfig>,
+    /// Additional masters, each feeding the receivers whose
+    /// `master_account_id` names it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masters: Vec<MasterConfig>,
+    /// How one master event is fanned out to the receivers
+    #[serde(default)]
+    pub execution_strategy: ExecutionStrategy,
+}
+
+/// Order in which a master event is copied to the receivers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ExecutionStrategy {
+    /// One receiver at a time, in config order
+    #[default]
+    Sequential,
+    /// All receivers at once (bounded by `event_processor::MAX_PARALLEL_EXECUTIONS`)
+    Parallel,
+    /// The primary receiver first, then the rest in parallel
+    PriorityOrdered { primary_terminal_id: String },
+}
+
+impl CopierConfig {
+    /// Every master: the primary first, then the additional ones
+    /// (duplicates of the primary skipped)
+    pub fn all_masters(&self) -> Vec<&MasterConfig> {
+        let mut masters = vec![&self.master];
+        for master in &self.masters {
+            if !masters.iter().any(|m| m.account_id == master.account_id) {
+                masters.push(master);
+            }
+        }
+        masters
+    }
+
+    /// Whether `receiver` copies from the master with `master_account_id`
+    pub fn receiver_follows(&self, receiver: &ReceiverConfig, master_account_id: &str) -> bool {
+        receiver.master_account_id.as_deref().unwrap_or(&self.master.account_id) == master_account_id
+    }
+
+    /// The config as seen by one master's group: that master as `master`
+    /// and only the receivers following it. None if no such master.
+    pub fn for_master(&self, master_account_id: &str) -> Option<CopierConfig> {
+        let master = self
+            .all_masters()
+            .into_iter()
+            .find(|m| m.account_id == master_account_id)?
+            .clone();
+        let receivers = self
+            .receivers
+            .iter()
+            .filter(|r| self.receiver_follows(r, master_account_id))
+            .cloned()
+            .collect();
+        Some(CopierConfig {
+            version: self.version,
+            config_hash: self.config_hash.clone(),
+            master,
+            receivers,
+            masters: Vec::new(),
+            execution_strategy: self.execution_strategy.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +151,221 @@ pub struct ReceiverConfig {
     pub max_daily_loss_r: Option<f64>,
     pub prop_firm_safe_mode: bool,
     pub symbol_mappings: Vec<SymbolMapping>,
+    /// Cap on copied entries per minute for this receiver (None = unlimited).
+    /// Entries over the cap are deferred through the execution queue.
+    #[serde(default)]
+    pub max_entries_per_minute: Option<u32>,
+    /// Open the master's already-open positions once this receiver comes
+    /// online (see `catch_up`)
+    #[serde(default)]
+    pub copy_existing_on_start: bool,
+    /// Place SL/TP at the master's distance from the receiver's own entry
+    /// (scaled to the receiver symbol's digits) instead of copying prices
+    #[serde(default)]
+    pub use_relative_sltp: bool,
+    /// Keep copying even if the receiver's EA is older than
+    /// `discovery::MIN_SUPPORTED_RECEIVER_EA_VERSION`
+    #[serde(default)]
+    pub allow_outdated_ea: bool,
+    /// `account_id` of the master this receiver copies (None = the primary
+    /// `CopierConfig::master`)
+    #[serde(default)]
+    pub master_account_id: Option<String>,
+    /// How master SL/TP levels are carried over, including zero/absent ones
+    #[serde(default)]
+    pub sltp_policy: SltpPolicy,
+    /// Skip entries whose master event is older than this many seconds
+    /// (None = copy regardless of age). Exits are never skipped.
+    #[serde(default)]
+    pub max_event_age_secs: Option<u64>,
+    /// Refuse entries once the receiver's price is more than this many pips
+    /// from the master's entry price (None = no check; the EA still
+    /// enforces `max_slippage_pips`)
+    #[serde(default)]
+    pub max_entry_deviation_pips: Option<f64>,
+    /// Hold back entries while the receiver's spread plus its expected
+    /// slippage (7-day average) is above this many pips; refused if it stays
+    /// above for `ENTRY_COST_WAIT` (None = no check)
+    #[serde(default)]
+    pub max_entry_cost_pips: Option<f64>,
+    /// Disabled receivers get no new opens; closes and modifies of positions
+    /// they already hold still go through
+    #[serde(default = "default_receiver_enabled")]
+    pub enabled: bool,
+    /// Shift master event timestamps by the master terminal's measured clock
+    /// skew before applying `max_event_age_secs`
+    #[serde(default)]
+    pub correct_clock_skew: bool,
+    /// Magic number the EA stamps on copied orders (None = the EA's own).
+    /// Reconciliation only considers receiver positions carrying it.
+    #[serde(default)]
+    pub magic_number: Option<i64>,
+    /// Comment prefix on copied orders, followed by the master position id
+    /// (None = the EA's "Copier:")
+    #[serde(default)]
+    pub comment_prefix: Option<String>,
+    /// Refuse new opens once the receiver's copied positions would total
+    /// more than this many lots (None = no ceiling). Closes always proceed.
+    #[serde(default)]
+    pub max_total_lots: Option<f64>,
+    /// Refuse new opens once this many copied positions are open
+    /// (None = no ceiling)
+    #[serde(default)]
+    pub max_open_positions: Option<u32>,
+    /// Hold new opens for the user to approve (see `approvals`)
+    #[serde(default)]
+    pub manual_confirm_mode: bool,
+    /// Seconds an open waits for approval before it's auto-rejected
+    /// (None = `approvals::DEFAULT_APPROVAL_TIMEOUT_SECS`)
+    #[serde(default)]
+    pub manual_confirm_timeout_secs: Option<u64>,
+    /// Copy only this share (0.0-1.0) of master positions, picked by a
+    /// stable hash of the master position id (None = copy everything)
+    #[serde(default)]
+    pub copy_fraction: Option<f64>,
+    /// Close everything and lock the receiver once equity is this much
+    /// above its starting balance (see `safety::update_equity`)
+    #[serde(default)]
+    pub profit_target_amount: Option<f64>,
+    /// Same as `profit_target_amount`, as a % of the starting balance
+    #[serde(default)]
+    pub profit_target_percent: Option<f64>,
+    /// Unit `max_slippage_pips` is given in. Pips unless set, so existing
+    /// configs keep their meaning.
+    #[serde(default)]
+    pub slippage_unit: slippage::SlippageUnit,
+    /// Entries on the same symbol closer together than this many seconds
+    /// are debounced per `signal_debounce` (None = no minimum). Exits are
+    /// never debounced.
+    #[serde(default)]
+    pub min_signal_interval_secs: Option<u64>,
+    /// What happens to an entry inside `min_signal_interval_secs`
+    #[serde(default)]
+    pub signal_debounce: SignalDebounce,
+    /// Leave SL/TP levels the user moved by hand on copied positions out of
+    /// reconciliation instead of syncing them back
+    #[serde(default)]
+    pub respect_manual_sltp: bool,
+    /// Block closes and SL/TP changes too while safety-paused. By default a
+    /// pause only blocks new exposure.
+    #[serde(default)]
+    pub block_closes_when_paused: bool,
+    /// Which master events the receiver copies
+    #[serde(default)]
+    pub copy_mode: CopyMode,
+    /// Reconciliation counts receiver SL/TP within this many pips of the
+    /// master's as in sync (None = `position_sync::SL_TP_TOLERANCE`)
+    #[serde(default)]
+    pub sltp_tolerance_pips: Option<f64>,
+    /// Seconds after an SL/TP sync during which the same position is only
+    /// flagged again for a mismatch beyond `sltp_cooldown_tolerance_pips`
+    /// (None = `position_sync::DEFAULT_SLTP_COOLDOWN_SECS`)
+    #[serde(default)]
+    pub sltp_cooldown_secs: Option<u64>,
+    /// None = `position_sync::DEFAULT_SLTP_COOLDOWN_TOLERANCE`
+    #[serde(default)]
+    pub sltp_cooldown_tolerance_pips: Option<f64>,
+    /// Hold the master's net position per symbol (scaled) instead of
+    /// copying each trade; see `aggregate`
+    #[serde(default)]
+    pub aggregate_mode: bool,
+    /// Lift a loss/drawdown/equity safety pause after this many seconds
+    /// (None = only the daily reset or a manual unpause)
+    #[serde(default)]
+    pub auto_resume_after_secs: Option<u64>,
+    /// Auto-resume only once equity is back at or above this
+    #[serde(default)]
+    pub auto_resume_min_equity: Option<f64>,
+    /// How calculated lots are brought onto the broker's lot step
+    #[serde(default)]
+    pub lot_rounding: lot_calculator::LotRounding,
+    /// Queue the unfilled volume of a partially filled entry as a new entry
+    #[serde(default)]
+    pub retry_partial_fill_remainder: bool,
+    /// Fixed delay before this receiver executes each event
+    #[serde(default)]
+    pub copy_delay_ms: u64,
+    /// Random extra delay of up to this much, drawn per receiver per event
+    #[serde(default)]
+    pub copy_delay_jitter_ms: u64,
+}
+
+impl ReceiverConfig {
+    /// Slippage limit with its unit
+    pub fn max_slippage(&self) -> slippage::SlippageSpec {
+        slippage::SlippageSpec::new(self.max_slippage_pips, self.slippage_unit)
+    }
+
+    /// SL/TP tolerances reconciliation applies to this receiver
+    pub fn sltp_sync(&self) -> position_sync::SlTpSync {
+        let defaults = position_sync::SlTpSync::default();
+        position_sync::SlTpSync {
+            tolerance: self.sltp_tolerance_pips.map(slippage::SlippageSpec::pips).unwrap_or(defaults.tolerance),
+            cooldown: self.sltp_cooldown_secs.map(std::time::Duration::from_secs).unwrap_or(defaults.cooldown),
+            cooldown_tolerance: self
+                .sltp_cooldown_tolerance_pips
+                .map(slippage::SlippageSpec::pips)
+                .unwrap_or(defaults.cooldown_tolerance),
+        }
+    }
+}
+
+fn default_receiver_enabled() -> bool {
+    true
+}
+
+/// Which master events a receiver copies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyMode {
+    /// Everything
+    #[default]
+    Full,
+    /// Opens, SL/TP changes and pending orders; closes are left to the user
+    EntriesOnly,
+    /// Closes and SL/TP changes of positions it already holds; nothing new
+    /// is opened (e.g. a risk-management account)
+    ExitsOnly,
+}
+
+/// What happens to an entry that arrives within a receiver's
+/// `min_signal_interval_secs` of the previous one on its symbol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalDebounce {
+    /// Skip it, recording why
+    #[default]
+    Drop,
+    /// Hold it in the execution queue until the interval has passed
+    Defer,
+}
+
+/// How a receiver treats the master's SL/TP levels. Shared by the execution
+/// path and position reconciliation so the two never disagree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SltpPolicy {
+    /// Mirror the master exactly: a zero/absent master level clears the
+    /// receiver's
+    Copy,
+    /// Copy levels the master has set; a zero/absent master level leaves the
+    /// receiver's level untouched
+    #[default]
+    CopyIfSet,
+    /// Never copy SL/TP; the receiver manages its own
+    Ignore,
+}
+
+impl SltpPolicy {
+    /// The level to send for a master SL or TP. `None` means "don't touch
+    /// the receiver's level", `Some(0.0)` clears it.
+    pub fn apply(self, master_level: Option<f64>) -> Option<f64> {
+        match self {
+            SltpPolicy::Copy => Some(master_level.filter(|l| *l > 0.0).unwrap_or(0.0)),
+            SltpPolicy::CopyIfSet => master_level.filter(|l| *l > 0.0),
+            SltpPolicy::Ignore => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,11 +423,45 @@ pub struct TradeEvent {
     #[serde(default)]
     pub master_account_number: Option<String>,
     /// Canonical idempotency key written by the Master EA.
-    /// Format: `{terminal_id}:{deal_id_or_position_id}:{event_type}`.
+    /// Format: `{terminal_id}:{deal_id_or_position_id}:{event_type}`, with the
+    /// order ticket in the middle for pending order events.
     /// When present, the file watcher uses this verbatim; otherwise it falls
     /// back to constructing the same shape from the other fields.
     #[serde(default)]
     pub idempotency_key: Option<String>,
+    /// Closed/remaining volume for `partial_close` events (mirrors the Master
+    /// EA's `partial_close_data` block).
+    #[serde(default)]
+    pub partial_close_data: Option<PartialCloseData>,
+    /// Pending order type for `pending_order` events ("buy_limit",
+    /// "sell_limit", "buy_stop", "sell_stop"); `price` is the trigger price
+    #[serde(default)]
+    pub order_type: Option<String>,
+    /// Master order ticket for `pending_order`/`pending_cancelled` events
+    #[serde(default)]
+    pub order_ticket: Option<i64>,
+    /// When the file watcher picked the event up (local clock). Set by the
+    /// app, not the EA; persisted with queued executions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_at: Option<String>,
+}
+
+impl TradeEvent {
+    /// Id in the middle of the canonical idempotency key: the order ticket
+    /// for pending order events (an order has no deal until it fills), else
+    /// the deal id, else the position ticket
+    pub fn idempotency_id(&self) -> i64 {
+        self.order_ticket.or(self.deal_id).unwrap_or(self.ticket)
+    }
+}
+
+/// Volume breakdown for a master partial close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialCloseData {
+    /// Lots closed on the master by this deal
+    pub closed_volume: f64,
+    /// Lots still open on the master after the deal
+    pub remaining_volume: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,9 +491,29 @@ pub struct Execution {
     /// Master account number (for cloud linking)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub master_account_number: Option<String>,
+    /// Lots the calculator asked for, set only when broker min/max/step
+    /// clamping materially changed the size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intended_lots: Option<f64>,
+    /// Lots sent to the receiver, set only when the broker filled fewer
+    /// (`receiver_lots` is then the filled volume)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_lots: Option<f64>,
+    /// What the clamping did (e.g. "0.0070 lots raised to broker minimum -> 0.01")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lot_adjustment: Option<String>,
+    /// Event picked up -> result recorded (queueing + EA round-trip)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Master event timestamp -> event picked up by the file watcher
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection_ms: Option<u64>,
+    /// Command written -> EA result read back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ea_roundtrip_ms: Option<u64>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct CopierState {
     pub api_key: Option<String>,
     pub config: Option<CopierConfig>,
@@ -147,6 +527,14 @@ pub struct CopierState {
     pub config_version: i32,
     pub recent_executions: Vec<Execution>,
     pub mt5_data_path: Option<String>,
+    /// Active config was loaded from the local cache because the cloud was unreachable
+    pub config_from_cache: bool,
+    /// Age of the cached config in seconds when it was loaded
+    pub config_cache_age_secs: Option<u64>,
+    /// Reason given for the last panic stop (cleared when copying restarts)
+    pub panic_reason: Option<String>,
+    /// When queued executions were last uploaded to the cloud
+    pub last_upload_at: Option<String>,
 }
 
 /// Diagnostics information
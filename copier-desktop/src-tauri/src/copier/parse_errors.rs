@@ -0,0 +1,104 @@
+//! Malformed EA queue files
+//!
+//! An event file that still doesn't parse after the watcher's read retries
+//! is moved to the `quarantine` folder. Each one is also counted here and
+//! raised as an alert naming the file and the parse error, so a master EA
+//! writing bad output shows up in the app rather than only in the log.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::alerts::{self, AlertSeverity};
+
+/// Parse errors kept for `get_parse_errors`; the oldest are dropped first
+const MAX_RECENT_PARSE_ERRORS: usize = 50;
+
+/// One queue file that couldn't be parsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseError {
+    pub timestamp: String,
+    pub file_name: String,
+    /// serde (or read) error
+    pub error: String,
+    /// Where the file was moved; None if quarantining failed
+    pub quarantined_to: Option<String>,
+}
+
+/// Parse errors since the app started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseErrorReport {
+    pub total: u64,
+    /// Newest first
+    pub recent: Vec<ParseError>,
+}
+
+#[derive(Debug, Default)]
+struct ParseErrorLog {
+    total: u64,
+    recent: VecDeque<ParseError>,
+}
+
+impl ParseErrorLog {
+    fn record(&mut self, error: ParseError) {
+        self.total += 1;
+        self.recent.push_front(error);
+        self.recent.truncate(MAX_RECENT_PARSE_ERRORS);
+    }
+
+    fn report(&self) -> ParseErrorReport {
+        ParseErrorReport {
+            total: self.total,
+            recent: self.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+static PARSE_ERRORS: LazyLock<Mutex<ParseErrorLog>> = LazyLock::new(|| Mutex::new(ParseErrorLog::default()));
+
+/// Count a malformed event file and raise an alert for it
+pub fn record(path: &Path, error: &str, quarantined_to: Option<&Path>) {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    alerts::push_alert(
+        AlertSeverity::Warning,
+        format!("Master EA wrote a malformed event file {}: {}", file_name, error),
+    );
+    PARSE_ERRORS.lock().record(ParseError {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        file_name,
+        error: error.to_string(),
+        quarantined_to: quarantined_to.map(|p| p.display().to_string()),
+    });
+}
+
+/// Parse error count and the most recent ones
+pub fn get_parse_errors() -> ParseErrorReport {
+    PARSE_ERRORS.lock().report()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_counts_all_and_keeps_recent() {
+        let mut log = ParseErrorLog::default();
+        for i in 0..MAX_RECENT_PARSE_ERRORS + 5 {
+            log.record(ParseError {
+                timestamp: String::new(),
+                file_name: format!("{}.json", i),
+                error: "EOF while parsing".to_string(),
+                quarantined_to: None,
+            });
+        }
+        let report = log.report();
+        assert_eq!(report.total, MAX_RECENT_PARSE_ERRORS as u64 + 5);
+        assert_eq!(report.recent.len(), MAX_RECENT_PARSE_ERRORS);
+        assert_eq!(report.recent[0].file_name, format!("{}.json", MAX_RECENT_PARSE_ERRORS + 4));
+    }
+}
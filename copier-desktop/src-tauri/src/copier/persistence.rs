@@ -0,0 +1,141 @@
+//! Corruption-aware loading of local state files
+//!
+//! State files are written atomically (temp file, then rename), but a power
+//! loss can still leave a truncated or zero-filled file behind. Rather than
+//! silently starting from defaults, loaders go through `load_state_file`:
+//! a file that fails validation is moved aside with a timestamp, the `.tmp`
+//! sibling from an interrupted save is restored if it validates, and the
+//! user gets an alert either way.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{error, warn};
+
+use super::alerts::{self, AlertSeverity};
+
+/// Load and validate a state file with `parse`.
+///
+/// Returns None when there is nothing usable (no file, or a corrupt file
+/// with no valid `.tmp` to recover from); callers then start from defaults.
+pub fn load_state_file<T>(path: &Path, label: &str, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+    let temp_path = path.with_extension("tmp");
+
+    let error = match read_and_parse(path, &parse) {
+        Some(Ok(value)) => return Some(value),
+        Some(Err(e)) => e,
+        // Crash between writing the temp file and renaming it over a missing original
+        None => {
+            let value = read_and_parse(&temp_path, &parse)?.ok()?;
+            if fs::rename(&temp_path, path).is_ok() {
+                warn!("Restored {} from {:?}", label, temp_path);
+            }
+            return Some(value);
+        }
+    };
+
+    let backup = back_up_corrupt(path);
+    let backup_note = backup
+        .as_ref()
+        .map(|b| format!("; corrupt copy saved as {}", b.display()))
+        .unwrap_or_default();
+
+    if let Some(Ok(value)) = read_and_parse(&temp_path, &parse) {
+        if let Err(e) = fs::rename(&temp_path, path) {
+            warn!("Failed to restore {} from {:?}: {}", label, temp_path, e);
+        }
+        warn!("Recovered corrupt {} from its temp file: {}", label, error);
+        alerts::push_alert(
+            AlertSeverity::Warning,
+            format!("{} was corrupt and has been recovered from the last save{}", label, backup_note),
+        );
+        return Some(value);
+    }
+
+    error!("Corrupt {} could not be recovered: {}", label, error);
+    alerts::push_alert(
+        AlertSeverity::Critical,
+        format!("{} was corrupt and has been reset{}", label, backup_note),
+    );
+    None
+}
+
+/// None if the file doesn't exist
+fn read_and_parse<T>(path: &Path, parse: &impl Fn(&str) -> Result<T, String>) -> Option<Result<T, String>> {
+    let bytes = fs::read(path).ok()?;
+    Some(
+        String::from_utf8(bytes)
+            .map_err(|_| "not valid UTF-8".to_string())
+            .and_then(|content| parse(&content)),
+    )
+}
+
+/// Move a corrupt file to `<name>.corrupt-<timestamp>` next to it
+fn back_up_corrupt(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_string_lossy();
+    let backup = path.with_file_name(format!(
+        "{}.corrupt-{}",
+        file_name,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    match fs::rename(path, &backup) {
+        Ok(()) => Some(backup),
+        Err(e) => {
+            warn!("Failed to back up corrupt file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_numbers(content: &str) -> Result<Vec<u32>, String> {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("saturn_persistence_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(".corrupt-"))
+            .collect()
+    }
+
+    #[test]
+    fn test_truncated_file_recovers_from_temp() {
+        let dir = temp_dir();
+        let path = dir.join("state.json");
+        fs::write(&path, "[1, 2, 3").unwrap();
+        fs::write(path.with_extension("tmp"), "[1, 2, 3, 4]").unwrap();
+
+        assert_eq!(load_state_file(&path, "Test state", parse_numbers), Some(vec![1, 2, 3, 4]));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[1, 2, 3, 4]");
+        assert!(!path.with_extension("tmp").exists());
+        assert_eq!(backups(&dir).len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unrecoverable_file_is_backed_up() {
+        let dir = temp_dir();
+        let path = dir.join("state.json");
+        fs::write(&path, [0u8; 16]).unwrap();
+
+        assert_eq!(load_state_file(&path, "Test state", parse_numbers), None);
+        assert!(!path.exists());
+        assert_eq!(backups(&dir).len(), 1);
+
+        // Nothing on disk is not corruption
+        assert_eq!(load_state_file(&dir.join("missing.json"), "Test state", parse_numbers), None);
+        assert_eq!(backups(&dir).len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
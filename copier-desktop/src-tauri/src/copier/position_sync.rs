@@ -1,12 +1,96 @@
 //! Position synchronization module
 //! Handles syncing open positions between master and receiver accounts
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
+use super::commanded_levels::{self, CommandedLevels};
+use super::file_lock;
+use super::slippage::{PriceScale, SlippageSpec, SlippageUnit};
+use super::symbol_catalog::SymbolCatalog;
+use super::{CopierConfig, CopierError, SltpPolicy};
+
+/// Receiver SL/TP levels this close to the master's count as matching
+pub const SL_TP_TOLERANCE: SlippageSpec = SlippageSpec {
+    value: 1.0,
+    unit: SlippageUnit::Pips,
+};
+
+/// Seconds after a `modify_sl_tp` during which the same position needs a
+/// larger mismatch before it's flagged again
+pub const DEFAULT_SLTP_COOLDOWN_SECS: u64 = 30;
+
+/// Mismatch a position needs while in its cooldown
+pub const DEFAULT_SLTP_COOLDOWN_TOLERANCE: SlippageSpec = SlippageSpec {
+    value: 5.0,
+    unit: SlippageUnit::Pips,
+};
+
+/// How closely one receiver's SL/TP levels must follow the master's.
+/// Without the cooldown a master level that jiggles by a point right at the
+/// tolerance would be flagged, and synced, on every pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlTpSync {
+    /// Levels this close to the master's count as matching
+    pub tolerance: SlippageSpec,
+    /// How long after a `modify_sl_tp` `cooldown_tolerance` applies instead
+    pub cooldown: Duration,
+    pub cooldown_tolerance: SlippageSpec,
+}
+
+impl Default for SlTpSync {
+    fn default() -> Self {
+        Self {
+            tolerance: SL_TP_TOLERANCE,
+            cooldown: Duration::from_secs(DEFAULT_SLTP_COOLDOWN_SECS),
+            cooldown_tolerance: DEFAULT_SLTP_COOLDOWN_TOLERANCE,
+        }
+    }
+}
+
+/// When a `modify_sl_tp` was last sent, by receiver terminal id and
+/// receiver position id
+static SLTP_MODIFIES: LazyLock<Mutex<HashMap<String, HashMap<i64, Instant>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Start a receiver position's SL/TP cooldown
+pub fn record_sltp_modify(terminal_id: &str, position_id: i64) {
+    SLTP_MODIFIES
+        .lock()
+        .entry(terminal_id.to_string())
+        .or_default()
+        .insert(position_id, Instant::now());
+}
+
+/// Receiver positions sent a `modify_sl_tp` within `cooldown`
+pub fn recently_modified(terminal_id: &str, cooldown: Duration) -> HashSet<i64> {
+    SLTP_MODIFIES
+        .lock()
+        .get(terminal_id)
+        .map(|positions| {
+            positions
+                .iter()
+                .filter(|(_, sent_at)| sent_at.elapsed() < cooldown)
+                .map(|(position_id, _)| *position_id)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Forget a receiver's `modify_sl_tp`s older than `cooldown`
+pub fn prune_sltp_modifies(terminal_id: &str, cooldown: Duration) {
+    if let Some(positions) = SLTP_MODIFIES.lock().get_mut(terminal_id) {
+        positions.retain(|_, sent_at| sent_at.elapsed() < cooldown);
+    }
+}
+
 /// Open position from master
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterPosition {
@@ -42,15 +126,20 @@ pub struct PositionSyncStatus {
 /// Receiver position info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReceiverPosition {
+    #[serde(alias = "receiver_position_id")]
     pub position_id: i64,
     pub master_position_id: i64,
     pub symbol: String,
     pub direction: String,
+    #[serde(alias = "lots")]
     pub volume: f64,
     #[serde(default)]
     pub sl: Option<f64>,
     #[serde(default)]
     pub tp: Option<f64>,
+    /// Magic number the position carries (None = not reported)
+    #[serde(default)]
+    pub magic: Option<i64>,
 }
 
 /// Discrepancy between master and receiver
@@ -71,10 +160,12 @@ pub enum DiscrepancyType {
     DirectionMismatch,   // Position exists on both but directions don't match
     SLMismatch,          // Stop loss doesn't match (outside tolerance)
     TPMismatch,          // Take profit doesn't match (outside tolerance)
+    SLUserModified,      // Receiver SL moved away from what the copier last set
+    TPUserModified,      // Receiver TP moved away from what the copier last set
 }
 
 /// Read open positions from master's queue folder
-pub fn read_master_positions(terminal_id: &str) -> Result<Vec<MasterPosition>, String> {
+pub fn read_master_positions(terminal_id: &str) -> Result<Vec<MasterPosition>, CopierError> {
     // Try to find terminal path using MT5 bridge for portable support
     let positions_file = find_terminal_files_path(terminal_id)?
         .join("CopierQueue")
@@ -85,17 +176,18 @@ pub fn read_master_positions(terminal_id: &str) -> Result<Vec<MasterPosition>, S
         return Ok(vec![]);
     }
     
-    let content = fs::read_to_string(&positions_file)
-        .map_err(|e| format!("Failed to read positions file: {}", e))?;
+    let content = file_lock::read_contended(&positions_file)
+        .map_err(|e| CopierError::io("Failed to read positions file", e))?;
     
     let file: OpenPositionsFile = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse positions file: {}", e))?;
+        .map_err(|e| CopierError::parse("Failed to parse positions file", e))?;
     
     Ok(file.positions)
 }
 
-/// Read receiver position mappings from copier-positions.json
-pub fn read_receiver_positions(terminal_id: &str) -> Result<Vec<ReceiverPosition>, String> {
+/// Read receiver position mappings from copier-positions.json, keeping
+/// only the copier's own positions (see `parse_receiver_positions`)
+pub fn read_receiver_positions(terminal_id: &str, magic: Option<i64>) -> Result<Vec<ReceiverPosition>, CopierError> {
     let positions_file = find_terminal_files_path(terminal_id)?
         .join("copier-positions.json");
     
@@ -104,24 +196,49 @@ pub fn read_receiver_positions(terminal_id: &str) -> Result<Vec<ReceiverPosition
         return Ok(vec![]);
     }
     
-    let content = fs::read_to_string(&positions_file)
-        .map_err(|e| format!("Failed to read receiver positions: {}", e))?;
+    let content = file_lock::read_contended(&positions_file)
+        .map_err(|e| CopierError::io("Failed to read receiver positions", e))?;
     
+    Ok(parse_receiver_positions(&content, magic))
+}
+
+/// Parse copier-positions.json and drop positions that don't carry the
+/// copier's magic number, so reconciliation never treats a manual trade as
+/// an orphaned copy. The magic is `magic` when the receiver configures one,
+/// else the `magic_number` the EA writes at the top of the file. Entries
+/// with no magic at all (older EAs) are kept, since there's no telling.
+pub fn parse_receiver_positions(content: &str, magic: Option<i64>) -> Vec<ReceiverPosition> {
+    let (positions, file_magic) = parse_position_entries(content);
+    let Some(magic) = magic.or(file_magic) else {
+        return positions;
+    };
+    positions
+        .into_iter()
+        .filter(|p| match p.magic.or(file_magic) {
+            Some(m) => m == magic,
+            None => true,
+        })
+        .collect()
+}
+
+fn parse_position_entries(content: &str) -> (Vec<ReceiverPosition>, Option<i64>) {
     // Try JSON format first (preferred format from EA)
-    if let Ok(positions) = serde_json::from_str::<Vec<ReceiverPosition>>(&content) {
-        return Ok(positions);
+    if let Ok(positions) = serde_json::from_str::<Vec<ReceiverPosition>>(content) {
+        return (positions, None);
     }
     
     // Try JSON with wrapper object (EA might write {"positions": [...]})
     #[derive(Deserialize)]
     struct PositionsWrapper {
         positions: Vec<ReceiverPosition>,
+        #[serde(default)]
+        magic_number: Option<i64>,
     }
-    if let Ok(wrapper) = serde_json::from_str::<PositionsWrapper>(&content) {
-        return Ok(wrapper.positions);
+    if let Ok(wrapper) = serde_json::from_str::<PositionsWrapper>(content) {
+        return (wrapper.positions, wrapper.magic_number);
     }
     
-    // Fallback to pipe-delimited format: master_pos_id|receiver_pos_id|symbol|direction|lots|sl|tp
+    // Fallback to pipe-delimited format: master_pos_id|receiver_pos_id|symbol|direction|lots|sl|tp|magic
     let mut positions = vec![];
     for line in content.lines() {
         let line = line.trim();
@@ -138,145 +255,203 @@ pub fn read_receiver_positions(terminal_id: &str) -> Result<Vec<ReceiverPosition
                 volume: parts[4].parse().unwrap_or(0.0),
                 sl: parts.get(5).and_then(|s| s.parse().ok()),
                 tp: parts.get(6).and_then(|s| s.parse().ok()),
+                magic: parts.get(7).and_then(|s| s.parse().ok()),
             });
         }
     }
     
-    Ok(positions)
+    (positions, None)
+}
+
+/// Suggested action when a receiver SL/TP level should change under
+/// `policy`, or None if it matches (within `tolerance`) or is left alone
+fn level_mismatch(
+    label: &str,
+    master_level: f64,
+    receiver_level: Option<f64>,
+    tolerance: f64,
+    policy: SltpPolicy,
+) -> Option<String> {
+    let receiver_level = receiver_level.filter(|l| *l > 0.0);
+    match (policy.apply(Some(master_level)), receiver_level) {
+        // Policy leaves the receiver's level alone
+        (None, _) => None,
+        (Some(target), None) if target > 0.0 => Some(format!("Set receiver {} to {}", label, target)),
+        (Some(target), Some(current)) if target > 0.0 => ((target - current).abs() > tolerance)
+            .then(|| format!("Update receiver {} from {} to {}", label, current, target)),
+        (Some(_), Some(current)) => Some(format!("Clear receiver {} {} (master has none)", label, current)),
+        (Some(_), None) => None,
+    }
+}
+
+/// How `find_discrepancies` judges one receiver's positions
+#[derive(Debug, Clone)]
+pub struct DiscrepancyCheck<'a> {
+    pub receiver_id: &'a str,
+    /// The receiver's policy, the same one the execution path applies
+    pub sltp_policy: SltpPolicy,
+    pub sltp_sync: SlTpSync,
+    /// Receiver positions in their SL/TP cooldown
+    pub recently_modified: HashSet<i64>,
+    /// The receiver's symbol catalog
+    pub catalog: Option<&'a SymbolCatalog>,
+    /// Levels the copier last set, by master position id
+    pub commanded: HashMap<i64, CommandedLevels>,
+    pub respect_manual_sltp: bool,
+}
+
+impl<'a> DiscrepancyCheck<'a> {
+    /// Default tolerances, no cooldowns, catalog or commanded levels
+    pub fn new(receiver_id: &'a str, sltp_policy: SltpPolicy) -> Self {
+        Self {
+            receiver_id,
+            sltp_policy,
+            sltp_sync: SlTpSync::default(),
+            recently_modified: HashSet::new(),
+            catalog: None,
+            commanded: HashMap::new(),
+            respect_manual_sltp: false,
+        }
+    }
 }
 
-/// Find discrepancies between master and receiver positions
+/// Find discrepancies between master and receiver positions. SL/TP
+/// mismatches are judged by the receiver's `sltp_policy` within
+/// `sltp_sync.tolerance` on the receiver symbol. Positions in
+/// `recently_modified` are in their cooldown and need a mismatch beyond
+/// `sltp_sync.cooldown_tolerance`.
+///
+/// A receiver level that differs from the one the copier last set
+/// (`commanded`) was moved by hand and is flagged as user-modified. With
+/// `respect_manual_sltp` it's then left out of the mismatch check, so
+/// syncing doesn't undo the user's change.
 pub fn find_discrepancies(
     master_positions: &[MasterPosition],
     receiver_positions: &[ReceiverPosition],
-    receiver_id: &str,
+    check: &DiscrepancyCheck,
 ) -> Vec<PositionDiscrepancy> {
+    let DiscrepancyCheck {
+        receiver_id,
+        sltp_policy,
+        ref sltp_sync,
+        ref recently_modified,
+        catalog,
+        ref commanded,
+        respect_manual_sltp,
+    } = *check;
     let mut discrepancies = vec![];
     
     // Check for positions on master that are missing on receiver
     for master_pos in master_positions {
-        // Calculate dynamic tolerance based on price (handles indices, forex, etc.)
-        let sl_tp_tolerance = get_sl_tp_tolerance(master_pos);
+        // A partially filled entry and its remainder both follow the master position
+        let mapped: Vec<&ReceiverPosition> = receiver_positions.iter()
+            .filter(|r| r.master_position_id == master_pos.position_id)
+            .collect();
         
-        let receiver_pos = receiver_positions.iter()
-            .find(|r| r.master_position_id == master_pos.position_id);
+        if mapped.is_empty() {
+            // m3 fix: Add note to verify symbol availability on receiver terminal
+            discrepancies.push(PositionDiscrepancy {
+                discrepancy_type: DiscrepancyType::MissingOnReceiver,
+                master_position: Some(master_pos.clone()),
+                receiver_id: receiver_id.to_string(),
+                receiver_position: None,
+                suggested_action: format!(
+                    "Open {} {} {} lots on receiver (verify symbol availability)",
+                    master_pos.symbol, master_pos.direction, master_pos.volume
+                ),
+            });
+            continue;
+        }
         
-        match receiver_pos {
-            None => {
-                // m3 fix: Add note to verify symbol availability on receiver terminal
+        for recv in mapped {
+            // U-10: VolumeMismatch removed for non-mirror risk modes.
+            // Receiver volume is derived from its own risk_mode/SL via
+            // `lot_calculator`; comparing it to master_volume produced
+            // false positives on every non-mirror receiver. We still flag
+            // pathological cases (>100x divergence or zero/negative volume)
+            // because those indicate a real sizing failure.
+            let zero_recv = recv.volume <= 0.0;
+            let huge_divergence = master_pos.volume > 0.0
+                && (recv.volume / master_pos.volume > 100.0
+                    || master_pos.volume / recv.volume.max(1e-9) > 100.0);
+            if zero_recv || huge_divergence {
                 discrepancies.push(PositionDiscrepancy {
-                    discrepancy_type: DiscrepancyType::MissingOnReceiver,
+                    discrepancy_type: DiscrepancyType::VolumeMismatch,
                     master_position: Some(master_pos.clone()),
                     receiver_id: receiver_id.to_string(),
-                    receiver_position: None,
+                    receiver_position: Some(recv.clone()),
                     suggested_action: format!(
-                        "Open {} {} {} lots on receiver (verify symbol availability)",
-                        master_pos.symbol, master_pos.direction, master_pos.volume
+                        "Receiver volume {} looks invalid for master {} — review sizing config",
+                        recv.volume, master_pos.volume
                     ),
                 });
             }
-            Some(recv) => {
-                // U-10: VolumeMismatch removed for non-mirror risk modes.
-                // Receiver volume is derived from its own risk_mode/SL via
-                // `lot_calculator`; comparing it to master_volume produced
-                // false positives on every non-mirror receiver. We still flag
-                // pathological cases (>100x divergence or zero/negative volume)
-                // because those indicate a real sizing failure.
-                let zero_recv = recv.volume <= 0.0;
-                let huge_divergence = master_pos.volume > 0.0
-                    && (recv.volume / master_pos.volume > 100.0
-                        || master_pos.volume / recv.volume.max(1e-9) > 100.0);
-                if zero_recv || huge_divergence {
+            
+            // Check for direction mismatch
+            if master_pos.direction != recv.direction {
+                discrepancies.push(PositionDiscrepancy {
+                    discrepancy_type: DiscrepancyType::DirectionMismatch,
+                    master_position: Some(master_pos.clone()),
+                    receiver_id: receiver_id.to_string(),
+                    receiver_position: Some(recv.clone()),
+                    suggested_action: "Close receiver position and re-open with correct direction".to_string(),
+                });
+            }
+            
+            // Check for SL/TP mismatch
+            let sl_tp_tolerance = get_sl_tp_tolerance(sltp_sync.tolerance, master_pos, &recv.symbol, catalog);
+            let sync_tolerance = if recently_modified.contains(&recv.position_id) {
+                get_sl_tp_tolerance(sltp_sync.cooldown_tolerance, master_pos, &recv.symbol, catalog)
+                    .max(sl_tp_tolerance)
+            } else {
+                sl_tp_tolerance
+            };
+            let set_by_copier = commanded.get(&master_pos.position_id).copied().unwrap_or_default();
+            let levels = [
+                (DiscrepancyType::SLMismatch, DiscrepancyType::SLUserModified, "SL", master_pos.sl, recv.sl, set_by_copier.sl),
+                (DiscrepancyType::TPMismatch, DiscrepancyType::TPUserModified, "TP", master_pos.tp, recv.tp, set_by_copier.tp),
+            ];
+            for (discrepancy_type, user_modified_type, label, master_level, recv_level, commanded_level) in levels {
+                if commanded_levels::moved_by_hand(commanded_level, recv_level, sl_tp_tolerance) {
+                    let moved = format!(
+                        "Receiver {} moved by hand to {} (copier set {})",
+                        label,
+                        recv_level.unwrap_or(0.0),
+                        commanded_level.unwrap_or(0.0)
+                    );
                     discrepancies.push(PositionDiscrepancy {
-                        discrepancy_type: DiscrepancyType::VolumeMismatch,
+                        discrepancy_type: user_modified_type,
                         master_position: Some(master_pos.clone()),
                         receiver_id: receiver_id.to_string(),
                         receiver_position: Some(recv.clone()),
-                        suggested_action: format!(
-                            "Receiver volume {} looks invalid for master {} — review sizing config",
-                            recv.volume, master_pos.volume
-                        ),
+                        suggested_action: if respect_manual_sltp {
+                            format!("{}; left as set", moved)
+                        } else {
+                            format!("{}; syncing will overwrite it", moved)
+                        },
                     });
+                    if respect_manual_sltp {
+                        continue;
+                    }
                 }
-                
-                // Check for direction mismatch
-                if master_pos.direction != recv.direction {
+                if let Some(suggested_action) =
+                    level_mismatch(label, master_level, recv_level, sync_tolerance, sltp_policy)
+                {
                     discrepancies.push(PositionDiscrepancy {
-                        discrepancy_type: DiscrepancyType::DirectionMismatch,
+                        discrepancy_type,
                         master_position: Some(master_pos.clone()),
                         receiver_id: receiver_id.to_string(),
                         receiver_position: Some(recv.clone()),
-                        suggested_action: "Close receiver position and re-open with correct direction".to_string(),
+                        suggested_action,
                     });
                 }
-                
-                // Check for SL mismatch
-                if master_pos.sl > 0.0 {
-                    if let Some(recv_sl) = recv.sl {
-                        let sl_diff = (master_pos.sl - recv_sl).abs();
-                        if sl_diff > sl_tp_tolerance {
-                            discrepancies.push(PositionDiscrepancy {
-                                discrepancy_type: DiscrepancyType::SLMismatch,
-                                master_position: Some(master_pos.clone()),
-                                receiver_id: receiver_id.to_string(),
-                                receiver_position: Some(recv.clone()),
-                                suggested_action: format!(
-                                    "Update receiver SL from {} to {}",
-                                    recv_sl, master_pos.sl
-                                ),
-                            });
-                        }
-                    } else {
-                        // Master has SL but receiver doesn't
-                        discrepancies.push(PositionDiscrepancy {
-                            discrepancy_type: DiscrepancyType::SLMismatch,
-                            master_position: Some(master_pos.clone()),
-                            receiver_id: receiver_id.to_string(),
-                            receiver_position: Some(recv.clone()),
-                            suggested_action: format!(
-                                "Set receiver SL to {}",
-                                master_pos.sl
-                            ),
-                        });
-                    }
-                }
-                
-                // Check for TP mismatch
-                if master_pos.tp > 0.0 {
-                    if let Some(recv_tp) = recv.tp {
-                        let tp_diff = (master_pos.tp - recv_tp).abs();
-                        if tp_diff > sl_tp_tolerance {
-                            discrepancies.push(PositionDiscrepancy {
-                                discrepancy_type: DiscrepancyType::TPMismatch,
-                                master_position: Some(master_pos.clone()),
-                                receiver_id: receiver_id.to_string(),
-                                receiver_position: Some(recv.clone()),
-                                suggested_action: format!(
-                                    "Update receiver TP from {} to {}",
-                                    recv_tp, master_pos.tp
-                                ),
-                            });
-                        }
-                    } else {
-                        // Master has TP but receiver doesn't
-                        discrepancies.push(PositionDiscrepancy {
-                            discrepancy_type: DiscrepancyType::TPMismatch,
-                            master_position: Some(master_pos.clone()),
-                            receiver_id: receiver_id.to_string(),
-                            receiver_position: Some(recv.clone()),
-                            suggested_action: format!(
-                                "Set receiver TP to {}",
-                                master_pos.tp
-                            ),
-                        });
-                    }
-                }
             }
         }
     }
     
     // Check for orphaned positions on receiver
-    for recv_pos in receiver_positions {
+    // Aggregate net positions follow no single master position
+    for recv_pos in receiver_positions.iter().filter(|p| !super::aggregate::is_aggregate_position(p.master_position_id)) {
         let master_exists = master_positions.iter()
             .any(|m| m.position_id == recv_pos.master_position_id);
         
@@ -297,9 +472,26 @@ pub fn find_discrepancies(
     discrepancies
 }
 
-/// Calculate dynamic SL/TP tolerance based on price level
-/// Returns a tolerance that is 0.1% of the price or 1 pip minimum
-fn get_sl_tp_tolerance(master_pos: &MasterPosition) -> f64 {
+/// SL/TP tolerance as a price distance: `tolerance` on the receiver
+/// symbol, or on a price-based pip estimate when the catalog doesn't list it
+fn get_sl_tp_tolerance(
+    tolerance: SlippageSpec,
+    master_pos: &MasterPosition,
+    receiver_symbol: &str,
+    catalog: Option<&SymbolCatalog>,
+) -> f64 {
+    match catalog.and_then(|c| c.symbols.iter().find(|s| s.name == receiver_symbol)) {
+        Some(spec) => tolerance.to_price(PriceScale::from_spec(spec)),
+        None => match tolerance.unit {
+            SlippageUnit::Pips => tolerance.value * price_based_tolerance(master_pos),
+            SlippageUnit::Points => tolerance.value * price_based_tolerance(master_pos) / 10.0,
+            SlippageUnit::Price => tolerance.value,
+        },
+    }
+}
+
+/// Fallback tolerance: 0.1% of the price or 1 pip minimum
+fn price_based_tolerance(master_pos: &MasterPosition) -> f64 {
     let price = master_pos.open_price;
     
     // 0.1% of the price as tolerance (handles indices with prices like 35000)
@@ -315,19 +507,93 @@ fn get_sl_tp_tolerance(master_pos: &MasterPosition) -> f64 {
     percentage_tolerance.max(min_tolerance)
 }
 
-/// Generate a sync report for all receivers
+/// Per-receiver SL/TP policies (by terminal id) from the active config
+pub fn sltp_policies(config: Option<&CopierConfig>) -> HashMap<String, SltpPolicy> {
+    config
+        .map(|c| {
+            c.receivers
+                .iter()
+                .map(|r| (r.terminal_id.clone(), r.sltp_policy))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Per-receiver SL/TP tolerances and cooldowns (by terminal id) from the
+/// active config
+pub fn sltp_sync_settings(config: Option<&CopierConfig>) -> HashMap<String, SlTpSync> {
+    config
+        .map(|c| {
+            c.receivers
+                .iter()
+                .map(|r| (r.terminal_id.clone(), r.sltp_sync()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Receivers (by terminal id) whose hand-moved SL/TP levels reconciliation
+/// leaves alone, from the active config
+pub fn manual_sltp_receivers(config: Option<&CopierConfig>) -> HashSet<String> {
+    config
+        .map(|c| {
+            c.receivers
+                .iter()
+                .filter(|r| r.respect_manual_sltp)
+                .map(|r| r.terminal_id.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Per-receiver copier magic numbers (by terminal id) from the active config;
+/// receivers without one use the magic their EA reports
+pub fn magic_numbers(config: Option<&CopierConfig>) -> HashMap<String, i64> {
+    config
+        .map(|c| {
+            c.receivers
+                .iter()
+                .filter_map(|r| Some((r.terminal_id.clone(), r.magic_number?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Generate a sync report for all receivers. Receivers missing from
+/// `sltp_policies` or `sltp_sync` are checked with the defaults, and those
+/// missing from `magic_numbers` by the magic their EA reports. Receivers in
+/// `respect_manual_sltp` keep SL/TP levels moved by hand.
 pub fn generate_sync_report(
     master_terminal_id: &str,
     receiver_terminal_ids: &[String],
-) -> Result<PositionSyncStatus, String> {
+    sltp_policies: &HashMap<String, SltpPolicy>,
+    sltp_sync: &HashMap<String, SlTpSync>,
+    magic_numbers: &HashMap<String, i64>,
+    respect_manual_sltp: &HashSet<String>,
+) -> Result<PositionSyncStatus, CopierError> {
     let master_positions = read_master_positions(master_terminal_id)?;
     
     let mut receiver_positions: HashMap<String, Vec<ReceiverPosition>> = HashMap::new();
     let mut all_discrepancies: Vec<PositionDiscrepancy> = vec![];
     
     for receiver_id in receiver_terminal_ids {
-        let recv_positions = read_receiver_positions(receiver_id)?;
-        let discrepancies = find_discrepancies(&master_positions, &recv_positions, receiver_id);
+        let recv_positions = read_receiver_positions(receiver_id, magic_numbers.get(receiver_id).copied())?;
+        let policy = sltp_policies.get(receiver_id).copied().unwrap_or_default();
+        let sync = sltp_sync.get(receiver_id).copied().unwrap_or_default();
+        let catalog = super::symbol_catalog::fetch_symbol_catalog(receiver_id).ok();
+        prune_sltp_modifies(receiver_id, sync.cooldown);
+        let discrepancies = find_discrepancies(
+            &master_positions,
+            &recv_positions,
+            &DiscrepancyCheck {
+                sltp_sync: sync,
+                recently_modified: recently_modified(receiver_id, sync.cooldown),
+                catalog: catalog.as_ref(),
+                commanded: commanded_levels::for_receiver(receiver_id),
+                respect_manual_sltp: respect_manual_sltp.contains(receiver_id),
+                ..DiscrepancyCheck::new(receiver_id, policy)
+            },
+        );
         
         receiver_positions.insert(receiver_id.clone(), recv_positions);
         all_discrepancies.extend(discrepancies);
@@ -340,43 +606,55 @@ pub fn generate_sync_report(
     })
 }
 
+/// Disambiguates sync command files written within the same millisecond
+static SYNC_COMMAND_SEQ: AtomicU64 = AtomicU64::new(0);
+
 /// Write a sync command file for a receiver to execute (atomic write - M4 fix)
 pub fn write_sync_command(
     receiver_terminal_id: &str,
     command: &SyncCommand,
-) -> Result<(), String> {
-    let commands_folder = find_terminal_files_path(receiver_terminal_id)?
-        .join("CopierCommands");
+) -> Result<(), CopierError> {
+    let files_path = find_terminal_files_path(receiver_terminal_id)?;
+    crate::mt5::discovery::ensure_files_writable(&files_path)?;
+    let commands_folder = files_path.join("CopierCommands");
     
     fs::create_dir_all(&commands_folder)
-        .map_err(|e| format!("Failed to create commands folder: {}", e))?;
+        .map_err(|e| CopierError::io("Failed to create commands folder", e))?;
     
-    let filename = format!("sync_{}.json", chrono::Utc::now().timestamp_millis());
+    let filename = format!(
+        "sync_{}_{}.json",
+        chrono::Utc::now().timestamp_millis(),
+        SYNC_COMMAND_SEQ.fetch_add(1, Ordering::Relaxed)
+    );
     let command_file = commands_folder.join(&filename);
     let temp_file = commands_folder.join(format!("{}.tmp", filename));
     
     let json = serde_json::to_string_pretty(command)
-        .map_err(|e| format!("Failed to serialize command: {}", e))?;
+        .map_err(|e| CopierError::parse("Failed to serialize command", e))?;
     
     // Atomic write: write to temp file first, then rename
-    fs::write(&temp_file, &json)
-        .map_err(|e| format!("Failed to write temp command file: {}", e))?;
-    fs::rename(&temp_file, &command_file)
-        .map_err(|e| format!("Failed to finalize command file: {}", e))?;
+    file_lock::with_terminal_lock(receiver_terminal_id, || {
+        fs::write(&temp_file, &json).map_err(|e| CopierError::io("Failed to write temp command file", e))?;
+        fs::rename(&temp_file, &command_file).map_err(|e| CopierError::io("Failed to finalize command file", e))
+    })?;
+
+    if let ("modify_sl_tp", Some(position_id)) = (command.command_type.as_str(), command.position_id) {
+        record_sltp_modify(receiver_terminal_id, position_id);
+    }
     
     Ok(())
 }
 
 /// Find the MQL5/Files path for a terminal.
 /// Delegates to the single source of truth in `mt5::bridge`.
-fn find_terminal_files_path(terminal_id: &str) -> Result<PathBuf, String> {
-    crate::mt5::bridge::resolve_files_path(terminal_id, false)
+fn find_terminal_files_path(terminal_id: &str) -> Result<PathBuf, CopierError> {
+    crate::mt5::bridge::resolve_files_path(terminal_id, false).map_err(CopierError::NotFound)
 }
 
 /// Sync command for receiver EA
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncCommand {
-    pub command_type: String, // "open", "close", "close_all", "modify"
+    pub command_type: String, // "open", "close", "partial_close", "close_all", "modify", "place_pending", "cancel_pending"
     pub position_id: Option<i64>,
     pub master_position_id: Option<i64>,
     pub symbol: Option<String>,
@@ -384,6 +662,12 @@ pub struct SyncCommand {
     pub volume: Option<f64>,
     pub sl: Option<f64>,
     pub tp: Option<f64>,
+    /// Pending order type ("buy_limit", "sell_limit", "buy_stop", "sell_stop")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_type: Option<String>,
+    /// Pending order trigger price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
     pub timestamp: String,
 }
 
@@ -399,6 +683,8 @@ impl SyncCommand {
             volume: None,
             sl: None,
             tp: None,
+            order_type: None,
+            price: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -413,6 +699,8 @@ impl SyncCommand {
             volume: Some(master_pos.volume),
             sl: Some(master_pos.sl),
             tp: Some(master_pos.tp),
+            order_type: None,
+            price: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -427,10 +715,28 @@ impl SyncCommand {
             volume: None,
             sl: None,
             tp: None,
+            order_type: None,
+            price: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
     
+    pub fn partial_close(receiver_position_id: i64, master_position_id: i64, volume: f64) -> Self {
+        Self {
+            command_type: "partial_close".to_string(),
+            position_id: Some(receiver_position_id),
+            master_position_id: Some(master_position_id),
+            symbol: None,
+            direction: None,
+            volume: Some(volume),
+            sl: None,
+            tp: None,
+            order_type: None,
+            price: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
     pub fn modify_sl_tp(receiver_position_id: i64, sl: Option<f64>, tp: Option<f64>) -> Self {
         Self {
             command_type: "modify_sl_tp".to_string(),
@@ -441,7 +747,362 @@ impl SyncCommand {
             volume: None,
             sl,
             tp,
+            order_type: None,
+            price: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Mirror a master pending order. The master order ticket goes in
+    /// `master_position_id`: MT5 gives the position a filled order opens the
+    /// order's ticket as its identifier, so the fill maps back to the master
+    /// position without extra bookkeeping.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_pending(
+        master_order_ticket: i64,
+        symbol: &str,
+        order_type: &str,
+        direction: &str,
+        volume: f64,
+        price: f64,
+        sl: Option<f64>,
+        tp: Option<f64>,
+    ) -> Self {
+        Self {
+            command_type: "place_pending".to_string(),
+            position_id: None,
+            master_position_id: Some(master_order_ticket),
+            symbol: Some(symbol.to_string()),
+            direction: Some(direction.to_string()),
+            volume: Some(volume),
+            sl,
+            tp,
+            order_type: Some(order_type.to_string()),
+            price: Some(price),
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
+
+    /// Remove the receiver's mirror of a master pending order
+    pub fn cancel_pending(master_order_ticket: i64) -> Self {
+        Self {
+            command_type: "cancel_pending".to_string(),
+            position_id: None,
+            master_position_id: Some(master_order_ticket),
+            symbol: None,
+            direction: None,
+            volume: None,
+            sl: None,
+            tp: None,
+            order_type: None,
+            price: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Master position with a TP but no SL
+    fn master_tp_only() -> MasterPosition {
+        MasterPosition {
+            position_id: 100,
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            volume: 1.0,
+            open_price: 1.1,
+            sl: 0.0,
+            tp: 1.12,
+            sl_distance_points: None,
+            tp_distance_points: None,
+        }
+    }
+
+    /// Receiver copy that has its own SL and no TP yet
+    fn receiver_with_sl() -> ReceiverPosition {
+        ReceiverPosition {
+            position_id: 555,
+            master_position_id: 100,
+            symbol: "EURUSD".to_string(),
+            direction: "buy".to_string(),
+            volume: 1.0,
+            sl: Some(1.09),
+            tp: None,
+            magic: None,
+        }
+    }
+
+    fn mismatches(policy: SltpPolicy) -> Vec<DiscrepancyType> {
+        find_discrepancies(
+            &[master_tp_only()],
+            &[receiver_with_sl()],
+            &DiscrepancyCheck::new("R1", policy),
+        )
+        .into_iter()
+            .map(|d| d.discrepancy_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_jpy_sl_tolerance_is_one_pip_with_catalog() {
+        let master = MasterPosition {
+            symbol: "USDJPY".to_string(),
+            open_price: 150.0,
+            sl: 149.5,
+            tp: 0.0,
+            ..master_tp_only()
+        };
+        // 5 pips off the master's SL
+        let receiver = ReceiverPosition {
+            symbol: "USDJPY".to_string(),
+            sl: Some(149.55),
+            ..receiver_with_sl()
+        };
+        let catalog = SymbolCatalog {
+            terminal_id: "R1".to_string(),
+            symbols: vec![crate::copier::symbol_catalog::SymbolSpec {
+                name: "USDJPY".to_string(),
+                normalized_key: "USDJPY".to_string(),
+                tick_value: 0.67,
+                tick_size: 0.001,
+                contract_size: 100000.0,
+                digits: 3,
+                min_lot: 0.01,
+                lot_step: 0.01,
+                max_lot: 100.0,
+                description: None,
+                trade_mode: None,
+                profit_currency: None,
+            }],
+            fetched_at: String::new(),
+            broker_suffix: None,
+        };
+        let sl_mismatch = |catalog: Option<&SymbolCatalog>| {
+            find_discrepancies(
+                std::slice::from_ref(&master),
+                std::slice::from_ref(&receiver),
+                &DiscrepancyCheck {
+                    catalog,
+                    ..DiscrepancyCheck::new("R1", SltpPolicy::Copy)
+                },
+            )
+                .iter()
+                .any(|d| d.discrepancy_type == DiscrepancyType::SLMismatch)
+        };
+
+        assert!(sl_mismatch(Some(&catalog)));
+        // The price-based fallback allows 0.15 on a 150 price
+        assert!(!sl_mismatch(None));
+    }
+
+    /// Whether a receiver SL of `receiver_sl` against a master SL of 1.09 on
+    /// 5-digit EURUSD is flagged for syncing
+    fn sl_flagged(receiver_sl: f64, sync: &SlTpSync, recently_modified: &HashSet<i64>) -> bool {
+        let master = MasterPosition {
+            sl: 1.09,
+            tp: 0.0,
+            ..master_tp_only()
+        };
+        let receiver = ReceiverPosition {
+            sl: Some(receiver_sl),
+            ..receiver_with_sl()
+        };
+        let catalog = SymbolCatalog {
+            terminal_id: "R1".to_string(),
+            symbols: vec![crate::copier::symbol_catalog::SymbolSpec {
+                name: "EURUSD".to_string(),
+                normalized_key: "EURUSD".to_string(),
+                tick_value: 1.0,
+                tick_size: 0.00001,
+                contract_size: 100000.0,
+                digits: 5,
+                min_lot: 0.01,
+                lot_step: 0.01,
+                max_lot: 100.0,
+                description: None,
+                trade_mode: None,
+                profit_currency: None,
+            }],
+            fetched_at: String::new(),
+            broker_suffix: None,
+        };
+        find_discrepancies(
+            &[master],
+            &[receiver],
+            &DiscrepancyCheck {
+                sltp_sync: *sync,
+                recently_modified: recently_modified.clone(),
+                catalog: Some(&catalog),
+                ..DiscrepancyCheck::new("R1", SltpPolicy::Copy)
+            },
+        )
+        .iter()
+        .any(|d| d.discrepancy_type == DiscrepancyType::SLMismatch)
+    }
+
+    #[test]
+    fn test_sub_tolerance_sl_change_is_not_synced() {
+        let defaults = SlTpSync::default();
+        let none = HashSet::new();
+        // Half a pip off vs. 3 pips off
+        assert!(!sl_flagged(1.09005, &defaults, &none));
+        assert!(sl_flagged(1.0903, &defaults, &none));
+
+        // A receiver configured with a 5 pip tolerance
+        let loose = SlTpSync {
+            tolerance: SlippageSpec::pips(5.0),
+            ..defaults
+        };
+        assert!(!sl_flagged(1.0903, &loose, &none));
+        assert!(sl_flagged(1.0910, &loose, &none));
+    }
+
+    #[test]
+    fn test_recently_modified_position_needs_a_larger_change() {
+        let sync = SlTpSync::default();
+        let modified = HashSet::from([555]);
+        // 3 pips is within the 5 pip cooldown tolerance; 10 pips isn't
+        assert!(!sl_flagged(1.0903, &sync, &modified));
+        assert!(sl_flagged(1.0910, &sync, &modified));
+
+        record_sltp_modify("cooldown-test", 555);
+        assert_eq!(recently_modified("cooldown-test", sync.cooldown), modified);
+        // The cooldown lapses; pruning then forgets the modify
+        assert!(recently_modified("cooldown-test", Duration::ZERO).is_empty());
+        assert_eq!(recently_modified("cooldown-test", sync.cooldown), modified);
+        prune_sltp_modifies("cooldown-test", Duration::ZERO);
+        assert!(recently_modified("cooldown-test", sync.cooldown).is_empty());
+    }
+
+    #[test]
+    fn test_copy_policy_mirrors_missing_sl() {
+        assert_eq!(SltpPolicy::Copy.apply(Some(0.0)), Some(0.0));
+        assert_eq!(SltpPolicy::Copy.apply(None), Some(0.0));
+        assert_eq!(SltpPolicy::Copy.apply(Some(1.12)), Some(1.12));
+
+        // Receiver SL must be cleared, TP set
+        assert_eq!(
+            mismatches(SltpPolicy::Copy),
+            vec![DiscrepancyType::SLMismatch, DiscrepancyType::TPMismatch]
+        );
+    }
+
+    #[test]
+    fn test_copy_if_set_policy_keeps_receiver_sl() {
+        assert_eq!(SltpPolicy::CopyIfSet.apply(Some(0.0)), None);
+        assert_eq!(SltpPolicy::CopyIfSet.apply(None), None);
+        assert_eq!(SltpPolicy::CopyIfSet.apply(Some(1.12)), Some(1.12));
+
+        assert_eq!(mismatches(SltpPolicy::CopyIfSet), vec![DiscrepancyType::TPMismatch]);
+    }
+
+    #[test]
+    fn test_ignore_policy_never_syncs_sltp() {
+        assert_eq!(SltpPolicy::Ignore.apply(Some(1.12)), None);
+        assert_eq!(SltpPolicy::Ignore.apply(Some(0.0)), None);
+
+        assert!(mismatches(SltpPolicy::Ignore).is_empty());
+    }
+
+    #[test]
+    fn test_manually_moved_sl_is_flagged_as_user_modified() {
+        let master = MasterPosition {
+            sl: 1.09,
+            ..master_tp_only()
+        };
+        // The copier set SL 1.09 / TP 1.12; the user has since moved the SL to 1.095
+        let receiver = ReceiverPosition {
+            sl: Some(1.095),
+            tp: Some(1.12),
+            ..receiver_with_sl()
+        };
+        let commanded = HashMap::from([(
+            100,
+            CommandedLevels {
+                sl: Some(1.09),
+                tp: Some(1.12),
+            },
+        )]);
+        let types = |respect_manual_sltp: bool| {
+            find_discrepancies(
+                std::slice::from_ref(&master),
+                std::slice::from_ref(&receiver),
+                &DiscrepancyCheck {
+                    commanded: commanded.clone(),
+                    respect_manual_sltp,
+                    ..DiscrepancyCheck::new("R1", SltpPolicy::Copy)
+                },
+            )
+            .into_iter()
+            .map(|d| d.discrepancy_type)
+            .collect::<Vec<_>>()
+        };
+
+        // Flagged either way; only synced back when manual changes aren't respected
+        assert_eq!(types(false), vec![DiscrepancyType::SLUserModified, DiscrepancyType::SLMismatch]);
+        assert_eq!(types(true), vec![DiscrepancyType::SLUserModified]);
+
+        // Without a record of what the copier set, it's a plain mismatch
+        let unknown = find_discrepancies(
+            &[master],
+            &[receiver],
+            &DiscrepancyCheck {
+                respect_manual_sltp: true,
+                ..DiscrepancyCheck::new("R1", SltpPolicy::Copy)
+            },
+        );
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].discrepancy_type, DiscrepancyType::SLMismatch);
+    }
+
+    #[test]
+    fn test_moved_by_hand_compares_with_commanded_level() {
+        assert!(!commanded_levels::moved_by_hand(Some(1.09), Some(1.0901), 0.0015));
+        assert!(commanded_levels::moved_by_hand(Some(1.09), Some(1.095), 0.0015));
+        // A level the copier cleared that now has a value, and the reverse
+        assert!(commanded_levels::moved_by_hand(Some(0.0), Some(1.08), 0.0015));
+        assert!(commanded_levels::moved_by_hand(Some(1.09), None, 0.0015));
+        assert!(!commanded_levels::moved_by_hand(None, Some(1.08), 0.0015));
+    }
+
+    /// As the receiver EA writes it: one copy, one manual trade (magic 0)
+    const EA_POSITIONS_FILE: &str = r#"{
+        "version": 2,
+        "receiver_id": "receiver_0",
+        "magic_number": 12345,
+        "updated_at": "2024-01-01T00:00:00Z",
+        "positions": [
+            {"master_position_id": 100, "receiver_position_id": 555, "symbol": "EURUSD", "direction": "buy", "lots": 1.00, "magic": 12345},
+            {"master_position_id": 0, "receiver_position_id": 777, "symbol": "GBPUSD", "direction": "sell", "lots": 0.50, "magic": 0}
+        ]
+    }"#;
+
+    #[test]
+    fn test_reconciliation_ignores_positions_without_copier_magic() {
+        let positions = parse_receiver_positions(EA_POSITIONS_FILE, None);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].position_id, 555);
+        assert_eq!(positions[0].volume, 1.0);
+
+        // The manual trade is neither reported as orphaned nor closed
+        let discrepancies = find_discrepancies(
+            &[master_tp_only()],
+            &positions,
+            &DiscrepancyCheck::new("R1", SltpPolicy::Ignore),
+        );
+        assert!(discrepancies.is_empty());
+    }
+
+    #[test]
+    fn test_configured_magic_overrides_file_magic() {
+        // Receiver configured with a different magic: neither position is the copier's
+        assert!(parse_receiver_positions(EA_POSITIONS_FILE, Some(999)).is_empty());
+
+        // Untagged entries from older EAs are kept
+        let legacy = "100|555|EURUSD|buy|1.0\n101|556|GBPUSD|sell|0.5|0|0|0\n";
+        let positions = parse_receiver_positions(legacy, Some(12345));
+        assert_eq!(positions.iter().map(|p| p.position_id).collect::<Vec<_>>(), vec![555]);
+    }
 }
@@ -0,0 +1,277 @@
+//! Per-receiver fill statistics
+//!
+//! Running totals of every attempted execution (status "success" or
+//! "error"; skipped ones don't count) per receiver account: fill rate,
+//! average and worst slippage, average latency. Totals are kept per UTC day
+//! for the last week plus an all-time total, so the UI can compare receiver
+//! brokers over today, the last 7 days or all time. Persisted so the
+//! numbers survive restarts.
+
+use chrono::{NaiveDate, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use crate::data_dir::app_data_dir;
+use super::{persistence, Execution};
+
+const STATS_FILE: &str = "receiver_stats.json";
+
+/// Days of daily totals kept, today included
+const DAILY_TOTALS_KEPT: i64 = 7;
+
+/// Period `get_receiver_stats` reports over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsWindow {
+    Today,
+    #[serde(rename = "7d")]
+    Last7Days,
+    #[default]
+    AllTime,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct FillTotals {
+    attempts: u64,
+    fills: u64,
+    slippage_sum: f64,
+    slippage_count: u64,
+    worst_slippage: Option<f64>,
+    latency_sum_ms: u64,
+    latency_count: u64,
+}
+
+impl FillTotals {
+    fn add(&mut self, execution: &Execution) {
+        self.attempts += 1;
+        if execution.status == "success" {
+            self.fills += 1;
+        }
+        if let Some(slippage) = execution.slippage_pips {
+            self.slippage_sum += slippage;
+            self.slippage_count += 1;
+            self.worst_slippage = Some(self.worst_slippage.map_or(slippage, |worst| worst.max(slippage)));
+        }
+        if let Some(latency) = execution.latency_ms {
+            self.latency_sum_ms += latency;
+            self.latency_count += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &FillTotals) {
+        self.attempts += other.attempts;
+        self.fills += other.fills;
+        self.slippage_sum += other.slippage_sum;
+        self.slippage_count += other.slippage_count;
+        self.worst_slippage = match (self.worst_slippage, other.worst_slippage) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.latency_sum_ms += other.latency_sum_ms;
+        self.latency_count += other.latency_count;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ReceiverTotals {
+    all_time: FillTotals,
+    daily: BTreeMap<NaiveDate, FillTotals>,
+}
+
+/// One receiver's fills over a window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiverStats {
+    pub receiver_account: String,
+    pub window: StatsWindow,
+    /// Executions attempted
+    pub executions: u64,
+    pub fills: u64,
+    /// fills / executions, 0.0-1.0
+    pub fill_rate: Option<f64>,
+    pub avg_slippage_pips: Option<f64>,
+    pub worst_slippage_pips: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// Fill totals for every receiver
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StatsBook {
+    receivers: HashMap<String, ReceiverTotals>,
+}
+
+fn average(sum: f64, count: u64) -> Option<f64> {
+    (count > 0).then(|| sum / count as f64)
+}
+
+impl StatsBook {
+    /// Count an execution on `day`. Executions that weren't attempted are ignored.
+    pub fn record(&mut self, execution: &Execution, day: NaiveDate) {
+        if execution.status != "success" && execution.status != "error" {
+            return;
+        }
+        let totals = self.receivers.entry(execution.receiver_account.clone()).or_default();
+        totals.all_time.add(execution);
+        totals.daily.entry(day).or_default().add(execution);
+        let oldest_kept = day - chrono::Duration::days(DAILY_TOTALS_KEPT - 1);
+        totals.daily.retain(|d, _| *d >= oldest_kept);
+    }
+
+    /// Stats per receiver over `window` ending `today`, by account
+    pub fn stats(&self, window: StatsWindow, today: NaiveDate) -> Vec<ReceiverStats> {
+        let first_day = match window {
+            StatsWindow::Today => Some(today),
+            StatsWindow::Last7Days => Some(today - chrono::Duration::days(DAILY_TOTALS_KEPT - 1)),
+            StatsWindow::AllTime => None,
+        };
+        let mut stats: Vec<ReceiverStats> = self
+            .receivers
+            .iter()
+            .map(|(account, totals)| {
+                let window_totals = match first_day {
+                    None => totals.all_time.clone(),
+                    Some(first_day) => {
+                        let mut sum = FillTotals::default();
+                        for day_totals in totals.daily.range(first_day..=today).map(|(_, t)| t) {
+                            sum.merge(day_totals);
+                        }
+                        sum
+                    }
+                };
+                ReceiverStats {
+                    receiver_account: account.clone(),
+                    window,
+                    executions: window_totals.attempts,
+                    fills: window_totals.fills,
+                    fill_rate: average(window_totals.fills as f64, window_totals.attempts),
+                    avg_slippage_pips: average(window_totals.slippage_sum, window_totals.slippage_count),
+                    worst_slippage_pips: window_totals.worst_slippage,
+                    avg_latency_ms: average(window_totals.latency_sum_ms as f64, window_totals.latency_count),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.receiver_account.cmp(&b.receiver_account));
+        stats
+    }
+}
+
+fn get_stats_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(STATS_FILE))
+}
+
+fn load() -> StatsBook {
+    get_stats_path()
+        .and_then(|path| {
+            persistence::load_state_file(&path, "Receiver statistics", |content| {
+                serde_json::from_str::<StatsBook>(content).map_err(|e| e.to_string())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Write the stats (atomic write)
+fn save(book: &StatsBook) -> Result<(), String> {
+    let Some(path) = get_stats_path() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string(book).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write receiver statistics: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save receiver statistics: {}", e))
+}
+
+static STATS: LazyLock<Mutex<StatsBook>> = LazyLock::new(|| Mutex::new(load()));
+
+/// Count a stored execution in its receiver's stats
+pub fn record(execution: &Execution) {
+    if execution.status != "success" && execution.status != "error" {
+        return;
+    }
+    let mut book = STATS.lock();
+    book.record(execution, Utc::now().date_naive());
+    if let Err(e) = save(&book) {
+        warn!("Failed to persist receiver statistics: {}", e);
+    }
+}
+
+/// Per-receiver fill stats over `window`
+pub fn receiver_stats(window: StatsWindow) -> Vec<ReceiverStats> {
+    STATS.lock().stats(window, Utc::now().date_naive())
+}
+
+/// Slippage an entry on this receiver can expect: its average over the last
+/// 7 days (None before any fill reported slippage)
+pub fn expected_slippage_pips(receiver_account: &str) -> Option<f64> {
+    receiver_stats(StatsWindow::Last7Days)
+        .into_iter()
+        .find(|s| s.receiver_account == receiver_account)?
+        .avg_slippage_pips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution(receiver: &str, status: &str, slippage: Option<f64>, latency_ms: Option<u64>) -> Execution {
+        serde_json::from_value(serde_json::json!({
+            "id": "x",
+            "timestamp": "2024-03-10T12:00:00Z",
+            "event_type": "entry",
+            "symbol": "EURUSD",
+            "direction": "buy",
+            "master_lots": 0.1,
+            "receiver_lots": 0.1,
+            "master_price": 1.1,
+            "executed_price": null,
+            "slippage_pips": slippage,
+            "status": status,
+            "error_message": null,
+            "receiver_account": receiver,
+            "latency_ms": latency_ms
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_averages_over_windows() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let mut book = StatsBook::default();
+        book.record(&execution("2002", "success", Some(1.0), Some(100)), today);
+        book.record(&execution("2002", "success", Some(3.0), Some(300)), today);
+        book.record(&execution("2002", "error", None, None), today);
+        book.record(&execution("2002", "blocked", None, None), today);
+        book.record(&execution("2002", "success", Some(8.0), Some(800)), today - chrono::Duration::days(3));
+        book.record(&execution("3003", "success", Some(0.5), Some(50)), today);
+
+        let today_stats = book.stats(StatsWindow::Today, today);
+        assert_eq!(today_stats.len(), 2);
+        let r = &today_stats[0];
+        assert_eq!(r.receiver_account, "2002");
+        assert_eq!((r.executions, r.fills), (3, 2));
+        assert!((r.fill_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(r.avg_slippage_pips, Some(2.0));
+        assert_eq!(r.worst_slippage_pips, Some(3.0));
+        assert_eq!(r.avg_latency_ms, Some(200.0));
+
+        let week = &book.stats(StatsWindow::Last7Days, today)[0];
+        assert_eq!((week.executions, week.fills), (4, 3));
+        assert_eq!(week.avg_slippage_pips, Some(4.0));
+        assert_eq!(week.worst_slippage_pips, Some(8.0));
+        assert_eq!(week.avg_latency_ms, Some(400.0));
+
+        // A week later the daily totals have aged out; all-time keeps them
+        let later = today + chrono::Duration::days(7);
+        book.record(&execution("2002", "success", Some(0.0), Some(10)), later);
+        assert_eq!(book.stats(StatsWindow::Last7Days, later)[0].executions, 1);
+        let all_time = &book.stats(StatsWindow::AllTime, later)[0];
+        assert_eq!((all_time.executions, all_time.fills), (5, 4));
+        assert_eq!(all_time.avg_slippage_pips, Some(3.0));
+    }
+}
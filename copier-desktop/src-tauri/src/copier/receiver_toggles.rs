@@ -0,0 +1,88 @@
+//! Local receiver enable/disable toggles
+//!
+//! Lets the user stop new opens on one receiver (e.g. a prop account close
+//! to its limit) without removing it from the cloud config. The disabled
+//! receiver ids are persisted locally and re-applied every time a config is
+//! installed, so a cloud re-sync doesn't silently turn a receiver back on.
+
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use crate::data_dir::app_data_dir;
+use super::CopierConfig;
+
+const TOGGLES_FILE: &str = "disabled_receivers.json";
+
+/// Receiver account ids the user disabled locally
+static DISABLED: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(load()));
+
+fn get_toggles_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(TOGGLES_FILE))
+}
+
+fn load() -> BTreeSet<String> {
+    let Some(path) = get_toggles_path() else {
+        return BTreeSet::new();
+    };
+    match fs::read_to_string(&path).map(|c| serde_json::from_str(&c)) {
+        Ok(Ok(ids)) => ids,
+        Ok(Err(e)) => {
+            warn!("Ignoring unreadable receiver toggles file: {}", e);
+            BTreeSet::new()
+        }
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+/// Write the disabled ids (atomic write)
+fn save(ids: &BTreeSet<String>) -> Result<(), String> {
+    let Some(path) = get_toggles_path() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(ids).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+    fs::rename(&temp_path, &path).map_err(|e| e.to_string())
+}
+
+/// Record a receiver's toggle and persist it
+pub fn set_enabled(receiver_id: &str, enabled: bool) -> Result<(), String> {
+    let mut disabled = DISABLED.lock();
+    let changed = if enabled {
+        disabled.remove(receiver_id)
+    } else {
+        disabled.insert(receiver_id.to_string())
+    };
+    if changed {
+        save(&disabled).map_err(|e| format!("Failed to save receiver toggles: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Apply the local toggles to a config. Returns false if no receiver in it
+/// has this id.
+pub fn apply_to(config: &mut CopierConfig, receiver_id: &str, enabled: bool) -> bool {
+    let mut found = false;
+    for receiver in config.receivers.iter_mut().filter(|r| r.account_id == receiver_id) {
+        receiver.enabled = enabled;
+        found = true;
+    }
+    found
+}
+
+/// Disable every receiver the user turned off locally
+pub fn apply_all(config: &mut CopierConfig) {
+    let disabled = DISABLED.lock();
+    for receiver in config.receivers.iter_mut() {
+        if disabled.contains(&receiver.account_id) {
+            receiver.enabled = false;
+        }
+    }
+}
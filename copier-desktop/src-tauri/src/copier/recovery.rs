@@ -0,0 +1,239 @@
+//! Re-check of executions recovered after a crash
+//!
+//! `ExecutionQueue::load_from_disk` moves executions that were in progress
+//! when the app stopped back to pending. The command may already have
+//! reached the receiver EA, so before the worker retries one it looks at the
+//! receiver's own files for evidence the trade already happened:
+//!
+//! - `copier-executed.json` lists the execution's idempotency key
+//! - an unread successful `resp_<timestamp>.json` echoing the execution's
+//!   idempotency key (commands without one, like the self-test, never match)
+//! - `copier-positions.json` already holds the position (entry) or no longer
+//!   holds it (exit)
+//!
+//! With evidence the execution is completed rather than sent twice.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+
+use super::event_processor::position_id_of;
+use super::execution_queue::QueuedExecution;
+use super::position_sync::{self, ReceiverPosition};
+use super::trade_executor::TradeResponse;
+use super::ReceiverConfig;
+
+/// Ledger of executed events the receiver EA keeps in `MQL5\Files`
+const EXECUTED_FILE: &str = "copier-executed.json";
+
+/// What the receiver's files say about recently executed trades
+#[derive(Debug, Default)]
+pub struct ReceiverEvidence {
+    /// Idempotency keys from `copier-executed.json`
+    pub executed_keys: HashSet<String>,
+    /// Unread responses left in `CopierCommands`
+    pub responses: Vec<TradeResponse>,
+    /// Open copies; None when the positions file couldn't be read
+    pub positions: Option<Vec<ReceiverPosition>>,
+}
+
+#[derive(Deserialize)]
+struct ExecutedLedger {
+    #[serde(default)]
+    events: Vec<ExecutedEntry>,
+}
+
+#[derive(Deserialize)]
+struct ExecutedEntry {
+    idempotency_key: String,
+}
+
+impl ReceiverEvidence {
+    /// Read the receiver's files. Anything unreadable is treated as no evidence.
+    pub fn read(receiver: &ReceiverConfig) -> Self {
+        let mut evidence = Self {
+            positions: position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number).ok(),
+            ..Default::default()
+        };
+        let Ok(files) = crate::mt5::bridge::resolve_files_path(&receiver.terminal_id, false) else {
+            return evidence;
+        };
+
+        if let Ok(content) = fs::read_to_string(files.join(EXECUTED_FILE)) {
+            evidence.executed_keys = parse_executed_keys(&content);
+        }
+        evidence.responses = read_leftover_responses(&files.join("CopierCommands"));
+        evidence
+    }
+}
+
+/// Idempotency keys in the EA's executed ledger
+fn parse_executed_keys(content: &str) -> HashSet<String> {
+    serde_json::from_str::<ExecutedLedger>(content)
+        .map(|ledger| ledger.events.into_iter().map(|e| e.idempotency_key).collect())
+        .unwrap_or_default()
+}
+
+/// Responses the desktop never read (it deletes them after reading)
+fn read_leftover_responses(folder: &Path) -> Vec<TradeResponse> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.starts_with("resp_") && name.ends_with(".json")
+        })
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+/// Why a recovered execution is known to have already run on the receiver,
+/// or None when it still has to be sent
+pub fn already_executed(exec: &QueuedExecution, evidence: &ReceiverEvidence) -> Option<String> {
+    if evidence.executed_keys.contains(&exec.idempotency_key) {
+        return Some(format!("receiver EA recorded {} as executed", exec.idempotency_key));
+    }
+
+    if let Some(response) = evidence
+        .responses
+        .iter()
+        .find(|r| r.success && r.idempotency_key.as_deref() == Some(exec.idempotency_key.as_str()))
+    {
+        return Some(match response.receiver_position_id {
+            Some(id) => format!("receiver answered the interrupted command (position {})", id),
+            None => "receiver answered the interrupted command".to_string(),
+        });
+    }
+
+    let positions = evidence.positions.as_ref()?;
+    let position_id = position_id_of(&exec.event);
+    let held = positions.iter().find(|p| p.master_position_id == position_id);
+    match exec.event.event_type.as_str() {
+        "entry" => held.map(|p| format!("receiver already holds position {}", p.position_id)),
+        "exit" if held.is_none() => Some(format!("position {} is already closed on the receiver", position_id)),
+        _ => None,
+    }
+}
+
+/// Complete a recovered execution if the receiver already ran it. Returns
+/// true when the caller should not send it.
+pub fn complete_if_already_executed(exec: &QueuedExecution, receiver: &ReceiverConfig) -> bool {
+    if !exec.recovered {
+        return false;
+    }
+    let Some(reason) = already_executed(exec, &ReceiverEvidence::read(receiver)) else {
+        debug!("No sign recovered execution {} ran on {}; retrying", exec.id, receiver.account_number);
+        return false;
+    };
+    info!(
+        "Recovered {} for {} not re-sent: {}",
+        exec.event.event_type,
+        receiver.account_number,
+        reason
+    );
+    super::execution_queue::EXECUTION_QUEUE.update(|queue| queue.mark_completed(&exec.id));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::execution_queue::{ExecutionQueue, QueueStatus, SharedExecutionQueue};
+    use crate::copier::test_support::test_event;
+    use chrono::Utc;
+
+    fn position(master_position_id: i64) -> ReceiverPosition {
+        serde_json::from_value(serde_json::json!({
+            "position_id": 900,
+            "master_position_id": master_position_id,
+            "symbol": "EURUSD",
+            "direction": "buy",
+            "volume": 0.1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recovered_execution_already_executed_is_not_resent() {
+        let path = std::env::temp_dir().join(format!("saturn_recovery_test_{}.json", uuid::Uuid::new_v4()));
+        let mut queue = ExecutionQueue::new(Some(path.clone()));
+        queue.defer(QueuedExecution::new(test_event("entry", 77), "R1", "k1"), Utc::now(), "test");
+        queue.dequeue_ready(Utc::now()).unwrap();
+        // App stops here, after the command reached the receiver
+        SharedExecutionQueue::new(queue).persist().unwrap();
+
+        let mut loaded = ExecutionQueue::load_from_disk(path.clone());
+        let exec = loaded.dequeue_ready(Utc::now()).unwrap();
+        assert!(exec.recovered);
+        assert!(exec.started_at.is_some());
+
+        let evidence = ReceiverEvidence {
+            positions: Some(vec![position(77)]),
+            ..Default::default()
+        };
+        assert!(already_executed(&exec, &evidence).unwrap().contains("900"));
+        loaded.mark_completed(&exec.id);
+
+        assert_eq!(loaded.pending_count(), 0);
+        assert!(loaded.dequeue_ready(Utc::now()).is_none());
+        let shared = SharedExecutionQueue::new(loaded);
+        assert_eq!(shared.recent_completed()[0].status, QueueStatus::Completed);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_evidence_sources() {
+        let exec = QueuedExecution::new(test_event("entry", 77), "R1", "k1");
+
+        // Nothing on the receiver: send it
+        let empty = ReceiverEvidence {
+            positions: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(already_executed(&exec, &empty).is_none());
+
+        let ledger = r#"{"version":1,"events":[{"idempotency_key":"k1","receiver_position_id":5,"executed_at":"2024.01.01 00:00:00","slippage_pips":0.0}]}"#;
+        let evidence = ReceiverEvidence {
+            executed_keys: parse_executed_keys(ledger),
+            ..Default::default()
+        };
+        assert!(already_executed(&exec, &evidence).is_some());
+
+        let response = |key: Option<&str>| -> TradeResponse {
+            serde_json::from_value(serde_json::json!({
+                "success": true,
+                "executed_price": 1.1,
+                "slippage_pips": 0.0,
+                "error": null,
+                "timestamp": 1_700_000_000,
+                "receiver_position_id": 5,
+                "idempotency_key": key
+            }))
+            .unwrap()
+        };
+        let evidence = ReceiverEvidence {
+            responses: vec![response(Some("k1"))],
+            ..Default::default()
+        };
+        assert!(already_executed(&exec, &evidence).is_some());
+
+        // Another command's answer, or the keyless self-test ack, proves nothing
+        let evidence = ReceiverEvidence {
+            responses: vec![response(Some("k0")), response(None)],
+            positions: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(already_executed(&exec, &evidence).is_none());
+
+        // An exit whose position is gone already ran; unreadable positions prove nothing
+        let exit = QueuedExecution::new(test_event("exit", 77), "R1", "k2");
+        assert!(already_executed(&exit, &empty).is_some());
+        assert!(already_executed(&exit, &ReceiverEvidence::default()).is_none());
+    }
+}
@@ -0,0 +1,299 @@
+//! On-demand full resync of one receiver
+//!
+//! When a receiver has drifted, the user can ask for it to match its
+//! master now. Every discrepancy reconciliation would report is turned
+//! into a sync command in one pass: missing positions are opened (sized by
+//! the receiver's risk mode, like catch-up), orphans closed, wrong-direction
+//! copies closed and re-opened, and SL/TP levels re-sent. The SL/TP
+//! cooldown is ignored, but the receiver's copy mode, SL/TP policy,
+//! `respect_manual_sltp` and safety limits still apply. Aggregate-mode
+//! receivers are brought to the master's net position instead.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::alerts::{self, AlertSeverity};
+use super::commanded_levels::{self, CommandedLevels};
+use super::event_processor::{self, get_cached_account_info};
+use super::position_sync::{self, DiscrepancyType, MasterPosition, ReceiverPosition, SyncCommand};
+use super::symbol_catalog::{self, SymbolCatalog};
+use super::{aggregate, catch_up, kill_switch, lot_calculator, safety, CopierState, CopyMode, ReceiverConfig};
+
+/// One command issued by a resync and what became of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResyncAction {
+    pub command: SyncCommand,
+    /// Why the command was needed
+    pub description: String,
+    /// "sent", "blocked" or "error"
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// Commands that bring `receiver_positions` in line with `master_positions`,
+/// each with a description. Closes come first, then SL/TP changes, then opens.
+pub fn plan_resync(
+    master_positions: &[MasterPosition],
+    receiver_positions: &[ReceiverPosition],
+    receiver: &ReceiverConfig,
+    receiver_account: Option<&lot_calculator::AccountInfo>,
+    catalog: Option<&SymbolCatalog>,
+    commanded: &HashMap<i64, CommandedLevels>,
+) -> Vec<(SyncCommand, String)> {
+    let discrepancies = position_sync::find_discrepancies(
+        master_positions,
+        receiver_positions,
+        &position_sync::DiscrepancyCheck {
+            sltp_sync: receiver.sltp_sync(),
+            catalog,
+            commanded: commanded.clone(),
+            respect_manual_sltp: receiver.respect_manual_sltp,
+            ..position_sync::DiscrepancyCheck::new(&receiver.terminal_id, receiver.sltp_policy)
+        },
+    );
+
+    let closes_allowed = receiver.copy_mode != CopyMode::EntriesOnly;
+    let opens_allowed = receiver.copy_mode != CopyMode::ExitsOnly;
+
+    let mut closes = Vec::new();
+    let mut modifies: Vec<SyncCommand> = Vec::new();
+    let mut reopened = HashSet::new();
+    for d in &discrepancies {
+        match (&d.discrepancy_type, &d.master_position, &d.receiver_position) {
+            (DiscrepancyType::OrphanedOnReceiver, _, Some(recv)) if closes_allowed => {
+                closes.push((
+                    SyncCommand::close_position(recv.position_id),
+                    format!("Close position {} (master position {} is closed)", recv.position_id, recv.master_position_id),
+                ));
+            }
+            (DiscrepancyType::DirectionMismatch, Some(master), Some(recv)) if closes_allowed && opens_allowed => {
+                closes.push((
+                    SyncCommand::close_position(recv.position_id),
+                    format!("Close position {} ({} on receiver, {} on master)", recv.position_id, recv.direction, master.direction),
+                ));
+                reopened.insert(master.position_id);
+            }
+            (DiscrepancyType::SLMismatch | DiscrepancyType::TPMismatch, Some(master), Some(recv)) => {
+                let index = match modifies.iter().position(|c| c.position_id == Some(recv.position_id)) {
+                    Some(index) => index,
+                    None => {
+                        modifies.push(SyncCommand {
+                            master_position_id: Some(master.position_id),
+                            ..SyncCommand::modify_sl_tp(recv.position_id, None, None)
+                        });
+                        modifies.len() - 1
+                    }
+                };
+                // Only the mismatched level is sent, so a hand-moved one kept
+                // under `respect_manual_sltp` isn't overwritten
+                if d.discrepancy_type == DiscrepancyType::SLMismatch {
+                    modifies[index].sl = receiver.sltp_policy.apply(Some(master.sl));
+                } else {
+                    modifies[index].tp = receiver.sltp_policy.apply(Some(master.tp));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut plan = closes;
+    let modifies = modifies
+        .into_iter()
+        .filter(|c| c.sl.is_some() || c.tp.is_some())
+        .filter(|c| !c.master_position_id.is_some_and(|id| reopened.contains(&id)));
+    plan.extend(modifies.map(|c| {
+        let description = format!("Set SL/TP of position {} to the master's", c.position_id.unwrap_or_default());
+        (c, description)
+    }));
+
+    if opens_allowed {
+        // Copies being re-opened count as missing
+        let held: Vec<ReceiverPosition> = receiver_positions
+            .iter()
+            .filter(|r| !reopened.contains(&r.master_position_id))
+            .cloned()
+            .collect();
+        for command in catch_up::build_catch_up_commands(master_positions, &held, receiver, receiver_account) {
+            let description = format!(
+                "Open {} {} {} lots (master position {})",
+                command.symbol.as_deref().unwrap_or_default(),
+                command.direction.as_deref().unwrap_or_default(),
+                command.volume.unwrap_or_default(),
+                command.master_position_id.unwrap_or_default()
+            );
+            plan.push((command, description));
+        }
+    }
+    plan
+}
+
+/// Make one receiver (by account id) match its master now, whatever the
+/// reconciliation settings. Returns every command issued and its outcome.
+pub fn resync_receiver(state: &Arc<Mutex<CopierState>>, receiver_id: &str) -> Result<Vec<ResyncAction>, String> {
+    if kill_switch::is_engaged() {
+        return Err("Kill switch is engaged".to_string());
+    }
+    let (receiver, master_terminal_id) = {
+        let copier = state.lock();
+        let config = copier.config.as_ref().ok_or("No copier config loaded")?;
+        let receiver = config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .cloned()
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+        let master = config
+            .all_masters()
+            .into_iter()
+            .find(|m| config.receiver_follows(&receiver, &m.account_id))
+            .ok_or_else(|| format!("Master of receiver {} is not in the current config", receiver_id))?;
+        (receiver, master.terminal_id.clone())
+    };
+
+    let master_positions = position_sync::read_master_positions(&master_terminal_id)?;
+    let receiver_positions = position_sync::read_receiver_positions(&receiver.terminal_id, receiver.magic_number)?;
+    let account = get_cached_account_info(&receiver.terminal_id);
+    let catalog = symbol_catalog::fetch_symbol_catalog(&receiver.terminal_id).ok();
+    let plan = if receiver.aggregate_mode {
+        aggregate::plan_aggregate(&master_positions, &receiver_positions, &receiver, account.as_ref(), catalog.as_ref())
+            .into_iter()
+            .map(|c| (c, "Match the master's net position".to_string()))
+            .collect()
+    } else {
+        plan_resync(
+            &master_positions,
+            &receiver_positions,
+            &receiver,
+            account.as_ref(),
+            catalog.as_ref(),
+            &commanded_levels::for_receiver(&receiver.terminal_id),
+        )
+    };
+
+    let safety_config = event_processor::receiver_safety_config(&receiver);
+
+    let mut actions = Vec::with_capacity(plan.len());
+    for (command, description) in plan {
+        let mut action = ResyncAction {
+            command,
+            description,
+            status: "sent".to_string(),
+            error: None,
+        };
+        if action.command.command_type == "open" {
+            let blocked = match &account {
+                None => Some("Receiver account info not available yet".to_string()),
+                Some(account) => match safety::check_trade_safety(&receiver.account_number, &safety_config, account.balance) {
+                    safety::SafetyCheckResult::Blocked(reason) => Some(reason),
+                    _ => None,
+                },
+            };
+            if let Some(reason) = blocked {
+                action.status = "blocked".to_string();
+                action.error = Some(reason);
+                actions.push(action);
+                continue;
+            }
+        }
+
+        match position_sync::write_sync_command(&receiver.terminal_id, &action.command) {
+            Ok(()) => commanded_levels::remember_command(&receiver.terminal_id, &action.command),
+            Err(e) => {
+                warn!("Resync command failed for {}: {}", receiver.account_number, e);
+                action.status = "error".to_string();
+                action.error = Some(e.to_string());
+            }
+        }
+        actions.push(action);
+    }
+
+    let sent = actions.iter().filter(|a| a.status == "sent").count();
+    info!("Resync of {}: {} of {} command(s) sent", receiver.account_number, sent, actions.len());
+    alerts::push_alert(
+        AlertSeverity::Info,
+        format!("Resynced {} with its master: {} of {} command(s) sent", receiver.account_number, sent, actions.len()),
+    );
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::test_support::test_receiver;
+
+    fn master_position(position_id: i64, symbol: &str, direction: &str) -> MasterPosition {
+        MasterPosition {
+            position_id,
+            symbol: symbol.to_string(),
+            direction: direction.to_string(),
+            volume: 1.0,
+            open_price: 1.1,
+            sl: 1.09,
+            tp: 1.12,
+            sl_distance_points: None,
+            tp_distance_points: None,
+        }
+    }
+
+    fn receiver_position(position_id: i64, master_position_id: i64, direction: &str) -> ReceiverPosition {
+        ReceiverPosition {
+            position_id,
+            master_position_id,
+            symbol: "EURUSD".to_string(),
+            direction: direction.to_string(),
+            volume: 0.2,
+            sl: Some(1.09),
+            tp: Some(1.12),
+            magic: None,
+        }
+    }
+
+    fn receiver() -> ReceiverConfig {
+        ReceiverConfig { risk_value: 0.2, ..test_receiver() }
+    }
+
+    #[test]
+    fn test_resync_empty_receiver_to_two_master_positions() {
+        let master = vec![master_position(100, "EURUSD", "buy"), master_position(101, "GBPUSD", "sell")];
+        let receiver = receiver();
+
+        let plan = plan_resync(&master, &[], &receiver, None, None, &HashMap::new());
+        assert_eq!(plan.len(), 2);
+        assert!(plan.iter().all(|(c, _)| c.command_type == "open"));
+        assert_eq!(plan[0].0.master_position_id, Some(100));
+        assert_eq!(plan[1].0.master_position_id, Some(101));
+        assert_eq!(plan[1].0.direction.as_deref(), Some("sell"));
+        assert!((plan[0].0.volume.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(plan[0].0.sl, Some(1.09));
+
+        // Once the receiver holds both copies there's nothing left to do
+        let mut held = vec![receiver_position(500, 100, "buy"), receiver_position(501, 101, "sell")];
+        held[1].symbol = "GBPUSD".to_string();
+        assert!(plan_resync(&master, &held, &receiver, None, None, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_resync_closes_orphans_and_reopens_wrong_direction() {
+        let master = vec![master_position(100, "EURUSD", "buy")];
+        let mut wrong_sl = receiver_position(500, 100, "sell");
+        wrong_sl.sl = Some(1.05);
+        let held = vec![wrong_sl, receiver_position(501, 99, "buy")];
+        let receiver = receiver();
+
+        let plan = plan_resync(&master, &held, &receiver, None, None, &HashMap::new());
+        let kinds: Vec<_> = plan.iter().map(|(c, _)| (c.command_type.as_str(), c.position_id, c.master_position_id)).collect();
+        assert!(kinds.contains(&("close", Some(500), None)));
+        assert!(kinds.contains(&("close", Some(501), None)));
+        assert!(kinds.contains(&("open", None, Some(100))));
+
+        // Exits-only receivers are never given opens
+        let mut exits_only = receiver.clone();
+        exits_only.copy_mode = CopyMode::ExitsOnly;
+        let plan = plan_resync(&master, &held, &exits_only, None, None, &HashMap::new());
+        assert!(plan.iter().all(|(c, _)| c.command_type != "open"));
+        assert!(plan.iter().any(|(c, _)| c.command_type == "modify_sl_tp" && c.sl == Some(1.09) && c.tp.is_none()));
+    }
+}
@@ -0,0 +1,123 @@
+//! Suggested risk settings
+//!
+//! Setup wizard helper: picks a starting `RiskConfig` for a receiver from the
+//! two account sizes and the risk per trade the user wants. Accounts of
+//! comparable size copy with `balance_multiplier`, which keeps the receiver
+//! in step with the master's sizing. Far apart in size, proportional lots get
+//! distorted (min-lot rounding on a small receiver, oversized lots on a big
+//! one), so `risk_percent` sizes each trade from the receiver's own balance.
+
+use serde::{Deserialize, Serialize};
+
+use super::config_generator::RiskConfig;
+
+/// Master risk per trade assumed when scaling a balance multiplier
+pub const REFERENCE_MASTER_RISK_PERCENT: f64 = 1.0;
+
+/// Receiver/master balance ratios copied proportionally
+pub const MIN_PROPORTIONAL_RATIO: f64 = 0.1;
+pub const MAX_PROPORTIONAL_RATIO: f64 = 5.0;
+
+/// Highest risk per trade the wizard will suggest
+pub const MAX_TARGET_RISK_PERCENT: f64 = 10.0;
+
+/// A recommended risk config and why it was picked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskSuggestion {
+    pub risk: RiskConfig,
+    pub rationale: String,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Suggested risk config for a receiver of `receiver_balance` copying a
+/// master of `master_balance`, risking `target_risk_percent` per trade
+pub fn suggest_risk_config(
+    master_balance: f64,
+    receiver_balance: f64,
+    target_risk_percent: f64,
+) -> Result<RiskSuggestion, String> {
+    if !(master_balance.is_finite() && master_balance > 0.0) {
+        return Err("Master balance must be greater than zero".to_string());
+    }
+    if !(receiver_balance.is_finite() && receiver_balance > 0.0) {
+        return Err("Receiver balance must be greater than zero".to_string());
+    }
+    if !(target_risk_percent > 0.0 && target_risk_percent <= MAX_TARGET_RISK_PERCENT) {
+        return Err(format!(
+            "Target risk must be above 0% and at most {}% per trade",
+            MAX_TARGET_RISK_PERCENT
+        ));
+    }
+
+    let ratio = receiver_balance / master_balance;
+    if (MIN_PROPORTIONAL_RATIO..=MAX_PROPORTIONAL_RATIO).contains(&ratio) {
+        let multiplier = round2(target_risk_percent / REFERENCE_MASTER_RISK_PERCENT);
+        let rationale = format!(
+            "Receiver is {:.2}x the master's balance, so lots can scale with the balance ratio. \
+             A multiplier of {} risks about {}% per trade if the master risks {}%; \
+             adjust it if the master risks more or less.",
+            ratio, multiplier, target_risk_percent, REFERENCE_MASTER_RISK_PERCENT
+        );
+        return Ok(RiskSuggestion {
+            risk: RiskConfig {
+                mode: "balance_multiplier".to_string(),
+                value: multiplier,
+            },
+            rationale,
+        });
+    }
+
+    let why = if ratio > MAX_PROPORTIONAL_RATIO {
+        "scaling the master's lots would size trades well beyond the master's own risk"
+    } else {
+        "scaled-down lots would be rounded up to the broker's minimum lot"
+    };
+    let rationale = format!(
+        "Receiver is {:.2}x the master's balance, so {}. Risk percent sizes every trade from \
+         the receiver's own balance and stop loss instead: {}% per trade. Trades without a \
+         stop loss are not copied in this mode.",
+        ratio, why, target_risk_percent
+    );
+    Ok(RiskSuggestion {
+        risk: RiskConfig {
+            mode: "risk_percent".to_string(),
+            value: target_risk_percent,
+        },
+        rationale,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_size_receiver_uses_balance_multiplier() {
+        let suggestion = suggest_risk_config(20000.0, 10000.0, 0.5).unwrap();
+        assert_eq!(suggestion.risk.mode, "balance_multiplier");
+        assert_eq!(suggestion.risk.value, 0.5);
+        assert!(suggestion.rationale.starts_with("Receiver is 0.50x the master's balance"));
+
+        // Same size, 1% target: a plain 1:1 copy
+        let suggestion = suggest_risk_config(10000.0, 10000.0, 1.0).unwrap();
+        assert_eq!((suggestion.risk.mode.as_str(), suggestion.risk.value), ("balance_multiplier", 1.0));
+    }
+
+    #[test]
+    fn test_much_larger_receiver_uses_risk_percent() {
+        let suggestion = suggest_risk_config(5000.0, 200000.0, 1.5).unwrap();
+        assert_eq!(suggestion.risk.mode, "risk_percent");
+        assert_eq!(suggestion.risk.value, 1.5);
+        assert!(suggestion.rationale.contains("40.00x"));
+        assert!(suggestion.rationale.contains("1.5% per trade"));
+
+        // Invalid inputs
+        assert!(suggest_risk_config(0.0, 10000.0, 1.0).is_err());
+        assert!(suggest_risk_config(10000.0, -1.0, 1.0).is_err());
+        assert!(suggest_risk_config(10000.0, 10000.0, 0.0).is_err());
+        assert!(suggest_risk_config(10000.0, 10000.0, 25.0).is_err());
+    }
+}
@@ -7,15 +7,18 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use chrono::{Utc, NaiveDate, Timelike};
 
+use super::{alerts, live_balance, persistence, CopierConfig};
+use crate::data_dir::app_data_dir;
+
 /// File for persisting safety state
 const SAFETY_STATE_FILE: &str = "safety_state.json";
 
-/// App data folder name (shared constant for consistency - m2 fix)
-pub const APP_DATA_FOLDER: &str = "SaturnTradeCopier";
+/// Set to `1` to allow `import_receiver_state` in release builds
+pub const SAFETY_IMPORT_ENV: &str = "SATURN_ALLOW_SAFETY_IMPORT";
 
 /// Receiver safety state
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -42,6 +45,22 @@ pub struct ReceiverSafetyState {
     pub pause_reason: Option<String>,
     /// Consecutive losses counter
     pub consecutive_losses: i32,
+    /// Paused for hitting the profit target. Unlike loss pauses this
+    /// survives the daily reset; only a manual unpause clears it.
+    #[serde(default)]
+    pub profit_target_locked: bool,
+    /// Equity the profit target is measured from once a manual unpause has
+    /// acknowledged reaching it (None = the starting balance)
+    #[serde(default)]
+    pub profit_target_base: Option<f64>,
+    /// When the current pause started (RFC 3339)
+    #[serde(default)]
+    pub paused_at: Option<String>,
+    /// The pause came from a loss, drawdown or equity limit and may be
+    /// lifted by `auto_resume_after_secs`. Profit-target and manual pauses
+    /// never are.
+    #[serde(default)]
+    pub auto_resumable: bool,
     /// Timestamp of last update
     pub last_updated: Option<String>,
 }
@@ -101,6 +120,17 @@ pub struct SafetyConfig {
     pub max_consecutive_losses: Option<i32>,
     /// Daily reset hour in UTC (0-23), default 0 = midnight
     pub daily_reset_hour_utc: Option<i32>,
+    /// Lock the receiver once equity is this much above the starting balance
+    pub profit_target_amount: Option<f64>,
+    /// Lock the receiver once equity is this % above the starting balance
+    pub profit_target_percent: Option<f64>,
+    /// Let a pause block closes as well as entries
+    pub block_closes_when_paused: bool,
+    /// Lift a loss/drawdown/equity pause this long after it started
+    /// (None = only the daily reset or a manual unpause lifts it)
+    pub auto_resume_after_secs: Option<u64>,
+    /// ...and only once equity is back at or above this
+    pub auto_resume_min_equity: Option<f64>,
 }
 
 impl Default for SafetyConfig {
@@ -116,6 +146,11 @@ impl Default for SafetyConfig {
             prop_firm_safe_mode: false,
             max_consecutive_losses: None,
             daily_reset_hour_utc: Some(0),
+            profit_target_amount: None,
+            profit_target_percent: None,
+            block_closes_when_paused: false,
+            auto_resume_after_secs: None,
+            auto_resume_min_equity: None,
         }
     }
 }
@@ -134,10 +169,7 @@ pub fn get_daily_reset_hour() -> i32 {
 
 /// Get the path to the safety state file
 fn get_safety_state_path() -> Option<PathBuf> {
-    let appdata = std::env::var("APPDATA").ok()?;
-    Some(PathBuf::from(appdata)
-        .join(APP_DATA_FOLDER)
-        .join(SAFETY_STATE_FILE))
+    Some(app_data_dir()?.join(SAFETY_STATE_FILE))
 }
 
 /// Load safety state from disk
@@ -145,15 +177,17 @@ fn load_safety_state() -> Result<HashMap<String, ReceiverSafetyState>, String> {
     let path = get_safety_state_path()
         .ok_or_else(|| "Failed to get safety state path".to_string())?;
     
-    if !path.exists() {
-        return Ok(HashMap::new());
-    }
-    
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read safety state: {}", e))?;
-    
-    let persisted: PersistedSafetyState = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse safety state: {}", e))?;
+    Ok(load_safety_state_from(&path))
+}
+
+/// Load safety state from `path`, recovering a corrupt file if possible
+/// (see `persistence::load_state_file`)
+fn load_safety_state_from(path: &Path) -> HashMap<String, ReceiverSafetyState> {
+    let Some(persisted) = persistence::load_state_file(path, "Safety state", |content| {
+        serde_json::from_str::<PersistedSafetyState>(content).map_err(|e| e.to_string())
+    }) else {
+        return HashMap::new();
+    };
     
     // Set the daily reset hour from persisted state
     set_daily_reset_hour(persisted.daily_reset_hour_utc);
@@ -173,19 +207,18 @@ fn load_safety_state() -> Result<HashMap<String, ReceiverSafetyState>, String> {
                 state.wins_today = 0;
                 state.losses_today = 0;
                 state.consecutive_losses = 0;
-                state.is_safety_paused = false;
-                state.pause_reason = None;
+                clear_daily_pause(state);
                 state.set_last_reset_date(today);
             }
         }
     }
     
-    Ok(states)
+    states
 }
 
 /// Get the "trading day" based on reset hour
 /// If it's before reset hour, we're still in the previous day's trading session
-fn get_trading_day(now: chrono::DateTime<Utc>, reset_hour: i32) -> NaiveDate {
+pub(crate) fn get_trading_day(now: chrono::DateTime<Utc>, reset_hour: i32) -> NaiveDate {
     let current_hour = now.hour() as i32;
     let today = now.date_naive();
     
@@ -201,7 +234,10 @@ fn get_trading_day(now: chrono::DateTime<Utc>, reset_hour: i32) -> NaiveDate {
 fn save_safety_state(states: &HashMap<String, ReceiverSafetyState>) -> Result<(), String> {
     let path = get_safety_state_path()
         .ok_or_else(|| "Failed to get safety state path".to_string())?;
-    
+    save_safety_state_to(&path, states)
+}
+
+fn save_safety_state_to(path: &Path, states: &HashMap<String, ReceiverSafetyState>) -> Result<(), String> {
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -222,7 +258,7 @@ fn save_safety_state(states: &HashMap<String, ReceiverSafetyState>) -> Result<()
     fs::write(&temp_path, &json)
         .map_err(|e| format!("Failed to write safety state: {}", e))?;
     
-    fs::rename(&temp_path, &path)
+    fs::rename(&temp_path, path)
         .map_err(|e| format!("Failed to finalize safety state: {}", e))?;
     
     Ok(())
@@ -254,6 +290,31 @@ pub fn update_receiver_state(receiver_id: &str, state: ReceiverSafetyState) {
     persist_state(&states);
 }
 
+/// Whether safety state may be imported: debug builds, or with
+/// `SATURN_ALLOW_SAFETY_IMPORT=1`
+pub fn safety_import_allowed() -> bool {
+    cfg!(debug_assertions) || std::env::var(SAFETY_IMPORT_ENV).is_ok_and(|v| v == "1")
+}
+
+/// Replace a receiver's safety state with a known one, to check limits and
+/// warnings without taking real losses. A state without `last_reset_date`
+/// counts as today's, so the next check doesn't reset it away.
+pub fn import_receiver_state(receiver_id: &str, mut state: ReceiverSafetyState) -> Result<(), String> {
+    if !safety_import_allowed() {
+        return Err(format!("Importing safety state is disabled; set {}=1 to allow it", SAFETY_IMPORT_ENV));
+    }
+    if state.last_reset_date.is_none() {
+        state.set_last_reset_date(get_trading_day(Utc::now(), get_daily_reset_hour()));
+    }
+    state.last_updated = Some(Utc::now().to_rfc3339());
+    tracing::warn!(
+        "Safety state for {} replaced by import (daily P&L {}, paused: {})",
+        receiver_id, state.daily_pnl, state.is_safety_paused
+    );
+    update_receiver_state(receiver_id, state);
+    Ok(())
+}
+
 /// Initialize receiver state with starting balance
 pub fn initialize_receiver(receiver_id: &str, starting_balance: f64, current_equity: f64) {
     let reset_hour = get_daily_reset_hour();
@@ -300,8 +361,7 @@ pub fn check_daily_reset(receiver_id: &str) {
             state.wins_today = 0;
             state.losses_today = 0;
             state.set_last_reset_date(today);
-            state.is_safety_paused = false;
-            state.pause_reason = None;
+            clear_daily_pause(state);
             state.consecutive_losses = 0;
             state.last_updated = Some(Utc::now().to_rfc3339());
             persist_state(&states);
@@ -309,8 +369,85 @@ pub fn check_daily_reset(receiver_id: &str) {
     }
 }
 
-/// Update equity and high water mark
-pub fn update_equity(receiver_id: &str, equity: f64) {
+/// Lift a pause at the daily reset, unless it is a profit-target lock
+fn clear_daily_pause(state: &mut ReceiverSafetyState) {
+    if !state.profit_target_locked {
+        clear_pause(state);
+    }
+}
+
+fn clear_pause(state: &mut ReceiverSafetyState) {
+    state.is_safety_paused = false;
+    state.pause_reason = None;
+    state.paused_at = None;
+    state.auto_resumable = false;
+}
+
+/// Why the profit target counts as reached, if it does. Either target
+/// (amount or percent) is enough.
+pub fn profit_target_reason(equity: f64, starting_balance: f64, config: &SafetyConfig) -> Option<String> {
+    if equity <= 0.0 || starting_balance <= 0.0 {
+        return None;
+    }
+    let profit = equity - starting_balance;
+    if let Some(target) = config.profit_target_amount {
+        if profit >= target {
+            return Some(format!("Profit target reached: ${:.2} (target: ${:.2})", profit, target));
+        }
+    }
+    if let Some(target_percent) = config.profit_target_percent {
+        let profit_percent = profit / starting_balance * 100.0;
+        if profit_percent >= target_percent {
+            return Some(format!(
+                "Profit target reached: {:.1}% (target: {}% of ${:.0})",
+                profit_percent, target_percent, starting_balance
+            ));
+        }
+    }
+    None
+}
+
+/// What the profit target is measured from: the equity at the last
+/// acknowledged target, else the recorded starting balance, else `fallback`
+fn profit_target_base(state: &ReceiverSafetyState, fallback: f64) -> f64 {
+    match state.profit_target_base.filter(|base| *base > 0.0) {
+        Some(base) => base,
+        None if state.starting_balance > 0.0 => state.starting_balance,
+        None => fallback,
+    }
+}
+
+/// Pause a receiver for its profit target until it is manually unpaused
+fn lock_profit_target(receiver_id: &str, state: &mut ReceiverSafetyState, reason: &str) {
+    record_pause(receiver_id, reason);
+    state.is_safety_paused = true;
+    state.profit_target_locked = true;
+    state.auto_resumable = false;
+    state.pause_reason = Some(reason.to_string());
+    let now = Utc::now().to_rfc3339();
+    state.paused_at = Some(now.clone());
+    state.last_updated = Some(now);
+}
+
+/// Pause for a loss, drawdown or equity limit. Unlike other pauses these
+/// may be lifted by `auto_resume_receivers`.
+fn limit_pause(receiver_id: &str, state: &mut ReceiverSafetyState, reason: &str) {
+    record_pause(receiver_id, reason);
+    state.is_safety_paused = true;
+    state.auto_resumable = true;
+    state.pause_reason = Some(reason.to_string());
+    let now = Utc::now().to_rfc3339();
+    state.paused_at = Some(now.clone());
+    state.last_updated = Some(now);
+}
+
+/// Update equity and high water mark, and check the profit target.
+///
+/// `balance` becomes the starting balance the target is measured from if
+/// none is recorded yet. Returns the reason when this update locks the
+/// receiver for reaching its profit target; the caller then closes all of
+/// its positions. An already locked receiver returns None.
+pub fn update_equity(receiver_id: &str, equity: f64, balance: f64, config: &SafetyConfig) -> Option<String> {
     let mut states = SAFETY_STATE.lock();
     let state = states.entry(receiver_id.to_string()).or_default();
     
@@ -318,8 +455,20 @@ pub fn update_equity(receiver_id: &str, equity: f64) {
     if equity > state.high_water_mark {
         state.high_water_mark = equity;
     }
+    if state.starting_balance <= 0.0 {
+        state.starting_balance = balance;
+    }
+    let locked = if state.profit_target_locked {
+        None
+    } else {
+        profit_target_reason(equity, profit_target_base(state, balance), config)
+    };
+    if let Some(reason) = &locked {
+        lock_profit_target(receiver_id, state, reason);
+    }
     state.last_updated = Some(Utc::now().to_rfc3339());
     persist_state(&states);
+    locked
 }
 
 /// Record a trade result
@@ -342,50 +491,38 @@ pub fn record_trade_result(receiver_id: &str, pnl: f64, is_winner: bool) {
     persist_state(&states);
 }
 
-/// Check if a trade should be allowed based on safety rules.
-///
-/// U-4: Daily-reset evaluation, state read, limit comparison, and pause-write
-/// all happen under a SINGLE lock acquisition. The previous implementation
-/// called `check_daily_reset` then `get_receiver_state` then `pause_receiver`,
-/// each re-acquiring the mutex. Between calls, a concurrent
-/// `record_trade_result` could mutate `daily_pnl`, letting two near-simultaneous
-/// trades both pass a limit that should have blocked the second one.
-pub fn check_trade_safety(
-    receiver_id: &str,
-    config: &SafetyConfig,
-    starting_balance: f64,
-) -> SafetyCheckResult {
-    let reset_hour = get_daily_reset_hour();
-    let today = get_trading_day(Utc::now(), reset_hour);
-
-    let mut states = SAFETY_STATE.lock();
-    let mut dirty = false;
-
-    // Ensure an entry exists so we can hold a single &mut for the whole check.
-    let state = states.entry(receiver_id.to_string()).or_default();
+/// Limit breach that pauses a receiver
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Breach {
+    ProfitTarget,
+    Limit,
+}
 
-    // -- Inline daily reset --
-    let needs_reset = match state.get_last_reset_date() {
-        Some(last_date) => last_date != today,
-        None => true,
-    };
-    if needs_reset {
-        tracing::info!(
-            "Resetting daily counters for receiver {} (reset hour: {} UTC)",
-            receiver_id, reset_hour
-        );
-        state.daily_pnl = 0.0;
-        state.trades_today = 0;
-        state.wins_today = 0;
-        state.losses_today = 0;
-        state.consecutive_losses = 0;
-        state.is_safety_paused = false;
-        state.pause_reason = None;
-        state.set_last_reset_date(today);
-        state.last_updated = Some(Utc::now().to_rfc3339());
-        dirty = true;
+/// Reset a state's daily counters if `today` is a new trading day.
+/// Returns whether it was reset.
+fn reset_daily_if_due(state: &mut ReceiverSafetyState, today: NaiveDate) -> bool {
+    if state.get_last_reset_date().is_some_and(|last_date| last_date == today) {
+        return false;
     }
+    state.daily_pnl = 0.0;
+    state.trades_today = 0;
+    state.wins_today = 0;
+    state.losses_today = 0;
+    state.consecutive_losses = 0;
+    clear_daily_pause(state);
+    state.set_last_reset_date(today);
+    state.last_updated = Some(Utc::now().to_rfc3339());
+    true
+}
 
+/// Judge a trade against `state` as it stands, checks in priority order.
+/// A block that should pause the receiver comes with its `Breach`; nothing
+/// is changed here.
+fn evaluate_trade_safety(
+    state: &ReceiverSafetyState,
+    config: &SafetyConfig,
+    starting_balance: f64,
+) -> (SafetyCheckResult, Option<Breach>) {
     // Effective starting balance.
     let effective_balance = if starting_balance > 0.0 {
         starting_balance
@@ -395,111 +532,132 @@ pub fn check_trade_safety(
         10000.0
     };
 
-    // Evaluate checks in priority order. Use a labelled block so we can
-    // return early from any branch without an IIFE (which would conflict
-    // with the outstanding `&mut state` borrow from `states.entry(...)`).
-    let result: SafetyCheckResult = 'check: {
-        if state.is_safety_paused {
-            break 'check SafetyCheckResult::Blocked(
-                state.pause_reason.clone().unwrap_or_else(|| "Safety limit reached".to_string())
+    if state.is_safety_paused {
+        let reason = state.pause_reason.clone().unwrap_or_else(|| "Safety limit reached".to_string());
+        return (SafetyCheckResult::Blocked(reason), None);
+    }
+
+    // Mirror of the loss limits: equity is measured from the recorded
+    // starting balance, since `starting_balance` here is the live balance
+    let target_base = profit_target_base(state, effective_balance);
+    if let Some(reason) = profit_target_reason(state.current_equity, target_base, config) {
+        return (SafetyCheckResult::Blocked(reason), Some(Breach::ProfitTarget));
+    }
+
+    if let Some(max_loss_percent) = config.max_daily_loss_percent {
+        let loss_limit = effective_balance * (max_loss_percent / 100.0);
+        if state.daily_pnl <= -loss_limit {
+            let reason = format!(
+                "Daily loss limit reached: ${:.2} ({}% of ${:.0})",
+                state.daily_pnl.abs(), max_loss_percent, effective_balance
+            );
+            return (SafetyCheckResult::Blocked(reason), Some(Breach::Limit));
+        }
+        if state.daily_pnl <= -(loss_limit * 0.8) {
+            let warning = format!(
+                "Approaching daily loss limit: ${:.2} of ${:.2}",
+                state.daily_pnl.abs(), loss_limit
             );
+            return (SafetyCheckResult::Warning(warning), None);
+        }
+    }
+
+    if let Some(max_loss_amount) = config.max_daily_loss_amount {
+        if state.daily_pnl <= -max_loss_amount {
+            let reason = format!("Daily loss limit reached: ${:.2}", state.daily_pnl.abs());
+            return (SafetyCheckResult::Blocked(reason), Some(Breach::Limit));
         }
+    }
 
-        if let Some(max_loss_percent) = config.max_daily_loss_percent {
-            let loss_limit = effective_balance * (max_loss_percent / 100.0);
-            if state.daily_pnl <= -loss_limit {
+    if let Some(max_dd_percent) = config.max_drawdown_percent {
+        if state.high_water_mark > 0.0 && state.current_equity > 0.0 {
+            let drawdown_percent =
+                ((state.high_water_mark - state.current_equity) / state.high_water_mark) * 100.0;
+            if drawdown_percent >= max_dd_percent {
                 let reason = format!(
-                    "Daily loss limit reached: ${:.2} ({}% of ${:.0})",
-                    state.daily_pnl.abs(), max_loss_percent, effective_balance
+                    "Maximum drawdown reached: {:.1}% (limit: {}%)",
+                    drawdown_percent, max_dd_percent
                 );
-                tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
-                state.is_safety_paused = true;
-                state.pause_reason = Some(reason.clone());
-                state.last_updated = Some(Utc::now().to_rfc3339());
-                dirty = true;
-                break 'check SafetyCheckResult::Blocked(reason);
+                return (SafetyCheckResult::Blocked(reason), Some(Breach::Limit));
             }
-            if state.daily_pnl <= -(loss_limit * 0.8) {
-                break 'check SafetyCheckResult::Warning(format!(
-                    "Approaching daily loss limit: ${:.2} of ${:.2}",
-                    state.daily_pnl.abs(), loss_limit
-                ));
+            if drawdown_percent >= max_dd_percent * 0.8 {
+                let warning = format!(
+                    "Approaching drawdown limit: {:.1}% of {}%",
+                    drawdown_percent, max_dd_percent
+                );
+                return (SafetyCheckResult::Warning(warning), None);
             }
         }
+    }
 
-        if let Some(max_loss_amount) = config.max_daily_loss_amount {
-            if state.daily_pnl <= -max_loss_amount {
-                let reason = format!("Daily loss limit reached: ${:.2}", state.daily_pnl.abs());
-                tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
-                state.is_safety_paused = true;
-                state.pause_reason = Some(reason.clone());
-                state.last_updated = Some(Utc::now().to_rfc3339());
-                dirty = true;
-                break 'check SafetyCheckResult::Blocked(reason);
-            }
+    if let Some(min_equity) = config.min_equity {
+        if state.current_equity > 0.0 && state.current_equity < min_equity {
+            let reason = format!(
+                "Below minimum equity: ${:.2} (minimum: ${:.2})",
+                state.current_equity, min_equity
+            );
+            return (SafetyCheckResult::Blocked(reason), Some(Breach::Limit));
         }
+    }
 
-        if let Some(max_dd_percent) = config.max_drawdown_percent {
-            if state.high_water_mark > 0.0 && state.current_equity > 0.0 {
-                let drawdown_percent =
-                    ((state.high_water_mark - state.current_equity) / state.high_water_mark) * 100.0;
-                if drawdown_percent >= max_dd_percent {
-                    let reason = format!(
-                        "Maximum drawdown reached: {:.1}% (limit: {}%)",
-                        drawdown_percent, max_dd_percent
-                    );
-                    tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
-                    state.is_safety_paused = true;
-                    state.pause_reason = Some(reason.clone());
-                    state.last_updated = Some(Utc::now().to_rfc3339());
-                    dirty = true;
-                    break 'check SafetyCheckResult::Blocked(reason);
-                }
-                if drawdown_percent >= max_dd_percent * 0.8 {
-                    break 'check SafetyCheckResult::Warning(format!(
-                        "Approaching drawdown limit: {:.1}% of {}%",
-                        drawdown_percent, max_dd_percent
-                    ));
-                }
-            }
+    if let Some(max_trades) = config.max_trades_per_day {
+        if state.trades_today >= max_trades {
+            let reason = format!(
+                "Maximum daily trades reached: {} (limit: {})",
+                state.trades_today, max_trades
+            );
+            return (SafetyCheckResult::Blocked(reason), None);
         }
+    }
 
-        if let Some(min_equity) = config.min_equity {
-            if state.current_equity > 0.0 && state.current_equity < min_equity {
-                let reason = format!(
-                    "Below minimum equity: ${:.2} (minimum: ${:.2})",
-                    state.current_equity, min_equity
-                );
-                tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
-                state.is_safety_paused = true;
-                state.pause_reason = Some(reason.clone());
-                state.last_updated = Some(Utc::now().to_rfc3339());
-                dirty = true;
-                break 'check SafetyCheckResult::Blocked(reason);
-            }
+    if config.prop_firm_safe_mode {
+        let max_consecutive = config.max_consecutive_losses.unwrap_or(3);
+        if state.consecutive_losses >= max_consecutive {
+            let warning = format!("{} consecutive losses - consider pausing", state.consecutive_losses);
+            return (SafetyCheckResult::Warning(warning), None);
         }
+    }
 
-        if let Some(max_trades) = config.max_trades_per_day {
-            if state.trades_today >= max_trades {
-                break 'check SafetyCheckResult::Blocked(format!(
-                    "Maximum daily trades reached: {} (limit: {})",
-                    state.trades_today, max_trades
-                ));
-            }
-        }
+    (SafetyCheckResult::Allowed, None)
+}
 
-        if config.prop_firm_safe_mode {
-            let max_consecutive = config.max_consecutive_losses.unwrap_or(3);
-            if state.consecutive_losses >= max_consecutive {
-                break 'check SafetyCheckResult::Warning(format!(
-                    "{} consecutive losses - consider pausing",
-                    state.consecutive_losses
-                ));
-            }
-        }
+/// Check if a trade should be allowed based on safety rules.
+///
+/// U-4: Daily-reset evaluation, state read, limit comparison, and pause-write
+/// all happen under a SINGLE lock acquisition. The previous implementation
+/// called `check_daily_reset` then `get_receiver_state` then `pause_receiver`,
+/// each re-acquiring the mutex. Between calls, a concurrent
+/// `record_trade_result` could mutate `daily_pnl`, letting two near-simultaneous
+/// trades both pass a limit that should have blocked the second one.
+pub fn check_trade_safety(
+    receiver_id: &str,
+    config: &SafetyConfig,
+    starting_balance: f64,
+) -> SafetyCheckResult {
+    let reset_hour = get_daily_reset_hour();
+    let today = get_trading_day(Utc::now(), reset_hour);
 
-        SafetyCheckResult::Allowed
-    };
+    let mut states = SAFETY_STATE.lock();
+
+    // Ensure an entry exists so we can hold a single &mut for the whole check.
+    let state = states.entry(receiver_id.to_string()).or_default();
+
+    let mut dirty = reset_daily_if_due(state, today);
+    if dirty {
+        tracing::info!(
+            "Resetting daily counters for receiver {} (reset hour: {} UTC)",
+            receiver_id, reset_hour
+        );
+    }
+
+    let (result, breach) = evaluate_trade_safety(state, config, starting_balance);
+    if let (Some(breach), SafetyCheckResult::Blocked(reason)) = (breach, &result) {
+        match breach {
+            Breach::ProfitTarget => lock_profit_target(receiver_id, state, reason),
+            Breach::Limit => limit_pause(receiver_id, state, reason),
+        }
+        dirty = true;
+    }
 
     // `state` mutable borrow ends here; safe to re-borrow `states` for persistence.
     if dirty {
@@ -508,32 +666,165 @@ pub fn check_trade_safety(
     result
 }
 
+/// What `check_trade_safety` would return now, without side effects: a due
+/// daily reset is applied to a copy of the state, and no breach pauses the
+/// receiver. For previews and explanations.
+pub fn preview_trade_safety(receiver_id: &str, config: &SafetyConfig, starting_balance: f64) -> SafetyCheckResult {
+    let mut state = get_receiver_state(receiver_id);
+    reset_daily_if_due(&mut state, get_trading_day(Utc::now(), get_daily_reset_hour()));
+    evaluate_trade_safety(&state, config, starting_balance).0
+}
+
+/// Safety check for an event that only reduces or adjusts existing exposure
+/// (a close, partial close or SL/TP change). Loss and trade-count limits
+/// don't apply, and a pause only blocks it with `block_closes_when_paused`;
+/// otherwise a pause could keep a losing position open after the master
+/// has left it. An SL/TP change that `loosens_stops` is blocked by any
+/// pause, since it adds risk rather than reducing it.
+pub fn check_exit_safety(receiver_id: &str, config: &SafetyConfig, loosens_stops: bool) -> SafetyCheckResult {
+    if !config.block_closes_when_paused && !loosens_stops {
+        return SafetyCheckResult::Allowed;
+    }
+    check_daily_reset(receiver_id);
+    let states = SAFETY_STATE.lock();
+    match states.get(receiver_id).filter(|s| s.is_safety_paused) {
+        Some(state) => {
+            let reason = state.pause_reason.clone().unwrap_or_else(|| "Safety limit reached".to_string());
+            if config.block_closes_when_paused {
+                SafetyCheckResult::Blocked(reason)
+            } else {
+                SafetyCheckResult::Blocked(format!("SL/TP change would loosen stops while paused: {}", reason))
+            }
+        }
+        None => SafetyCheckResult::Allowed,
+    }
+}
 
+/// Whether moving a position's SL/TP from `current` to `new` gives it more
+/// room: the SL moves away from the trade or is removed, or the TP moves
+/// further out or is removed. Levels are (sl, tp); 0 or `None` in `current`
+/// means no level, `None` in `new` leaves the level unchanged.
+pub fn loosens_stops(direction: &str, current: (Option<f64>, Option<f64>), new: (Option<f64>, Option<f64>)) -> bool {
+    // +1 when prices rising are in the trade's favour
+    let favour = if direction.eq_ignore_ascii_case("buy") { 1.0 } else { -1.0 };
+    // `outward` is the direction (in favour terms) that gives the level room
+    let loosened = |current: Option<f64>, new: Option<f64>, outward: f64| {
+        match (current.filter(|level| *level > 0.0), new) {
+            (Some(current), Some(new)) => new <= 0.0 || (new - current) * favour * outward > 0.0,
+            _ => false,
+        }
+    };
+    loosened(current.0, new.0, -1.0) || loosened(current.1, new.1, 1.0)
+}
 
+/// Log a safety pause and raise an alert for it
+fn record_pause(receiver_id: &str, reason: &str) {
+    tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
+    alerts::push_alert(
+        alerts::AlertSeverity::Critical,
+        format!("Receiver {} safety paused: {}", receiver_id, reason),
+    );
+}
 
 /// Pause a receiver due to safety breach
 fn pause_receiver(receiver_id: &str, reason: &str) {
-    tracing::warn!("Safety pause for {}: {}", receiver_id, reason);
+    record_pause(receiver_id, reason);
     
     let mut states = SAFETY_STATE.lock();
     let state = states.entry(receiver_id.to_string()).or_default();
     state.is_safety_paused = true;
+    state.auto_resumable = false;
     state.pause_reason = Some(reason.to_string());
-    state.last_updated = Some(Utc::now().to_rfc3339());
+    let now = Utc::now().to_rfc3339();
+    state.paused_at = Some(now.clone());
+    state.last_updated = Some(now);
     persist_state(&states);
 }
 
-/// Manually unpause a receiver
+/// Manually unpause a receiver. Lifting a profit-target lock acknowledges
+/// it: the target is then measured from the current equity, so the next
+/// event doesn't lock the receiver (and close all) again straight away.
 pub fn unpause_receiver(receiver_id: &str) {
     let mut states = SAFETY_STATE.lock();
     if let Some(state) = states.get_mut(receiver_id) {
-        state.is_safety_paused = false;
-        state.pause_reason = None;
+        clear_pause(state);
+        if state.profit_target_locked && state.current_equity > 0.0 {
+            state.profit_target_base = Some(state.current_equity);
+        }
+        state.profit_target_locked = false;
         state.last_updated = Some(Utc::now().to_rfc3339());
         persist_state(&states);
     }
 }
 
+/// Whether a paused receiver's auto-resume cooldown has passed with equity
+/// recovered. A limit still breached pauses it again on its next entry.
+fn auto_resume_due(state: &ReceiverSafetyState, config: &SafetyConfig, now: chrono::DateTime<Utc>) -> bool {
+    if !state.is_safety_paused || !state.auto_resumable || state.profit_target_locked {
+        return false;
+    }
+    let Some(cooldown) = config.auto_resume_after_secs else {
+        return false;
+    };
+    let Some(paused_at) = state
+        .paused_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    else {
+        return false;
+    };
+    let elapsed = now.signed_duration_since(paused_at.with_timezone(&Utc));
+    elapsed >= chrono::Duration::seconds(cooldown as i64)
+        && config.auto_resume_min_equity.is_none_or(|min| state.current_equity >= min)
+}
+
+/// Lift a receiver's pause if its auto-resume is due, judging recovery on
+/// `live_equity` rather than the equity recorded at its last trade. Without
+/// a live reading a minimum-equity condition can't be confirmed, so the
+/// receiver stays paused. Returns whether it was resumed.
+pub fn check_auto_resume(receiver_id: &str, config: &SafetyConfig, live_equity: Option<f64>) -> bool {
+    let mut states = SAFETY_STATE.lock();
+    let Some(state) = states.get_mut(receiver_id) else {
+        return false;
+    };
+    match live_equity {
+        Some(equity) => state.current_equity = equity,
+        None if config.auto_resume_min_equity.is_some() => return false,
+        None => {}
+    }
+    if !auto_resume_due(state, config, Utc::now()) {
+        return false;
+    }
+    let reason = state.pause_reason.clone().unwrap_or_default();
+    clear_pause(state);
+    state.last_updated = Some(Utc::now().to_rfc3339());
+    let equity = state.current_equity;
+    persist_state(&states);
+    drop(states);
+
+    tracing::info!(
+        "Auto-resumed receiver {} after its cooldown (equity ${:.2}; was paused: {})",
+        receiver_id, equity, reason
+    );
+    alerts::push_alert(
+        alerts::AlertSeverity::Info,
+        format!("Receiver {} auto-resumed after its safety pause ({})", receiver_id, reason),
+    );
+    true
+}
+
+/// Run `check_auto_resume` for every paused receiver, with the equity from
+/// its account info. Called from the queue worker.
+pub fn auto_resume_receivers(config: &CopierConfig) {
+    for receiver in &config.receivers {
+        let safety_config = super::event_processor::receiver_safety_config(receiver);
+        if safety_config.auto_resume_after_secs.is_some() && is_receiver_paused(&receiver.account_number) {
+            let equity = live_balance::live_receiver_heartbeat(&receiver.terminal_id).map(|hb| hb.equity);
+            check_auto_resume(&receiver.account_number, &safety_config, equity);
+        }
+    }
+}
+
 /// Check if receiver is safety paused
 pub fn is_receiver_paused(receiver_id: &str) -> bool {
     let states = SAFETY_STATE.lock();
@@ -559,6 +850,24 @@ pub fn clear_receiver_state(receiver_id: &str) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_loosens_stops() {
+        let buy = (Some(1.09), Some(1.12));
+        assert!(!loosens_stops("buy", buy, (Some(1.095), Some(1.11))));
+        assert!(!loosens_stops("buy", buy, (None, None)));
+        assert!(loosens_stops("buy", buy, (Some(1.08), None)));
+        assert!(loosens_stops("buy", buy, (None, Some(1.13))));
+        assert!(loosens_stops("buy", buy, (Some(0.0), None)));
+
+        let sell = (Some(1.12), Some(1.09));
+        assert!(!loosens_stops("sell", sell, (Some(1.11), Some(1.10))));
+        assert!(loosens_stops("sell", sell, (Some(1.13), None)));
+        assert!(loosens_stops("sell", sell, (None, Some(1.08))));
+
+        // Setting a level where there was none is never a loosening
+        assert!(!loosens_stops("buy", (None, Some(0.0)), (Some(1.05), Some(1.2))));
+    }
+
     #[test]
     fn test_safety_check_allowed() {
         let config = SafetyConfig::default();
@@ -585,6 +894,205 @@ mod tests {
         clear_receiver_state(receiver_id);
     }
     
+    #[test]
+    fn test_preview_blocks_without_pausing() {
+        let receiver_id = "test_preview_safety";
+        let config = SafetyConfig {
+            max_daily_loss_amount: Some(300.0),
+            ..Default::default()
+        };
+        let today = get_trading_day(Utc::now(), get_daily_reset_hour()).format("%Y-%m-%d").to_string();
+        update_receiver_state(
+            receiver_id,
+            ReceiverSafetyState {
+                daily_pnl: -350.0,
+                last_reset_date: Some(today),
+                ..Default::default()
+            },
+        );
+
+        assert!(matches!(preview_trade_safety(receiver_id, &config, 10000.0), SafetyCheckResult::Blocked(_)));
+        assert!(!get_receiver_state(receiver_id).is_safety_paused);
+
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10000.0), SafetyCheckResult::Blocked(_)));
+        assert!(get_receiver_state(receiver_id).is_safety_paused);
+        clear_receiver_state(receiver_id);
+    }
+
+    #[test]
+    fn test_profit_target_locks_until_manual_unpause() {
+        let receiver_id = "test_profit_target";
+        clear_receiver_state(receiver_id);
+        let config = SafetyConfig {
+            profit_target_percent: Some(8.0),
+            ..Default::default()
+        };
+
+        assert_eq!(update_equity(receiver_id, 10500.0, 10000.0, &config), None);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10000.0), SafetyCheckResult::Allowed));
+
+        let reason = update_equity(receiver_id, 10850.0, 10000.0, &config).unwrap();
+        assert!(reason.starts_with("Profit target reached"));
+        // Only the crossing asks for a close-all
+        assert_eq!(update_equity(receiver_id, 10900.0, 10000.0, &config), None);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10900.0), SafetyCheckResult::Blocked(_)));
+
+        // A new trading day doesn't lift the lock
+        let mut state = get_receiver_state(receiver_id);
+        state.set_last_reset_date(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap());
+        update_receiver_state(receiver_id, state);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10900.0), SafetyCheckResult::Blocked(_)));
+        assert!(is_receiver_paused(receiver_id));
+
+        // Unpausing acknowledges the target: the same config doesn't lock
+        // again until equity gains another 8% on 10900
+        unpause_receiver(receiver_id);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10900.0), SafetyCheckResult::Allowed));
+        assert_eq!(update_equity(receiver_id, 11000.0, 10000.0, &config), None);
+        assert!(update_equity(receiver_id, 11800.0, 10000.0, &config).is_some());
+
+        clear_receiver_state(receiver_id);
+    }
+
+    fn paused_for_loss(minutes_ago: i64, equity: f64) -> ReceiverSafetyState {
+        ReceiverSafetyState {
+            is_safety_paused: true,
+            auto_resumable: true,
+            pause_reason: Some("Daily loss limit reached".to_string()),
+            paused_at: Some((Utc::now() - chrono::Duration::minutes(minutes_ago)).to_rfc3339()),
+            current_equity: equity,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_auto_resume_after_cooldown_with_recovery() {
+        let receiver_id = "test_auto_resume";
+        let config = SafetyConfig {
+            auto_resume_after_secs: Some(3600),
+            auto_resume_min_equity: Some(9800.0),
+            ..Default::default()
+        };
+
+        // Cooldown not over yet
+        update_receiver_state(receiver_id, paused_for_loss(30, 9900.0));
+        assert!(!check_auto_resume(receiver_id, &config, Some(9900.0)));
+        assert!(is_receiver_paused(receiver_id));
+
+        // The equity recorded at the last trade is stale: the live reading
+        // decides, and without one the receiver stays paused
+        update_receiver_state(receiver_id, paused_for_loss(61, 9900.0));
+        assert!(!check_auto_resume(receiver_id, &config, None));
+        assert!(!check_auto_resume(receiver_id, &config, Some(9700.0)));
+        update_receiver_state(receiver_id, paused_for_loss(61, 9700.0));
+        assert!(check_auto_resume(receiver_id, &config, Some(9900.0)));
+        let state = get_receiver_state(receiver_id);
+        assert!(!state.is_safety_paused);
+        assert_eq!((state.pause_reason, state.paused_at), (None, None));
+
+        clear_receiver_state(receiver_id);
+    }
+
+    #[test]
+    fn test_auto_resume_without_recovery_stays_paused() {
+        let config = SafetyConfig {
+            auto_resume_after_secs: Some(3600),
+            auto_resume_min_equity: Some(9800.0),
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        // Cooldown over, but equity still below the threshold
+        assert!(!auto_resume_due(&paused_for_loss(90, 9700.0), &config, now));
+        assert!(auto_resume_due(&paused_for_loss(90, 9800.0), &config, now));
+
+        // Profit-target and manual pauses never auto-resume
+        let profit_target = ReceiverSafetyState {
+            profit_target_locked: true,
+            ..paused_for_loss(90, 11000.0)
+        };
+        assert!(!auto_resume_due(&profit_target, &config, now));
+        let manual = ReceiverSafetyState {
+            auto_resumable: false,
+            ..paused_for_loss(90, 11000.0)
+        };
+        assert!(!auto_resume_due(&manual, &config, now));
+
+        // Off unless configured
+        assert!(!auto_resume_due(&paused_for_loss(90, 11000.0), &SafetyConfig::default(), now));
+    }
+
+    #[test]
+    fn test_profit_target_amount_blocks_in_trade_check() {
+        let receiver_id = "test_profit_target_amount";
+        clear_receiver_state(receiver_id);
+        let config = SafetyConfig {
+            profit_target_amount: Some(500.0),
+            ..Default::default()
+        };
+        let mut state = get_receiver_state(receiver_id);
+        state.starting_balance = 10000.0;
+        state.current_equity = 10499.0;
+        update_receiver_state(receiver_id, state);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10000.0), SafetyCheckResult::Allowed));
+
+        let mut state = get_receiver_state(receiver_id);
+        state.current_equity = 10500.0;
+        update_receiver_state(receiver_id, state);
+        assert!(matches!(check_trade_safety(receiver_id, &config, 10000.0), SafetyCheckResult::Blocked(_)));
+        assert!(get_receiver_state(receiver_id).profit_target_locked);
+
+        clear_receiver_state(receiver_id);
+    }
+
+    #[test]
+    fn test_safety_state_written_to_given_folder() {
+        let receiver_id = "test_safety_state_folder";
+        let dir = std::env::temp_dir().join(format!("saturn_safety_dir_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SAFETY_STATE_FILE);
+        let states = HashMap::from([(
+            receiver_id.to_string(),
+            ReceiverSafetyState { daily_pnl: -20.0, ..Default::default() },
+        )]);
+
+        save_safety_state_to(&path, &states).unwrap();
+        assert!(load_safety_state_from(&path).contains_key(receiver_id));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_exported_state_imports_back() {
+        let receiver_id = "test_safety_import";
+        let near_limit = ReceiverSafetyState {
+            daily_pnl: -290.0,
+            starting_balance: 10000.0,
+            current_equity: 9710.0,
+            high_water_mark: 10000.0,
+            trades_today: 4,
+            losses_today: 4,
+            consecutive_losses: 4,
+            ..Default::default()
+        };
+        import_receiver_state(receiver_id, near_limit).unwrap();
+
+        let exported = get_receiver_state(receiver_id);
+        assert_eq!(exported.daily_pnl, -290.0);
+        assert_eq!(exported.consecutive_losses, 4);
+        // Stamped as today's, so the daily reset leaves it in place
+        assert!(exported.last_reset_date.is_some());
+        check_daily_reset(receiver_id);
+        assert_eq!(get_receiver_state(receiver_id).daily_pnl, -290.0);
+
+        let json = serde_json::to_string(&exported).unwrap();
+        import_receiver_state("test_safety_import_copy", serde_json::from_str(&json).unwrap()).unwrap();
+        let copy = get_receiver_state("test_safety_import_copy");
+        assert_eq!((copy.daily_pnl, copy.last_reset_date), (exported.daily_pnl, exported.last_reset_date));
+
+        clear_receiver_state(receiver_id);
+        clear_receiver_state("test_safety_import_copy");
+    }
+
     #[test]
     fn test_trading_day_calculation() {
         use chrono::TimeZone;
@@ -0,0 +1,322 @@
+//! End-to-end copy self-test
+//!
+//! Checks one receiver's whole pipeline without trading:
+//! 1. `dry_run`: a synthetic entry is routed through `process_event_dry_run`
+//!    and must come out as "copy"
+//! 2. `write`: a `cmd_<timestamp>.json` with action "selftest" is written to
+//!    the receiver's CopierCommands folder
+//! 3. `pickup`: the receiver EA reads the command (its response appears or
+//!    the command file is removed)
+//! 4. `response`: the EA's `resp_<timestamp>.json` acknowledges it. The EA
+//!    answers "selftest" commands with a success response and places no order.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::event_processor::process_event_dry_run;
+use super::trade_executor::TradeResponse;
+use super::{CopierConfig, CopierState, ReceiverConfig, TradeEvent};
+
+/// How long the EA gets to pick up and answer the test command. Its timer
+/// runs every second; allow for a busy terminal.
+pub const SELFTEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// One stage of a self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestStage {
+    /// dry_run, write, pickup or response
+    pub name: String,
+    pub passed: bool,
+    /// Time the stage took; None if it didn't run
+    pub elapsed_ms: Option<u64>,
+    pub detail: Option<String>,
+}
+
+/// Outcome of `run_copy_selftest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestResult {
+    pub receiver_id: String,
+    /// Every stage passed
+    pub passed: bool,
+    pub stages: Vec<SelftestStage>,
+    pub total_ms: u64,
+}
+
+fn stage(name: &str, passed: bool, started: Instant, detail: Option<String>) -> SelftestStage {
+    SelftestStage {
+        name: name.to_string(),
+        passed,
+        elapsed_ms: Some(started.elapsed().as_millis() as u64),
+        detail,
+    }
+}
+
+fn skipped(name: &str) -> SelftestStage {
+    SelftestStage {
+        name: name.to_string(),
+        passed: false,
+        elapsed_ms: None,
+        detail: Some("Skipped after an earlier stage failed".to_string()),
+    }
+}
+
+/// Entry the dry run routes: a minimum-lot buy in the receiver's first
+/// enabled mapped symbol
+fn synthetic_entry(receiver: &ReceiverConfig) -> TradeEvent {
+    let symbol = receiver
+        .symbol_mappings
+        .iter()
+        .find(|m| m.is_enabled)
+        .map(|m| m.master_symbol.clone())
+        .unwrap_or_else(|| "EURUSD".to_string());
+    let now = chrono::Utc::now().to_rfc3339();
+    TradeEvent {
+        event_type: "entry".to_string(),
+        ticket: 0,
+        deal_id: None,
+        symbol,
+        direction: "buy".to_string(),
+        lots: 0.01,
+        price: 0.0,
+        sl: None,
+        tp: None,
+        timestamp: now.clone(),
+        sl_distance_points: None,
+        tp_distance_points: None,
+        master_balance: None,
+        master_equity: None,
+        tick_value: None,
+        contract_size: None,
+        digits: None,
+        point: None,
+        terminal_id: None,
+        master_account_number: None,
+        idempotency_key: None,
+        partial_close_data: None,
+        order_type: None,
+        order_ticket: None,
+        detected_at: Some(now),
+    }
+}
+
+/// Route the synthetic entry to this receiver alone
+fn dry_run_stage(config: &CopierConfig, receiver: &ReceiverConfig) -> SelftestStage {
+    let started = Instant::now();
+    let single = CopierConfig {
+        receivers: vec![receiver.clone()],
+        ..config.clone()
+    };
+    let event = synthetic_entry(receiver);
+    match process_event_dry_run(&event, &single).into_iter().next() {
+        Some(route) if route.outcome == "copy" => stage(
+            "dry_run",
+            true,
+            started,
+            Some(format!("{} would be copied at {:.2} lots", event.symbol, route.lots.unwrap_or_default())),
+        ),
+        Some(route) => stage(
+            "dry_run",
+            false,
+            started,
+            Some(match route.reason {
+                Some(reason) => format!("Routed as {}: {}", route.outcome, reason),
+                None => format!("Routed as {}", route.outcome),
+            }),
+        ),
+        None => stage("dry_run", false, started, Some("No route for the receiver".to_string())),
+    }
+}
+
+fn write_selftest_command(commands_folder: &Path, command_path: &Path, timestamp: i64) -> std::io::Result<()> {
+    fs::create_dir_all(commands_folder)?;
+    let json = serde_json::json!({
+        "action": "selftest",
+        "symbol": "",
+        "direction": "",
+        "lots": 0.0,
+        "timestamp": timestamp,
+    });
+    let temp_path = command_path.with_extension("json.tmp");
+    fs::write(&temp_path, json.to_string())?;
+    fs::rename(&temp_path, command_path)
+}
+
+/// Write, pickup and response stages through `<files>/CopierCommands`;
+/// split out so tests can use a temp folder
+fn command_round_trip(terminal_id: &str, files_path: &Path, timeout: Duration) -> Vec<SelftestStage> {
+    let commands_folder = files_path.join("CopierCommands");
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let command_path = commands_folder.join(format!("cmd_{}.json", timestamp));
+    let response_path = commands_folder.join(format!("resp_{}.json", timestamp));
+
+    let started = Instant::now();
+    let written = super::file_lock::with_terminal_lock(terminal_id, || {
+        write_selftest_command(&commands_folder, &command_path, timestamp)
+    });
+    if let Err(e) = written {
+        return vec![
+            stage("write", false, started, Some(format!("Cannot write to {}: {}", commands_folder.display(), e))),
+            skipped("pickup"),
+            skipped("response"),
+        ];
+    }
+    let mut stages = vec![stage("write", true, started, Some(command_path.display().to_string()))];
+
+    let deadline = Instant::now() + timeout;
+    let started = Instant::now();
+    while command_path.exists() && !response_path.exists() && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    if command_path.exists() && !response_path.exists() {
+        // Don't leave the command behind for a late EA to answer
+        let _ = fs::remove_file(&command_path);
+        stages.push(stage(
+            "pickup",
+            false,
+            started,
+            Some("The receiver EA didn't pick up the command - is it attached and running?".to_string()),
+        ));
+        stages.push(skipped("response"));
+        return stages;
+    }
+    stages.push(stage("pickup", true, started, None));
+
+    let started = Instant::now();
+    let response = loop {
+        let content = fs::read_to_string(&response_path).ok().filter(|c| !c.trim().is_empty());
+        if let Some(content) = content {
+            let _ = fs::remove_file(&response_path);
+            break Some(serde_json::from_str::<TradeResponse>(&content).map_err(|e| e.to_string()));
+        }
+        if Instant::now() >= deadline {
+            break None;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    stages.push(match response {
+        Some(Ok(response)) if response.success => stage("response", true, started, None),
+        Some(Ok(response)) => stage(
+            "response",
+            false,
+            started,
+            Some(format!(
+                "The EA answered with an error: {} (it may predate self-test support)",
+                response.error.unwrap_or_else(|| "unknown error".to_string())
+            )),
+        ),
+        Some(Err(e)) => stage("response", false, started, Some(format!("Unreadable response: {}", e))),
+        None => stage("response", false, started, Some("The EA picked up the command but didn't answer".to_string())),
+    });
+    stages
+}
+
+/// Self-test one receiver (by `account_id`). Blocks for up to
+/// `SELFTEST_TIMEOUT` waiting on the EA. No order is placed.
+pub fn run_copy_selftest(state: &Arc<Mutex<CopierState>>, receiver_id: &str) -> Result<SelftestResult, String> {
+    let (config, receiver) = {
+        let copier = state.lock();
+        let config = copier.config.clone().ok_or("No copier config loaded")?;
+        let receiver = config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .cloned()
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+        (config, receiver)
+    };
+
+    let started = Instant::now();
+    let mut stages = vec![dry_run_stage(&config, &receiver)];
+    match super::config_generator::get_terminal_files_path(&receiver.terminal_id) {
+        Some(files_path) => stages.extend(command_round_trip(&receiver.terminal_id, &files_path, SELFTEST_TIMEOUT)),
+        None => {
+            stages.push(SelftestStage {
+                detail: Some(format!("Could not find MQL5/Files for terminal {}", receiver.terminal_id)),
+                ..skipped("write")
+            });
+            stages.push(skipped("pickup"));
+            stages.push(skipped("response"));
+        }
+    }
+
+    Ok(SelftestResult {
+        receiver_id: receiver_id.to_string(),
+        passed: stages.iter().all(|s| s.passed),
+        stages,
+        total_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_files_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("saturn_selftest_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_round_trip_with_ea_ack() {
+        let dir = temp_files_dir();
+        let commands = dir.join("CopierCommands");
+
+        // Simulated EA: acknowledge the selftest command as the receiver does
+        let ea_commands = commands.clone();
+        let ea = std::thread::spawn(move || {
+            for _ in 0..200 {
+                let command = fs::read_dir(&ea_commands).ok().and_then(|entries| {
+                    entries
+                        .flatten()
+                        .map(|e| e.path())
+                        .find(|p| p.extension().is_some_and(|ext| ext == "json"))
+                });
+                if let Some(path) = command {
+                    let v: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+                    assert_eq!(v["action"], "selftest");
+                    let response = serde_json::json!({
+                        "success": true,
+                        "executed_price": 0.0,
+                        "slippage_pips": 0.0,
+                        "receiver_position_id": 0,
+                        "timestamp": 0,
+                    });
+                    let resp_path = ea_commands.join(format!("resp_{}.json", v["timestamp"]));
+                    fs::write(&resp_path, response.to_string()).unwrap();
+                    fs::remove_file(&path).unwrap();
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        let stages = command_round_trip("T-SELFTEST", &dir, Duration::from_secs(5));
+        ea.join().unwrap();
+
+        let names: Vec<&str> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["write", "pickup", "response"]);
+        assert!(stages.iter().all(|s| s.passed && s.elapsed_ms.is_some()), "{:?}", stages);
+        assert_eq!(fs::read_dir(&commands).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_no_ea_fails_pickup() {
+        let dir = temp_files_dir();
+
+        let stages = command_round_trip("T-SELFTEST", &dir, Duration::from_millis(100));
+        assert!(stages[0].passed);
+        assert!(!stages[1].passed);
+        assert!(stages[2].elapsed_ms.is_none());
+        // The unanswered command is withdrawn
+        assert_eq!(fs::read_dir(dir.join("CopierCommands")).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
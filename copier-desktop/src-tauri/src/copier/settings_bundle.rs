@@ -0,0 +1,490 @@
+//! Settings bundle export/import
+//!
+//! Moves this machine's local settings to another install (or into a
+//! backup) as one JSON file: manual terminal paths, the safety reset hour,
+//! market hours, queue watch settings, saved mapping profiles and the cached
+//! copier config. The API key and request signing secret are left out
+//! unless the export asks for them, since the file is plain JSON.
+//!
+//! Importing is two steps. `stage_import` reads and validates a bundle and
+//! reports what it contains without touching anything; `confirm_import`
+//! then writes the staged bundle over the current settings.
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use super::mapping_profiles::{self, MappingProfile};
+use super::market_hours::{self, MarketCalendar};
+use super::safety;
+use super::watch_settings::{self, WatchSettings};
+use super::CopierConfig;
+use crate::mt5::discovery::{self, ManualTerminal};
+
+/// Newest bundle format this build reads
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: String,
+    /// Only present when the export included it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Request signing secret; only present when the export included the
+    /// API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    #[serde(default)]
+    pub manual_terminals: Vec<ManualTerminal>,
+    /// Hour (UTC) daily safety counters reset at
+    pub daily_reset_hour_utc: i32,
+    #[serde(default)]
+    pub market_hours: MarketCalendar,
+    #[serde(default)]
+    pub watch_settings: WatchSettings,
+    #[serde(default)]
+    pub mapping_profiles: Vec<MappingProfile>,
+    /// Last config synced from the cloud, for offline starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_config: Option<CopierConfig>,
+}
+
+/// What a bundle contains, shown before an import is confirmed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSummary {
+    pub exported_at: String,
+    pub has_api_key: bool,
+    pub has_signing_secret: bool,
+    pub manual_terminals: usize,
+    pub daily_reset_hour_utc: i32,
+    pub mapping_profiles: Vec<String>,
+    /// Version of the cached copier config, if the bundle has one
+    pub cached_config_version: Option<i32>,
+}
+
+impl SettingsBundle {
+    pub fn summary(&self) -> BundleSummary {
+        BundleSummary {
+            exported_at: self.exported_at.clone(),
+            has_api_key: self.api_key.is_some(),
+            has_signing_secret: self.signing_secret.is_some(),
+            manual_terminals: self.manual_terminals.len(),
+            daily_reset_hour_utc: self.daily_reset_hour_utc,
+            mapping_profiles: self.mapping_profiles.iter().map(|p| p.name.clone()).collect(),
+            cached_config_version: self.cached_config.as_ref().map(|c| c.version),
+        }
+    }
+
+    /// Reject bundles that would restore settings the app can't use
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version == 0 || self.version > BUNDLE_VERSION {
+            return Err(format!(
+                "Settings bundle version {} is not supported (this app reads up to {})",
+                self.version, BUNDLE_VERSION
+            ));
+        }
+        if !(0..=23).contains(&self.daily_reset_hour_utc) {
+            return Err(format!("Invalid daily reset hour {}", self.daily_reset_hour_utc));
+        }
+        if self.api_key.as_deref().is_some_and(|key| key.trim().is_empty()) {
+            return Err("Settings bundle has an empty API key".to_string());
+        }
+        if self.signing_secret.as_deref().is_some_and(|secret| secret.trim().is_empty()) {
+            return Err("Settings bundle has an empty signing secret".to_string());
+        }
+        for window in &self.market_hours.closed {
+            for time in [window.from, window.to] {
+                if time.hour > 23 || time.minute > 59 {
+                    return Err(format!("Invalid market hours time {:02}:{:02}", time.hour, time.minute));
+                }
+            }
+        }
+        if self.mapping_profiles.iter().any(|p| p.name.trim().is_empty()) {
+            return Err("Settings bundle has a mapping profile without a name".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Gather the current settings into a bundle
+pub fn collect(include_api_key: bool) -> SettingsBundle {
+    let (api_key, signing_secret) = if include_api_key {
+        (
+            crate::sync::config::load_api_key().ok().filter(|key| !key.is_empty()),
+            crate::sync::config::load_signing_secret(),
+        )
+    } else {
+        (None, None)
+    };
+    let mapping_profiles = mapping_profiles::list_profiles()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| match mapping_profiles::load_profile(name) {
+            Ok(profile) => Some(profile),
+            Err(e) => {
+                warn!("Leaving mapping profile '{}' out of the settings bundle: {}", name, e);
+                None
+            }
+        })
+        .collect();
+
+    SettingsBundle {
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        api_key,
+        signing_secret,
+        manual_terminals: discovery::manual_terminals(),
+        daily_reset_hour_utc: safety::get_daily_reset_hour(),
+        market_hours: market_hours::current(),
+        watch_settings: watch_settings::current(),
+        mapping_profiles,
+        cached_config: crate::sync::config::load_cached_config(),
+    }
+}
+
+/// Write a bundle to `path` (atomic write)
+pub fn write_bundle(path: &Path, bundle: &SettingsBundle) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(bundle).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder for settings bundle: {}", e))?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write settings bundle: {}", e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to save settings bundle: {}", e))
+}
+
+/// Read and validate a bundle
+pub fn read_bundle(path: &Path) -> Result<SettingsBundle, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read settings bundle: {}", e))?;
+    let bundle: SettingsBundle =
+        serde_json::from_str(&content).map_err(|e| format!("Not a valid settings bundle: {}", e))?;
+    bundle.validate()?;
+    Ok(bundle)
+}
+
+/// Export the current settings to `path`
+pub fn export(path: &Path, include_api_key: bool) -> Result<BundleSummary, String> {
+    let bundle = collect(include_api_key);
+    write_bundle(path, &bundle)?;
+    info!("Exported settings bundle to {:?}", path);
+    Ok(bundle.summary())
+}
+
+/// Bundle waiting for `confirm_import`
+type StagedBundle = Mutex<Option<SettingsBundle>>;
+
+static STAGED: LazyLock<StagedBundle> = LazyLock::new(|| Mutex::new(None));
+
+/// Where a restored bundle's sections are written
+trait SettingsStore {
+    fn set_manual_terminals(&mut self, terminals: Vec<ManualTerminal>) -> Result<(), String>;
+    fn set_daily_reset_hour(&mut self, hour: i32) -> Result<(), String>;
+    fn set_market_hours(&mut self, calendar: MarketCalendar) -> Result<(), String>;
+    fn set_watch_settings(&mut self, settings: WatchSettings) -> Result<(), String>;
+    fn import_profile(&mut self, profile: &MappingProfile) -> Result<(), String>;
+    fn save_api_key(&mut self, api_key: &str) -> Result<(), String>;
+    fn save_signing_secret(&mut self, secret: &str) -> Result<(), String>;
+    fn cache_config(&mut self, config: &CopierConfig) -> Result<(), String>;
+}
+
+/// The app's own settings files
+struct LiveSettings;
+
+impl SettingsStore for LiveSettings {
+    fn set_manual_terminals(&mut self, terminals: Vec<ManualTerminal>) -> Result<(), String> {
+        discovery::set_manual_terminals(terminals).map_err(|e| e.to_string())?;
+        discovery::refresh_discovery_cache();
+        Ok(())
+    }
+
+    fn set_daily_reset_hour(&mut self, hour: i32) -> Result<(), String> {
+        safety::set_daily_reset_hour(hour);
+        safety::save_all_safety_states()
+    }
+
+    fn set_market_hours(&mut self, calendar: MarketCalendar) -> Result<(), String> {
+        market_hours::set(calendar)
+    }
+
+    fn set_watch_settings(&mut self, settings: WatchSettings) -> Result<(), String> {
+        watch_settings::set(settings)
+    }
+
+    fn import_profile(&mut self, profile: &MappingProfile) -> Result<(), String> {
+        mapping_profiles::import_profile(profile).map_err(|e| e.to_string())
+    }
+
+    fn save_api_key(&mut self, api_key: &str) -> Result<(), String> {
+        crate::sync::config::save_api_key(api_key).map_err(|e| format!("Failed to save API key: {}", e))
+    }
+
+    fn save_signing_secret(&mut self, secret: &str) -> Result<(), String> {
+        crate::sync::config::save_signing_secret(secret).map_err(|e| format!("Failed to save signing secret: {}", e))
+    }
+
+    fn cache_config(&mut self, config: &CopierConfig) -> Result<(), String> {
+        crate::sync::config::cache_config(config).map_err(|e| format!("Failed to cache copier config: {}", e))
+    }
+}
+
+/// Read and validate a bundle and hold it for `confirm_import`. Nothing is
+/// changed yet.
+pub fn stage_import(path: &Path) -> Result<BundleSummary, String> {
+    stage_import_into(&STAGED, path)
+}
+
+fn stage_import_into(staged: &StagedBundle, path: &Path) -> Result<BundleSummary, String> {
+    let bundle = read_bundle(path)?;
+    let summary = bundle.summary();
+    *staged.lock() = Some(bundle);
+    Ok(summary)
+}
+
+/// Restore the staged bundle over the current settings. Returns its API
+/// key, if it carried one, for the caller to make live.
+pub fn confirm_import() -> Result<Option<String>, String> {
+    confirm_import_from(&STAGED, &mut LiveSettings)
+}
+
+fn confirm_import_from(staged: &StagedBundle, store: &mut impl SettingsStore) -> Result<Option<String>, String> {
+    let bundle = staged.lock().take().ok_or("No settings import is waiting for confirmation")?;
+    restore(&bundle, store)?;
+    info!("Imported settings bundle exported at {}", bundle.exported_at);
+    Ok(bundle.api_key)
+}
+
+/// Write every section of a validated bundle
+fn restore(bundle: &SettingsBundle, store: &mut impl SettingsStore) -> Result<(), String> {
+    store.set_manual_terminals(bundle.manual_terminals.clone())?;
+    store.set_daily_reset_hour(bundle.daily_reset_hour_utc)?;
+    store.set_market_hours(bundle.market_hours.clone())?;
+    store.set_watch_settings(bundle.watch_settings)?;
+
+    for profile in &bundle.mapping_profiles {
+        store.import_profile(profile)?;
+    }
+
+    if let Some(api_key) = &bundle.api_key {
+        store.save_api_key(api_key.trim())?;
+    }
+    if let Some(secret) = &bundle.signing_secret {
+        store.save_signing_secret(secret.trim())?;
+    }
+    if let Some(config) = &bundle.cached_config {
+        store.cache_config(config)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::market_hours::{ClosedWindow, WeeklyTime};
+    use crate::copier::symbol_catalog::SymbolMapping;
+    use crate::copier::watch_settings::WatchMode;
+    use chrono::Weekday;
+
+    fn bundle() -> SettingsBundle {
+        SettingsBundle {
+            version: BUNDLE_VERSION,
+            exported_at: "2024-01-01T00:00:00+00:00".to_string(),
+            api_key: Some("key-123".to_string()),
+            signing_secret: Some("secret-456".to_string()),
+            manual_terminals: vec![ManualTerminal {
+                path: "D:\\MT5\\terminal64.exe".to_string(),
+                added_at: "2023-12-01T00:00:00+00:00".to_string(),
+            }],
+            daily_reset_hour_utc: 22,
+            market_hours: MarketCalendar {
+                enabled: true,
+                closed: vec![ClosedWindow {
+                    from: WeeklyTime {
+                        weekday: Weekday::Fri,
+                        hour: 21,
+                        minute: 0,
+                    },
+                    to: WeeklyTime {
+                        weekday: Weekday::Sun,
+                        hour: 23,
+                        minute: 5,
+                    },
+                }],
+            },
+            watch_settings: WatchSettings {
+                mode: WatchMode::Poll,
+                poll_interval_ms: 250,
+                ..WatchSettings::default()
+            },
+            mapping_profiles: vec![MappingProfile {
+                name: "ICM".to_string(),
+                created_at: "2023-11-01T00:00:00+00:00".to_string(),
+                mappings: vec![SymbolMapping {
+                    master_symbol: "XAUUSD".to_string(),
+                    receiver_symbol: "GOLD.r".to_string(),
+                    is_enabled: true,
+                    auto_mapped: false,
+                    match_method: "manual".to_string(),
+                    confidence: 100,
+                }],
+            }],
+            cached_config: Some(
+                serde_json::from_value(serde_json::json!({
+                    "version": 7,
+                    "config_hash": "h",
+                    "master": {"account_id": "m1", "account_number": "1001", "broker": "A", "terminal_id": "M1"},
+                    "receivers": []
+                }))
+                .unwrap(),
+            ),
+        }
+    }
+
+    /// Records what a restore wrote
+    #[derive(Default)]
+    struct RecordedSettings {
+        manual_terminals: Vec<ManualTerminal>,
+        daily_reset_hour: Option<i32>,
+        market_hours: Option<MarketCalendar>,
+        watch_settings: Option<WatchSettings>,
+        profiles: Vec<String>,
+        api_key: Option<String>,
+        signing_secret: Option<String>,
+        config_version: Option<i32>,
+    }
+
+    impl SettingsStore for RecordedSettings {
+        fn set_manual_terminals(&mut self, terminals: Vec<ManualTerminal>) -> Result<(), String> {
+            self.manual_terminals = terminals;
+            Ok(())
+        }
+
+        fn set_daily_reset_hour(&mut self, hour: i32) -> Result<(), String> {
+            self.daily_reset_hour = Some(hour);
+            Ok(())
+        }
+
+        fn set_market_hours(&mut self, calendar: MarketCalendar) -> Result<(), String> {
+            self.market_hours = Some(calendar);
+            Ok(())
+        }
+
+        fn set_watch_settings(&mut self, settings: WatchSettings) -> Result<(), String> {
+            self.watch_settings = Some(settings);
+            Ok(())
+        }
+
+        fn import_profile(&mut self, profile: &MappingProfile) -> Result<(), String> {
+            self.profiles.push(profile.name.clone());
+            Ok(())
+        }
+
+        fn save_api_key(&mut self, api_key: &str) -> Result<(), String> {
+            self.api_key = Some(api_key.to_string());
+            Ok(())
+        }
+
+        fn save_signing_secret(&mut self, secret: &str) -> Result<(), String> {
+            self.signing_secret = Some(secret.to_string());
+            Ok(())
+        }
+
+        fn cache_config(&mut self, config: &CopierConfig) -> Result<(), String> {
+            self.config_version = Some(config.version);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_round_trip_preserves_every_section() {
+        let dir = std::env::temp_dir().join(format!("saturn_bundle_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("settings.json");
+        let original = bundle();
+
+        write_bundle(&path, &original).unwrap();
+        let imported = read_bundle(&path).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&imported).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+        let summary = imported.summary();
+        assert!(summary.has_api_key);
+        assert!(summary.has_signing_secret);
+        assert_eq!(summary.manual_terminals, 1);
+        assert_eq!(summary.mapping_profiles, vec!["ICM"]);
+        assert_eq!(summary.cached_config_version, Some(7));
+
+        // Without the secrets their fields are left out of the file altogether
+        let redacted = SettingsBundle {
+            api_key: None,
+            signing_secret: None,
+            ..original
+        };
+        write_bundle(&path, &redacted).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("api_key") && !written.contains("signing_secret"));
+        assert!(read_bundle(&path).unwrap().api_key.is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_invalid_bundles_are_rejected() {
+        let future = SettingsBundle {
+            version: BUNDLE_VERSION + 1,
+            ..bundle()
+        };
+        assert!(future.validate().is_err());
+        let bad_hour = SettingsBundle {
+            daily_reset_hour_utc: 24,
+            ..bundle()
+        };
+        assert!(bad_hour.validate().is_err());
+        assert!(bundle().validate().is_ok());
+    }
+
+    #[test]
+    fn test_staged_import_restores_every_section_once_confirmed() {
+        let dir = std::env::temp_dir().join(format!("saturn_bundle_import_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("settings.json");
+        write_bundle(&path, &bundle()).unwrap();
+        let staged = StagedBundle::default();
+        let mut store = RecordedSettings::default();
+
+        // Nothing to confirm yet
+        assert!(confirm_import_from(&staged, &mut store).is_err());
+
+        let summary = stage_import_into(&staged, &path).unwrap();
+        assert_eq!(summary.daily_reset_hour_utc, 22);
+        // Staging alone writes nothing
+        assert!(store.daily_reset_hour.is_none());
+
+        let api_key = confirm_import_from(&staged, &mut store).unwrap();
+        assert_eq!(api_key.as_deref(), Some("key-123"));
+        assert_eq!(store.manual_terminals.len(), 1);
+        assert_eq!(store.daily_reset_hour, Some(22));
+        assert!(store.market_hours.as_ref().is_some_and(|calendar| calendar.enabled));
+        assert_eq!(store.watch_settings.map(|s| s.poll_interval_ms), Some(250));
+        assert_eq!(store.profiles, vec!["ICM"]);
+        assert_eq!(store.api_key.as_deref(), Some("key-123"));
+        assert_eq!(store.signing_secret.as_deref(), Some("secret-456"));
+        assert_eq!(store.config_version, Some(7));
+
+        // The staged bundle is used up
+        assert!(confirm_import_from(&staged, &mut store).is_err());
+
+        // An invalid bundle is never staged
+        let bad = SettingsBundle {
+            daily_reset_hour_utc: 30,
+            ..bundle()
+        };
+        write_bundle(&path, &bad).unwrap();
+        assert!(stage_import_into(&staged, &path).is_err());
+        assert!(staged.lock().is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
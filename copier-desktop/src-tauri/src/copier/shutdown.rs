@@ -0,0 +1,102 @@
+//! Graceful shutdown
+//!
+//! On quit the background loops are stopped, executions already in flight
+//! get a short window to finish, and safety state plus the execution queue
+//! are written to disk before the process exits. Anything still in
+//! progress when the window closes is persisted as in-progress and comes
+//! back as pending on the next start (see `ExecutionQueue::load_from_disk`).
+
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::execution_queue::{SharedExecutionQueue, EXECUTION_QUEUE};
+use super::{file_watcher, safety};
+
+/// How long to wait for in-progress executions before exiting anyway
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Poll interval while draining
+const DRAIN_POLL: Duration = Duration::from_millis(50);
+
+/// Wait for the queue's in-progress executions to finish (or be requeued).
+/// Returns false if some were still running after `timeout`.
+fn wait_for_in_progress(queue: &SharedExecutionQueue, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while queue.in_progress_count() > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(DRAIN_POLL);
+    }
+    true
+}
+
+/// Save safety state and the execution queue. Also used when the window is
+/// closed to the tray, where the app keeps running.
+pub fn persist_state() {
+    persist_queue_and_safety(&EXECUTION_QUEUE);
+}
+
+fn persist_queue_and_safety(queue: &SharedExecutionQueue) {
+    if let Err(e) = safety::save_all_safety_states() {
+        warn!("Failed to save safety states: {}", e);
+    }
+    if let Err(e) = queue.persist() {
+        warn!("Failed to persist execution queue: {}", e);
+    }
+}
+
+/// Stop the background loops with `stop_loops`, drain and persist `queue`
+fn shutdown(queue: &SharedExecutionQueue, drain_timeout: Duration, stop_loops: impl FnOnce()) {
+    stop_loops();
+
+    if !wait_for_in_progress(queue, drain_timeout) {
+        warn!(
+            "{} execution(s) still in progress at shutdown - they will be retried on next start",
+            queue.in_progress_count()
+        );
+    }
+
+    persist_queue_and_safety(queue);
+    info!("Shutdown complete ({} pending execution(s) saved)", queue.pending_count());
+}
+
+/// Stop copying, persist state and exit the process
+pub fn graceful_shutdown() -> ! {
+    info!("Graceful shutdown started");
+    // Stops the file watcher and the queue worker at their next check
+    shutdown(&EXECUTION_QUEUE, DRAIN_TIMEOUT, file_watcher::request_shutdown);
+    std::process::exit(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copier::execution_queue::{ExecutionQueue, QueuedExecution};
+    use crate::copier::test_support::test_event;
+    use chrono::Utc;
+
+    #[test]
+    fn test_shutdown_persists_pending_queue_entries() {
+        let path = std::env::temp_dir().join(format!("saturn_shutdown_test_{}.json", uuid::Uuid::new_v4()));
+        let queue = SharedExecutionQueue::new(ExecutionQueue::new(Some(path.clone())));
+        let later = Utc::now() + chrono::Duration::minutes(5);
+        queue.update(|q| {
+            q.defer(QueuedExecution::new(test_event("entry", 7), "R1", "k1"), later, "rate limited");
+            q.defer(QueuedExecution::new(test_event("entry", 7), "R2", "k2"), Utc::now(), "rate limited");
+            // One execution is mid-flight and never finishes
+            q.dequeue_ready(Utc::now()).unwrap();
+        });
+
+        // The real shutdown flag would stop every other test's watcher
+        let mut stopped = false;
+        shutdown(&queue, Duration::from_millis(100), || stopped = true);
+
+        assert!(stopped);
+        assert!(path.exists());
+        let restored = ExecutionQueue::load_from_disk(path.clone());
+        assert_eq!(restored.pending_count(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,170 @@
+//! Slippage and price tolerance units
+//!
+//! "Pips" mean different things per symbol: 10 points on a 5-digit EURUSD
+//! or 3-digit USDJPY quote, one point on an index. `SlippageSpec` carries a
+//! value with its unit and converts it with the symbol's `point`/`digits`
+//! (from the symbol catalog or the tick file), using the same pip
+//! convention as the receiver EA (`ticks::pip_size`). Crypto has no FX pip:
+//! a pip there is one point, whatever the digits, and is converted to the
+//! EA's pips before it's sent (`to_ea_pips`).
+
+use serde::{Deserialize, Serialize};
+
+use super::symbol_catalog::{self, SymbolSpec};
+use super::ticks::pip_size;
+
+/// Unit a slippage or tolerance value is given in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlippageUnit {
+    #[default]
+    Pips,
+    Points,
+    /// Absolute price distance
+    Price,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SlippageSpec {
+    pub value: f64,
+    pub unit: SlippageUnit,
+}
+
+/// Point size and digits of one symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceScale {
+    pub point: f64,
+    pub digits: i32,
+    /// One pip is one point (crypto)
+    pub point_pips: bool,
+}
+
+impl PriceScale {
+    pub fn new(point: f64, digits: i32) -> Self {
+        Self {
+            point,
+            digits,
+            point_pips: false,
+        }
+    }
+
+    pub fn from_spec(spec: &SymbolSpec) -> Self {
+        Self::new(spec.tick_size, spec.digits).for_symbol(&spec.name)
+    }
+
+    /// This scale with `symbol`'s pip convention
+    pub fn for_symbol(self, symbol: &str) -> Self {
+        Self {
+            point_pips: symbol_catalog::is_crypto_symbol(symbol),
+            ..self
+        }
+    }
+
+    fn pip(&self) -> f64 {
+        if self.point_pips {
+            self.point
+        } else {
+            self.ea_pip()
+        }
+    }
+
+    /// Pip as the receiver EA counts it, FX convention on every symbol
+    fn ea_pip(&self) -> f64 {
+        pip_size(self.point, self.digits)
+    }
+}
+
+impl SlippageSpec {
+    pub fn new(value: f64, unit: SlippageUnit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn pips(value: f64) -> Self {
+        Self::new(value, SlippageUnit::Pips)
+    }
+
+    /// Absolute price distance on a symbol
+    pub fn to_price(self, scale: PriceScale) -> f64 {
+        match self.unit {
+            SlippageUnit::Pips => self.value * scale.pip(),
+            SlippageUnit::Points => self.value * scale.point,
+            SlippageUnit::Price => self.value,
+        }
+    }
+
+    /// Distance in points of a symbol (0 when the point size is unknown)
+    pub fn to_points(self, scale: PriceScale) -> f64 {
+        match self.unit {
+            SlippageUnit::Points => self.value,
+            _ if scale.point > 0.0 => self.to_price(scale) / scale.point,
+            _ => 0.0,
+        }
+    }
+
+    /// Distance in the receiver EA's pips (0 when the point size is
+    /// unknown). Same as `to_pips` except on crypto.
+    pub fn to_ea_pips(self, scale: PriceScale) -> f64 {
+        match self.unit {
+            SlippageUnit::Pips if !scale.point_pips => self.value,
+            _ if scale.ea_pip() > 0.0 => self.to_price(scale) / scale.ea_pip(),
+            _ => 0.0,
+        }
+    }
+
+    /// Distance in pips of a symbol (0 when the point size is unknown)
+    pub fn to_pips(self, scale: PriceScale) -> f64 {
+        match self.unit {
+            SlippageUnit::Pips => self.value,
+            _ if scale.pip() > 0.0 => self.to_price(scale) / scale.pip(),
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_three_pips_to_price() {
+        let three_pips = SlippageSpec::pips(3.0);
+        let eurusd = PriceScale::new(0.00001, 5);
+        let usdjpy = PriceScale::new(0.001, 3);
+        let us30 = PriceScale::new(0.01, 2);
+
+        assert!(close(three_pips.to_price(eurusd), 0.0003));
+        assert!(close(three_pips.to_price(usdjpy), 0.03));
+        assert!(close(three_pips.to_price(us30), 0.03));
+
+        assert!(close(three_pips.to_points(eurusd), 30.0));
+        assert!(close(three_pips.to_points(us30), 3.0));
+    }
+
+    #[test]
+    fn test_units_round_trip() {
+        let usdjpy = PriceScale::new(0.001, 3);
+        assert!(close(SlippageSpec::new(25.0, SlippageUnit::Points).to_pips(usdjpy), 2.5));
+        assert!(close(SlippageSpec::new(0.05, SlippageUnit::Price).to_pips(usdjpy), 5.0));
+        assert!(close(SlippageSpec::new(0.05, SlippageUnit::Price).to_points(usdjpy), 50.0));
+        // No scale known: nothing to convert with
+        assert_eq!(SlippageSpec::new(0.05, SlippageUnit::Price).to_pips(PriceScale::new(0.0, 0)), 0.0);
+    }
+
+    #[test]
+    fn test_crypto_pips_are_catalog_points() {
+        // XRPUSD quoted to 5 digits: an FX pip would be 10 points
+        let xrpusd = PriceScale::new(0.00001, 5).for_symbol("XRPUSD");
+        let fifty_pips = SlippageSpec::pips(50.0);
+        assert!(close(fifty_pips.to_price(xrpusd), 0.0005));
+        assert!(close(fifty_pips.to_points(xrpusd), 50.0));
+        // The EA counts 10 points per pip on 5 digits
+        assert!(close(fifty_pips.to_ea_pips(xrpusd), 5.0));
+
+        let eurusd = PriceScale::new(0.00001, 5).for_symbol("EURUSD");
+        assert_eq!(fifty_pips.to_ea_pips(eurusd), 50.0);
+    }
+}
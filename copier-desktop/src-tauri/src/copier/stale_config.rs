@@ -0,0 +1,143 @@
+//! Stale config watchdog
+//!
+//! When the cloud can't be reached (an expired API key, say) the app keeps
+//! copying with the last config it has, possibly for days after the master
+//! changed accounts. Once `last_sync` is older than the threshold (default
+//! 24h, persisted per machine), the health snapshot warns and the queue
+//! worker raises one alert prompting a resync; the alert re-arms after the
+//! next successful sync.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use tracing::warn;
+
+use super::alerts::{self, AlertSeverity};
+use super::CopierState;
+use crate::data_dir::app_data_dir;
+
+const SETTINGS_FILE: &str = "stale_config.json";
+
+/// Config age that counts as stale unless configured otherwise
+pub const DEFAULT_STALE_AFTER_SECS: u64 = 24 * 3600;
+
+/// Shortest threshold accepted, so a typo can't alert on every pass
+pub const MIN_STALE_AFTER_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleConfigSettings {
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+}
+
+fn default_stale_after_secs() -> u64 {
+    DEFAULT_STALE_AFTER_SECS
+}
+
+impl Default for StaleConfigSettings {
+    fn default() -> Self {
+        Self {
+            stale_after_secs: DEFAULT_STALE_AFTER_SECS,
+        }
+    }
+}
+
+static SETTINGS: LazyLock<Mutex<StaleConfigSettings>> = LazyLock::new(|| Mutex::new(load()));
+
+/// The stale alert was raised and the config hasn't been synced since
+static ALERTED: AtomicBool = AtomicBool::new(false);
+
+fn get_settings_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(SETTINGS_FILE))
+}
+
+fn load() -> StaleConfigSettings {
+    let Some(path) = get_settings_path() else {
+        return StaleConfigSettings::default();
+    };
+    match fs::read_to_string(&path).map(|c| serde_json::from_str(&c)) {
+        Ok(Ok(settings)) => settings,
+        Ok(Err(e)) => {
+            warn!("Ignoring unreadable stale config settings file: {}", e);
+            StaleConfigSettings::default()
+        }
+        Err(_) => StaleConfigSettings::default(),
+    }
+}
+
+/// Current settings
+pub fn current() -> StaleConfigSettings {
+    *SETTINGS.lock()
+}
+
+/// Replace the settings and persist them (atomic write)
+pub fn set(settings: StaleConfigSettings) -> Result<(), String> {
+    let settings = StaleConfigSettings {
+        stale_after_secs: settings.stale_after_secs.max(MIN_STALE_AFTER_SECS),
+    };
+    if let Some(path) = get_settings_path() {
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write stale config settings: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save stale config settings: {}", e))?;
+    }
+    *SETTINGS.lock() = settings;
+    Ok(())
+}
+
+/// Seconds since the config last came from the cloud (None if it never has)
+pub fn config_age_secs(last_sync: Option<&str>, now: DateTime<Utc>) -> Option<u64> {
+    let synced = DateTime::parse_from_rfc3339(last_sync?).ok()?;
+    Some(now.signed_duration_since(synced).num_seconds().max(0) as u64)
+}
+
+/// Warning for a config older than `stale_after_secs`
+pub fn stale_config_warning(age_secs: Option<u64>, stale_after_secs: u64) -> Option<String> {
+    let age = age_secs.filter(|age| *age > stale_after_secs)?;
+    Some(format!(
+        "Config last synced {}h ago - resync to pick up changes (check the API key is still valid)",
+        age / 3600
+    ))
+}
+
+/// Alert once when the active config goes stale. Called from the queue
+/// worker while copying.
+pub fn watch(state: &CopierState) {
+    let age = config_age_secs(state.last_sync.as_deref(), Utc::now());
+    match stale_config_warning(age, current().stale_after_secs) {
+        Some(warning) => {
+            if !ALERTED.swap(true, Ordering::Relaxed) {
+                alerts::push_alert(AlertSeverity::Warning, warning);
+            }
+        }
+        None => ALERTED.store(false, Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_age_and_warning() {
+        let now = Utc::now();
+        let two_days_ago = (now - chrono::Duration::days(2)).to_rfc3339();
+        let age = config_age_secs(Some(&two_days_ago), now);
+        assert_eq!(age, Some(2 * 24 * 3600));
+
+        let warning = stale_config_warning(age, DEFAULT_STALE_AFTER_SECS).unwrap();
+        assert!(warning.starts_with("Config last synced 48h ago"));
+
+        // Fresh, or never synced
+        let an_hour_ago = (now - chrono::Duration::hours(1)).to_rfc3339();
+        assert!(stale_config_warning(config_age_secs(Some(&an_hour_ago), now), DEFAULT_STALE_AFTER_SECS).is_none());
+        assert!(stale_config_warning(config_age_secs(None, now), DEFAULT_STALE_AFTER_SECS).is_none());
+    }
+}
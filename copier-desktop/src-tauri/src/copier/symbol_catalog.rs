@@ -8,6 +8,10 @@ use std::collections::HashMap;
 use std::path::Path;
 use tracing::{debug, info, warn};
 
+use super::symbol_rules::{self, SymbolRules};
+use super::lot_calculator::{self, LotRounding};
+use super::{file_lock, CopierError};
+
 /// Symbol specification from MT5
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolSpec {
@@ -29,12 +33,35 @@ pub struct SymbolSpec {
     pub profit_currency: Option<String>,
 }
 
+impl SymbolSpec {
+    /// Whether the broker allows every kind of trade on the symbol. An
+    /// unreported trade mode counts as full.
+    pub fn allows_full_trading(&self) -> bool {
+        matches!(self.trade_mode.as_deref(), None | Some("full"))
+    }
+
+    /// Whether the broker lets a new `direction` ("buy"/"sell") position be
+    /// opened. Closes are allowed in every mode but "disabled".
+    pub fn allows_opening(&self, direction: &str) -> bool {
+        match self.trade_mode.as_deref() {
+            Some("disabled") | Some("close_only") => false,
+            Some("long_only") => direction == "buy",
+            Some("short_only") => direction == "sell",
+            _ => true,
+        }
+    }
+}
+
 /// Symbol catalog for a terminal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolCatalog {
     pub terminal_id: String,
     pub symbols: Vec<SymbolSpec>,
     pub fetched_at: String,
+    /// Suffix shared by most of this broker's symbols (e.g. ".pro", "m"),
+    /// detected from the catalog. `None` means the static list is used.
+    #[serde(default)]
+    pub broker_suffix: Option<String>,
 }
 
 /// Symbol mapping between master and receiver
@@ -45,7 +72,7 @@ pub struct SymbolMapping {
     pub is_enabled: bool,
     #[serde(default)]
     pub auto_mapped: bool,
-    /// How the match was made: exact, normalized, specs, specs_ambiguous, manual
+    /// How the match was made: exact, normalized, broker_hint, specs, specs_ambiguous, spec_mismatch, conflict, manual
     #[serde(default)]
     pub match_method: String,
     /// Confidence score 0-100
@@ -53,7 +80,8 @@ pub struct SymbolMapping {
     pub confidence: u8,
 }
 
-/// Common suffixes to strip when normalizing symbols
+/// Common suffixes to strip when normalizing symbols; `symbol_rules.json`
+/// can add more
 const SYMBOL_SUFFIXES: &[&str] = &[
     ".m", ".pro", ".cash", ".a", ".i", ".raw", ".ecn", ".stp", ".std",
     "_m", "_pro", "_cash", "_raw", "_ecn", "_stp",
@@ -61,50 +89,144 @@ const SYMBOL_SUFFIXES: &[&str] = &[
     "m", "pro", // Single letter suffixes (careful with these)
 ];
 
-/// Normalize a symbol name for matching
+/// Normalize a symbol name for matching, using the built-in suffixes plus
+/// the user's `symbol_rules.json`
 pub fn normalize_symbol(name: &str) -> String {
+    normalize_symbol_with_rules(name, symbol_rules::current())
+}
+
+/// Normalize with explicit user rules (already uppercased): drop an ignored
+/// prefix, resolve an alias, else strip one suffix and resolve again
+pub fn normalize_symbol_with_rules(name: &str, rules: &SymbolRules) -> String {
     let mut result = name.to_uppercase();
+
+    if let Some(prefix) = rules
+        .ignore_prefixes
+        .iter()
+        .find(|p| result.starts_with(p.as_str()) && result.len() > p.len())
+    {
+        result = result[prefix.len()..].to_string();
+    }
+    if let Some(alias) = rules.aliases.get(&result) {
+        return alias.clone();
+    }
     
     // Sort suffixes by length (longest first) to avoid partial matches
-    let mut suffixes: Vec<&str> = SYMBOL_SUFFIXES.to_vec();
+    let mut suffixes: Vec<String> = SYMBOL_SUFFIXES
+        .iter()
+        .map(|s| s.to_uppercase())
+        .chain(rules.suffixes.iter().cloned())
+        .collect();
     suffixes.sort_by(|a, b| b.len().cmp(&a.len()));
     
-    for suffix in suffixes {
-        let upper_suffix = suffix.to_uppercase();
+    for upper_suffix in suffixes {
         if result.ends_with(&upper_suffix) && result.len() > upper_suffix.len() {
             result = result[..result.len() - upper_suffix.len()].to_string();
             break; // Only strip one suffix
         }
     }
     
-    result
+    rules.aliases.get(&result).cloned().unwrap_or(result)
+}
+
+/// Minimum share of the catalog that must carry a suffix for it to count as
+/// the broker's suffix
+const DOMINANT_SUFFIX_RATIO: f64 = 0.6;
+
+/// Trailing token that could be a broker suffix: everything from the last
+/// `.`/`_`, or a trailing run of lowercase letters after an uppercase base
+/// (MT5 bases are uppercase, so "EURUSDm" -> "m" but "US30" -> none).
+fn trailing_token(name: &str) -> Option<&str> {
+    if let Some(idx) = name.rfind(['.', '_']) {
+        return (idx > 0 && idx + 1 < name.len()).then(|| &name[idx..]);
+    }
+    let base_len = name.trim_end_matches(|c: char| c.is_ascii_lowercase()).len();
+    (base_len > 0 && base_len < name.len()).then(|| &name[base_len..])
+}
+
+/// Infer a broker's symbol suffix from its full catalog.
+///
+/// Returns the trailing token shared by a clear majority of symbols, or `None`
+/// when no token dominates (mixed or unsuffixed catalogs).
+pub fn detect_broker_suffix<'a>(names: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut total = 0usize;
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        total += 1;
+        if let Some(token) = trailing_token(name) {
+            *counts.entry(token).or_insert(0) += 1;
+        }
+    }
+
+    let (suffix, count) = counts.into_iter().max_by_key(|(_, c)| *c)?;
+    if total >= 3 && count as f64 / total as f64 >= DOMINANT_SUFFIX_RATIO {
+        Some(suffix.to_string())
+    } else {
+        None
+    }
+}
+
+/// Normalize using a detected broker suffix: strip only that suffix, so names
+/// that merely end in suffix-like letters are left alone. Falls back to the
+/// static list in `normalize_symbol` when no suffix was detected.
+pub fn normalize_symbol_with_suffix(name: &str, broker_suffix: Option<&str>) -> String {
+    let Some(suffix) = broker_suffix else {
+        return normalize_symbol(name);
+    };
+
+    let upper = name.to_uppercase();
+    let upper_suffix = suffix.to_uppercase();
+    if upper.ends_with(&upper_suffix) && upper.len() > upper_suffix.len() {
+        upper[..upper.len() - upper_suffix.len()].to_string()
+    } else {
+        upper
+    }
 }
 
 /// Fetch symbol catalog from a receiver terminal
-pub fn fetch_symbol_catalog(terminal_id: &str) -> Result<SymbolCatalog, String> {
+pub fn fetch_symbol_catalog(terminal_id: &str) -> Result<SymbolCatalog, CopierError> {
     let files_path = get_terminal_files_path(terminal_id)?;
+    load_symbol_catalog(terminal_id, &files_path)
+}
+
+/// Read `CopierSymbolCatalog.json` from a terminal's MQL5/Files folder
+fn load_symbol_catalog(terminal_id: &str, files_path: &Path) -> Result<SymbolCatalog, CopierError> {
     let catalog_file = files_path.join("CopierSymbolCatalog.json");
     
     if !catalog_file.exists() {
         // Try to trigger catalog generation by writing a request file
         let request_file = files_path.join("CopierCommands").join("request_symbols.json");
-        if let Some(parent) = request_file.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-        let _ = std::fs::write(&request_file, r#"{"action": "export_symbols"}"#);
+        file_lock::with_terminal_lock(terminal_id, || {
+            if let Some(parent) = request_file.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let temp_file = request_file.with_extension("json.tmp");
+            if std::fs::write(&temp_file, r#"{"action": "export_symbols"}"#).is_ok() {
+                let _ = std::fs::rename(&temp_file, &request_file);
+            }
+        });
         
-        return Err("Symbol catalog not available. Attach Receiver EA to generate it.".to_string());
+        return Err(CopierError::EaNotAttached(
+            "Symbol catalog not available. Attach Receiver EA to generate it.".to_string(),
+        ));
     }
     
-    let content = std::fs::read_to_string(&catalog_file)
-        .map_err(|e| format!("Failed to read symbol catalog: {}", e))?;
+    let content = file_lock::read_contended(&catalog_file)
+        .map_err(|e| CopierError::io("Failed to read symbol catalog", e))?;
     
     let raw: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse symbol catalog: {}", e))?;
+        .map_err(|e| CopierError::parse("Failed to parse symbol catalog", e))?;
     
     let symbols_array = raw.get("symbols")
         .and_then(|v| v.as_array())
-        .ok_or("Invalid symbol catalog format")?;
+        .ok_or_else(|| CopierError::ParseError("Invalid symbol catalog format".to_string()))?;
+    
+    let broker_suffix = detect_broker_suffix(
+        symbols_array.iter().filter_map(|sym| sym.get("name").and_then(|v| v.as_str())),
+    );
+    if let Some(suffix) = &broker_suffix {
+        debug!("Detected broker suffix {:?} for terminal {}", suffix, terminal_id);
+    }
     
     let mut symbols = Vec::new();
     for sym in symbols_array {
@@ -115,7 +237,7 @@ pub fn fetch_symbol_catalog(terminal_id: &str) -> Result<SymbolCatalog, String>
         
         symbols.push(SymbolSpec {
             name: name.clone(),
-            normalized_key: normalize_symbol(&name),
+            normalized_key: normalize_symbol_with_suffix(&name, broker_suffix.as_deref()),
             tick_value: sym.get("tick_value").and_then(|v| v.as_f64()).unwrap_or(1.0),
             tick_size: sym.get("tick_size").and_then(|v| v.as_f64()).unwrap_or(0.00001),
             contract_size: sym.get("contract_size").and_then(|v| v.as_f64()).unwrap_or(100000.0),
@@ -135,19 +257,20 @@ pub fn fetch_symbol_catalog(terminal_id: &str) -> Result<SymbolCatalog, String>
         terminal_id: terminal_id.to_string(),
         symbols,
         fetched_at: chrono::Utc::now().to_rfc3339(),
+        broker_suffix,
     })
 }
 
 /// Get master symbols from open positions file
-pub fn get_master_symbols(terminal_id: &str) -> Result<Vec<String>, String> {
+pub fn get_master_symbols(terminal_id: &str) -> Result<Vec<String>, CopierError> {
     let files_path = get_terminal_files_path(terminal_id)?;
     let positions_file = files_path.join("CopierQueue").join("open_positions.json");
     
     let mut symbols = Vec::new();
     
     if positions_file.exists() {
-        let content = std::fs::read_to_string(&positions_file)
-            .map_err(|e| format!("Failed to read positions: {}", e))?;
+        let content = file_lock::read_contended(&positions_file)
+            .map_err(|e| CopierError::io("Failed to read positions", e))?;
         
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
             if let Some(positions) = json.get("positions").and_then(|v| v.as_array()) {
@@ -181,13 +304,18 @@ pub fn get_master_symbols(terminal_id: &str) -> Result<Vec<String>, String> {
     Ok(symbols)
 }
 
+/// Whether two symbols have the same contract size (within 1%)
+fn contract_sizes_match(a: &SymbolSpec, b: &SymbolSpec) -> bool {
+    (a.contract_size - b.contract_size).abs() < 0.01
+        || (a.contract_size > 0.0 && b.contract_size > 0.0
+            && ((a.contract_size / b.contract_size) - 1.0).abs() < 0.01)
+}
+
 /// Check if two symbols match by contract specifications
 /// CRITICAL: This is the primary matching method per requirements
 fn specs_match(a: &SymbolSpec, b: &SymbolSpec) -> bool {
     // Contract size must match exactly (within tolerance)
-    let contract_match = (a.contract_size - b.contract_size).abs() < 0.01 
-        || (a.contract_size > 0.0 && b.contract_size > 0.0 
-            && ((a.contract_size / b.contract_size) - 1.0).abs() < 0.01);
+    let contract_match = contract_sizes_match(a, b);
     
     // Digits must match exactly
     let digits_match = a.digits == b.digits;
@@ -213,14 +341,135 @@ fn specs_match(a: &SymbolSpec, b: &SymbolSpec) -> bool {
     contract_match && digits_match && tick_size_match && currency_match
 }
 
+/// Instrument category, picking how strictly specs have to agree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolCategory {
+    Fx,
+    Metal,
+    Index,
+    Crypto,
+    Other,
+}
+
+/// Index families: the names brokers list an index under, and keywords
+/// their descriptions use for it
+const INDEX_FAMILIES: &[(&str, &[&str], &[&str])] = &[
+    ("DJ30", &["US30", "DJ30", "WS30", "USA30", "DOW", "WALLST"], &["dow jones", "wall street"]),
+    ("NAS100", &["NAS100", "US100", "USTEC", "USTECH", "NDX"], &["nasdaq"]),
+    ("SPX500", &["US500", "SPX", "SP500", "USA500"], &["s&p", "sp 500"]),
+    ("DAX", &["GER40", "GER30", "DE40", "DE30", "DAX"], &["dax", "germany 40"]),
+    ("FTSE100", &["UK100", "FTSE"], &["ftse"]),
+    ("NIKKEI225", &["JP225", "JPN225", "NI225", "NIKKEI"], &["nikkei"]),
+    ("ASX200", &["AUS200", "ASX"], &["asx", "australia 200"]),
+];
+
+const METAL_PATTERNS: &[&str] = &["XAU", "XAG", "XPT", "XPD", "GOLD", "SILVER"];
+const CRYPTO_PATTERNS: &[&str] = &["BTC", "ETH", "XRP", "LTC", "SOL", "DOGE", "CRYPTO"];
+
+/// Index family from the description's keywords, else the name. The flag
+/// says whether the description identified it.
+fn index_family(spec: &SymbolSpec) -> Option<(&'static str, bool)> {
+    let description = spec.description.as_deref().unwrap_or("").to_lowercase();
+    let by_description = INDEX_FAMILIES
+        .iter()
+        .find(|(_, _, keywords)| keywords.iter().any(|k| description.contains(k)))
+        .map(|(family, _, _)| (*family, true));
+    by_description.or_else(|| {
+        let upper = spec.name.to_uppercase();
+        INDEX_FAMILIES
+            .iter()
+            .find(|(_, names, _)| names.iter().any(|n| upper.contains(n)))
+            .map(|(family, _, _)| (*family, false))
+    })
+}
+
+/// Whether a symbol is a cryptocurrency, by name. These trade around the
+/// clock, in fractional lots, and have no FX-style pip.
+pub fn is_crypto_symbol(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    !METAL_PATTERNS.iter().any(|p| upper.contains(p)) && CRYPTO_PATTERNS.iter().any(|p| upper.contains(p))
+}
+
+/// Categorize a symbol by name patterns (and description for indices)
+pub fn categorize_symbol(spec: &SymbolSpec) -> SymbolCategory {
+    let upper = spec.name.to_uppercase();
+    if METAL_PATTERNS.iter().any(|p| upper.contains(p)) {
+        SymbolCategory::Metal
+    } else if is_crypto_symbol(&spec.name) {
+        SymbolCategory::Crypto
+    } else if index_family(spec).is_some() {
+        SymbolCategory::Index
+    } else if spec.normalized_key.len() == 6 && spec.normalized_key.chars().all(|c| c.is_ascii_alphabetic()) {
+        SymbolCategory::Fx
+    } else {
+        SymbolCategory::Other
+    }
+}
+
+/// Account-currency value of a one-unit price move per unit of contract.
+/// Brokers quoting an index in 1 vs 0.1 lots differ in contract size,
+/// digits and tick value, but agree on this.
+fn unit_move_value(spec: &SymbolSpec) -> Option<f64> {
+    (spec.tick_size > 0.0 && spec.contract_size > 0.0 && spec.tick_value > 0.0)
+        .then(|| spec.tick_value / spec.tick_size / spec.contract_size)
+}
+
+/// Confidence of a relaxed index match
+const RELAXED_INDEX_CONFIDENCE: u8 = 75;
+
+/// Match method of a mapping whose receiver symbol carries a different
+/// exposure per lot. Lots aren't rescaled by contract size, so these are
+/// left disabled for the user to confirm.
+pub const SPEC_MISMATCH_METHOD: &str = "spec_mismatch";
+
+/// Whether a lot of `a` and a lot of `b` carry the same exposure: equal
+/// contract size and, where both report it, the value of a price move per
+/// contract unit within 20%
+fn lot_exposure_matches(a: &SymbolSpec, b: &SymbolSpec) -> bool {
+    if !contract_sizes_match(a, b) {
+        return false;
+    }
+    match (unit_move_value(a), unit_move_value(b)) {
+        (Some(value_a), Some(value_b)) => (0.8..1.25).contains(&(value_a / value_b)),
+        _ => true,
+    }
+}
+
+/// Relaxed policy for indices, used when no symbol matches strictly:
+/// digits and contract size may differ, but both symbols must be the same
+/// index family and agree on the value of a price move per contract unit
+/// (and on profit currency). Returns the match confidence. A match across
+/// contract sizes is still only a candidate: `auto_map_symbols_by_specs`
+/// leaves it disabled as `spec_mismatch`.
+fn relaxed_index_match(a: &SymbolSpec, b: &SymbolSpec) -> Option<u8> {
+    if categorize_symbol(a) != SymbolCategory::Index || categorize_symbol(b) != SymbolCategory::Index {
+        return None;
+    }
+    let ((family_a, described_a), (family_b, described_b)) = (index_family(a)?, index_family(b)?);
+    if family_a != family_b {
+        return None;
+    }
+    let ratio = unit_move_value(a)? / unit_move_value(b)?;
+    if !(0.8..1.25).contains(&ratio) {
+        return None;
+    }
+    if let (Some(a_curr), Some(b_curr)) = (&a.profit_currency, &b.profit_currency) {
+        if !a_curr.eq_ignore_ascii_case(b_curr) {
+            return None;
+        }
+    }
+    // Descriptions naming the index are better evidence than names
+    let bonus = 5 * (described_a as u8 + described_b as u8);
+    Some(RELAXED_INDEX_CONFIDENCE + bonus)
+}
+
 /// Calculate a match score for two symbols based on specifications
 fn calculate_spec_match_score(a: &SymbolSpec, b: &SymbolSpec) -> u8 {
     let mut score: u8 = 0;
     
     // Contract size match (20 points)
-    if (a.contract_size - b.contract_size).abs() < 0.01 
-        || (a.contract_size > 0.0 && b.contract_size > 0.0 
-            && ((a.contract_size / b.contract_size) - 1.0).abs() < 0.01) {
+    if contract_sizes_match(a, b) {
         score += 20;
     }
     
@@ -256,30 +505,171 @@ fn calculate_spec_match_score(a: &SymbolSpec, b: &SymbolSpec) -> u8 {
     }
     
     // Name similarity bonus (10 points)
-    if normalize_symbol(&a.name) == normalize_symbol(&b.name) {
+    if a.normalized_key == b.normalized_key {
         score += 10;
     }
     
     score
 }
 
+/// Two or more enabled master symbols mapped onto one receiver symbol,
+/// which would make closes on the receiver ambiguous
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingConflict {
+    pub receiver_symbol: String,
+    /// Master symbol whose mapping stayed enabled (highest confidence)
+    pub kept_master_symbol: String,
+    /// Master symbols whose mappings were disabled as `conflict`
+    pub disabled_master_symbols: Vec<String>,
+}
+
+/// Auto-mapping output plus the conflicts the user needs to resolve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoMapResult {
+    pub mappings: Vec<SymbolMapping>,
+    pub conflicts: Vec<MappingConflict>,
+}
+
+/// Disable all but the highest-confidence enabled mapping per receiver
+/// symbol (ties keep the first). Disabled mappings get
+/// `match_method: "conflict"`.
+pub fn resolve_mapping_conflicts(mappings: &mut [SymbolMapping]) -> Vec<MappingConflict> {
+    let mut by_receiver: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, m) in mappings.iter().enumerate().filter(|(_, m)| m.is_enabled) {
+        by_receiver.entry(m.receiver_symbol.clone()).or_default().push(i);
+    }
+
+    let mut conflicts = Vec::new();
+    for (receiver_symbol, indices) in by_receiver.into_iter().filter(|(_, v)| v.len() > 1) {
+        // max_by_key returns the last maximum; reverse so ties keep the first
+        let kept = *indices
+            .iter()
+            .rev()
+            .max_by_key(|&&i| mappings[i].confidence)
+            .expect("conflict has at least two mappings");
+
+        let mut disabled_master_symbols = Vec::new();
+        for &i in indices.iter().filter(|&&i| i != kept) {
+            let mapping = &mut mappings[i];
+            mapping.is_enabled = false;
+            mapping.match_method = "conflict".to_string();
+            disabled_master_symbols.push(mapping.master_symbol.clone());
+        }
+
+        warn!(
+            "Symbol mapping conflict on {}: kept {}, disabled {:?}",
+            receiver_symbol, mappings[kept].master_symbol, disabled_master_symbols
+        );
+        conflicts.push(MappingConflict {
+            receiver_symbol,
+            kept_master_symbol: mappings[kept].master_symbol.clone(),
+            disabled_master_symbols,
+        });
+    }
+
+    conflicts.sort_by(|a, b| a.receiver_symbol.cmp(&b.receiver_symbol));
+    conflicts
+}
+
+/// Disable mappings onto receiver symbols the broker doesn't fully allow
+/// trading (close-only, disabled, one direction only) for manual review,
+/// so they can't win a conflict or take copied entries
+fn flag_restricted_symbols(mappings: &mut [SymbolMapping], receiver_catalog: &SymbolCatalog) {
+    for mapping in mappings.iter_mut() {
+        let restricted = receiver_catalog
+            .symbols
+            .iter()
+            .find(|s| s.name == mapping.receiver_symbol)
+            .filter(|s| !s.allows_full_trading());
+        if let Some(spec) = restricted {
+            warn!(
+                "{} -> {}: receiver symbol trade mode is {}, mapping disabled",
+                mapping.master_symbol,
+                mapping.receiver_symbol,
+                spec.trade_mode.as_deref().unwrap_or_default()
+            );
+            mapping.is_enabled = false;
+            mapping.match_method = "symbol_disabled".to_string();
+        }
+    }
+}
+
+/// `name` without the catalog's detected broker suffix
+fn without_broker_suffix<'a>(name: &'a str, broker_suffix: Option<&str>) -> &'a str {
+    broker_suffix
+        .and_then(|suffix| name.strip_suffix(suffix))
+        .filter(|base| !base.is_empty())
+        .unwrap_or(name)
+}
+
+/// Receiver symbol a broker hint (`mapping_hints`) names for a master
+/// symbol, if the receiver's catalog has it. Both sides are compared with
+/// and without their broker suffix.
+fn hinted_receiver_symbol<'a>(
+    master_sym: &SymbolSpec,
+    master_catalog: &SymbolCatalog,
+    receiver_catalog: &'a SymbolCatalog,
+    hints: &HashMap<String, String>,
+) -> Option<&'a SymbolSpec> {
+    let hinted = hints
+        .get(&master_sym.name)
+        .or_else(|| hints.get(without_broker_suffix(&master_sym.name, master_catalog.broker_suffix.as_deref())))?;
+    receiver_catalog.symbols.iter().find(|s| {
+        s.name == *hinted || without_broker_suffix(&s.name, receiver_catalog.broker_suffix.as_deref()) == hinted
+    })
+}
+
 /// Auto-map master symbols to receiver symbols using SPECS-FIRST approach
 /// CRITICAL: Per requirements - "Do NOT map by symbol name. Map using: Contract size, Tick size, Tick value, Digits"
+/// Broker hints for the pair (`mapping_hints::hints_for`) are tried first.
+/// Many-to-one results are then resolved by `resolve_mapping_conflicts`.
 pub fn auto_map_symbols_by_specs(
     master_catalog: &SymbolCatalog,
     receiver_catalog: &SymbolCatalog,
-) -> Vec<SymbolMapping> {
+    hints: &HashMap<String, String>,
+) -> AutoMapResult {
     let mut mappings = Vec::new();
     
     for master_sym in &master_catalog.symbols {
+        // ========================================
+        // PRIORITY 0: Known broker-pair naming (bundled/user hints)
+        // ========================================
+        if let Some(receiver_sym) = hinted_receiver_symbol(master_sym, master_catalog, receiver_catalog, hints) {
+            // A hint only names the symbol; its specs still have to agree
+            let compatible = lot_exposure_matches(master_sym, receiver_sym);
+            if !compatible {
+                warn!(
+                    "{} -> {}: broker hint names a symbol with different contract specs, mapping disabled",
+                    master_sym.name, receiver_sym.name
+                );
+            }
+            mappings.push(SymbolMapping {
+                master_symbol: master_sym.name.clone(),
+                receiver_symbol: receiver_sym.name.clone(),
+                is_enabled: compatible,
+                auto_mapped: true,
+                match_method: if compatible { "broker_hint" } else { SPEC_MISMATCH_METHOD }.to_string(),
+                confidence: if compatible { 97 } else { 50 },
+            });
+            continue;
+        }
+
         // ========================================
         // PRIORITY 1: Match by CONTRACT SPECS FIRST
         // This is the most reliable method
         // ========================================
-        let spec_candidates: Vec<(&SymbolSpec, u8)> = receiver_catalog.symbols.iter()
+        let mut spec_candidates: Vec<(&SymbolSpec, u8)> = receiver_catalog.symbols.iter()
             .filter(|s| specs_match(master_sym, s))
             .map(|s| (s, calculate_spec_match_score(master_sym, s)))
             .collect();
+
+        // Indices rarely agree on digits/contract size across brokers;
+        // fall back to the relaxed index policy
+        if spec_candidates.is_empty() && categorize_symbol(master_sym) == SymbolCategory::Index {
+            spec_candidates = receiver_catalog.symbols.iter()
+                .filter_map(|s| relaxed_index_match(master_sym, s).map(|score| (s, score)))
+                .collect();
+        }
         
         if !spec_candidates.is_empty() {
             // Sort by score descending
@@ -289,6 +679,24 @@ pub fn auto_map_symbols_by_specs(
             let best_match = sorted[0];
             let is_unique = sorted.len() == 1 || sorted[0].1 > sorted[1].1;
             
+            if !contract_sizes_match(master_sym, best_match.0) {
+                // Relaxed index match across contract sizes: the receiver
+                // would get a different exposure per copied lot
+                warn!(
+                    "{} -> {}: contract size {} vs {}, mapping disabled",
+                    master_sym.name, best_match.0.name, master_sym.contract_size, best_match.0.contract_size
+                );
+                mappings.push(SymbolMapping {
+                    master_symbol: master_sym.name.clone(),
+                    receiver_symbol: best_match.0.name.clone(),
+                    is_enabled: false,
+                    auto_mapped: true,
+                    match_method: SPEC_MISMATCH_METHOD.to_string(),
+                    confidence: best_match.1.min(50),
+                });
+                continue;
+            }
+
             if is_unique {
                 // Unique best match by specs - high confidence
                 mappings.push(SymbolMapping {
@@ -303,7 +711,7 @@ pub fn auto_map_symbols_by_specs(
             } else {
                 // Multiple good matches - check if names help disambiguate
                 let name_matching: Vec<_> = sorted.iter()
-                    .filter(|(s, _)| normalize_symbol(&s.name) == normalize_symbol(&master_sym.name))
+                    .filter(|(s, _)| s.normalized_key == master_sym.normalized_key)
                     .collect();
                 
                 if name_matching.len() == 1 {
@@ -353,9 +761,8 @@ pub fn auto_map_symbols_by_specs(
         // ========================================
         // PRIORITY 3: Normalized name match (last resort)
         // ========================================
-        let master_normalized = normalize_symbol(&master_sym.name);
         if let Some(receiver_sym) = receiver_catalog.symbols.iter()
-            .find(|s| normalize_symbol(&s.name) == master_normalized) 
+            .find(|s| s.normalized_key == master_sym.normalized_key) 
         {
             mappings.push(SymbolMapping {
                 master_symbol: master_sym.name.clone(),
@@ -373,7 +780,9 @@ pub fn auto_map_symbols_by_specs(
     }
     
     info!("Auto-mapped {} symbols (specs-first approach)", mappings.len());
-    mappings
+    flag_restricted_symbols(&mut mappings, receiver_catalog);
+    let conflicts = resolve_mapping_conflicts(&mut mappings);
+    AutoMapResult { mappings, conflicts }
 }
 
 /// Legacy: Auto-map symbols between master and receiver by name only
@@ -433,31 +842,107 @@ pub fn auto_map_symbols(
 }
 
 /// Get terminal files path — delegates to the single source of truth in `mt5::bridge`.
-fn get_terminal_files_path(terminal_id: &str) -> Result<std::path::PathBuf, String> {
-    crate::mt5::bridge::resolve_files_path(terminal_id, false)
+fn get_terminal_files_path(terminal_id: &str) -> Result<std::path::PathBuf, CopierError> {
+    crate::mt5::bridge::resolve_files_path(terminal_id, false).map_err(CopierError::NotFound)
 }
 
-/// Clamp lots to the broker's valid range and round down to lot step.
+/// Relative change between intended and final lots above which the
+/// adjustment is surfaced to the user
+const MATERIAL_LOT_CHANGE: f64 = 0.2;
+
+/// Outcome of clamping a lot size to a symbol's broker specs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LotCalcResult {
+    pub lots: f64,
+    /// Raised to `min_lot` (the intended size was below the broker minimum)
+    pub was_clamped_min: bool,
+    /// Capped at `max_lot`
+    pub was_clamped_max: bool,
+    /// Intended minus final lots; negative when the size was rounded up
+    pub rounding_loss: f64,
+}
+
+impl LotCalcResult {
+    /// Result for lots that were not clamped (e.g. no catalog yet)
+    pub fn unchanged(lots: f64) -> Self {
+        Self {
+            lots,
+            was_clamped_min: false,
+            was_clamped_max: false,
+            rounding_loss: 0.0,
+        }
+    }
+
+    /// Size the calculator asked for before clamping
+    pub fn intended_lots(&self) -> f64 {
+        self.lots + self.rounding_loss
+    }
+
+    /// Whether clamping moved the size by more than `MATERIAL_LOT_CHANGE`
+    pub fn is_material(&self) -> bool {
+        let intended = self.intended_lots();
+        if intended <= 0.0 {
+            return self.lots > 0.0;
+        }
+        (self.rounding_loss / intended).abs() > MATERIAL_LOT_CHANGE
+    }
+
+    /// Short description for the execution log
+    pub fn describe(&self) -> String {
+        let reason = if self.was_clamped_min {
+            "raised to broker minimum"
+        } else if self.was_clamped_max {
+            "capped at broker maximum"
+        } else {
+            "rounded to lot step"
+        };
+        format!("{:.4} lots {} -> {}", self.intended_lots(), reason, self.lots)
+    }
+}
+
+/// Clamp lots to the symbol's max, lot step (rounding down) and min,
+/// reporting what changed.
 ///
-/// Centralized here so the live event path (`event_processor`) and any future
-/// preview/UI path use the same min/max/step semantics as the receiver EA.
-pub fn clamp_lots(lots: f64, symbol: &SymbolSpec) -> f64 {
+/// Centralized here so the live event path (`event_processor`) and the
+/// preview path use the same min/max/step semantics as the receiver EA.
+pub fn clamp_lots_detailed(lots: f64, symbol: &SymbolSpec) -> LotCalcResult {
+    clamp_lots_with(lots, symbol, LotRounding::Floor)
+}
+
+/// [`clamp_lots_detailed`] with the receiver's rounding strategy. A size
+/// rounded up past `max_lot` is brought back down to it.
+pub fn clamp_lots_with(lots: f64, symbol: &SymbolSpec, rounding: LotRounding) -> LotCalcResult {
     let mut result = lots;
+    let mut was_clamped_max = false;
+    let mut was_clamped_min = false;
 
     if result > symbol.max_lot && symbol.max_lot > 0.0 {
         result = symbol.max_lot;
+        was_clamped_max = true;
     }
 
     if symbol.lot_step > 0.0 {
-        result = (result / symbol.lot_step).floor() * symbol.lot_step;
+        result = rounding.to_step(result, symbol.lot_step);
+        if result > symbol.max_lot && symbol.max_lot > 0.0 {
+            result = LotRounding::Floor.to_step(symbol.max_lot, symbol.lot_step);
+            was_clamped_max = true;
+        }
     }
 
     if result < symbol.min_lot {
         result = symbol.min_lot;
+        was_clamped_min = true;
     }
 
-    // Round to 2 decimal places to avoid float drift
-    (result * 100.0).round() / 100.0
+    // Round away float drift, keeping the decimals of finer (crypto) steps
+    let result = lot_calculator::round_to_step_precision(result, symbol.lot_step);
+
+    LotCalcResult {
+        lots: result,
+        was_clamped_min,
+        was_clamped_max,
+        rounding_loss: lots - result,
+    }
 }
 
 
@@ -475,6 +960,24 @@ mod tests {
         assert_eq!(normalize_symbol("US100.cash"), "US100");
     }
 
+    #[test]
+    fn test_user_symbol_rules() {
+        let rules = symbol_rules::parse_rules(
+            r##"{"suffixes": [".x", "#"], "aliases": {"GOLD": "XAUUSD"}, "ignore_prefixes": ["m."]}"##,
+        )
+        .unwrap();
+
+        // User-added suffixes are stripped; without the rules they're kept
+        assert_eq!(normalize_symbol_with_rules("EURUSD.x", &rules), "EURUSD");
+        assert_eq!(normalize_symbol_with_rules("GBPUSD#", &rules), "GBPUSD");
+        assert_eq!(normalize_symbol_with_rules("EURUSD.x", &SymbolRules::default()), "EURUSD.X");
+        // Built-in suffixes still apply
+        assert_eq!(normalize_symbol_with_rules("EURUSD.pro", &rules), "EURUSD");
+
+        assert_eq!(normalize_symbol_with_rules("m.EURUSD", &rules), "EURUSD");
+        assert_eq!(normalize_symbol_with_rules("gold.x", &rules), "XAUUSD");
+    }
+
     #[test]
     fn test_clamp_lots() {
         let symbol = SymbolSpec {
@@ -492,9 +995,360 @@ mod tests {
             profit_currency: None,
         };
 
-        assert_eq!(clamp_lots(0.001, &symbol), 0.01);  // Below min
-        assert_eq!(clamp_lots(15.0, &symbol), 10.0);   // Above max
-        assert_eq!(clamp_lots(1.234, &symbol), 1.23);  // Round to step
+        let clamp_lots = |lots| clamp_lots_detailed(lots, &symbol).lots;
+        assert_eq!(clamp_lots(0.001), 0.01);  // Below min
+        assert_eq!(clamp_lots(15.0), 10.0);   // Above max
+        assert_eq!(clamp_lots(1.234), 1.23);  // Round to step
+    }
+
+    fn eurusd_spec() -> SymbolSpec {
+        SymbolSpec {
+            name: "EURUSD".to_string(),
+            normalized_key: "EURUSD".to_string(),
+            tick_value: 1.0,
+            tick_size: 0.00001,
+            contract_size: 100000.0,
+            digits: 5,
+            min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 10.0,
+            description: None,
+            trade_mode: None,
+            profit_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_clamp_lots_detailed_rounds_up_to_min_lot() {
+        // Tiny multiplier: 0.007 lots intended, broker minimum is 0.01
+        let result = clamp_lots_detailed(0.007, &eurusd_spec());
+        assert_eq!(result.lots, 0.01);
+        assert!(result.was_clamped_min);
+        assert!(!result.was_clamped_max);
+        assert!((result.rounding_loss - -0.003).abs() < 1e-9);
+        assert!((result.intended_lots() - 0.007).abs() < 1e-9);
+        assert!(result.is_material());
+        assert!(result.describe().contains("raised to broker minimum"));
+    }
+
+    #[test]
+    fn test_lot_rounding_strategies() {
+        let spec = eurusd_spec();
+        let clamp = |rounding| clamp_lots_with(0.137, &spec, rounding).lots;
+        assert_eq!(clamp(LotRounding::Floor), 0.13);
+        assert_eq!(clamp(LotRounding::Round), 0.14);
+        assert_eq!(clamp(LotRounding::Ceil), 0.14);
+        assert_eq!(clamp_lots_detailed(0.137, &spec).lots, 0.13);
+
+        // On-step sizes aren't moved by float drift
+        assert_eq!(clamp_lots_detailed(0.29, &spec).lots, 0.29);
+        assert_eq!(clamp_lots_with(0.29, &spec, LotRounding::Ceil).lots, 0.29);
+
+        // Ceil never exceeds the broker maximum
+        let odd_max = SymbolSpec {
+            max_lot: 9.995,
+            ..eurusd_spec()
+        };
+        let capped = clamp_lots_with(9.993, &odd_max, LotRounding::Ceil);
+        assert_eq!(capped.lots, 9.99);
+        assert!(capped.was_clamped_max);
+        assert_eq!(clamp_lots_with(15.0, &spec, LotRounding::Ceil).lots, 10.0);
+    }
+
+    #[test]
+    fn test_clamp_lots_detailed_step_rounding_not_material() {
+        let result = clamp_lots_detailed(1.234, &eurusd_spec());
+        assert_eq!(result.lots, 1.23);
+        assert!(!result.was_clamped_min && !result.was_clamped_max);
+        assert!(!result.is_material());
+
+        let capped = clamp_lots_detailed(15.0, &eurusd_spec());
+        assert!(capped.was_clamped_max);
+        assert!(capped.is_material());
+    }
+
+    fn index_spec(name: &str) -> SymbolSpec {
+        SymbolSpec {
+            name: name.to_string(),
+            normalized_key: normalize_symbol(name),
+            tick_value: 0.01,
+            tick_size: 0.01,
+            contract_size: 1.0,
+            digits: 2,
+            min_lot: 0.1,
+            lot_step: 0.1,
+            max_lot: 100.0,
+            description: None,
+            trade_mode: None,
+            profit_currency: Some("USD".to_string()),
+        }
+    }
+
+    fn catalog(terminal_id: &str, symbols: Vec<SymbolSpec>) -> SymbolCatalog {
+        SymbolCatalog {
+            terminal_id: terminal_id.to_string(),
+            symbols,
+            fetched_at: String::new(),
+            broker_suffix: None,
+        }
+    }
+
+    #[test]
+    fn test_two_masters_colliding_on_one_receiver_symbol() {
+        // Two feeds' names for the same index, one receiver symbol
+        let master = catalog("M1", vec![index_spec("US100"), index_spec("NAS100")]);
+        let receiver = catalog("R1", vec![index_spec("NAS100")]);
+
+        let result = auto_map_symbols_by_specs(&master, &receiver, &HashMap::new());
+        assert_eq!(result.mappings.len(), 2);
+        assert!(result.mappings.iter().all(|m| m.receiver_symbol == "NAS100"));
+
+        let enabled: Vec<_> = result.mappings.iter().filter(|m| m.is_enabled).collect();
+        assert_eq!(enabled.len(), 1);
+        assert_eq!(enabled[0].master_symbol, "NAS100");
+
+        let disabled = result.mappings.iter().find(|m| m.master_symbol == "US100").unwrap();
+        assert!(!disabled.is_enabled);
+        assert_eq!(disabled.match_method, "conflict");
+
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].receiver_symbol, "NAS100");
+        assert_eq!(result.conflicts[0].kept_master_symbol, "NAS100");
+        assert_eq!(result.conflicts[0].disabled_master_symbols, vec!["US100".to_string()]);
+    }
+
+    #[test]
+    fn test_close_only_receiver_symbol_is_flagged() {
+        let master = catalog("M1", vec![eurusd_spec(), index_spec("NAS100")]);
+        let receiver = catalog(
+            "R1",
+            vec![
+                SymbolSpec {
+                    trade_mode: Some("close_only".to_string()),
+                    ..eurusd_spec()
+                },
+                SymbolSpec {
+                    trade_mode: Some("full".to_string()),
+                    ..index_spec("NAS100")
+                },
+            ],
+        );
+
+        let result = auto_map_symbols_by_specs(&master, &receiver, &HashMap::new());
+        let eurusd = result.mappings.iter().find(|m| m.master_symbol == "EURUSD").unwrap();
+        assert_eq!(eurusd.receiver_symbol, "EURUSD");
+        assert!(!eurusd.is_enabled);
+        assert_eq!(eurusd.match_method, "symbol_disabled");
+        let nas = result.mappings.iter().find(|m| m.master_symbol == "NAS100").unwrap();
+        assert!(nas.is_enabled);
+
+        let close_only = &receiver.symbols[0];
+        assert!(!close_only.allows_opening("buy"));
+        let long_only = SymbolSpec {
+            trade_mode: Some("long_only".to_string()),
+            ..eurusd_spec()
+        };
+        assert!(long_only.allows_opening("buy") && !long_only.allows_opening("sell"));
+    }
+
+    fn us30_spec(name: &str, contract_size: f64, digits: i32, description: &str) -> SymbolSpec {
+        let tick_size = 10f64.powi(-digits);
+        SymbolSpec {
+            name: name.to_string(),
+            normalized_key: normalize_symbol(name),
+            // $1 per point per contract unit
+            tick_value: tick_size * contract_size,
+            tick_size,
+            contract_size,
+            digits,
+            description: Some(description.to_string()),
+            ..index_spec(name)
+        }
+    }
+
+    #[test]
+    fn test_us30_maps_across_contract_sizes() {
+        // 1-lot = 1 contract at 2 digits vs 1-lot = 10 contracts at 1 digit
+        let master = catalog("M1", vec![us30_spec("US30", 1.0, 2, "Wall Street 30")]);
+        let receiver = catalog(
+            "R1",
+            vec![
+                us30_spec("USTEC", 10.0, 1, "US Tech 100 (Nasdaq)"),
+                us30_spec("DJ30.cash", 10.0, 1, "Dow Jones Industrial Average"),
+            ],
+        );
+        assert!(!specs_match(&master.symbols[0], &receiver.symbols[1]));
+
+        assert_eq!(relaxed_index_match(&master.symbols[0], &receiver.symbols[1]), Some(RELAXED_INDEX_CONFIDENCE + 10));
+
+        // Found, but a copied lot would be 10x the exposure: left for the user
+        let result = auto_map_symbols_by_specs(&master, &receiver, &HashMap::new());
+        assert_eq!(result.mappings.len(), 1);
+        let mapping = &result.mappings[0];
+        assert_eq!(mapping.receiver_symbol, "DJ30.cash");
+        assert_eq!(mapping.match_method, SPEC_MISMATCH_METHOD);
+        assert!(!mapping.is_enabled);
+
+        // Same contract size at different digits is mapped and enabled
+        let receiver = catalog("R1", vec![us30_spec("DJ30.cash", 1.0, 1, "Dow Jones Industrial Average")]);
+        let mapping = &auto_map_symbols_by_specs(&master, &receiver, &HashMap::new()).mappings[0];
+        assert_eq!(mapping.match_method, "specs");
+        assert!(mapping.is_enabled);
+        assert_eq!(mapping.confidence, RELAXED_INDEX_CONFIDENCE + 10);
+
+        // A different quote currency scale is not the same instrument
+        let mut cents = us30_spec("US30", 10.0, 1, "Dow Jones");
+        cents.tick_value /= 100.0;
+        assert!(relaxed_index_match(&master.symbols[0], &cents).is_none());
+    }
+
+    #[test]
+    fn test_known_broker_pair_uses_bundled_hint() {
+        // FXCM's SPX500 and FTMO's US500.cash differ in specs and name; two
+        // receiver indices match the master's specs equally well
+        let master = catalog("M1", vec![us30_spec("SPX500", 1.0, 2, "S&P 500")]);
+        let receiver = SymbolCatalog {
+            broker_suffix: Some(".cash".to_string()),
+            ..catalog(
+                "R1",
+                vec![
+                    us30_spec("US30.cash", 1.0, 2, "Dow Jones"),
+                    us30_spec("US500.cash", 10.0, 1, "US 500"),
+                ],
+            )
+        };
+        assert_ne!(
+            auto_map_symbols_by_specs(&master, &receiver, &HashMap::new()).mappings[0].receiver_symbol,
+            "US500.cash"
+        );
+
+        // The hint finds it, but at 10x the contract size it needs review
+        let hints = crate::copier::mapping_hints::bundled().pair_hints(Some("FXCM"), Some("FTMO"));
+        let result = auto_map_symbols_by_specs(&master, &receiver, &hints);
+        let mapping = &result.mappings[0];
+        assert_eq!(mapping.receiver_symbol, "US500.cash");
+        assert_eq!(mapping.match_method, SPEC_MISMATCH_METHOD);
+        assert!(!mapping.is_enabled);
+
+        // Same contract, different digits: the hint mapping is enabled
+        let receiver = SymbolCatalog {
+            symbols: vec![us30_spec("US30.cash", 1.0, 2, "Dow Jones"), us30_spec("US500.cash", 1.0, 1, "US 500")],
+            ..receiver
+        };
+        let mapping = &auto_map_symbols_by_specs(&master, &receiver, &hints).mappings[0];
+        assert_eq!(mapping.receiver_symbol, "US500.cash");
+        assert_eq!(mapping.match_method, "broker_hint");
+        assert!(mapping.is_enabled);
     }
-}
 
+    #[test]
+    fn test_symbol_categories() {
+        let spec = |name: &str| SymbolSpec {
+            description: None,
+            ..index_spec(name)
+        };
+        assert_eq!(categorize_symbol(&spec("EURUSD.pro")), SymbolCategory::Fx);
+        assert_eq!(categorize_symbol(&spec("XAUUSD")), SymbolCategory::Metal);
+        assert_eq!(categorize_symbol(&spec("BTCUSD")), SymbolCategory::Crypto);
+        assert_eq!(categorize_symbol(&spec("GER40.cash")), SymbolCategory::Index);
+        assert_eq!(categorize_symbol(&spec("USOIL")), SymbolCategory::Other);
+        // Strict policy still applies outside indices
+        let mut gold = spec("XAUUSD");
+        gold.digits = 3;
+        assert!(relaxed_index_match(&spec("XAUUSD"), &gold).is_none());
+    }
+
+    fn temp_files_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("saturn_catalog_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_missing_catalog_is_ea_not_attached() {
+        let dir = temp_files_dir();
+
+        let err = load_symbol_catalog("T1", &dir).unwrap_err();
+        assert!(matches!(err, CopierError::EaNotAttached(_)));
+        assert_eq!(err.to_string(), "Symbol catalog not available. Attach Receiver EA to generate it.");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_malformed_catalog_is_parse_error() {
+        let dir = temp_files_dir();
+        std::fs::write(dir.join("CopierSymbolCatalog.json"), "{ not json").unwrap();
+        assert!(matches!(load_symbol_catalog("T1", &dir), Err(CopierError::ParseError(_))));
+
+        std::fs::write(dir.join("CopierSymbolCatalog.json"), r#"{"other": []}"#).unwrap();
+        assert!(matches!(load_symbol_catalog("T1", &dir), Err(CopierError::ParseError(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_valid_catalog_loads() {
+        let dir = temp_files_dir();
+        std::fs::write(
+            dir.join("CopierSymbolCatalog.json"),
+            r#"{"symbols": [{"name": "EURUSD.m", "tick_value": 1.0}]}"#,
+        )
+        .unwrap();
+
+        let catalog = load_symbol_catalog("T1", &dir).unwrap();
+        assert_eq!(catalog.symbols.len(), 1);
+        assert_eq!(catalog.symbols[0].normalized_key, "EURUSD");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_detects_uniform_pro_suffix() {
+        let names = ["EURUSD.pro", "GBPUSD.pro", "XAUUSD.pro", "US30.pro", "BTCUSD"];
+        assert_eq!(detect_broker_suffix(names.iter().copied()).as_deref(), Some(".pro"));
+
+        // Only the detected suffix is stripped
+        assert_eq!(normalize_symbol_with_suffix("EURUSD.pro", Some(".pro")), "EURUSD");
+        assert_eq!(normalize_symbol_with_suffix("BTCUSD", Some(".pro")), "BTCUSD");
+    }
+
+    #[test]
+    fn test_detected_suffix_protects_symbols_ending_in_m() {
+        // Broker without suffixes: nothing is detected, so names fall back to
+        // the static list and its bare "m" is stripped
+        let names = ["EURUSD", "GBPUSD", "XAUUSD", "US30m"];
+        assert_eq!(detect_broker_suffix(names.iter().copied()), None);
+        assert_eq!(normalize_symbol_with_suffix("US30m", None), "US30");
+
+        // A detected suffix is the only one stripped, so "US30m" keeps its tail
+        assert_eq!(normalize_symbol_with_suffix("US30m", Some(".pro")), "US30M");
+
+        let names = ["EURUSDm", "GBPUSDm", "XAUUSDm", "US30"];
+        assert_eq!(detect_broker_suffix(names.iter().copied()).as_deref(), Some("m"));
+        assert_eq!(normalize_symbol_with_suffix("EURUSDm", Some("m")), "EURUSD");
+    }
+
+    #[test]
+    fn test_no_dominant_suffix_falls_back_to_static_list() {
+        let names = ["EURUSD.a", "GBPUSD_ecn", "XAUUSD.cash", "US30"];
+        assert_eq!(detect_broker_suffix(names.iter().copied()), None);
+        assert_eq!(normalize_symbol_with_suffix("XAUUSD.cash", None), "XAUUSD");
+    }
+
+    #[test]
+    fn test_catalog_records_broker_suffix() {
+        let dir = temp_files_dir();
+        std::fs::write(
+            dir.join("CopierSymbolCatalog.json"),
+            r#"{"symbols": [{"name": "EURUSD.pro"}, {"name": "GBPUSD.pro"}, {"name": "US30.pro"}]}"#,
+        )
+        .unwrap();
+
+        let catalog = load_symbol_catalog("T1", &dir).unwrap();
+        assert_eq!(catalog.broker_suffix.as_deref(), Some(".pro"));
+        assert_eq!(catalog.symbols[2].normalized_key, "US30");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,139 @@
+//! User-editable symbol normalization rules
+//!
+//! Brokers keep inventing suffixes, so `symbol_catalog::normalize_symbol`
+//! augments its built-in suffix list with an optional `symbol_rules.json`
+//! in the app data dir:
+//!
+//! ```json
+//! {
+//!   "suffixes": [".x", "#"],
+//!   "aliases": { "GOLD": "XAUUSD" },
+//!   "ignore_prefixes": ["m."]
+//! }
+//! ```
+//!
+//! The file is read once, on first use; edits take effect after a restart.
+//! A file that doesn't parse or validate is ignored with a warning and the
+//! built-in rules apply.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use crate::data_dir::app_data_dir;
+
+const RULES_FILE: &str = "symbol_rules.json";
+
+/// Longest rule string accepted; anything longer is a typo, not a suffix
+const MAX_RULE_LEN: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolRules {
+    /// Extra suffixes to strip, on top of the built-in list
+    #[serde(default)]
+    pub suffixes: Vec<String>,
+    /// Broker name -> canonical name (e.g. "GOLD" -> "XAUUSD"), matched
+    /// case-insensitively before and after suffix stripping
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Prefixes to drop (e.g. "m.", "#")
+    #[serde(default)]
+    pub ignore_prefixes: Vec<String>,
+}
+
+impl SymbolRules {
+    /// Reject empty or whitespace-containing rules and aliases without a target
+    pub fn validate(&self) -> Result<(), String> {
+        for (kind, rule) in self
+            .suffixes
+            .iter()
+            .map(|s| ("suffix", s))
+            .chain(self.ignore_prefixes.iter().map(|p| ("prefix", p)))
+        {
+            if rule.is_empty() || rule.len() > MAX_RULE_LEN || rule.chars().any(char::is_whitespace) {
+                return Err(format!("Invalid {} {:?}", kind, rule));
+            }
+        }
+        for (from, to) in &self.aliases {
+            if from.trim().is_empty() || to.trim().is_empty() {
+                return Err(format!("Invalid alias {:?} -> {:?}", from, to));
+            }
+        }
+        Ok(())
+    }
+
+    /// Uppercased copy, so matching is a plain comparison
+    fn to_upper(&self) -> Self {
+        Self {
+            suffixes: self.suffixes.iter().map(|s| s.to_uppercase()).collect(),
+            aliases: self
+                .aliases
+                .iter()
+                .map(|(from, to)| (from.trim().to_uppercase(), to.trim().to_uppercase()))
+                .collect(),
+            ignore_prefixes: self.ignore_prefixes.iter().map(|p| p.to_uppercase()).collect(),
+        }
+    }
+}
+
+/// Parse and validate a rules file, uppercasing it for matching
+pub fn parse_rules(content: &str) -> Result<SymbolRules, String> {
+    let rules: SymbolRules = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    rules.validate()?;
+    Ok(rules.to_upper())
+}
+
+static RULES: LazyLock<SymbolRules> = LazyLock::new(load);
+
+fn get_rules_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(RULES_FILE))
+}
+
+fn load() -> SymbolRules {
+    let Some(path) = get_rules_path() else {
+        return SymbolRules::default();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SymbolRules::default();
+    };
+    match parse_rules(&content) {
+        Ok(rules) => {
+            info!(
+                "Loaded symbol rules: {} suffixes, {} aliases, {} prefixes",
+                rules.suffixes.len(),
+                rules.aliases.len(),
+                rules.ignore_prefixes.len()
+            );
+            rules
+        }
+        Err(e) => {
+            warn!("Ignoring invalid symbol rules file, using defaults: {}", e);
+            SymbolRules::default()
+        }
+    }
+}
+
+/// Current rules (uppercased)
+pub fn current() -> &'static SymbolRules {
+    &RULES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        assert!(parse_rules(r#"{"suffixes": [""]}"#).is_err());
+        assert!(parse_rules(r#"{"ignore_prefixes": ["m ."]}"#).is_err());
+        assert!(parse_rules(r#"{"aliases": {"GOLD": " "}}"#).is_err());
+        assert!(parse_rules(r#"{"suffixes": ".x"}"#).is_err());
+
+        let rules = parse_rules(r#"{"suffixes": [".x"], "aliases": {"gold": "xauusd"}}"#).unwrap();
+        assert_eq!(rules.suffixes, vec![".X"]);
+        assert_eq!(rules.aliases["GOLD"], "XAUUSD");
+    }
+}
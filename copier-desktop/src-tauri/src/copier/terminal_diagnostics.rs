@@ -0,0 +1,211 @@
+//! Per-terminal diagnostics
+//!
+//! The terminal list only carries summary info. `get_terminal_diagnostics`
+//! is the deep dive for one terminal: what discovery knows (exe path, data
+//! folder, data_id, discovery method, EA versions) plus live checks of its
+//! MQL5 folder - installed EAs against the bundled ones, handshake and
+//! heartbeat age, queue and command folder contents, and the symbol catalog.
+//! Files are only read, never written.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::mt5::discovery::{self, DiscoveryMethod, EaStatus, TerminalInfo, TerminalRole};
+use crate::mt5::ea_verify::{self, EaInstallStatus};
+
+/// Handshake files older than this are reported as stale
+const HANDSHAKE_FRESH_SECS: u64 = 300;
+
+/// Everything known about one terminal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalDiagnostics {
+    pub terminal_id: String,
+    pub install_label: Option<String>,
+    pub executable_path: Option<String>,
+    pub data_folder: String,
+    pub data_id: Option<String>,
+    pub discovery_method: DiscoveryMethod,
+    pub platform: String,
+    pub is_running: bool,
+    pub multiple_instances: bool,
+    pub role: Option<TerminalRole>,
+    /// MQL5/Files folder (None if it couldn't be resolved)
+    pub files_path: Option<String>,
+    pub files_writable: bool,
+    pub ea_status: EaStatus,
+    pub master_installed: bool,
+    pub receiver_installed: bool,
+    /// Broker / login from the EA handshake
+    pub broker: Option<String>,
+    pub login: Option<i64>,
+    pub ea_version: Option<String>,
+    pub ea_outdated: bool,
+    /// Installed EAs compared with the ones this build bundles
+    pub master_ea: EaInstallStatus,
+    pub receiver_ea: EaInstallStatus,
+    /// Age of `CopierAccountInfo.json` (None if missing)
+    pub handshake_age_secs: Option<u64>,
+    /// Handshake written within the last 5 minutes
+    pub handshake_fresh: bool,
+    pub last_heartbeat: Option<String>,
+    /// Age of `CopierQueue/heartbeat.json` (None if missing)
+    pub heartbeat_age_secs: Option<u64>,
+    /// Event files waiting in `CopierQueue/pending`
+    pub queue_pending_files: usize,
+    /// Command files waiting in `CopierCommands`
+    pub command_files: usize,
+    /// Symbols in `CopierSymbolCatalog.json` (None if missing or unreadable)
+    pub catalog_symbol_count: Option<usize>,
+    pub catalog_age_secs: Option<u64>,
+}
+
+fn age_secs(path: &Path, now: SystemTime) -> Option<u64> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(now.duration_since(modified).unwrap_or_default().as_secs())
+}
+
+/// `.json` files directly in `folder` (0 if it's missing)
+fn json_files_in(folder: &Path) -> usize {
+    fs::read_dir(folder)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+fn catalog_symbol_count(catalog_file: &Path) -> Option<usize> {
+    let content = fs::read_to_string(catalog_file).ok()?;
+    let raw: serde_json::Value = serde_json::from_str(&content).ok()?;
+    Some(raw.get("symbols")?.as_array()?.len())
+}
+
+/// Diagnostics for `terminal` with live checks of `files_path`
+fn assemble(terminal: &TerminalInfo, files_path: Option<&Path>, now: SystemTime) -> TerminalDiagnostics {
+    let file_age = |name: &str| files_path.and_then(|p| age_secs(&p.join(name), now));
+    let handshake_age_secs = file_age("CopierAccountInfo.json");
+    let catalog_file = files_path.map(|p| p.join("CopierSymbolCatalog.json"));
+    let experts_path = files_path.and_then(Path::parent).map(|mql5| mql5.join("Experts"));
+    let installed_ea = |ea_type: &str| {
+        experts_path
+            .as_deref()
+            .and_then(|p| ea_verify::verify_in(p, ea_type).ok())
+            .unwrap_or(EaInstallStatus::NotInstalled)
+    };
+
+    TerminalDiagnostics {
+        terminal_id: terminal.terminal_id.clone(),
+        install_label: terminal.install_label.clone(),
+        executable_path: terminal.executable_path.clone(),
+        data_folder: terminal.data_folder.clone(),
+        data_id: terminal.data_id.clone(),
+        discovery_method: terminal.discovery_method.clone(),
+        platform: terminal.platform.clone(),
+        is_running: terminal.is_running,
+        multiple_instances: terminal.multiple_instances,
+        role: terminal.role,
+        files_path: files_path.map(|p| p.display().to_string()),
+        files_writable: files_path.is_some_and(discovery::files_writable),
+        ea_status: terminal.ea_status.clone(),
+        master_installed: terminal.master_installed,
+        receiver_installed: terminal.receiver_installed,
+        broker: terminal.broker.clone(),
+        login: terminal.login,
+        ea_version: terminal.ea_version.clone(),
+        ea_outdated: terminal.ea_outdated,
+        master_ea: installed_ea("master"),
+        receiver_ea: installed_ea("receiver"),
+        handshake_age_secs,
+        handshake_fresh: handshake_age_secs.is_some_and(|age| age <= HANDSHAKE_FRESH_SECS),
+        last_heartbeat: terminal.last_heartbeat.clone(),
+        heartbeat_age_secs: file_age("CopierQueue/heartbeat.json"),
+        queue_pending_files: files_path.map_or(0, |p| json_files_in(&p.join("CopierQueue").join("pending"))),
+        command_files: files_path.map_or(0, |p| json_files_in(&p.join("CopierCommands"))),
+        catalog_symbol_count: catalog_file.as_deref().and_then(catalog_symbol_count),
+        catalog_age_secs: catalog_file.as_deref().and_then(|f| age_secs(f, now)),
+    }
+}
+
+/// Deep-dive diagnostics for one discovered terminal
+pub fn get_terminal_diagnostics(terminal_id: &str) -> Result<TerminalDiagnostics, String> {
+    let terminal = discovery::discover_all_terminals()
+        .into_iter()
+        .find(|t| t.terminal_id == terminal_id)
+        .ok_or_else(|| format!("Terminal {} was not discovered", terminal_id))?;
+    let files_path = crate::mt5::bridge::resolve_files_path(terminal_id, false).ok();
+    Ok(assemble(&terminal, files_path.as_deref(), SystemTime::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble_from_seeded_layout() {
+        let data_folder = std::env::temp_dir().join(format!("saturn_termdiag_{}", uuid::Uuid::new_v4()));
+        let files = data_folder.join("MQL5").join("Files");
+        fs::create_dir_all(files.join("CopierQueue").join("pending")).unwrap();
+        fs::create_dir_all(files.join("CopierCommands")).unwrap();
+        let experts = data_folder.join("MQL5").join("Experts");
+        fs::create_dir_all(&experts).unwrap();
+        fs::write(experts.join("TradeCopierReceiver.mq5"), "#property version   \"0.90\"\n").unwrap();
+        fs::write(files.join("CopierAccountInfo.json"), "{}").unwrap();
+        fs::write(files.join("CopierQueue").join("heartbeat.json"), "{}").unwrap();
+        fs::write(files.join("CopierQueue").join("pending").join("evt_1.json"), "{}").unwrap();
+        fs::write(files.join("CopierQueue").join("pending").join("evt_2.json"), "{}").unwrap();
+        fs::write(files.join("CopierQueue").join("pending").join("evt_3.json.tmp"), "{}").unwrap();
+        fs::write(files.join("CopierCommands").join("cmd_1.json"), "{}").unwrap();
+        fs::write(
+            files.join("CopierSymbolCatalog.json"),
+            r#"{"symbols": [{"name": "EURUSD"}, {"name": "XAUUSD"}, {"name": "US30"}]}"#,
+        )
+        .unwrap();
+
+        let terminal: TerminalInfo = serde_json::from_value(serde_json::json!({
+            "terminal_id": "ABC123",
+            "executable_path": "C:\\Program Files\\MT5\\terminal64.exe",
+            "data_folder": data_folder.display().to_string(),
+            "broker": "ICMarkets",
+            "server": "ICMarkets-Demo",
+            "login": 5001,
+            "account_name": null,
+            "platform": "mt5",
+            "is_running": true,
+            "ea_status": "receiver",
+            "last_heartbeat": null,
+            "discovery_method": "app_data",
+            "has_mql5": true,
+            "master_installed": false,
+            "receiver_installed": true,
+            "verified": true,
+            "data_id": "ABC123",
+            "ea_version": "2.4.0"
+        }))
+        .unwrap();
+
+        let diag = assemble(&terminal, Some(&files), SystemTime::now());
+        assert_eq!(diag.discovery_method, DiscoveryMethod::AppData);
+        assert_eq!(diag.ea_status, EaStatus::Receiver);
+        assert_eq!(diag.data_id.as_deref(), Some("ABC123"));
+        assert_eq!(diag.ea_version.as_deref(), Some("2.4.0"));
+        assert!(diag.files_writable);
+        assert!(diag.handshake_fresh);
+        assert!(diag.heartbeat_age_secs.is_some());
+        assert_eq!(diag.queue_pending_files, 2);
+        assert_eq!(diag.command_files, 1);
+        assert_eq!(diag.catalog_symbol_count, Some(3));
+        assert_eq!(diag.receiver_ea, EaInstallStatus::Outdated);
+        assert_eq!(diag.master_ea, EaInstallStatus::NotInstalled);
+
+        // No files folder: live checks come back empty
+        let diag = assemble(&terminal, None, SystemTime::now());
+        assert!(!diag.files_writable && !diag.handshake_fresh);
+        assert_eq!(diag.catalog_symbol_count, None);
+
+        let _ = fs::remove_dir_all(&data_folder);
+    }
+}
@@ -0,0 +1,38 @@
+//! Fixtures shared by the copier unit tests
+//!
+//! Build on these with struct update syntax (`..test_receiver()`) so a new
+//! config or event field doesn't mean editing every test module.
+
+use super::{ReceiverConfig, TradeEvent};
+
+/// Receiver "acc" (account 2002, terminal R1): fixed 0.1 lots, 3 pip
+/// slippage, every optional setting at its default
+pub fn test_receiver() -> ReceiverConfig {
+    serde_json::from_value(serde_json::json!({
+        "account_id": "acc",
+        "account_number": "2002",
+        "broker": "B",
+        "terminal_id": "R1",
+        "risk_mode": "fixed_lot",
+        "risk_value": 0.1,
+        "max_slippage_pips": 3.0,
+        "max_daily_loss_r": null,
+        "prop_firm_safe_mode": false,
+        "symbol_mappings": []
+    }))
+    .unwrap()
+}
+
+/// Master EURUSD buy of 0.1 lots at 1.1 on position `ticket`
+pub fn test_event(event_type: &str, ticket: i64) -> TradeEvent {
+    serde_json::from_value(serde_json::json!({
+        "event_type": event_type,
+        "ticket": ticket,
+        "symbol": "EURUSD",
+        "direction": "buy",
+        "lots": 0.1,
+        "price": 1.1,
+        "timestamp": "2024-01-01T00:00:00Z"
+    }))
+    .unwrap()
+}
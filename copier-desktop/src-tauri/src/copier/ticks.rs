@@ -0,0 +1,108 @@
+//! Receiver price ticks
+//!
+//! The receiver EA writes the latest bid/ask of its Market Watch symbols to
+//! `CopierTicks.json` about once a second. The event processor uses them to
+//! refuse entries whose receiver price has already run away from the
+//! master's entry price, before a command is ever written.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::CopierError;
+
+const TICKS_FILE: &str = "CopierTicks.json";
+
+/// Ticks older than this no longer describe the market
+const MAX_TICK_AGE_SECS: i64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolTick {
+    pub bid: f64,
+    pub ask: f64,
+    pub point: f64,
+    pub digits: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickSnapshot {
+    pub timestamp_utc: String,
+    pub ticks: HashMap<String, SymbolTick>,
+}
+
+fn load_ticks(files_path: &Path) -> Result<TickSnapshot, CopierError> {
+    let content = super::file_lock::read_contended(&files_path.join(TICKS_FILE))
+        .map_err(|e| CopierError::io("Failed to read receiver ticks", e))?;
+    serde_json::from_str(&content).map_err(|e| CopierError::parse("Failed to parse receiver ticks", e))
+}
+
+/// The receiver's latest tick snapshot, if its tick file is fresh
+pub fn latest_snapshot(terminal_id: &str) -> Option<TickSnapshot> {
+    let files_path = crate::mt5::bridge::resolve_files_path(terminal_id, false).ok()?;
+    let snapshot = load_ticks(&files_path).ok()?;
+    let written = chrono::DateTime::parse_from_rfc3339(&snapshot.timestamp_utc).ok()?;
+    let age = chrono::Utc::now().signed_duration_since(written).num_seconds();
+    if age > MAX_TICK_AGE_SECS {
+        return None;
+    }
+    Some(snapshot)
+}
+
+/// The receiver's latest tick for `symbol`, if its tick file is fresh
+pub fn latest_tick(terminal_id: &str, symbol: &str) -> Option<SymbolTick> {
+    latest_snapshot(terminal_id)?.ticks.get(symbol).cloned()
+}
+
+/// Pip size for a symbol: 10 points on 3/5-digit quotes, else one point
+/// (same convention as the receiver EA's slippage calculation)
+pub fn pip_size(point: f64, digits: i32) -> f64 {
+    if digits == 3 || digits == 5 {
+        point * 10.0
+    } else {
+        point
+    }
+}
+
+/// The tick's spread in pips
+pub fn spread_pips(tick: &SymbolTick) -> f64 {
+    let pip = pip_size(tick.point, tick.digits);
+    if pip <= 0.0 {
+        return 0.0;
+    }
+    (tick.ask - tick.bid).max(0.0) / pip
+}
+
+/// How far (in pips) the price a receiver would fill at has moved from the
+/// master's entry: the ask for buys, the bid for sells
+pub fn entry_deviation_pips(direction: &str, master_price: f64, tick: &SymbolTick) -> f64 {
+    let fill_price = if direction == "sell" { tick.bid } else { tick.ask };
+    let pip = pip_size(tick.point, tick.digits);
+    if pip <= 0.0 {
+        return 0.0;
+    }
+    (fill_price - master_price).abs() / pip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_ticks_file() {
+        let dir = std::env::temp_dir().join(format!("saturn_ticks_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(TICKS_FILE),
+            r#"{"timestamp_utc": "2024-01-01T00:00:00Z",
+                "ticks": {"EURUSD.r": {"bid": 1.1, "ask": 1.10012, "point": 0.00001, "digits": 5}}}"#,
+        )
+        .unwrap();
+
+        let snapshot = load_ticks(&dir).unwrap();
+        let tick = &snapshot.ticks["EURUSD.r"];
+        assert!((entry_deviation_pips("buy", 1.1, tick) - 1.2).abs() < 1e-6);
+        assert!(entry_deviation_pips("sell", 1.1, tick) < 1e-6);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
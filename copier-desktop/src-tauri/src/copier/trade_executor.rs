@@ -4,7 +4,8 @@
 //! and polls for the matching response JSON. Includes a small synchronous retry
 //! with exponential backoff for transient broker/file errors.
 
-use super::ReceiverConfig;
+use super::slippage::{PriceScale, SlippageUnit};
+use super::{file_lock, symbol_catalog, ticks, ReceiverConfig, TradeEvent};
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
@@ -48,6 +49,158 @@ pub struct TradeCommand {
     pub timestamp: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub master_position_id: Option<i64>,
+    /// SL/TP distances in receiver points; when set the EA places stops
+    /// relative to its own entry price instead of using `sl`/`tp`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_distance_points: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_distance_points: Option<f64>,
+    /// Master's fill price; the EA reports slippage against it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub master_price: Option<f64>,
+    /// Receiver's configured tagging; the EA keeps its own when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magic_number: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_prefix: Option<String>,
+    /// Echoed back in the response so a recovered execution can tell its
+    /// own answer from any other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+/// SL or TP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopKind {
+    Sl,
+    Tp,
+}
+
+/// Whether `level` is on the right side of `entry` for a `direction`
+/// ("buy"/"sell") position: below it for a buy's SL or a sell's TP
+fn stop_on_correct_side(kind: StopKind, direction: &str, entry: f64, level: f64) -> bool {
+    match (kind, direction) {
+        (StopKind::Sl, "buy") | (StopKind::Tp, "sell") => level < entry,
+        (StopKind::Tp, "buy") | (StopKind::Sl, "sell") => level > entry,
+        _ => true,
+    }
+}
+
+/// Master point size from the event, else from its digits
+fn master_point(event: &TradeEvent) -> Option<f64> {
+    event
+        .point
+        .filter(|p| *p > 0.0)
+        .or_else(|| event.digits.map(|d| 10f64.powi(-d)))
+}
+
+/// SL/TP price levels for an opening trade on the receiver
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EntryStops {
+    pub sl: Option<f64>,
+    pub tp: Option<f64>,
+}
+
+/// The master's SL/TP for an opening trade as receiver price levels.
+///
+/// A distance (`sl_distance_points`/`tp_distance_points`, in master points)
+/// takes precedence: the level is the entry price -/+ that distance, snapped
+/// to the receiver's point grid. Otherwise the absolute `sl`/`tp` is used,
+/// as long as it's on the right side of the entry for `direction`; one that
+/// isn't (an SL above a buy's entry) is dropped with a warning.
+pub fn entry_stops(event: &TradeEvent, direction: &str, receiver_point: Option<f64>) -> EntryStops {
+    let point = master_point(event);
+    let grid = receiver_point.filter(|p| *p > 0.0).or(point);
+    let snap = |level: f64| grid.map_or(level, |g| (level / g).round() * g);
+    let sign = if direction == "sell" { -1.0 } else { 1.0 };
+
+    let resolve = |kind: StopKind, distance: Option<f64>, level: Option<f64>| {
+        if let (Some(distance), Some(point)) = (distance.filter(|d| *d > 0.0), point) {
+            let offset = distance * point * if kind == StopKind::Sl { -sign } else { sign };
+            return Some(snap(event.price + offset));
+        }
+        let level = level.filter(|l| *l > 0.0)?;
+        if !stop_on_correct_side(kind, direction, event.price, level) {
+            warn!(
+                "Ignoring master {:?} {} on the wrong side of {} entry {} ({} {}, ticket {})",
+                kind, level, direction, event.price, event.symbol, event.event_type, event.ticket
+            );
+            return None;
+        }
+        Some(level)
+    };
+
+    EntryStops {
+        sl: resolve(StopKind::Sl, event.sl_distance_points, event.sl),
+        tp: resolve(StopKind::Tp, event.tp_distance_points, event.tp),
+    }
+}
+
+/// SL/TP distances for relative pricing, in receiver points
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RelativeStops {
+    pub sl_points: Option<f64>,
+    pub tp_points: Option<f64>,
+}
+
+/// Re-express the master's SL/TP distances in receiver points.
+///
+/// The master distance (in master points) is converted to a price distance
+/// with the master's point size, then divided by the receiver symbol's point
+/// and rounded to whole points. A 3-digit and a 5-digit quote of the same
+/// instrument therefore get the same stop distance in price, and any price
+/// offset between brokers drops out because the EA anchors on its own entry.
+pub fn relative_stops(event: &TradeEvent, receiver_digits: Option<i32>) -> RelativeStops {
+    let Some(master_point) = master_point(event) else {
+        return RelativeStops::default();
+    };
+    let receiver_point = receiver_digits.map(|d| 10f64.powi(-d)).unwrap_or(master_point);
+
+    // Prefer the EA-reported distance, else derive it from the absolute
+    // price if that's on the right side of the entry
+    let master_points = |kind: StopKind, distance: Option<f64>, level: Option<f64>| {
+        distance.filter(|d| *d > 0.0).or_else(|| {
+            level
+                .filter(|l| *l > 0.0 && stop_on_correct_side(kind, &event.direction, event.price, *l))
+                .map(|l| (event.price - l).abs() / master_point)
+        })
+    };
+    let to_receiver = |points: f64| (points * master_point / receiver_point).round();
+
+    RelativeStops {
+        sl_points: master_points(StopKind::Sl, event.sl_distance_points, event.sl).map(to_receiver),
+        tp_points: master_points(StopKind::Tp, event.tp_distance_points, event.tp).map(to_receiver),
+    }
+}
+
+/// Point size and digits of a receiver symbol, from its catalog or else
+/// the latest tick file
+pub(crate) fn receiver_price_scale(terminal_id: &str, symbol: &str) -> Option<PriceScale> {
+    let from_catalog = symbol_catalog::fetch_symbol_catalog(terminal_id)
+        .ok()
+        .and_then(|catalog| catalog.symbols.iter().find(|s| s.name == symbol).map(PriceScale::from_spec));
+    from_catalog.or_else(|| {
+        ticks::latest_tick(terminal_id, symbol).map(|t| PriceScale::new(t.point, t.digits).for_symbol(symbol))
+    })
+}
+
+/// The receiver's slippage limit in pips of `symbol`, the unit the EA
+/// checks slippage in. On crypto the limit's pips are points.
+fn max_slippage_pips(receiver: &ReceiverConfig, symbol: &str) -> f64 {
+    let limit = receiver.max_slippage();
+    if limit.unit == SlippageUnit::Pips && !symbol_catalog::is_crypto_symbol(symbol) {
+        return limit.value;
+    }
+    match receiver_price_scale(&receiver.terminal_id, symbol).map(|scale| limit.to_ea_pips(scale)) {
+        Some(pips) if pips > 0.0 => pips,
+        _ => {
+            warn!(
+                "No point size known for {} on {}; sending slippage limit {} {:?} as pips",
+                symbol, receiver.account_number, limit.value, limit.unit
+            );
+            limit.value
+        }
+    }
 }
 
 /// Response from MT5 EA after trade execution
@@ -57,67 +210,78 @@ pub struct TradeResponse {
     pub executed_price: f64,
     pub slippage_pips: f64,
     pub error: Option<String>,
-    #[allow(dead_code)]
-    pub timestamp: i64,
     #[serde(default)]
     pub receiver_position_id: Option<i64>,
+    /// Volume the broker filled; None from EAs that don't report it (full fill assumed)
+    #[serde(default)]
+    pub filled_lots: Option<f64>,
+    /// The command's `idempotency_key`, echoed by the EA
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+/// One trade for the receiver EA, as sized and priced by the desktop
+#[derive(Debug, Clone, Default)]
+pub struct TradeOrder {
+    pub event_type: String,
+    pub symbol: String,
+    pub direction: String,
+    pub lots: f64,
+    pub sl: Option<f64>,
+    pub tp: Option<f64>,
+    pub stops: RelativeStops,
+    /// Master's fill price; the EA reports slippage against it
+    pub master_price: Option<f64>,
+    pub master_position_id: Option<i64>,
+    pub idempotency_key: Option<String>,
 }
 
 /// Result of trade execution
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
-    pub success: bool,
     pub executed_price: f64,
     pub slippage_pips: f64,
     pub receiver_position_id: Option<i64>,
-    pub attempts: u32,
-    pub error: Option<String>,
+    pub filled_lots: Option<f64>,
 }
 
 /// Execute a trade on the receiver terminal via file-based communication
 /// Uses synchronous file operations to avoid runtime-within-runtime issues
-pub fn execute_trade(
-    event_type: &str,
-    symbol: &str,
-    direction: &str,
-    lots: f64,
-    sl: Option<f64>,
-    tp: Option<f64>,
-    receiver: &ReceiverConfig,
-) -> Result<(f64, f64), TradeError> {
+pub fn execute_trade(order: &TradeOrder, receiver: &ReceiverConfig) -> Result<ExecutionResult, TradeError> {
     // Use fully synchronous implementation to avoid block_on deadlock risk
-    execute_trade_sync(event_type, symbol, direction, lots, sl, tp, receiver, None, &RetryConfig::default())
+    execute_trade_sync(order, receiver, &RetryConfig::default())
 }
 
 /// Synchronous trade execution with retry mechanism
 /// Uses std::fs for all file operations - no async runtime required
 fn execute_trade_sync(
-    event_type: &str,
-    symbol: &str,
-    direction: &str,
-    lots: f64,
-    sl: Option<f64>,
-    tp: Option<f64>,
+    order: &TradeOrder,
     receiver: &ReceiverConfig,
-    master_position_id: Option<i64>,
     retry_config: &RetryConfig,
-) -> Result<(f64, f64), TradeError> {
+) -> Result<ExecutionResult, TradeError> {
+    let symbol = order.symbol.as_str();
     info!(
         "Executing {} {} {} {} lots on {} (sync)",
-        event_type, direction, symbol, lots, receiver.account_number
+        order.event_type, order.direction, symbol, order.lots, receiver.account_number
     );
 
     let command = TradeCommand {
-        action: event_type.to_string(),
-        symbol: symbol.to_string(),
-        direction: direction.to_string(),
-        lots,
-        calculated_lots: Some(lots), // Desktop calculated - EA should use this
-        sl,
-        tp,
-        max_slippage_pips: receiver.max_slippage_pips,
+        action: order.event_type.clone(),
+        symbol: order.symbol.clone(),
+        direction: order.direction.clone(),
+        lots: order.lots,
+        calculated_lots: Some(order.lots), // Desktop calculated - EA should use this
+        sl: order.sl,
+        tp: order.tp,
+        max_slippage_pips: max_slippage_pips(receiver, symbol),
         timestamp: chrono::Utc::now().timestamp_millis(),
-        master_position_id,
+        master_position_id: order.master_position_id,
+        sl_distance_points: order.stops.sl_points,
+        tp_distance_points: order.stops.tp_points,
+        master_price: order.master_price,
+        magic_number: receiver.magic_number,
+        comment_prefix: receiver.comment_prefix.clone(),
+        idempotency_key: order.idempotency_key.clone(),
     };
 
     let mut last_error = None;
@@ -130,7 +294,12 @@ fn execute_trade_sync(
                         "Trade executed successfully on attempt {}: {} @ {} (slippage: {} pips)",
                         attempt + 1, symbol, response.executed_price, response.slippage_pips
                     );
-                    return Ok((response.executed_price, response.slippage_pips));
+                    return Ok(ExecutionResult {
+                        executed_price: response.executed_price,
+                        slippage_pips: response.slippage_pips,
+                        receiver_position_id: response.receiver_position_id,
+                        filled_lots: response.filled_lots,
+                    });
                 } else {
                     let error_msg = response.error.clone().unwrap_or_else(|| "Unknown error".to_string());
                     warn!("Trade failed on attempt {}: {}", attempt + 1, error_msg);
@@ -175,10 +344,10 @@ fn execute_single_attempt_sync(
     let temp_file = format!("{}.tmp", command_file);
 
     // Atomic write: temp file then rename
-    fs::write(&temp_file, &command_json)
-        .map_err(|e| TradeError::FileWriteError(e.to_string()))?;
-    fs::rename(&temp_file, &command_file)
-        .map_err(|e| TradeError::FileWriteError(e.to_string()))?;
+    file_lock::with_terminal_lock(&receiver.terminal_id, || {
+        fs::write(&temp_file, &command_json).map_err(|e| TradeError::FileWriteError(e.to_string()))?;
+        fs::rename(&temp_file, &command_file).map_err(|e| TradeError::FileWriteError(e.to_string()))
+    })?;
 
     info!("Command written to: {}", command_file);
 
@@ -207,7 +376,7 @@ fn wait_for_response_sync(
             // Brief settle in case rename is observed before contents flush.
             std::thread::sleep(Duration::from_millis(10));
 
-            let content = match fs::read_to_string(&response_path) {
+            let content = match file_lock::read_contended(Path::new(&response_path)) {
                 Ok(c) if !c.trim().is_empty() => c,
                 Ok(_) => {
                     // Empty/partial file — let the next poll re-read.
@@ -246,7 +415,7 @@ fn wait_for_response_sync(
 }
 
 /// Calculate exponential backoff delay
-fn calculate_backoff_delay(attempt: u32, config: &RetryConfig) -> u64 {
+pub(crate) fn calculate_backoff_delay(attempt: u32, config: &RetryConfig) -> u64 {
     let delay = config.base_delay_ms as f64 * config.exponential_base.powi(attempt as i32);
     (delay as u64).min(config.max_delay_ms)
 }
@@ -365,4 +534,87 @@ mod tests {
         assert!(!is_retryable_error("Invalid volume"));
         assert!(!is_retryable_error("Invalid symbol"));
     }
+
+    fn event_with_stops(price: f64, sl: f64, tp: f64, digits: i32) -> TradeEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_type": "entry",
+            "ticket": 1,
+            "symbol": "USDJPY",
+            "direction": "buy",
+            "lots": 0.1,
+            "price": price,
+            "sl": sl,
+            "tp": tp,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "digits": digits,
+            "point": 10f64.powi(-digits)
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_relative_stops_3_digit_master_to_5_digit_receiver() {
+        // 25 pips SL / 50 pips TP on a 3-digit quote
+        let event = event_with_stops(150.000, 149.750, 150.500, 3);
+        let stops = relative_stops(&event, Some(5));
+        assert_eq!(stops.sl_points, Some(25000.0));
+        assert_eq!(stops.tp_points, Some(50000.0));
+
+        // Same price distance on the receiver's point grid
+        assert!((stops.sl_points.unwrap() * 0.00001 - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_relative_stops_5_digit_master_to_3_digit_receiver() {
+        let mut event = event_with_stops(1.10000, 1.09750, 0.0, 5);
+        event.sl_distance_points = Some(250.0);
+        let stops = relative_stops(&event, Some(3));
+        assert_eq!(stops.sl_points, Some(3.0)); // 0.0025 -> 2.5 points, rounded
+        assert_eq!(stops.tp_points, None);
+
+        // Unknown receiver digits: keep the master's point size
+        assert_eq!(relative_stops(&event, None).sl_points, Some(250.0));
+    }
+
+    #[test]
+    fn test_entry_stops_prefer_distance_on_receiver_grid() {
+        // 25 pip SL / 50 pip TP sent as distances on a 5-digit master
+        let mut event = event_with_stops(1.10000, 0.0, 0.0, 5);
+        event.sl_distance_points = Some(250.0);
+        event.tp_distance_points = Some(500.0);
+        let stops = entry_stops(&event, "buy", Some(0.0001));
+        assert!((stops.sl.unwrap() - 1.0975).abs() < 1e-9);
+        assert!((stops.tp.unwrap() - 1.1050).abs() < 1e-9);
+
+        // A sell mirrors them, and the distance wins over an absolute level
+        event.sl = Some(1.2);
+        let stops = entry_stops(&event, "sell", None);
+        assert!((stops.sl.unwrap() - 1.1025).abs() < 1e-9);
+        assert!((stops.tp.unwrap() - 1.0950).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entry_stops_use_absolute_levels_on_correct_side() {
+        let event = event_with_stops(150.000, 149.750, 150.500, 3);
+        let stops = entry_stops(&event, "buy", Some(0.00001));
+        assert_eq!(stops, EntryStops { sl: Some(149.750), tp: Some(150.500) });
+
+        // No levels: nothing to set
+        let none = event_with_stops(150.000, 0.0, 0.0, 3);
+        assert_eq!(entry_stops(&none, "buy", None), EntryStops::default());
+    }
+
+    #[test]
+    fn test_malformed_sl_is_rejected() {
+        // SL above a buy's entry, TP still fine
+        let event = event_with_stops(150.000, 150.250, 150.500, 3);
+        let stops = entry_stops(&event, "buy", None);
+        assert_eq!(stops.sl, None);
+        assert_eq!(stops.tp, Some(150.500));
+
+        // For a sell the SL is fine and the TP is the one on the wrong side
+        assert_eq!(entry_stops(&event, "sell", None), EntryStops { sl: Some(150.250), tp: None });
+        // Relative mode drops it too rather than flipping it to the other side
+        assert_eq!(relative_stops(&event, Some(3)).sl_points, None);
+    }
 }
@@ -0,0 +1,152 @@
+//! Queue folder detection settings
+//!
+//! `notify` events don't fire reliably on some network drives and VPS
+//! setups, so the file watcher can also (or only) scan the queue folder on
+//! a timer. The mode is a per-machine choice, persisted locally rather than
+//! in the cloud config.
+//!
+//! The EA may still be writing an event file when it's picked up, so reads
+//! are retried with backoff (`read_attempts` spread over `read_retry_ms`)
+//! before a file is treated as bad. Reads of other files the EA may hold
+//! open use the same retries (`file_lock::read_contended`).
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::data_dir::app_data_dir;
+
+const SETTINGS_FILE: &str = "watch_settings.json";
+
+/// Shortest scan interval accepted; anything lower is clamped
+pub const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+/// Most read attempts accepted for one event file
+pub const MAX_READ_ATTEMPTS: u32 = 20;
+
+/// How new event files in the queue folder are detected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// Filesystem notifications only
+    Notify,
+    /// Timed folder scans only
+    Poll,
+    /// Notifications, with timed scans catching anything they miss
+    #[default]
+    Both,
+}
+
+impl WatchMode {
+    pub fn uses_notify(self) -> bool {
+        matches!(self, WatchMode::Notify | WatchMode::Both)
+    }
+
+    pub fn uses_poll(self) -> bool {
+        matches!(self, WatchMode::Poll | WatchMode::Both)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchSettings {
+    #[serde(default)]
+    pub mode: WatchMode,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Times an event file is read before it's treated as bad
+    #[serde(default = "default_read_attempts")]
+    pub read_attempts: u32,
+    /// Total time the read retries are spread over
+    #[serde(default = "default_read_retry_ms")]
+    pub read_retry_ms: u64,
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_read_attempts() -> u32 {
+    5
+}
+
+fn default_read_retry_ms() -> u64 {
+    250
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            mode: WatchMode::default(),
+            poll_interval_ms: default_poll_interval_ms(),
+            read_attempts: default_read_attempts(),
+            read_retry_ms: default_read_retry_ms(),
+        }
+    }
+}
+
+impl WatchSettings {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(MIN_POLL_INTERVAL_MS))
+    }
+
+    /// Wait before read attempt `attempt + 1` (0-based). Delays double, so
+    /// the retries together take `read_retry_ms`.
+    pub fn read_retry_delay(&self, attempt: u32) -> Duration {
+        let retries = self.read_attempts.clamp(1, MAX_READ_ATTEMPTS) - 1;
+        if retries == 0 {
+            return Duration::ZERO;
+        }
+        let first_delay_ms = self.read_retry_ms as f64 / ((1u64 << retries) - 1) as f64;
+        Duration::from_millis((first_delay_ms * (1u64 << attempt.min(retries - 1)) as f64).round() as u64)
+    }
+}
+
+static SETTINGS: LazyLock<Mutex<WatchSettings>> = LazyLock::new(|| Mutex::new(load()));
+
+fn get_settings_path() -> Option<PathBuf> {
+    Some(app_data_dir()?.join(SETTINGS_FILE))
+}
+
+fn load() -> WatchSettings {
+    let Some(path) = get_settings_path() else {
+        return WatchSettings::default();
+    };
+    match fs::read_to_string(&path).map(|c| serde_json::from_str(&c)) {
+        Ok(Ok(settings)) => settings,
+        Ok(Err(e)) => {
+            warn!("Ignoring unreadable watch settings file: {}", e);
+            WatchSettings::default()
+        }
+        Err(_) => WatchSettings::default(),
+    }
+}
+
+/// Current settings
+pub fn current() -> WatchSettings {
+    *SETTINGS.lock()
+}
+
+/// Replace the settings and persist them (atomic write). Running watchers
+/// re-arm with the new mode on their next tick.
+pub fn set(settings: WatchSettings) -> Result<(), String> {
+    let settings = WatchSettings {
+        poll_interval_ms: settings.poll_interval_ms.max(MIN_POLL_INTERVAL_MS),
+        read_attempts: settings.read_attempts.clamp(1, MAX_READ_ATTEMPTS),
+        ..settings
+    };
+    if let Some(path) = get_settings_path() {
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write watch settings: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save watch settings: {}", e))?;
+    }
+    *SETTINGS.lock() = settings;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Base folder for the app's own data
+//!
+//! By default the copier keeps its state in `%APPDATA%\SaturnTradeCopier`,
+//! and the logs and sync config in the platform folders from
+//! `directories::ProjectDirs`. Portable (USB) installs and test setups can
+//! point all of it at one folder instead: the `SATURN_DATA_DIR` environment
+//! variable, or `set_data_dir_override` at runtime, which takes precedence.
+//! The folder is validated and created when the override is set.
+//!
+//! Changing the override at runtime doesn't move anything: state already
+//! loaded stays in memory and is written to the new folder on its next save.
+
+use parking_lot::Mutex;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::warn;
+
+/// App data folder name under `%APPDATA%`
+pub const APP_DATA_FOLDER: &str = "SaturnTradeCopier";
+
+/// Environment variable overriding the data folder
+pub const DATA_DIR_ENV: &str = "SATURN_DATA_DIR";
+
+/// Override in effect, seeded from `SATURN_DATA_DIR`
+static OVERRIDE: LazyLock<Mutex<Option<PathBuf>>> = LazyLock::new(|| Mutex::new(env_override()));
+
+/// Check a folder can be used as the data folder, creating it if needed
+pub fn validate_data_dir(path: &Path) -> Result<PathBuf, String> {
+    if path.as_os_str().is_empty() {
+        return Err("Data directory is empty".to_string());
+    }
+    if !path.is_absolute() {
+        return Err(format!("Data directory must be an absolute path: {}", path.display()));
+    }
+    fs::create_dir_all(path).map_err(|e| format!("Failed to create data directory {}: {}", path.display(), e))?;
+    if !path.is_dir() {
+        return Err(format!("Data directory {} is not a folder", path.display()));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn env_override() -> Option<PathBuf> {
+    let value = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty())?;
+    match validate_data_dir(Path::new(&value)) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            warn!("Ignoring {}: {}", DATA_DIR_ENV, e);
+            None
+        }
+    }
+}
+
+/// The overridden data folder, if any
+pub fn data_dir_override() -> Option<PathBuf> {
+    OVERRIDE.lock().clone()
+}
+
+/// Point the app's data at `path`, or with `None` go back to the default
+/// locations. Returns the folder now in use as an override.
+pub fn set_data_dir_override(path: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let dir = path.map(validate_data_dir).transpose()?;
+    *OVERRIDE.lock() = dir.clone();
+    Ok(dir)
+}
+
+/// Folder for the copier's state files: the override, else
+/// `%APPDATA%\SaturnTradeCopier`. `None` when neither is available.
+pub fn app_data_dir() -> Option<PathBuf> {
+    resolve_app_data_dir(data_dir_override(), std::env::var_os("APPDATA"))
+}
+
+fn resolve_app_data_dir(override_dir: Option<PathBuf>, appdata: Option<OsString>) -> Option<PathBuf> {
+    override_dir.or_else(|| appdata.map(|appdata| PathBuf::from(appdata).join(APP_DATA_FOLDER)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_must_be_an_absolute_folder() {
+        assert!(validate_data_dir(Path::new("")).is_err());
+        assert!(validate_data_dir(Path::new("relative/data")).is_err());
+
+        let dir = std::env::temp_dir().join(format!("saturn_data_dir_test_{}", uuid::Uuid::new_v4()));
+        assert_eq!(validate_data_dir(&dir.join("nested")).unwrap(), dir.join("nested"));
+        assert!(dir.join("nested").is_dir());
+
+        fs::write(dir.join("file"), "x").unwrap();
+        assert!(validate_data_dir(&dir.join("file")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_override_takes_precedence_over_appdata() {
+        let appdata = || Some(OsString::from("/appdata"));
+        let portable = PathBuf::from("/usb/saturn");
+
+        assert_eq!(resolve_app_data_dir(Some(portable.clone()), appdata()), Some(portable));
+        assert_eq!(resolve_app_data_dir(None, appdata()), Some(Path::new("/appdata").join(APP_DATA_FOLDER)));
+        assert_eq!(resolve_app_data_dir(None, None), None);
+    }
+}
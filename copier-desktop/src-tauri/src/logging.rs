@@ -1,21 +1,55 @@
 //! Logging configuration for Saturn Trade Copier
 //!
 //! Provides structured file-based logging using the tracing ecosystem.
-//! Logs are written to the app's data directory with daily rotation.
+//! Logs are written to the app's data directory with daily rotation, with
+//! account numbers and API keys masked (see `redact`).
 
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{
     fmt,
     layer::SubscriberExt,
+    reload,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Registry,
 };
 
-/// Get the log directory path
+use crate::redact::RedactingWriter;
+
+/// Log file name prefix; the daily appender adds a `.YYYY-MM-DD` suffix
+const LOG_FILE_PREFIX: &str = "saturn-copier.log";
+
+/// Chunk size used when reading a log file backwards
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Filter used when neither `RUST_LOG` nor a saved level is set
+const DEFAULT_FILTER: &str = "info,saturn_trade_copier=debug";
+
+/// Target of this crate's own events, which a chosen level applies to
+const APP_TARGET: &str = "saturn_trade_copier";
+
+/// Most verbose level dependencies log at, whatever level is chosen
+const MAX_DEPENDENCY_LEVEL: &str = "info";
+
+/// File (next to the logs folder) holding the level chosen in the UI
+const LOG_LEVEL_FILE: &str = "log_level";
+
+/// Levels accepted by `set_log_level`
+const VALID_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Handle for swapping the filter at runtime, set by `init_logging`
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Get the log directory path (under the data folder override, if set)
 pub fn get_log_dir() -> PathBuf {
-    if let Some(proj_dirs) = directories::ProjectDirs::from("com", "saturn", "trade-copier") {
-        let log_dir = proj_dirs.data_dir().join("logs");
+    let base = crate::data_dir::data_dir_override().or_else(|| {
+        directories::ProjectDirs::from("com", "saturn", "trade-copier").map(|proj_dirs| proj_dirs.data_dir().to_path_buf())
+    });
+    if let Some(base) = base {
+        let log_dir = base.join("logs");
         // Create the directory if it doesn't exist
         let _ = std::fs::create_dir_all(&log_dir);
         log_dir
@@ -33,15 +67,22 @@ pub fn init_logging() -> tracing_appender::non_blocking::WorkerGuard {
     let file_appender = RollingFileAppender::new(
         Rotation::DAILY,
         &log_dir,
-        "saturn-copier.log",
+        LOG_FILE_PREFIX,
     );
     
     // Make file appender non-blocking
     let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
     
-    // Create filter from environment or use default
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,saturn_trade_copier=debug"));
+    // Filter from environment, else the level saved from the UI, else default
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        load_saved_level()
+            .and_then(|level| build_filter(&level).ok())
+            .unwrap_or_else(|| EnvFilter::new(DEFAULT_FILTER))
+    });
+
+    // Wrap in a reload layer so `set_log_level` can change it without a restart
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER_HANDLE.set(handle);
     
     // Set up subscriber with both console and file output
     tracing_subscriber::registry()
@@ -53,7 +94,7 @@ pub fn init_logging() -> tracing_appender::non_blocking::WorkerGuard {
                 .with_file(true)
                 .with_line_number(true)
                 .with_ansi(false)
-                .with_writer(non_blocking)
+                .with_writer(RedactingWriter(non_blocking))
         )
         .with(
             fmt::layer()
@@ -61,6 +102,7 @@ pub fn init_logging() -> tracing_appender::non_blocking::WorkerGuard {
                 .with_thread_ids(false)
                 .compact()
                 .with_ansi(true)
+                .with_writer(RedactingWriter(std::io::stdout))
         )
         .init();
     
@@ -69,6 +111,125 @@ pub fn init_logging() -> tracing_appender::non_blocking::WorkerGuard {
     guard
 }
 
+/// Build the filter for a level name, rejecting anything but the
+/// standard levels. The level applies to this crate; dependencies log at it
+/// too, but never more verbosely than `MAX_DEPENDENCY_LEVEL`.
+fn build_filter(level: &str) -> Result<EnvFilter, String> {
+    let level = level.trim().to_lowercase();
+    if !VALID_LEVELS.contains(&level.as_str()) {
+        return Err(format!(
+            "Invalid log level '{}' (expected one of: {})",
+            level,
+            VALID_LEVELS.join(", ")
+        ));
+    }
+    // VALID_LEVELS is ordered most verbose first
+    let rank = |l: &str| VALID_LEVELS.iter().position(|v| *v == l);
+    let dependency_level = if rank(&level) < rank(MAX_DEPENDENCY_LEVEL) { MAX_DEPENDENCY_LEVEL } else { level.as_str() };
+    EnvFilter::try_new(format!("{},{}={}", dependency_level, APP_TARGET, level))
+        .map_err(|e| format!("Invalid log level '{}': {}", level, e))
+}
+
+/// Swap the filter behind `handle` for the given level
+fn reload_level(handle: &reload::Handle<EnvFilter, Registry>, level: &str) -> Result<(), String> {
+    let filter = build_filter(level)?;
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to change log level: {}", e))
+}
+
+fn log_level_file() -> PathBuf {
+    get_log_dir().with_file_name(LOG_LEVEL_FILE)
+}
+
+fn load_saved_level() -> Option<String> {
+    std::fs::read_to_string(log_level_file())
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Change the log level at runtime and remember it for the next start
+pub fn set_log_level(level: &str) -> Result<(), String> {
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "Logging is not initialized".to_string())?;
+    reload_level(handle, level)?;
+
+    let level = level.trim().to_lowercase();
+    std::fs::write(log_level_file(), &level)
+        .map_err(|e| format!("Failed to save log level: {}", e))?;
+
+    tracing::info!("Log level set to {}", level);
+    Ok(())
+}
+
+/// Path of today's log file (daily rotation names files by UTC date)
+pub fn current_log_file() -> PathBuf {
+    get_log_dir().join(format!(
+        "{}.{}",
+        LOG_FILE_PREFIX,
+        chrono::Utc::now().format("%Y-%m-%d")
+    ))
+}
+
+/// Last `lines` lines of today's log. Empty if nothing has been logged yet.
+pub fn tail_log(lines: usize) -> Vec<String> {
+    tail_file(&current_log_file(), lines).unwrap_or_default()
+}
+
+/// Read the last `lines` lines of a file, seeking back from the end in
+/// chunks so large logs are never loaded whole.
+pub fn tail_file(path: &Path, lines: usize) -> std::io::Result<Vec<String>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = File::open(path)?;
+    let len = file.seek(SeekFrom::End(0))?;
+
+    // Collect chunks from the end until we've seen more newlines than
+    // requested lines (one extra covers a trailing newline)
+    let mut buf: Vec<u8> = Vec::new();
+    let mut pos = len;
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read_len = TAIL_CHUNK_SIZE.min(pos);
+        pos -= read_len;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0u8; read_len as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut all: Vec<&str> = text.lines().collect();
+    // The first line is partial unless we read from the start of the file
+    if pos > 0 && !all.is_empty() {
+        all.remove(0);
+    }
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Open the log directory in the OS file manager
+pub fn open_log_dir() -> Result<(), String> {
+    let dir = get_log_dir();
+
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program)
+        .arg(&dir)
+        .spawn()
+        .map_err(|e| format!("Failed to open log directory {:?}: {}", dir, e))?;
+    Ok(())
+}
+
 /// Log a trade execution event
 #[macro_export]
 macro_rules! log_trade {
@@ -95,3 +256,86 @@ macro_rules! log_error {
         );
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tracing_subscriber::layer::{Context, Layer};
+
+    /// Counts events that make it past the filter
+    struct CountingLayer(Arc<AtomicUsize>);
+
+    impl<S: tracing::Subscriber> Layer<S> for CountingLayer {
+        fn on_event(&self, _event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_reload_changes_filtered_messages() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let (filter, handle) = reload::Layer::new(build_filter("info").unwrap());
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(CountingLayer(count.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("hidden at info");
+            tracing::info!("shown at info");
+            assert_eq!(count.load(Ordering::SeqCst), 1);
+
+            reload_level(&handle, "debug").unwrap();
+            tracing::debug!("shown at debug");
+            assert_eq!(count.load(Ordering::SeqCst), 2);
+            // Dependencies stay at info
+            tracing::debug!(target: "hyper::proto", "hidden for dependencies");
+            tracing::info!(target: "hyper::proto", "shown for dependencies");
+            assert_eq!(count.load(Ordering::SeqCst), 3);
+
+            reload_level(&handle, "WARN").unwrap();
+            tracing::info!("hidden at warn");
+            tracing::info!(target: "hyper::proto", "hidden for dependencies at warn");
+            assert_eq!(count.load(Ordering::SeqCst), 3);
+        });
+    }
+
+    #[test]
+    fn test_invalid_level_rejected() {
+        assert!(build_filter("verbose").is_err());
+        assert!(build_filter("debug,foo=trace").is_err());
+        assert!(build_filter(" Error ").is_ok());
+    }
+
+    fn temp_log(content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("saturn_tail_test_{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tail_returns_last_lines() {
+        let content: String = (1..=5000).map(|i| format!("line {}\n", i)).collect();
+        let path = temp_log(&content);
+
+        let tail = tail_file(&path, 3).unwrap();
+        assert_eq!(tail, vec!["line 4998", "line 4999", "line 5000"]);
+
+        // Spans several read chunks
+        let tail = tail_file(&path, 2000).unwrap();
+        assert_eq!(tail.len(), 2000);
+        assert_eq!(tail[0], "line 3001");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tail_short_file_and_missing_file() {
+        let path = temp_log("only\ntwo");
+        assert_eq!(tail_file(&path, 10).unwrap(), vec!["only", "two"]);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(tail_file(&path, 10).is_err());
+    }
+}
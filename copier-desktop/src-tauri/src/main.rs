@@ -4,23 +4,25 @@
 )]
 
 mod copier;
+mod data_dir;
 mod logging;
 mod mt5;
+mod redact;
 mod sync;
 
 use tracing::{info, warn};
 
 use copier::CopierState;
 use copier::config_generator::{
-    build_config_file, ensure_copier_folders, save_config_to_terminal,
-    ReceiverConfigFile, RiskConfig, SafetyConfig,
+    build_config_file, ensure_copier_folders, provision_terminal,
+    CopierConfigFile, ReceiverConfigFile, RiskConfig, SafetyConfig,
 };
 use copier::position_sync::{
-    generate_sync_report, PositionSyncStatus, SyncCommand, write_sync_command,
+    generate_sync_report, magic_numbers, manual_sltp_receivers, sltp_policies, sltp_sync_settings, PositionSyncStatus, SyncCommand, write_sync_command,
 };
 use copier::commands::{
     close_all_positions, pause_all_receivers, resume_all_receivers,
-    read_master_heartbeat, is_master_online, Heartbeat,
+    read_master_heartbeat, is_master_online, Heartbeat, PingResult, ReceiverCommandResult,
 };
 use parking_lot::Mutex;
 use std::collections::HashMap;
@@ -44,10 +46,13 @@ pub struct AppState {
 /// process, regardless of whether they're started at boot or after pairing.
 static AGENT_SYNC_STARTED: OnceLock<()> = OnceLock::new();
 
-fn start_agent_sync(api_key: String, state: &AppState) {
+fn start_agent_sync(state: &AppState) {
     if AGENT_SYNC_STARTED.set(()).is_err() {
         return;
     }
+    // The loops re-read the key every tick, so `set_api_key` can rotate it
+    let copier = state.copier.clone();
+    let api_key: sync::config::ApiKeySource = Arc::new(move || copier.lock().api_key.clone());
     sync::state::spawn(api_key.clone(), state.snapshotter.clone());
     sync::commands::spawn(api_key, state.install_id.clone(), state.router.clone());
     tracing::info!("Agent sync loops started (install_id={})", state.install_id);
@@ -64,32 +69,49 @@ fn get_copier_status(state: tauri::State<AppState>) -> serde_json::Value {
         "trades_today": copier.trades_today,
         "pnl_today": copier.pnl_today,
         "open_positions": copier.open_positions,
-        "last_error": copier.last_error,
+        "last_error": copier.last_error.as_deref().map(redact::redact),
         "config_version": copier.config_version,
+        "config_from_cache": copier.config_from_cache,
+        "config_cache_age_secs": copier.config_cache_age_secs,
+        "config_age_secs": copier::stale_config::config_age_secs(copier.last_sync.as_deref(), chrono::Utc::now()),
+        "panic_reason": copier.panic_reason.as_deref().map(redact::redact),
+        "last_upload_at": copier.last_upload_at,
     })
 }
 
 #[tauri::command]
 async fn set_api_key(api_key: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    {
-        let mut copier = state.copier.lock();
-        copier.api_key = Some(api_key.clone());
+    let api_key = api_key.trim().to_string();
+    if api_key.is_empty() {
+        return Err("API key is empty".to_string());
     }
+    redact::register_api_key(&api_key);
+
+    // Only a key the cloud accepts replaces the current one
+    sync::config::validate_api_key(&api_key).await.map_err(|e| match e {
+        sync::config::ConfigError::InvalidApiKey(_) => {
+            "API key was rejected - check it was copied correctly from the web Console".to_string()
+        }
+        e => format!("Could not validate API key: {}", e),
+    })?;
 
     // Save to config file
     if let Err(e) = sync::config::save_api_key(&api_key) {
         return Err(format!("Failed to save API key: {}", e));
     }
 
+    // Running loops pick up a rotated key on their next tick
+    state.copier.lock().api_key = Some(api_key);
+
     // Start (or no-op if already running) the agent telemetry + command loops.
-    start_agent_sync(api_key, &state);
+    start_agent_sync(&state);
 
     Ok(())
 }
 
 
 #[tauri::command]
-async fn sync_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn sync_config(state: tauri::State<'_, AppState>) -> Result<copier::config_diff::ConfigDiff, String> {
     let api_key = {
         let copier = state.copier.lock();
         copier.api_key.clone()
@@ -97,13 +119,44 @@ async fn sync_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
     
     let api_key = api_key.ok_or("No API key configured")?;
     
-    match sync::config::fetch_config(&api_key).await {
-        Ok(config) => {
+    match sync::config::fetch_config_or_cached(&api_key).await {
+        Ok(loaded) => {
             let mut copier = state.copier.lock();
-            copier.config = Some(config);
-            copier.last_sync = Some(chrono::Utc::now().to_rfc3339());
-            copier.is_connected = true;
-            Ok(())
+            let previous = copier.config.clone();
+            let changed = copier::hot_reload::install_config(&mut copier, loaded.config);
+            let diff = match &copier.config {
+                Some(config) => copier::config_diff::diff_from(previous.as_ref(), config),
+                None => copier::config_diff::ConfigDiff::default(),
+            };
+            if !diff.is_empty() {
+                info!(
+                    "Config sync: {} receiver(s) added, {} removed, {} risk / {} mapping / {} safety change(s)",
+                    diff.added_receivers.len(),
+                    diff.removed_receivers.len(),
+                    diff.risk_changes.len(),
+                    diff.symbol_mapping_changes.len(),
+                    diff.safety_changes.len()
+                );
+            }
+            copier.config_from_cache = loaded.from_cache;
+            copier.config_cache_age_secs = loaded.cache_age_secs;
+            if loaded.from_cache {
+                // Keep last_sync pointing at the last successful cloud sync;
+                // on a cold start that is when the cache was written
+                if copier.last_sync.is_none() {
+                    copier.last_sync = loaded.cache_age_secs.map(|age| {
+                        (chrono::Utc::now() - chrono::Duration::seconds(age as i64)).to_rfc3339()
+                    });
+                }
+                copier.is_connected = false;
+            } else {
+                copier.last_sync = Some(chrono::Utc::now().to_rfc3339());
+                copier.is_connected = true;
+            }
+            if changed {
+                spawn_reprovision(copier.config.clone());
+            }
+            Ok(diff)
         }
         Err(e) => {
             let mut copier = state.copier.lock();
@@ -113,6 +166,13 @@ async fn sync_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
     }
 }
 
+/// Re-provision receiver terminals for a changed config in the background
+fn spawn_reprovision(config: Option<copier::CopierConfig>) {
+    if let Some(config) = config {
+        copier::hot_reload::queue_reprovision(config);
+    }
+}
+
 #[tauri::command]
 fn start_copier(state: tauri::State<AppState>) -> Result<(), String> {
     let mut copier = state.copier.lock();
@@ -120,6 +180,7 @@ fn start_copier(state: tauri::State<AppState>) -> Result<(), String> {
         return Err("No configuration loaded. Please sync first.".to_string());
     }
     copier.is_running = true;
+    copier.panic_reason = None;
     Ok(())
 }
 
@@ -148,10 +209,18 @@ fn find_terminals() -> Vec<mt5::bridge::Mt5Terminal> {
     mt5::bridge::find_mt5_terminals()
 }
 
-/// Enhanced terminal discovery using multiple strategies
+/// Enhanced terminal discovery using multiple strategies. Terminals used by
+/// the loaded config are tagged with their role; configured terminals that
+/// weren't found are logged.
 #[tauri::command]
-fn discover_terminals() -> Vec<mt5::discovery::TerminalInfo> {
-    mt5::discovery::discover_all_terminals()
+fn discover_terminals(state: tauri::State<AppState>) -> mt5::discovery::TerminalDiscovery {
+    let mut terminals = mt5::discovery::discover_all_terminals();
+    let config = state.copier.lock().config.clone();
+    let warnings = config.map(|config| mt5::discovery::annotate_roles(&mut terminals, &config)).unwrap_or_default();
+    for warning in &warnings {
+        warn!("{}", warning);
+    }
+    mt5::discovery::TerminalDiscovery { terminals, warnings }
 }
 
 
@@ -175,13 +244,13 @@ fn add_terminal_path(path: String) -> Option<mt5::bridge::Mt5Terminal> {
 
 /// Get symbol catalog from a receiver terminal
 #[tauri::command]
-fn get_symbol_catalog(terminal_id: String) -> Result<copier::symbol_catalog::SymbolCatalog, String> {
+fn get_symbol_catalog(terminal_id: String) -> Result<copier::symbol_catalog::SymbolCatalog, copier::CopierError> {
     copier::symbol_catalog::fetch_symbol_catalog(&terminal_id)
 }
 
 /// Get master symbols for mapping UI
 #[tauri::command]
-fn get_master_symbols(terminal_id: String) -> Result<Vec<String>, String> {
+fn get_master_symbols(terminal_id: String) -> Result<Vec<String>, copier::CopierError> {
     copier::symbol_catalog::get_master_symbols(&terminal_id)
 }
 
@@ -195,6 +264,69 @@ fn auto_map_symbols(
     Ok(copier::symbol_catalog::auto_map_symbols(&master_symbols, &catalog))
 }
 
+/// Auto-map by contract specs, after the broker pair's mapping hints;
+/// many-to-one conflicts come back disabled alongside a conflict list for
+/// the user to resolve
+#[tauri::command]
+fn auto_map_symbols_by_specs(
+    master_terminal_id: String,
+    receiver_terminal_id: String,
+) -> Result<copier::symbol_catalog::AutoMapResult, String> {
+    let master = copier::symbol_catalog::fetch_symbol_catalog(&master_terminal_id)?;
+    let receiver = copier::symbol_catalog::fetch_symbol_catalog(&receiver_terminal_id)?;
+    let terminals = mt5::discovery::discover_all_terminals();
+    let broker_of = |terminal_id: &str| {
+        terminals
+            .iter()
+            .find(|t| t.terminal_id == terminal_id)
+            .and_then(|t| t.broker.clone())
+    };
+    let hints = copier::mapping_hints::hints_for(
+        broker_of(&master_terminal_id).as_deref(),
+        broker_of(&receiver_terminal_id).as_deref(),
+    );
+    Ok(copier::symbol_catalog::auto_map_symbols_by_specs(&master, &receiver, &hints))
+}
+
+/// Bundled broker mapping hints with the user's local entries applied
+#[tauri::command]
+fn get_mapping_hints() -> copier::mapping_hints::HintsTable {
+    copier::mapping_hints::current()
+}
+
+/// Set a local mapping hint: `broker`'s symbol for `instrument`. Without a
+/// symbol the bundled entry is hidden.
+#[tauri::command]
+fn set_mapping_hint(broker: String, instrument: String, symbol: Option<String>) -> Result<(), String> {
+    copier::mapping_hints::set_override(&broker, &instrument, symbol.as_deref())
+}
+
+/// Save a reviewed set of mappings as a named profile
+#[tauri::command]
+fn save_mapping_profile(
+    name: String,
+    mappings: Vec<copier::symbol_catalog::SymbolMapping>,
+) -> Result<copier::mapping_profiles::MappingProfile, String> {
+    Ok(copier::mapping_profiles::save_profile(&name, mappings)?)
+}
+
+#[tauri::command]
+fn list_mapping_profiles() -> Result<Vec<String>, String> {
+    Ok(copier::mapping_profiles::list_profiles()?)
+}
+
+/// Apply a saved profile to a terminal, dropping mappings whose receiver
+/// symbol isn't in its catalog
+#[tauri::command]
+fn apply_mapping_profile(
+    name: String,
+    terminal_id: String,
+) -> Result<copier::mapping_profiles::AppliedProfile, String> {
+    let profile = copier::mapping_profiles::load_profile(&name)?;
+    let catalog = copier::symbol_catalog::fetch_symbol_catalog(&terminal_id)?;
+    Ok(copier::mapping_profiles::apply_profile(&profile, &catalog))
+}
+
 /// Discovery debug counts (which strategy succeeded?)
 #[tauri::command]
 fn get_discovery_debug() -> serde_json::Value {
@@ -217,6 +349,191 @@ fn get_discovery_debug() -> serde_json::Value {
     })
 }
 
+/// Aggregated copier health; cheap, never refreshes discovery
+#[tauri::command]
+fn get_health_snapshot(state: tauri::State<AppState>) -> copier::health::HealthSnapshot {
+    copier::health::health_snapshot(&state.copier.lock())
+}
+
+/// p50/p95/max copy latency for today's successful executions
+#[tauri::command]
+fn get_latency_summary() -> copier::latency::LatencySummary {
+    copier::latency::summary_today()
+}
+
+/// Per-receiver fill rate, slippage and latency over `window`
+/// (default all time)
+#[tauri::command]
+fn get_receiver_stats(
+    window: Option<copier::receiver_stats::StatsWindow>,
+) -> Vec<copier::receiver_stats::ReceiverStats> {
+    copier::receiver_stats::receiver_stats(window.unwrap_or_default())
+}
+
+/// A receiver's equity samples between `from` and `to` (RFC 3339, either
+/// end open). `receiver_id` is the account number safety state is kept
+/// under.
+#[tauri::command]
+fn get_equity_curve(
+    receiver_id: String,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+) -> Vec<copier::equity_curve::EquitySample> {
+    copier::equity_curve::equity_curve(&receiver_id, from, to)
+}
+
+/// A receiver's safety state (daily P&L, pause), by account number
+#[tauri::command]
+fn export_safety_state(receiver_id: String) -> copier::safety::ReceiverSafetyState {
+    copier::safety::get_receiver_state(&receiver_id)
+}
+
+/// Replace a receiver's safety state, e.g. to test limits near their
+/// threshold. Only in debug builds or with `SATURN_ALLOW_SAFETY_IMPORT=1`.
+#[tauri::command]
+fn import_safety_state(receiver_id: String, state: copier::safety::ReceiverSafetyState) -> Result<(), String> {
+    copier::safety::import_receiver_state(&receiver_id, state)
+}
+
+#[tauri::command]
+fn get_watch_settings() -> copier::watch_settings::WatchSettings {
+    copier::watch_settings::current()
+}
+
+/// Switch queue folder detection between notifications, polling or both
+#[tauri::command]
+fn set_watch_settings(settings: copier::watch_settings::WatchSettings) -> Result<(), String> {
+    copier::watch_settings::set(settings)?;
+    info!("Queue watch mode set to {:?} ({}ms poll)", settings.mode, settings.poll_interval_ms);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_stale_config_settings() -> copier::stale_config::StaleConfigSettings {
+    copier::stale_config::current()
+}
+
+/// Set how old the synced config may get before the stale config warning
+#[tauri::command]
+fn set_stale_config_settings(settings: copier::stale_config::StaleConfigSettings) -> Result<(), String> {
+    copier::stale_config::set(settings)?;
+    info!("Stale config threshold set to {}s", copier::stale_config::current().stale_after_secs);
+    Ok(())
+}
+
+/// Global daily entry cap and today's count across all receivers
+#[tauri::command]
+fn get_global_entry_cap() -> copier::global_cap::GlobalEntryCap {
+    copier::global_cap::current()
+}
+
+/// Set (or clear) the global daily entry cap that stops the copier
+#[tauri::command]
+fn set_global_entry_cap(max_entries_per_day: Option<u32>) -> Result<(), String> {
+    copier::global_cap::set_max_entries_per_day(max_entries_per_day)?;
+    info!("Global daily entry cap set to {:?}", max_entries_per_day);
+    Ok(())
+}
+
+/// Export local settings to one file. The API key and signing secret are
+/// only written when `include_api_key` is set.
+#[tauri::command]
+fn export_settings_bundle(
+    path: String,
+    include_api_key: Option<bool>,
+) -> Result<copier::settings_bundle::BundleSummary, String> {
+    copier::settings_bundle::export(std::path::Path::new(&path), include_api_key.unwrap_or(false))
+}
+
+/// Validate a settings bundle and hold it until `confirm_settings_import`
+#[tauri::command]
+fn import_settings_bundle(path: String) -> Result<copier::settings_bundle::BundleSummary, String> {
+    copier::settings_bundle::stage_import(std::path::Path::new(&path))
+}
+
+/// Apply the bundle staged by `import_settings_bundle`
+#[tauri::command]
+fn confirm_settings_import(state: tauri::State<AppState>) -> Result<(), String> {
+    if let Some(api_key) = copier::settings_bundle::confirm_import()? {
+        // Running loops pick up the imported key on their next tick
+        state.copier.lock().api_key = Some(api_key.trim().to_string());
+        start_agent_sync(&state);
+    }
+    Ok(())
+}
+
+/// Write a support bundle (logs, redacted config, safety/queue state,
+/// health) to `path`
+#[tauri::command]
+async fn collect_diagnostics(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<copier::diagnostics::DiagnosticsManifest, String> {
+    // Snapshot the state so the copier isn't locked while the archive is written
+    let snapshot = state.copier.lock().clone();
+    tokio::task::spawn_blocking(move || copier::diagnostics::collect_diagnostics(std::path::Path::new(&path), &snapshot))
+        .await
+        .map_err(|e| format!("Diagnostics task failed: {}", e))?
+}
+
+/// Point the app's data at another folder (portable installs), or with no
+/// path go back to the default locations. Returns the override now in use.
+#[tauri::command]
+fn set_data_dir(path: Option<String>) -> Result<Option<String>, String> {
+    let dir = data_dir::set_data_dir_override(path.as_deref().map(std::path::Path::new))?;
+    info!("Data directory override set to {:?}", dir);
+    Ok(dir.map(|d| d.display().to_string()))
+}
+
+#[tauri::command]
+fn get_alerts() -> Vec<copier::alerts::Alert> {
+    copier::alerts::get_alerts()
+        .into_iter()
+        .map(|alert| copier::alerts::Alert {
+            message: redact::redact(&alert.message),
+            ..alert
+        })
+        .collect()
+}
+
+/// Command files a receiver's EA hasn't picked up (it may be detached)
+#[tauri::command]
+fn get_unconsumed_commands(
+    receiver_id: String,
+    state: tauri::State<AppState>,
+) -> Result<copier::command_backlog::UnconsumedCommands, String> {
+    let config = state.copier.lock().config.clone().ok_or("No copier config loaded")?;
+    copier::command_backlog::get_unconsumed_commands(&config, &receiver_id)
+}
+
+/// Event files the master EA wrote that couldn't be parsed (quarantined)
+#[tauri::command]
+fn get_parse_errors() -> copier::parse_errors::ParseErrorReport {
+    copier::parse_errors::get_parse_errors()
+}
+
+#[tauri::command]
+fn acknowledge_alert(id: String) -> Result<(), String> {
+    if copier::alerts::acknowledge_alert(&id) {
+        Ok(())
+    } else {
+        Err(format!("Alert {} not found", id))
+    }
+}
+
+#[tauri::command]
+fn clear_alerts() {
+    copier::alerts::clear_alerts();
+}
+
+/// Deep-dive diagnostics for one terminal: discovery details plus live file checks
+#[tauri::command]
+async fn get_terminal_diagnostics(terminal_id: String) -> Result<copier::terminal_diagnostics::TerminalDiagnostics, String> {
+    tokio::task::spawn_blocking(move || copier::terminal_diagnostics::get_terminal_diagnostics(&terminal_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 /// Get diagnostics information
 #[tauri::command]
 fn get_diagnostics() -> copier::DiagnosticsInfo {
@@ -243,18 +560,29 @@ fn get_diagnostics() -> copier::DiagnosticsInfo {
     }).collect();
     
     let idempotency_count = copier::idempotency::get_processed_keys_count();
+
+    let queue = &copier::execution_queue::EXECUTION_QUEUE;
+    let queue_pending = queue.pending_count();
+    let queue_in_progress = queue.in_progress_count();
+    let queue_stats = queue.today_stats();
     
     copier::DiagnosticsInfo {
         terminals: terminal_diags,
-        queue_pending: 0,
-        queue_in_progress: 0,
-        queue_completed_today: 0,
-        queue_failed_today: 0,
+        queue_pending,
+        queue_in_progress,
+        queue_completed_today: queue_stats.completed_today,
+        queue_failed_today: queue_stats.failed_today,
         idempotency_keys_count: idempotency_count,
         recent_errors: vec![],
     }
 }
 
+/// Recently completed/failed queued executions, newest first
+#[tauri::command]
+fn get_queue_recent() -> Vec<copier::execution_queue::QueuedExecution> {
+    copier::execution_queue::EXECUTION_QUEUE.recent_completed()
+}
+
 #[tauri::command]
 fn install_ea(
     terminal_id: String,
@@ -282,18 +610,18 @@ fn install_ea(
     mt5::bridge::install_ea_to_terminal(&terminal_id, &ea_type, &ea_content)
 }
 
+/// Whether a terminal's installed EA matches the one bundled with this build
+#[tauri::command]
+fn verify_installed_ea(terminal_id: String, ea_type: String) -> Result<mt5::ea_verify::EaInstallStatus, String> {
+    mt5::ea_verify::verify_installed_ea(&terminal_id, &ea_type)
+}
+
 
 // ==================== NEW COMMANDS ====================
 
-#[tauri::command]
-fn save_copier_config(
-    master_terminal_id: String,
-    master_account_number: String,
-    master_broker: String,
-    receivers: Vec<serde_json::Value>,
-) -> Result<String, String> {
-    // Convert receivers from JSON to ReceiverConfigFile
-    let receiver_configs: Vec<ReceiverConfigFile> = receivers
+/// Convert wizard receiver JSON into config file receivers
+fn parse_receiver_configs(receivers: Vec<serde_json::Value>) -> Vec<ReceiverConfigFile> {
+    receivers
         .into_iter()
         .enumerate()
         .map(|(idx, r)| {
@@ -341,41 +669,87 @@ fn save_copier_config(
                 symbol_overrides: None,
             }
         })
-        .collect();
+        .collect()
+}
+
+/// Write a built config to the master's folders and every receiver terminal
+fn write_copier_config(config: &CopierConfigFile) -> Result<String, String> {
+    // Ensure copier folders exist for master
+    ensure_copier_folders(&config.master.terminal_id)?;
     
-    // Build the config file
+    // Provision each receiver terminal: folders first, config last
+    for receiver in &config.receivers {
+        let summary = provision_terminal(&receiver.terminal_id, config)?;
+        info!(
+            "Provisioned {} ({} new folder(s), config at {})",
+            summary.terminal_id,
+            summary.created_folders.len(),
+            summary.config_path.display()
+        );
+    }
+    
+    Ok(config.config_hash.clone())
+}
+
+#[tauri::command]
+fn save_copier_config(
+    master_terminal_id: String,
+    master_account_number: String,
+    master_broker: String,
+    receivers: Vec<serde_json::Value>,
+) -> Result<String, String> {
     let config = build_config_file(
         &master_terminal_id,
         &master_account_number,
         &master_broker,
-        receiver_configs.clone(),
+        parse_receiver_configs(receivers),
     );
-    
-    // Ensure copier folders exist for master
-    ensure_copier_folders(&master_terminal_id)?;
-    
-    // Save config to each receiver terminal
-    for receiver in &receiver_configs {
-        ensure_copier_folders(&receiver.terminal_id)?;
-        save_config_to_terminal(&receiver.terminal_id, &config)?;
-    }
-    
-    Ok(config.config_hash)
+    write_copier_config(&config)
+}
+
+/// Build the config the EAs would receive, without writing it anywhere
+#[tauri::command]
+fn preview_config(
+    master_terminal_id: String,
+    master_account_number: String,
+    master_broker: String,
+    receivers: Vec<serde_json::Value>,
+) -> CopierConfigFile {
+    build_config_file(
+        &master_terminal_id,
+        &master_account_number,
+        &master_broker,
+        parse_receiver_configs(receivers),
+    )
+}
+
+/// Write a config returned by `preview_config`. Rejected if it was modified
+/// since the preview (hash mismatch).
+#[tauri::command]
+fn save_previewed_config(config: CopierConfigFile) -> Result<String, String> {
+    write_copier_config(&config)
 }
 
 #[tauri::command]
 fn get_position_sync_status(
     master_terminal_id: String,
     receiver_terminal_ids: Vec<String>,
-) -> Result<PositionSyncStatus, String> {
-    generate_sync_report(&master_terminal_id, &receiver_terminal_ids)
+    state: tauri::State<AppState>,
+) -> Result<PositionSyncStatus, copier::CopierError> {
+    let copier = state.copier.lock();
+    let policies = sltp_policies(copier.config.as_ref());
+    let sltp_sync = sltp_sync_settings(copier.config.as_ref());
+    let magics = magic_numbers(copier.config.as_ref());
+    let manual_sltp = manual_sltp_receivers(copier.config.as_ref());
+    drop(copier);
+    generate_sync_report(&master_terminal_id, &receiver_terminal_ids, &policies, &sltp_sync, &magics, &manual_sltp)
 }
 
 #[tauri::command]
 fn sync_position_to_receiver(
     receiver_terminal_id: String,
     command: serde_json::Value,
-) -> Result<(), String> {
+) -> Result<(), copier::CopierError> {
     let sync_command = SyncCommand {
         command_type: command["command_type"].as_str().unwrap_or("open").to_string(),
         position_id: command["position_id"].as_i64(),
@@ -385,10 +759,16 @@ fn sync_position_to_receiver(
         volume: command["volume"].as_f64(),
         sl: command["sl"].as_f64(),
         tp: command["tp"].as_f64(),
+        order_type: command["order_type"].as_str().map(String::from),
+        price: command["price"].as_f64(),
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
     
-    write_sync_command(&receiver_terminal_id, &sync_command)
+    write_sync_command(&receiver_terminal_id, &sync_command)?;
+    // Levels sent by hand are the copier's too; keep reconciliation from
+    // reporting them as manual edits
+    copier::commanded_levels::remember_command(&receiver_terminal_id, &sync_command);
+    Ok(())
 }
 
 #[tauri::command]
@@ -396,6 +776,13 @@ fn emergency_close_all(receiver_terminal_ids: Vec<String>, reason: Option<String
     close_all_positions(&receiver_terminal_ids, reason)
 }
 
+/// Flatten every configured receiver, pause copying, and stop the copier
+#[tauri::command]
+fn panic_button(reason: Option<String>, state: tauri::State<AppState>) -> Vec<ReceiverCommandResult> {
+    let reason = reason.unwrap_or_else(|| "Panic button".to_string());
+    copier::commands::panic_stop(&state.copier, &reason)
+}
+
 #[tauri::command]
 fn pause_receivers(receiver_terminal_ids: Vec<String>) -> Result<(), String> {
     pause_all_receivers(&receiver_terminal_ids)
@@ -406,6 +793,239 @@ fn resume_receivers(receiver_terminal_ids: Vec<String>) -> Result<(), String> {
     resume_all_receivers(&receiver_terminal_ids)
 }
 
+/// Close one copied position on one receiver, found by its master position
+/// id. Returns the receiver position id.
+#[tauri::command]
+fn close_receiver_position(
+    receiver_id: String,
+    master_position_id: i64,
+    state: tauri::State<AppState>,
+) -> Result<i64, String> {
+    copier::commands::close_receiver_position(&state.copier, &receiver_id, master_position_id)
+}
+
+/// Make one receiver match its master now: open what's missing, close
+/// orphans, re-send SL/TP. Returns every command issued and its outcome.
+#[tauri::command]
+fn resync_receiver(
+    receiver_id: String,
+    state: tauri::State<AppState>,
+) -> Result<Vec<copier::resync::ResyncAction>, String> {
+    copier::resync::resync_receiver(&state.copier, &receiver_id)
+}
+
+/// Stop (or resume) new opens on one receiver without removing it from the
+/// config. Closes of its existing positions keep being copied.
+#[tauri::command]
+fn set_receiver_enabled(receiver_id: String, enabled: bool, state: tauri::State<AppState>) -> Result<(), String> {
+    let mut copier = state.copier.lock();
+    let config = copier.config.as_mut().ok_or("No copier config loaded")?;
+    if !copier::receiver_toggles::apply_to(config, &receiver_id, enabled) {
+        return Err(format!("Receiver {} is not in the current config", receiver_id));
+    }
+    copier::receiver_toggles::set_enabled(&receiver_id, enabled)?;
+    info!("Receiver {} {}", receiver_id, if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Map one master symbol to `receiver_symbol` on a receiver by hand. The
+/// symbol must be tradable in the receiver's catalog; the mapping is kept
+/// across config syncs and the receiver's EA config is re-written.
+#[tauri::command]
+fn set_symbol_mapping(
+    receiver_id: String,
+    master_symbol: String,
+    receiver_symbol: String,
+    state: tauri::State<AppState>,
+) -> Result<copier::SymbolMapping, String> {
+    let terminal_id = {
+        let copier = state.copier.lock();
+        let config = copier.config.as_ref().ok_or("No copier config loaded")?;
+        config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .map(|r| r.terminal_id.clone())
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?
+    };
+    let catalog = copier::symbol_catalog::fetch_symbol_catalog(&terminal_id)?;
+
+    let mut copier = state.copier.lock();
+    let config = copier.config.as_mut().ok_or("No copier config loaded")?;
+    let mapping = copier::manual_mappings::apply_to(config, &receiver_id, &master_symbol, &receiver_symbol, &catalog)?;
+    copier::manual_mappings::remember(&receiver_id, &mapping)?;
+    copier::hot_reload::reprovision_receiver(config, &terminal_id)?;
+    info!("Receiver {} maps {} -> {} (manual)", receiver_id, mapping.master_symbol, mapping.receiver_symbol);
+    Ok(mapping)
+}
+
+/// Re-check a receiver's mappings against its freshly fetched catalog:
+/// vanished receiver symbols are marked stale or re-mapped, duplicates
+/// merged. Returns the proposed changes; nothing is applied.
+#[tauri::command]
+fn reconcile_symbol_mappings(
+    receiver_id: String,
+    state: tauri::State<AppState>,
+) -> Result<copier::mapping_reconcile::MappingReconciliation, String> {
+    let (receiver, master) = {
+        let copier = state.copier.lock();
+        let config = copier.config.as_ref().ok_or("No copier config loaded")?;
+        let receiver = config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .cloned()
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?;
+        let master = config
+            .all_masters()
+            .into_iter()
+            .find(|m| config.receiver_follows(&receiver, &m.account_id))
+            .cloned();
+        (receiver, master)
+    };
+
+    let receiver_catalog = copier::symbol_catalog::fetch_symbol_catalog(&receiver.terminal_id)?;
+    // Without the master's catalog orphans are re-mapped by name only
+    let master_catalog = master
+        .as_ref()
+        .and_then(|m| copier::symbol_catalog::fetch_symbol_catalog(&m.terminal_id).ok());
+    let hints = copier::mapping_hints::hints_for(master.as_ref().map(|m| m.broker.as_str()), Some(&receiver.broker));
+    let result = copier::mapping_reconcile::reconcile(
+        &receiver_id,
+        &receiver.symbol_mappings,
+        master_catalog.as_ref(),
+        &receiver_catalog,
+        &hints,
+    );
+    info!("Reconciled symbol mappings of {}: {} change(s)", receiver_id, result.changes.len());
+    Ok(result)
+}
+
+/// Apply the changes the user confirmed from `reconcile_symbol_mappings`
+/// (stale and conflicting mappings disabled, duplicates merged, re-mapped
+/// symbols switched). They are kept across config syncs and the receiver's
+/// EA config is re-written.
+#[tauri::command]
+fn apply_mapping_reconciliation(
+    receiver_id: String,
+    changes: Vec<copier::mapping_reconcile::MappingChange>,
+    state: tauri::State<AppState>,
+) -> Result<(), String> {
+    let terminal_id = {
+        let copier = state.copier.lock();
+        let config = copier.config.as_ref().ok_or("No copier config loaded")?;
+        config
+            .receivers
+            .iter()
+            .find(|r| r.account_id == receiver_id)
+            .map(|r| r.terminal_id.clone())
+            .ok_or_else(|| format!("Receiver {} is not in the current config", receiver_id))?
+    };
+    let catalog = copier::symbol_catalog::fetch_symbol_catalog(&terminal_id)?;
+
+    let mut copier = state.copier.lock();
+    let config = copier.config.as_mut().ok_or("No copier config loaded")?;
+    let entries = copier::mapping_reconcile::apply_changes(config, &receiver_id, &changes, &catalog)?;
+    copier::manual_mappings::remember_all(&receiver_id, entries)?;
+    copier::hot_reload::reprovision_receiver(config, &terminal_id)?;
+    info!("Applied {} symbol mapping change(s) to {}", changes.len(), receiver_id);
+    Ok(())
+}
+
+/// Walk every check an entry on `master_symbol` would go through for a
+/// receiver and report each outcome
+#[tauri::command]
+fn explain_copy_decision(
+    receiver_id: String,
+    master_symbol: String,
+    state: tauri::State<AppState>,
+) -> Result<copier::copy_decision::CopyDecision, String> {
+    copier::copy_decision::explain_for(&state.copier, &receiver_id, &master_symbol)
+}
+
+/// Lots each risk mode would send for a sample trade, clamped to
+/// `symbol_spec`. `risk_values` maps mode -> setting; missing modes use
+/// defaults.
+#[tauri::command]
+fn preview_lot_sizing(
+    master_lots: f64,
+    master_balance: f64,
+    receiver_balance: f64,
+    sl_distance_pips: f64,
+    symbol_spec: copier::symbol_catalog::SymbolSpec,
+    risk_values: Option<HashMap<String, f64>>,
+) -> std::collections::BTreeMap<String, f64> {
+    copier::lot_preview::preview_lot_sizing(
+        master_lots,
+        master_balance,
+        receiver_balance,
+        sl_distance_pips,
+        &symbol_spec,
+        &risk_values.unwrap_or_default(),
+    )
+}
+
+/// Setup wizard: recommended risk config for a receiver, with the reasoning
+#[tauri::command]
+fn suggest_risk_config(
+    master_balance: f64,
+    receiver_balance: f64,
+    target_risk_percent: f64,
+) -> Result<copier::risk_suggestion::RiskSuggestion, String> {
+    copier::risk_suggestion::suggest_risk_config(master_balance, receiver_balance, target_risk_percent)
+}
+
+#[tauri::command]
+fn get_market_hours() -> copier::market_hours::MarketCalendar {
+    copier::market_hours::current()
+}
+
+/// Replace the session calendar; executions already parked keep their
+/// retry time but are re-checked when picked up
+#[tauri::command]
+fn set_market_hours(calendar: copier::market_hours::MarketCalendar) -> Result<(), String> {
+    copier::market_hours::set(calendar)
+}
+
+/// Trades waiting for manual approval, oldest first
+#[tauri::command]
+fn get_pending_approvals() -> Vec<copier::approvals::PendingApproval> {
+    copier::approvals::list_pending()
+}
+
+/// Approve a held trade; it then executes like a live event
+#[tauri::command]
+fn approve_execution(id: String, state: tauri::State<AppState>) -> Result<(), String> {
+    copier::approvals::approve(&id, &state.copier)
+}
+
+#[tauri::command]
+fn reject_execution(id: String, state: tauri::State<AppState>) -> Result<(), String> {
+    copier::approvals::reject(&id, &state.copier)
+}
+
+/// Debug: run a journal file (default: today's) back through the event
+/// processor in dry-run mode against the current config
+#[tauri::command]
+fn replay_journal(
+    path: Option<String>,
+    state: tauri::State<AppState>,
+) -> Result<Vec<copier::journal::ReplayedEvent>, String> {
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => copier::journal::JOURNAL
+            .path_for(chrono::Utc::now().date_naive())
+            .ok_or_else(|| "Event journal is not available".to_string())?,
+    };
+    let config = state
+        .copier
+        .lock()
+        .config
+        .clone()
+        .ok_or_else(|| "No configuration loaded".to_string())?;
+    copier::journal::replay(&path, &config)
+}
+
 #[tauri::command]
 fn get_master_heartbeat(terminal_id: String) -> Result<Heartbeat, String> {
     read_master_heartbeat(&terminal_id)
@@ -416,8 +1036,77 @@ fn check_master_online(terminal_id: String) -> bool {
     is_master_online(&terminal_id)
 }
 
+/// Send a receiver a test command its EA acknowledges without trading and
+/// report each stage of the round trip
+#[tauri::command]
+async fn run_copy_selftest(
+    receiver_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<copier::selftest::SelftestResult, String> {
+    let copier_state = state.copier.clone();
+    // Waits on the EA for up to SELFTEST_TIMEOUT - keep it off the main thread
+    tokio::task::spawn_blocking(move || copier::selftest::run_copy_selftest(&copier_state, &receiver_id))
+        .await
+        .map_err(|e| format!("Self-test task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn ping_terminal(terminal_id: String) -> Result<PingResult, String> {
+    // Polls for up to a few seconds - keep it off the main thread
+    tokio::task::spawn_blocking(move || {
+        copier::commands::ping_terminal(&terminal_id, std::time::Duration::from_secs(3))
+    })
+    .await
+    .map_err(|e| format!("Ping task failed: {}", e))?
+}
+
+
+
+
+// ==================== KILL SWITCH ====================
+
+/// Create the kill switch file; all trade processing stops while it exists
+#[tauri::command]
+fn engage_kill_switch(reason: Option<String>) -> Result<(), String> {
+    let reason = reason.unwrap_or_else(|| "Engaged from the app".to_string());
+    copier::kill_switch::KILL_SWITCH.engage(&reason)
+}
+
+#[tauri::command]
+fn release_kill_switch() -> Result<(), String> {
+    copier::kill_switch::KILL_SWITCH.release()
+}
+
+#[tauri::command]
+fn is_kill_switch_engaged() -> bool {
+    copier::kill_switch::is_engaged()
+}
+
+// ==================== LOGS ====================
+
+#[tauri::command]
+fn get_log_dir_path() -> String {
+    logging::get_log_dir().to_string_lossy().to_string()
+}
+
+/// Last `lines` lines of today's log (empty if nothing logged yet)
+#[tauri::command]
+async fn tail_log(lines: usize) -> Vec<String> {
+    tokio::task::spawn_blocking(move || logging::tail_log(lines))
+        .await
+        .unwrap_or_default()
+}
 
+#[tauri::command]
+fn open_log_dir() -> Result<(), String> {
+    logging::open_log_dir()
+}
 
+/// Change log verbosity at runtime (trace/debug/info/warn/error); persisted
+#[tauri::command]
+fn set_log_level(level: String) -> Result<(), String> {
+    logging::set_log_level(&level)
+}
 
 // ==================== DEBUG BUNDLE EXPORT ====================
 
@@ -453,9 +1142,8 @@ async fn export_debug_bundle(save_path: String) -> Result<String, String> {
     
     // Safety states
     bundle.push_str("=== SAFETY STATES ===\n");
-    if let Ok(safety_json) = std::fs::read_to_string(
-        std::env::var("APPDATA").unwrap_or_default() + "\\" + copier::safety::APP_DATA_FOLDER + "\\safety_state.json"
-    ) {
+    let safety_path = data_dir::app_data_dir().unwrap_or_default().join("safety_state.json");
+    if let Ok(safety_json) = std::fs::read_to_string(safety_path) {
         bundle.push_str(&safety_json);
     } else {
         bundle.push_str("No safety state file found\n");
@@ -474,8 +1162,8 @@ async fn export_debug_bundle(save_path: String) -> Result<String, String> {
         }
     }
     
-    // Write to file
-    std::fs::write(&save_path, &bundle)
+    // Write to file, with logins and config account numbers masked
+    std::fs::write(&save_path, redact::redact(&bundle))
         .map_err(|e| format!("Failed to write debug bundle: {}", e))?;
     
     Ok(save_path)
@@ -528,9 +1216,15 @@ fn create_system_tray() -> SystemTray {
 fn main() {
     // Initialize structured logging (keep guard alive for the app's lifetime)
     let _log_guard = logging::init_logging();
+    if let Some(dir) = data_dir::data_dir_override() {
+        info!("Using data directory {}", dir.display());
+    }
 
     let copier_state = Arc::new(Mutex::new(CopierState::default()));
 
+    // Surface a kill switch left engaged from a previous session
+    copier::kill_switch::blocks(&copier_state);
+
     // Try to load saved API key
     if let Ok(api_key) = sync::config::load_api_key() {
         copier_state.lock().api_key = Some(api_key);
@@ -602,15 +1296,29 @@ fn main() {
         copier::commands::resume_all_receivers(&[id]).map_err(|e| e.to_string())?;
         Ok(serde_json::json!({}))
     });
-    router.on("sync_positions", |payload| async move {
-        let master = payload["master_terminal_id"].as_str().unwrap_or("").to_string();
-        let receivers: Vec<String> = payload["receiver_terminal_ids"]
-            .as_array()
-            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
-            .unwrap_or_default();
-        let report = copier::position_sync::generate_sync_report(&master, &receivers)
-            .map_err(|e| e.to_string())?;
-        Ok(serde_json::to_value(report).unwrap_or(serde_json::json!({})))
+    let copier_for_sync = copier_state.clone();
+    router.on("sync_positions", move |payload| {
+        let copier = copier_for_sync.clone();
+        async move {
+            let master = payload["master_terminal_id"].as_str().unwrap_or("").to_string();
+            let receivers: Vec<String> = payload["receiver_terminal_ids"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            let (policies, sltp_sync, magics, manual_sltp) = {
+                let copier = copier.lock();
+                (
+                    sltp_policies(copier.config.as_ref()),
+                    sltp_sync_settings(copier.config.as_ref()),
+                    magic_numbers(copier.config.as_ref()),
+                    manual_sltp_receivers(copier.config.as_ref()),
+                )
+            };
+            let report =
+                copier::position_sync::generate_sync_report(&master, &receivers, &policies, &sltp_sync, &magics, &manual_sltp)
+                    .map_err(|e| e.to_string())?;
+            Ok(serde_json::to_value(report).unwrap_or(serde_json::json!({})))
+        }
     });
     router.on("rescan_terminals", |_payload| async move {
         let terms = mt5::discovery::refresh_discovery_cache();
@@ -626,8 +1334,12 @@ fn main() {
                 .await
                 .map_err(|e| e.to_string())?;
             let mut c = copier.lock();
-            c.config = Some(cfg);
+            if copier::hot_reload::install_config(&mut c, cfg) {
+                spawn_reprovision(c.config.clone());
+            }
             c.last_sync = Some(chrono::Utc::now().to_rfc3339());
+            c.config_from_cache = false;
+            c.config_cache_age_secs = None;
             Ok(serde_json::json!({}))
         }
     });
@@ -665,8 +1377,12 @@ fn main() {
                         tauri::async_runtime::spawn(async move {
                             if let Ok(config) = sync::config::fetch_config(&key).await {
                                 let mut copier = state_clone.lock();
-                                copier.config = Some(config);
+                                if copier::hot_reload::install_config(&mut copier, config) {
+                                    spawn_reprovision(copier.config.clone());
+                                }
                                 copier.last_sync = Some(chrono::Utc::now().to_rfc3339());
+                                copier.config_from_cache = false;
+                                copier.config_cache_age_secs = None;
                                 info!("Config synced successfully");
                             }
                         });
@@ -685,21 +1401,7 @@ fn main() {
                 }
                 "quit" => {
                     info!("Application shutting down via tray menu");
-                    
-                    // Request file watcher shutdown (graceful termination - C3 fix)
-                    copier::file_watcher::request_shutdown();
-                    
-                    // Give file watcher time to finish any in-progress work
-                    std::thread::sleep(std::time::Duration::from_millis(500));
-                    
-                    // Save copier state before exit
-                    let state = app.state::<AppState>();
-                    let copier = state.copier.lock();
-                    if let Err(e) = copier::safety::save_all_safety_states() {
-                        warn!("Failed to save safety states on exit: {}", e);
-                    }
-                    drop(copier);
-                    std::process::exit(0);
+                    copier::shutdown::graceful_shutdown();
                 }
                 _ => {}
             },
@@ -707,10 +1409,9 @@ fn main() {
         })
         .on_window_event(|event| match event.event() {
             tauri::WindowEvent::CloseRequested { api, .. } => {
-                // Save safety states before hiding (in case user quits from tray)
-                if let Err(e) = copier::safety::save_all_safety_states() {
-                    warn!("Failed to save safety states on window close: {}", e);
-                }
+                // Save safety state and the queue before hiding (in case the
+                // process is killed while in the tray)
+                copier::shutdown::persist_state();
                 event.window().hide().unwrap();
                 api.prevent_close();
             }
@@ -729,22 +1430,80 @@ fn main() {
             discover_terminals,
             add_terminal_path,
             install_ea,
+            verify_installed_ea,
             get_symbol_catalog,
             get_master_symbols,
             auto_map_symbols,
+            auto_map_symbols_by_specs,
+            get_mapping_hints,
+            set_mapping_hint,
+            save_mapping_profile,
+            list_mapping_profiles,
+            apply_mapping_profile,
             get_diagnostics,
+            get_terminal_diagnostics,
+            get_health_snapshot,
+            get_latency_summary,
+            get_receiver_stats,
+            get_equity_curve,
+            export_safety_state,
+            import_safety_state,
+            get_watch_settings,
+            set_watch_settings,
+            get_stale_config_settings,
+            set_stale_config_settings,
+            get_global_entry_cap,
+            set_global_entry_cap,
+            export_settings_bundle,
+            import_settings_bundle,
+            confirm_settings_import,
+            collect_diagnostics,
+            set_data_dir,
+            get_alerts,
+            get_parse_errors,
+            get_unconsumed_commands,
+            acknowledge_alert,
+            clear_alerts,
+            get_queue_recent,
             get_discovery_debug,
             // Config & sync commands
             save_copier_config,
+            preview_config,
+            save_previewed_config,
             get_position_sync_status,
             sync_position_to_receiver,
             emergency_close_all,
+            panic_button,
             pause_receivers,
             resume_receivers,
+            close_receiver_position,
+            resync_receiver,
+            set_receiver_enabled,
+            set_symbol_mapping,
+            reconcile_symbol_mappings,
+            apply_mapping_reconciliation,
+            explain_copy_decision,
+            preview_lot_sizing,
+            suggest_risk_config,
+            get_market_hours,
+            set_market_hours,
+            get_pending_approvals,
+            approve_execution,
+            reject_execution,
+            replay_journal,
             get_master_heartbeat,
             check_master_online,
+            ping_terminal,
+            run_copy_selftest,
             // Debug commands
             export_debug_bundle,
+            engage_kill_switch,
+            release_kill_switch,
+            is_kill_switch_engaged,
+            get_log_dir_path,
+            tail_log,
+            open_log_dir,
+            set_log_level,
         ])
         .setup(|app| {
             // Show main window on startup
@@ -761,28 +1520,40 @@ fn main() {
                 copier::file_watcher::start_watching(copier);
             });
 
-            // Periodic execution upload to cloud (Phase 3.3 client side)
-            let copier_for_flush = state.copier.clone();
-            tauri::async_runtime::spawn(async move {
-                loop {
-                    tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-                    let api_key = { copier_for_flush.lock().api_key.clone() };
-                    if let Some(key) = api_key {
-                        match sync::executions::process_queue(&key).await {
-                            Ok(n) if n > 0 => info!("Uploaded {} queued executions to cloud", n),
-                            Ok(_) => {}
-                            Err(e) => warn!("Execution upload failed: {}", e),
-                        }
+            // Retry deferred (rate-limited / failed) executions once their
+            // retry time has passed, and catch up receivers that came online
+            // after the master opened positions
+            let copier_for_queue = state.copier.clone();
+            std::thread::spawn(move || while !copier::file_watcher::is_shutdown_requested() {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                let (is_running, config) = {
+                    let copier = copier_for_queue.lock();
+                    if copier.is_running && copier.config.is_some() {
+                        copier::stale_config::watch(&copier);
                     }
+                    (copier.is_running, copier.config.clone())
+                };
+                if let (true, Some(config)) = (is_running, config) {
+                    copier::event_processor::process_deferred(&config, copier_for_queue.clone());
+                    copier::approvals::expire_overdue(&copier_for_queue);
+                    copier::catch_up::run_pending(&config, &copier_for_queue);
+                    copier::aggregate::run_pending(&config, &copier_for_queue);
+                    copier::safety::auto_resume_receivers(&config);
+                    copier::equity_curve::sample_receivers(&config);
+                    copier::alerts::watch_masters(&config);
+                    copier::command_backlog::watch_receivers(&config);
                 }
             });
 
+            // Execution upload to cloud (Phase 3.3 client side)
+            tauri::async_runtime::spawn(sync::executions::run_upload_task(state.copier.clone()));
+
             // Start the agent telemetry + command loops if we have an API key.
             // If not, `set_api_key` will start them right after pairing.
             let state_ref = app.state::<AppState>();
-            let api_key = state_ref.copier.lock().api_key.clone();
-            if let Some(key) = api_key {
-                start_agent_sync(key, &state_ref);
+            let has_api_key = state_ref.copier.lock().api_key.is_some();
+            if has_api_key {
+                start_agent_sync(&state_ref);
             } else {
                 warn!("Agent sync not started — no API key yet. Pair the desktop in the web Console.");
             }
@@ -116,7 +116,7 @@ fn extract_broker_from_folder_name(install_path: &Path) -> Option<String> {
     if cleaned.is_empty() || cleaned == "Program Files" || cleaned == "Program Files (x86)" {
         return None;
     }
-    Some(cleaned)
+    Some(super::broker_names::normalize_broker_name(&cleaned))
 }
 
 /// Read broker from .srv files in config directory
@@ -133,12 +133,9 @@ fn read_broker_from_srv_files(data_path: &Path) -> Option<String> {
             if entry_path.extension().map(|e| e == "srv").unwrap_or(false) {
                 if let Some(stem) = entry_path.file_stem() {
                     let name = stem.to_string_lossy();
-                    // Extract first part before "-" (broker name)
-                    // E.g., "VantageInt-Live01" -> "VantageInt"
-                    if let Some(first_part) = name.split('-').next() {
-                        if !first_part.is_empty() {
-                            return Some(expand_broker_abbreviation(first_part));
-                        }
+                    // Broker part before "-", e.g. "VantageInt-Live01" -> "Vantage International"
+                    if let Some(broker) = super::broker_names::broker_from_server(&name) {
+                        return Some(broker);
                     }
                 }
             }
@@ -147,12 +144,6 @@ fn read_broker_from_srv_files(data_path: &Path) -> Option<String> {
     None
 }
 
-/// Expand common broker abbreviations to full names
-/// Delegates to discovery module for consistent behavior
-fn expand_broker_abbreviation(abbr: &str) -> String {
-    super::discovery::expand_broker_abbreviation(abbr)
-}
-
 fn read_broker_from_ini(ini_path: &Path) -> Option<String> {
     if !ini_path.exists() {
         return None;
@@ -200,11 +191,9 @@ fn detect_broker_from_community_folder(path: &Path) -> Option<String> {
                     // Server file name often contains broker info
                     if let Some(name) = entry_path.file_stem() {
                         let name_str = name.to_string_lossy().to_string();
-                        // Extract broker name from server file name (e.g., "ICMarkets-Demo01" -> "ICMarkets")
-                        if let Some(broker_part) = name_str.split('-').next() {
-                            if !broker_part.is_empty() {
-                                return Some(expand_broker_abbreviation(broker_part));
-                            }
+                        // Extract broker name from server file name (e.g., "ICMarkets-Demo01" -> "IC Markets")
+                        if let Some(broker) = super::broker_names::broker_from_server(&name_str) {
+                            return Some(broker);
                         }
                         return Some(name_str);
                     }
@@ -349,7 +338,7 @@ pub fn install_ea_to_terminal(
 pub fn find_terminal_path(terminal_id: &str) -> Result<PathBuf, String> {
     // 1. Discovery cache (covers AppData hashes, Registry installs, manual paths,
     //    LocalAppData\Programs, portable installs).
-    let terminals = discovery::discover_all_terminals_cached(false);
+    let terminals = discovery::discover_all_terminals();
     let discovered_count = terminals.len();
     for t in &terminals {
         if t.terminal_id == terminal_id {
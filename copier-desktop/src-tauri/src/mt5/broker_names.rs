@@ -0,0 +1,134 @@
+//! Broker name normalization
+//!
+//! Turns the raw names we see pre-handshake (folder names, `.srv` file names,
+//! `terminal.ini` Company=) into canonical broker names:
+//! 1. The static abbreviation table in `discovery` wins when it matches the
+//!    whole name, less corporate suffixes ("ICM Capital" is not "ICM")
+//! 2. Corporate suffixes ("Ltd", "LLC", "Group", ...) are stripped
+//! 3. Server-name prefixes learned from EA handshakes map to the broker the
+//!    EA reported, so other terminals on the same servers resolve before
+//!    their own handshake
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use super::discovery::known_broker;
+
+/// Trailing words dropped from a company name
+const CORPORATE_SUFFIXES: &[&str] = &[
+    "LTD", "LIMITED", "LLC", "INC", "CORP", "PLC", "PTY", "CO", "GROUP", "INTERNATIONAL", "GLOBAL",
+];
+
+/// Further trailing words ignored when looking a name up in the table
+/// ("FTMO Global Markets Ltd" is "FTMO")
+const TABLE_SUFFIXES: &[&str] = &["MARKETS"];
+
+/// Trailing words that name a server rather than a broker ("AcmeFX Live")
+const SERVER_SUFFIXES: &[&str] = &["LIVE", "DEMO", "REAL", "SERVER", "TRADE"];
+
+/// Uppercased server prefix -> broker name reported by an EA handshake
+static LEARNED_SERVERS: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Canonical broker name for a raw company/folder name
+pub fn normalize_broker_name(raw: &str) -> String {
+    let mut words: Vec<&str> = raw.split_whitespace().collect();
+    if words.is_empty() {
+        return raw.trim().to_string();
+    }
+
+    // The whole name, dropping suffixes one at a time, in the static table
+    let mut lookup = words.as_slice();
+    loop {
+        if let Some(name) = known_broker(&lookup.join(" ")).or_else(|| known_broker(&lookup.concat())) {
+            return name.to_string();
+        }
+        match lookup.split_last() {
+            Some((last, rest)) if !rest.is_empty() && (is_one_of(last, CORPORATE_SUFFIXES) || is_one_of(last, TABLE_SUFFIXES)) => {
+                lookup = rest;
+            }
+            _ => break,
+        }
+    }
+
+    while words.len() > 1 && is_one_of(words[words.len() - 1], CORPORATE_SUFFIXES) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// Remember which broker a server belongs to (from an EA handshake)
+pub fn learn_server_broker(server: &str, broker: &str) {
+    let Some(prefix) = server_prefix(server) else {
+        return;
+    };
+    if broker.trim().is_empty() {
+        return;
+    }
+    LEARNED_SERVERS
+        .lock()
+        .insert(prefix.to_uppercase(), normalize_broker_name(broker));
+}
+
+/// Broker name for a server or `.srv` file name ("FTMO-Server3", "AcmeFX-Live")
+pub fn broker_from_server(server: &str) -> Option<String> {
+    let prefix = server_prefix(server)?;
+    if let Some(learned) = LEARNED_SERVERS.lock().get(&prefix.to_uppercase()) {
+        return Some(learned.clone());
+    }
+    Some(normalize_broker_name(&prefix))
+}
+
+/// Broker part of a server name: text before the first '-', without
+/// trailing "Live"/"Demo"/"Server2"-style words
+fn server_prefix(server: &str) -> Option<String> {
+    let head = server.split('-').next()?.trim();
+    let mut words: Vec<&str> = head.split_whitespace().collect();
+    while words.len() > 1 && is_one_of(words[words.len() - 1].trim_end_matches(|c: char| c.is_ascii_digit()), SERVER_SUFFIXES) {
+        words.pop();
+    }
+    let prefix = words.join(" ");
+    if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix)
+    }
+}
+
+fn is_one_of(word: &str, list: &[&str]) -> bool {
+    let word = word.trim_end_matches(['.', ',']).to_uppercase();
+    list.contains(&word.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_suffixes_and_applies_table() {
+        assert_eq!(normalize_broker_name("FTMO Global Markets Ltd"), "FTMO");
+        assert_eq!(normalize_broker_name("  Vantage   International Group Limited"), "Vantage International");
+        assert_eq!(normalize_broker_name("Acme Capital Ltd."), "Acme Capital");
+        assert_eq!(normalize_broker_name("Unknown"), "Unknown");
+        assert_eq!(normalize_broker_name("IC Markets Global"), "IC Markets");
+        assert_eq!(normalize_broker_name("IG Markets Ltd"), "IG Markets");
+    }
+
+    #[test]
+    fn test_table_abbreviation_must_be_the_whole_name() {
+        assert_eq!(normalize_broker_name("ICM Capital Ltd"), "ICM Capital");
+        assert_eq!(normalize_broker_name("XM Trading Solutions"), "XM Trading Solutions");
+        assert_eq!(normalize_broker_name("ICM Ltd"), "IC Markets");
+    }
+
+    #[test]
+    fn test_unknown_broker_from_live_server() {
+        assert_eq!(broker_from_server("ZentraFX-Live").as_deref(), Some("ZentraFX"));
+        assert_eq!(broker_from_server("ZentraFX Live2").as_deref(), Some("ZentraFX"));
+        assert_eq!(broker_from_server("ICM-Live07").as_deref(), Some("IC Markets"));
+
+        // A handshake teaches the real name for every server on that prefix
+        learn_server_broker("ZentraFX-Live", "Zentra FX Trading LLC");
+        assert_eq!(broker_from_server("ZentraFX-Demo").as_deref(), Some("Zentra FX Trading"));
+    }
+}
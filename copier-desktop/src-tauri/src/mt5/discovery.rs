@@ -17,6 +17,9 @@ use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+use super::broker_names::{broker_from_server, learn_server_broker, normalize_broker_name};
+use crate::copier::{CopierConfig, CopierError};
+
 // ==================== CACHING ====================
 // Cache discovery results to prevent UI freezing from repeated expensive scans
 
@@ -24,7 +27,21 @@ lazy_static::lazy_static! {
     static ref DISCOVERY_CACHE: Mutex<DiscoveryCache> = Mutex::new(DiscoveryCache::default());
 }
 
-const CACHE_TTL_SECS: u64 = 10; // Refresh at most every 10 seconds
+/// Cache age the UI accepts: refresh at most every 10 seconds
+pub const DEFAULT_CACHE_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Cache age background tasks accept. They only look up terminals that are
+/// already configured, so a minute-old scan is as good as a fresh one and
+/// spares a WMIC scan on most passes.
+pub const BACKGROUND_CACHE_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Oldest master EA version the app copies from. Older EAs can write queue
+/// files this version doesn't understand.
+pub const MIN_SUPPORTED_MASTER_EA_VERSION: &str = "1.01";
+
+/// Oldest receiver EA version the app copies to. Older receivers don't echo
+/// idempotency keys or track every position copied from one master position.
+pub const MIN_SUPPORTED_RECEIVER_EA_VERSION: &str = "2.02";
 
 #[derive(Default)]
 struct DiscoveryCache {
@@ -32,6 +49,20 @@ struct DiscoveryCache {
     last_refresh: Option<Instant>,
 }
 
+impl DiscoveryCache {
+    /// Cached terminals, re-scanned with `scan` first if older than `max_age`
+    fn get(&mut self, max_age: Duration, scan: impl FnOnce() -> Vec<TerminalInfo>) -> Vec<TerminalInfo> {
+        if self.last_refresh.is_none_or(|last| last.elapsed() > max_age) {
+            debug!("Refreshing terminal discovery cache...");
+            self.terminals = scan();
+            self.last_refresh = Some(Instant::now());
+        } else {
+            debug!("Using cached terminal discovery results");
+        }
+        self.terminals.clone()
+    }
+}
+
 /// How the terminal was discovered
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -54,6 +85,14 @@ pub enum EaStatus {
     Both,
 }
 
+/// Part a terminal plays in the loaded copier config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalRole {
+    Master,
+    Receiver,
+}
+
 /// Extended terminal information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalInfo {
@@ -88,6 +127,36 @@ pub struct TerminalInfo {
     /// Quick reference for symbol count
     #[serde(default)]
     pub symbol_count: Option<usize>,
+    /// More than one executable maps to this data folder (merged into one entry)
+    #[serde(default)]
+    pub multiple_instances: bool,
+    /// Copier EA version from the handshake file
+    #[serde(default)]
+    pub ea_version: Option<String>,
+    /// Handshake EA is older than the minimum for its type (see
+    /// `min_supported_ea_version`) - the EA should be reinstalled
+    #[serde(default)]
+    pub ea_outdated: bool,
+    /// The app can create files in MQL5/Files. False under install locations
+    /// that need elevation (e.g. Program Files).
+    #[serde(default = "default_files_writable")]
+    pub files_writable: bool,
+    /// Role in the loaded copier config (see `annotate_roles`); None if the
+    /// config doesn't use this terminal or none is loaded
+    #[serde(default)]
+    pub role: Option<TerminalRole>,
+}
+
+fn default_files_writable() -> bool {
+    true
+}
+
+/// Discovered terminals plus warnings about configured terminals that
+/// weren't found
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TerminalDiscovery {
+    pub terminals: Vec<TerminalInfo>,
+    pub warnings: Vec<String>,
 }
 
 /// Config for persisted manual terminals
@@ -126,25 +195,26 @@ fn load_config() -> DiscoveryConfig {
 }
 
 /// Save discovery config
-fn save_config(config: &DiscoveryConfig) -> Result<(), String> {
-    let path = get_config_path().ok_or("Failed to get config path")?;
+fn save_config(config: &DiscoveryConfig) -> Result<(), CopierError> {
+    let path = get_config_path()
+        .ok_or_else(|| CopierError::NotFound("Failed to get config path".to_string()))?;
     
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            .map_err(|e| CopierError::io("Failed to create config directory", e))?;
     }
     
     let content = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        .map_err(|e| CopierError::parse("Failed to serialize config", e))?;
     
     std::fs::write(&path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+        .map_err(|e| CopierError::io("Failed to write config", e))?;
     
     Ok(())
 }
 
 /// Add a manual terminal path and persist it
-pub fn add_manual_terminal(path: &str) -> Result<(), String> {
+pub fn add_manual_terminal(path: &str) -> Result<(), CopierError> {
     let mut config = load_config();
     
     // Check if already exists
@@ -161,40 +231,95 @@ pub fn add_manual_terminal(path: &str) -> Result<(), String> {
 }
 
 /// Remove a manual terminal path
-pub fn remove_manual_terminal(path: &str) -> Result<(), String> {
+pub fn remove_manual_terminal(path: &str) -> Result<(), CopierError> {
     let mut config = load_config();
     config.manual_terminals.retain(|t| t.path != path);
     save_config(&config)
 }
 
+/// Persisted manual terminal paths
+pub fn manual_terminals() -> Vec<ManualTerminal> {
+    load_config().manual_terminals
+}
+
+/// Replace the persisted manual terminal paths (settings import)
+pub fn set_manual_terminals(manual_terminals: Vec<ManualTerminal>) -> Result<(), CopierError> {
+    save_config(&DiscoveryConfig { manual_terminals })
+}
+
 /// Discover all MT5 terminals using cached results (throttled)
 /// Use this for UI to prevent freezing
 pub fn discover_all_terminals() -> Vec<TerminalInfo> {
-    discover_all_terminals_cached(false)
+    discover_all_terminals_cached(DEFAULT_CACHE_MAX_AGE)
 }
 
-/// Discover terminals with optional force refresh
-pub fn discover_all_terminals_cached(force: bool) -> Vec<TerminalInfo> {
-    let mut cache = DISCOVERY_CACHE.lock().unwrap();
-    
-    let should_refresh = force || cache.last_refresh.map_or(true, |last| {
-        last.elapsed() > Duration::from_secs(CACHE_TTL_SECS)
-    });
-    
-    if should_refresh {
-        debug!("Refreshing terminal discovery cache...");
-        cache.terminals = discover_all_terminals_internal();
-        cache.last_refresh = Some(Instant::now());
-    } else {
-        debug!("Using cached terminal discovery results");
-    }
-    
-    cache.terminals.clone()
+/// Discover terminals, re-scanning only if the cache is older than
+/// `max_age` (`Duration::ZERO` forces a scan). UI paths use
+/// `DEFAULT_CACHE_MAX_AGE`, background tasks `BACKGROUND_CACHE_MAX_AGE`.
+pub fn discover_all_terminals_cached(max_age: Duration) -> Vec<TerminalInfo> {
+    DISCOVERY_CACHE.lock().unwrap().get(max_age, discover_all_terminals_internal)
+}
+
+/// Age of the discovery cache without refreshing it (None if never filled)
+pub fn discovery_cache_age() -> Option<Duration> {
+    DISCOVERY_CACHE.lock().unwrap().last_refresh.map(|last| last.elapsed())
 }
 
 /// Force refresh the discovery cache (for manual refresh buttons)
 pub fn refresh_discovery_cache() -> Vec<TerminalInfo> {
-    discover_all_terminals_cached(true)
+    // Re-probe too, in case folder permissions were fixed
+    WRITABLE_FOLDERS.lock().unwrap().clear();
+    discover_all_terminals_cached(Duration::ZERO)
+}
+
+/// Whether a discovered terminal is the one a config entry points at: same
+/// terminal id, or the EA handshake reports the same login
+fn is_config_terminal(terminal: &TerminalInfo, terminal_id: &str, account_number: &str) -> bool {
+    terminal.terminal_id == terminal_id || terminal.login.is_some_and(|login| login.to_string() == account_number)
+}
+
+/// Set `role` on each terminal from `config` (clearing stale roles) and
+/// return a warning for every configured master or receiver terminal that
+/// discovery didn't find, e.g. because it was moved or uninstalled
+pub fn annotate_roles(terminals: &mut [TerminalInfo], config: &CopierConfig) -> Vec<String> {
+    for terminal in terminals.iter_mut() {
+        terminal.role = None;
+    }
+
+    let masters = config
+        .all_masters()
+        .into_iter()
+        .map(|m| (TerminalRole::Master, &m.terminal_id, &m.account_number));
+    let receivers = config
+        .receivers
+        .iter()
+        .map(|r| (TerminalRole::Receiver, &r.terminal_id, &r.account_number));
+
+    let mut warnings = Vec::new();
+    for (role, terminal_id, account_number) in masters.chain(receivers) {
+        let mut found = false;
+        for terminal in terminals
+            .iter_mut()
+            .filter(|t| is_config_terminal(t, terminal_id, account_number))
+        {
+            found = true;
+            // A terminal configured as both keeps the master role
+            terminal.role.get_or_insert(role);
+        }
+        if !found {
+            let label = match role {
+                TerminalRole::Master => "master",
+                TerminalRole::Receiver => "receiver",
+            };
+            let warning = format!(
+                "Configured {} terminal {} (account {}) was not found - it may have been moved or uninstalled",
+                label, terminal_id, account_number
+            );
+            warn!("{}", warning);
+            warnings.push(warning);
+        }
+    }
+    warnings
 }
 
 /// Internal discovery - does the actual work
@@ -250,10 +375,77 @@ fn discover_all_terminals_internal() -> Vec<TerminalInfo> {
         }
     }
 
+    // Secondary dedupe: two installs (or ids) pointing at the same data folder
+    let results = merge_shared_data_folders(results);
+    let logins: Vec<String> = results.iter().filter_map(|t| t.login.map(|l| l.to_string())).collect();
+    crate::redact::register_accounts(&logins);
+
+    for terminal in results.iter().filter(|t| t.ea_outdated) {
+        warn!(
+            "Terminal {} runs outdated copier EA {} - reinstall the EA",
+            terminal.terminal_id,
+            terminal.ea_version.as_deref().unwrap_or("of unknown version"),
+        );
+    }
+
     info!("Total terminals discovered: {}", results.len());
     results
 }
 
+/// Normalize a data folder path for comparison: resolve it on disk when
+/// possible, then ignore case, separator style and trailing separators.
+fn canonical_data_folder(data_folder: &str) -> String {
+    let resolved = std::fs::canonicalize(data_folder)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| data_folder.to_string());
+    let resolved = resolved.strip_prefix(r"\\?\").unwrap_or(&resolved);
+    resolved.replace('/', "\\").trim_end_matches('\\').to_lowercase()
+}
+
+/// Merge terminals whose data folders are the same directory into one
+/// entry flagged `multiple_instances`. Both EAs would otherwise read and
+/// write the same command/queue files.
+fn merge_shared_data_folders(terminals: Vec<TerminalInfo>) -> Vec<TerminalInfo> {
+    let mut merged: Vec<TerminalInfo> = Vec::with_capacity(terminals.len());
+    let mut by_folder: HashMap<String, usize> = HashMap::new();
+
+    for terminal in terminals {
+        let key = canonical_data_folder(&terminal.data_folder);
+        let Some(&idx) = by_folder.get(&key) else {
+            by_folder.insert(key, merged.len());
+            merged.push(terminal);
+            continue;
+        };
+
+        let existing = &mut merged[idx];
+        warn!(
+            "Terminals {} ({:?}) and {} ({:?}) share data folder {} - merging",
+            existing.terminal_id, existing.executable_path,
+            terminal.terminal_id, terminal.executable_path,
+            existing.data_folder
+        );
+        if existing.is_running && terminal.is_running {
+            warn!("Both instances of {} are running - EAs may write the same queue", existing.data_folder);
+        }
+
+        existing.multiple_instances = true;
+        existing.is_running |= terminal.is_running;
+        existing.verified |= terminal.verified;
+        existing.master_installed |= terminal.master_installed;
+        existing.receiver_installed |= terminal.receiver_installed;
+        existing.executable_path = existing.executable_path.take().or(terminal.executable_path);
+        existing.install_label = existing.install_label.take().or(terminal.install_label);
+        existing.broker = existing.broker.take().or(terminal.broker);
+        existing.server = existing.server.take().or(terminal.server);
+        existing.login = existing.login.or(terminal.login);
+        existing.account_name = existing.account_name.take().or(terminal.account_name);
+        existing.last_heartbeat = existing.last_heartbeat.take().or(terminal.last_heartbeat);
+        existing.data_id = existing.data_id.take().or(terminal.data_id);
+    }
+
+    merged
+}
+
 /// Build AppData index: maps exe_path -> (data_folder, data_id)
 fn build_appdata_index() -> Vec<(String, String, String)> {
     let mut index = Vec::new();
@@ -585,7 +777,7 @@ fn terminal_from_install(
     };
     
     // Only get broker/server/login from EA handshake
-    let (broker, server, login, account_name, verified) = read_ea_handshake(&actual_files_path);
+    let EaHandshake { broker, server, login, account_name, verified, ea_version, ea_type } = read_ea_handshake(&actual_files_path);
     
     // Check EA installation
     let experts_path = if mql5_path.exists() {
@@ -633,24 +825,43 @@ fn terminal_from_install(
         data_id: Some(data_id),
         cached_symbols: None,
         symbol_count: None,
+        multiple_instances: false,
+        ea_outdated: is_ea_outdated(verified, ea_type.as_deref(), ea_version.as_deref()),
+        ea_version,
+        files_writable: files_writable(&actual_files_path),
+        role: None,
     })
 }
 
+/// Fields read from the EA handshake file
+#[derive(Debug, Default)]
+struct EaHandshake {
+    broker: Option<String>,
+    server: Option<String>,
+    login: Option<i64>,
+    account_name: Option<String>,
+    /// Handshake file exists and parsed
+    verified: bool,
+    ea_version: Option<String>,
+    /// "master" or "receiver" (None from EAs that predate reporting it)
+    ea_type: Option<String>,
+}
+
 /// Read EA handshake file (only source of broker/server/login)
-fn read_ea_handshake(files_path: &Path) -> (Option<String>, Option<String>, Option<i64>, Option<String>, bool) {
+fn read_ea_handshake(files_path: &Path) -> EaHandshake {
     let info_file = files_path.join("CopierAccountInfo.json");
     if !info_file.exists() {
-        return (None, None, None, None, false);
+        return EaHandshake::default();
     }
 
     let content = match std::fs::read_to_string(&info_file) {
         Ok(c) => c,
-        Err(_) => return (None, None, None, None, false),
+        Err(_) => return EaHandshake::default(),
     };
     
     let json: serde_json::Value = match serde_json::from_str(&content) {
         Ok(j) => j,
-        Err(_) => return (None, None, None, None, false),
+        Err(_) => return EaHandshake::default(),
     };
 
     let broker = json.get("broker").and_then(|v| v.as_str()).map(String::from);
@@ -659,12 +870,105 @@ fn read_ea_handshake(files_path: &Path) -> (Option<String>, Option<String>, Opti
         .and_then(|v| v.as_str())
         .and_then(|s| s.parse().ok());
     
+    let ea_version = json.get("ea_version").and_then(|v| v.as_str()).map(String::from);
+    let ea_type = json.get("ea_type").and_then(|v| v.as_str()).map(String::from);
+
+    if let (Some(b), Some(s)) = (&broker, &server) {
+        learn_server_broker(s, b);
+    }
+    
     let account_name = match (&broker, login) {
         (Some(b), Some(l)) => Some(format!("{} - {}", b, l)),
         _ => None,
     };
 
-    (broker, server, login, account_name, true)
+    EaHandshake {
+        broker,
+        server,
+        login,
+        account_name,
+        verified: true,
+        ea_version,
+        ea_type,
+    }
+}
+
+/// Numeric components of a version string ("2.01" -> [2, 1])
+pub(crate) fn parse_ea_version(version: &str) -> Vec<u32> {
+    version
+        .trim()
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(0))
+        .collect()
+}
+
+/// Oldest supported version of an `ea_type` EA. Handshakes that predate
+/// `ea_type` are held to the receiver minimum, so pre-2.02 receivers are
+/// flagged (and masters that old are asked for a reinstall too).
+pub fn min_supported_ea_version(ea_type: Option<&str>) -> &'static str {
+    match ea_type {
+        Some("master") => MIN_SUPPORTED_MASTER_EA_VERSION,
+        _ => MIN_SUPPORTED_RECEIVER_EA_VERSION,
+    }
+}
+
+/// Whether a verified terminal's EA is too old to copy with. EAs that
+/// predate the `ea_version` handshake field count as outdated.
+pub fn is_ea_outdated(verified: bool, ea_type: Option<&str>, ea_version: Option<&str>) -> bool {
+    if !verified {
+        return false;
+    }
+    match ea_version {
+        Some(version) => parse_ea_version(version) < parse_ea_version(min_supported_ea_version(ea_type)),
+        None => true,
+    }
+}
+
+// ==================== WRITABILITY ====================
+
+lazy_static::lazy_static! {
+    /// Write probe results by MQL5/Files path
+    static ref WRITABLE_FOLDERS: Mutex<HashMap<PathBuf, bool>> = Mutex::new(HashMap::new());
+}
+
+const WRITE_PROBE_FILE: &str = ".copier_write_probe";
+
+/// Create and remove a probe file in `files_path`, or in its nearest
+/// existing ancestor when the folder hasn't been created yet
+fn probe_writable(files_path: &Path) -> bool {
+    let Some(dir) = files_path.ancestors().find(|p| p.exists()) else {
+        return false;
+    };
+    let probe = dir.join(WRITE_PROBE_FILE);
+    match std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(e) => {
+            warn!("Terminal folder {:?} is not writable: {}", dir, e);
+            false
+        }
+    }
+}
+
+/// Whether the app can write into a terminal's MQL5/Files. Each folder is
+/// probed once; a manual discovery refresh probes again.
+pub fn files_writable(files_path: &Path) -> bool {
+    *WRITABLE_FOLDERS
+        .lock()
+        .unwrap()
+        .entry(files_path.to_path_buf())
+        .or_insert_with(|| probe_writable(files_path))
+}
+
+/// Fail early, with advice, instead of a generic IO error halfway through a write
+pub fn ensure_files_writable(files_path: &Path) -> Result<(), CopierError> {
+    if files_writable(files_path) {
+        Ok(())
+    } else {
+        Err(CopierError::not_writable(files_path))
+    }
 }
 
 /// Create TerminalInfo from data folder (for AppData terminals not found via install)
@@ -693,7 +997,7 @@ fn terminal_from_data_folder_enhanced(
         .map(|dir| extract_install_label(dir));
 
     // Only get broker/server/login from EA handshake
-    let (broker, server, login, account_name, verified) = read_ea_handshake(&files_path);
+    let EaHandshake { broker, server, login, account_name, verified, ea_version, ea_type } = read_ea_handshake(&files_path);
 
     // Check EA installation
     let experts_path = mql5_path.join("Experts");
@@ -736,6 +1040,11 @@ fn terminal_from_data_folder_enhanced(
         data_id: Some(terminal_id),
         cached_symbols: None,
         symbol_count: None,
+        multiple_instances: false,
+        ea_outdated: is_ea_outdated(verified, ea_type.as_deref(), ea_version.as_deref()),
+        ea_version,
+        files_writable: files_writable(&files_path),
+        role: None,
     })
 }
 
@@ -804,7 +1113,7 @@ fn terminal_from_executable(exe_path: &str, method: DiscoveryMethod, is_running:
     let install_label = extract_install_label(install_dir);
 
     // Only get broker/server/login from EA handshake
-    let (broker, server, login, account_name, verified) = read_ea_handshake(&files_path);
+    let EaHandshake { broker, server, login, account_name, verified, ea_version, ea_type } = read_ea_handshake(&files_path);
 
     // Check EA installation
     let experts_path = mql5_path.join("Experts");
@@ -847,6 +1156,11 @@ fn terminal_from_executable(exe_path: &str, method: DiscoveryMethod, is_running:
         data_id: Some(terminal_id),
         cached_symbols: None,
         symbol_count: None,
+        multiple_instances: false,
+        ea_outdated: is_ea_outdated(verified, ea_type.as_deref(), ea_version.as_deref()),
+        ea_version,
+        files_writable: files_writable(&files_path),
+        role: None,
     })
 }
 
@@ -979,10 +1293,8 @@ fn read_broker_from_srv(config_path: &Path) -> Option<String> {
                 if let Some(stem) = path.file_stem() {
                     let name = stem.to_string_lossy();
                     // Format: "BrokerName-ServerName.srv"
-                    if let Some(broker_part) = name.split('-').next() {
-                        if !broker_part.is_empty() {
-                            return Some(expand_broker_abbreviation(broker_part));
-                        }
+                    if let Some(broker) = broker_from_server(&name) {
+                        return Some(broker);
                     }
                 }
             }
@@ -1010,41 +1322,42 @@ fn extract_broker_from_folder(path: &Path) -> Option<String> {
         return None;
     }
 
-    Some(expand_broker_abbreviation(&cleaned))
+    Some(normalize_broker_name(&cleaned))
 }
 
-/// Expand common broker abbreviations to full names
-pub fn expand_broker_abbreviation(abbr: &str) -> String {
-    match abbr.to_uppercase().as_str() {
-        "FTMO" | "FTMOGLOBAL" | "FTMO-GLOBAL" => "FTMO".to_string(),
-        "FN" | "FUNDEDNEXT" | "FUNDED-NEXT" => "FundedNext".to_string(),
-        "TFT" | "THEFUNDEDTRADER" | "THE-FUNDED-TRADER" => "The Funded Trader".to_string(),
-        "MFF" | "MYFOREXFUNDS" => "My Forex Funds".to_string(),
-        "E8" | "E8FUNDING" | "E8-FUNDING" => "E8 Funding".to_string(),
-        "5ER" | "5ERS" | "FIVER" | "THE5ERS" => "The5ers".to_string(),
-        "ICM" | "ICMARKETS" | "IC-MARKETS" => "IC Markets".to_string(),
-        "VANTAGEINT" | "VANTAGEINTERNATIONAL" | "VANTAGE" => "Vantage International".to_string(),
-        "PEPPERSTONE" | "PEPPER" | "PEPPERSTONEGROUP" => "Pepperstone".to_string(),
-        "XM" | "XMGROUP" | "XM-GROUP" => "XM Group".to_string(),
-        "OANDA" | "OANDACORPORATION" => "OANDA".to_string(),
-        "FXCM" | "FXCMGROUP" => "FXCM".to_string(),
-        "IG" | "IGGROUP" | "IG-GROUP" => "IG Markets".to_string(),
-        "EXNESS" | "EXNESSGROUP" => "Exness".to_string(),
-        "ADMIRALS" | "ADMIRALMARKETS" | "ADMIRAL" => "Admirals".to_string(),
-        "ROBOFOREX" | "ROBOMARKETS" | "ROBO" => "RoboForex".to_string(),
-        "FBS" | "FBSMARKETS" => "FBS".to_string(),
-        "XTB" | "XTBGROUP" => "XTB".to_string(),
-        "TICKMILL" | "TICKMILLGROUP" => "Tickmill".to_string(),
-        "FXPRO" | "FX-PRO" => "FxPro".to_string(),
-        "AVATRADE" | "AVA-TRADE" => "AvaTrade".to_string(),
-        "ALPARI" | "ALPARIGROUP" => "Alpari".to_string(),
-        "HYCM" | "HY-CM" => "HYCM".to_string(),
-        "AXITRADER" | "AXI" => "Axi".to_string(),
-        "CMC" | "CMCMARKETS" => "CMC Markets".to_string(),
-        "FOREX.COM" | "FOREXCOM" => "Forex.com".to_string(),
-        "THINKORSWIM" | "TOS" => "thinkorswim".to_string(),
-        _ => abbr.to_string(),
-    }
+/// Expand common broker abbreviations to full names (None if not listed)
+pub fn known_broker(abbr: &str) -> Option<&'static str> {
+    let name = match abbr.to_uppercase().as_str() {
+        "FTMO" | "FTMOGLOBAL" | "FTMO-GLOBAL" => "FTMO",
+        "FN" | "FUNDEDNEXT" | "FUNDED-NEXT" => "FundedNext",
+        "TFT" | "THEFUNDEDTRADER" | "THE-FUNDED-TRADER" => "The Funded Trader",
+        "MFF" | "MYFOREXFUNDS" => "My Forex Funds",
+        "E8" | "E8FUNDING" | "E8-FUNDING" => "E8 Funding",
+        "5ER" | "5ERS" | "FIVER" | "THE5ERS" => "The5ers",
+        "ICM" | "ICMARKETS" | "IC-MARKETS" => "IC Markets",
+        "VANTAGEINT" | "VANTAGEINTERNATIONAL" | "VANTAGE" => "Vantage International",
+        "PEPPERSTONE" | "PEPPER" | "PEPPERSTONEGROUP" => "Pepperstone",
+        "XM" | "XMGROUP" | "XM-GROUP" => "XM Group",
+        "OANDA" | "OANDACORPORATION" => "OANDA",
+        "FXCM" | "FXCMGROUP" => "FXCM",
+        "IG" | "IGGROUP" | "IG-GROUP" => "IG Markets",
+        "EXNESS" | "EXNESSGROUP" => "Exness",
+        "ADMIRALS" | "ADMIRALMARKETS" | "ADMIRAL" => "Admirals",
+        "ROBOFOREX" | "ROBOMARKETS" | "ROBO" => "RoboForex",
+        "FBS" | "FBSMARKETS" => "FBS",
+        "XTB" | "XTBGROUP" => "XTB",
+        "TICKMILL" | "TICKMILLGROUP" => "Tickmill",
+        "FXPRO" | "FX-PRO" => "FxPro",
+        "AVATRADE" | "AVA-TRADE" => "AvaTrade",
+        "ALPARI" | "ALPARIGROUP" => "Alpari",
+        "HYCM" | "HY-CM" => "HYCM",
+        "AXITRADER" | "AXI" => "Axi",
+        "CMC" | "CMCMARKETS" => "CMC Markets",
+        "FOREX.COM" | "FOREXCOM" => "Forex.com",
+        "THINKORSWIM" | "TOS" => "thinkorswim",
+        _ => return None,
+    };
+    Some(name)
 }
 
 /// Get executable path from origin.txt
@@ -1078,20 +1391,200 @@ fn get_heartbeat_timestamp(heartbeat_path: &Path) -> Option<String> {
 
 /// Check if terminal is currently running
 pub fn is_terminal_running(terminal_id: &str) -> bool {
-    // Quick check by re-running process discovery
-    let process_terminals = discover_from_processes();
-    process_terminals.iter().any(|t| t.terminal_id == terminal_id)
+    // Uses the throttled discovery cache, which carries per-terminal process status
+    discover_all_terminals()
+        .iter()
+        .any(|t| t.terminal_id == terminal_id && t.is_running)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_large_max_age_keeps_cache_past_default_ttl() {
+        let mut cache = DiscoveryCache {
+            terminals: vec![terminal("T1", "C:\\MT5\\terminal64.exe", "C:\\Data\\T1", true)],
+            last_refresh: Instant::now().checked_sub(DEFAULT_CACHE_MAX_AGE + Duration::from_secs(5)),
+        };
+        assert!(cache.last_refresh.is_some());
+
+        let cached = cache.get(Duration::from_secs(300), || panic!("cache should not be re-scanned"));
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].terminal_id, "T1");
+
+        // The UI default re-scans the same cache
+        let rescanned = cache.get(DEFAULT_CACHE_MAX_AGE, Vec::new);
+        assert!(rescanned.is_empty());
+    }
+
     #[test]
     fn test_broker_expansion() {
-        assert_eq!(expand_broker_abbreviation("FTMO"), "FTMO");
-        assert_eq!(expand_broker_abbreviation("ICM"), "IC Markets");
-        assert_eq!(expand_broker_abbreviation("VantageInt"), "Vantage International");
-        assert_eq!(expand_broker_abbreviation("Unknown"), "Unknown");
+        assert_eq!(normalize_broker_name("FTMO"), "FTMO");
+        assert_eq!(normalize_broker_name("ICM"), "IC Markets");
+        assert_eq!(normalize_broker_name("VantageInt"), "Vantage International");
+        assert_eq!(normalize_broker_name("Unknown"), "Unknown");
+    }
+
+    fn terminal(terminal_id: &str, exe: &str, data_folder: &str, is_running: bool) -> TerminalInfo {
+        TerminalInfo {
+            terminal_id: terminal_id.to_string(),
+            executable_path: Some(exe.to_string()),
+            data_folder: data_folder.to_string(),
+            install_label: None,
+            broker: None,
+            server: None,
+            login: None,
+            account_name: None,
+            platform: "MT5".to_string(),
+            is_running,
+            ea_status: EaStatus::None,
+            last_heartbeat: None,
+            discovery_method: DiscoveryMethod::Registry,
+            has_mql5: true,
+            master_installed: false,
+            receiver_installed: false,
+            verified: false,
+            data_id: None,
+            cached_symbols: None,
+            symbol_count: None,
+            multiple_instances: false,
+            ea_version: None,
+            ea_outdated: false,
+            files_writable: true,
+            role: None,
+        }
+    }
+
+    #[test]
+    fn test_ea_version_below_minimum_is_outdated() {
+        assert!(is_ea_outdated(true, Some("master"), Some("1.00")));
+        assert!(is_ea_outdated(true, Some("receiver"), Some("2.01")));
+        // Handshake from an EA that predates version reporting
+        assert!(is_ea_outdated(true, Some("receiver"), None));
+        // Receivers from before `ea_type` was reported
+        assert!(is_ea_outdated(true, None, Some("2.01")));
+    }
+
+    #[test]
+    fn test_ea_version_at_or_above_minimum_is_ok() {
+        assert!(!is_ea_outdated(true, Some("master"), Some(MIN_SUPPORTED_MASTER_EA_VERSION)));
+        assert!(!is_ea_outdated(true, Some("master"), Some("1.10")));
+        assert!(!is_ea_outdated(true, Some("receiver"), Some(MIN_SUPPORTED_RECEIVER_EA_VERSION)));
+        assert!(!is_ea_outdated(true, Some("receiver"), Some("2.10")));
+        // No handshake yet: nothing to judge
+        assert!(!is_ea_outdated(false, None, None));
+
+        let dir = std::env::temp_dir().join(format!("saturn_handshake_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("CopierAccountInfo.json"),
+            r#"{"account_number": "1001", "broker": "B", "server": "S", "ea_version": "2.02", "ea_type": "receiver"}"#,
+        )
+        .unwrap();
+        let handshake = read_ea_handshake(&dir);
+        assert!(handshake.verified);
+        assert_eq!(handshake.ea_version.as_deref(), Some("2.02"));
+        assert_eq!(handshake.ea_type.as_deref(), Some("receiver"));
+        assert_eq!(handshake.login, Some(1001));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_config_master_terminal_gets_master_role() {
+        use crate::copier::{CopierConfig, MasterConfig};
+
+        let config: CopierConfig = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "config_hash": "",
+            "master": {
+                "account_id": "m",
+                "account_number": "1001",
+                "broker": "B",
+                "terminal_id": "MASTERHASH"
+            },
+            "receivers": []
+        }))
+        .unwrap();
+        let mut terminals = vec![
+            terminal("MASTERHASH", "C:\\MT5\\terminal64.exe", "C:\\Data\\MASTERHASH", true),
+            terminal("OTHER", "C:\\MT5b\\terminal64.exe", "C:\\Data\\OTHER", true),
+        ];
+        terminals[1].role = Some(TerminalRole::Receiver);
+
+        assert!(annotate_roles(&mut terminals, &config).is_empty());
+        assert_eq!(terminals[0].role, Some(TerminalRole::Master));
+        assert_eq!(terminals[1].role, None);
+
+        // A second master whose terminal is gone is reported
+        let config = CopierConfig {
+            masters: vec![MasterConfig {
+                account_id: "m2".to_string(),
+                account_number: "1002".to_string(),
+                broker: "B".to_string(),
+                terminal_id: "GONE".to_string(),
+            }],
+            ..config
+        };
+        let warnings = annotate_roles(&mut terminals, &config);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("GONE"));
+    }
+
+    #[test]
+    fn test_two_exes_sharing_data_folder_are_merged() {
+        let dir = std::env::temp_dir().join(format!("saturn_discovery_test_{}", uuid::Uuid::new_v4()));
+        let data = dir.join("Data");
+        std::fs::create_dir_all(&data).unwrap();
+        // Same folder reached through a different spelling of the path
+        let alias = dir.join(".").join("Data");
+
+        let terminals = vec![
+            terminal("AAA", r"C:\MT5 A\terminal64.exe", &data.to_string_lossy(), false),
+            terminal("BBB", r"C:\MT5 B\terminal64.exe", &format!("{}/", alias.to_string_lossy()), true),
+            terminal("CCC", r"C:\MT5 C\terminal64.exe", &dir.join("Other").to_string_lossy(), false),
+        ];
+
+        let merged = merge_shared_data_folders(terminals);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].terminal_id, "AAA");
+        assert!(merged[0].multiple_instances);
+        assert!(merged[0].is_running);
+        assert!(!merged[1].multiple_instances);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_only_folder_is_not_writable() {
+        let dir = std::env::temp_dir().join(format!("saturn_writable_test_{}", uuid::Uuid::new_v4()));
+        let files = dir.join("MQL5").join("Files");
+        std::fs::create_dir_all(&files).unwrap();
+        assert!(probe_writable(&files));
+        // A folder not created yet is judged by its parent
+        assert!(probe_writable(&files.join("CopierCommands")));
+        assert!(!files.join(WRITE_PROBE_FILE).exists());
+
+        let original = std::fs::metadata(&files).unwrap().permissions();
+        let mut read_only = original.clone();
+        read_only.set_readonly(true);
+        std::fs::set_permissions(&files, read_only).unwrap();
+        // Elevated users (root in CI) bypass the read-only bit
+        if std::fs::write(files.join("elevated"), "").is_err() {
+            assert!(!probe_writable(&files));
+            let error = ensure_files_writable(&files).unwrap_err();
+            assert!(matches!(error, CopierError::PermissionDenied(_)));
+            assert!(error.to_string().contains("not writable"));
+        }
+        std::fs::set_permissions(&files, original).unwrap();
+
+        // A file where the folder should be can't be written into either
+        let blocked = dir.join("blocked");
+        std::fs::write(&blocked, "").unwrap();
+        assert!(!probe_writable(&blocked.join("Files")));
+        assert!(ensure_files_writable(&blocked.join("Files")).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,123 @@
+//! Installed EA verification
+//!
+//! `install_ea` copies a bundled `.mq5` into the terminal's MQL5/Experts
+//! folder. `verify_installed_ea` compares the installed copy with the EA this
+//! build ships so users know to reinstall after an app update, or that someone
+//! edited or replaced the file.
+//!
+//! Content is compared by FNV-1a hash with carriage returns ignored, so a
+//! CRLF conversion doesn't count as an edit. A mismatching file whose
+//! `#property version` is older than the bundled one is `Outdated`; any other
+//! mismatch is `Modified`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::bridge::find_terminal_path;
+use super::discovery::parse_ea_version;
+
+const BUNDLED_MASTER: &[u8] = include_bytes!("../../resources/TradeCopierMaster.mq5");
+const BUNDLED_RECEIVER: &[u8] = include_bytes!("../../resources/TradeCopierReceiver.mq5");
+
+/// How an installed EA compares with the bundled one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EaInstallStatus {
+    UpToDate,
+    /// An older release of the EA - reinstall
+    Outdated,
+    /// Differs from the bundled EA without being an older release
+    Modified,
+    NotInstalled,
+}
+
+/// File name and bundled content for "master" or "receiver"
+fn bundled_ea(ea_type: &str) -> Result<(&'static str, &'static [u8]), String> {
+    match ea_type {
+        "master" => Ok(("TradeCopierMaster.mq5", BUNDLED_MASTER)),
+        "receiver" => Ok(("TradeCopierReceiver.mq5", BUNDLED_RECEIVER)),
+        _ => Err(format!("Invalid EA type: {}", ea_type)),
+    }
+}
+
+/// FNV-1a 64-bit hash of `content`, skipping `\r`
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.iter().filter(|b| **b != b'\r') {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The `#property version` of an EA source
+fn property_version(content: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(content).lines().find_map(|line| {
+        let value = line.trim().strip_prefix("#property")?.trim().strip_prefix("version")?;
+        Some(value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn compare(installed: Option<&[u8]>, bundled: &[u8]) -> EaInstallStatus {
+    let Some(installed) = installed else {
+        return EaInstallStatus::NotInstalled;
+    };
+    if content_hash(installed) == content_hash(bundled) {
+        return EaInstallStatus::UpToDate;
+    }
+    match (property_version(installed), property_version(bundled)) {
+        (Some(old), Some(new)) if parse_ea_version(&old) < parse_ea_version(&new) => EaInstallStatus::Outdated,
+        _ => EaInstallStatus::Modified,
+    }
+}
+
+/// Status of the `ea_type` EA in an MQL5/Experts folder
+pub fn verify_in(experts_path: &Path, ea_type: &str) -> Result<EaInstallStatus, String> {
+    let (file_name, bundled) = bundled_ea(ea_type)?;
+    let installed = fs::read(experts_path.join(file_name)).ok();
+    Ok(compare(installed.as_deref(), bundled))
+}
+
+/// Status of the `ea_type` ("master" or "receiver") EA installed on a terminal
+pub fn verify_installed_ea(terminal_id: &str, ea_type: &str) -> Result<EaInstallStatus, String> {
+    let terminal_path = find_terminal_path(terminal_id)?;
+    verify_in(&terminal_path.join("MQL5").join("Experts"), ea_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_ea_matches_or_mismatches_bundle() {
+        let experts = std::env::temp_dir().join(format!("saturn_ea_verify_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&experts).unwrap();
+        let installed = experts.join("TradeCopierReceiver.mq5");
+
+        assert_eq!(verify_in(&experts, "receiver").unwrap(), EaInstallStatus::NotInstalled);
+
+        // Exact copy, and a copy with CRLF line endings
+        fs::write(&installed, BUNDLED_RECEIVER).unwrap();
+        assert_eq!(verify_in(&experts, "receiver").unwrap(), EaInstallStatus::UpToDate);
+        let crlf = String::from_utf8_lossy(BUNDLED_RECEIVER).replace("\r\n", "\n").replace('\n', "\r\n");
+        fs::write(&installed, crlf).unwrap();
+        assert_eq!(verify_in(&experts, "receiver").unwrap(), EaInstallStatus::UpToDate);
+
+        // Edited by hand
+        let mut edited = BUNDLED_RECEIVER.to_vec();
+        edited.extend_from_slice(b"\n// local tweak\n");
+        fs::write(&installed, &edited).unwrap();
+        assert_eq!(verify_in(&experts, "receiver").unwrap(), EaInstallStatus::Modified);
+
+        // An older release
+        fs::write(&installed, "#property version   \"0.90\"\nvoid OnTick() {}\n").unwrap();
+        assert_eq!(verify_in(&experts, "receiver").unwrap(), EaInstallStatus::Outdated);
+
+        // The master EA isn't installed in this folder
+        assert_eq!(verify_in(&experts, "master").unwrap(), EaInstallStatus::NotInstalled);
+        assert!(verify_in(&experts, "other").is_err());
+
+        let _ = fs::remove_dir_all(&experts);
+    }
+}
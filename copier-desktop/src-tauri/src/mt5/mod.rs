@@ -1,5 +1,7 @@
 pub mod bridge;
+pub mod broker_names;
 pub mod discovery;
+pub mod ea_verify;
 
 #[allow(unused_imports)]
 pub use bridge::*;
@@ -0,0 +1,178 @@
+//! Masking of account numbers and API keys
+//!
+//! Log lines and error strings mention account numbers freely, and a key
+//! can end up in one while debugging (an echoed header, a `{:?}` of the
+//! state). Rather than rely on every call site, the log writers are wrapped
+//! in `RedactingWriter`, which rewrites each formatted line before it
+//! reaches the log file or console: known API keys become `[REDACTED]` and
+//! known account numbers keep only their last 3 characters.
+//!
+//! Secrets are known once registered: keys when they are loaded or set,
+//! account numbers whenever a config is fetched, read from the cache or
+//! installed, and terminal logins as discovery finds them. Text sent to the
+//! UI that may quote them (status errors, alerts, the debug bundle) goes
+//! through `redact` as well.
+
+use parking_lot::RwLock;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::copier::CopierConfig;
+
+/// Text put in place of an API key
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Account numbers shorter than this aren't masked in free text; they'd
+/// match too much unrelated content
+const MIN_MASKED_ACCOUNT_LEN: usize = 4;
+
+/// `account` with all but its last 3 characters masked
+pub fn mask_account(account: &str) -> String {
+    let chars: Vec<char> = account.chars().collect();
+    let keep = chars.len().min(3);
+    let masked = "*".repeat(chars.len().saturating_sub(keep).max(1));
+    format!("{}{}", masked, chars[chars.len() - keep..].iter().collect::<String>())
+}
+
+/// Account numbers of every master and receiver in a config
+pub fn config_accounts(config: &CopierConfig) -> Vec<String> {
+    config
+        .all_masters()
+        .into_iter()
+        .map(|m| m.account_number.clone())
+        .chain(config.receivers.iter().map(|r| r.account_number.clone()))
+        .collect()
+}
+
+/// Replaces API keys and account numbers in free text
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    replacements: Vec<(String, String)>,
+}
+
+impl Redactor {
+    pub fn new<K: AsRef<str>, A: AsRef<str>>(api_keys: &[K], accounts: &[A]) -> Self {
+        let mut redactor = Self::default();
+        redactor.add_api_keys(api_keys);
+        redactor.add_accounts(accounts);
+        redactor
+    }
+
+    pub fn add_api_keys<K: AsRef<str>>(&mut self, api_keys: &[K]) {
+        let keys = api_keys.iter().map(|k| k.as_ref().trim()).filter(|k| !k.is_empty());
+        self.add(keys.map(|key| (key.to_string(), REDACTED.to_string())));
+    }
+
+    pub fn add_accounts<A: AsRef<str>>(&mut self, accounts: &[A]) {
+        let accounts = accounts.iter().map(|a| a.as_ref().trim()).filter(|a| a.len() >= MIN_MASKED_ACCOUNT_LEN);
+        self.add(accounts.map(|account| (account.to_string(), mask_account(account))));
+    }
+
+    fn add(&mut self, replacements: impl Iterator<Item = (String, String)>) {
+        self.replacements.extend(replacements);
+        // Longest first, so a number containing another is replaced whole
+        self.replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        self.replacements.dedup_by(|a, b| a.0 == b.0);
+    }
+
+    pub fn scrub(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (secret, replacement)| text.replace(secret, replacement))
+    }
+}
+
+/// Secrets masked in the logs
+static LOG_REDACTOR: LazyLock<RwLock<Redactor>> = LazyLock::new(|| RwLock::new(Redactor::default()));
+
+/// Never let `api_key` reach the logs
+pub fn register_api_key(api_key: &str) {
+    LOG_REDACTOR.write().add_api_keys(&[api_key]);
+}
+
+/// Mask these account numbers in the logs
+pub fn register_accounts<A: AsRef<str>>(accounts: &[A]) {
+    LOG_REDACTOR.write().add_accounts(accounts);
+}
+
+/// `text` with every registered secret masked
+pub fn redact(text: &str) -> String {
+    LOG_REDACTOR.read().scrub(text)
+}
+
+/// Log writer factory that masks registered secrets in everything written
+/// through it
+pub struct RedactingWriter<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingWriter<M> {
+    type Writer = Redacting<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Redacting(self.0.make_writer())
+    }
+}
+
+/// Writer masking registered secrets. `fmt` layers write each event in a
+/// single call, so a secret is never split across writes.
+pub struct Redacting<W>(W);
+
+impl<W: Write> Write for Redacting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Log sink the test can read back
+    #[derive(Clone, Default)]
+    struct Captured(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_account_numbers_and_keys_are_masked_in_log_lines() {
+        register_accounts(&["90817263"]);
+        register_api_key("sk_live_redact_test_key");
+        let captured = Captured::default();
+        let sink = captured.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(RedactingWriter(move || sink.clone())));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Trade copied to 90817263 with key sk_live_redact_test_key");
+        });
+
+        let line = String::from_utf8(captured.0.lock().clone()).unwrap();
+        assert!(line.contains("Trade copied to *****263 with key [REDACTED]"), "{}", line);
+        assert!(!line.contains("90817263") && !line.contains("sk_live_redact_test_key"));
+    }
+
+    #[test]
+    fn test_mask_account() {
+        assert_eq!(mask_account("51234567"), "*****567");
+        assert_eq!(mask_account("12"), "*12");
+        // Short numbers are left alone in free text
+        assert_eq!(Redactor::new::<&str, _>(&[], &["123"]).scrub("ticket 123"), "ticket 123");
+    }
+}
@@ -8,7 +8,7 @@
 //! router.on("pause_receiver", |payload| Box::pin(async move {
 //!     copier::pause_receiver(payload).await.map_err(|e| e.to_string())
 //! }));
-//! sync::commands::spawn(api_key, install_id, Arc::new(router));
+//! sync::commands::spawn(api_key_source, install_id, Arc::new(router));
 //! ```
 //!
 //! Each handler returns `Result<serde_json::Value, String>`; success populates
@@ -22,6 +22,8 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 
+use super::config::ApiKeySource;
+
 const API_BASE_URL: &str = "https://soosdjmnpcyuqppdjsse.supabase.co/functions/v1";
 const DEFAULT_INTERVAL_SECS: u64 = 2;
 
@@ -70,7 +72,7 @@ impl CommandRouter {
     }
 }
 
-pub fn spawn(api_key: String, install_id: String, router: Arc<CommandRouter>) {
+pub fn spawn(api_key: ApiKeySource, install_id: String, router: Arc<CommandRouter>) {
     spawn_with_interval(
         api_key,
         install_id,
@@ -80,7 +82,7 @@ pub fn spawn(api_key: String, install_id: String, router: Arc<CommandRouter>) {
 }
 
 pub fn spawn_with_interval(
-    api_key: String,
+    api_key: ApiKeySource,
     install_id: String,
     router: Arc<CommandRouter>,
     interval: Duration,
@@ -90,6 +92,9 @@ pub fn spawn_with_interval(
         let mut ticker = tokio::time::interval(interval);
         loop {
             ticker.tick().await;
+            let Some(api_key) = (api_key)() else {
+                continue;
+            };
             match fetch(&client, &api_key, &install_id).await {
                 Ok(commands) => {
                     for cmd in commands {
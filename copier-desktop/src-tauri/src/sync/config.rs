@@ -1,20 +1,156 @@
 #![allow(dead_code)]
+use crate::copier::trade_executor::{calculate_backoff_delay, RetryConfig};
 use crate::copier::CopierConfig;
-use std::path::PathBuf;
+use crate::sync::signing::sign_request;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 const API_BASE_URL: &str = "https://soosdjmnpcyuqppdjsse.supabase.co/functions/v1";
 const CONFIG_FILE_NAME: &str = "saturn_copier_config.json";
 
-/// Fetch configuration from the cloud
+/// Current API key, read by the background sync loops on every tick so a
+/// rotated key takes effect without restarting them
+pub type ApiKeySource = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// Configuration plus where it came from
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: CopierConfig,
+    /// True when the cloud was unreachable and the local cache was used
+    pub from_cache: bool,
+    /// Age of the cache file in seconds (only set when `from_cache`)
+    pub cache_age_secs: Option<u64>,
+}
+
+/// Fetch configuration from the cloud, retrying transient failures
 pub async fn fetch_config(api_key: &str) -> Result<CopierConfig, ConfigError> {
+    let config = fetch_config_from(API_BASE_URL, api_key, &RetryConfig::default()).await?;
+
+    // Cache the config locally
+    if let Err(e) = cache_config(&config) {
+        tracing::warn!("Failed to cache config: {}", e);
+    }
+
+    Ok(config)
+}
+
+/// Fetch configuration from the cloud, falling back to the local cache when
+/// the network is unavailable. Non-transient errors (e.g. a rejected API key)
+/// are returned as-is so a revoked key isn't masked by stale config.
+pub async fn fetch_config_or_cached(api_key: &str) -> Result<LoadedConfig, ConfigError> {
+    let cache_path = get_config_path();
+    fetch_or_cached_from(API_BASE_URL, api_key, cache_path.as_deref(), &RetryConfig::default()).await
+}
+
+async fn fetch_or_cached_from(
+    base_url: &str,
+    api_key: &str,
+    cache_path: Option<&Path>,
+    retry: &RetryConfig,
+) -> Result<LoadedConfig, ConfigError> {
+    match fetch_config_from(base_url, api_key, retry).await {
+        Ok(config) => {
+            if let Some(path) = cache_path {
+                if let Err(e) = write_cached_config(path, &config) {
+                    tracing::warn!("Failed to cache config: {}", e);
+                }
+            }
+            Ok(LoadedConfig {
+                config,
+                from_cache: false,
+                cache_age_secs: None,
+            })
+        }
+        Err(e) if e.is_transient() => {
+            let Some((config, age)) = cache_path.and_then(read_cached_config) else {
+                return Err(e);
+            };
+            tracing::warn!(
+                "Cloud config unavailable ({}), using cached config v{} ({}s old)",
+                e,
+                config.version,
+                age
+            );
+            Ok(LoadedConfig {
+                config,
+                from_cache: true,
+                cache_age_secs: Some(age),
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Check an API key with a lightweight authenticated call before it's saved
+pub async fn validate_api_key(api_key: &str) -> Result<(), ConfigError> {
+    validate_api_key_at(API_BASE_URL, api_key).await
+}
+
+async fn validate_api_key_at(base_url: &str, api_key: &str) -> Result<(), ConfigError> {
+    let install_id = load_or_create_install_id().unwrap_or_else(|_| "unknown".to_string());
+    let url = format!("{}/copier-config", base_url);
+    let client = reqwest::Client::new();
+    let request = client
+        .head(&url)
+        .header("x-api-key", api_key)
+        .header("x-install-id", &install_id);
+    let response = sign_request(request, "HEAD", &url, &[])
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(ConfigError::InvalidApiKey(format!("rejected by server (HTTP {})", status.as_u16())));
+    }
+    if status.is_server_error() {
+        return Err(ConfigError::ServerError(format!("HTTP {}", status)));
+    }
+    Err(ConfigError::ApiError(format!("HTTP {}", status)))
+}
+
+/// Fetch with exponential backoff; only network errors and 5xx responses are retried
+async fn fetch_config_from(
+    base_url: &str,
+    api_key: &str,
+    retry: &RetryConfig,
+) -> Result<CopierConfig, ConfigError> {
+    let mut attempt = 0;
+    loop {
+        match fetch_config_once(base_url, api_key).await {
+            Ok(config) => return Ok(config),
+            Err(e) if e.is_transient() && attempt + 1 < retry.max_attempts => {
+                let delay_ms = calculate_backoff_delay(attempt, retry);
+                tracing::warn!(
+                    "Config fetch attempt {} failed: {}. Retrying in {}ms...",
+                    attempt + 1,
+                    e,
+                    delay_ms
+                );
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn fetch_config_once(base_url: &str, api_key: &str) -> Result<CopierConfig, ConfigError> {
     tracing::info!("Fetching configuration from cloud...");
 
     let install_id = load_or_create_install_id().unwrap_or_else(|_| "unknown".to_string());
+    let url = format!("{}/copier-config", base_url);
     let client = reqwest::Client::new();
-    let response = client
-        .get(format!("{}/copier-config", API_BASE_URL))
+    let request = client
+        .get(&url)
         .header("x-api-key", api_key)
-        .header("x-install-id", &install_id)
+        .header("x-install-id", &install_id);
+    let response = sign_request(request, "GET", &url, &[])
         .send()
         .await
         .map_err(|e| ConfigError::NetworkError(e.to_string()))?;
@@ -22,6 +158,9 @@ pub async fn fetch_config(api_key: &str) -> Result<CopierConfig, ConfigError> {
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
+        if status.is_server_error() {
+            return Err(ConfigError::ServerError(format!("HTTP {}: {}", status, body)));
+        }
         return Err(ConfigError::ApiError(format!(
             "HTTP {}: {}",
             status, body
@@ -32,11 +171,7 @@ pub async fn fetch_config(api_key: &str) -> Result<CopierConfig, ConfigError> {
         .json()
         .await
         .map_err(|e| ConfigError::ParseError(e.to_string()))?;
-
-    // Cache the config locally
-    if let Err(e) = cache_config(&config) {
-        tracing::warn!("Failed to cache config: {}", e);
-    }
+    crate::redact::register_accounts(&crate::redact::config_accounts(&config));
 
     tracing::info!(
         "Configuration loaded: version {}, {} receivers",
@@ -50,24 +185,40 @@ pub async fn fetch_config(api_key: &str) -> Result<CopierConfig, ConfigError> {
 /// Load cached configuration for offline use
 pub fn load_cached_config() -> Option<CopierConfig> {
     let config_path = get_config_path()?;
-    
-    if !config_path.exists() {
-        return None;
-    }
+    read_cached_config(&config_path).map(|(config, _)| config)
+}
 
-    let content = std::fs::read_to_string(&config_path).ok()?;
-    serde_json::from_str(&content).ok()
+/// Read a cached config along with its age in seconds (from file mtime)
+fn read_cached_config(path: &Path) -> Option<(CopierConfig, u64)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let config = serde_json::from_str(&content).ok()?;
+    crate::redact::register_accounts(&crate::redact::config_accounts(&config));
+    let age = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Some((config, age))
 }
 
 /// Cache configuration locally
-fn cache_config(config: &CopierConfig) -> Result<(), ConfigError> {
+pub fn cache_config(config: &CopierConfig) -> Result<(), ConfigError> {
     let config_path = get_config_path()
         .ok_or_else(|| ConfigError::StorageError("Could not determine config path".to_string()))?;
+    write_cached_config(&config_path, config)
+}
+
+fn write_cached_config(path: &Path, config: &CopierConfig) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ConfigError::StorageError(e.to_string()))?;
+    }
 
     let content = serde_json::to_string_pretty(config)
         .map_err(|e| ConfigError::ParseError(e.to_string()))?;
 
-    std::fs::write(&config_path, content)
+    std::fs::write(path, content)
         .map_err(|e| ConfigError::StorageError(e.to_string()))?;
 
     Ok(())
@@ -84,6 +235,7 @@ pub fn save_api_key(api_key: &str) -> Result<(), ConfigError> {
             .map_err(|e| ConfigError::StorageError(e.to_string()))?;
     }
 
+    crate::redact::register_api_key(api_key);
     std::fs::write(&key_path, api_key)
         .map_err(|e| ConfigError::StorageError(e.to_string()))?;
 
@@ -95,26 +247,53 @@ pub fn load_api_key() -> Result<String, ConfigError> {
     let key_path = get_api_key_path()
         .ok_or_else(|| ConfigError::StorageError("Could not determine key path".to_string()))?;
 
-    std::fs::read_to_string(&key_path)
-        .map_err(|e| ConfigError::StorageError(e.to_string()))
-        .map(|s| s.trim().to_string())
+    let api_key = std::fs::read_to_string(&key_path)
+        .map_err(|e| ConfigError::StorageError(e.to_string()))?
+        .trim()
+        .to_string();
+    crate::redact::register_api_key(&api_key);
+    Ok(api_key)
+}
+
+/// Load the per-client request signing secret, stored alongside the API key.
+/// Returns `None` when no secret has been provisioned (signing disabled).
+pub fn load_signing_secret() -> Option<String> {
+    let path = get_config_dir()?.join("signing_secret");
+    let secret = std::fs::read_to_string(path).ok()?.trim().to_string();
+    crate::redact::register_api_key(&secret);
+    (!secret.is_empty()).then_some(secret)
+}
+
+/// Store the request signing secret next to the API key
+pub fn save_signing_secret(secret: &str) -> Result<(), ConfigError> {
+    let dir = get_config_dir()
+        .ok_or_else(|| ConfigError::StorageError("Could not determine config path".to_string()))?;
+    std::fs::create_dir_all(&dir).map_err(|e| ConfigError::StorageError(e.to_string()))?;
+    crate::redact::register_api_key(secret);
+    std::fs::write(dir.join("signing_secret"), secret).map_err(|e| ConfigError::StorageError(e.to_string()))
+}
+
+/// Folder for the API key and cached config: the data folder override if
+/// set, else the platform config folder
+fn get_config_dir() -> Option<PathBuf> {
+    crate::data_dir::data_dir_override().or_else(|| {
+        directories::ProjectDirs::from("com", "saturn", "tradecopier").map(|dirs| dirs.config_dir().to_path_buf())
+    })
 }
 
 fn get_config_path() -> Option<PathBuf> {
-    directories::ProjectDirs::from("com", "saturn", "tradecopier")
-        .map(|dirs| dirs.config_dir().join(CONFIG_FILE_NAME))
+    get_config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
 }
 
 fn get_api_key_path() -> Option<PathBuf> {
-    directories::ProjectDirs::from("com", "saturn", "tradecopier")
-        .map(|dirs| dirs.config_dir().join("api_key"))
+    get_config_dir().map(|dir| dir.join("api_key"))
 }
 
 /// Load (or create + persist) a stable per-install UUID used to key
 /// `agent_state` / `agent_commands` rows in the cloud.
 pub fn load_or_create_install_id() -> Result<String, ConfigError> {
-    let path = directories::ProjectDirs::from("com", "saturn", "tradecopier")
-        .map(|d| d.config_dir().join("install_id"))
+    let path = get_config_dir()
+        .map(|dir| dir.join("install_id"))
         .ok_or_else(|| ConfigError::StorageError("no config dir".into()))?;
     if let Ok(s) = std::fs::read_to_string(&path) {
         let id = s.trim().to_string();
@@ -137,8 +316,157 @@ pub enum ConfigError {
     NetworkError(String),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("Server error: {0}")]
+    ServerError(String),
     #[error("Parse error: {0}")]
     ParseError(String),
     #[error("Storage error: {0}")]
     StorageError(String),
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(String),
+}
+
+impl ConfigError {
+    /// Whether the failure is worth retrying / falling back to cache for
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ConfigError::NetworkError(_) | ConfigError::ServerError(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CONFIG_JSON: &str = r#"{
+        "version": 7,
+        "config_hash": "abc",
+        "master": {"account_id": "m", "account_number": "1001", "broker": "B", "terminal_id": "T1"},
+        "receivers": []
+    }"#;
+
+    fn fast_retry() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 5,
+            exponential_base: 2.0,
+        }
+    }
+
+    /// Minimal HTTP server: answers 503 for the first `failures` requests, then 200 + config
+    fn spawn_flaky_server(failures: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_srv = hits.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+
+                let n = hits_srv.fetch_add(1, Ordering::SeqCst);
+                let response = if n < failures {
+                    "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        CONFIG_JSON.len(),
+                        CONFIG_JSON
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        (url, hits)
+    }
+
+    /// Minimal HTTP server: 204 for requests carrying `valid_key`, 401 otherwise
+    fn spawn_key_server(valid_key: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let response = if request.contains(&format!("x-api-key: {}\r\n", valid_key)) {
+                    "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n"
+                } else {
+                    "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        url
+    }
+
+    /// URL of a port that nothing is listening on
+    fn unreachable_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        url
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_until_success() {
+        let (url, hits) = spawn_flaky_server(2);
+
+        let config = fetch_config_from(&url, "key", &fast_retry()).await.unwrap();
+        assert_eq!(config.version, 7);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_falls_back_to_cache_when_offline() {
+        let dir = std::env::temp_dir().join(format!("saturn_config_test_{}", uuid::Uuid::new_v4()));
+        let cache_path = dir.join(CONFIG_FILE_NAME);
+        let cached: CopierConfig = serde_json::from_str(CONFIG_JSON).unwrap();
+        write_cached_config(&cache_path, &cached).unwrap();
+
+        let loaded = fetch_or_cached_from(&unreachable_url(), "key", Some(&cache_path), &fast_retry())
+            .await
+            .unwrap();
+        assert!(loaded.from_cache);
+        assert_eq!(loaded.config.version, 7);
+        assert!(loaded.cache_age_secs.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_offline_without_cache_errors() {
+        let missing = std::env::temp_dir().join(format!("saturn_missing_{}.json", uuid::Uuid::new_v4()));
+        let result = fetch_or_cached_from(&unreachable_url(), "key", Some(&missing), &fast_retry()).await;
+        assert!(matches!(result, Err(ConfigError::NetworkError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_accepts_known_key() {
+        let url = spawn_key_server("good-key");
+        validate_api_key_at(&url, "good-key").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_rejects_unknown_key() {
+        let url = spawn_key_server("good-key");
+        let result = validate_api_key_at(&url, "typo-key").await;
+        assert!(matches!(result, Err(ConfigError::InvalidApiKey(_))));
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_offline_is_network_error() {
+        let result = validate_api_key_at(&unreachable_url(), "good-key").await;
+        assert!(matches!(result, Err(ConfigError::NetworkError(_))));
+    }
 }
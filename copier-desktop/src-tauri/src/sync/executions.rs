@@ -1,29 +1,96 @@
-use crate::copier::Execution;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::{Notify, Semaphore};
+
+use crate::copier::{CopierState, Execution};
+use crate::sync::signing::sign_request;
 
 const API_BASE_URL: &str = "https://soosdjmnpcyuqppdjsse.supabase.co/functions/v1";
 /// Max executions flushed per `process_queue` invocation (avoid hammering after long offline)
 const MAX_PER_FLUSH: usize = 200;
+/// Executions sent per upload request
+pub const UPLOAD_BATCH_SIZE: usize = 50;
+/// Upload requests in flight at once, across all callers
+pub const MAX_CONCURRENT_UPLOADS: usize = 2;
+/// Normal wait between queue flushes
+pub const UPLOAD_INTERVAL: Duration = Duration::from_secs(30);
+/// After an execution is queued, wait this long for more before flushing,
+/// so a burst goes out as a few batches instead of a request each
+pub const UPLOAD_COALESCE_WINDOW: Duration = Duration::from_secs(2);
+/// Per-request timeout
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest wait between flushes while uploads keep failing
+const MAX_UPLOAD_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Uploaded ids older than this are dropped from the ledger
+const LEDGER_RETENTION_DAYS: i64 = 7;
+const LEDGER_FILE_NAME: &str = "uploaded_executions.json";
+
+/// Wakes the upload task early when a new execution is queued
+static UPLOAD_REQUESTED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// The HTTP client (one connection pool) shared by every upload, and the
+/// cap on uploads in flight
+struct Uploader {
+    client: reqwest::Client,
+    permits: Semaphore,
+}
+
+#[cfg(test)]
+static CLIENTS_BUILT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
-/// Upload execution records to the cloud
-pub async fn upload_executions(
+impl Uploader {
+    fn new(max_concurrent: usize) -> Self {
+        #[cfg(test)]
+        CLIENTS_BUILT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let client = reqwest::Client::builder()
+            .timeout(UPLOAD_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self {
+            client,
+            permits: Semaphore::new(max_concurrent),
+        }
+    }
+}
+
+static UPLOADER: LazyLock<Uploader> = LazyLock::new(|| Uploader::new(MAX_CONCURRENT_UPLOADS));
+
+/// Upload execution records to the cloud. Waits for a slot if
+/// `MAX_CONCURRENT_UPLOADS` uploads are already in flight.
+async fn upload_executions_to(
+    base_url: &str,
     executions: &[Execution],
     api_key: &str,
 ) -> Result<(), ExecutionSyncError> {
     if executions.is_empty() {
         return Ok(());
     }
+    let _permit = UPLOADER
+        .permits
+        .acquire()
+        .await
+        .map_err(|e| ExecutionSyncError::NetworkError(e.to_string()))?;
 
     tracing::info!("Uploading {} executions to cloud...", executions.len());
 
     let install_id = crate::sync::config::load_or_create_install_id()
         .unwrap_or_else(|_| "unknown".to_string());
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/copier-executions", API_BASE_URL))
+    // Serialize up front so the signature covers the exact bytes sent
+    let body = serde_json::to_vec(executions)
+        .map_err(|e| ExecutionSyncError::SerializationError(e.to_string()))?;
+    let url = format!("{}/copier-executions", base_url);
+    let request = UPLOADER
+        .client
+        .post(&url)
         .header("x-api-key", api_key)
         .header("x-install-id", &install_id)
-        .header("Content-Type", "application/json")
-        .json(executions)
+        .header("Content-Type", "application/json");
+    let response = sign_request(request, "POST", &url, &body)
+        .body(body)
         .send()
         .await
         .map_err(|e| ExecutionSyncError::NetworkError(e.to_string()))?;
@@ -45,9 +112,14 @@ pub async fn upload_executions(
 pub fn queue_for_upload(execution: &Execution) -> Result<(), ExecutionSyncError> {
     let queue_path = get_queue_path()
         .ok_or_else(|| ExecutionSyncError::StorageError("Could not determine queue path".to_string()))?;
+    queue_in(&queue_path, execution)?;
+    request_upload();
+    Ok(())
+}
 
+fn queue_in(queue_path: &Path, execution: &Execution) -> Result<(), ExecutionSyncError> {
     // Create queue directory if needed
-    std::fs::create_dir_all(&queue_path)
+    std::fs::create_dir_all(queue_path)
         .map_err(|e| ExecutionSyncError::StorageError(e.to_string()))?;
 
     // Write execution to queue file
@@ -63,16 +135,75 @@ pub fn queue_for_upload(execution: &Execution) -> Result<(), ExecutionSyncError>
     Ok(())
 }
 
+/// Ids of executions the server has confirmed, with when they were confirmed.
+///
+/// Guards against re-uploading a queued file whose batch went through but
+/// which was never deleted (crash between upload and delete). Past the
+/// ledger, the server dedupes on (receiver account, idempotency key).
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct UploadLedger {
+    uploaded: HashMap<String, String>,
+}
+
+impl UploadLedger {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), ExecutionSyncError> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| ExecutionSyncError::SerializationError(e.to_string()))?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, content)
+            .map_err(|e| ExecutionSyncError::StorageError(e.to_string()))?;
+        std::fs::rename(&tmp_path, path).map_err(|e| ExecutionSyncError::StorageError(e.to_string()))
+    }
+
+    fn contains(&self, id: &str) -> bool {
+        self.uploaded.contains_key(id)
+    }
+
+    fn record<'a>(&mut self, ids: impl IntoIterator<Item = &'a str>) {
+        let now = chrono::Utc::now().to_rfc3339();
+        for id in ids {
+            self.uploaded.insert(id.to_string(), now.clone());
+        }
+    }
+
+    /// Drop ids confirmed more than `LEDGER_RETENTION_DAYS` ago
+    fn prune(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let cutoff = now - chrono::Duration::days(LEDGER_RETENTION_DAYS);
+        self.uploaded.retain(|_, confirmed_at| {
+            chrono::DateTime::parse_from_rfc3339(confirmed_at)
+                .map(|t| t.with_timezone(&chrono::Utc) > cutoff)
+                .unwrap_or(false)
+        });
+    }
+}
+
 /// Process queued executions and upload them
 pub async fn process_queue(api_key: &str) -> Result<usize, ExecutionSyncError> {
     let queue_path = get_queue_path()
         .ok_or_else(|| ExecutionSyncError::StorageError("Could not determine queue path".to_string()))?;
+    let ledger_path = get_ledger_path()
+        .ok_or_else(|| ExecutionSyncError::StorageError("Could not determine ledger path".to_string()))?;
+    process_queue_from(API_BASE_URL, &queue_path, &ledger_path, api_key).await
+}
 
+async fn process_queue_from(
+    base_url: &str,
+    queue_path: &Path,
+    ledger_path: &Path,
+    api_key: &str,
+) -> Result<usize, ExecutionSyncError> {
     if !queue_path.exists() {
         return Ok(0);
     }
 
-    let entries: Vec<_> = std::fs::read_dir(&queue_path)
+    let entries: Vec<_> = std::fs::read_dir(queue_path)
         .map_err(|e| ExecutionSyncError::StorageError(e.to_string()))?
         .flatten()
         .filter(|e| {
@@ -90,48 +221,142 @@ pub async fn process_queue(api_key: &str) -> Result<usize, ExecutionSyncError> {
     // Cap per-run flush size
     let entries: Vec<_> = entries.into_iter().take(MAX_PER_FLUSH).collect();
 
-    let mut executions = Vec::new();
-    let mut files_to_delete = Vec::new();
+    let mut ledger = UploadLedger::load(ledger_path);
+    ledger.prune(chrono::Utc::now());
+
+    let mut pending: Vec<(Execution, PathBuf)> = Vec::new();
 
     for entry in &entries {
         let path = entry.path();
         if let Ok(content) = std::fs::read_to_string(&path) {
             if let Ok(execution) = serde_json::from_str::<Execution>(&content) {
-                executions.push(execution);
-                files_to_delete.push(path);
+                if ledger.contains(&execution.id) {
+                    // Already confirmed by the server, only the delete was lost
+                    let _ = std::fs::remove_file(&path);
+                } else {
+                    pending.push((execution, path));
+                }
             }
         }
     }
 
-    if executions.is_empty() {
-        return Ok(0);
+    // Batches go out concurrently, up to `MAX_CONCURRENT_UPLOADS` at a time
+    let batches: Vec<&[(Execution, PathBuf)]> = pending.chunks(UPLOAD_BATCH_SIZE).collect();
+    let mut uploads = tokio::task::JoinSet::new();
+    for (index, chunk) in batches.iter().enumerate() {
+        let executions: Vec<Execution> = chunk.iter().map(|(e, _)| e.clone()).collect();
+        let (base_url, api_key) = (base_url.to_string(), api_key.to_string());
+        uploads.spawn(async move { (index, upload_executions_to(&base_url, &executions, &api_key).await) });
     }
 
-    // Upload in batches of 50
     let mut uploaded = 0;
-    for chunk in executions.chunks(50) {
-        match upload_executions(chunk, api_key).await {
+    let mut first_error = None;
+    while let Some(joined) = uploads.join_next().await {
+        let (index, result) = match joined {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                first_error.get_or_insert(ExecutionSyncError::NetworkError(e.to_string()));
+                continue;
+            }
+        };
+        let chunk = batches[index];
+        match result {
             Ok(_) => {
+                // Record the whole batch before deleting anything
+                ledger.record(chunk.iter().map(|(e, _)| e.id.as_str()));
+                if let Err(e) = ledger.save(ledger_path) {
+                    tracing::warn!("Failed to save upload ledger: {}", e);
+                }
+                for (_, path) in chunk.iter() {
+                    let _ = std::fs::remove_file(path);
+                }
                 uploaded += chunk.len();
             }
             Err(e) => {
                 tracing::error!("Failed to upload execution batch: {}", e);
-                break;
+                first_error.get_or_insert(e);
             }
         }
     }
 
-    // Delete successfully uploaded files
-    for path in files_to_delete.iter().take(uploaded) {
-        let _ = std::fs::remove_file(path);
+    // Nothing went through: report it so the caller backs off
+    match first_error {
+        Some(e) if uploaded == 0 => Err(e),
+        _ => Ok(uploaded),
     }
+}
 
-    Ok(uploaded)
+/// Ask the upload task to flush the queue now instead of at its next tick
+pub fn request_upload() {
+    UPLOAD_REQUESTED.notify_one();
+}
+
+/// Wait before the next flush: the normal interval after a success, doubling
+/// up to `MAX_UPLOAD_BACKOFF` while uploads keep failing
+fn next_upload_delay(current: Duration, succeeded: bool) -> Duration {
+    if succeeded {
+        UPLOAD_INTERVAL
+    } else {
+        (current * 2).min(MAX_UPLOAD_BACKOFF)
+    }
+}
+
+/// Background task flushing the execution queue to the cloud.
+///
+/// Runs every `UPLOAD_INTERVAL`, or `UPLOAD_COALESCE_WINDOW` after an
+/// execution is queued. While offline it backs off and ignores early
+/// wake-ups, so a burst of executions can't make it spin against an
+/// unreachable API.
+pub async fn run_upload_task(state: Arc<Mutex<CopierState>>) {
+    let mut delay = UPLOAD_INTERVAL;
+    loop {
+        if delay > UPLOAD_INTERVAL {
+            tokio::time::sleep(delay).await;
+        } else {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = UPLOAD_REQUESTED.notified() => {
+                    // Let the rest of a burst land in the queue first
+                    tokio::time::sleep(UPLOAD_COALESCE_WINDOW).await;
+                }
+            }
+        }
+
+        let api_key = { state.lock().api_key.clone() };
+        let Some(key) = api_key else {
+            continue;
+        };
+
+        match process_queue(&key).await {
+            Ok(n) => {
+                if n > 0 {
+                    tracing::info!("Uploaded {} queued executions to cloud", n);
+                    state.lock().last_upload_at = Some(chrono::Utc::now().to_rfc3339());
+                }
+                delay = next_upload_delay(delay, true);
+            }
+            Err(e) => {
+                delay = next_upload_delay(delay, false);
+                tracing::warn!("Execution upload failed, retrying in {}s: {}", delay.as_secs(), e);
+            }
+        }
+    }
+}
+
+/// Folder for the upload queue and ledger: the data folder override if set,
+/// else the platform data folder
+fn get_sync_data_dir() -> Option<PathBuf> {
+    crate::data_dir::data_dir_override().or_else(|| {
+        directories::ProjectDirs::from("com", "saturn", "tradecopier").map(|dirs| dirs.data_dir().to_path_buf())
+    })
 }
 
 fn get_queue_path() -> Option<std::path::PathBuf> {
-    directories::ProjectDirs::from("com", "saturn", "tradecopier")
-        .map(|dirs| dirs.data_dir().join("execution_queue"))
+    get_sync_data_dir().map(|dir| dir.join("execution_queue"))
+}
+
+fn get_ledger_path() -> Option<PathBuf> {
+    get_sync_data_dir().map(|dir| dir.join(LEDGER_FILE_NAME))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -145,3 +370,168 @@ pub enum ExecutionSyncError {
     #[error("Storage error: {0}")]
     StorageError(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn execution(id: &str) -> Execution {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "event_type": "entry",
+            "symbol": "EURUSD",
+            "direction": "buy",
+            "master_lots": 0.1,
+            "receiver_lots": 0.1,
+            "master_price": 1.1,
+            "executed_price": 1.1001,
+            "slippage_pips": 1.0,
+            "status": "success",
+            "error_message": null,
+            "receiver_account": "2001"
+        }))
+        .unwrap()
+    }
+
+    /// Minimal HTTP server answering 200 to every request, counting hits
+    fn spawn_counting_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_srv = hits.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 8192];
+                let _ = stream.read(&mut buf);
+                hits_srv.fetch_add(1, Ordering::SeqCst);
+                let body = r#"{"inserted":1,"skipped":0}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        (url, hits)
+    }
+
+    /// HTTP server handling each request on its own thread after `delay`,
+    /// tracking the most requests it saw in flight at once
+    fn spawn_slow_server(delay: Duration) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let peak_srv = peak.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let (in_flight, peak) = (in_flight.clone(), peak_srv.clone());
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 8192];
+                    let _ = stream.read(&mut buf);
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(delay);
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                    );
+                });
+            }
+        });
+
+        (url, peak)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_uploads_share_client_and_respect_cap() {
+        let (url, peak) = spawn_slow_server(Duration::from_millis(100));
+
+        let mut uploads = tokio::task::JoinSet::new();
+        for i in 0..6 {
+            let url = url.clone();
+            let batch = [execution(&format!("burst-{}", i))];
+            uploads.spawn(async move { upload_executions_to(&url, &batch, "key").await });
+        }
+        while let Some(result) = uploads.join_next().await {
+            result.unwrap().unwrap();
+        }
+
+        assert_eq!(CLIENTS_BUILT.load(Ordering::SeqCst), 1);
+        let peak = peak.load(Ordering::SeqCst);
+        assert!((1..=MAX_CONCURRENT_UPLOADS).contains(&peak), "peak {} in flight", peak);
+    }
+
+    #[test]
+    fn test_recorded_execution_lands_in_queue_dir() {
+        let dir = std::env::temp_dir().join(format!("saturn_exec_queue_test_{}", uuid::Uuid::new_v4()));
+
+        queue_in(&dir, &execution("exec-1")).unwrap();
+
+        let queued: Execution =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("exec-1.json")).unwrap()).unwrap();
+        assert_eq!(queued.id, "exec-1");
+        assert_eq!(queued.receiver_account, "2001");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_uploaded_execution_is_not_uploaded_again() {
+        let dir = std::env::temp_dir().join(format!("saturn_exec_ledger_test_{}", uuid::Uuid::new_v4()));
+        let queue_path = dir.join("execution_queue");
+        let ledger_path = dir.join(LEDGER_FILE_NAME);
+        let (url, hits) = spawn_counting_server();
+
+        queue_in(&queue_path, &execution("exec-1")).unwrap();
+        let uploaded = process_queue_from(&url, &queue_path, &ledger_path, "key").await.unwrap();
+        assert_eq!(uploaded, 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert!(!queue_path.join("exec-1.json").exists());
+
+        // Simulate a crash between upload and delete: the file comes back
+        queue_in(&queue_path, &execution("exec-1")).unwrap();
+        let uploaded = process_queue_from(&url, &queue_path, &ledger_path, "key").await.unwrap();
+        assert_eq!(uploaded, 0);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert!(!queue_path.join("exec-1.json").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ledger_prunes_old_ids() {
+        let now = chrono::Utc::now();
+        let mut ledger = UploadLedger::default();
+        ledger.record(["fresh"]);
+        ledger
+            .uploaded
+            .insert("old".to_string(), (now - chrono::Duration::days(8)).to_rfc3339());
+
+        ledger.prune(now);
+        assert!(ledger.contains("fresh"));
+        assert!(!ledger.contains("old"));
+    }
+
+    #[test]
+    fn test_upload_backoff_doubles_and_resets() {
+        let mut delay = UPLOAD_INTERVAL;
+        delay = next_upload_delay(delay, false);
+        assert_eq!(delay, Duration::from_secs(60));
+        for _ in 0..10 {
+            delay = next_upload_delay(delay, false);
+        }
+        assert_eq!(delay, MAX_UPLOAD_BACKOFF);
+        assert_eq!(next_upload_delay(delay, true), UPLOAD_INTERVAL);
+    }
+}
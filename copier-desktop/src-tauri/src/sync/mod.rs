@@ -1,6 +1,7 @@
 pub mod commands;
 pub mod config;
 pub mod executions;
+pub mod signing;
 pub mod state;
 
 #[allow(unused_imports)]
@@ -8,6 +8,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+
+use super::config::ApiKeySource;
 use std::time::Duration;
 
 const API_BASE_URL: &str = "https://soosdjmnpcyuqppdjsse.supabase.co/functions/v1";
@@ -54,11 +56,11 @@ pub type Snapshotter = Arc<dyn Fn() -> AgentSnapshot + Send + Sync>;
 
 /// Spawn the background heartbeat task. Returns immediately; the task lives
 /// for the lifetime of the process.
-pub fn spawn(api_key: String, snapshotter: Snapshotter) {
+pub fn spawn(api_key: ApiKeySource, snapshotter: Snapshotter) {
     spawn_with_interval(api_key, snapshotter, Duration::from_secs(DEFAULT_INTERVAL_SECS));
 }
 
-pub fn spawn_with_interval(api_key: String, snapshotter: Snapshotter, interval: Duration) {
+pub fn spawn_with_interval(api_key: ApiKeySource, snapshotter: Snapshotter, interval: Duration) {
     tokio::spawn(async move {
         let client = reqwest::Client::new();
         let mut ticker = tokio::time::interval(interval);
@@ -66,11 +68,14 @@ pub fn spawn_with_interval(api_key: String, snapshotter: Snapshotter, interval:
         ticker.tick().await;
         loop {
             ticker.tick().await;
+            let Some(key) = (api_key)() else {
+                continue;
+            };
             let snapshot = (snapshotter)();
             if snapshot.install_id.is_empty() {
                 continue;
             }
-            if let Err(e) = push(&client, &api_key, &snapshot).await {
+            if let Err(e) = push(&client, &key, &snapshot).await {
                 tracing::warn!("agent-state push failed: {}", e);
             }
         }
@@ -13,6 +13,8 @@ import {
   Layers,
   XCircle,
   Download,
+  FileText,
+  FolderOpen,
 } from "lucide-react";
 import { DiagnosticsInfo } from "../types";
 
@@ -34,6 +36,8 @@ export default function Diagnostics() {
   const [error, setError] = useState<string | null>(null);
   const [autoRefresh, setAutoRefresh] = useState(true);
   const [exporting, setExporting] = useState(false);
+  const [logLines, setLogLines] = useState<string[] | null>(null);
+  const [logLevel, setLogLevel] = useState("");
 
   const fetchDiagnostics = async () => {
     try {
@@ -70,6 +74,30 @@ export default function Diagnostics() {
     }
   };
 
+  const handleShowLog = async () => {
+    try {
+      setLogLines(await invoke<string[]>("tail_log", { lines: 200 }));
+    } catch (err) {
+      alert(`Failed to read log: ${err}`);
+    }
+  };
+
+  const handleLogLevelChange = async (level: string) => {
+    try {
+      await invoke("set_log_level", { level });
+      setLogLevel(level);
+    } catch (err) {
+      alert(`Failed to change log level: ${err}`);
+    }
+  };
+
+  const handleOpenLogDir = async () => {
+    try {
+      await invoke("open_log_dir");
+    } catch (err) {
+      alert(`Failed to open log folder: ${err}`);
+    }
+  };
 
   useEffect(() => {
     fetchDiagnostics();
@@ -147,6 +175,13 @@ export default function Diagnostics() {
             <Download className={`w-4 h-4 ${exporting ? "animate-pulse" : ""}`} />
             Export Bundle
           </button>
+          <button
+            onClick={handleOpenLogDir}
+            className="flex items-center gap-2 px-3 py-2 text-sm border border-border rounded-lg hover:bg-accent"
+          >
+            <FolderOpen className="w-4 h-4" />
+            Open Logs
+          </button>
           <label className="flex items-center gap-2 text-sm text-muted-foreground cursor-pointer">
             <input
               type="checkbox"
@@ -362,6 +397,35 @@ export default function Diagnostics() {
             </div>
           </div>
 
+          {/* Recent Log */}
+          <div className="bg-card border border-border rounded-lg overflow-hidden">
+            <div className="px-4 py-3 border-b border-border bg-muted/50 flex items-center gap-2">
+              <FileText className="w-4 h-4 text-primary" />
+              <h3 className="text-sm font-semibold">Recent Log</h3>
+              <select
+                value={logLevel}
+                onChange={(e) => handleLogLevelChange(e.target.value)}
+                className="ml-auto text-xs px-2 py-1 border border-border rounded bg-background"
+              >
+                <option value="" disabled>Log level</option>
+                {["trace", "debug", "info", "warn", "error"].map((level) => (
+                  <option key={level} value={level}>{level}</option>
+                ))}
+              </select>
+              <button
+                onClick={handleShowLog}
+                className="text-xs px-2 py-1 border border-border rounded hover:bg-accent"
+              >
+                {logLines ? "Reload" : "Show last 200 lines"}
+              </button>
+            </div>
+            {logLines && (
+              <pre className="p-3 max-h-80 overflow-auto text-xs font-mono whitespace-pre-wrap break-all">
+                {logLines.length === 0 ? "Nothing logged today yet" : logLines.join("\n")}
+              </pre>
+            )}
+          </div>
+
           {/* System Info */}
           <div className="bg-muted/50 rounded-lg p-4">
             <div className="flex items-center gap-2 text-muted-foreground mb-3">
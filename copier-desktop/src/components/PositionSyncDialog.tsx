@@ -1,6 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
-import { PositionDiscrepancy, PositionSyncStatus } from "../types";
+import { PositionDiscrepancy, PositionSyncStatus, errorMessage } from "../types";
 
 interface PositionSyncDialogProps {
   masterTerminalId: string;
@@ -38,7 +38,7 @@ export default function PositionSyncDialog({
       setSyncStatus(status);
     } catch (err) {
       console.error("Failed to load sync status:", err);
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -79,7 +79,7 @@ export default function PositionSyncDialog({
       await loadSyncStatus();
     } catch (err) {
       console.error("Failed to sync position:", err);
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setSyncing((prev) => ({ ...prev, [key]: false }));
     }
@@ -1,6 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
-import { Mt5Terminal, TerminalInfo } from "../types";
+import { Mt5Terminal, TerminalDiscovery, TerminalInfo } from "../types";
 
 interface TerminalManagerProps {
   onTerminalSelect?: (terminalId: string) => void;
@@ -9,6 +9,7 @@ interface TerminalManagerProps {
 export default function TerminalManager({ onTerminalSelect }: TerminalManagerProps) {
   const [terminals, setTerminals] = useState<Mt5Terminal[]>([]);
   const [discoveryInfo, setDiscoveryInfo] = useState<TerminalInfo[]>([]);
+  const [warnings, setWarnings] = useState<string[]>([]);
   const [loading, setLoading] = useState(true);
   const [installing, setInstalling] = useState<string | null>(null);
   const [error, setError] = useState<string | null>(null);
@@ -19,8 +20,10 @@ export default function TerminalManager({ onTerminalSelect }: TerminalManagerPro
     setError(null);
     try {
       // Use enhanced discovery for better install_label support
-      const result = await invoke<TerminalInfo[]>("discover_terminals");
+      const discovery = await invoke<TerminalDiscovery>("discover_terminals");
+      const result = discovery.terminals;
       setDiscoveryInfo(result);
+      setWarnings(discovery.warnings);
       
       // Convert to Mt5Terminal format for compatibility
       const converted: Mt5Terminal[] = result.map(t => ({
@@ -47,6 +50,7 @@ export default function TerminalManager({ onTerminalSelect }: TerminalManagerPro
       setTerminals(converted);
     } catch (err) {
       // Fallback to old method
+      setWarnings([]);
       try {
         const result = await invoke<Mt5Terminal[]>("find_terminals");
         setTerminals(result);
@@ -108,6 +112,14 @@ export default function TerminalManager({ onTerminalSelect }: TerminalManagerPro
         </div>
       )}
 
+      {warnings.length > 0 && (
+        <div className="p-3 bg-yellow-500/10 border border-yellow-500/30 rounded text-sm text-yellow-600 space-y-1">
+          {warnings.map((warning) => (
+            <p key={warning}>{warning}</p>
+          ))}
+        </div>
+      )}
+
       {success && (
         <div className="p-3 bg-green-500/10 border border-green-500/30 rounded text-sm text-green-600">
           {success}
@@ -157,6 +169,14 @@ export default function TerminalManager({ onTerminalSelect }: TerminalManagerPro
                       {!isVerified && (
                         <span className="ml-2 text-amber-500">(attach EA to verify)</span>
                       )}
+                      {info?.multiple_instances && (
+                        <span className="ml-2 text-amber-500">(multiple installs share this data folder)</span>
+                      )}
+                      {info?.ea_outdated && (
+                        <span className="ml-2 text-red-500">
+                          (EA {info.ea_version ?? "version unknown"} is outdated - reinstall to keep copying)
+                        </span>
+                      )}
                     </p>
                   </div>
                   <div className="flex gap-1">
@@ -1,12 +1,14 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
 import {
+  CopierConfigFile,
   Mt5Terminal,
   RiskConfig,
   SafetyConfig,
   SymbolMapping,
   DEFAULT_RISK_CONFIG,
   DEFAULT_SAFETY_CONFIG,
+  errorMessage,
 } from "../../types";
 
 interface ConfirmationStepProps {
@@ -33,47 +35,74 @@ export default function ConfirmationStep({
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [saved, setSaved] = useState(false);
+  const [preview, setPreview] = useState<CopierConfigFile | null>(null);
 
-  const handleSaveAndComplete = async () => {
+  const buildConfigArgs = (master: Mt5Terminal) => {
+    // Build symbol mappings object
+    const symbolMappingsObj: Record<string, string> = {};
+    symbolMappings.forEach((m) => {
+      if (m.enabled) {
+        symbolMappingsObj[m.master_symbol] = m.receiver_symbol;
+      }
+    });
+
+    // Build receiver configs
+    const receivers = receiverTerminals.map((terminal) => ({
+      terminal_id: terminal.terminal_id,
+      account_number: terminal.account_info?.account_number || "",
+      broker: terminal.broker || terminal.account_info?.broker || "",
+      risk: riskConfig,
+      safety: safetyConfig,
+      symbol_mappings: symbolMappingsObj,
+    }));
+
+    return {
+      masterTerminalId: master.terminal_id,
+      masterAccountNumber: master.account_info?.account_number || "",
+      masterBroker: master.broker || master.account_info?.broker || "",
+      receivers,
+    };
+  };
+
+  const validate = (): Mt5Terminal | null => {
     if (!masterTerminal) {
       setError("No master terminal selected");
-      return;
+      return null;
     }
 
     if (receiverTerminals.length === 0) {
       setError("No receiver terminals selected");
-      return;
+      return null;
     }
 
+    return masterTerminal;
+  };
+
+  const handlePreview = async () => {
+    const master = validate();
+    if (!master) return;
+
+    setError(null);
+    try {
+      setPreview(await invoke<CopierConfigFile>("preview_config", buildConfigArgs(master)));
+    } catch (err) {
+      console.error("Failed to preview config:", err);
+      setError(errorMessage(err));
+    }
+  };
+
+  const handleSaveAndComplete = async () => {
+    const master = validate();
+    if (!master) return;
+
     setSaving(true);
     setError(null);
 
     try {
-      // Build symbol mappings object
-      const symbolMappingsObj: Record<string, string> = {};
-      symbolMappings.forEach((m) => {
-        if (m.enabled) {
-          symbolMappingsObj[m.master_symbol] = m.receiver_symbol;
-        }
-      });
-
-      // Build receiver configs
-      const receivers = receiverTerminals.map((terminal) => ({
-        terminal_id: terminal.terminal_id,
-        account_number: terminal.account_info?.account_number || "",
-        broker: terminal.broker || terminal.account_info?.broker || "",
-        risk: riskConfig,
-        safety: safetyConfig,
-        symbol_mappings: symbolMappingsObj,
-      }));
-
-      // Save config to all receiver terminals
-      const configHash = await invoke<string>("save_copier_config", {
-        masterTerminalId: masterTerminal.terminal_id,
-        masterAccountNumber: masterTerminal.account_info?.account_number || "",
-        masterBroker: masterTerminal.broker || masterTerminal.account_info?.broker || "",
-        receivers,
-      });
+      // Save config to all receiver terminals - exactly what was previewed, if anything
+      const configHash = preview
+        ? await invoke<string>("save_previewed_config", { config: preview })
+        : await invoke<string>("save_copier_config", buildConfigArgs(master));
 
       console.log("Config saved with hash:", configHash);
       setSaved(true);
@@ -84,7 +113,7 @@ export default function ConfirmationStep({
       }, 1500);
     } catch (err) {
       console.error("Failed to save config:", err);
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -183,6 +212,30 @@ export default function ConfirmationStep({
         </div>
       </div>
 
+      {/* Config Preview */}
+      <div className="p-4 bg-muted/50 rounded-lg space-y-2">
+        <div className="flex items-center justify-between">
+          <p className="text-sm font-medium">Generated Config</p>
+          <button
+            onClick={preview ? () => setPreview(null) : handlePreview}
+            disabled={saving || saved}
+            className="text-xs text-primary hover:underline disabled:opacity-50"
+          >
+            {preview ? "Hide" : "Preview JSON"}
+          </button>
+        </div>
+        {preview && (
+          <>
+            <p className="text-xs text-muted-foreground">
+              Hash <span className="font-mono">{preview.config_hash}</span> — this exact config will be saved.
+            </p>
+            <pre className="max-h-64 overflow-auto p-2 bg-background border border-border rounded text-xs font-mono">
+              {JSON.stringify(preview, null, 2)}
+            </pre>
+          </>
+        )}
+      </div>
+
       {/* Next Steps */}
       <div className="p-4 bg-muted/50 rounded-lg">
         <p className="text-sm font-medium mb-2">Next steps:</p>
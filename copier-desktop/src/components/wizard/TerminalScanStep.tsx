@@ -2,7 +2,7 @@ import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
 import { open } from "@tauri-apps/api/dialog";
 import { FolderOpen, RefreshCw, Monitor, CheckCircle2, AlertCircle, Cpu } from "lucide-react";
-import { Mt5Terminal, TerminalInfo } from "../../types";
+import { Mt5Terminal, TerminalDiscovery, TerminalInfo } from "../../types";
 
 interface TerminalScanStepProps {
   terminals: Mt5Terminal[];
@@ -19,14 +19,17 @@ export default function TerminalScanStep({
   const [error, setError] = useState<string | null>(null);
   const [addingManual, setAddingManual] = useState(false);
   const [discoveryInfo, setDiscoveryInfo] = useState<TerminalInfo[]>([]);
+  const [warnings, setWarnings] = useState<string[]>([]);
 
   const scanTerminals = async () => {
     setLoading(true);
     setError(null);
     try {
       // Use enhanced discovery
-      const result = await invoke<TerminalInfo[]>("discover_terminals");
+      const discovery = await invoke<TerminalDiscovery>("discover_terminals");
+      const result = discovery.terminals;
       setDiscoveryInfo(result);
+      setWarnings(discovery.warnings);
       
       // Convert to Mt5Terminal format for compatibility
       const converted: Mt5Terminal[] = result.map(t => ({
@@ -53,6 +56,7 @@ export default function TerminalScanStep({
       onTerminalsFound(converted);
     } catch (err) {
       // Fallback to old method
+      setWarnings([]);
       try {
         const result = await invoke<Mt5Terminal[]>("find_terminals");
         onTerminalsFound(result);
@@ -147,6 +151,17 @@ export default function TerminalScanStep({
         </div>
       )}
 
+      {warnings.length > 0 && (
+        <div className="p-3 bg-yellow-500/10 border border-yellow-500/30 rounded-lg text-sm text-yellow-600 space-y-1">
+          {warnings.map((warning) => (
+            <p key={warning} className="flex items-center gap-2">
+              <AlertCircle className="w-4 h-4 flex-shrink-0" />
+              {warning}
+            </p>
+          ))}
+        </div>
+      )}
+
       {loading ? (
         <div className="flex flex-col items-center justify-center py-12">
           <RefreshCw className="w-8 h-8 animate-spin text-primary" />
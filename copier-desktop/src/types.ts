@@ -9,6 +9,33 @@ export interface CopierStatus {
   open_positions: number;
   last_error: string | null;
   config_version: number;
+  /** Seconds since the config last synced from the cloud */
+  config_age_secs?: number | null;
+  last_upload_at?: string | null;
+}
+
+// copier-config.json as generated for the EAs (see preview_config)
+export interface CopierConfigFile {
+  version: number;
+  config_hash: string;
+  created_at: string;
+  master: {
+    account_id: string;
+    account_number: string;
+    broker: string;
+    terminal_id: string;
+  };
+  receivers: Array<{
+    receiver_id: string;
+    account_name: string;
+    account_number: string;
+    broker: string;
+    terminal_id: string;
+    risk: RiskConfig;
+    safety: SafetyConfig;
+    symbol_mappings: Record<string, string>;
+    symbol_overrides: Record<string, unknown> | null;
+  }>;
 }
 
 export interface Execution {
@@ -25,6 +52,232 @@ export interface Execution {
   status: string;
   error_message: string | null;
   receiver_account: string;
+  /** Master position the event belongs to */
+  master_position_id?: number;
+  /** Position opened on the receiver, when the EA reported it */
+  receiver_position_id?: number;
+  intended_lots?: number;
+  /** Lots sent when the broker only partially filled (receiver_lots is the fill) */
+  requested_lots?: number;
+  lot_adjustment?: string;
+  /** Event picked up -> result recorded */
+  latency_ms?: number;
+  /** Master event timestamp -> event picked up */
+  detection_ms?: number;
+  /** Command written -> EA result read back */
+  ea_roundtrip_ms?: number;
+}
+
+export interface LatencyStats {
+  count: number;
+  p50_ms: number | null;
+  p95_ms: number | null;
+  max_ms: number | null;
+}
+
+export interface LatencySummary {
+  date: string;
+  total: LatencyStats;
+  detection: LatencyStats;
+  ea_roundtrip: LatencyStats;
+}
+
+// How the file watcher detects new event files in the queue folder
+export type WatchMode = 'notify' | 'poll' | 'both';
+
+export interface WatchSettings {
+  mode: WatchMode;
+  poll_interval_ms: number;
+  read_attempts: number;
+  read_retry_ms: number;
+}
+
+// Config age after which the stale config warning fires
+export interface StaleConfigSettings {
+  stale_after_secs: number;
+}
+
+// Result of explain_copy_decision: each check an entry goes through
+export type CheckOutcome = 'pass' | 'warn' | 'fail' | 'unknown';
+
+export interface DecisionCheck {
+  check: string;
+  outcome: CheckOutcome;
+  detail: string;
+}
+
+export interface CopyDecision {
+  receiver_id: string;
+  master_symbol: string;
+  receiver_symbol: string;
+  would_copy: boolean;
+  checks: DecisionCheck[];
+}
+
+// Result of preview_lot_sizing: risk mode -> clamped lots for the sample trade
+export type LotSizingPreview = Record<string, number>;
+
+// Result of suggest_risk_config (setup wizard)
+export interface RiskSuggestion {
+  risk: RiskConfig;
+  rationale: string;
+}
+
+// Trade held for manual approval (get_pending_approvals)
+export interface PendingApproval {
+  id: string;
+  receiver_id: string;
+  receiver_account: string;
+  event: {
+    event_type: string;
+    ticket: number;
+    symbol: string;
+    direction: string;
+    lots: number;
+    price: number;
+    sl?: number | null;
+    tp?: number | null;
+    timestamp: string;
+  };
+  created_at: string;
+  expires_at: string;
+}
+
+export interface WeeklyTime {
+  weekday: 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+  hour: number;
+  minute: number;
+}
+
+// Recurring weekly closure (UTC); may wrap around the week
+export interface ClosedWindow {
+  from: WeeklyTime;
+  to: WeeklyTime;
+}
+
+export interface MarketCalendar {
+  enabled: boolean;
+  closed: ClosedWindow[];
+}
+
+// Event journal replay (debug)
+export interface DryRunRoute {
+  receiver_account: string;
+  outcome: 'copy' | 'disabled' | 'aggregate' | 'copy_mode' | 'sampled_out' | 'awaiting_approval' | 'market_closed' | 'stale' | 'blocked' | 'symbol_disabled';
+  lots?: number;
+  reason?: string;
+}
+
+export interface ReplayedEvent {
+  entry: {
+    recorded_at: string;
+    master_account_id: string;
+    event: PendingApproval['event'];
+    routed_to: string[];
+  };
+  routes: DryRunRoute[];
+}
+
+// Contents of a settings bundle (export_settings_bundle / import_settings_bundle)
+export interface BundleSummary {
+  exported_at: string;
+  has_api_key: boolean;
+  has_signing_secret: boolean;
+  manual_terminals: number;
+  daily_reset_hour_utc: number;
+  mapping_profiles: string[];
+  /** Version of the cached copier config in the bundle */
+  cached_config_version: number | null;
+}
+
+// One point of a receiver's equity curve (get_equity_curve)
+export interface EquitySample {
+  timestamp: string;
+  equity: number;
+  balance: number;
+  daily_pnl: number;
+}
+
+// A receiver's safety state (export_safety_state / import_safety_state)
+export interface ReceiverSafetyState {
+  daily_pnl: number;
+  trades_today: number;
+  wins_today: number;
+  losses_today: number;
+  high_water_mark: number;
+  current_equity: number;
+  starting_balance: number;
+  /** YYYY-MM-DD */
+  last_reset_date: string | null;
+  is_safety_paused: boolean;
+  pause_reason: string | null;
+  consecutive_losses: number;
+  profit_target_locked: boolean;
+  /** Equity the profit target counts from after an acknowledged unpause */
+  profit_target_base: number | null;
+  paused_at: string | null;
+  /** Loss/drawdown/equity pause that may auto-resume */
+  auto_resumable: boolean;
+  last_updated: string | null;
+}
+
+// Event files the master EA wrote that couldn't be parsed (get_parse_errors)
+export interface ParseError {
+  timestamp: string;
+  file_name: string;
+  error: string;
+  quarantined_to: string | null;
+}
+
+export interface ParseErrorReport {
+  total: number;
+  /** Newest first */
+  recent: ParseError[];
+}
+
+// Command files a receiver's EA hasn't picked up (get_unconsumed_commands)
+export interface UnconsumedCommands {
+  receiver_id: string;
+  terminal_id: string;
+  count: number;
+  oldest_age_secs: number | null;
+  /** Oldest first */
+  files: string[];
+}
+
+// Contents of a diagnostics archive (collect_diagnostics)
+export interface DiagnosticsManifest {
+  created_at: string;
+  app_version: string;
+  entries: string[];
+}
+
+export type StatsWindow = "today" | "7d" | "all_time";
+
+// Per-receiver fill statistics (get_receiver_stats)
+export interface ReceiverStats {
+  receiver_account: string;
+  window: StatsWindow;
+  executions: number;
+  fills: number;
+  /** 0-1 */
+  fill_rate: number | null;
+  avg_slippage_pips: number | null;
+  worst_slippage_pips: number | null;
+  avg_latency_ms: number | null;
+}
+
+// Circuit breaker on copied entries across all receivers per trading day
+export interface GlobalEntryCap {
+  max_entries_per_day: number | null;
+  trading_day: string | null;
+  entries_today: number;
+}
+
+// Broker -> instrument -> that broker's symbol, used as auto-mapping hints
+export interface MappingHints {
+  version: number;
+  brokers: Record<string, Record<string, string>>;
 }
 
 // Discovery method for terminals
@@ -71,6 +324,25 @@ export interface TerminalInfo {
   // Cached symbol information
   cached_symbols?: string[];
   symbol_count?: number;
+  /** More than one executable maps to this data folder */
+  multiple_instances?: boolean;
+  /** Copier EA version reported in the handshake */
+  ea_version?: string | null;
+  /** Installed EA is below the minimum supported version - reinstall it */
+  ea_outdated?: boolean;
+  /** App can write to MQL5/Files (false = needs admin or a portable data dir) */
+  files_writable?: boolean;
+  /** Role in the loaded copier config, if it uses this terminal */
+  role?: TerminalRole | null;
+}
+
+export type TerminalRole = "master" | "receiver";
+
+// Result of discover_terminals
+export interface TerminalDiscovery {
+  terminals: TerminalInfo[];
+  /** Configured master/receiver terminals that discovery didn't find */
+  warnings: string[];
 }
 
 export interface AccountInfo {
@@ -106,6 +378,7 @@ export interface SymbolCatalog {
   terminal_id: string;
   symbols: SymbolSpec[];
   fetched_at: string;
+  broker_suffix?: string | null;
 }
 
 export type CopierRole = 'master' | 'receiver' | 'independent';
@@ -138,6 +411,45 @@ export interface DiagnosticsInfo {
   recent_errors: ErrorEntry[];
 }
 
+export type AlertSeverity = "info" | "warning" | "critical";
+
+export interface Alert {
+  id: string;
+  severity: AlertSeverity;
+  message: string;
+  timestamp: string;
+  acknowledged: boolean;
+}
+
+export interface HealthSnapshot {
+  timestamp: string;
+  is_running: boolean;
+  queue_pending: number;
+  queue_in_progress: number;
+  completed_today: number;
+  failed_today: number;
+  masters: {
+    account_number: string;
+    terminal_id: string;
+    online: boolean;
+    /** Terminal clock minus local clock, in seconds */
+    estimated_skew_secs: number | null;
+  }[];
+  receivers: {
+    account_number: string;
+    terminal_id: string;
+    is_safety_paused: boolean;
+    pause_reason: string | null;
+    estimated_skew_secs: number | null;
+  }[];
+  discovery_cache_age_secs: number | null;
+  config_from_cache: boolean;
+  config_cache_age_secs: number | null;
+  /** Seconds since the config last synced from the cloud */
+  config_age_secs: number | null;
+  warnings: string[];
+}
+
 export interface TerminalDiagnostic {
   terminal_id: string;
   /** Install label (from registry DisplayName or folder) - shown pre-EA */
@@ -153,6 +465,48 @@ export interface TerminalDiagnostic {
   verified: boolean;
 }
 
+// verify_installed_ea: installed EA vs the one bundled with the app
+export type EaInstallStatus = "up_to_date" | "outdated" | "modified" | "not_installed";
+
+// get_terminal_diagnostics: one terminal's discovery details plus live file checks
+export interface TerminalDiagnostics {
+  terminal_id: string;
+  install_label: string | null;
+  executable_path: string | null;
+  data_folder: string;
+  data_id: string | null;
+  discovery_method: DiscoveryMethod;
+  platform: string;
+  is_running: boolean;
+  multiple_instances: boolean;
+  role: TerminalRole | null;
+  /** MQL5/Files folder, if it could be resolved */
+  files_path: string | null;
+  files_writable: boolean;
+  ea_status: EaStatus;
+  master_installed: boolean;
+  receiver_installed: boolean;
+  broker: string | null;
+  login: number | null;
+  ea_version: string | null;
+  ea_outdated: boolean;
+  /** Installed EAs compared with the ones this build bundles */
+  master_ea: EaInstallStatus;
+  receiver_ea: EaInstallStatus;
+  /** Age of CopierAccountInfo.json */
+  handshake_age_secs: number | null;
+  /** Handshake written within the last 5 minutes */
+  handshake_fresh: boolean;
+  last_heartbeat: string | null;
+  heartbeat_age_secs: number | null;
+  /** Event files waiting in CopierQueue/pending */
+  queue_pending_files: number;
+  /** Command files waiting in CopierCommands */
+  command_files: number;
+  catalog_symbol_count: number | null;
+  catalog_age_secs: number | null;
+}
+
 export interface ErrorEntry {
   timestamp: string;
   message: string;
@@ -188,13 +542,55 @@ export interface SymbolMapping {
   master_symbol: string;
   receiver_symbol: string;
   enabled: boolean;
-  /** How the match was made: exact, normalized, specs, specs_ambiguous, manual */
+  /** How the match was made: exact, normalized, broker_hint, specs, specs_ambiguous, spec_mismatch, conflict, symbol_disabled, manual, saved, stale */
   match_method?: string;
   /** Confidence score 0-100 */
   confidence?: number;
   auto_mapped?: boolean;
 }
 
+// Several master symbols auto-mapped onto one receiver symbol
+export interface MappingConflict {
+  receiver_symbol: string;
+  kept_master_symbol: string;
+  disabled_master_symbols: string[];
+}
+
+export interface AutoMapResult {
+  mappings: SymbolMapping[];
+  conflicts: MappingConflict[];
+}
+
+// Proposed changes to a receiver's mappings after a catalog change (reconcile_symbol_mappings)
+export type MappingChangeKind = "merged" | "stale" | "remapped" | "conflict";
+
+export interface MappingChange {
+  master_symbol: string;
+  kind: MappingChangeKind;
+  before: SymbolMapping;
+  after: SymbolMapping;
+}
+
+export interface MappingReconciliation {
+  receiver_id: string;
+  mappings: SymbolMapping[];
+  changes: MappingChange[];
+  conflicts: MappingConflict[];
+}
+
+// Named set of mappings saved for reuse on the same broker
+export interface MappingProfile {
+  name: string;
+  created_at: string;
+  mappings: SymbolMapping[];
+}
+
+export interface AppliedProfile {
+  mappings: SymbolMapping[];
+  /** Receiver symbols the target terminal doesn't have (dropped) */
+  missing_symbols: string[];
+}
+
 // Per-symbol override
 export interface SymbolOverride {
   symbol: string;
@@ -256,6 +652,7 @@ export interface ReceiverPosition {
   symbol: string;
   direction: string;
   volume: number;
+  magic?: number | null;
 }
 
 export type DiscrepancyType = 
@@ -264,7 +661,9 @@ export type DiscrepancyType =
   | 'VolumeMismatch' 
   | 'DirectionMismatch'
   | 'SLMismatch'
-  | 'TPMismatch';
+  | 'TPMismatch'
+  | 'SLUserModified'
+  | 'TPUserModified';
 
 export interface PositionDiscrepancy {
   discrepancy_type: DiscrepancyType;
@@ -280,6 +679,74 @@ export interface PositionSyncStatus {
   discrepancies: PositionDiscrepancy[];
 }
 
+export interface SyncCommand {
+  command_type: string;
+  position_id: number | null;
+  master_position_id: number | null;
+  symbol: string | null;
+  direction: string | null;
+  volume: number | null;
+  sl: number | null;
+  tp: number | null;
+  order_type?: string;
+  price?: number;
+  timestamp: string;
+}
+
+// One stage of run_copy_selftest
+export interface SelftestStage {
+  name: 'dry_run' | 'write' | 'pickup' | 'response';
+  passed: boolean;
+  /** null if the stage was skipped */
+  elapsed_ms: number | null;
+  detail: string | null;
+}
+
+export interface SelftestResult {
+  receiver_id: string;
+  passed: boolean;
+  stages: SelftestStage[];
+  total_ms: number;
+}
+
+// One command issued by resync_receiver
+export interface ResyncAction {
+  command: SyncCommand;
+  description: string;
+  status: 'sent' | 'blocked' | 'error';
+  error: string | null;
+}
+
+// One receiver setting changed by a config sync
+export interface ConfigFieldChange {
+  receiver_id: string;
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+export interface ConfigMappingChange {
+  receiver_id: string;
+  master_symbol: string;
+  old: ConfigSymbolMapping | null;
+  new: ConfigSymbolMapping | null;
+}
+
+export interface ConfigSymbolMapping {
+  master_symbol: string;
+  receiver_symbol: string;
+  is_enabled: boolean;
+}
+
+// What sync_config changed; receivers are identified by account_id
+export interface ConfigDiff {
+  added_receivers: string[];
+  removed_receivers: string[];
+  risk_changes: ConfigFieldChange[];
+  symbol_mapping_changes: ConfigMappingChange[];
+  safety_changes: ConfigFieldChange[];
+}
+
 // Heartbeat from master
 export interface MasterHeartbeat {
   timestamp_utc: string;
@@ -335,4 +802,24 @@ export const PROP_FIRM_SAFETY_PRESET: SafetyConfig = {
   use_relative_sl_tp: true, // Recommended for indices
   enable_retry: true,
   max_retry_attempts: 5,
-};
\ No newline at end of file
+};
+// Structured error returned by discovery / symbol catalog / position sync commands
+export type CopierErrorKind =
+  | "NotFound"
+  | "ParseError"
+  | "Io"
+  | "PermissionDenied"
+  | "EaNotAttached";
+
+export interface CopierError {
+  kind: CopierErrorKind;
+  message: string;
+}
+
+export function errorMessage(err: unknown): string {
+  if (typeof err === "string") return err;
+  if (err && typeof err === "object" && "message" in err) {
+    return String((err as CopierError).message);
+  }
+  return String(err);
+}
@@ -93,6 +93,11 @@ serve(async (req) => {
       );
     }
 
+    // HEAD = key validation from the desktop app: no config body needed
+    if (req.method === 'HEAD') {
+      return new Response(null, { status: 204, headers: corsHeaders });
+    }
+
     const userId = account.user_id;
     
     // Determine if this is a receiver requesting config or a general config request
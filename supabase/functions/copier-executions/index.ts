@@ -24,11 +24,26 @@ interface DesktopExecution {
   master_account_number?: string | null;
 }
 
+// Desktop statuses for trades deliberately not copied
+const SKIPPED_STATUSES = new Set([
+  "blocked",
+  "skipped",
+  "stale",
+  "disabled",
+  "sampled_out",
+  "exposure_limit",
+  "rejected_manual",
+  "open_not_completed",
+  "debounced",
+  "copy_mode",
+  "symbol_disabled",
+]);
+
 // Map desktop status -> DB-allowed enum (success | failed | skipped)
 function normalizeStatus(s: string): "success" | "failed" | "skipped" {
   const x = (s || "").toLowerCase();
   if (x === "success") return "success";
-  if (x === "blocked" || x === "skipped") return "skipped";
+  if (SKIPPED_STATUSES.has(x)) return "skipped";
   return "failed";
 }
 